@@ -0,0 +1,124 @@
+/* * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * *\
+Copyright (C) 2023 CJ McAllister
+    This program is free software; you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation; either version 3 of the License, or
+    (at your option) any later version.
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+    You should have received a copy of the GNU General Public License
+    along with this program; if not, write to the Free Software Foundation,
+    Inc., 51 Franklin Street, Fifth Floor, Boston, MA 02110-1301  USA
+
+\* * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * */
+
+// Numerals two LCD rows tall, built from custom glyphs, for values that must be read from a distance
+
+use crate::charset::Glyph;
+
+///////////////////////////////////////////////////////////////////////////////
+//  Named Constants
+///////////////////////////////////////////////////////////////////////////////
+
+pub const LINE_LEN: usize = 16;
+const DIGIT_WIDTH: usize = 3;
+
+const BLANK: u8 = b' ';
+const B: u8 = Glyph::Block as u8;
+const U: u8 = Glyph::UpperBar as u8;
+const L: u8 = Glyph::LowerBar as u8;
+const M: u8 = Glyph::BothBars as u8;
+
+// Top and bottom rows of each numeral
+const NUMERALS: [[[u8; DIGIT_WIDTH]; 2]; 10] = [
+    [[B, U, B], [B, L, B]],
+    [[U, B, BLANK], [L, B, L]],
+    [[M, M, B], [B, L, L]],
+    [[M, M, B], [L, L, B]],
+    [[B, L, B], [BLANK, BLANK, B]],
+    [[B, M, M], [L, L, B]],
+    [[B, M, M], [B, L, B]],
+    [[U, U, B], [BLANK, BLANK, B]],
+    [[B, M, B], [B, L, B]],
+    [[B, M, B], [L, L, B]],
+];
+
+///////////////////////////////////////////////////////////////////////////////
+//  Data Structures
+///////////////////////////////////////////////////////////////////////////////
+
+// Character codes for both rows of the display, padded with spaces
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct BigText {
+    pub rows: [[u8; LINE_LEN]; 2],
+    // Columns taken by the numerals, from the left
+    pub width: usize,
+}
+
+///////////////////////////////////////////////////////////////////////////////
+//  Static Functions
+///////////////////////////////////////////////////////////////////////////////
+
+// Lay out a string of ASCII digits. They're spaced apart when there's room, and anything else or
+// anything that doesn't fit is left out.
+pub fn render(digits: &str) -> BigText {
+    let numerals = digits
+        .bytes()
+        .filter(u8::is_ascii_digit)
+        .map(|d| &NUMERALS[(d - b'0') as usize]);
+    let count = numerals.clone().count();
+    // The last numeral needs no space after it
+    let spacing = if count * (DIGIT_WIDTH + 1) <= LINE_LEN + 1 {
+        DIGIT_WIDTH + 1
+    } else {
+        DIGIT_WIDTH
+    };
+
+    let mut text = BigText {
+        rows: [[BLANK; LINE_LEN]; 2],
+        width: 0,
+    };
+    for (i, numeral) in numerals.enumerate() {
+        let start = i * spacing;
+        if start + DIGIT_WIDTH > LINE_LEN {
+            break;
+        }
+
+        for (row, cells) in text.rows.iter_mut().zip(numeral) {
+            row[start..start + DIGIT_WIDTH].copy_from_slice(cells);
+        }
+        text.width = start + DIGIT_WIDTH;
+    }
+
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn spaces_numerals_when_they_fit() {
+        let text = render("10");
+        assert_eq!(text.width, 7);
+        assert_eq!(&text.rows[0][..7], &[U, B, BLANK, BLANK, B, U, B]);
+        assert_eq!(&text.rows[1][..7], &[L, B, L, BLANK, B, L, B]);
+        assert!(text.rows[0][7..].iter().all(|&c| c == BLANK));
+    }
+
+    #[test]
+    fn packs_five_numerals() {
+        let text = render("25400");
+        assert_eq!(text.width, 15);
+        assert_eq!(&text.rows[0][3..6], &NUMERALS[5][0]);
+    }
+
+    #[test]
+    fn empty_entry_is_blank() {
+        let text = render("");
+        assert_eq!(text.width, 0);
+        assert_eq!(text.rows, [[BLANK; LINE_LEN]; 2]);
+    }
+}
@@ -0,0 +1,240 @@
+/* * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * *\
+Copyright (C) 2023 CJ McAllister
+    This program is free software; you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation; either version 3 of the License, or
+    (at your option) any later version.
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+    You should have received a copy of the GNU General Public License
+    along with this program; if not, write to the Free Software Foundation,
+    Inc., 51 Franklin Street, Fifth Floor, Boston, MA 02110-1301  USA
+
+\* * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * */
+///////////////////////////////////////////////////////////////////////////////
+
+///////////////////////////////////////////////////////////////////////////////
+//  Named Constants
+///////////////////////////////////////////////////////////////////////////////
+
+// Enough for every device on both buses
+pub const MAX_DEVICES: usize = 8;
+
+// Trouble raises a device's score and clean transfers wear it back down, so the health reflects recent
+// transfers rather than everything since power-on. A retry now and then is normal on a long cable; a
+// few transfers lost close together is a connection on its way out.
+const RETRY_SCORE: u8 = 8;
+const FAILURE_SCORE: u8 = 32;
+const DEGRADED_SCORE: u8 = 16;
+const FAILING_SCORE: u8 = 96;
+
+///////////////////////////////////////////////////////////////////////////////
+//  Data Structures
+///////////////////////////////////////////////////////////////////////////////
+
+// How a transfer went, after any retry
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Outcome {
+    Ok,
+    // Not acknowledged, or otherwise refused by the bus
+    Failed,
+    // Stopped for taking too long, as when a device holds the bus
+    TimedOut,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Health {
+    Good,
+    // Needing retries or losing the odd transfer
+    Degraded,
+    // Losing transfers often enough that the device can't be relied on
+    Failing,
+}
+
+// Transfer counts for one device since power-on
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct DeviceHealth {
+    pub addr: u8,
+    pub transfers: u32,
+    pub retries: u32,
+    pub failures: u32,
+    pub timeouts: u32,
+    score: u8,
+}
+
+// Every device that has been talked to, in the order they first were
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct BusHealth {
+    devices: [DeviceHealth; MAX_DEVICES],
+    len: usize,
+}
+
+///////////////////////////////////////////////////////////////////////////////
+//  Object Implementations
+///////////////////////////////////////////////////////////////////////////////
+
+impl Health {
+    // One character for a corner of the status line, blank while all is well
+    pub fn glyph(self) -> &'static str {
+        match self {
+            Self::Good => " ",
+            Self::Degraded => "?",
+            Self::Failing => "!",
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Good => "OK",
+            Self::Degraded => "WEAK",
+            Self::Failing => "FAIL",
+        }
+    }
+}
+
+impl DeviceHealth {
+    pub fn health(&self) -> Health {
+        match self.score {
+            s if s >= FAILING_SCORE => Health::Failing,
+            s if s >= DEGRADED_SCORE => Health::Degraded,
+            _ => Health::Good,
+        }
+    }
+
+    fn record(&mut self, outcome: Outcome, retried: bool) {
+        self.transfers = self.transfers.saturating_add(1);
+        if retried {
+            self.retries = self.retries.saturating_add(1);
+            self.score = self.score.saturating_add(RETRY_SCORE);
+        }
+        match outcome {
+            Outcome::Ok => {
+                if !retried {
+                    self.score = self.score.saturating_sub(1);
+                }
+            }
+            Outcome::Failed => {
+                self.failures = self.failures.saturating_add(1);
+                self.score = self.score.saturating_add(FAILURE_SCORE);
+            }
+            Outcome::TimedOut => {
+                self.timeouts = self.timeouts.saturating_add(1);
+                self.score = self.score.saturating_add(FAILURE_SCORE);
+            }
+        }
+    }
+}
+
+impl BusHealth {
+    pub const fn new() -> Self {
+        Self {
+            devices: [DeviceHealth {
+                addr: 0,
+                transfers: 0,
+                retries: 0,
+                failures: 0,
+                timeouts: 0,
+                score: 0,
+            }; MAX_DEVICES],
+            len: 0,
+        }
+    }
+
+    // Count a transfer to the device at the address, returning its health after it. Devices beyond the
+    // first MAX_DEVICES aren't tracked.
+    pub fn record(&mut self, addr: u8, outcome: Outcome, retried: bool) -> Health {
+        let index = match self.devices().iter().position(|d| d.addr == addr) {
+            Some(index) => index,
+            None if self.len < MAX_DEVICES => {
+                self.devices[self.len] = DeviceHealth {
+                    addr,
+                    ..DeviceHealth::default()
+                };
+                self.len += 1;
+                self.len - 1
+            }
+            None => return Health::Good,
+        };
+
+        let device = &mut self.devices[index];
+        device.record(outcome, retried);
+        device.health()
+    }
+
+    pub fn devices(&self) -> &[DeviceHealth] {
+        &self.devices[..self.len]
+    }
+
+    // The worst of any device's
+    pub fn overall(&self) -> Health {
+        self.devices()
+            .iter()
+            .map(DeviceHealth::health)
+            .max()
+            .unwrap_or(Health::Good)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LCD: u8 = 0x20;
+    const KEYPAD: u8 = 0x21;
+
+    #[test]
+    fn occasional_retries_stay_healthy() {
+        let mut bus = BusHealth::new();
+        for i in 0..1000 {
+            bus.record(LCD, Outcome::Ok, i % 100 == 0);
+        }
+        assert_eq!(bus.overall(), Health::Good);
+
+        let lcd = bus.devices()[0];
+        assert_eq!(lcd.transfers, 1000);
+        assert_eq!(lcd.retries, 10);
+        assert_eq!(lcd.failures, 0);
+    }
+
+    #[test]
+    fn lost_transfers_degrade_then_fail_then_recover() {
+        let mut bus = BusHealth::new();
+        bus.record(LCD, Outcome::Ok, false);
+        assert_eq!(bus.record(KEYPAD, Outcome::Failed, true), Health::Degraded);
+        assert_eq!(
+            bus.record(KEYPAD, Outcome::TimedOut, false),
+            Health::Degraded
+        );
+        assert_eq!(bus.record(KEYPAD, Outcome::Failed, true), Health::Failing);
+        assert_eq!(bus.overall(), Health::Failing);
+
+        let keypad = bus.devices()[1];
+        assert_eq!(keypad.addr, KEYPAD);
+        assert_eq!(
+            (keypad.failures, keypad.timeouts, keypad.retries),
+            (2, 1, 2)
+        );
+
+        // Clean transfers wear it back down, though the counts stay
+        for _ in 0..200 {
+            bus.record(KEYPAD, Outcome::Ok, false);
+        }
+        assert_eq!(bus.overall(), Health::Good);
+        assert_eq!(bus.devices()[1].failures, 2);
+    }
+
+    #[test]
+    fn devices_past_the_table_are_ignored() {
+        let mut bus = BusHealth::new();
+        for addr in 0..MAX_DEVICES as u8 + 2 {
+            bus.record(addr, Outcome::Failed, false);
+        }
+        assert_eq!(bus.devices().len(), MAX_DEVICES);
+        assert_eq!(bus.record(0x7F, Outcome::TimedOut, false), Health::Good);
+    }
+}
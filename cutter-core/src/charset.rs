@@ -0,0 +1,171 @@
+/* * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * *\
+Copyright (C) 2023 CJ McAllister
+    This program is free software; you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation; either version 3 of the License, or
+    (at your option) any later version.
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+    You should have received a copy of the GNU General Public License
+    along with this program; if not, write to the Free Software Foundation,
+    Inc., 51 Franklin Street, Fifth Floor, Boston, MA 02110-1301  USA
+
+\* * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * */
+
+// Character codes for the HD44780's two common character ROMs, with custom (CGRAM) glyphs standing in
+// for characters a ROM lacks
+
+///////////////////////////////////////////////////////////////////////////////
+//  Named Constants
+///////////////////////////////////////////////////////////////////////////////
+
+// Shown for characters neither ROM nor a custom glyph can draw
+const UNKNOWN_CODE: u8 = b'?';
+
+///////////////////////////////////////////////////////////////////////////////
+//  Data Structures
+///////////////////////////////////////////////////////////////////////////////
+
+// Which character ROM the LCD's controller was made with, printed as the HD44780's part suffix
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Rom {
+    // Japanese: ASCII apart from the yen sign and arrows in place of '\', '~' and DEL, katakana above
+    A00 = 0,
+    // European: full ASCII, with accented Latin, Cyrillic and Greek above
+    A02 = 1,
+}
+
+// Glyphs defined in CGRAM, each kept in the slot given by its value. Codes 0-7 display these.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Glyph {
+    Heart = 0,
+    Backslash,
+    Tilde,
+    // Building blocks for big digits
+    Block,
+    UpperBar,
+    LowerBar,
+    BothBars,
+}
+
+///////////////////////////////////////////////////////////////////////////////
+//  Object Implementations
+///////////////////////////////////////////////////////////////////////////////
+
+impl Rom {
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::A00 => "A00",
+            Self::A02 => "A02",
+        }
+    }
+
+    pub fn toggled(self) -> Self {
+        match self {
+            Self::A00 => Self::A02,
+            Self::A02 => Self::A00,
+        }
+    }
+
+    // The character code to write for `c`
+    pub fn encode(self, c: char) -> u8 {
+        match (self, c) {
+            (Self::A00, '\\') => Glyph::Backslash.code(),
+            (Self::A00, '~') => Glyph::Tilde.code(),
+            (_, ' '..='~') => c as u8,
+            (_, '♥') => Glyph::Heart.code(),
+            (Self::A00, '°') => 0xDF,
+            (Self::A02, '°') => 0xB0,
+            (Self::A00, 'µ') => 0xE4,
+            (Self::A02, 'µ') => 0xB5,
+            (Self::A00, '→') => 0x7E,
+            (Self::A00, '←') => 0x7F,
+            _ => UNKNOWN_CODE,
+        }
+    }
+}
+
+impl From<u8> for Rom {
+    fn from(value: u8) -> Self {
+        match value {
+            1 => Self::A02,
+            _ => Self::A00,
+        }
+    }
+}
+
+impl Glyph {
+    pub const ALL: [Self; 7] = [
+        Self::Heart,
+        Self::Backslash,
+        Self::Tilde,
+        Self::Block,
+        Self::UpperBar,
+        Self::LowerBar,
+        Self::BothBars,
+    ];
+
+    pub fn code(self) -> u8 {
+        self as u8
+    }
+
+    // 5x8 dot pattern, top row first, rightmost dot in bit 0
+    pub fn pattern(self) -> [u8; 8] {
+        match self {
+            Self::Heart => [
+                0b00000, 0b01010, 0b11111, 0b11111, 0b01110, 0b00100, 0b00000, 0b00000,
+            ],
+            Self::Backslash => [
+                0b00000, 0b10000, 0b01000, 0b00100, 0b00010, 0b00001, 0b00000, 0b00000,
+            ],
+            Self::Tilde => [
+                0b00000, 0b00000, 0b01000, 0b10101, 0b00010, 0b00000, 0b00000, 0b00000,
+            ],
+            Self::Block => [0b11111; 8],
+            Self::UpperBar => [
+                0b11111, 0b11111, 0b11111, 0b00000, 0b00000, 0b00000, 0b00000, 0b00000,
+            ],
+            Self::LowerBar => [
+                0b00000, 0b00000, 0b00000, 0b00000, 0b00000, 0b11111, 0b11111, 0b11111,
+            ],
+            Self::BothBars => [
+                0b11111, 0b11111, 0b11111, 0b00000, 0b00000, 0b11111, 0b11111, 0b11111,
+            ],
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ascii_passes_through() {
+        for rom in [Rom::A00, Rom::A02] {
+            assert_eq!(rom.encode('A'), b'A');
+            assert_eq!(rom.encode('#'), b'#');
+        }
+    }
+
+    #[test]
+    fn rom_differences_are_covered() {
+        assert_eq!(Rom::A00.encode('\\'), Glyph::Backslash.code());
+        assert_eq!(Rom::A02.encode('\\'), b'\\');
+        assert_eq!(Rom::A00.encode('°'), 0xDF);
+        assert_eq!(Rom::A02.encode('°'), 0xB0);
+        assert_eq!(Rom::A02.encode('→'), UNKNOWN_CODE);
+    }
+
+    #[test]
+    fn custom_glyphs_fill_gaps() {
+        assert_eq!(Rom::A02.encode('♥'), Glyph::Heart.code());
+        for (slot, glyph) in Glyph::ALL.iter().enumerate() {
+            assert_eq!(glyph.code() as usize, slot);
+            assert!(glyph.pattern().iter().all(|row| row >> 5 == 0));
+        }
+    }
+}
@@ -0,0 +1,305 @@
+/* * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * *\
+Copyright (C) 2023 CJ McAllister
+    This program is free software; you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation; either version 3 of the License, or
+    (at your option) any later version.
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+    You should have received a copy of the GNU General Public License
+    along with this program; if not, write to the Free Software Foundation,
+    Inc., 51 Franklin Street, Fifth Floor, Boston, MA 02110-1301  USA
+
+\* * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * */
+
+///////////////////////////////////////////////////////////////////////////////
+//  Named Constants
+///////////////////////////////////////////////////////////////////////////////
+
+// Longest command or payload line accepted
+pub const MAX_LINE_LEN: usize = 160;
+
+///////////////////////////////////////////////////////////////////////////////
+//  Data Structures
+///////////////////////////////////////////////////////////////////////////////
+
+// Assembles received bytes into lines, dropping any that don't fit or aren't text
+pub struct LineReader {
+    bytes: [u8; MAX_LINE_LEN],
+    len: usize,
+    overflowed: bool,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum LineError {
+    TooLong,
+    NotText,
+}
+
+// Commands accepted on the serial console, one per line
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Command<'a> {
+    // Load `length,count,label` lines into the job queue, up to a line reading `end`
+    Import,
+    // Print the saved settings as checksummed hex
+    SettingsExport,
+    // Replace the saved settings with checksummed hex from an export
+    SettingsImport(&'a str),
+    // Print everything known about the machine, for remote debugging
+    Dump,
+    // Print the event journal kept in flash, for piecing together what happened before a complaint
+    Journal,
+    // Print the finished jobs kept in flash, with the machine's lifetime totals, as CSV
+    History,
+    // Start cutting the queue, as '#' does on the queue screen
+    Run,
+    // Print the key presses recorded since power-on, one `at_ms,key` line each
+    RecordExport,
+    // Load `at_ms,key` lines, up to a line reading `end`, to be replayed from the next power-on
+    RecordImport,
+    // Print every tunable parameter's value
+    TuneList,
+    // Print one tunable parameter's value
+    TuneGet(&'a str),
+    // Change a tunable parameter until restart
+    TuneSet(&'a str, u32),
+    // Keep the tuned values in settings, so they survive a restart
+    TuneSave,
+    // Print the cut stroke of an operator profile, numbered from 1 as on the LCD
+    StrokeGet(usize),
+    // Change and save the cut stroke of an operator profile
+    StrokeSet(usize, &'a str),
+    // Print every item of the settings menu, for machines without a keypad to use it
+    ConfigList,
+    // Print one item of the settings menu
+    ConfigGet(&'a str),
+    // Change and save an item of the settings menu
+    ConfigSet(&'a str, &'a str),
+    // Print every configurable input's assignment, numbered from 1
+    InputList,
+    // Change and save a configurable input's assignment
+    InputSet(usize, &'a str),
+    // Save the running settings as they are, e.g. to accept the defaults after they were found corrupt
+    SettingsSave,
+}
+
+///////////////////////////////////////////////////////////////////////////////
+//  Object Implementations
+///////////////////////////////////////////////////////////////////////////////
+
+impl LineReader {
+    pub fn new() -> Self {
+        Self {
+            bytes: [0; MAX_LINE_LEN],
+            len: 0,
+            overflowed: false,
+        }
+    }
+
+    // Add a received byte, returning the line once its terminator arrives.
+    // Lines may end in "\n" or "\r\n", and come back trimmed of surrounding whitespace.
+    pub fn push(&mut self, byte: u8) -> Option<Result<&str, LineError>> {
+        if byte != b'\n' {
+            if self.len == MAX_LINE_LEN {
+                self.overflowed = true;
+            } else {
+                self.bytes[self.len] = byte;
+                self.len += 1;
+            }
+            return None;
+        }
+
+        let len = self.len;
+        let overflowed = self.overflowed;
+        self.len = 0;
+        self.overflowed = false;
+
+        if overflowed {
+            return Some(Err(LineError::TooLong));
+        }
+        Some(
+            core::str::from_utf8(&self.bytes[..len])
+                .map(str::trim)
+                .map_err(|_| LineError::NotText),
+        )
+    }
+}
+
+impl Default for LineReader {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl LineError {
+    pub fn message(&self) -> &'static str {
+        match self {
+            Self::TooLong => "line too long",
+            Self::NotText => "line is not text",
+        }
+    }
+}
+
+impl<'a> Command<'a> {
+    // Command words are case-insensitive, so spreadsheet macros and terminals alike can send them
+    pub fn parse(line: &'a str) -> Option<Self> {
+        let mut words = line.split_ascii_whitespace();
+        let (first, second, third) = (words.next(), words.next(), words.next());
+        if words.next().is_some() {
+            return None;
+        }
+
+        let is =
+            |word: Option<&str>, expected| word.is_some_and(|w| w.eq_ignore_ascii_case(expected));
+        if is(first, "import") && second.is_none() {
+            Some(Self::Import)
+        } else if is(first, "dump") && second.is_none() {
+            Some(Self::Dump)
+        } else if is(first, "journal") && second.is_none() {
+            Some(Self::Journal)
+        } else if is(first, "history") && second.is_none() {
+            Some(Self::History)
+        } else if is(first, "run") && second.is_none() {
+            Some(Self::Run)
+        } else if is(first, "record") && is(second, "export") && third.is_none() {
+            Some(Self::RecordExport)
+        } else if is(first, "record") && is(second, "import") && third.is_none() {
+            Some(Self::RecordImport)
+        } else if is(first, "tune") && second.is_none() {
+            Some(Self::TuneList)
+        } else if is(first, "tune") && is(second, "save") && third.is_none() {
+            Some(Self::TuneSave)
+        } else if is(first, "tune") && third.is_none() {
+            second.map(Self::TuneGet)
+        } else if is(first, "tune") {
+            let value = third?.parse().ok()?;
+            second.map(|name| Self::TuneSet(name, value))
+        } else if is(first, "stroke") && third.is_none() {
+            Some(Self::StrokeGet(second?.parse().ok()?))
+        } else if is(first, "stroke") {
+            let profile = second?.parse().ok()?;
+            third.map(|stroke| Self::StrokeSet(profile, stroke))
+        } else if is(first, "config") && second.is_none() {
+            Some(Self::ConfigList)
+        } else if is(first, "config") && third.is_none() {
+            second.map(Self::ConfigGet)
+        } else if is(first, "config") {
+            Some(Self::ConfigSet(second?, third?))
+        } else if is(first, "input") && second.is_none() {
+            Some(Self::InputList)
+        } else if is(first, "input") {
+            let number = second?.parse().ok()?;
+            third.map(|spec| Self::InputSet(number, spec))
+        } else if is(first, "settings") && is(second, "save") && third.is_none() {
+            Some(Self::SettingsSave)
+        } else if is(first, "settings") && is(second, "export") && third.is_none() {
+            Some(Self::SettingsExport)
+        } else if is(first, "settings") && is(second, "import") {
+            third.map(Self::SettingsImport)
+        } else {
+            None
+        }
+    }
+}
+
+// Whether a line ends an import payload
+pub fn is_end_of_payload(line: &str) -> bool {
+    line.eq_ignore_ascii_case("end")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn feed(reader: &mut LineReader, bytes: &[u8]) -> Vec<Result<String, LineError>> {
+        bytes
+            .iter()
+            .filter_map(|&b| reader.push(b).map(|line| line.map(String::from)))
+            .collect()
+    }
+
+    #[test]
+    fn splits_lines() {
+        let mut reader = LineReader::new();
+        assert_eq!(
+            feed(&mut reader, b"import\r\n 12,3,A \npartial"),
+            [Ok("import".to_string()), Ok("12,3,A".to_string())]
+        );
+        assert_eq!(feed(&mut reader, b"\n"), [Ok("partial".to_string())]);
+    }
+
+    #[test]
+    fn rejects_bad_lines_and_recovers() {
+        let mut reader = LineReader::new();
+        let long = [b'x'; MAX_LINE_LEN + 1];
+        assert_eq!(feed(&mut reader, &long), []);
+        assert_eq!(feed(&mut reader, b"\n"), [Err(LineError::TooLong)]);
+        assert_eq!(feed(&mut reader, b"\xff\n"), [Err(LineError::NotText)]);
+        assert_eq!(feed(&mut reader, b"end\n"), [Ok("end".to_string())]);
+    }
+
+    #[test]
+    fn parses_commands() {
+        assert_eq!(Command::parse("IMPORT"), Some(Command::Import));
+        assert_eq!(Command::parse("import now"), None);
+        assert_eq!(Command::parse("dump"), Some(Command::Dump));
+        assert_eq!(Command::parse("Journal"), Some(Command::Journal));
+        assert_eq!(Command::parse("journal clear"), None);
+        assert_eq!(Command::parse("HISTORY"), Some(Command::History));
+        assert_eq!(Command::parse("history csv"), None);
+        assert_eq!(Command::parse("Run"), Some(Command::Run));
+        assert_eq!(
+            Command::parse("settings export"),
+            Some(Command::SettingsExport)
+        );
+        assert_eq!(
+            Command::parse("Settings Import 00FF"),
+            Some(Command::SettingsImport("00FF"))
+        );
+        assert_eq!(Command::parse("settings import"), None);
+        assert_eq!(Command::parse("Record Export"), Some(Command::RecordExport));
+        assert_eq!(Command::parse("record import"), Some(Command::RecordImport));
+        assert_eq!(Command::parse("record"), None);
+        assert_eq!(Command::parse("tune"), Some(Command::TuneList));
+        assert_eq!(Command::parse("tune SAVE"), Some(Command::TuneSave));
+        assert_eq!(
+            Command::parse("tune cut_dwell_ms"),
+            Some(Command::TuneGet("cut_dwell_ms"))
+        );
+        assert_eq!(
+            Command::parse("tune cut_dwell_ms 1200"),
+            Some(Command::TuneSet("cut_dwell_ms", 1200))
+        );
+        assert_eq!(Command::parse("tune cut_dwell_ms fast"), None);
+        assert_eq!(Command::parse("stroke 2"), Some(Command::StrokeGet(2)));
+        assert_eq!(
+            Command::parse("Stroke 2 90:150,120:1300"),
+            Some(Command::StrokeSet(2, "90:150,120:1300"))
+        );
+        assert_eq!(Command::parse("stroke"), None);
+        assert_eq!(Command::parse("stroke two plain"), None);
+        assert_eq!(Command::parse("settings"), None);
+        assert_eq!(Command::parse("config"), Some(Command::ConfigList));
+        assert_eq!(
+            Command::parse("Config mark_every"),
+            Some(Command::ConfigGet("mark_every"))
+        );
+        assert_eq!(
+            Command::parse("config foot_switch single_cut"),
+            Some(Command::ConfigSet("foot_switch", "single_cut"))
+        );
+        assert_eq!(Command::parse("input"), Some(Command::InputList));
+        assert_eq!(
+            Command::parse("input 3 door:P0.02:low"),
+            Some(Command::InputSet(3, "door:P0.02:low"))
+        );
+        assert_eq!(Command::parse("input 3"), None);
+        assert_eq!(Command::parse("input three unused"), None);
+        assert_eq!(Command::parse("Settings Save"), Some(Command::SettingsSave));
+        assert!(is_end_of_payload("End"));
+    }
+}
@@ -0,0 +1,96 @@
+/* * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * *\
+Copyright (C) 2023 CJ McAllister
+    This program is free software; you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation; either version 3 of the License, or
+    (at your option) any later version.
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+    You should have received a copy of the GNU General Public License
+    along with this program; if not, write to the Free Software Foundation,
+    Inc., 51 Franklin Street, Fifth Floor, Boston, MA 02110-1301  USA
+
+\* * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * */
+
+///////////////////////////////////////////////////////////////////////////////
+//  Named Constants
+///////////////////////////////////////////////////////////////////////////////
+
+pub const ERROR_LOG_LEN: usize = 8;
+
+///////////////////////////////////////////////////////////////////////////////
+//  Data Structures
+///////////////////////////////////////////////////////////////////////////////
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct LoggedError {
+    pub uptime_s: u32,
+    pub message: &'static str,
+}
+
+// The most recent errors since boot, oldest dropped first
+#[derive(Debug)]
+pub struct ErrorLog {
+    entries: [LoggedError; ERROR_LOG_LEN],
+    // Errors ever recorded, including those since dropped
+    total: u32,
+}
+
+///////////////////////////////////////////////////////////////////////////////
+//  Object Implementations
+///////////////////////////////////////////////////////////////////////////////
+
+impl ErrorLog {
+    pub const fn new() -> Self {
+        Self {
+            entries: [LoggedError {
+                uptime_s: 0,
+                message: "",
+            }; ERROR_LOG_LEN],
+            total: 0,
+        }
+    }
+
+    pub fn record(&mut self, uptime_s: u32, message: &'static str) {
+        self.entries[self.total as usize % ERROR_LOG_LEN] = LoggedError { uptime_s, message };
+        self.total = self.total.saturating_add(1);
+    }
+
+    pub fn total(&self) -> u32 {
+        self.total
+    }
+
+    // Retained errors, oldest first
+    pub fn iter(&self) -> impl Iterator<Item = &LoggedError> {
+        let retained = (self.total as usize).min(ERROR_LOG_LEN);
+        let oldest = self.total as usize - retained;
+        (oldest..self.total as usize).map(move |i| &self.entries[i % ERROR_LOG_LEN])
+    }
+}
+
+impl Default for ErrorLog {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keeps_most_recent_in_order() {
+        let mut log = ErrorLog::new();
+        assert_eq!(log.iter().count(), 0);
+
+        for uptime_s in 0..ERROR_LOG_LEN as u32 + 3 {
+            log.record(uptime_s, "JAM");
+        }
+
+        let times: Vec<u32> = log.iter().map(|e| e.uptime_s).collect();
+        assert_eq!(times, (3..ERROR_LOG_LEN as u32 + 3).collect::<Vec<_>>());
+        assert_eq!(log.total(), ERROR_LOG_LEN as u32 + 3);
+    }
+}
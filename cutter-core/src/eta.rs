@@ -0,0 +1,268 @@
+/* * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * *\
+Copyright (C) 2023 CJ McAllister
+    This program is free software; you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation; either version 3 of the License, or
+    (at your option) any later version.
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+    You should have received a copy of the GNU General Public License
+    along with this program; if not, write to the Free Software Foundation,
+    Inc., 51 Franklin Street, Fifth Floor, Boston, MA 02110-1301  USA
+
+\* * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * */
+///////////////////////////////////////////////////////////////////////////////
+
+use core::convert::TryInto;
+
+use crate::profiles::NUM_PROFILES;
+
+///////////////////////////////////////////////////////////////////////////////
+//  Named Constants
+///////////////////////////////////////////////////////////////////////////////
+
+// ASCII "CYCL", marks the page as holding learned cycle times (erased flash reads 0xFFFFFFFF)
+const CYCLE_TIMES_MAGIC: u32 = 0x4359_434C;
+
+const HEADER_LEN: usize = 4;
+const HISTORY_LEN: usize = 4;
+pub const SERIALIZED_LEN: usize = HEADER_LEN + NUM_PROFILES * HISTORY_LEN;
+
+// Learned times are kept as how long pieces really take against the plan, in thousandths
+pub const UNITY_PERMILLE: u16 = 1000;
+
+// Jobs the rolling average spans, so a run of unusual jobs fades out again
+const WINDOW_JOBS: u32 = 8;
+
+// A piece taking less than half or more than three times its plan was held up or interrupted, rather
+// than showing how the machine runs, so it's left out
+const MIN_PIECE_PERMILLE: u64 = 500;
+const MAX_PIECE_PERMILLE: u64 = 3000;
+
+// Too few pieces to say anything about the job as a whole
+const MIN_SAMPLE_PIECES: u32 = 3;
+
+///////////////////////////////////////////////////////////////////////////////
+//  Data Structures
+///////////////////////////////////////////////////////////////////////////////
+
+// How long pieces have really taken against the plan, averaged over recent jobs
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct CycleHistory {
+    pub scale_permille: u16,
+    // Jobs averaged so far, up to the window
+    pub jobs: u16,
+}
+
+// Piece times measured over one job
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct CycleSample {
+    planned_ms: u64,
+    actual_ms: u64,
+    pieces: u32,
+}
+
+// Learned cycle times for each operator profile, each of which sets its own feed speed
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct CycleTimes {
+    histories: [CycleHistory; NUM_PROFILES],
+}
+
+///////////////////////////////////////////////////////////////////////////////
+//  Object Implementations
+///////////////////////////////////////////////////////////////////////////////
+
+impl CycleHistory {
+    // Nothing learned yet, so estimates are the plan
+    pub const NONE: Self = Self {
+        scale_permille: UNITY_PERMILLE,
+        jobs: 0,
+    };
+
+    // Fold a finished job into the average. Returns false if it had too few usable pieces to count.
+    pub fn record(&mut self, sample: &CycleSample) -> bool {
+        if sample.pieces < MIN_SAMPLE_PIECES {
+            return false;
+        }
+
+        let job_permille = (sample.actual_ms * UNITY_PERMILLE as u64 / sample.planned_ms)
+            .clamp(MIN_PIECE_PERMILLE, MAX_PIECE_PERMILLE) as u32;
+
+        // A plain average until the window fills, then each job replaces an average one's share
+        let weight = (self.jobs as u32 + 1).min(WINDOW_JOBS);
+        let scale = self.scale_permille as u32;
+        let scale = (scale * (weight - 1) + job_permille + weight / 2) / weight;
+        self.scale_permille = scale as u16;
+        self.jobs = (self.jobs + 1).min(WINDOW_JOBS as u16);
+        true
+    }
+
+    // How long something planned to take the given time is likely to really take
+    pub fn estimate_ms(&self, planned_ms: u32) -> u32 {
+        let estimate = planned_ms as u64 * self.scale_permille as u64 / UNITY_PERMILLE as u64;
+        estimate.min(u32::MAX as u64) as u32
+    }
+}
+
+impl Default for CycleHistory {
+    fn default() -> Self {
+        Self::NONE
+    }
+}
+
+impl CycleSample {
+    // Count a finished piece, unless it took so far from its plan that something held it up
+    pub fn add_piece(&mut self, planned_ms: u32, actual_ms: u32) {
+        let planned_ms = planned_ms as u64;
+        let actual_ms = actual_ms as u64;
+        let permille = actual_ms * UNITY_PERMILLE as u64 / planned_ms.max(1);
+        if planned_ms == 0 || !(MIN_PIECE_PERMILLE..=MAX_PIECE_PERMILLE).contains(&permille) {
+            return;
+        }
+
+        self.planned_ms += planned_ms;
+        self.actual_ms += actual_ms;
+        self.pieces += 1;
+    }
+
+    pub fn pieces(&self) -> u32 {
+        self.pieces
+    }
+}
+
+impl CycleTimes {
+    // Decode learned times, or None if the bytes don't hold any (e.g. erased flash)
+    pub fn from_bytes(bytes: &[u8; SERIALIZED_LEN]) -> Option<Self> {
+        let magic = u32::from_le_bytes(bytes[0..4].try_into().unwrap());
+        if magic != CYCLE_TIMES_MAGIC {
+            return None;
+        }
+
+        let mut times = Self::default();
+        let body = &bytes[HEADER_LEN..];
+        for (history, bytes) in times
+            .histories
+            .iter_mut()
+            .zip(body.chunks_exact(HISTORY_LEN))
+        {
+            let scale_permille = u16::from_le_bytes(bytes[0..2].try_into().unwrap());
+            let jobs = u16::from_le_bytes(bytes[2..4].try_into().unwrap());
+            // Anything outside what record() can produce is corrupt, so start that profile over
+            if (MIN_PIECE_PERMILLE..=MAX_PIECE_PERMILLE).contains(&(scale_permille as u64))
+                && jobs <= WINDOW_JOBS as u16
+            {
+                *history = CycleHistory {
+                    scale_permille,
+                    jobs,
+                };
+            }
+        }
+
+        Some(times)
+    }
+
+    pub fn to_bytes(&self) -> [u8; SERIALIZED_LEN] {
+        let mut bytes = [0; SERIALIZED_LEN];
+        bytes[0..4].copy_from_slice(&CYCLE_TIMES_MAGIC.to_le_bytes());
+
+        let body = &mut bytes[HEADER_LEN..];
+        for (history, bytes) in self
+            .histories
+            .iter()
+            .zip(body.chunks_exact_mut(HISTORY_LEN))
+        {
+            bytes[0..2].copy_from_slice(&history.scale_permille.to_le_bytes());
+            bytes[2..4].copy_from_slice(&history.jobs.to_le_bytes());
+        }
+
+        bytes
+    }
+
+    pub fn get(&self, profile: usize) -> CycleHistory {
+        self.histories[profile]
+    }
+
+    pub fn get_mut(&mut self, profile: usize) -> &mut CycleHistory {
+        &mut self.histories[profile]
+    }
+}
+
+impl Default for CycleTimes {
+    fn default() -> Self {
+        Self {
+            histories: [CycleHistory::NONE; NUM_PROFILES],
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample(planned_ms: u32, actual_ms: u32, pieces: u32) -> CycleSample {
+        let mut sample = CycleSample::default();
+        for _ in 0..pieces {
+            sample.add_piece(planned_ms, actual_ms);
+        }
+        sample
+    }
+
+    #[test]
+    fn estimates_follow_recent_jobs() {
+        let mut history = CycleHistory::NONE;
+        assert_eq!(history.estimate_ms(2000), 2000);
+
+        // The first job is taken as it is
+        assert!(history.record(&sample(1000, 1500, 10)));
+        assert_eq!(history.estimate_ms(2000), 3000);
+
+        // Later ones are averaged in, until the window fills and older jobs fade out
+        assert!(history.record(&sample(1000, 1000, 10)));
+        assert_eq!(history.scale_permille, 1250);
+        for _ in 0..50 {
+            history.record(&sample(1000, 1100, 10));
+        }
+        assert_eq!(history.jobs, WINDOW_JOBS as u16);
+        assert!((1095..=1105).contains(&history.scale_permille));
+    }
+
+    #[test]
+    fn held_up_pieces_and_short_jobs_are_ignored() {
+        let mut job = sample(1000, 1200, 4);
+        // Paused for a minute, and a piece the clock got wrong
+        job.add_piece(1000, 60_000);
+        job.add_piece(1000, 10);
+        job.add_piece(0, 500);
+        assert_eq!(job.pieces(), 4);
+
+        let mut history = CycleHistory::NONE;
+        assert!(history.record(&job));
+        assert_eq!(history.scale_permille, 1200);
+
+        assert!(!history.record(&sample(1000, 2000, 2)));
+        assert_eq!(history.scale_permille, 1200);
+        assert_eq!(history.jobs, 1);
+    }
+
+    #[test]
+    fn codec_round_trips() {
+        let mut times = CycleTimes::default();
+        times.get_mut(2).record(&sample(800, 1000, 5));
+        let decoded = CycleTimes::from_bytes(&times.to_bytes()).unwrap();
+        assert_eq!(decoded, times);
+        assert_eq!(decoded.get(2).scale_permille, 1250);
+        assert_eq!(decoded.get(0), CycleHistory::NONE);
+
+        assert!(CycleTimes::from_bytes(&[0xFF; SERIALIZED_LEN]).is_none());
+
+        // A corrupt entry starts over without taking the others with it
+        let mut bytes = times.to_bytes();
+        bytes[HEADER_LEN + 2 * HISTORY_LEN..HEADER_LEN + 2 * HISTORY_LEN + 2]
+            .copy_from_slice(&9999u16.to_le_bytes());
+        let decoded = CycleTimes::from_bytes(&bytes).unwrap();
+        assert_eq!(decoded.get(2), CycleHistory::NONE);
+    }
+}
@@ -0,0 +1,180 @@
+/* * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * *\
+Copyright (C) 2023 CJ McAllister
+    This program is free software; you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation; either version 3 of the License, or
+    (at your option) any later version.
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+    You should have received a copy of the GNU General Public License
+    along with this program; if not, write to the Free Software Foundation,
+    Inc., 51 Franklin Street, Fifth Floor, Boston, MA 02110-1301  USA
+
+\* * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * */
+
+use crate::inputs::PedalAction;
+
+///////////////////////////////////////////////////////////////////////////////
+//  Named Constants
+///////////////////////////////////////////////////////////////////////////////
+
+pub const MAX_EVENTS: usize = 8;
+
+///////////////////////////////////////////////////////////////////////////////
+//  Data Structures
+///////////////////////////////////////////////////////////////////////////////
+
+// Something that happened outside the UI loop, for it to act on when it next looks
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Event {
+    // A foot switch was pressed
+    Pedal(PedalAction),
+}
+
+// First-in first-out queue of events. Once full, new events are dropped, so a burst can't push out
+// the one that started it.
+#[derive(Debug, Default)]
+pub struct EventQueue {
+    events: [Option<Event>; MAX_EVENTS],
+    head: usize,
+    len: usize,
+    dropped: u32,
+}
+
+// What a running job makes of the presses queued since it last looked
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct MidJob {
+    // A pause was pressed, whatever else was pressed around it
+    pub pause: bool,
+    // Single cuts asked for while the job has the blade, which are refused rather than left queued
+    pub refused_cuts: u32,
+}
+
+///////////////////////////////////////////////////////////////////////////////
+//  Object Implementations
+///////////////////////////////////////////////////////////////////////////////
+
+impl EventQueue {
+    pub const fn new() -> Self {
+        Self {
+            events: [None; MAX_EVENTS],
+            head: 0,
+            len: 0,
+            dropped: 0,
+        }
+    }
+
+    pub fn push(&mut self, event: Event) {
+        if self.len == MAX_EVENTS {
+            self.dropped += 1;
+            return;
+        }
+
+        self.events[(self.head + self.len) % MAX_EVENTS] = Some(event);
+        self.len += 1;
+    }
+
+    pub fn pop(&mut self) -> Option<Event> {
+        if self.len == 0 {
+            return None;
+        }
+
+        let event = self.events[self.head].take();
+        self.head = (self.head + 1) % MAX_EVENTS;
+        self.len -= 1;
+        event
+    }
+
+    // Take every pending event as a running job sees it. A start press means nothing to a job that's
+    // already running; pressed again once it's paused, it resumes it.
+    pub fn take_mid_job(&mut self) -> MidJob {
+        let mut seen = MidJob::default();
+        while let Some(event) = self.pop() {
+            match event {
+                Event::Pedal(PedalAction::Pause) => seen.pause = true,
+                Event::Pedal(PedalAction::StartJob) => {}
+                Event::Pedal(PedalAction::SingleCut) => seen.refused_cuts += 1,
+            }
+        }
+
+        seen
+    }
+
+    // Forget any pending events, e.g. presses made before the screen that handles them was up
+    pub fn clear(&mut self) {
+        while self.pop().is_some() {}
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    // Events lost to a full queue since power-on
+    pub fn dropped(&self) -> u32 {
+        self.dropped
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn events_come_out_in_order() {
+        let mut queue = EventQueue::new();
+        // Enough rounds to wrap around the buffer
+        for _ in 0..MAX_EVENTS {
+            queue.push(Event::Pedal(PedalAction::Pause));
+            queue.push(Event::Pedal(PedalAction::SingleCut));
+            assert_eq!(queue.pop(), Some(Event::Pedal(PedalAction::Pause)));
+            assert_eq!(queue.pop(), Some(Event::Pedal(PedalAction::SingleCut)));
+        }
+
+        queue.push(Event::Pedal(PedalAction::StartJob));
+        queue.push(Event::Pedal(PedalAction::Pause));
+        assert_eq!(queue.len(), 2);
+
+        queue.clear();
+        assert!(queue.is_empty());
+        assert_eq!(queue.pop(), None);
+    }
+
+    #[test]
+    fn full_queue_drops_new_events() {
+        let mut queue = EventQueue::new();
+        queue.push(Event::Pedal(PedalAction::StartJob));
+        for _ in 1..MAX_EVENTS + 2 {
+            queue.push(Event::Pedal(PedalAction::Pause));
+        }
+
+        assert_eq!(queue.len(), MAX_EVENTS);
+        assert_eq!(queue.dropped(), 2);
+        assert_eq!(queue.pop(), Some(Event::Pedal(PedalAction::StartJob)));
+    }
+
+    #[test]
+    fn pause_behind_single_cut_is_seen_mid_feed() {
+        let mut queue = EventQueue::new();
+        queue.push(Event::Pedal(PedalAction::SingleCut));
+        queue.push(Event::Pedal(PedalAction::Pause));
+
+        assert_eq!(
+            queue.take_mid_job(),
+            MidJob {
+                pause: true,
+                refused_cuts: 1,
+            }
+        );
+        assert!(queue.is_empty());
+
+        queue.push(Event::Pedal(PedalAction::StartJob));
+        assert_eq!(queue.take_mid_job(), MidJob::default());
+    }
+}
@@ -0,0 +1,171 @@
+/* * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * *\
+Copyright (C) 2023 CJ McAllister
+    This program is free software; you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation; either version 3 of the License, or
+    (at your option) any later version.
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+    You should have received a copy of the GNU General Public License
+    along with this program; if not, write to the Free Software Foundation,
+    Inc., 51 Franklin Street, Fifth Floor, Boston, MA 02110-1301  USA
+
+\* * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * */
+
+// Shadow of the characters on the LCD, so a screen can be updated as often as its figures change while
+// only the characters that differ from what's showing go over the bus, and only when it's drawn.
+// Covers both pages of a two-page screen.
+
+///////////////////////////////////////////////////////////////////////////////
+//  Named Constants
+///////////////////////////////////////////////////////////////////////////////
+
+pub const ROWS: usize = 2;
+pub const COLS: usize = 32;
+
+///////////////////////////////////////////////////////////////////////////////
+//  Data Structures
+///////////////////////////////////////////////////////////////////////////////
+
+// Characters as they should be, and which of them the display doesn't show yet
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Frame {
+    cells: [[u8; COLS]; ROWS],
+    // One bit per column
+    dirty: [u32; ROWS],
+}
+
+// Consecutive changed characters, to be written from one cursor position
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Run {
+    pub row: usize,
+    pub col: usize,
+    pub len: usize,
+}
+
+///////////////////////////////////////////////////////////////////////////////
+//  Object Implementations
+///////////////////////////////////////////////////////////////////////////////
+
+impl Frame {
+    // Matches a cleared display
+    pub const fn new() -> Self {
+        Self {
+            cells: [[b' '; COLS]; ROWS],
+            dirty: [0; ROWS],
+        }
+    }
+
+    // Text beyond the end of the row is dropped
+    pub fn write(&mut self, row: usize, col: usize, text: &[u8]) {
+        let Some(cells) = self.cells.get_mut(row) else {
+            return;
+        };
+
+        for (i, &byte) in text.iter().enumerate().take(COLS.saturating_sub(col)) {
+            if cells[col + i] != byte {
+                cells[col + i] = byte;
+                self.dirty[row] |= 1 << (col + i);
+            }
+        }
+    }
+
+    pub fn is_dirty(&self) -> bool {
+        self.dirty.iter().any(|&dirty| dirty != 0)
+    }
+
+    // The next run of changed characters, now counted as shown
+    pub fn take_run(&mut self) -> Option<Run> {
+        let row = self.dirty.iter().position(|&dirty| dirty != 0)?;
+        let dirty = self.dirty[row];
+        let col = dirty.trailing_zeros() as usize;
+        let len = (!(dirty >> col)).trailing_zeros() as usize;
+
+        self.dirty[row] &= !(mask(len) << col);
+        Some(Run { row, col, len })
+    }
+
+    pub fn text(&self, run: &Run) -> &[u8] {
+        &self.cells[run.row][run.col..run.col + run.len]
+    }
+}
+
+impl Default for Frame {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+///////////////////////////////////////////////////////////////////////////////
+//  Helper Functions
+///////////////////////////////////////////////////////////////////////////////
+
+// The lowest `len` bits set
+fn mask(len: usize) -> u32 {
+    if len >= 32 {
+        u32::MAX
+    } else {
+        (1 << len) - 1
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn drain(frame: &mut Frame) -> Vec<(usize, usize, String)> {
+        let mut runs = Vec::new();
+        while let Some(run) = frame.take_run() {
+            let text = String::from_utf8(frame.text(&run).to_vec()).unwrap();
+            runs.push((run.row, run.col, text));
+        }
+        runs
+    }
+
+    #[test]
+    fn only_changes_are_drawn() {
+        let mut frame = Frame::new();
+        frame.write(1, 0, b"00009 / 00100");
+        assert_eq!(
+            drain(&mut frame),
+            [
+                (1, 0, "00009".to_string()),
+                (1, 6, "/".to_string()),
+                (1, 8, "00100".to_string())
+            ]
+        );
+
+        frame.write(1, 0, b"00010");
+        assert_eq!(drain(&mut frame), [(1, 3, "10".to_string())]);
+        assert!(!frame.is_dirty());
+    }
+
+    #[test]
+    fn coalesces_repeated_updates() {
+        let mut frame = Frame::new();
+        frame.write(1, 0, b"00001");
+        frame.write(1, 0, b"00002");
+        frame.write(1, 0, b"00003");
+        assert_eq!(drain(&mut frame), [(1, 0, "00003".to_string())]);
+
+        frame.write(1, 4, b"4");
+        frame.write(1, 4, b"5");
+        assert_eq!(drain(&mut frame), [(1, 4, "5".to_string())]);
+
+        // Changed back before being drawn is still drawn again, which is harmless
+        frame.write(1, 4, b"6");
+        frame.write(1, 4, b"5");
+        assert_eq!(drain(&mut frame), [(1, 4, "5".to_string())]);
+    }
+
+    #[test]
+    fn full_rows_and_clipping() {
+        let mut frame = Frame::new();
+        frame.write(0, 0, &[b'x'; COLS + 4]);
+        frame.write(0, COLS, b"y");
+        frame.write(ROWS, 0, b"z");
+        assert_eq!(drain(&mut frame), [(0, 0, "x".repeat(COLS))]);
+    }
+}
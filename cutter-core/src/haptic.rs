@@ -0,0 +1,124 @@
+/* * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * *\
+Copyright (C) 2023 CJ McAllister
+    This program is free software; you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation; either version 3 of the License, or
+    (at your option) any later version.
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+    You should have received a copy of the GNU General Public License
+    along with this program; if not, write to the Free Software Foundation,
+    Inc., 51 Franklin Street, Fifth Floor, Boston, MA 02110-1301  USA
+
+\* * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * */
+///////////////////////////////////////////////////////////////////////////////
+// A vibration motor, for feeling key presses and the end of a job in shops too loud to hear the buzzer
+// over. It's felt rather than heard, so silent mode and quiet hours don't hold it back.
+
+///////////////////////////////////////////////////////////////////////////////
+//  Named Constants
+///////////////////////////////////////////////////////////////////////////////
+
+// Long enough for a coin motor to spin up and be felt through a glove
+const KEY_PRESS: [Pulse; 1] = [Pulse::new(40, 0)];
+const JOB_DONE: [Pulse; 3] = [
+    Pulse::new(250, 150),
+    Pulse::new(250, 150),
+    Pulse::new(250, 0),
+];
+
+///////////////////////////////////////////////////////////////////////////////
+//  Data Structures
+///////////////////////////////////////////////////////////////////////////////
+
+// What the motor's run for
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Cue {
+    KeyPress,
+    // Whether it finished or halted
+    JobDone,
+}
+
+// The motor running, then resting before whatever follows
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Pulse {
+    pub on_ms: u32,
+    pub off_ms: u32,
+}
+
+// Which cues the motor runs for, if one's fitted
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Haptic {
+    pub keys: bool,
+    pub job_done: bool,
+}
+
+///////////////////////////////////////////////////////////////////////////////
+//  Object Implementations
+///////////////////////////////////////////////////////////////////////////////
+
+impl Cue {
+    pub fn pattern(self) -> &'static [Pulse] {
+        match self {
+            Cue::KeyPress => &KEY_PRESS,
+            Cue::JobDone => &JOB_DONE,
+        }
+    }
+}
+
+impl Pulse {
+    pub const fn new(on_ms: u32, off_ms: u32) -> Self {
+        Self { on_ms, off_ms }
+    }
+}
+
+impl Haptic {
+    // Only builds with a motor fitted run it, so it's on for everything until turned off
+    pub const DEFAULT: Self = Self {
+        keys: true,
+        job_done: true,
+    };
+
+    pub fn allows(&self, cue: Cue) -> bool {
+        match cue {
+            Cue::KeyPress => self.keys,
+            Cue::JobDone => self.job_done,
+        }
+    }
+}
+
+impl Default for Haptic {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cues_are_switched_separately() {
+        let keys_only = Haptic {
+            keys: true,
+            job_done: false,
+        };
+        assert!(keys_only.allows(Cue::KeyPress));
+        assert!(!keys_only.allows(Cue::JobDone));
+        assert!(Haptic::DEFAULT.allows(Cue::JobDone));
+
+        // A key press mustn't hold up the next scan for long, nor end on a rest
+        let press: u32 = Cue::KeyPress
+            .pattern()
+            .iter()
+            .map(|p| p.on_ms + p.off_ms)
+            .sum();
+        assert!(press <= 50);
+        for cue in [Cue::KeyPress, Cue::JobDone] {
+            assert_eq!(cue.pattern().last().unwrap().off_ms, 0);
+        }
+    }
+}
@@ -0,0 +1,100 @@
+/* * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * *\
+Copyright (C) 2023 CJ McAllister
+    This program is free software; you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation; either version 3 of the License, or
+    (at your option) any later version.
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+    You should have received a copy of the GNU General Public License
+    along with this program; if not, write to the Free Software Foundation,
+    Inc., 51 Franklin Street, Fifth Floor, Boston, MA 02110-1301  USA
+
+\* * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * */
+
+// Hints for operators new to the machine, shown on a prompt's second line until they've been used.
+// What's been learned lasts until power-off, so every session starts out showing them all again.
+
+///////////////////////////////////////////////////////////////////////////////
+//  Named Constants
+///////////////////////////////////////////////////////////////////////////////
+
+const ENTRY_TEXT: &str = "#OK *DEL";
+const PAGE_TEXT: &str = "2/8SEL #ED *BACK";
+
+///////////////////////////////////////////////////////////////////////////////
+//  Data Structures
+///////////////////////////////////////////////////////////////////////////////
+
+// Where a hint's shown
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Hint {
+    // After a number being entered, until the first key's pressed
+    Entry,
+    // Flashed on opening a settings page
+    Page,
+}
+
+// The hints used this session
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct Help {
+    learned: u8,
+}
+
+///////////////////////////////////////////////////////////////////////////////
+//  Object Implementations
+///////////////////////////////////////////////////////////////////////////////
+
+impl Hint {
+    pub fn text(self) -> &'static str {
+        match self {
+            Hint::Entry => ENTRY_TEXT,
+            Hint::Page => PAGE_TEXT,
+        }
+    }
+
+    fn bit(self) -> u8 {
+        1 << self as u8
+    }
+}
+
+impl Help {
+    pub const NEW: Self = Self { learned: 0 };
+
+    // The hint's text while it's still to be learned, if it fits in `width` columns
+    pub fn hint(&self, hint: Hint, width: usize) -> Option<&'static str> {
+        let text = hint.text();
+        (self.learned & hint.bit() == 0 && text.len() <= width).then_some(text)
+    }
+
+    pub fn learn(&mut self, hint: Hint) {
+        self.learned |= hint.bit();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hints_are_shown_until_used() {
+        let mut help = Help::NEW;
+        assert_eq!(help.hint(Hint::Entry, 16), Some("#OK *DEL"));
+        assert_eq!(help.hint(Hint::Page, 16), Some("2/8SEL #ED *BACK"));
+
+        // Only where there's room for all of it
+        assert_eq!(help.hint(Hint::Entry, 7), None);
+
+        help.learn(Hint::Entry);
+        assert_eq!(help.hint(Hint::Entry, 16), None);
+        assert!(help.hint(Hint::Page, 16).is_some());
+
+        // Every hint has to fit on a line
+        for hint in [Hint::Entry, Hint::Page].iter() {
+            assert!(hint.text().len() <= 16);
+        }
+    }
+}
@@ -0,0 +1,147 @@
+/* * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * *\
+Copyright (C) 2023 CJ McAllister
+    This program is free software; you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation; either version 3 of the License, or
+    (at your option) any later version.
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+    You should have received a copy of the GNU General Public License
+    along with this program; if not, write to the Free Software Foundation,
+    Inc., 51 Franklin Street, Fifth Floor, Boston, MA 02110-1301  USA
+
+\* * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * */
+
+use crate::queue::{Job, QueueError};
+
+///////////////////////////////////////////////////////////////////////////////
+//  Data Structures
+///////////////////////////////////////////////////////////////////////////////
+
+// Bounds an imported job must fall within, in the operator's units
+#[derive(Copy, Clone, Debug)]
+pub struct JobLimits {
+    pub min_cut_length: u32,
+    pub max_cut_length: u32,
+    pub min_num_cuts: u32,
+    pub max_num_cuts: u32,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum ImportError {
+    MissingCount,
+    BadLength,
+    BadCount,
+    LengthOutOfRange,
+    CountOutOfRange,
+    Queue(QueueError),
+}
+
+///////////////////////////////////////////////////////////////////////////////
+//  Object Implementations
+///////////////////////////////////////////////////////////////////////////////
+
+impl ImportError {
+    pub fn message(&self) -> &'static str {
+        match self {
+            Self::MissingCount => "expected length,count[,label]",
+            Self::BadLength => "length is not a number",
+            Self::BadCount => "count is not a number",
+            Self::LengthOutOfRange => "length out of range",
+            Self::CountOutOfRange => "count out of range",
+            Self::Queue(e) => e.message(),
+        }
+    }
+}
+
+impl From<QueueError> for ImportError {
+    fn from(e: QueueError) -> Self {
+        Self::Queue(e)
+    }
+}
+
+///////////////////////////////////////////////////////////////////////////////
+//  Static Functions
+///////////////////////////////////////////////////////////////////////////////
+
+// Parse one `length,count,label` line of a cut list. The label is optional and may be quoted.
+// Blank lines and a `length,...` header row, as spreadsheets export, give Ok(None).
+pub fn parse_job_line(line: &str, limits: &JobLimits) -> Result<Option<Job>, ImportError> {
+    let line = line.trim();
+    if line.is_empty() {
+        return Ok(None);
+    }
+
+    let mut fields = line.splitn(3, ',').map(str::trim);
+    let length_field = fields.next().unwrap_or("");
+    if length_field.eq_ignore_ascii_case("length") {
+        return Ok(None);
+    }
+    let count_field = fields.next().ok_or(ImportError::MissingCount)?;
+    let label = fields.next().unwrap_or("").trim_matches('"');
+
+    let cut_length: u32 = length_field.parse().map_err(|_| ImportError::BadLength)?;
+    let num_cuts: u32 = count_field.parse().map_err(|_| ImportError::BadCount)?;
+    if !(limits.min_cut_length..=limits.max_cut_length).contains(&cut_length) {
+        return Err(ImportError::LengthOutOfRange);
+    }
+    if !(limits.min_num_cuts..=limits.max_num_cuts).contains(&num_cuts) {
+        return Err(ImportError::CountOutOfRange);
+    }
+
+    Ok(Some(Job::new(cut_length, num_cuts).with_label(label)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LIMITS: JobLimits = JobLimits {
+        min_cut_length: 1,
+        max_cut_length: 1000,
+        min_num_cuts: 1,
+        max_num_cuts: 99999,
+    };
+
+    #[test]
+    fn parses_jobs() {
+        let job = parse_job_line("120, 40, \"PANEL, A\"", &LIMITS)
+            .unwrap()
+            .unwrap();
+        assert_eq!((job.cut_length, job.num_cuts), (120, 40));
+        assert_eq!(job.label(), "PANEL, A");
+
+        let job = parse_job_line("6,2", &LIMITS).unwrap().unwrap();
+        assert_eq!(job.label(), "");
+    }
+
+    #[test]
+    fn skips_headers_and_blanks() {
+        assert_eq!(parse_job_line("Length,Count,Label", &LIMITS), Ok(None));
+        assert_eq!(parse_job_line("  ", &LIMITS), Ok(None));
+    }
+
+    #[test]
+    fn reports_bad_lines() {
+        assert_eq!(
+            parse_job_line("12", &LIMITS),
+            Err(ImportError::MissingCount)
+        );
+        assert_eq!(
+            parse_job_line("12.5,3", &LIMITS),
+            Err(ImportError::BadLength)
+        );
+        assert_eq!(parse_job_line("12,x", &LIMITS), Err(ImportError::BadCount));
+        assert_eq!(
+            parse_job_line("0,3", &LIMITS),
+            Err(ImportError::LengthOutOfRange)
+        );
+        assert_eq!(
+            parse_job_line("12,0", &LIMITS),
+            Err(ImportError::CountOutOfRange)
+        );
+    }
+}
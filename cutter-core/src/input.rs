@@ -0,0 +1,484 @@
+/* * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * *\
+Copyright (C) 2023 CJ McAllister
+    This program is free software; you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation; either version 3 of the License, or
+    (at your option) any later version.
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+    You should have received a copy of the GNU General Public License
+    along with this program; if not, write to the Free Software Foundation,
+    Inc., 51 Franklin Street, Fifth Floor, Boston, MA 02110-1301  USA
+
+\* * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * */
+
+///////////////////////////////////////////////////////////////////////////////
+//  Named Constants
+///////////////////////////////////////////////////////////////////////////////
+
+// Cap input to 5 digits, which is also the most the LCD's write_u32 can display
+pub const MAX_INPUT_CHARS: usize = 5;
+
+// Consecutive identical samples needed before a change in key state is believed
+pub const DEBOUNCE_SAMPLES: u8 = 4;
+
+///////////////////////////////////////////////////////////////////////////////
+//  Data Structures
+///////////////////////////////////////////////////////////////////////////////
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Key {
+    One,
+    Two,
+    Three,
+    Four,
+    Five,
+    Six,
+    Seven,
+    Eight,
+    Nine,
+    Star,
+    Zero,
+    Pound,
+}
+
+// Turns raw keypad samples into one event per press, reported when the key is released
+#[derive(Debug)]
+pub struct Debouncer {
+    stable: Option<Key>,
+    candidate: Option<Key>,
+    count: u8,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum EntryError {
+    Empty,
+    Full,
+    NotADigit,
+    TooSmall,
+    TooLarge,
+}
+
+/// Bounded buffer of keypad digits which parses to a range-checked u32.
+#[derive(Debug)]
+pub struct NumberEntry {
+    digits: [u8; MAX_INPUT_CHARS],
+    len: usize,
+    min: u32,
+    max: u32,
+}
+
+///////////////////////////////////////////////////////////////////////////////
+//  Object Implementations
+///////////////////////////////////////////////////////////////////////////////
+
+impl Key {
+    // Numeric value of the key, if it is a digit key
+    pub fn digit(self) -> Option<u8> {
+        match self {
+            Key::One => Some(1),
+            Key::Two => Some(2),
+            Key::Three => Some(3),
+            Key::Four => Some(4),
+            Key::Five => Some(5),
+            Key::Six => Some(6),
+            Key::Seven => Some(7),
+            Key::Eight => Some(8),
+            Key::Nine => Some(9),
+            Key::Zero => Some(0),
+            Key::Star | Key::Pound => None,
+        }
+    }
+
+    // The key labelled with the given character, the inverse of its `&str` form
+    pub fn from_symbol(symbol: u8) -> Option<Self> {
+        match symbol {
+            b'1' => Some(Key::One),
+            b'2' => Some(Key::Two),
+            b'3' => Some(Key::Three),
+            b'4' => Some(Key::Four),
+            b'5' => Some(Key::Five),
+            b'6' => Some(Key::Six),
+            b'7' => Some(Key::Seven),
+            b'8' => Some(Key::Eight),
+            b'9' => Some(Key::Nine),
+            b'*' => Some(Key::Star),
+            b'0' => Some(Key::Zero),
+            b'#' => Some(Key::Pound),
+            _ => None,
+        }
+    }
+}
+
+impl From<Key> for &str {
+    fn from(key: Key) -> Self {
+        match key {
+            Key::One => "1",
+            Key::Two => "2",
+            Key::Three => "3",
+            Key::Four => "4",
+            Key::Five => "5",
+            Key::Six => "6",
+            Key::Seven => "7",
+            Key::Eight => "8",
+            Key::Nine => "9",
+            Key::Star => "*",
+            Key::Zero => "0",
+            Key::Pound => "#",
+        }
+    }
+}
+
+impl Debouncer {
+    // Starts out idle, as if the keypad had been released for a while
+    pub const fn new() -> Self {
+        Self {
+            stable: None,
+            candidate: None,
+            count: DEBOUNCE_SAMPLES,
+        }
+    }
+
+    // Feed one raw sample, returning a key once its press has ended
+    pub fn update(&mut self, sample: Option<Key>) -> Option<Key> {
+        if sample == self.candidate {
+            self.count = self.count.saturating_add(1);
+        } else {
+            self.candidate = sample;
+            self.count = 1;
+        }
+
+        // Only a run of identical samples can change the stable state
+        if self.count >= DEBOUNCE_SAMPLES && self.candidate != self.stable {
+            let released = self.stable;
+            self.stable = self.candidate;
+            return released;
+        }
+
+        None
+    }
+
+    // No key is held, and none has been seen long enough to count as pressed
+    pub fn is_idle(&self) -> bool {
+        self.stable.is_none() && self.candidate.is_none() && self.count >= DEBOUNCE_SAMPLES
+    }
+}
+
+impl Default for Debouncer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl EntryError {
+    /// Short, LCD-line-sized description of the error.
+    pub fn message(&self) -> &'static str {
+        match self {
+            Self::Empty => "NO VALUE ENTERED",
+            Self::Full => "TOO MANY DIGITS",
+            Self::NotADigit => "NOT A DIGIT",
+            Self::TooSmall => "VALUE TOO SMALL",
+            Self::TooLarge => "VALUE TOO LARGE",
+        }
+    }
+}
+
+impl NumberEntry {
+    pub fn new(min: u32, max: u32) -> Self {
+        Self {
+            digits: [0; MAX_INPUT_CHARS],
+            len: 0,
+            min,
+            max,
+        }
+    }
+
+    // Pre-populate the entry with an existing value, e.g. from the last job. Zero means "no value".
+    pub fn with_value(min: u32, max: u32, value: u32) -> Self {
+        let mut entry = Self::new(min, max);
+        if value == 0 {
+            return entry;
+        }
+
+        // Collect digits least-significant first, then store them in display order
+        let mut remaining = value;
+        while remaining > 0 && entry.len < MAX_INPUT_CHARS {
+            entry.digits[entry.len] = b'0' + (remaining % 10) as u8;
+            entry.len += 1;
+            remaining /= 10;
+        }
+
+        // Values too long to fit are dropped rather than truncated
+        if remaining > 0 {
+            return Self::new(min, max);
+        }
+
+        entry.digits[..entry.len].reverse();
+        entry
+    }
+
+    // Append a digit, e.g. from Key::digit(); None is rejected as not a digit
+    pub fn push(&mut self, digit: Option<u8>) -> Result<(), EntryError> {
+        let digit = digit.filter(|d| *d < 10).ok_or(EntryError::NotADigit)?;
+
+        if self.len >= MAX_INPUT_CHARS {
+            return Err(EntryError::Full);
+        }
+
+        self.digits[self.len] = b'0' + digit;
+        self.len += 1;
+
+        Ok(())
+    }
+
+    /// Removes the last digit, returning false if there was nothing to remove.
+    pub fn pop(&mut self) -> bool {
+        if self.len == 0 {
+            return false;
+        }
+
+        self.len -= 1;
+        true
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// The digits entered so far, as displayed on the LCD.
+    pub fn as_str(&self) -> &str {
+        // Only ASCII digits are ever stored, so this cannot fail
+        core::str::from_utf8(&self.digits[..self.len]).unwrap()
+    }
+
+    /// Parses the entered digits, saturating on overflow and validating against the range.
+    pub fn value(&self) -> Result<u32, EntryError> {
+        if self.is_empty() {
+            return Err(EntryError::Empty);
+        }
+
+        let parsed = self.digits[..self.len].iter().fold(0_u32, |acc, digit| {
+            acc.saturating_mul(10).saturating_add((digit - b'0') as u32)
+        });
+
+        if parsed < self.min {
+            Err(EntryError::TooSmall)
+        } else if parsed > self.max {
+            Err(EntryError::TooLarge)
+        } else {
+            Ok(parsed)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    fn entry_of(min: u32, max: u32, digits: &[u8]) -> NumberEntry {
+        let mut entry = NumberEntry::new(min, max);
+        for &digit in digits {
+            entry.push(Some(digit)).unwrap();
+        }
+        entry
+    }
+
+    #[test]
+    fn parses_entered_digits() {
+        let entry = entry_of(1, 1000, &[0, 4, 2]);
+        assert_eq!(entry.as_str(), "042");
+        assert_eq!(entry.value(), Ok(42));
+    }
+
+    #[test]
+    fn rejects_out_of_range_values() {
+        assert_eq!(NumberEntry::new(1, 10).value(), Err(EntryError::Empty));
+        assert_eq!(entry_of(1, 10, &[0]).value(), Err(EntryError::TooSmall));
+        assert_eq!(entry_of(1, 10, &[1, 1]).value(), Err(EntryError::TooLarge));
+    }
+
+    #[test]
+    fn rejects_non_digits_and_overfill() {
+        let mut entry = entry_of(0, u32::MAX, &[9; MAX_INPUT_CHARS]);
+        assert_eq!(entry.push(Some(1)), Err(EntryError::Full));
+        assert_eq!(entry.push(None), Err(EntryError::NotADigit));
+        assert_eq!(entry.push(Some(10)), Err(EntryError::NotADigit));
+        assert_eq!(entry.value(), Ok(99999));
+    }
+
+    #[test]
+    fn pop_removes_last_digit() {
+        let mut entry = entry_of(0, 100, &[1, 2]);
+        assert!(entry.pop());
+        assert_eq!(entry.as_str(), "1");
+        assert!(entry.pop());
+        assert!(!entry.pop());
+    }
+
+    #[test]
+    fn prefill_round_trips() {
+        assert_eq!(NumberEntry::with_value(1, 1000, 250).as_str(), "250");
+        assert!(NumberEntry::with_value(1, 1000, 0).is_empty());
+        assert!(NumberEntry::with_value(1, u32::MAX, 123_456).is_empty());
+    }
+
+    proptest! {
+        #[test]
+        fn entry_round_trips(val in 1..=99_999_u32) {
+            // Enter the value as typed, then parse it back
+            let mut entry = NumberEntry::new(1, 99_999);
+            for c in val.to_string().bytes() {
+                entry.push(Some(c - b'0')).unwrap();
+            }
+            prop_assert_eq!(entry.value(), Ok(val));
+            prop_assert_eq!(NumberEntry::with_value(1, 99_999, val).value(), Ok(val));
+        }
+
+        #[test]
+        fn leading_zeros_dont_change_value(val in 0..=999_u32, zeros in 0..=2_usize) {
+            let mut entry = NumberEntry::new(0, u32::MAX);
+            for _ in 0..zeros {
+                entry.push(Some(0)).unwrap();
+            }
+            for c in val.to_string().bytes() {
+                entry.push(Some(c - b'0')).unwrap();
+            }
+            prop_assert_eq!(entry.value(), Ok(val));
+        }
+
+        #[test]
+        fn range_check_is_exact(val in 0..=99_999_u32, min in 0..=99_999_u32, max in 0..=99_999_u32) {
+            let entry = NumberEntry::with_value(min, max, val);
+            let expected = if val == 0 {
+                Err(EntryError::Empty)
+            } else if val < min {
+                Err(EntryError::TooSmall)
+            } else if val > max {
+                Err(EntryError::TooLarge)
+            } else {
+                Ok(val)
+            };
+            prop_assert_eq!(entry.value(), expected);
+        }
+    }
+
+    // Fuzz harness for the debouncer: simulated presses with contact bounce at both edges
+
+    const KEYS: [Key; 12] = [
+        Key::One,
+        Key::Two,
+        Key::Three,
+        Key::Four,
+        Key::Five,
+        Key::Six,
+        Key::Seven,
+        Key::Eight,
+        Key::Nine,
+        Key::Star,
+        Key::Zero,
+        Key::Pound,
+    ];
+
+    #[derive(Debug, Clone)]
+    struct SimulatedPress {
+        key: Key,
+        bounce_in: Vec<u8>,
+        hold: usize,
+        bounce_out: Vec<u8>,
+        gap: usize,
+    }
+
+    // Bounces are runs of alternating contact states, each too short to be believed
+    fn bounce() -> impl Strategy<Value = Vec<u8>> {
+        prop::collection::vec(1..DEBOUNCE_SAMPLES, 0..8)
+    }
+
+    fn press() -> impl Strategy<Value = SimulatedPress> {
+        let min = DEBOUNCE_SAMPLES as usize;
+        (
+            prop::sample::select(&KEYS[..]),
+            bounce(),
+            min..min * 10,
+            bounce(),
+            min..min * 10,
+        )
+            .prop_map(|(key, bounce_in, hold, bounce_out, gap)| SimulatedPress {
+                key,
+                bounce_in,
+                hold,
+                bounce_out,
+                gap,
+            })
+    }
+
+    fn push_runs(
+        samples: &mut Vec<Option<Key>>,
+        runs: &[u8],
+        first: Option<Key>,
+        second: Option<Key>,
+    ) {
+        for (i, &run) in runs.iter().enumerate() {
+            let state = if i % 2 == 0 { first } else { second };
+            samples.extend(std::iter::repeat_n(state, run as usize));
+        }
+    }
+
+    fn simulate(presses: &[SimulatedPress]) -> Vec<Option<Key>> {
+        let mut samples = Vec::new();
+        for press in presses {
+            let key = Some(press.key);
+            push_runs(&mut samples, &press.bounce_in, key, None);
+            samples.extend(std::iter::repeat_n(key, press.hold));
+            push_runs(&mut samples, &press.bounce_out, None, key);
+            samples.extend(std::iter::repeat_n(None, press.gap));
+        }
+        samples
+    }
+
+    proptest! {
+        #[test]
+        fn debounced_presses_are_reported_exactly_once(presses in prop::collection::vec(press(), 0..20)) {
+            let mut debouncer = Debouncer::new();
+            let events: Vec<Key> = simulate(&presses)
+                .into_iter()
+                .filter_map(|sample| debouncer.update(sample))
+                .collect();
+
+            let expected: Vec<Key> = presses.iter().map(|p| p.key).collect();
+            prop_assert_eq!(events, expected);
+            prop_assert!(debouncer.is_idle());
+        }
+
+        #[test]
+        fn pure_bounce_is_ignored(runs in bounce(), key in prop::sample::select(&KEYS[..])) {
+            let mut debouncer = Debouncer::new();
+            let mut samples = Vec::new();
+            push_runs(&mut samples, &runs, Some(key), None);
+            samples.extend(std::iter::repeat_n(None, DEBOUNCE_SAMPLES as usize));
+
+            for sample in samples {
+                prop_assert_eq!(debouncer.update(sample), None);
+            }
+            prop_assert!(debouncer.is_idle());
+        }
+    }
+
+    #[test]
+    fn held_key_reports_nothing_until_released() {
+        let mut debouncer = Debouncer::new();
+        for _ in 0..1_000_000 {
+            assert_eq!(debouncer.update(Some(Key::Five)), None);
+        }
+        assert!(!debouncer.is_idle());
+
+        let events: Vec<_> = (0..DEBOUNCE_SAMPLES)
+            .filter_map(|_| debouncer.update(None))
+            .collect();
+        assert_eq!(events, [Key::Five]);
+    }
+}
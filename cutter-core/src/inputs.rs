@@ -0,0 +1,322 @@
+/* * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * *\
+Copyright (C) 2023 CJ McAllister
+    This program is free software; you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation; either version 3 of the License, or
+    (at your option) any later version.
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+    You should have received a copy of the GNU General Public License
+    along with this program; if not, write to the Free Software Foundation,
+    Inc., 51 Franklin Street, Fifth Floor, Boston, MA 02110-1301  USA
+
+\* * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * */
+
+use core::fmt;
+
+///////////////////////////////////////////////////////////////////////////////
+//  Named Constants
+///////////////////////////////////////////////////////////////////////////////
+
+pub const MAX_INPUTS: usize = 8;
+
+// Consecutive agreeing samples before an input changes state
+pub const INPUT_DEBOUNCE_SAMPLES: u8 = 4;
+
+// Pin select bits, as used by the nRF GPIO: pin number in the low 5 bits, port in bit 5
+const PSEL_PORT_BIT: u8 = 0x20;
+const PSEL_PIN_MASK: u8 = 0x1F;
+const PORT1_NUM_PINS: u8 = 10;
+
+///////////////////////////////////////////////////////////////////////////////
+//  Data Structures
+///////////////////////////////////////////////////////////////////////////////
+
+// What a digital input is wired to, which decides how the machine reacts to it
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum InputFunction {
+    Unused = 0,
+    // End of travel on an axis; stops the job when active
+    LimitSwitch = 1,
+    // Wire supply has run out; stops the job when active
+    WireRunout = 2,
+    // Enclosure door open; pauses the job while active
+    Door = 3,
+    // E-stop circuit healthy; stops the job when NOT active
+    EstopOk = 4,
+    // Pedal, doing the machine's configured PedalAction when pressed
+    FootSwitch = 5,
+}
+
+// What pressing a foot switch does, as set in the machine settings
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum PedalAction {
+    // Start cutting the queue, or resume a paused job
+    StartJob = 0,
+    // One stroke of the blade, e.g. to square up the wire before a job
+    SingleCut = 1,
+    // Pause the running job between pieces
+    Pause = 2,
+}
+
+// Assignment of one digital input
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct InputConfig {
+    pub function: InputFunction,
+    // GPIO in nRF pin select form, e.g. 0x23 for P1.03
+    pub psel: u8,
+    // Switch pulls the pin to ground when active, so the pin gets a pull-up; otherwise a pull-down
+    pub active_low: bool,
+}
+
+// Debounced state of a bank of inputs, one bit per input
+#[derive(Debug, Default)]
+pub struct InputBank {
+    stable: u8,
+    counts: [u8; MAX_INPUTS],
+}
+
+///////////////////////////////////////////////////////////////////////////////
+//  Object Implementations
+///////////////////////////////////////////////////////////////////////////////
+
+impl InputFunction {
+    pub const ALL: [InputFunction; 6] = [
+        Self::Unused,
+        Self::LimitSwitch,
+        Self::WireRunout,
+        Self::Door,
+        Self::EstopOk,
+        Self::FootSwitch,
+    ];
+
+    // As written on the serial console
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Unused => "unused",
+            Self::LimitSwitch => "limit_switch",
+            Self::WireRunout => "wire_runout",
+            Self::Door => "door",
+            Self::EstopOk => "estop_ok",
+            Self::FootSwitch => "foot_switch",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL
+            .iter()
+            .copied()
+            .find(|function| function.name().eq_ignore_ascii_case(name))
+    }
+
+    // Whether an input's active state means the machine must not run
+    pub fn is_fault(self, active: bool) -> bool {
+        match self {
+            Self::LimitSwitch | Self::WireRunout => active,
+            Self::EstopOk => !active,
+            Self::Unused | Self::Door | Self::FootSwitch => false,
+        }
+    }
+}
+
+impl From<u8> for InputFunction {
+    fn from(value: u8) -> Self {
+        match value {
+            1 => Self::LimitSwitch,
+            2 => Self::WireRunout,
+            3 => Self::Door,
+            4 => Self::EstopOk,
+            5 => Self::FootSwitch,
+            _ => Self::Unused,
+        }
+    }
+}
+
+impl From<u8> for PedalAction {
+    fn from(value: u8) -> Self {
+        match value {
+            1 => Self::SingleCut,
+            2 => Self::Pause,
+            _ => Self::StartJob,
+        }
+    }
+}
+
+impl InputConfig {
+    pub const UNUSED: Self = Self {
+        function: InputFunction::Unused,
+        psel: 0,
+        active_low: true,
+    };
+
+    pub fn port(&self) -> u8 {
+        (self.psel & PSEL_PORT_BIT != 0) as u8
+    }
+
+    pub fn pin(&self) -> u8 {
+        self.psel & PSEL_PIN_MASK
+    }
+
+    // Whether the pin exists on the nRF52833
+    pub fn is_valid_pin(&self) -> bool {
+        let no_stray_bits = self.psel & !(PSEL_PORT_BIT | PSEL_PIN_MASK) == 0;
+        no_stray_bits && (self.port() == 0 || self.pin() < PORT1_NUM_PINS)
+    }
+
+    // Convert a raw pin level to whether the input is active
+    pub fn is_active(&self, pin_high: bool) -> bool {
+        pin_high != self.active_low
+    }
+
+    // An assignment as the console writes it: `unused`, or `function:Pport.pin:low|high` with the
+    // level the input is active at, e.g. `estop_ok:P1.03:low`. None if it doesn't name a real pin.
+    pub fn parse(spec: &str) -> Option<Self> {
+        let mut fields = spec.split(':');
+        let function = InputFunction::from_name(fields.next()?)?;
+        if function == InputFunction::Unused {
+            return fields.next().is_none().then_some(Self::UNUSED);
+        }
+
+        let (port, pin) = fields.next()?.strip_prefix(['P', 'p'])?.split_once('.')?;
+        let (port, pin) = (port.parse::<u8>().ok()?, pin.parse::<u8>().ok()?);
+        let active_low = match fields.next()? {
+            level if level.eq_ignore_ascii_case("low") => true,
+            level if level.eq_ignore_ascii_case("high") => false,
+            _ => return None,
+        };
+        if fields.next().is_some() || port > 1 || pin > PSEL_PIN_MASK {
+            return None;
+        }
+
+        let config = Self {
+            function,
+            psel: (port * PSEL_PORT_BIT) | pin,
+            active_low,
+        };
+        config.is_valid_pin().then_some(config)
+    }
+}
+
+impl fmt::Display for InputConfig {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.function == InputFunction::Unused {
+            return write!(f, "{}", self.function.name());
+        }
+
+        write!(
+            f,
+            "{}:P{}.{:02}:{}",
+            self.function.name(),
+            self.port(),
+            self.pin(),
+            if self.active_low { "low" } else { "high" }
+        )
+    }
+}
+
+impl InputBank {
+    pub fn new(active: u8) -> Self {
+        Self {
+            stable: active,
+            ..Self::default()
+        }
+    }
+
+    // Feed in one sample of every input's active state, returning the debounced states
+    pub fn update(&mut self, active: u8) -> u8 {
+        for (i, count) in self.counts.iter_mut().enumerate() {
+            let mask = 1 << i;
+            if active & mask == self.stable & mask {
+                *count = 0;
+                continue;
+            }
+
+            *count += 1;
+            if *count >= INPUT_DEBOUNCE_SAMPLES {
+                self.stable ^= mask;
+                *count = 0;
+            }
+        }
+
+        self.stable
+    }
+
+    pub fn state(&self) -> u8 {
+        self.stable
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn changes_need_consecutive_samples() {
+        let mut bank = InputBank::default();
+        for _ in 0..INPUT_DEBOUNCE_SAMPLES - 1 {
+            assert_eq!(bank.update(0b101), 0);
+        }
+        // A bounce back restarts the count for that input only
+        assert_eq!(bank.update(0b001), 0b001);
+        for _ in 0..INPUT_DEBOUNCE_SAMPLES - 1 {
+            bank.update(0b101);
+        }
+        assert_eq!(bank.update(0b101), 0b101);
+    }
+
+    #[test]
+    fn polarity_and_faults() {
+        let config = InputConfig {
+            function: InputFunction::EstopOk,
+            psel: 0x23,
+            active_low: true,
+        };
+        assert_eq!((config.port(), config.pin()), (1, 3));
+        assert!(config.is_active(false));
+        assert!(config.function.is_fault(config.is_active(true)));
+        assert!(InputFunction::WireRunout.is_fault(true));
+        assert!(!InputFunction::Door.is_fault(true));
+        assert!(!InputFunction::FootSwitch.is_fault(true));
+        assert_eq!(InputFunction::from(5), InputFunction::FootSwitch);
+    }
+
+    #[test]
+    fn pins_beyond_the_chip_are_invalid() {
+        let pin = |psel| InputConfig {
+            psel,
+            ..InputConfig::UNUSED
+        };
+        assert!(pin(31).is_valid_pin());
+        assert!(pin(0x29).is_valid_pin());
+        assert!(!pin(0x2A).is_valid_pin());
+        assert!(!pin(0x40).is_valid_pin());
+    }
+
+    #[test]
+    fn assignments_round_trip_as_text() {
+        let config = InputConfig::parse("estop_ok:P1.03:low").unwrap();
+        assert_eq!(config.function, InputFunction::EstopOk);
+        assert_eq!(config.psel, 0x23);
+        assert!(config.active_low);
+        assert_eq!(config.to_string(), "estop_ok:P1.03:low");
+
+        let pedal = InputConfig::parse("FOOT_SWITCH:p0.4:HIGH").unwrap();
+        assert_eq!(pedal.to_string(), "foot_switch:P0.04:high");
+        assert_eq!(InputConfig::parse("unused"), Some(InputConfig::UNUSED));
+        assert_eq!(InputConfig::UNUSED.to_string(), "unused");
+
+        // Pins the chip doesn't have, and anything half-written
+        assert_eq!(InputConfig::parse("door:P1.10:low"), None);
+        assert_eq!(InputConfig::parse("door:P2.01:low"), None);
+        assert_eq!(InputConfig::parse("door:P0.32:low"), None);
+        assert_eq!(InputConfig::parse("door:P0.03"), None);
+        assert_eq!(InputConfig::parse("door:P0.03:low:x"), None);
+        assert_eq!(InputConfig::parse("hatch:P0.03:low"), None);
+        assert_eq!(InputConfig::parse("unused:P0.03:low"), None);
+    }
+}
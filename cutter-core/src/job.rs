@@ -0,0 +1,945 @@
+/* * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * *\
+Copyright (C) 2023 CJ McAllister
+    This program is free software; you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation; either version 3 of the License, or
+    (at your option) any later version.
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+    You should have received a copy of the GNU General Public License
+    along with this program; if not, write to the Free Software Foundation,
+    Inc., 51 Franklin Street, Fifth Floor, Boston, MA 02110-1301  USA
+
+\* * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * */
+
+// Sequencing of a job's pieces, kept apart from the hardware so it can be tested on the host. Each
+// step cuts a piece then feeds the wire for the next, retrying a move that failed in a way another
+// attempt may get past.
+//
+// Scrap lengths, if set, are trimmed off either side of the pieces as extra feeds and cuts. The leader
+// is fed and cut off before the wire for the first piece, taking whatever was already past the blade
+// with it. The trailer replaces the last piece's feed for the one after, and is cut off too, leaving
+// the wire end at the blade.
+//
+// Jobs can also stop after every so many pieces for the operator to mark them, e.g. with heat-shrink
+// labels, carrying on once they're done. Jobs split into bundles stop the same way after each bundle's
+// last piece, for the operator to take the bundle away. The first of these stops can be a check of
+// the job's first length of wire as soon as it's fed, for the operator to approve what the encoder
+// measured before the rest are cut, catching a feed that's drifted out of calibration.
+//
+// A piece whose cut or feed fails is scrapped. A jam, i.e. a fault that outlasted its retries, stops
+// the job until the operator has cleared it and put the wire end back at the blade; the job then
+// carries on by feeding the next piece afresh. Faults no retry would get past but the operator can
+// clear, e.g. an e-stop, stop the job the same way without being retried. Scrapped pieces are made
+// up at the end of the job if replacements are on, so it still delivers its count, or just go
+// missing from it if not.
+//
+// A pause asked for while the wire is feeding can stop the feed short, having ramped it down rather
+// than leave the wire to skid on. The job pauses there, and once resumed feeds the rest of the length
+// before carrying on where it left off, so the piece isn't cut short.
+
+use crate::numeric;
+use crate::queue::Job;
+
+///////////////////////////////////////////////////////////////////////////////
+//  Named Constants
+///////////////////////////////////////////////////////////////////////////////
+
+// "BUNDLE n OF m" with the longest numbers a u32 can hold
+pub const BUNDLE_LABEL_LEN: usize = 7 + 2 * numeric::MAX_U32_DIGITS + 4;
+
+///////////////////////////////////////////////////////////////////////////////
+//  Data Structures
+///////////////////////////////////////////////////////////////////////////////
+
+// Why a move failed, whether it's worth trying again, and whether the job can carry on once the
+// operator has dealt with it
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Fault {
+    pub message: &'static str,
+    pub retryable: bool,
+    // Also true of any retryable fault
+    pub clearable: bool,
+}
+
+// How much of a feed was made
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Fed {
+    All,
+    // Stopped short by a pause, with this much still to feed
+    Short { left_mils: u32 },
+}
+
+// Pieces cut so far out of the job's total
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Progress {
+    pub piece: u32,
+    pub num_cuts: u32,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum State {
+    Running,
+    // Stopped between pieces, or partway through a feed, until resumed
+    Paused,
+    // Stopped between pieces for the operator to mark the last one, until resumed
+    Marking,
+    // Stopped after the last piece of a bundle for the operator to take it away, until resumed
+    Bundled,
+    // Stopped after the job's first length of wire is fed for the operator to approve it as measured,
+    // until resumed, or aborted if it's off
+    Checking,
+    // Stopped after a move kept failing in a way another attempt may get past, e.g. a wire jam, or
+    // failed in a way the operator can clear, e.g. an e-stop, until they clear it and resume
+    Jammed(Fault),
+    Finished,
+    Aborted(Fault),
+}
+
+// Which of a job's bundles is being cut, or was just finished while the job waits for it to be taken
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Bundle {
+    // Counting from 1
+    pub number: u32,
+    pub count: u32,
+}
+
+// Wire discarded at the start and end of a job, e.g. to get rid of kinked wire
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Scrap {
+    pub leader_mils: u32,
+    pub trailer_mils: u32,
+}
+
+// What's left of a feed a pause stopped short
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+struct ShortFeed {
+    left_mils: u32,
+    // The feed was for the piece after the one just cut, rather than for the piece about to be cut
+    after_cut: bool,
+}
+
+// Drives a job through the machine one piece at a time
+#[derive(Copy, Clone, Debug)]
+pub struct Engine {
+    job: Job,
+    scrap: Scrap,
+    // Pieces between stops for marking, or 0 for none
+    mark_every: u32,
+    max_retries: u32,
+    // Make up scrapped pieces at the end of the job
+    replace_scrapped: bool,
+    // Still to stop for the first length fed to be checked
+    check_first_piece: bool,
+    // Pieces delivered so far, i.e. cut and not scrapped
+    piece: u32,
+    scrapped: u32,
+    retries: u32,
+    // The wire end was left at the blade, e.g. by clearing a jam, so the next piece has to be fed first
+    refeed: bool,
+    short_feed: Option<ShortFeed>,
+    state: State,
+}
+
+pub trait Cutter {
+    // Cut the given piece (counting from 1) off the wire
+    fn cut(&mut self, piece: u32) -> Result<(), Fault>;
+
+    // Cut off scrap, which isn't a piece of the job
+    fn cut_scrap(&mut self) -> Result<(), Fault>;
+}
+
+pub trait Feeder {
+    // Feed the given length of wire through the blade, ready for the next cut
+    fn feed(&mut self, length: u32) -> Result<Fed, Fault>;
+
+    // Feed the rest of a length a pause stopped short, measured in mils whatever the job's units
+    fn feed_rest(&mut self, length_mils: u32) -> Result<Fed, Fault>;
+
+    // Feed scrap through the blade, measured in mils whatever the job's units
+    fn feed_scrap(&mut self, length_mils: u32) -> Result<(), Fault>;
+}
+
+pub trait Display {
+    fn show_progress(&mut self, progress: Progress);
+}
+
+// All of the above, for hardware that has to be shared between them, e.g. a bus or timer
+pub trait Machine: Cutter + Feeder + Display {}
+
+///////////////////////////////////////////////////////////////////////////////
+//  Object Implementations
+///////////////////////////////////////////////////////////////////////////////
+
+impl<T: Cutter + Feeder + Display> Machine for T {}
+
+impl Progress {
+    pub fn remaining(&self) -> u32 {
+        self.num_cuts.saturating_sub(self.piece)
+    }
+
+    pub fn percent(&self) -> u32 {
+        if self.num_cuts == 0 {
+            return 100;
+        }
+
+        (self.piece as u64 * 100 / self.num_cuts as u64) as u32
+    }
+}
+
+impl Bundle {
+    // As "BUNDLE 3 OF 10"
+    pub fn label(self, buf: &mut [u8; BUNDLE_LABEL_LEN]) -> &str {
+        let mut len = 0;
+        let mut digits = [0; numeric::MAX_U32_DIGITS];
+        for part in [
+            "BUNDLE ",
+            numeric::trimmed(self.number, &mut digits),
+            " OF ",
+            numeric::trimmed(self.count, &mut [0; numeric::MAX_U32_DIGITS]),
+        ] {
+            buf[len..len + part.len()].copy_from_slice(part.as_bytes());
+            len += part.len();
+        }
+
+        // Only ASCII is ever written, so this cannot fail
+        core::str::from_utf8(&buf[..len]).unwrap()
+    }
+}
+
+impl Engine {
+    // Moves that fail with a retryable fault are tried again up to `max_retries` times each
+    pub fn new(job: Job, max_retries: u32) -> Self {
+        Self {
+            job,
+            scrap: Scrap::default(),
+            mark_every: 0,
+            max_retries,
+            replace_scrapped: false,
+            check_first_piece: false,
+            piece: 0,
+            scrapped: 0,
+            retries: 0,
+            refeed: false,
+            short_feed: None,
+            state: if job.num_cuts == 0 {
+                State::Finished
+            } else {
+                State::Running
+            },
+        }
+    }
+
+    // Trim scrap off either side of the job's pieces
+    pub fn with_scrap(self, scrap: Scrap) -> Self {
+        Self { scrap, ..self }
+    }
+
+    // Stop after every `mark_every` pieces for marking, other than after the last; 0 never stops
+    pub fn with_marking(self, mark_every: u32) -> Self {
+        Self { mark_every, ..self }
+    }
+
+    // Cut an extra piece at the end of the job for each one scrapped
+    pub fn with_replacements(self, replace_scrapped: bool) -> Self {
+        Self {
+            replace_scrapped,
+            ..self
+        }
+    }
+
+    // Stop once the first piece's length has been fed for it to be checked, other than on a job of one
+    pub fn with_first_piece_check(self, check_first_piece: bool) -> Self {
+        Self {
+            check_first_piece,
+            ..self
+        }
+    }
+
+    pub fn job(&self) -> &Job {
+        &self.job
+    }
+
+    pub fn state(&self) -> State {
+        self.state
+    }
+
+    // Pieces delivered out of those the job can still deliver, which is fewer than it asked for once a
+    // piece is scrapped without replacement
+    pub fn progress(&self) -> Progress {
+        Progress {
+            piece: self.piece,
+            num_cuts: self.piece + self.pieces_left(),
+        }
+    }
+
+    // Pieces scrapped so far
+    pub fn scrapped(&self) -> u32 {
+        self.scrapped
+    }
+
+    // Retries used so far across the whole job
+    pub fn retries(&self) -> u32 {
+        self.retries
+    }
+
+    // Hold off cutting after the current piece
+    pub fn pause(&mut self) {
+        if self.state == State::Running {
+            self.state = State::Paused;
+        }
+    }
+
+    // The bundle being cut, or the one just finished while it waits to be taken, if the job has bundles
+    pub fn bundle(&self) -> Option<Bundle> {
+        let size = self.job.pieces_per_bundle;
+        if size == 0 {
+            return None;
+        }
+
+        let count = self.progress().num_cuts.div_ceil(size);
+        let number = match self.state {
+            State::Bundled => self.piece / size,
+            _ => self.piece / size + 1,
+        };
+        Some(Bundle {
+            number: number.min(count),
+            count,
+        })
+    }
+
+    // Carry on from a pause, once marking is done or a bundle taken, once a jam is cleared, or once the
+    // first piece is approved, stopping straight away if that piece also needs marking or ends a bundle
+    pub fn resume(&mut self) {
+        match self.state {
+            State::Checking => self.state = self.stop_after_piece(),
+            State::Paused | State::Marking | State::Bundled | State::Jammed(_) => {
+                self.state = State::Running
+            }
+            _ => {}
+        }
+    }
+
+    // Give up on the job, unless it's already over
+    pub fn abort(&mut self, fault: Fault) {
+        if let State::Running
+        | State::Paused
+        | State::Marking
+        | State::Bundled
+        | State::Checking
+        | State::Jammed(_) = self.state
+        {
+            self.state = State::Aborted(fault);
+        }
+    }
+
+    // Cut the next piece and feed the wire for the one after, unless paused or done
+    pub fn step(&mut self, machine: &mut dyn Machine) -> State {
+        if self.state != State::Running {
+            return self.state;
+        }
+        // A jam can take the last piece with it, leaving nothing to resume
+        if self.pieces_left() == 0 {
+            self.state = State::Finished;
+            return self.state;
+        }
+
+        let piece = self.piece + 1;
+        let length = self.job.cut_length;
+        let Scrap {
+            leader_mils,
+            trailer_mils,
+        } = self.scrap;
+        // Finish a feed a pause stopped short before anything else
+        let short_feed = self.short_feed.take();
+        let mut result = Ok(Fed::All);
+        match short_feed {
+            Some(ShortFeed {
+                left_mils,
+                after_cut,
+            }) => {
+                result = self.attempt(machine, |machine| machine.feed_rest(left_mils));
+                if after_cut {
+                    return self.fed_next(result);
+                }
+            }
+            None if self.refeed => {
+                result = self.attempt(machine, |machine| machine.feed(length));
+            }
+            None if piece == 1 && leader_mils > 0 => {
+                result = self
+                    .trim(machine, leader_mils)
+                    .and_then(|()| self.attempt(machine, |machine| machine.feed(length)));
+            }
+            None => {}
+        }
+        if let Ok(Fed::Short { left_mils }) = result {
+            return self.stop_short(left_mils, false);
+        }
+        let result = result.and_then(|_| self.attempt(machine, |machine| machine.cut(piece)));
+        if let Err(fault) = result {
+            self.scrapped += 1;
+            return self.fail(fault);
+        }
+
+        self.piece = piece;
+        self.refeed = false;
+        let result = if self.pieces_left() == 0 && trailer_mils > 0 {
+            self.trim(machine, trailer_mils).map(|()| Fed::All)
+        } else {
+            self.attempt(machine, |machine| machine.feed(length))
+        };
+        machine.show_progress(self.progress());
+        self.fed_next(result)
+    }
+
+    // Where feeding the wire for the next piece, after cutting one, leaves the job
+    fn fed_next(&mut self, result: Result<Fed, Fault>) -> State {
+        match result {
+            Ok(Fed::All) => {}
+            Ok(Fed::Short { left_mils }) => return self.stop_short(left_mils, true),
+            Err(fault) => {
+                // What was fed for the next piece is too short to be one
+                if self.pieces_left() > 0 {
+                    self.scrapped += 1;
+                }
+                return self.fail(fault);
+            }
+        }
+
+        self.state = if self.pieces_left() == 0 {
+            State::Finished
+        } else if self.check_first_piece {
+            self.check_first_piece = false;
+            State::Checking
+        } else {
+            self.stop_after_piece()
+        };
+
+        self.state
+    }
+
+    // Pause with the rest of a feed still to make once resumed
+    fn stop_short(&mut self, left_mils: u32, after_cut: bool) -> State {
+        self.short_feed = Some(ShortFeed {
+            left_mils,
+            after_cut,
+        });
+        self.state = State::Paused;
+        self.state
+    }
+
+    // Where the piece just delivered leaves the job, with more to come
+    fn stop_after_piece(&self) -> State {
+        let bundle_size = self.job.pieces_per_bundle;
+        if bundle_size > 0 && self.piece.is_multiple_of(bundle_size) {
+            State::Bundled
+        } else if self.mark_every > 0 && self.piece.is_multiple_of(self.mark_every) {
+            State::Marking
+        } else {
+            State::Running
+        }
+    }
+
+    fn pieces_left(&self) -> u32 {
+        let lost = if self.replace_scrapped {
+            0
+        } else {
+            self.scrapped
+        };
+        self.job.num_cuts.saturating_sub(self.piece + lost)
+    }
+
+    // Stop for a jam to be cleared, or for good if nothing the operator does will help
+    fn fail(&mut self, fault: Fault) -> State {
+        self.refeed = true;
+        self.state = if fault.retryable || fault.clearable {
+            State::Jammed(fault)
+        } else {
+            State::Aborted(fault)
+        };
+        self.state
+    }
+
+    // Feed out scrap and cut it off
+    fn trim(&mut self, machine: &mut dyn Machine, length_mils: u32) -> Result<(), Fault> {
+        self.attempt(machine, |machine| machine.feed_scrap(length_mils))?;
+        self.attempt(machine, |machine| machine.cut_scrap())
+    }
+
+    fn attempt<T>(
+        &mut self,
+        machine: &mut dyn Machine,
+        mut action: impl FnMut(&mut dyn Machine) -> Result<T, Fault>,
+    ) -> Result<T, Fault> {
+        let mut retries_left = self.max_retries;
+        loop {
+            match action(machine) {
+                Err(fault) if fault.retryable && retries_left > 0 => {
+                    retries_left -= 1;
+                    self.retries += 1;
+                }
+                result => return result,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const JAM: Fault = Fault {
+        message: "JAM",
+        retryable: true,
+        clearable: true,
+    };
+    const ESTOP: Fault = Fault {
+        message: "ESTOP",
+        retryable: false,
+        clearable: true,
+    };
+    const DOOR: Fault = Fault {
+        message: "DOOR",
+        retryable: false,
+        clearable: false,
+    };
+
+    // Records what it was asked to do, failing the first few cuts with a given fault, and stopping one
+    // feed short as if paused. Scrap is recorded in line with the pieces, as cut 0 and its length
+    // negated.
+    #[derive(Default)]
+    struct MockMachine {
+        cuts: Vec<u32>,
+        feeds: Vec<i64>,
+        rests: Vec<u32>,
+        shown: Vec<Progress>,
+        failing_cuts: u32,
+        fault: Option<Fault>,
+        // Which feed of a piece's length (counting from 0) to stop short, and by how much
+        short_feed: Option<(usize, u32)>,
+    }
+
+    impl Cutter for MockMachine {
+        fn cut(&mut self, piece: u32) -> Result<(), Fault> {
+            self.cuts.push(piece);
+            match self.fault {
+                Some(fault) if self.failing_cuts > 0 => {
+                    self.failing_cuts -= 1;
+                    Err(fault)
+                }
+                _ => Ok(()),
+            }
+        }
+
+        fn cut_scrap(&mut self) -> Result<(), Fault> {
+            self.cuts.push(0);
+            Ok(())
+        }
+    }
+
+    impl Feeder for MockMachine {
+        fn feed(&mut self, length: u32) -> Result<Fed, Fault> {
+            let count = self.feeds.iter().filter(|&&feed| feed > 0).count();
+            self.feeds.push(length as i64);
+            match self.short_feed {
+                Some((feed, left_mils)) if feed == count => Ok(Fed::Short { left_mils }),
+                _ => Ok(Fed::All),
+            }
+        }
+
+        fn feed_rest(&mut self, length_mils: u32) -> Result<Fed, Fault> {
+            self.rests.push(length_mils);
+            Ok(Fed::All)
+        }
+
+        fn feed_scrap(&mut self, length_mils: u32) -> Result<(), Fault> {
+            self.feeds.push(-(length_mils as i64));
+            Ok(())
+        }
+    }
+
+    impl Display for MockMachine {
+        fn show_progress(&mut self, progress: Progress) {
+            self.shown.push(progress);
+        }
+    }
+
+    fn run(engine: &mut Engine, machine: &mut MockMachine) -> State {
+        while engine.step(machine) == State::Running {}
+        engine.state()
+    }
+
+    #[test]
+    fn cuts_and_feeds_every_piece_in_order() {
+        let mut engine = Engine::new(Job::new(1200, 3), 0);
+        let mut machine = MockMachine::default();
+
+        assert_eq!(run(&mut engine, &mut machine), State::Finished);
+        assert_eq!(machine.cuts, [1, 2, 3]);
+        assert_eq!(machine.feeds, [1200; 3]);
+        assert_eq!(machine.shown.last(), Some(&engine.progress()));
+        assert_eq!(engine.progress().percent(), 100);
+    }
+
+    #[test]
+    fn scrap_is_trimmed_either_side() {
+        let scrap = Scrap {
+            leader_mils: 3000,
+            trailer_mils: 2000,
+        };
+        let mut engine = Engine::new(Job::new(1200, 2), 0).with_scrap(scrap);
+        let mut machine = MockMachine::default();
+
+        assert_eq!(run(&mut engine, &mut machine), State::Finished);
+        assert_eq!(machine.cuts, [0, 1, 2, 0]);
+        assert_eq!(machine.feeds, [-3000, 1200, 1200, -2000]);
+        assert_eq!(engine.progress().piece, 2);
+
+        // Just a trailer, on a single piece
+        let scrap = Scrap {
+            leader_mils: 0,
+            trailer_mils: 2000,
+        };
+        let mut engine = Engine::new(Job::new(1200, 1), 0).with_scrap(scrap);
+        let mut machine = MockMachine::default();
+
+        assert_eq!(run(&mut engine, &mut machine), State::Finished);
+        assert_eq!(machine.cuts, [1, 0]);
+        assert_eq!(machine.feeds, [-2000]);
+    }
+
+    #[test]
+    fn empty_jobs_are_already_finished() {
+        let mut engine = Engine::new(Job::new(1200, 0), 0);
+        let mut machine = MockMachine::default();
+
+        assert_eq!(engine.step(&mut machine), State::Finished);
+        assert!(machine.cuts.is_empty());
+    }
+
+    #[test]
+    fn progress_rounds_down() {
+        let progress = Progress {
+            piece: 1,
+            num_cuts: 3,
+        };
+        assert_eq!(progress.percent(), 33);
+        assert_eq!(progress.remaining(), 2);
+    }
+
+    #[test]
+    fn retryable_faults_are_retried() {
+        let mut engine = Engine::new(Job::new(1200, 2), 2);
+        let mut machine = MockMachine {
+            failing_cuts: 2,
+            fault: Some(JAM),
+            ..Default::default()
+        };
+
+        assert_eq!(run(&mut engine, &mut machine), State::Finished);
+        assert_eq!(machine.cuts, [1, 1, 1, 2]);
+        assert_eq!(machine.feeds.len(), 2);
+        assert_eq!(engine.retries(), 2);
+    }
+
+    #[test]
+    fn jams_once_retries_run_out() {
+        let mut engine = Engine::new(Job::new(1200, 2), 1);
+        let mut machine = MockMachine {
+            failing_cuts: 2,
+            fault: Some(JAM),
+            ..Default::default()
+        };
+
+        assert_eq!(run(&mut engine, &mut machine), State::Jammed(JAM));
+        assert_eq!(machine.cuts, [1, 1]);
+        assert!(machine.feeds.is_empty());
+        assert_eq!(engine.progress().piece, 0);
+        assert_eq!(engine.scrapped(), 1);
+    }
+
+    #[test]
+    fn clearable_faults_stop_without_retries() {
+        let mut engine = Engine::new(Job::new(1200, 2), 3);
+        let mut machine = MockMachine {
+            failing_cuts: 1,
+            fault: Some(ESTOP),
+            ..Default::default()
+        };
+
+        assert_eq!(engine.step(&mut machine), State::Jammed(ESTOP));
+        assert_eq!(machine.cuts, [1]);
+        assert_eq!(engine.retries(), 0);
+
+        // Without replacements, the piece it scrapped goes missing from the job
+        engine.resume();
+        assert_eq!(run(&mut engine, &mut machine), State::Finished);
+        assert_eq!(machine.cuts, [1, 1]);
+        assert_eq!(engine.progress().piece, 1);
+    }
+
+    #[test]
+    fn scrapped_pieces_are_replaced_after_a_jam() {
+        let mut engine = Engine::new(Job::new(1200, 3), 0).with_replacements(true);
+        let mut machine = MockMachine::default();
+
+        engine.step(&mut machine);
+        machine.failing_cuts = 1;
+        machine.fault = Some(JAM);
+        assert_eq!(engine.step(&mut machine), State::Jammed(JAM));
+
+        // The wire is fed afresh once the jam is cleared, and the job still delivers every piece
+        engine.resume();
+        assert_eq!(run(&mut engine, &mut machine), State::Finished);
+        assert_eq!(machine.cuts, [1, 2, 2, 3]);
+        assert_eq!(machine.feeds, [1200, 1200, 1200, 1200]);
+        assert_eq!(engine.progress().piece, 3);
+        assert_eq!(engine.progress().percent(), 100);
+        assert_eq!(engine.scrapped(), 1);
+    }
+
+    #[test]
+    fn scrapped_pieces_go_missing_without_replacements() {
+        let mut engine = Engine::new(Job::new(1200, 3), 0);
+        let mut machine = MockMachine {
+            failing_cuts: 1,
+            fault: Some(JAM),
+            ..Default::default()
+        };
+
+        assert_eq!(engine.step(&mut machine), State::Jammed(JAM));
+        engine.resume();
+        assert_eq!(run(&mut engine, &mut machine), State::Finished);
+        assert_eq!(machine.cuts, [1, 1, 2]);
+        assert_eq!(
+            engine.progress(),
+            Progress {
+                piece: 2,
+                num_cuts: 2,
+            }
+        );
+
+        // Scrapping the last piece leaves nothing to resume
+        let mut engine = Engine::new(Job::new(1200, 1), 0);
+        let mut machine = MockMachine {
+            failing_cuts: 1,
+            fault: Some(JAM),
+            ..Default::default()
+        };
+        assert_eq!(engine.step(&mut machine), State::Jammed(JAM));
+        engine.resume();
+        assert_eq!(engine.step(&mut machine), State::Finished);
+        assert_eq!(machine.cuts, [1]);
+    }
+
+    #[test]
+    fn other_faults_abort_straight_away() {
+        let mut engine = Engine::new(Job::new(1200, 2), 3);
+        let mut machine = MockMachine {
+            failing_cuts: 1,
+            fault: Some(DOOR),
+            ..Default::default()
+        };
+
+        assert_eq!(run(&mut engine, &mut machine), State::Aborted(DOOR));
+        assert_eq!(machine.cuts, [1]);
+        // The piece under the blade is lost
+        assert_eq!(engine.scrapped(), 1);
+    }
+
+    #[test]
+    fn paused_jobs_wait_between_pieces() {
+        let mut engine = Engine::new(Job::new(1200, 2), 0);
+        let mut machine = MockMachine::default();
+
+        engine.step(&mut machine);
+        engine.pause();
+        assert_eq!(engine.step(&mut machine), State::Paused);
+        assert_eq!(machine.cuts, [1]);
+
+        engine.resume();
+        assert_eq!(run(&mut engine, &mut machine), State::Finished);
+        assert_eq!(machine.cuts, [1, 2]);
+    }
+
+    #[test]
+    fn feeds_stopped_short_are_finished_on_resuming() {
+        // The feed for the second piece, made after cutting the first, which still needs marking
+        let mut engine = Engine::new(Job::new(1200, 3), 0).with_marking(1);
+        let mut machine = MockMachine {
+            short_feed: Some((0, 500)),
+            ..Default::default()
+        };
+
+        assert_eq!(engine.step(&mut machine), State::Paused);
+        assert_eq!(machine.cuts, [1]);
+        assert_eq!(engine.progress().piece, 1);
+        engine.resume();
+        assert_eq!(engine.step(&mut machine), State::Marking);
+        assert_eq!(machine.rests, [500]);
+        assert_eq!(machine.cuts, [1]);
+
+        engine.resume();
+        while engine.step(&mut machine) == State::Marking {
+            engine.resume();
+        }
+        assert_eq!(engine.state(), State::Finished);
+        assert_eq!(machine.cuts, [1, 2, 3]);
+        assert_eq!(engine.scrapped(), 0);
+
+        // The feed for the first piece, after the leader is trimmed off, which is cut once finished
+        let scrap = Scrap {
+            leader_mils: 3000,
+            trailer_mils: 0,
+        };
+        let mut engine = Engine::new(Job::new(1200, 2), 0).with_scrap(scrap);
+        let mut machine = MockMachine {
+            short_feed: Some((0, 700)),
+            ..Default::default()
+        };
+
+        assert_eq!(engine.step(&mut machine), State::Paused);
+        assert_eq!(machine.cuts, [0]);
+        assert_eq!(engine.progress().piece, 0);
+        engine.resume();
+        assert_eq!(run(&mut engine, &mut machine), State::Finished);
+        assert_eq!(machine.rests, [700]);
+        assert_eq!(machine.cuts, [0, 1, 2]);
+        assert_eq!(machine.feeds, [-3000, 1200, 1200, 1200]);
+    }
+
+    #[test]
+    fn marking_stops_every_nth_piece_but_the_last() {
+        let mut engine = Engine::new(Job::new(1200, 6), 0).with_marking(3);
+        let mut machine = MockMachine::default();
+
+        assert_eq!(run(&mut engine, &mut machine), State::Marking);
+        assert_eq!(machine.cuts, [1, 2, 3]);
+        assert_eq!(engine.step(&mut machine), State::Marking);
+        assert_eq!(machine.cuts.len(), 3);
+
+        engine.resume();
+        assert_eq!(run(&mut engine, &mut machine), State::Finished);
+        assert_eq!(machine.cuts, [1, 2, 3, 4, 5, 6]);
+    }
+
+    #[test]
+    fn bundles_stop_after_their_last_piece() {
+        let job = Job::new(1200, 5).with_bundles(2);
+        let mut engine = Engine::new(job, 0).with_marking(1);
+        let mut machine = MockMachine::default();
+        let mut buf = [0; BUNDLE_LABEL_LEN];
+        assert_eq!(engine.bundle().unwrap().label(&mut buf), "BUNDLE 1 OF 3");
+
+        assert_eq!(run(&mut engine, &mut machine), State::Marking);
+        engine.resume();
+        // A bundle's stop stands in for marking its last piece
+        assert_eq!(run(&mut engine, &mut machine), State::Bundled);
+        assert_eq!(machine.cuts, [1, 2]);
+        assert_eq!(
+            engine.bundle(),
+            Some(Bundle {
+                number: 1,
+                count: 3
+            })
+        );
+
+        engine.resume();
+        assert_eq!(
+            engine.bundle(),
+            Some(Bundle {
+                number: 2,
+                count: 3
+            })
+        );
+        engine.step(&mut machine);
+        engine.resume();
+        assert_eq!(run(&mut engine, &mut machine), State::Bundled);
+        engine.resume();
+
+        // The last bundle can be short, and finishing the job stands in for its stop
+        assert_eq!(run(&mut engine, &mut machine), State::Finished);
+        assert_eq!(machine.cuts, [1, 2, 3, 4, 5]);
+        assert_eq!(
+            engine.bundle(),
+            Some(Bundle {
+                number: 3,
+                count: 3
+            })
+        );
+        assert_eq!(Engine::new(Job::new(1200, 5), 0).bundle(), None);
+    }
+
+    #[test]
+    fn first_piece_is_checked_once_fed() {
+        let mut engine = Engine::new(Job::new(1200, 3), 0).with_first_piece_check(true);
+        let mut machine = MockMachine::default();
+
+        assert_eq!(run(&mut engine, &mut machine), State::Checking);
+        assert_eq!(machine.cuts, [1]);
+        assert_eq!(machine.feeds, [1200]);
+
+        engine.resume();
+        assert_eq!(run(&mut engine, &mut machine), State::Finished);
+        assert_eq!(machine.cuts, [1, 2, 3]);
+
+        // A rejected length ends the job there
+        let mut engine = Engine::new(Job::new(1200, 3), 0).with_first_piece_check(true);
+        run(&mut engine, &mut machine);
+        engine.abort(DOOR);
+        assert_eq!(engine.step(&mut machine), State::Aborted(DOOR));
+
+        // A job of one has nothing left to check it for
+        let mut engine = Engine::new(Job::new(1200, 1), 0).with_first_piece_check(true);
+        assert_eq!(run(&mut engine, &mut machine), State::Finished);
+    }
+
+    #[test]
+    fn first_piece_check_comes_before_other_stops() {
+        let job = Job::new(1200, 4).with_bundles(1);
+        let mut engine = Engine::new(job, 0).with_first_piece_check(true);
+        let mut machine = MockMachine::default();
+
+        assert_eq!(run(&mut engine, &mut machine), State::Checking);
+        engine.resume();
+        assert_eq!(engine.state(), State::Bundled);
+        assert_eq!(engine.bundle().unwrap().number, 1);
+
+        // A first piece lost to a jam leaves the check for the next one
+        let mut engine = Engine::new(Job::new(1200, 3), 0).with_first_piece_check(true);
+        let mut machine = MockMachine {
+            failing_cuts: 1,
+            fault: Some(JAM),
+            ..Default::default()
+        };
+        assert_eq!(engine.step(&mut machine), State::Jammed(JAM));
+        engine.resume();
+        assert_eq!(run(&mut engine, &mut machine), State::Checking);
+        assert_eq!(machine.cuts, [1, 1]);
+    }
+
+    #[test]
+    fn abort_stops_the_job_but_not_a_finished_one() {
+        let mut engine = Engine::new(Job::new(1200, 2), 0);
+        let mut machine = MockMachine::default();
+
+        engine.step(&mut machine);
+        engine.abort(DOOR);
+        assert_eq!(engine.step(&mut machine), State::Aborted(DOOR));
+        assert_eq!(machine.cuts, [1]);
+
+        let mut engine = Engine::new(Job::new(1200, 1), 0);
+        run(&mut engine, &mut machine);
+        engine.abort(DOOR);
+        assert_eq!(engine.state(), State::Finished);
+    }
+}
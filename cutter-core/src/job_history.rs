@@ -0,0 +1,316 @@
+/* * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * *\
+Copyright (C) 2023 CJ McAllister
+    This program is free software; you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation; either version 3 of the License, or
+    (at your option) any later version.
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+    You should have received a copy of the GNU General Public License
+    along with this program; if not, write to the Free Software Foundation,
+    Inc., 51 Franklin Street, Fifth Floor, Boston, MA 02110-1301  USA
+
+\* * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * */
+
+// History of finished jobs kept in flash, exported over serial as CSV for shop spreadsheets to track
+// wire use without copying counts off the LCD. Records go into a ring of pages laid out as the
+// journal's. Each also carries the machine's lifetime totals up to and including its job, so the totals
+// outlive the oldest page being erased, and the newest record's are the machine's own.
+
+use core::{convert::TryInto, fmt};
+
+use crate::journal::{HEADER_LEN, PAGE_LEN};
+use crate::label::Date;
+use crate::profiles::Units;
+use crate::queue::{Job, MAX_LABEL_LEN};
+use crate::sound;
+use crate::units::{Length, Rounding, Unit};
+
+///////////////////////////////////////////////////////////////////////////////
+//  Named Constants
+///////////////////////////////////////////////////////////////////////////////
+
+pub const RECORD_LEN: usize = 56;
+pub const RECORDS_PER_PAGE: usize = (PAGE_LEN - HEADER_LEN) / RECORD_LEN;
+
+// The columns of every record's CSV line
+pub const CSV_HEADER: &str = "job,date,time,label,length,units,ordered,cut,scrapped,wire_mm,\
+                              total_pieces,total_scrapped,total_wire_mm";
+
+// Written into every record, so anything else in the page isn't taken for one
+const RECORD_MARKER: u8 = 0x01;
+const NO_MINUTE: u16 = u16::MAX;
+const LABEL_OFFSET: usize = 44;
+
+// Erased flash, where nothing has been written yet
+const ERASED_BYTE: u8 = 0xFF;
+
+///////////////////////////////////////////////////////////////////////////////
+//  Data Structures
+///////////////////////////////////////////////////////////////////////////////
+
+// Everything the machine has done since its history was first kept
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Totals {
+    pub jobs: u32,
+    pub pieces: u32,
+    pub scrapped: u32,
+    pub wire_mm: u32,
+}
+
+// One finished job, whether it delivered its count or halted short of it
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Record {
+    // Left blank if the date or clock hadn't been set
+    pub date: Option<Date>,
+    pub minute_of_day: Option<u16>,
+    pub cut_length: u32,
+    pub units: Units,
+    // Pieces asked for, and those delivered and scrapped
+    pub num_cuts: u32,
+    pub pieces: u32,
+    pub scrapped: u32,
+    // Wire used up, scrap included
+    pub wire_mm: u32,
+    // Including this job
+    pub totals: Totals,
+    label: [u8; MAX_LABEL_LEN],
+}
+
+///////////////////////////////////////////////////////////////////////////////
+//  Object Implementations
+///////////////////////////////////////////////////////////////////////////////
+
+impl Totals {
+    fn with(self, record: &Record) -> Self {
+        Self {
+            jobs: self.jobs.saturating_add(1),
+            pieces: self.pieces.saturating_add(record.pieces),
+            scrapped: self.scrapped.saturating_add(record.scrapped),
+            wire_mm: self.wire_mm.saturating_add(record.wire_mm),
+        }
+    }
+}
+
+impl Record {
+    // A job that's over, having used `wire` to deliver `pieces` and scrap the rest. Its totals are its
+    // own until it's counted after the jobs before it.
+    pub fn new(job: &Job, units: Units, pieces: u32, scrapped: u32, wire: Length) -> Self {
+        let mut label = [0; MAX_LABEL_LEN];
+        label[..job.label().len()].copy_from_slice(job.label().as_bytes());
+        let record = Self {
+            date: None,
+            minute_of_day: None,
+            cut_length: job.cut_length,
+            units,
+            num_cuts: job.num_cuts,
+            pieces,
+            scrapped,
+            wire_mm: wire.to(Unit::Millimeter, Rounding::Nearest),
+            totals: Totals::default(),
+            label,
+        };
+        record.after(Totals::default())
+    }
+
+    // When the job finished, as far as the machine knows
+    pub fn at(self, date: Option<Date>, minute_of_day: Option<u16>) -> Self {
+        Self {
+            date,
+            minute_of_day,
+            ..self
+        }
+    }
+
+    // Counted into the totals of the jobs before it
+    pub fn after(self, totals: Totals) -> Self {
+        Self {
+            totals: totals.with(&self),
+            ..self
+        }
+    }
+
+    pub fn label(&self) -> &str {
+        let len = self
+            .label
+            .iter()
+            .position(|&b| b == 0)
+            .unwrap_or(MAX_LABEL_LEN);
+        // Labels are only ever kept as printable ASCII, as the queue takes them
+        core::str::from_utf8(&self.label[..len]).unwrap_or("")
+    }
+
+    // None for an erased slot, or one holding something other than a record
+    pub fn from_bytes(bytes: &[u8; RECORD_LEN]) -> Option<Self> {
+        if bytes[7] != RECORD_MARKER {
+            return None;
+        }
+
+        let word =
+            |offset: usize| u32::from_le_bytes(bytes[offset..offset + 4].try_into().unwrap());
+        let year = u16::from_le_bytes(bytes[0..2].try_into().unwrap());
+        let minute_of_day = u16::from_le_bytes(bytes[4..6].try_into().unwrap());
+        let mut label = [0; MAX_LABEL_LEN];
+        label.copy_from_slice(&bytes[LABEL_OFFSET..LABEL_OFFSET + MAX_LABEL_LEN]);
+        Some(Self {
+            date: Date::new(year, bytes[2], bytes[3]),
+            minute_of_day: (minute_of_day < sound::MINUTES_PER_DAY).then_some(minute_of_day),
+            cut_length: word(8),
+            units: Units::from(bytes[6]),
+            num_cuts: word(12),
+            pieces: word(16),
+            scrapped: word(20),
+            wire_mm: word(24),
+            totals: Totals {
+                jobs: word(28),
+                pieces: word(32),
+                scrapped: word(36),
+                wire_mm: word(40),
+            },
+            label,
+        })
+    }
+
+    pub fn to_bytes(&self) -> [u8; RECORD_LEN] {
+        let mut bytes = [0; RECORD_LEN];
+        if let Some(date) = self.date {
+            bytes[0..2].copy_from_slice(&date.year.to_le_bytes());
+            bytes[2] = date.month;
+            bytes[3] = date.day;
+        }
+        bytes[4..6].copy_from_slice(&self.minute_of_day.unwrap_or(NO_MINUTE).to_le_bytes());
+        bytes[6] = self.units as u8;
+        bytes[7] = RECORD_MARKER;
+        for (offset, value) in [
+            (8, self.cut_length),
+            (12, self.num_cuts),
+            (16, self.pieces),
+            (20, self.scrapped),
+            (24, self.wire_mm),
+            (28, self.totals.jobs),
+            (32, self.totals.pieces),
+            (36, self.totals.scrapped),
+            (40, self.totals.wire_mm),
+        ] {
+            bytes[offset..offset + 4].copy_from_slice(&value.to_le_bytes());
+        }
+        bytes[LABEL_OFFSET..LABEL_OFFSET + MAX_LABEL_LEN].copy_from_slice(&self.label);
+        bytes
+    }
+
+    // One line under CSV_HEADER, the job numbered by its place in the machine's lifetime
+    pub fn write_csv(&self, out: &mut dyn fmt::Write) -> fmt::Result {
+        write!(out, "{},", self.totals.jobs)?;
+        if let Some(date) = self.date {
+            write!(out, "{}", date)?;
+        }
+        write!(out, ",")?;
+        if let Some(minute_of_day) = self.minute_of_day {
+            write!(out, "{:02}:{:02}", minute_of_day / 60, minute_of_day % 60)?;
+        }
+
+        // Labels are quoted, as they may hold commas, with any quotes in them doubled
+        write!(out, ",\"")?;
+        for part in self.label().split_inclusive('"') {
+            write!(out, "{}", part)?;
+            if part.ends_with('"') {
+                write!(out, "\"")?;
+            }
+        }
+        writeln!(
+            out,
+            "\",{},{},{},{},{},{},{},{},{}",
+            self.cut_length,
+            self.units.label(),
+            self.num_cuts,
+            self.pieces,
+            self.scrapped,
+            self.wire_mm,
+            self.totals.pieces,
+            self.totals.scrapped,
+            self.totals.wire_mm
+        )
+    }
+}
+
+///////////////////////////////////////////////////////////////////////////////
+//  Static Functions
+///////////////////////////////////////////////////////////////////////////////
+
+// Whether a slot is still erased, and so free to be written
+pub fn is_free(slot: &[u8; RECORD_LEN]) -> bool {
+    slot.iter().all(|&b| b == ERASED_BYTE)
+}
+
+pub fn slot_offset(slot: usize) -> usize {
+    HEADER_LEN + slot * RECORD_LEN
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record() -> Record {
+        let job = Job::new(12, 100).with_label("PANEL \"A\",2");
+        let wire = Units::Inches.length(12).times(102);
+        Record::new(&job, Units::Inches, 98, 4, wire)
+    }
+
+    #[test]
+    fn records_round_trip() {
+        let record = record()
+            .at(Date::new(2025, 3, 4), Some(14 * 60 + 5))
+            .after(Totals {
+                jobs: 11,
+                pieces: 5000,
+                scrapped: 20,
+                wire_mm: 1_000_000,
+            });
+        assert_eq!(Record::from_bytes(&record.to_bytes()), Some(record));
+        assert_eq!(record.wire_mm, 31_090);
+        assert_eq!(
+            record.totals,
+            Totals {
+                jobs: 12,
+                pieces: 5098,
+                scrapped: 24,
+                wire_mm: 1_031_090,
+            }
+        );
+
+        // Before the date or clock were set
+        let record = self::record();
+        assert_eq!(Record::from_bytes(&record.to_bytes()), Some(record));
+        assert_eq!(record.totals.jobs, 1);
+
+        let erased = [ERASED_BYTE; RECORD_LEN];
+        assert!(is_free(&erased));
+        assert!(Record::from_bytes(&erased).is_none());
+        assert!(!is_free(&record.to_bytes()));
+    }
+
+    #[test]
+    fn records_are_csv_lines() {
+        let mut line = String::new();
+        record()
+            .at(Date::new(2025, 3, 4), Some(9 * 60 + 5))
+            .write_csv(&mut line)
+            .unwrap();
+        assert_eq!(
+            line,
+            "1,2025-03-04,09:05,\"PANEL \"\"A\"\",2\",12,in,100,98,4,31090,98,4,31090\n"
+        );
+
+        let mut line = String::new();
+        let job = Job::new(305, 2);
+        let wire = Units::Millimeters.length(305).times(2);
+        Record::new(&job, Units::Millimeters, 2, 0, wire)
+            .write_csv(&mut line)
+            .unwrap();
+        assert_eq!(line, "1,,,\"\",305,mm,2,2,0,610,2,0,610\n");
+        assert_eq!(CSV_HEADER.split(',').count(), line.split(',').count());
+    }
+}
@@ -0,0 +1,276 @@
+/* * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * *\
+Copyright (C) 2023 CJ McAllister
+    This program is free software; you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation; either version 3 of the License, or
+    (at your option) any later version.
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+    You should have received a copy of the GNU General Public License
+    along with this program; if not, write to the Free Software Foundation,
+    Inc., 51 Franklin Street, Fifth Floor, Boston, MA 02110-1301  USA
+
+\* * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * */
+///////////////////////////////////////////////////////////////////////////////
+
+// Journal of the machine's major events, kept in flash so the story of a field complaint survives the
+// power cycle that usually follows it. The journal is a ring of pages, each a sequence number then
+// fixed-size entries written into erased flash one at a time; once the newest page is full, the oldest
+// is erased and starts over as the newest.
+
+use core::convert::TryInto;
+use core::fmt;
+
+///////////////////////////////////////////////////////////////////////////////
+//  Named Constants
+///////////////////////////////////////////////////////////////////////////////
+
+pub const NUM_PAGES: usize = 2;
+pub const PAGE_LEN: usize = 4096;
+
+pub const HEADER_LEN: usize = 4;
+pub const ENTRY_LEN: usize = 24;
+pub const ENTRIES_PER_PAGE: usize = (PAGE_LEN - HEADER_LEN) / ENTRY_LEN;
+pub const TEXT_LEN: usize = 16;
+
+// Erased flash, where nothing has been written yet
+const ERASED_WORD: u32 = u32::MAX;
+const ERASED_BYTE: u8 = 0xFF;
+
+// POWER.RESETREAS bits, for why the last reset happened
+const RESETREAS_PIN: u32 = 1 << 0;
+const RESETREAS_DOG: u32 = 1 << 1;
+const RESETREAS_SREQ: u32 = 1 << 2;
+const RESETREAS_LOCKUP: u32 = 1 << 3;
+const RESETREAS_OFF: u32 = 1 << 16;
+
+///////////////////////////////////////////////////////////////////////////////
+//  Data Structures
+///////////////////////////////////////////////////////////////////////////////
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Kind {
+    // Power-on or reset; the argument holds RESETREAS and the text its cause
+    Reset = 1,
+    // The argument is the number of cuts, and the text the job's label
+    JobStart = 2,
+    // The argument is the pieces cut, short of the job's count if it halted
+    JobEnd = 3,
+    // The text is the error's message
+    Error = 4,
+}
+
+// One journalled event, timed by the uptime since the last reset
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Entry {
+    pub uptime_s: u32,
+    pub kind: Kind,
+    pub arg: u16,
+    text: [u8; TEXT_LEN],
+}
+
+///////////////////////////////////////////////////////////////////////////////
+//  Object Implementations
+///////////////////////////////////////////////////////////////////////////////
+
+impl Kind {
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Reset => "reset",
+            Self::JobStart => "job_start",
+            Self::JobEnd => "job_end",
+            Self::Error => "error",
+        }
+    }
+
+    fn from_u8(value: u8) -> Option<Self> {
+        match value {
+            1 => Some(Self::Reset),
+            2 => Some(Self::JobStart),
+            3 => Some(Self::JobEnd),
+            4 => Some(Self::Error),
+            _ => None,
+        }
+    }
+}
+
+impl Entry {
+    // Text past TEXT_LEN bytes is cut off
+    pub fn new(uptime_s: u32, kind: Kind, arg: u16, text: &str) -> Self {
+        let mut bytes = [0; TEXT_LEN];
+        let len = text.len().min(TEXT_LEN);
+        bytes[..len].copy_from_slice(&text.as_bytes()[..len]);
+        Self {
+            uptime_s,
+            kind,
+            arg,
+            text: bytes,
+        }
+    }
+
+    pub fn text(&self) -> &str {
+        let len = self.text.iter().position(|&b| b == 0).unwrap_or(TEXT_LEN);
+        // A cut that split a character leaves only the whole ones
+        match core::str::from_utf8(&self.text[..len]) {
+            Ok(text) => text,
+            Err(e) => core::str::from_utf8(&self.text[..e.valid_up_to()]).unwrap(),
+        }
+    }
+
+    // None for an erased slot, or one holding something other than an entry
+    pub fn from_bytes(bytes: &[u8; ENTRY_LEN]) -> Option<Self> {
+        Some(Self {
+            uptime_s: u32::from_le_bytes(bytes[0..4].try_into().unwrap()),
+            kind: Kind::from_u8(bytes[4])?,
+            arg: u16::from_le_bytes(bytes[6..8].try_into().unwrap()),
+            text: bytes[8..ENTRY_LEN].try_into().unwrap(),
+        })
+    }
+
+    pub fn to_bytes(&self) -> [u8; ENTRY_LEN] {
+        let mut bytes = [0; ENTRY_LEN];
+        bytes[0..4].copy_from_slice(&self.uptime_s.to_le_bytes());
+        bytes[4] = self.kind as u8;
+        bytes[6..8].copy_from_slice(&self.arg.to_le_bytes());
+        bytes[8..ENTRY_LEN].copy_from_slice(&self.text);
+        bytes
+    }
+}
+
+// `uptime_s kind arg text`, as the console prints it
+impl fmt::Display for Entry {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{} {} {} {}",
+            self.uptime_s,
+            self.kind.label(),
+            self.arg,
+            self.text()
+        )
+    }
+}
+
+///////////////////////////////////////////////////////////////////////////////
+//  Static Functions
+///////////////////////////////////////////////////////////////////////////////
+
+// A page's sequence number from its header, or None if it has never been started
+pub fn sequence(header: &[u8; HEADER_LEN]) -> Option<u32> {
+    let sequence = u32::from_le_bytes(*header);
+    (sequence != ERASED_WORD).then_some(sequence)
+}
+
+pub fn header(sequence: u32) -> [u8; HEADER_LEN] {
+    sequence.to_le_bytes()
+}
+
+// Whether a slot is still erased, and so free to be written
+pub fn is_free(slot: &[u8; ENTRY_LEN]) -> bool {
+    slot.iter().all(|&b| b == ERASED_BYTE)
+}
+
+pub fn slot_offset(slot: usize) -> usize {
+    HEADER_LEN + slot * ENTRY_LEN
+}
+
+// Pages from oldest to newest by their sequence numbers, leaving out any never started
+pub fn page_order(sequences: [Option<u32>; NUM_PAGES]) -> ([usize; NUM_PAGES], usize) {
+    let mut order = [0; NUM_PAGES];
+    let mut len = 0;
+    for (page, sequence) in sequences.iter().enumerate() {
+        if sequence.is_some() {
+            order[len] = page;
+            len += 1;
+        }
+    }
+    order[..len].sort_unstable_by_key(|&page| sequences[page]);
+    (order, len)
+}
+
+// The page after the newest, to be erased and started over once the newest fills, with its sequence
+pub fn next_page(sequences: [Option<u32>; NUM_PAGES]) -> (usize, u32) {
+    let (order, len) = page_order(sequences);
+    match len {
+        0 => (0, 0),
+        _ => {
+            let newest = order[len - 1];
+            (
+                (newest + 1) % NUM_PAGES,
+                sequences[newest].unwrap().wrapping_add(1),
+            )
+        }
+    }
+}
+
+// Why the machine last reset, from POWER.RESETREAS
+pub fn reset_cause(resetreas: u32) -> &'static str {
+    if resetreas & RESETREAS_DOG != 0 {
+        "WATCHDOG"
+    } else if resetreas & RESETREAS_LOCKUP != 0 {
+        "LOCKUP"
+    } else if resetreas & RESETREAS_SREQ != 0 {
+        "SOFT RESET"
+    } else if resetreas & RESETREAS_PIN != 0 {
+        "RESET PIN"
+    } else if resetreas & RESETREAS_OFF != 0 {
+        "WOKE FROM OFF"
+    } else if resetreas == 0 {
+        "POWER ON"
+    } else {
+        "OTHER"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn entries_round_trip() {
+        let entry = Entry::new(3600, Kind::JobStart, 250, "PANEL HARNESS 12");
+        let decoded = Entry::from_bytes(&entry.to_bytes()).unwrap();
+        assert_eq!(decoded, entry);
+        assert_eq!(decoded.text(), "PANEL HARNESS 12");
+        assert_eq!(decoded.to_string(), "3600 job_start 250 PANEL HARNESS 12");
+
+        let long = Entry::new(0, Kind::Error, 0, "A MESSAGE TOO LONG TO KEEP");
+        assert_eq!(long.text(), "A MESSAGE TOO LO");
+        // Cut through the middle of a two-byte character
+        let split = Entry::new(0, Kind::Error, 0, "ABCDEFGHIJKLMNO\u{e9}");
+        assert_eq!(split.text(), "ABCDEFGHIJKLMNO");
+
+        let erased = [ERASED_BYTE; ENTRY_LEN];
+        assert!(is_free(&erased));
+        assert!(Entry::from_bytes(&erased).is_none());
+        assert!(!is_free(&entry.to_bytes()));
+    }
+
+    #[test]
+    fn pages_ring_by_sequence() {
+        assert_eq!(page_order([None, None]).1, 0);
+        assert_eq!(next_page([None, None]), (0, 0));
+
+        assert_eq!(page_order([Some(0), None]), ([0, 0], 1));
+        assert_eq!(next_page([Some(0), None]), (1, 1));
+
+        // Page 0 started over after page 1 filled, so it's now the newest
+        assert_eq!(page_order([Some(2), Some(1)]), ([1, 0], 2));
+        assert_eq!(next_page([Some(2), Some(1)]), (1, 3));
+
+        assert_eq!(sequence(&header(7)), Some(7));
+        assert_eq!(sequence(&[ERASED_BYTE; HEADER_LEN]), None);
+    }
+
+    #[test]
+    fn reset_causes() {
+        assert_eq!(reset_cause(0), "POWER ON");
+        assert_eq!(reset_cause(RESETREAS_PIN), "RESET PIN");
+        assert_eq!(reset_cause(RESETREAS_DOG | RESETREAS_PIN), "WATCHDOG");
+        assert_eq!(reset_cause(RESETREAS_SREQ), "SOFT RESET");
+        assert_eq!(reset_cause(1 << 20), "OTHER");
+    }
+}
@@ -0,0 +1,217 @@
+/* * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * *\
+Copyright (C) 2023 CJ McAllister
+    This program is free software; you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation; either version 3 of the License, or
+    (at your option) any later version.
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+    You should have received a copy of the GNU General Public License
+    along with this program; if not, write to the Free Software Foundation,
+    Inc., 51 Franklin Street, Fifth Floor, Boston, MA 02110-1301  USA
+
+\* * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * */
+///////////////////////////////////////////////////////////////////////////////
+// Labels for the bundle of pieces a finished job leaves, sent over serial for a label printer to print.
+// Each is a few short lines of plain text ending in a form feed, which most serial label and receipt
+// printers take as the end of a label.
+
+use core::{convert::TryInto, fmt};
+
+use crate::profiles::Units;
+
+///////////////////////////////////////////////////////////////////////////////
+//  Named Constants
+///////////////////////////////////////////////////////////////////////////////
+
+const FORM_FEED: char = '\x0C';
+
+const MONTHS_PER_YEAR: u8 = 12;
+pub const MIN_YEAR: u16 = 2000;
+pub const MAX_YEAR: u16 = 2099;
+
+///////////////////////////////////////////////////////////////////////////////
+//  Data Structures
+///////////////////////////////////////////////////////////////////////////////
+
+// A calendar date, as YYYY-MM-DD
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Date {
+    pub year: u16,
+    pub month: u8,
+    pub day: u8,
+}
+
+// What's printed on a bundle's label
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Label<'a> {
+    pub cut_length: u32,
+    pub units: Units,
+    // Pieces in the bundle
+    pub count: u32,
+    // Left off if the date hasn't been set
+    pub date: Option<Date>,
+    // Operator profile the job was cut with
+    pub preset: &'a str,
+    pub job: &'a str,
+}
+
+///////////////////////////////////////////////////////////////////////////////
+//  Object Implementations
+///////////////////////////////////////////////////////////////////////////////
+
+impl Date {
+    pub const FIRST: Self = Self {
+        year: MIN_YEAR,
+        month: 1,
+        day: 1,
+    };
+
+    // None if it isn't a real date, or is outside the years this firmware deals in
+    pub fn new(year: u16, month: u8, day: u8) -> Option<Self> {
+        let date = Self { year, month, day };
+        let valid = (MIN_YEAR..=MAX_YEAR).contains(&year)
+            && (1..=MONTHS_PER_YEAR).contains(&month)
+            && (1..=date.days_in_month()).contains(&day);
+        valid.then_some(date)
+    }
+
+    // Parse "YYYY-MM-DD"
+    pub fn parse(text: &str) -> Option<Self> {
+        let mut fields = text.split('-');
+        let (year, month, day) = (fields.next()?, fields.next()?, fields.next()?);
+        if fields.next().is_some() || year.len() != 4 || month.len() != 2 || day.len() != 2 {
+            return None;
+        }
+
+        Self::new(year.parse().ok()?, month.parse().ok()?, day.parse().ok()?)
+    }
+
+    // The same day in another year, or the end of February for the 29th in a year without one
+    pub fn with_year(self, year: u16) -> Option<Self> {
+        Self::new(year, self.month, self.day).or_else(|| Self::new(year, self.month, self.day - 1))
+    }
+
+    // The given month and day, as MMDD, in the same year
+    pub fn with_mmdd(self, mmdd: u32) -> Option<Self> {
+        Self::new(self.year, (mmdd / 100).try_into().ok()?, (mmdd % 100) as u8)
+    }
+
+    pub fn mmdd(self) -> u32 {
+        self.month as u32 * 100 + self.day as u32
+    }
+
+    // The date the given number of days later
+    pub fn plus_days(self, days: u32) -> Self {
+        let mut date = self;
+        for _ in 0..days {
+            date.day += 1;
+            if date.day > date.days_in_month() {
+                date.day = 1;
+                date.month += 1;
+                if date.month > MONTHS_PER_YEAR {
+                    date.month = 1;
+                    date.year += 1;
+                }
+            }
+        }
+
+        date
+    }
+
+    fn days_in_month(self) -> u8 {
+        match self.month {
+            2 if self.is_leap_year() => 29,
+            2 => 28,
+            4 | 6 | 9 | 11 => 30,
+            _ => 31,
+        }
+    }
+
+    fn is_leap_year(self) -> bool {
+        self.year.is_multiple_of(4)
+            && (!self.year.is_multiple_of(100) || self.year.is_multiple_of(400))
+    }
+}
+
+impl fmt::Display for Date {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:04}-{:02}-{:02}", self.year, self.month, self.day)
+    }
+}
+
+impl Label<'_> {
+    pub fn write(&self, out: &mut dyn fmt::Write) -> fmt::Result {
+        writeln!(
+            out,
+            "{} {} x {}",
+            self.cut_length,
+            self.units.label(),
+            self.count
+        )?;
+        if let Some(date) = self.date {
+            writeln!(out, "{}", date)?;
+        }
+        writeln!(out, "{}", self.preset)?;
+        if !self.job.is_empty() {
+            writeln!(out, "{}", self.job)?;
+        }
+        write!(out, "{}", FORM_FEED)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn dates_parse_only_when_real() {
+        let date = Date::parse("2024-02-29").unwrap();
+        assert_eq!(date.to_string(), "2024-02-29");
+        assert_eq!(Date::parse("2023-02-29"), None);
+        assert_eq!(Date::parse("2024-13-01"), None);
+        assert_eq!(Date::parse("2024-1-01"), None);
+        assert_eq!(Date::parse("2024-01-01-01"), None);
+        assert_eq!(Date::parse("today"), None);
+
+        assert_eq!(date.with_year(2023), Date::new(2023, 2, 28));
+        assert_eq!(date.with_mmdd(1231), Date::new(2024, 12, 31));
+        assert_eq!(date.with_mmdd(230), None);
+        assert_eq!(date.mmdd(), 229);
+    }
+
+    #[test]
+    fn days_roll_over_months_and_years() {
+        let date = Date::parse("2024-02-28").unwrap();
+        assert_eq!(date.plus_days(1).to_string(), "2024-02-29");
+        assert_eq!(date.plus_days(2).to_string(), "2024-03-01");
+        assert_eq!(
+            Date::parse("2023-12-31").unwrap().plus_days(1).to_string(),
+            "2024-01-01"
+        );
+    }
+
+    #[test]
+    fn label_lines() {
+        let mut label = Label {
+            cut_length: 12,
+            units: Units::Inches,
+            count: 50,
+            date: Date::parse("2024-06-03"),
+            preset: "OPERATOR 2",
+            job: "HARNESS A",
+        };
+        let mut out = String::new();
+        label.write(&mut out).unwrap();
+        assert_eq!(out, "12 in x 50\n2024-06-03\nOPERATOR 2\nHARNESS A\n\x0C");
+
+        label.date = None;
+        label.job = "";
+        out.clear();
+        label.write(&mut out).unwrap();
+        assert_eq!(out, "12 in x 50\nOPERATOR 2\n\x0C");
+    }
+}
@@ -0,0 +1,163 @@
+/* * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * *\
+Copyright (C) 2023 CJ McAllister
+    This program is free software; you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation; either version 3 of the License, or
+    (at your option) any later version.
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+    You should have received a copy of the GNU General Public License
+    along with this program; if not, write to the Free Software Foundation,
+    Inc., 51 Franklin Street, Fifth Floor, Boston, MA 02110-1301  USA
+
+\* * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * */
+///////////////////////////////////////////////////////////////////////////////
+
+// Worst-case latencies of the paths that keep the machine safe, measured while it runs, against the
+// bounds the design relies on. All times are in microseconds.
+
+use core::fmt;
+
+///////////////////////////////////////////////////////////////////////////////
+//  Data Structures
+///////////////////////////////////////////////////////////////////////////////
+
+// The longest of a latency measured so far
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Worst {
+    pub worst_us: u32,
+    pub samples: u32,
+}
+
+// Bounds the latencies have to stay within
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Limits {
+    pub cut_to_pwm_us: u32,
+    pub estop_to_safe_us: u32,
+}
+
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Audit {
+    // From a cut stroke falling due to the servo's PWM sequence being restarted with it
+    pub cut_to_pwm: Worst,
+    // Between one look at the inputs and the next while a cycle runs, the longest an e-stop can go unseen
+    pub input_gap: Worst,
+    // From a tripped input being seen to the machine being left safe
+    pub trip_to_safe: Worst,
+}
+
+///////////////////////////////////////////////////////////////////////////////
+//  Object Implementations
+///////////////////////////////////////////////////////////////////////////////
+
+impl Worst {
+    pub const NONE: Self = Self {
+        worst_us: 0,
+        samples: 0,
+    };
+
+    pub fn record(&mut self, latency_us: u32) {
+        self.worst_us = self.worst_us.max(latency_us);
+        self.samples = self.samples.saturating_add(1);
+    }
+}
+
+impl Audit {
+    pub const fn new() -> Self {
+        Self {
+            cut_to_pwm: Worst::NONE,
+            input_gap: Worst::NONE,
+            trip_to_safe: Worst::NONE,
+        }
+    }
+
+    // Worst case from an e-stop opening to the machine being safe: it may open just after the inputs
+    // were looked at, and go unseen for the longest gap, then take the longest seen to act on
+    pub fn estop_to_safe_us(&self) -> u32 {
+        self.input_gap
+            .worst_us
+            .saturating_add(self.trip_to_safe.worst_us)
+    }
+
+    // Whether every latency measured is within its bound. Nothing measured is nothing shown to be late.
+    pub fn meets(&self, limits: &Limits) -> bool {
+        self.cut_to_pwm.worst_us <= limits.cut_to_pwm_us
+            && self.estop_to_safe_us() <= limits.estop_to_safe_us
+    }
+
+    // `key value` lines, as the console's dump prints them
+    pub fn write_report(&self, limits: &Limits, out: &mut dyn fmt::Write) -> fmt::Result {
+        writeln!(
+            out,
+            "latency_cut_to_pwm_us {} of {} over {} cuts",
+            self.cut_to_pwm.worst_us, limits.cut_to_pwm_us, self.cut_to_pwm.samples
+        )?;
+        writeln!(
+            out,
+            "latency_estop_to_safe_us {} of {} ({} unseen, {} to act over {} trips)",
+            self.estop_to_safe_us(),
+            limits.estop_to_safe_us,
+            self.input_gap.worst_us,
+            self.trip_to_safe.worst_us,
+            self.trip_to_safe.samples
+        )?;
+        writeln!(
+            out,
+            "latency_bounds {}",
+            if self.meets(limits) {
+                "met"
+            } else {
+                "EXCEEDED"
+            }
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LIMITS: Limits = Limits {
+        cut_to_pwm_us: 1_000,
+        estop_to_safe_us: 50_000,
+    };
+
+    #[test]
+    fn estop_bound_counts_the_gap_and_the_reaction() {
+        let mut audit = Audit::new();
+        assert!(audit.meets(&LIMITS));
+
+        audit.cut_to_pwm.record(40);
+        audit.cut_to_pwm.record(120);
+        audit.input_gap.record(10_500);
+        audit.trip_to_safe.record(30_000);
+        assert_eq!(
+            audit.cut_to_pwm,
+            Worst {
+                worst_us: 120,
+                samples: 2
+            }
+        );
+        assert_eq!(audit.estop_to_safe_us(), 40_500);
+        assert!(audit.meets(&LIMITS));
+
+        // e.g. the blade's dwell, with nobody watching the inputs
+        audit.input_gap.record(400_000);
+        assert!(!audit.meets(&LIMITS));
+    }
+
+    #[test]
+    fn report_says_whether_bounds_are_met() {
+        let mut audit = Audit::new();
+        audit.cut_to_pwm.record(2_000);
+        let mut report = String::new();
+        audit.write_report(&LIMITS, &mut report).unwrap();
+        assert!(report.starts_with("latency_cut_to_pwm_us 2000 of 1000 over 1 cuts\n"));
+        assert!(report.ends_with("latency_bounds EXCEEDED\n"));
+    }
+}
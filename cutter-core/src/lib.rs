@@ -0,0 +1,60 @@
+/* * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * *\
+Copyright (C) 2023 CJ McAllister
+    This program is free software; you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation; either version 3 of the License, or
+    (at your option) any later version.
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+    You should have received a copy of the GNU General Public License
+    along with this program; if not, write to the Free Software Foundation,
+    Inc., 51 Franklin Street, Fifth Floor, Boston, MA 02110-1301  USA
+
+\* * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * */
+
+// Hardware-independent logic shared with the firmware. Builds for the host as well as the target, so
+// it can be tested without flashing:
+//   cargo test -p cutter-core --target x86_64-unknown-linux-gnu
+#![cfg_attr(not(test), no_std)]
+
+pub mod big_digits;
+pub mod bus_health;
+pub mod charset;
+pub mod console;
+pub mod error_log;
+pub mod eta;
+pub mod events;
+pub mod frame;
+pub mod haptic;
+pub mod help;
+pub mod import;
+pub mod input;
+pub mod inputs;
+pub mod job;
+pub mod job_history;
+pub mod journal;
+pub mod label;
+pub mod latency;
+pub mod matrix_text;
+pub mod motion;
+pub mod numeric;
+pub mod profiles;
+pub mod qa;
+pub mod queue;
+pub mod recovery;
+pub mod replay;
+pub mod settings;
+pub mod settings_menu;
+pub mod sorter;
+pub mod sound;
+pub mod spool;
+pub mod status;
+pub mod summary;
+pub mod thermal;
+pub mod tick_watch;
+pub mod transfer;
+pub mod tuning;
+pub mod units;
+pub mod widgets;
@@ -0,0 +1,175 @@
+/* * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * *\
+Copyright (C) 2023 CJ McAllister
+    This program is free software; you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation; either version 3 of the License, or
+    (at your option) any later version.
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+    You should have received a copy of the GNU General Public License
+    along with this program; if not, write to the Free Software Foundation,
+    Inc., 51 Franklin Street, Fifth Floor, Boston, MA 02110-1301  USA
+
+\* * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * */
+// Text scrolled right to left across the 5x5 LED matrix, for short messages with no LCD to show them
+
+///////////////////////////////////////////////////////////////////////////////
+//  Named Constants
+///////////////////////////////////////////////////////////////////////////////
+
+pub const MATRIX_SIZE: usize = 5;
+const GLYPH_WIDTH: usize = 5;
+// Columns each character takes, including the blank one after it
+const GLYPH_PITCH: usize = GLYPH_WIDTH + 1;
+
+// Rows of each glyph top to bottom, with the leftmost column in the highest bit
+type Glyph = [u8; MATRIX_SIZE];
+
+const DIGITS: [Glyph; 10] = [
+    [0b01110, 0b10011, 0b10101, 0b11001, 0b01110],
+    [0b00100, 0b01100, 0b00100, 0b00100, 0b01110],
+    [0b11110, 0b00001, 0b01110, 0b10000, 0b11111],
+    [0b11110, 0b00001, 0b00110, 0b00001, 0b11110],
+    [0b00110, 0b01010, 0b10010, 0b11111, 0b00010],
+    [0b11111, 0b10000, 0b11110, 0b00001, 0b11110],
+    [0b01110, 0b10000, 0b11110, 0b10001, 0b01110],
+    [0b11111, 0b00010, 0b00100, 0b01000, 0b01000],
+    [0b01110, 0b10001, 0b01110, 0b10001, 0b01110],
+    [0b01110, 0b10001, 0b01111, 0b00001, 0b01110],
+];
+const LETTERS: [Glyph; 26] = [
+    [0b01110, 0b10001, 0b11111, 0b10001, 0b10001],
+    [0b11110, 0b10001, 0b11110, 0b10001, 0b11110],
+    [0b01111, 0b10000, 0b10000, 0b10000, 0b01111],
+    [0b11110, 0b10001, 0b10001, 0b10001, 0b11110],
+    [0b11111, 0b10000, 0b11110, 0b10000, 0b11111],
+    [0b11111, 0b10000, 0b11110, 0b10000, 0b10000],
+    [0b01111, 0b10000, 0b10011, 0b10001, 0b01111],
+    [0b10001, 0b10001, 0b11111, 0b10001, 0b10001],
+    [0b11111, 0b00100, 0b00100, 0b00100, 0b11111],
+    [0b00111, 0b00001, 0b00001, 0b10001, 0b01110],
+    [0b10010, 0b10100, 0b11000, 0b10100, 0b10010],
+    [0b10000, 0b10000, 0b10000, 0b10000, 0b11111],
+    [0b10001, 0b11011, 0b10101, 0b10001, 0b10001],
+    [0b10001, 0b11001, 0b10101, 0b10011, 0b10001],
+    [0b01110, 0b10001, 0b10001, 0b10001, 0b01110],
+    [0b11110, 0b10001, 0b11110, 0b10000, 0b10000],
+    [0b01110, 0b10001, 0b10101, 0b10010, 0b01101],
+    [0b11110, 0b10001, 0b11110, 0b10010, 0b10001],
+    [0b01111, 0b10000, 0b01110, 0b00001, 0b11110],
+    [0b11111, 0b00100, 0b00100, 0b00100, 0b00100],
+    [0b10001, 0b10001, 0b10001, 0b10001, 0b01110],
+    [0b10001, 0b10001, 0b10001, 0b01010, 0b00100],
+    [0b10001, 0b10001, 0b10101, 0b11011, 0b10001],
+    [0b10001, 0b01010, 0b00100, 0b01010, 0b10001],
+    [0b10001, 0b01010, 0b00100, 0b00100, 0b00100],
+    [0b11111, 0b00010, 0b00100, 0b01000, 0b11111],
+];
+const SPACE: Glyph = [0; MATRIX_SIZE];
+const DASH: Glyph = [0b00000, 0b00000, 0b01110, 0b00000, 0b00000];
+const BANG: Glyph = [0b00100, 0b00100, 0b00100, 0b00000, 0b00100];
+const DOT: Glyph = [0b00000, 0b00000, 0b00000, 0b00000, 0b00100];
+const COLON: Glyph = [0b00000, 0b00100, 0b00000, 0b00100, 0b00000];
+// Shown for anything the font doesn't have
+const UNKNOWN: Glyph = [0b01110, 0b10001, 0b00110, 0b00000, 0b00100];
+
+///////////////////////////////////////////////////////////////////////////////
+//  Static Functions
+///////////////////////////////////////////////////////////////////////////////
+
+// Frames it takes to scroll the whole message in from the right and off to the left
+pub fn num_frames(text: &str) -> usize {
+    MATRIX_SIZE + text.len() * GLYPH_PITCH
+}
+
+// The matrix after scrolling the message along by `offset` columns, starting from blank
+pub fn frame(text: &str, offset: usize) -> [[u8; MATRIX_SIZE]; MATRIX_SIZE] {
+    let mut image = [[0; MATRIX_SIZE]; MATRIX_SIZE];
+    for x in 0..MATRIX_SIZE {
+        let column = column(text.as_bytes(), offset + x);
+        for (y, row) in image.iter_mut().enumerate() {
+            row[x] = (column >> y) & 1;
+        }
+    }
+
+    image
+}
+
+///////////////////////////////////////////////////////////////////////////////
+//  Helper Functions
+///////////////////////////////////////////////////////////////////////////////
+
+// One column of the message as it runs past, with the top row in the lowest bit. The matrix's width of
+// blank comes first, so the text scrolls in from the edge.
+fn column(text: &[u8], position: usize) -> u8 {
+    if position < MATRIX_SIZE {
+        return 0;
+    }
+    let position = position - MATRIX_SIZE;
+    let x = position % GLYPH_PITCH;
+
+    match text.get(position / GLYPH_PITCH) {
+        Some(&c) if x < GLYPH_WIDTH => {
+            let bit = GLYPH_WIDTH - 1 - x;
+            glyph(c)
+                .iter()
+                .enumerate()
+                .fold(0, |column, (y, row)| column | ((row >> bit) & 1) << y)
+        }
+        _ => 0,
+    }
+}
+
+fn glyph(c: u8) -> Glyph {
+    match c.to_ascii_uppercase() {
+        c @ b'0'..=b'9' => DIGITS[(c - b'0') as usize],
+        c @ b'A'..=b'Z' => LETTERS[(c - b'A') as usize],
+        b' ' => SPACE,
+        b'-' => DASH,
+        b'!' => BANG,
+        b'.' => DOT,
+        b':' => COLON,
+        _ => UNKNOWN,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn text_scrolls_in_from_the_right() {
+        // Blank to start, then the first column of the 'E' enters at the right edge
+        assert_eq!(frame("E03", 0), [[0; MATRIX_SIZE]; MATRIX_SIZE]);
+        let entering = frame("E03", 1);
+        assert!(entering.iter().all(|row| row[MATRIX_SIZE - 1] == 1));
+        assert!(entering.iter().all(|row| row[..MATRIX_SIZE - 1] == [0; 4]));
+
+        // Fully in view, the glyph reads as its rows
+        let e = frame("E03", MATRIX_SIZE);
+        assert_eq!(e[0], [1, 1, 1, 1, 1]);
+        assert_eq!(e[2], [1, 1, 1, 1, 0]);
+    }
+
+    #[test]
+    fn characters_are_spaced_and_run_off_the_end() {
+        // The blank column between characters shows at the left edge, with the next glyph after it
+        let gap = frame("JJ", MATRIX_SIZE + GLYPH_WIDTH);
+        assert!(gap.iter().all(|row| row[0] == 0));
+        assert_eq!(gap[0][1..], [0, 0, 1, 1]);
+
+        assert_eq!(num_frames("JAM"), MATRIX_SIZE + 3 * GLYPH_PITCH);
+        assert_eq!(
+            frame("JAM", num_frames("JAM")),
+            [[0; MATRIX_SIZE]; MATRIX_SIZE]
+        );
+    }
+
+    #[test]
+    fn lowercase_and_unknown_characters_still_show() {
+        assert_eq!(frame("done", MATRIX_SIZE), frame("DONE", MATRIX_SIZE));
+        assert_eq!(glyph(b'#'), UNKNOWN);
+    }
+}
@@ -0,0 +1,736 @@
+/* * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * *\
+Copyright (C) 2023 CJ McAllister
+    This program is free software; you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation; either version 3 of the License, or
+    (at your option) any later version.
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+    You should have received a copy of the GNU General Public License
+    along with this program; if not, write to the Free Software Foundation,
+    Inc., 51 Franklin Street, Fifth Floor, Boston, MA 02110-1301  USA
+
+\* * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * */
+
+///////////////////////////////////////////////////////////////////////////////
+//  Named Constants
+///////////////////////////////////////////////////////////////////////////////
+
+// Enough for a clamped cut cycle with a piece chute, wire straightener and fully shaped cut stroke
+pub const MAX_STEPS: usize = 13;
+
+// Most points a cut stroke can be shaped with, and their size when saved
+pub const MAX_STROKE_POINTS: usize = 4;
+pub const STROKE_LEN: usize = MAX_STROKE_POINTS * 4;
+// Longest a shaped stroke may hold the blade at any one point
+pub const MAX_STROKE_HOLD_MS: u16 = 10_000;
+
+use core::{convert::TryInto, fmt};
+
+use crate::sorter::Bin;
+
+const MS_PER_SECOND: u64 = 1000;
+const US_PER_SECOND: u64 = 1_000_000;
+const MILS_PER_INCH: u64 = 1000;
+
+///////////////////////////////////////////////////////////////////////////////
+//  Data Structures
+///////////////////////////////////////////////////////////////////////////////
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Action {
+    ClampClose,
+    CutClose,
+    // Move the closing blade to the next point of a shaped stroke, in the cutter servo's tenths of a
+    // percent of duty
+    CutStage { position: u16 },
+    CutOpen,
+    ClampOpen,
+    FeedStart { distance_mils: u32 },
+    FeedStop,
+    // Swing the piece chute over a bin, ready for the piece being cut
+    Divert { bin: Bin },
+    StraightenerOn,
+    StraightenerOff,
+}
+
+#[derive(Copy, Clone, Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Step {
+    // Offset from the start of the cycle
+    pub at_ms: u32,
+    pub action: Action,
+}
+
+// Trapezoidal velocity limits for the feed axis
+#[derive(Copy, Clone, Debug)]
+pub struct FeedProfile {
+    pub max_speed_mils_per_s: u32,
+    pub accel_mils_per_s2: u32,
+}
+
+// Timed moves for the servo-driven axes
+#[derive(Copy, Clone, Debug)]
+pub struct CycleTiming {
+    // How long the blade is held closed to complete the cut
+    pub cut_dwell_ms: u32,
+    // Shape of the blade's closing travel, which replaces the closed position and dwell unless plain
+    pub stroke: StrokeProfile,
+    // Time after the blade starts opening until it is clear of the wire path and feeding may begin
+    pub blade_clearance_ms: u32,
+    // Clamp dwells, if a clamp is fitted
+    pub clamp: Option<ClampTiming>,
+}
+
+// Dwells for a wire clamp, which holds the wire still while the blade passes through it
+#[derive(Copy, Clone, Debug)]
+pub struct ClampTiming {
+    // Time for the clamp to grip the wire before cutting
+    pub settle_ms: u32,
+    // Time for the clamp to let go of the wire before feeding
+    pub release_ms: u32,
+}
+
+// Run-on of the straightener/de-reeler motor either side of each feed
+#[derive(Copy, Clone, Debug)]
+pub struct StraightenerTiming {
+    // Head start for the motor to get up to speed before the feed axis pulls on the wire
+    pub pre_roll_ms: u32,
+    // Time the motor keeps running once the feed axis has stopped, to let the wire settle
+    pub post_roll_ms: u32,
+}
+
+// One point of a shaped cut stroke: where the blade is driven, and for how long it's held there
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct StrokePoint {
+    // Cutter servo position, in tenths of a percent of duty like the tuned blade positions
+    pub position: u16,
+    pub hold_ms: u16,
+}
+
+// Piecewise duty-vs-time curve for closing the blade, e.g. a fast approach onto the wire, a slow shear
+// through it and a short hold, before the blade snaps open as usual. A plain stroke has no points, and
+// drives the blade straight to its tuned closed position for the tuned dwell.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct StrokeProfile {
+    points: [StrokePoint; MAX_STROKE_POINTS],
+    len: usize,
+}
+
+// Time-ordered list of actions making up one feed/cut cycle
+#[derive(Copy, Clone, Debug)]
+pub struct Plan {
+    steps: [Step; MAX_STEPS],
+    len: usize,
+}
+
+///////////////////////////////////////////////////////////////////////////////
+//  Object Implementations
+///////////////////////////////////////////////////////////////////////////////
+
+impl FeedProfile {
+    // Scale the cruise speed by a percentage, e.g. an operator's feed speed preference
+    pub fn scaled(self, speed_pct: u32) -> Self {
+        Self {
+            max_speed_mils_per_s: self.max_speed_mils_per_s * speed_pct / 100,
+            ..self
+        }
+    }
+
+    // Time to feed the given distance from rest to rest
+    pub fn duration_ms(&self, distance_mils: u32) -> u32 {
+        if distance_mils == 0 {
+            return 0;
+        }
+
+        let distance = distance_mils as u64;
+        let speed = self.max_speed_mils_per_s as u64;
+        let accel = self.accel_mils_per_s2 as u64;
+
+        // Distance covered accelerating to cruise speed and decelerating back to rest
+        let ramp_mils = speed * speed / accel;
+
+        let duration_ms = if distance >= ramp_mils {
+            // Trapezoid: both ramps, plus cruising for the remainder
+            2 * speed * MS_PER_SECOND / accel + (distance - ramp_mils) * MS_PER_SECOND / speed
+        } else {
+            // Triangle: never reaches cruise speed, so accelerate halfway then decelerate
+            2 * isqrt(distance * MS_PER_SECOND * MS_PER_SECOND / accel)
+        };
+
+        duration_ms.min(u32::MAX as u64) as u32
+    }
+
+    // Steps an axis with `steps_per_inch` takes to ramp down to rest from `steps_per_s`
+    pub fn stopping_steps(&self, steps_per_s: u32, steps_per_inch: u32) -> u32 {
+        let speed = steps_per_s as u64;
+        let accel = self.accel_mils_per_s2.max(1) as u64 * steps_per_inch as u64;
+        (speed * speed * MILS_PER_INCH / (2 * accel)).min(u32::MAX as u64) as u32
+    }
+
+    // Time to hold off before the next step of a stop, with `steps_left` of it to go counting that
+    // step, the axis slowing as it nears rest as the ramp allows
+    pub fn stopping_step_us(&self, steps_left: u32, steps_per_inch: u32) -> u32 {
+        // Speed at the step is sqrt(2 * accel * steps_left), and the time to it one over that
+        let accel = self.accel_mils_per_s2.max(1) as u64 * steps_per_inch as u64;
+        let squared_us =
+            US_PER_SECOND * US_PER_SECOND * MILS_PER_INCH / (2 * accel * steps_left.max(1) as u64);
+        isqrt(squared_us).min(u32::MAX as u64) as u32
+    }
+}
+
+impl CycleTiming {
+    // How long the blade spends closing and closed on each cut
+    pub fn stroke_ms(&self) -> u32 {
+        if self.stroke.is_plain() {
+            self.cut_dwell_ms
+        } else {
+            self.stroke.duration_ms()
+        }
+    }
+}
+
+impl StrokeProfile {
+    pub const PLAIN: Self = Self {
+        points: [StrokePoint {
+            position: 0,
+            hold_ms: 0,
+        }; MAX_STROKE_POINTS],
+        len: 0,
+    };
+
+    // A stroke through the given points, or None if there are too many or any hold is out of range
+    pub fn new(points: &[StrokePoint]) -> Option<Self> {
+        if points.len() > MAX_STROKE_POINTS
+            || points
+                .iter()
+                .any(|point| !(1..=MAX_STROKE_HOLD_MS).contains(&point.hold_ms))
+        {
+            return None;
+        }
+
+        let mut stroke = Self::PLAIN;
+        stroke.points[..points.len()].copy_from_slice(points);
+        stroke.len = points.len();
+        Some(stroke)
+    }
+
+    // Parse `position:hold_ms` points separated by commas, e.g. "90:150,110:900,120:300", or "plain"
+    pub fn parse(text: &str) -> Option<Self> {
+        if text.eq_ignore_ascii_case("plain") {
+            return Some(Self::PLAIN);
+        }
+
+        let mut points = [StrokePoint {
+            position: 0,
+            hold_ms: 0,
+        }; MAX_STROKE_POINTS];
+        let mut len = 0;
+        for field in text.split(',') {
+            let (position, hold_ms) = field.split_once(':')?;
+            *points.get_mut(len)? = StrokePoint {
+                position: position.parse().ok()?,
+                hold_ms: hold_ms.parse().ok()?,
+            };
+            len += 1;
+        }
+
+        Self::new(&points[..len])
+    }
+
+    pub fn is_plain(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn points(&self) -> &[StrokePoint] {
+        &self.points[..self.len]
+    }
+
+    pub fn duration_ms(&self) -> u32 {
+        self.points().iter().map(|point| point.hold_ms as u32).sum()
+    }
+
+    // A zero or erased hold ends the points, so flash saved before strokes existed reads as plain
+    pub fn from_bytes(bytes: &[u8; STROKE_LEN]) -> Self {
+        let mut points = [StrokePoint {
+            position: 0,
+            hold_ms: 0,
+        }; MAX_STROKE_POINTS];
+        let mut len = 0;
+        for chunk in bytes.chunks_exact(4) {
+            let point = StrokePoint {
+                position: u16::from_le_bytes(chunk[0..2].try_into().unwrap()),
+                hold_ms: u16::from_le_bytes(chunk[2..4].try_into().unwrap()),
+            };
+            if !(1..=MAX_STROKE_HOLD_MS).contains(&point.hold_ms) {
+                break;
+            }
+            points[len] = point;
+            len += 1;
+        }
+
+        Self::new(&points[..len]).unwrap_or(Self::PLAIN)
+    }
+
+    pub fn to_bytes(&self) -> [u8; STROKE_LEN] {
+        let mut bytes = [0; STROKE_LEN];
+        for (point, chunk) in self.points().iter().zip(bytes.chunks_exact_mut(4)) {
+            chunk[0..2].copy_from_slice(&point.position.to_le_bytes());
+            chunk[2..4].copy_from_slice(&point.hold_ms.to_le_bytes());
+        }
+
+        bytes
+    }
+}
+
+// As parsed
+impl fmt::Display for StrokeProfile {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.is_plain() {
+            return write!(f, "plain");
+        }
+
+        for (i, point) in self.points().iter().enumerate() {
+            if i > 0 {
+                write!(f, ",")?;
+            }
+            write!(f, "{}:{}", point.position, point.hold_ms)?;
+        }
+        Ok(())
+    }
+}
+
+impl Plan {
+    // Plan a single cycle: clamp, cut, then feed the next piece once the blade is clear and the clamp
+    // has let go. Feeding overlaps the tail of the blade's opening travel.
+    pub fn cut_cycle(timing: &CycleTiming, feed: &FeedProfile, feed_mils: u32) -> Self {
+        let mut plan = Self {
+            steps: [Step {
+                at_ms: 0,
+                action: Action::FeedStop,
+            }; MAX_STEPS],
+            len: 0,
+        };
+
+        let mut t_ms = 0;
+        if let Some(clamp) = timing.clamp {
+            plan.push(t_ms, Action::ClampClose);
+            t_ms += clamp.settle_ms;
+        }
+
+        if timing.stroke.is_plain() {
+            plan.push(t_ms, Action::CutClose);
+            t_ms += timing.cut_dwell_ms;
+        } else {
+            for point in timing.stroke.points() {
+                plan.push(
+                    t_ms,
+                    Action::CutStage {
+                        position: point.position,
+                    },
+                );
+                t_ms += point.hold_ms as u32;
+            }
+        }
+        plan.push(t_ms, Action::CutOpen);
+
+        t_ms += timing.blade_clearance_ms;
+        if let Some(clamp) = timing.clamp {
+            plan.push(t_ms, Action::ClampOpen);
+            t_ms += clamp.release_ms;
+        }
+        plan.push(
+            t_ms,
+            Action::FeedStart {
+                distance_mils: feed_mils,
+            },
+        );
+        plan.push(t_ms + feed.duration_ms(feed_mils), Action::FeedStop);
+
+        plan
+    }
+
+    // The same cycle with the piece chute moved over `bin` first, giving it the whole cut to get there
+    pub fn diverted_to(&self, bin: Bin) -> Self {
+        let mut plan = Self { len: 0, ..*self };
+        plan.push(0, Action::Divert { bin });
+        for step in self.steps() {
+            plan.push(step.at_ms, step.action);
+        }
+
+        plan
+    }
+
+    // The same cycle with the straightener running around each feed
+    pub fn with_straightener(&self, timing: &StraightenerTiming) -> Self {
+        let mut plan = *self;
+        for step in self.steps() {
+            match step.action {
+                Action::FeedStart { .. } => plan.insert(
+                    step.at_ms.saturating_sub(timing.pre_roll_ms),
+                    Action::StraightenerOn,
+                ),
+                Action::FeedStop => {
+                    plan.insert(step.at_ms + timing.post_roll_ms, Action::StraightenerOff)
+                }
+                _ => {}
+            }
+        }
+
+        plan
+    }
+
+    // Split the cycle where feeding starts, so the cut can be run (and retried) on its own. The feed
+    // half is timed from the end of the cut half, so running one after the other keeps the original
+    // timing, including the wait for the blade to clear.
+    pub fn split_at_feed(&self) -> (Self, Self) {
+        let index = self
+            .steps()
+            .iter()
+            .position(|step| matches!(step.action, Action::FeedStart { .. }))
+            .unwrap_or(self.len);
+
+        let mut cut = Self { len: 0, ..*self };
+        for step in &self.steps()[..index] {
+            cut.push(step.at_ms, step.action);
+        }
+
+        let start_ms = cut.duration_ms();
+        let mut feed = Self { len: 0, ..*self };
+        for step in &self.steps()[index..] {
+            feed.push(step.at_ms - start_ms, step.action);
+        }
+
+        (cut, feed)
+    }
+
+    pub fn steps(&self) -> &[Step] {
+        &self.steps[..self.len]
+    }
+
+    pub fn duration_ms(&self) -> u32 {
+        self.steps().last().map_or(0, |step| step.at_ms)
+    }
+
+    fn push(&mut self, at_ms: u32, action: Action) {
+        // Steps are executed in order, so they must be added in time order
+        debug_assert!(self.len < MAX_STEPS);
+        debug_assert!(self.len == 0 || self.steps[self.len - 1].at_ms <= at_ms);
+
+        self.steps[self.len] = Step { at_ms, action };
+        self.len += 1;
+    }
+
+    // Add a step among the existing ones, after any others due at the same time
+    fn insert(&mut self, at_ms: u32, action: Action) {
+        debug_assert!(self.len < MAX_STEPS);
+
+        let index = self.steps().partition_point(|step| step.at_ms <= at_ms);
+        self.steps.copy_within(index..self.len, index + 1);
+        self.steps[index] = Step { at_ms, action };
+        self.len += 1;
+    }
+}
+
+///////////////////////////////////////////////////////////////////////////////
+//  Helper Functions
+///////////////////////////////////////////////////////////////////////////////
+
+fn isqrt(value: u64) -> u64 {
+    if value < 2 {
+        return value;
+    }
+
+    // Newton's method, starting from an overestimate
+    let mut x = value;
+    let mut y = x.div_ceil(2);
+    while y < x {
+        x = y;
+        y = (x + value / x) / 2;
+    }
+
+    x
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PROFILE: FeedProfile = FeedProfile {
+        max_speed_mils_per_s: 4000,
+        accel_mils_per_s2: 8000,
+    };
+
+    #[test]
+    fn trapezoid_includes_ramps_and_cruise() {
+        // 2000 mils of ramps taking 1s, then 8000 mils cruising at 4 in/s
+        assert_eq!(PROFILE.duration_ms(10_000), 3000);
+    }
+
+    #[test]
+    fn short_moves_are_triangular() {
+        // Never reaches cruise speed; 500 mils each way at 8 in/s^2 takes ~354ms
+        assert_eq!(PROFILE.duration_ms(1000), 706);
+        assert_eq!(PROFILE.duration_ms(0), 0);
+    }
+
+    #[test]
+    fn stops_ramp_down_to_rest() {
+        // 1600 steps/s^2 at 200 steps/in, so 1000 steps/s takes 312.5 steps to stop
+        assert_eq!(PROFILE.stopping_steps(1000, 200), 312);
+        assert_eq!(PROFILE.stopping_steps(0, 200), 0);
+
+        // Near full speed at the start of the stop, slowing to ~56 steps/s for the last step
+        assert_eq!(PROFILE.stopping_step_us(312, 200), 1000);
+        assert_eq!(PROFILE.stopping_step_us(1, 200), 17_677);
+        assert!(PROFILE.stopping_step_us(100, 200) < PROFILE.stopping_step_us(10, 200));
+    }
+
+    #[test]
+    fn scaling_changes_cruise_speed_only() {
+        let slow = PROFILE.scaled(50);
+        assert_eq!(slow.max_speed_mils_per_s, 2000);
+        assert_eq!(slow.accel_mils_per_s2, PROFILE.accel_mils_per_s2);
+    }
+
+    #[test]
+    fn cycle_feeds_after_blade_clears() {
+        let timing = CycleTiming {
+            cut_dwell_ms: 1500,
+            blade_clearance_ms: 200,
+            stroke: StrokeProfile::PLAIN,
+            clamp: None,
+        };
+        let plan = Plan::cut_cycle(&timing, &PROFILE, 10_000);
+
+        let actions: Vec<_> = plan.steps().iter().map(|s| (s.at_ms, s.action)).collect();
+        assert_eq!(
+            actions,
+            [
+                (0, Action::CutClose),
+                (1500, Action::CutOpen),
+                (
+                    1700,
+                    Action::FeedStart {
+                        distance_mils: 10_000
+                    }
+                ),
+                (4700, Action::FeedStop),
+            ]
+        );
+        assert_eq!(plan.duration_ms(), 4700);
+    }
+
+    #[test]
+    fn clamp_brackets_the_cut() {
+        let timing = CycleTiming {
+            cut_dwell_ms: 1500,
+            blade_clearance_ms: 200,
+            stroke: StrokeProfile::PLAIN,
+            clamp: Some(ClampTiming {
+                settle_ms: 100,
+                release_ms: 50,
+            }),
+        };
+        let plan = Plan::cut_cycle(&timing, &PROFILE, 0);
+
+        let actions: Vec<_> = plan.steps().iter().map(|s| (s.at_ms, s.action)).collect();
+        assert_eq!(
+            actions,
+            [
+                (0, Action::ClampClose),
+                (100, Action::CutClose),
+                (1600, Action::CutOpen),
+                (1800, Action::ClampOpen),
+                (1850, Action::FeedStart { distance_mils: 0 }),
+                (1850, Action::FeedStop),
+            ]
+        );
+    }
+
+    #[test]
+    fn divert_leads_the_cycle() {
+        let timing = CycleTiming {
+            cut_dwell_ms: 1500,
+            blade_clearance_ms: 200,
+            stroke: StrokeProfile::PLAIN,
+            clamp: Some(ClampTiming {
+                settle_ms: 100,
+                release_ms: 50,
+            }),
+        };
+        let plan = Plan::cut_cycle(&timing, &PROFILE, 1000);
+        let diverted = plan.diverted_to(Bin::B);
+
+        assert_eq!(diverted.steps().len(), plan.steps().len() + 1);
+        assert_eq!(diverted.steps()[0].action, Action::Divert { bin: Bin::B });
+        assert_eq!(diverted.steps()[0].at_ms, 0);
+        assert_eq!(diverted.duration_ms(), plan.duration_ms());
+    }
+
+    #[test]
+    fn straightener_runs_around_the_feed() {
+        let timing = CycleTiming {
+            cut_dwell_ms: 1500,
+            blade_clearance_ms: 200,
+            stroke: StrokeProfile::PLAIN,
+            clamp: None,
+        };
+        let straightener = StraightenerTiming {
+            pre_roll_ms: 300,
+            post_roll_ms: 100,
+        };
+        let plan = Plan::cut_cycle(&timing, &PROFILE, 10_000).with_straightener(&straightener);
+
+        let actions: Vec<_> = plan.steps().iter().map(|s| (s.at_ms, s.action)).collect();
+        assert_eq!(
+            actions,
+            [
+                (0, Action::CutClose),
+                (1400, Action::StraightenerOn),
+                (1500, Action::CutOpen),
+                (
+                    1700,
+                    Action::FeedStart {
+                        distance_mils: 10_000
+                    }
+                ),
+                (4700, Action::FeedStop),
+                (4800, Action::StraightenerOff),
+            ]
+        );
+    }
+
+    #[test]
+    fn everything_fits_in_a_full_plan() {
+        let timing = CycleTiming {
+            cut_dwell_ms: 1500,
+            blade_clearance_ms: 200,
+            stroke: StrokeProfile::PLAIN,
+            clamp: Some(ClampTiming {
+                settle_ms: 100,
+                release_ms: 50,
+            }),
+        };
+        let straightener = StraightenerTiming {
+            pre_roll_ms: 5000,
+            post_roll_ms: 0,
+        };
+        let plan = Plan::cut_cycle(&timing, &PROFILE, 1000)
+            .with_straightener(&straightener)
+            .diverted_to(Bin::A);
+
+        assert_eq!(plan.steps().len(), 9);
+        assert!(plan.steps().windows(2).all(|w| w[0].at_ms <= w[1].at_ms));
+        // Pre-roll can't start before the cycle does
+        assert_eq!(plan.steps()[2].at_ms, 0);
+        assert_eq!(plan.steps()[2].action, Action::StraightenerOn);
+    }
+
+    #[test]
+    fn split_halves_keep_cycle_timing() {
+        let timing = CycleTiming {
+            cut_dwell_ms: 1500,
+            blade_clearance_ms: 200,
+            stroke: StrokeProfile::PLAIN,
+            clamp: Some(ClampTiming {
+                settle_ms: 100,
+                release_ms: 50,
+            }),
+        };
+        let plan = Plan::cut_cycle(&timing, &PROFILE, 10_000);
+        let (cut, feed) = plan.split_at_feed();
+
+        let cut_actions: Vec<_> = cut.steps().iter().map(|s| (s.at_ms, s.action)).collect();
+        assert_eq!(
+            cut_actions,
+            [
+                (0, Action::ClampClose),
+                (100, Action::CutClose),
+                (1600, Action::CutOpen),
+                (1800, Action::ClampOpen),
+            ]
+        );
+        let feed_actions: Vec<_> = feed.steps().iter().map(|s| (s.at_ms, s.action)).collect();
+        assert_eq!(
+            feed_actions,
+            [
+                (
+                    50,
+                    Action::FeedStart {
+                        distance_mils: 10_000
+                    }
+                ),
+                (3050, Action::FeedStop),
+            ]
+        );
+        assert_eq!(cut.duration_ms() + feed.duration_ms(), plan.duration_ms());
+    }
+
+    #[test]
+    fn shaped_stroke_replaces_the_dwell() {
+        let timing = CycleTiming {
+            cut_dwell_ms: 1500,
+            blade_clearance_ms: 200,
+            stroke: StrokeProfile::parse("90:150,110:900,120:300").unwrap(),
+            clamp: Some(ClampTiming {
+                settle_ms: 100,
+                release_ms: 50,
+            }),
+        };
+        let straightener = StraightenerTiming {
+            pre_roll_ms: 300,
+            post_roll_ms: 100,
+        };
+        let plan = Plan::cut_cycle(&timing, &PROFILE, 0)
+            .with_straightener(&straightener)
+            .diverted_to(Bin::A);
+
+        let actions: Vec<_> = plan.steps().iter().map(|s| (s.at_ms, s.action)).collect();
+        assert_eq!(
+            actions[..8],
+            [
+                (0, Action::Divert { bin: Bin::A }),
+                (0, Action::ClampClose),
+                (100, Action::CutStage { position: 90 }),
+                (250, Action::CutStage { position: 110 }),
+                (1150, Action::CutStage { position: 120 }),
+                (1400, Action::StraightenerOn),
+                (1450, Action::CutOpen),
+                (1650, Action::ClampOpen),
+            ]
+        );
+        assert_eq!(plan.steps().len(), 11);
+        assert_eq!(timing.stroke_ms(), 1350);
+    }
+
+    #[test]
+    fn stroke_codecs_round_trip() {
+        let stroke = StrokeProfile::parse("90:150,120:1300").unwrap();
+        assert_eq!(stroke.to_string(), "90:150,120:1300");
+        assert_eq!(StrokeProfile::from_bytes(&stroke.to_bytes()), stroke);
+        assert_eq!(StrokeProfile::parse("Plain"), Some(StrokeProfile::PLAIN));
+        assert_eq!(StrokeProfile::PLAIN.to_string(), "plain");
+
+        // Erased flash, e.g. saved before strokes existed, reads as plain
+        assert_eq!(
+            StrokeProfile::from_bytes(&[0xFF; STROKE_LEN]),
+            StrokeProfile::PLAIN
+        );
+
+        assert_eq!(StrokeProfile::parse("90:0"), None);
+        assert_eq!(StrokeProfile::parse("90:150,"), None);
+        assert_eq!(StrokeProfile::parse("1:1,2:2,3:3,4:4,5:5"), None);
+    }
+
+    #[test]
+    fn isqrt_is_floor() {
+        for value in 0..10_000_u64 {
+            let root = isqrt(value);
+            assert!(root * root <= value && (root + 1) * (root + 1) > value);
+        }
+    }
+}
@@ -0,0 +1,120 @@
+/* * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * *\
+Copyright (C) 2023 CJ McAllister
+    This program is free software; you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation; either version 3 of the License, or
+    (at your option) any later version.
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+    You should have received a copy of the GNU General Public License
+    along with this program; if not, write to the Free Software Foundation,
+    Inc., 51 Franklin Street, Fifth Floor, Boston, MA 02110-1301  USA
+
+\* * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * */
+
+///////////////////////////////////////////////////////////////////////////////
+//  Named Constants
+///////////////////////////////////////////////////////////////////////////////
+
+// Fixed-width fields are five digits, enough for any count or length the UI accepts
+pub const PADDED_WIDTH: usize = 5;
+pub const MAX_PADDED: u32 = 99_999;
+
+// u32::MAX has 10 digits
+pub const MAX_U32_DIGITS: usize = 10;
+
+///////////////////////////////////////////////////////////////////////////////
+//  Static Functions
+///////////////////////////////////////////////////////////////////////////////
+
+// Zero-padded ASCII digits of a value, saturating at 99999 rather than overflowing the field
+pub fn padded(val: u32) -> [u8; PADDED_WIDTH] {
+    let mut remaining = val.min(MAX_PADDED);
+
+    let mut digits = [b'0'; PADDED_WIDTH];
+    for digit in digits.iter_mut().rev() {
+        *digit = b'0' + (remaining % 10) as u8;
+        remaining /= 10;
+    }
+
+    digits
+}
+
+// ASCII digits of a value without zero-padding, written into the given buffer
+pub fn trimmed(val: u32, buf: &mut [u8; MAX_U32_DIGITS]) -> &str {
+    // Fill from the end so the digits come out most-significant first
+    let mut start = MAX_U32_DIGITS;
+    let mut remaining = val;
+    loop {
+        start -= 1;
+        buf[start] = b'0' + (remaining % 10) as u8;
+        remaining /= 10;
+
+        if remaining == 0 {
+            break;
+        }
+    }
+
+    // Only ASCII digits are ever stored, so this cannot fail
+    core::str::from_utf8(&buf[start..]).unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    fn parse(digits: &[u8]) -> u32 {
+        core::str::from_utf8(digits).unwrap().parse().unwrap()
+    }
+
+    #[test]
+    fn edge_cases() {
+        assert_eq!(&padded(0), b"00000");
+        assert_eq!(&padded(9), b"00009");
+        assert_eq!(&padded(10), b"00010");
+        assert_eq!(&padded(MAX_PADDED), b"99999");
+        assert_eq!(&padded(MAX_PADDED + 1), b"99999");
+        assert_eq!(&padded(u32::MAX), b"99999");
+
+        let mut buf = [0; MAX_U32_DIGITS];
+        assert_eq!(trimmed(0, &mut buf), "0");
+        assert_eq!(trimmed(10, &mut buf), "10");
+        assert_eq!(trimmed(u32::MAX, &mut buf), "4294967295");
+    }
+
+    #[test]
+    fn powers_of_ten_boundaries() {
+        let mut buf = [0; MAX_U32_DIGITS];
+        let mut power = 1_u32;
+        for num_digits in 1..MAX_U32_DIGITS {
+            power *= 10;
+            assert_eq!(trimmed(power - 1, &mut buf).len(), num_digits);
+            assert_eq!(trimmed(power, &mut buf).len(), num_digits + 1);
+        }
+    }
+
+    proptest! {
+        #[test]
+        fn padded_round_trips(val in 0..=MAX_PADDED) {
+            let digits = padded(val);
+            prop_assert!(digits.iter().all(u8::is_ascii_digit));
+            prop_assert_eq!(parse(&digits), val);
+        }
+
+        #[test]
+        fn padded_saturates(val in MAX_PADDED..) {
+            prop_assert_eq!(parse(&padded(val)), MAX_PADDED);
+        }
+
+        #[test]
+        fn trimmed_round_trips(val in any::<u32>()) {
+            let mut buf = [0; MAX_U32_DIGITS];
+            let digits = trimmed(val, &mut buf);
+            prop_assert!(val == 0 || !digits.starts_with('0'));
+            prop_assert_eq!(digits.parse::<u32>().unwrap(), val);
+        }
+    }
+}
@@ -0,0 +1,371 @@
+/* * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * *\
+Copyright (C) 2023 CJ McAllister
+    This program is free software; you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation; either version 3 of the License, or
+    (at your option) any later version.
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+    You should have received a copy of the GNU General Public License
+    along with this program; if not, write to the Free Software Foundation,
+    Inc., 51 Franklin Street, Fifth Floor, Boston, MA 02110-1301  USA
+
+\* * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * */
+
+use core::convert::TryInto;
+
+use crate::motion::{StrokeProfile, STROKE_LEN};
+use crate::numeric;
+use crate::units::{Length, Rounding, Unit};
+
+///////////////////////////////////////////////////////////////////////////////
+//  Named Constants
+///////////////////////////////////////////////////////////////////////////////
+
+pub const NUM_PROFILES: usize = 4;
+pub const PROFILE_NAME_LEN: usize = 12;
+
+pub const DEFAULT_FEED_SPEED_PCT: u8 = 100;
+pub const MIN_FEED_SPEED_PCT: u8 = 10;
+pub const MAX_FEED_SPEED_PCT: u8 = 200;
+
+// ASCII "PROF", marks the page as holding valid profile data (erased flash reads 0xFFFFFFFF)
+const PROFILES_MAGIC: u32 = 0x5052_4F46;
+
+const HEADER_LEN: usize = 8;
+const PROFILE_LEN: usize = 24;
+// Cut strokes came later, so they follow the profiles rather than growing them. Flash saved before then
+// reads erased there, which decodes as the plain stroke.
+const STROKES_OFFSET: usize = HEADER_LEN + NUM_PROFILES * PROFILE_LEN;
+pub const SERIALIZED_LEN: usize = STROKES_OFFSET + NUM_PROFILES * STROKE_LEN;
+
+// "=", the longest whole part a u32 can hold, then a point, two decimals and the units' label
+pub const PREVIEW_LEN: usize = 1 + numeric::MAX_U32_DIGITS + 3 + 2;
+
+///////////////////////////////////////////////////////////////////////////////
+//  Data Structures
+///////////////////////////////////////////////////////////////////////////////
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Units {
+    Inches = 0,
+    Millimeters = 1,
+}
+
+#[derive(Copy, Clone, Debug)]
+pub struct Profile {
+    name: [u8; PROFILE_NAME_LEN],
+    pub units: Units,
+    pub feed_speed_pct: u8,
+    pub completion_melody: u8,
+    // Show the cut length in digits two rows tall while it's entered
+    pub big_digits: bool,
+    pub last_cut_length: u32,
+    pub last_num_cuts: u32,
+    // How the blade closes for the material this operator cuts
+    pub stroke: StrokeProfile,
+}
+
+#[derive(Debug)]
+pub struct Profiles {
+    active: usize,
+    profiles: [Profile; NUM_PROFILES],
+}
+
+///////////////////////////////////////////////////////////////////////////////
+//  Object Implementations
+///////////////////////////////////////////////////////////////////////////////
+
+impl Units {
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Inches => "in",
+            Self::Millimeters => "mm",
+        }
+    }
+
+    pub fn toggled(self) -> Self {
+        match self {
+            Self::Inches => Self::Millimeters,
+            Self::Millimeters => Self::Inches,
+        }
+    }
+
+    // A whole number of these units as a length
+    pub fn length(self, value: u32) -> Length {
+        Length::new(value, self.into())
+    }
+
+    // What lengths of wire by the spool are counted in, alongside these
+    pub fn spool_unit(self) -> Unit {
+        match self {
+            Self::Inches => Unit::Foot,
+            Self::Millimeters => Unit::Meter,
+        }
+    }
+
+    // Convert a length in these units to whole inches, rounding up
+    pub fn to_inches(self, length: u32) -> u32 {
+        self.length(length).to(Unit::Inch, Rounding::Up)
+    }
+
+    pub fn to_mils(self, length: u32) -> u32 {
+        self.length(length).to(Unit::Mil, Rounding::Down)
+    }
+
+    // A length in these units as it reads in the other, e.g. "=5.00in" for 127mm, to the hundredth of
+    // an inch or tenth of a millimetre, dropping decimals to fit within `width`. Empty if it won't fit.
+    pub fn preview(self, length: u32, width: usize, buf: &mut [u8; PREVIEW_LEN]) -> &str {
+        let other = self.toggled();
+        let max_decimals = match other {
+            Self::Inches => 2,
+            Self::Millimeters => 1,
+        };
+
+        for decimals in (0..=max_decimals).rev() {
+            let scale = 10u32.pow(decimals);
+            let scaled = self
+                .length(length)
+                .to_scaled(other.into(), decimals, Rounding::Nearest);
+            let whole = scaled / scale;
+            let fraction = numeric::padded(scaled % scale);
+
+            let mut len = 0;
+            let mut digits = [0; numeric::MAX_U32_DIGITS];
+            let point = if decimals > 0 { "." } else { "" };
+            for part in [
+                "=".as_bytes(),
+                numeric::trimmed(whole, &mut digits).as_bytes(),
+                point.as_bytes(),
+                &fraction[numeric::PADDED_WIDTH - decimals as usize..],
+                other.label().as_bytes(),
+            ] {
+                buf[len..len + part.len()].copy_from_slice(part);
+                len += part.len();
+            }
+
+            if len <= width {
+                // Only ASCII is ever written, so this cannot fail
+                return core::str::from_utf8(&buf[..len]).unwrap();
+            }
+        }
+
+        ""
+    }
+}
+
+impl From<u8> for Units {
+    fn from(value: u8) -> Self {
+        match value {
+            1 => Self::Millimeters,
+            _ => Self::Inches,
+        }
+    }
+}
+
+impl Profile {
+    fn default_for(index: usize) -> Self {
+        // Default names are "OPERATOR 1" through "OPERATOR 4"
+        let mut name = [b' '; PROFILE_NAME_LEN];
+        name[..9].copy_from_slice(b"OPERATOR ");
+        name[9] = b'1' + index as u8;
+
+        Self {
+            name,
+            units: Units::Inches,
+            feed_speed_pct: DEFAULT_FEED_SPEED_PCT,
+            completion_melody: 0,
+            big_digits: false,
+            last_cut_length: 0,
+            last_num_cuts: 0,
+            stroke: StrokeProfile::PLAIN,
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        core::str::from_utf8(&self.name)
+            .unwrap_or("OPERATOR")
+            .trim_end()
+    }
+
+    fn serialize(&self, bytes: &mut [u8]) {
+        bytes[0..12].copy_from_slice(&self.name);
+        bytes[12] = self.units as u8;
+        bytes[13] = self.feed_speed_pct;
+        bytes[14] = self.completion_melody;
+        bytes[15] = self.big_digits as u8;
+        bytes[16..20].copy_from_slice(&self.last_cut_length.to_le_bytes());
+        bytes[20..24].copy_from_slice(&self.last_num_cuts.to_le_bytes());
+    }
+
+    fn deserialize(bytes: &[u8]) -> Self {
+        let mut name = [0; PROFILE_NAME_LEN];
+        name.copy_from_slice(&bytes[0..12]);
+
+        Self {
+            name,
+            units: Units::from(bytes[12]),
+            feed_speed_pct: bytes[13].clamp(MIN_FEED_SPEED_PCT, MAX_FEED_SPEED_PCT),
+            // Out-of-range melodies are wrapped by the player
+            completion_melody: bytes[14],
+            big_digits: bytes[15] == 1,
+            last_cut_length: u32::from_le_bytes(bytes[16..20].try_into().unwrap()),
+            last_num_cuts: u32::from_le_bytes(bytes[20..24].try_into().unwrap()),
+            // Saved after all the profiles, and read from there by Profiles::from_bytes()
+            stroke: StrokeProfile::PLAIN,
+        }
+    }
+}
+
+impl Profiles {
+    // Decode profiles, or None if the bytes don't hold saved profiles (e.g. erased flash)
+    pub fn from_bytes(bytes: &[u8; SERIALIZED_LEN]) -> Option<Self> {
+        let magic = u32::from_le_bytes(bytes[0..4].try_into().unwrap());
+        if magic != PROFILES_MAGIC {
+            return None;
+        }
+
+        let active = u32::from_le_bytes(bytes[4..8].try_into().unwrap()) as usize;
+        let mut profiles = Self {
+            active: active.min(NUM_PROFILES - 1),
+            ..Self::default()
+        };
+        for (i, profile) in profiles.profiles.iter_mut().enumerate() {
+            let start = HEADER_LEN + i * PROFILE_LEN;
+            *profile = Profile::deserialize(&bytes[start..start + PROFILE_LEN]);
+            let start = STROKES_OFFSET + i * STROKE_LEN;
+            profile.stroke =
+                StrokeProfile::from_bytes(bytes[start..start + STROKE_LEN].try_into().unwrap());
+        }
+
+        Some(profiles)
+    }
+
+    pub fn to_bytes(&self) -> [u8; SERIALIZED_LEN] {
+        let mut bytes = [0; SERIALIZED_LEN];
+        bytes[0..4].copy_from_slice(&PROFILES_MAGIC.to_le_bytes());
+        bytes[4..8].copy_from_slice(&(self.active as u32).to_le_bytes());
+        for (i, profile) in self.profiles.iter().enumerate() {
+            let start = HEADER_LEN + i * PROFILE_LEN;
+            profile.serialize(&mut bytes[start..start + PROFILE_LEN]);
+            let start = STROKES_OFFSET + i * STROKE_LEN;
+            bytes[start..start + STROKE_LEN].copy_from_slice(&profile.stroke.to_bytes());
+        }
+
+        bytes
+    }
+
+    pub fn active_index(&self) -> usize {
+        self.active
+    }
+
+    pub fn active(&self) -> &Profile {
+        &self.profiles[self.active]
+    }
+
+    pub fn active_mut(&mut self) -> &mut Profile {
+        &mut self.profiles[self.active]
+    }
+
+    pub fn get_mut(&mut self, index: usize) -> Option<&mut Profile> {
+        self.profiles.get_mut(index)
+    }
+
+    pub fn select(&mut self, index: usize) {
+        self.active = index.min(NUM_PROFILES - 1);
+    }
+}
+
+impl Default for Profiles {
+    fn default() -> Self {
+        Self {
+            active: 0,
+            profiles: [
+                Profile::default_for(0),
+                Profile::default_for(1),
+                Profile::default_for(2),
+                Profile::default_for(3),
+            ],
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn previews_fit_their_width() {
+        let mut buf = [0; PREVIEW_LEN];
+        assert_eq!(Units::Millimeters.preview(127, 16, &mut buf), "=5.00in");
+        assert_eq!(Units::Millimeters.preview(10, 16, &mut buf), "=0.39in");
+        assert_eq!(Units::Inches.preview(5, 16, &mut buf), "=127.0mm");
+
+        // Decimals go first, then the whole preview
+        assert_eq!(Units::Millimeters.preview(25400, 8, &mut buf), "=1000in");
+        assert_eq!(Units::Inches.preview(1000, 8, &mut buf), "=25400mm");
+        assert_eq!(Units::Inches.preview(1000, 7, &mut buf), "");
+    }
+
+    #[test]
+    fn codec_round_trips() {
+        let mut profiles = Profiles::default();
+        profiles.select(2);
+        let profile = profiles.active_mut();
+        profile.units = Units::Millimeters;
+        profile.feed_speed_pct = 150;
+        profile.completion_melody = 2;
+        profile.big_digits = true;
+        profile.last_cut_length = 305;
+        profile.last_num_cuts = 12;
+        profile.stroke = StrokeProfile::parse("90:200,120:1000").unwrap();
+
+        let decoded = Profiles::from_bytes(&profiles.to_bytes()).unwrap();
+        assert_eq!(decoded.active_index(), 2);
+        let profile = decoded.active();
+        assert_eq!(profile.name(), "OPERATOR 3");
+        assert_eq!(profile.units, Units::Millimeters);
+        assert_eq!(profile.feed_speed_pct, 150);
+        assert_eq!(profile.completion_melody, 2);
+        assert!(profile.big_digits);
+        assert_eq!(profile.last_cut_length, 305);
+        assert_eq!(profile.last_num_cuts, 12);
+        assert_eq!(profile.stroke.to_string(), "90:200,120:1000");
+    }
+
+    #[test]
+    fn profiles_saved_before_strokes_have_plain_strokes() {
+        let mut bytes = Profiles::default().to_bytes();
+        bytes[STROKES_OFFSET..].fill(0xFF);
+
+        let decoded = Profiles::from_bytes(&bytes).unwrap();
+        assert!(decoded.profiles.iter().all(|p| p.stroke.is_plain()));
+    }
+
+    #[test]
+    fn erased_flash_has_no_profiles() {
+        assert!(Profiles::from_bytes(&[0xFF; SERIALIZED_LEN]).is_none());
+    }
+
+    #[test]
+    fn corrupt_fields_are_sanitized() {
+        let mut bytes = Profiles::default().to_bytes();
+        bytes[4] = 9; // Active index
+        bytes[HEADER_LEN + 13] = 0; // Feed speed of first profile
+
+        let decoded = Profiles::from_bytes(&bytes).unwrap();
+        assert_eq!(decoded.active_index(), NUM_PROFILES - 1);
+        assert_eq!(decoded.profiles[0].feed_speed_pct, MIN_FEED_SPEED_PCT);
+    }
+
+    #[test]
+    fn unit_conversions() {
+        assert_eq!(Units::Millimeters.to_inches(254), 10);
+        assert_eq!(Units::Millimeters.to_inches(1), 1);
+        assert_eq!(Units::Inches.to_mils(3), 3000);
+        assert_eq!(Units::Millimeters.to_mils(254), 10_000);
+    }
+}
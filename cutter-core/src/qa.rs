@@ -0,0 +1,268 @@
+/* * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * *\
+Copyright (C) 2023 CJ McAllister
+    This program is free software; you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation; either version 3 of the License, or
+    (at your option) any later version.
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+    You should have received a copy of the GNU General Public License
+    along with this program; if not, write to the Free Software Foundation,
+    Inc., 51 Franklin Street, Fifth Floor, Boston, MA 02110-1301  USA
+
+\* * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * */
+
+use crate::numeric;
+use crate::profiles::Units;
+use crate::units::{Length, Rounding, Unit};
+
+///////////////////////////////////////////////////////////////////////////////
+//  Named Constants
+///////////////////////////////////////////////////////////////////////////////
+
+pub const NUM_BINS: usize = 9;
+
+// "measured/commanded" and the units, with the longest numbers a u32 can hold and 3 decimals
+pub const FIRST_PIECE_LEN: usize = 2 * numeric::MAX_U32_DIGITS + 4 + 1 + 2;
+
+// Each bin spans this many mils of error, with the middle bin centred on zero.
+// The outermost bins also take everything beyond them.
+pub const BIN_WIDTH_MILS: i32 = 10;
+
+const CENTRE_BIN: i32 = NUM_BINS as i32 / 2;
+
+// Spreads small seeds, e.g. a single key, across the generator's state
+const SEED_SCRAMBLE_MUL: u32 = 0x9E37_79B9;
+const SEED_SCRAMBLE_XOR: u32 = 0x2545_F491;
+
+///////////////////////////////////////////////////////////////////////////////
+//  Data Structures
+///////////////////////////////////////////////////////////////////////////////
+
+// Distribution of feed length errors (measured minus commanded) over a verification run
+#[derive(Debug, Default)]
+pub struct ErrorHistogram {
+    bins: [u32; NUM_BINS],
+    count: u32,
+    sum_mils: i64,
+    min_mils: i32,
+    max_mils: i32,
+}
+
+// Pseudo-random piece lengths within a range, for characterizing accuracy across the feed envelope.
+// A seed always gives the same sequence, so a run can be repeated exactly.
+#[derive(Clone, Debug)]
+pub struct RandomLengths {
+    // xorshift32, which must never be zero
+    state: u32,
+    min_mils: u32,
+    max_mils: u32,
+}
+
+///////////////////////////////////////////////////////////////////////////////
+//  Object Implementations
+///////////////////////////////////////////////////////////////////////////////
+
+impl ErrorHistogram {
+    pub fn record(&mut self, error_mils: i32) {
+        let offset = (error_mils + BIN_WIDTH_MILS / 2).div_euclid(BIN_WIDTH_MILS);
+        let bin = (CENTRE_BIN + offset).clamp(0, NUM_BINS as i32 - 1);
+        self.bins[bin as usize] += 1;
+
+        if self.count == 0 {
+            self.min_mils = error_mils;
+            self.max_mils = error_mils;
+        } else {
+            self.min_mils = self.min_mils.min(error_mils);
+            self.max_mils = self.max_mils.max(error_mils);
+        }
+        self.count += 1;
+        self.sum_mils += error_mils as i64;
+    }
+
+    pub fn bins(&self) -> &[u32; NUM_BINS] {
+        &self.bins
+    }
+
+    // Error at the centre of the given bin
+    pub fn bin_centre_mils(bin: usize) -> i32 {
+        (bin as i32 - CENTRE_BIN) * BIN_WIDTH_MILS
+    }
+
+    pub fn count(&self) -> u32 {
+        self.count
+    }
+
+    // Mean error, rounded towards zero, or None before any pieces are recorded
+    pub fn mean_mils(&self) -> Option<i32> {
+        (self.count > 0).then(|| (self.sum_mils / self.count as i64) as i32)
+    }
+
+    // Smallest and largest errors, or None before any pieces are recorded
+    pub fn range_mils(&self) -> Option<(i32, i32)> {
+        (self.count > 0).then_some((self.min_mils, self.max_mils))
+    }
+}
+
+impl RandomLengths {
+    pub fn new(seed: u32, min_mils: u32, max_mils: u32) -> Self {
+        let state = seed.wrapping_mul(SEED_SCRAMBLE_MUL) ^ SEED_SCRAMBLE_XOR;
+        Self {
+            state: state.max(1),
+            min_mils: min_mils.min(max_mils),
+            max_mils,
+        }
+    }
+}
+
+impl Iterator for RandomLengths {
+    type Item = u32;
+
+    fn next(&mut self) -> Option<u32> {
+        let mut x = self.state;
+        x ^= x << 13;
+        x ^= x >> 17;
+        x ^= x << 5;
+        self.state = x;
+
+        let span = (self.max_mils - self.min_mils) as u64 + 1;
+        Some(self.min_mils + (x as u64 % span) as u32)
+    }
+}
+
+///////////////////////////////////////////////////////////////////////////////
+//  Static Functions
+///////////////////////////////////////////////////////////////////////////////
+
+// A first piece as measured against the length it was commanded, in the job's units, e.g.
+// "11.950/12in". Measured to the mil or hundredth of a millimetre, dropping decimals to fit `width`.
+pub fn first_piece_line(
+    measured_mils: u32,
+    commanded: u32,
+    units: Units,
+    width: usize,
+    buf: &mut [u8; FIRST_PIECE_LEN],
+) -> &str {
+    let max_decimals = match units {
+        Units::Inches => 3,
+        Units::Millimeters => 2,
+    };
+
+    let mut len = 0;
+    for decimals in (0..=max_decimals).rev() {
+        let scale = 10u32.pow(decimals);
+        let scaled = Length::new(measured_mils, Unit::Mil).to_scaled(
+            units.into(),
+            decimals,
+            Rounding::Nearest,
+        );
+        let fraction = numeric::padded(scaled % scale);
+
+        len = 0;
+        let mut whole = [0; numeric::MAX_U32_DIGITS];
+        let mut target = [0; numeric::MAX_U32_DIGITS];
+        let point = if decimals > 0 { "." } else { "" };
+        for part in [
+            numeric::trimmed(scaled / scale, &mut whole).as_bytes(),
+            point.as_bytes(),
+            &fraction[numeric::PADDED_WIDTH - decimals as usize..],
+            b"/",
+            numeric::trimmed(commanded, &mut target).as_bytes(),
+            units.label().as_bytes(),
+        ] {
+            buf[len..len + part.len()].copy_from_slice(part);
+            len += part.len();
+        }
+
+        if len <= width {
+            break;
+        }
+    }
+
+    // Only ASCII is ever written, so this cannot fail
+    core::str::from_utf8(&buf[..len]).unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn errors_land_in_centred_bins() {
+        let mut histogram = ErrorHistogram::default();
+        for error_mils in [-4, 0, 4, 5, -6, 100, -100] {
+            histogram.record(error_mils);
+        }
+
+        assert_eq!(histogram.bins(), &[1, 0, 0, 1, 3, 1, 0, 0, 1]);
+        assert_eq!(ErrorHistogram::bin_centre_mils(0), -40);
+        assert_eq!(ErrorHistogram::bin_centre_mils(NUM_BINS - 1), 40);
+    }
+
+    #[test]
+    fn summary_statistics() {
+        let mut histogram = ErrorHistogram::default();
+        assert_eq!(histogram.mean_mils(), None);
+        assert_eq!(histogram.range_mils(), None);
+
+        for error_mils in [12, -3, 6] {
+            histogram.record(error_mils);
+        }
+        assert_eq!(histogram.count(), 3);
+        assert_eq!(histogram.mean_mils(), Some(5));
+        assert_eq!(histogram.range_mils(), Some((-3, 12)));
+    }
+
+    #[test]
+    fn first_piece_lines_fit_their_width() {
+        let mut buf = [0; FIRST_PIECE_LEN];
+        assert_eq!(
+            first_piece_line(11_950, 12, Units::Inches, 16, &mut buf),
+            "11.950/12in"
+        );
+        assert_eq!(
+            first_piece_line(12_000, 305, Units::Millimeters, 16, &mut buf),
+            "304.80/305mm"
+        );
+
+        // Decimals go first, but the line is never left empty
+        assert_eq!(
+            first_piece_line(393_701, 10_000, Units::Millimeters, 14, &mut buf),
+            "10000/10000mm"
+        );
+        assert_eq!(
+            first_piece_line(393_701, 10_000, Units::Millimeters, 4, &mut buf),
+            "10000/10000mm"
+        );
+    }
+
+    #[test]
+    fn random_lengths_repeat_for_a_seed() {
+        let first: Vec<u32> = RandomLengths::new(7, 500, 12_000).take(50).collect();
+        let again: Vec<u32> = RandomLengths::new(7, 500, 12_000).take(50).collect();
+        let other: Vec<u32> = RandomLengths::new(8, 500, 12_000).take(50).collect();
+
+        assert_eq!(first, again);
+        assert_ne!(first, other);
+    }
+
+    #[test]
+    fn random_lengths_cover_the_range() {
+        let lengths: Vec<u32> = RandomLengths::new(0, 500, 12_000).take(1000).collect();
+        assert!(lengths.iter().all(|mils| (500..=12_000).contains(mils)));
+
+        // Every tenth of the range gets some pieces
+        for tenth in 0..10 {
+            let low = 500 + tenth * 1150;
+            assert!(lengths
+                .iter()
+                .any(|&mils| (low..low + 1150).contains(&mils)));
+        }
+
+        assert!(RandomLengths::new(3, 900, 900)
+            .take(5)
+            .all(|mils| mils == 900));
+    }
+}
@@ -0,0 +1,301 @@
+/* * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * *\
+Copyright (C) 2023 CJ McAllister
+    This program is free software; you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation; either version 3 of the License, or
+    (at your option) any later version.
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+    You should have received a copy of the GNU General Public License
+    along with this program; if not, write to the Free Software Foundation,
+    Inc., 51 Franklin Street, Fifth Floor, Boston, MA 02110-1301  USA
+
+\* * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * */
+
+use crate::profiles::Units;
+use crate::units::Length;
+
+///////////////////////////////////////////////////////////////////////////////
+//  Named Constants
+///////////////////////////////////////////////////////////////////////////////
+
+pub const MAX_JOBS: usize = 8;
+pub const MAX_LABEL_LEN: usize = 11;
+
+///////////////////////////////////////////////////////////////////////////////
+//  Data Structures
+///////////////////////////////////////////////////////////////////////////////
+
+// A run of identical pieces, in the operator's units
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Job {
+    pub cut_length: u32,
+    pub num_cuts: u32,
+    // Pieces the operator takes away at a time, or 0 to take them all at the end
+    pub pieces_per_bundle: u32,
+    label: [u8; MAX_LABEL_LEN],
+    label_len: usize,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum QueueError {
+    Full,
+    NoSuchJob,
+    // The job at the front is being cut, so it can't be changed or overtaken
+    JobActive,
+}
+
+// Jobs waiting to be cut, front first. Once started, the front job is active until finished.
+#[derive(Clone, Debug)]
+pub struct JobQueue {
+    jobs: [Job; MAX_JOBS],
+    len: usize,
+    active: bool,
+}
+
+///////////////////////////////////////////////////////////////////////////////
+//  Object Implementations
+///////////////////////////////////////////////////////////////////////////////
+
+impl Job {
+    pub fn new(cut_length: u32, num_cuts: u32) -> Self {
+        Self {
+            cut_length,
+            num_cuts,
+            pieces_per_bundle: 0,
+            label: [0; MAX_LABEL_LEN],
+            label_len: 0,
+        }
+    }
+
+    // Stop for the operator to take the pieces away every so many, e.g. one bundle per coil or panel
+    pub fn with_bundles(self, pieces_per_bundle: u32) -> Self {
+        Self {
+            pieces_per_bundle,
+            ..self
+        }
+    }
+
+    // Attach a label, e.g. a part number, truncated to fit; anything but printable ASCII is dropped
+    pub fn with_label(mut self, label: &str) -> Self {
+        self.label_len = 0;
+        for byte in label.bytes().filter(|b| (b' '..=b'~').contains(b)) {
+            if self.label_len == MAX_LABEL_LEN {
+                break;
+            }
+            self.label[self.label_len] = byte;
+            self.label_len += 1;
+        }
+
+        self
+    }
+
+    pub fn label(&self) -> &str {
+        // Only ever holds printable ASCII
+        core::str::from_utf8(&self.label[..self.label_len]).unwrap_or("")
+    }
+
+    // Every piece laid end to end, the job's lengths being in `units`
+    pub fn wire(&self, units: Units) -> Length {
+        units.length(self.cut_length).times(self.num_cuts)
+    }
+}
+
+impl QueueError {
+    // Short, LCD-line-sized description of the error
+    pub fn message(&self) -> &'static str {
+        match self {
+            Self::Full => "QUEUE FULL",
+            Self::NoSuchJob => "NO SUCH JOB",
+            Self::JobActive => "JOB IS RUNNING",
+        }
+    }
+}
+
+impl JobQueue {
+    pub fn new() -> Self {
+        Self {
+            jobs: [Job::new(0, 0); MAX_JOBS],
+            len: 0,
+            active: false,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn get(&self, index: usize) -> Option<&Job> {
+        self.jobs[..self.len].get(index)
+    }
+
+    // Wire for every job queued but the one at `except`, e.g. as it's being edited
+    pub fn wire(&self, units: Units, except: Option<usize>) -> Length {
+        self.jobs[..self.len]
+            .iter()
+            .enumerate()
+            .filter(|&(index, _)| Some(index) != except)
+            .fold(Length::ZERO, |wire, (_, job)| wire.plus(job.wire(units)))
+    }
+
+    pub fn is_active(&self, index: usize) -> bool {
+        self.active && index == 0
+    }
+
+    pub fn push(&mut self, job: Job) -> Result<(), QueueError> {
+        if self.len == MAX_JOBS {
+            return Err(QueueError::Full);
+        }
+
+        self.jobs[self.len] = job;
+        self.len += 1;
+        Ok(())
+    }
+
+    pub fn replace(&mut self, index: usize, job: Job) -> Result<(), QueueError> {
+        self.check_editable(index)?;
+        self.jobs[index] = job;
+        Ok(())
+    }
+
+    pub fn remove(&mut self, index: usize) -> Result<Job, QueueError> {
+        self.check_editable(index)?;
+
+        let job = self.jobs[index];
+        self.jobs.copy_within(index + 1..self.len, index);
+        self.len -= 1;
+        Ok(job)
+    }
+
+    // Swap a job with the one ahead of it
+    pub fn move_up(&mut self, index: usize) -> Result<(), QueueError> {
+        self.check_editable(index)?;
+        if index == 0 {
+            return Err(QueueError::NoSuchJob);
+        }
+        self.check_editable(index - 1)?;
+
+        self.jobs.swap(index, index - 1);
+        Ok(())
+    }
+
+    // Swap a job with the one behind it
+    pub fn move_down(&mut self, index: usize) -> Result<(), QueueError> {
+        self.check_editable(index + 1)?;
+        self.move_up(index + 1)
+    }
+
+    // Make the front job active and return it, or None if there's nothing to cut
+    pub fn start(&mut self) -> Option<Job> {
+        let job = self.get(0).copied()?;
+        self.active = true;
+        Some(job)
+    }
+
+    // Drop the active job once it's been cut
+    pub fn finish(&mut self) {
+        if self.active {
+            self.active = false;
+            // Can't fail, the queue held the active job
+            let _ = self.remove(0);
+        }
+    }
+
+    fn check_editable(&self, index: usize) -> Result<(), QueueError> {
+        if index >= self.len {
+            Err(QueueError::NoSuchJob)
+        } else if self.is_active(index) {
+            Err(QueueError::JobActive)
+        } else {
+            Ok(())
+        }
+    }
+}
+
+impl Default for JobQueue {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::units::Unit;
+
+    fn queue_of(lengths: &[u32]) -> JobQueue {
+        let mut queue = JobQueue::new();
+        for &length in lengths {
+            queue.push(Job::new(length, 1)).unwrap();
+        }
+        queue
+    }
+
+    fn lengths(queue: &JobQueue) -> Vec<u32> {
+        (0..queue.len())
+            .map(|i| queue.get(i).unwrap().cut_length)
+            .collect()
+    }
+
+    #[test]
+    fn reorders_and_removes() {
+        let mut queue = queue_of(&[1, 2, 3]);
+        queue.move_up(2).unwrap();
+        assert_eq!(lengths(&queue), [1, 3, 2]);
+        queue.move_down(0).unwrap();
+        assert_eq!(lengths(&queue), [3, 1, 2]);
+        assert_eq!(queue.remove(1).unwrap().cut_length, 1);
+        assert_eq!(lengths(&queue), [3, 2]);
+
+        assert_eq!(queue.move_up(0), Err(QueueError::NoSuchJob));
+        assert_eq!(queue.move_down(1), Err(QueueError::NoSuchJob));
+    }
+
+    #[test]
+    fn active_job_is_protected() {
+        let mut queue = queue_of(&[1, 2, 3]);
+        assert_eq!(queue.start().unwrap().cut_length, 1);
+
+        assert_eq!(queue.remove(0), Err(QueueError::JobActive));
+        assert_eq!(queue.replace(0, Job::new(9, 9)), Err(QueueError::JobActive));
+        assert_eq!(queue.move_down(0), Err(QueueError::JobActive));
+        assert_eq!(queue.move_up(1), Err(QueueError::JobActive));
+
+        // Jobs behind it can still be shuffled
+        queue.move_up(2).unwrap();
+        queue.finish();
+        assert_eq!(lengths(&queue), [3, 2]);
+        assert!(!queue.is_active(0));
+    }
+
+    #[test]
+    fn capacity_is_bounded() {
+        let mut queue = queue_of(&[1; MAX_JOBS]);
+        assert_eq!(queue.push(Job::new(1, 1)), Err(QueueError::Full));
+    }
+
+    #[test]
+    fn wire_is_totalled_across_jobs() {
+        let mut queue = queue_of(&[12, 6]);
+        queue.push(Job::new(3, 4)).unwrap();
+        assert_eq!(queue.wire(Units::Inches, None), Length::new(30, Unit::Inch));
+        assert_eq!(
+            queue.wire(Units::Inches, Some(0)),
+            Length::new(18, Unit::Inch)
+        );
+        assert_eq!(JobQueue::new().wire(Units::Millimeters, None), Length::ZERO);
+    }
+
+    #[test]
+    fn labels_are_truncated_ascii() {
+        let job = Job::new(1, 1).with_label("PANEL-A\u{e9}\tHARNESS-22");
+        assert_eq!(job.label(), "PANEL-AHARN");
+    }
+}
@@ -0,0 +1,101 @@
+/* * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * *\
+Copyright (C) 2023 CJ McAllister
+    This program is free software; you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation; either version 3 of the License, or
+    (at your option) any later version.
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+    You should have received a copy of the GNU General Public License
+    along with this program; if not, write to the Free Software Foundation,
+    Inc., 51 Franklin Street, Fifth Floor, Boston, MA 02110-1301  USA
+
+\* * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * */
+
+// Guided re-zero run once a jam or e-stop is cleared, before the job is allowed to carry on. The blade
+// is opened, the feed re-referenced where the wire now sits, and the sensors checked, each stage
+// shown in turn so the operator can see what the machine is doing and what's holding it up.
+
+///////////////////////////////////////////////////////////////////////////////
+//  Named Constants
+///////////////////////////////////////////////////////////////////////////////
+
+pub const NUM_STAGES: usize = 3;
+
+///////////////////////////////////////////////////////////////////////////////
+//  Data Structures
+///////////////////////////////////////////////////////////////////////////////
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Stage {
+    OpenBlade,
+    ReferenceFeed,
+    // Repeated until nothing's left tripped
+    CheckSensors,
+}
+
+///////////////////////////////////////////////////////////////////////////////
+//  Object Implementations
+///////////////////////////////////////////////////////////////////////////////
+
+impl Stage {
+    // In the order they're run
+    pub const ALL: [Self; NUM_STAGES] = [Self::OpenBlade, Self::ReferenceFeed, Self::CheckSensors];
+
+    // As the LCD's first line, counting the stages through
+    pub fn heading(self) -> &'static str {
+        match self {
+            Self::OpenBlade => "RE-ZERO 1/3",
+            Self::ReferenceFeed => "RE-ZERO 2/3",
+            Self::CheckSensors => "RE-ZERO 3/3",
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::OpenBlade => "OPENING BLADE",
+            Self::ReferenceFeed => "HOMING FEED",
+            Self::CheckSensors => "CHECKING SENSORS",
+        }
+    }
+}
+
+///////////////////////////////////////////////////////////////////////////////
+//  Static Functions
+///////////////////////////////////////////////////////////////////////////////
+
+// What keeps the job from carrying on, if anything. The door comes first, as nothing can move until
+// it's shut.
+pub fn blocker(door_closed: bool, tripped: Option<&'static str>) -> Option<&'static str> {
+    if !door_closed {
+        return Some("DOOR OPEN");
+    }
+
+    tripped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stages_fit_the_lcd_in_order() {
+        for (i, stage) in Stage::ALL.iter().enumerate() {
+            assert!(stage.heading().len() <= 16);
+            assert!(stage.label().len() <= 16);
+            assert!(stage
+                .heading()
+                .contains(&format!("{}/{}", i + 1, NUM_STAGES)));
+        }
+
+        assert_eq!(blocker(true, None), None);
+        assert_eq!(blocker(false, Some("E-STOP TRIPPED")), Some("DOOR OPEN"));
+        assert_eq!(
+            blocker(true, Some("E-STOP TRIPPED")),
+            Some("E-STOP TRIPPED")
+        );
+    }
+}
@@ -0,0 +1,295 @@
+/* * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * *\
+Copyright (C) 2023 CJ McAllister
+    This program is free software; you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation; either version 3 of the License, or
+    (at your option) any later version.
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+    You should have received a copy of the GNU General Public License
+    along with this program; if not, write to the Free Software Foundation,
+    Inc., 51 Franklin Street, Fifth Floor, Boston, MA 02110-1301  USA
+
+\* * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * */
+
+// Key presses recorded with their timings, so an operator's session can be played back through the UI
+// press for press. Sessions travel as text, one `at_ms,key` line per press, and are kept in flash as
+// bytes.
+
+use core::convert::TryInto;
+use core::fmt;
+
+use crate::input::Key;
+
+///////////////////////////////////////////////////////////////////////////////
+//  Named Constants
+///////////////////////////////////////////////////////////////////////////////
+
+pub const MAX_EVENTS: usize = 256;
+
+// Press count, then each press as its key's symbol and its time; padded to a whole number of words
+const HEADER_LEN: usize = 4;
+const EVENT_LEN: usize = 5;
+pub const SERIALIZED_LEN: usize = HEADER_LEN + MAX_EVENTS * EVENT_LEN;
+
+///////////////////////////////////////////////////////////////////////////////
+//  Data Structures
+///////////////////////////////////////////////////////////////////////////////
+
+// A press, timed from the start of the session
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Event {
+    pub key: Key,
+    pub at_ms: u32,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum RecordError {
+    Full,
+    // Presses must be added in the order they happened
+    OutOfOrder,
+    BadLine,
+}
+
+// The presses of one session, oldest first. Once full, later presses are dropped rather than earlier
+// ones, so the recording still plays back from the start of the session.
+#[derive(Clone, Debug)]
+pub struct Recording {
+    events: [Event; MAX_EVENTS],
+    len: usize,
+    dropped: u32,
+}
+
+// Position in a recording being played back
+#[derive(Copy, Clone, Debug, Default)]
+pub struct Player {
+    next: usize,
+}
+
+///////////////////////////////////////////////////////////////////////////////
+//  Object Implementations
+///////////////////////////////////////////////////////////////////////////////
+
+impl Event {
+    // Read an `at_ms,key` line, as written by Display
+    pub fn parse(line: &str) -> Result<Self, RecordError> {
+        let (at_ms, key) = line.split_once(',').ok_or(RecordError::BadLine)?;
+        let key = match key.trim().as_bytes() {
+            [symbol] => Key::from_symbol(*symbol),
+            _ => None,
+        };
+
+        Ok(Self {
+            key: key.ok_or(RecordError::BadLine)?,
+            at_ms: at_ms.trim().parse().map_err(|_| RecordError::BadLine)?,
+        })
+    }
+}
+
+impl fmt::Display for Event {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{},{}", self.at_ms, <&str>::from(self.key))
+    }
+}
+
+impl RecordError {
+    pub fn message(&self) -> &'static str {
+        match self {
+            Self::Full => "recording full",
+            Self::OutOfOrder => "out of order",
+            Self::BadLine => "expected at_ms,key",
+        }
+    }
+}
+
+impl Recording {
+    pub fn new() -> Self {
+        Self {
+            events: [Event {
+                key: Key::Zero,
+                at_ms: 0,
+            }; MAX_EVENTS],
+            len: 0,
+            dropped: 0,
+        }
+    }
+
+    pub fn push(&mut self, event: Event) -> Result<(), RecordError> {
+        if self.len == MAX_EVENTS {
+            self.dropped = self.dropped.saturating_add(1);
+            return Err(RecordError::Full);
+        }
+        if self
+            .events()
+            .last()
+            .is_some_and(|last| last.at_ms > event.at_ms)
+        {
+            return Err(RecordError::OutOfOrder);
+        }
+
+        self.events[self.len] = event;
+        self.len += 1;
+        Ok(())
+    }
+
+    pub fn events(&self) -> &[Event] {
+        &self.events[..self.len]
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    // Presses that didn't fit
+    pub fn dropped(&self) -> u32 {
+        self.dropped
+    }
+
+    // Erased flash, or anything else which isn't a recording, gives None
+    pub fn from_bytes(bytes: &[u8; SERIALIZED_LEN]) -> Option<Self> {
+        let len = u16::from_le_bytes([bytes[0], bytes[1]]) as usize;
+        if len > MAX_EVENTS {
+            return None;
+        }
+
+        let mut recording = Self::new();
+        for chunk in bytes[HEADER_LEN..].chunks_exact(EVENT_LEN).take(len) {
+            let event = Event {
+                key: Key::from_symbol(chunk[0])?,
+                at_ms: u32::from_le_bytes(chunk[1..].try_into().unwrap()),
+            };
+            recording.push(event).ok()?;
+        }
+
+        Some(recording)
+    }
+
+    pub fn to_bytes(&self) -> [u8; SERIALIZED_LEN] {
+        let mut bytes = [0; SERIALIZED_LEN];
+        bytes[..2].copy_from_slice(&(self.len as u16).to_le_bytes());
+        for (chunk, event) in bytes[HEADER_LEN..]
+            .chunks_exact_mut(EVENT_LEN)
+            .zip(self.events())
+        {
+            chunk[0] = <&str>::from(event.key).as_bytes()[0];
+            chunk[1..].copy_from_slice(&event.at_ms.to_le_bytes());
+        }
+
+        bytes
+    }
+}
+
+impl Default for Recording {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Player {
+    pub fn new() -> Self {
+        Self { next: 0 }
+    }
+
+    // The next press, once `elapsed_ms` into the session has reached its time
+    pub fn poll(&mut self, recording: &Recording, elapsed_ms: u32) -> Option<Event> {
+        let event = *recording.events().get(self.next)?;
+        if event.at_ms > elapsed_ms {
+            return None;
+        }
+
+        self.next += 1;
+        Some(event)
+    }
+
+    pub fn is_finished(&self, recording: &Recording) -> bool {
+        self.next >= recording.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn press(key: Key, at_ms: u32) -> Event {
+        Event { key, at_ms }
+    }
+
+    #[test]
+    fn round_trips_through_flash() {
+        let mut recording = Recording::new();
+        recording.push(press(Key::Star, 1200)).unwrap();
+        recording.push(press(Key::Pound, 1200)).unwrap();
+        recording.push(press(Key::Nine, 65_000)).unwrap();
+
+        let decoded = Recording::from_bytes(&recording.to_bytes()).unwrap();
+        assert_eq!(decoded.events(), recording.events());
+        assert_eq!(SERIALIZED_LEN % 4, 0);
+    }
+
+    #[test]
+    fn erased_flash_is_not_a_recording() {
+        assert!(Recording::from_bytes(&[0xFF; SERIALIZED_LEN]).is_none());
+        assert!(Recording::from_bytes(&[0; SERIALIZED_LEN])
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn keeps_the_start_of_long_sessions() {
+        let mut recording = Recording::new();
+        for i in 0..MAX_EVENTS as u32 {
+            recording.push(press(Key::One, i)).unwrap();
+        }
+        assert_eq!(recording.push(press(Key::Two, 999)), Err(RecordError::Full));
+        assert_eq!(recording.dropped(), 1);
+        assert_eq!(recording.events()[0].at_ms, 0);
+    }
+
+    #[test]
+    fn rejects_presses_out_of_order() {
+        let mut recording = Recording::new();
+        recording.push(press(Key::One, 500)).unwrap();
+        assert_eq!(
+            recording.push(press(Key::Two, 499)),
+            Err(RecordError::OutOfOrder)
+        );
+    }
+
+    #[test]
+    fn lines_round_trip() {
+        let event = press(Key::Pound, 31_250);
+        let line = format!("{}", event);
+        assert_eq!(line, "31250,#");
+        assert_eq!(Event::parse(&line), Ok(event));
+        assert_eq!(Event::parse(" 7 , 0 "), Ok(press(Key::Zero, 7)));
+        assert_eq!(Event::parse("7,A"), Err(RecordError::BadLine));
+        assert_eq!(Event::parse("7,12"), Err(RecordError::BadLine));
+        assert_eq!(Event::parse("x,1"), Err(RecordError::BadLine));
+    }
+
+    #[test]
+    fn plays_back_each_press_once_due() {
+        let mut recording = Recording::new();
+        recording.push(press(Key::One, 100)).unwrap();
+        recording.push(press(Key::Pound, 100)).unwrap();
+        recording.push(press(Key::Two, 300)).unwrap();
+
+        let mut player = Player::new();
+        assert_eq!(player.poll(&recording, 99), None);
+        assert_eq!(player.poll(&recording, 150), Some(press(Key::One, 100)));
+        assert_eq!(player.poll(&recording, 150), Some(press(Key::Pound, 100)));
+        assert_eq!(player.poll(&recording, 150), None);
+        assert!(!player.is_finished(&recording));
+        assert_eq!(player.poll(&recording, 1000), Some(press(Key::Two, 300)));
+        assert!(player.is_finished(&recording));
+        assert_eq!(player.poll(&recording, 2000), None);
+    }
+}
@@ -0,0 +1,682 @@
+/* * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * *\
+Copyright (C) 2023 CJ McAllister
+    This program is free software; you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation; either version 3 of the License, or
+    (at your option) any later version.
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+    You should have received a copy of the GNU General Public License
+    along with this program; if not, write to the Free Software Foundation,
+    Inc., 51 Franklin Street, Fifth Floor, Boston, MA 02110-1301  USA
+
+\* * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * */
+
+use core::convert::TryInto;
+
+use crate::charset::Rom;
+use crate::haptic::Haptic;
+use crate::inputs::{InputConfig, InputFunction, PedalAction, MAX_INPUTS};
+use crate::job::Scrap;
+use crate::sound::{QuietHours, Sound, MINUTES_PER_DAY};
+use crate::spool::Spool;
+use crate::transfer;
+use crate::tuning::{Overrides, NUM_PARAMS};
+use crate::units::{Length, Rounding, Unit};
+
+///////////////////////////////////////////////////////////////////////////////
+//  Named Constants
+///////////////////////////////////////////////////////////////////////////////
+
+// ASCII "SETS", marks the page as holding valid settings (erased flash reads 0xFFFFFFFF)
+const SETTINGS_MAGIC: u32 = 0x5345_5453;
+
+// Magic, then the length of the settings that follow and the layout version. Settings added later are
+// appended, and take their defaults when loading a blob saved before they existed. Anything more than
+// that, e.g. a setting changing its units, bumps the version and gets a step in migrate().
+const HEADER_LEN: usize = 8;
+// Blobs saved before the version was added had a 32-bit length, whose high half reads as version 0
+pub const SETTINGS_VERSION: u16 = 2;
+// From this version on, the body is followed by a CRC-32 of the header and body
+const CHECKSUM_VERSION: u16 = 2;
+const CHECKSUM_LEN: usize = 4;
+const INPUT_LEN: usize = 4;
+const INPUTS_LEN: usize = MAX_INPUTS * INPUT_LEN;
+const FEED_LEN: usize = 4;
+const DISPLAY_LEN: usize = 4;
+const KEYPAD_LEN: usize = 4;
+const TUNING_LEN: usize = NUM_PARAMS * 4;
+const PEDAL_LEN: usize = 4;
+const SCRAP_LEN: usize = 8;
+const MARKING_LEN: usize = 4;
+const SERVO_LEN: usize = 4;
+const SOUND_LEN: usize = 8;
+const JAMS_LEN: usize = 4;
+const LABELS_LEN: usize = 4;
+const CHECK_LEN: usize = 4;
+const HAPTIC_LEN: usize = 4;
+const STARTUP_LEN: usize = 4;
+const SPOOL_LEN: usize = 8;
+const STUCK_KEY_LEN: usize = 4;
+const LARGE_JOB_LEN: usize = 8;
+const FEED_OFFSET: usize = INPUTS_LEN;
+const DISPLAY_OFFSET: usize = FEED_OFFSET + FEED_LEN;
+const KEYPAD_OFFSET: usize = DISPLAY_OFFSET + DISPLAY_LEN;
+const TUNING_OFFSET: usize = KEYPAD_OFFSET + KEYPAD_LEN;
+const PEDAL_OFFSET: usize = TUNING_OFFSET + TUNING_LEN;
+const SCRAP_OFFSET: usize = PEDAL_OFFSET + PEDAL_LEN;
+const MARKING_OFFSET: usize = SCRAP_OFFSET + SCRAP_LEN;
+const SERVO_OFFSET: usize = MARKING_OFFSET + MARKING_LEN;
+const SOUND_OFFSET: usize = SERVO_OFFSET + SERVO_LEN;
+const JAMS_OFFSET: usize = SOUND_OFFSET + SOUND_LEN;
+const LABELS_OFFSET: usize = JAMS_OFFSET + JAMS_LEN;
+const CHECK_OFFSET: usize = LABELS_OFFSET + LABELS_LEN;
+const HAPTIC_OFFSET: usize = CHECK_OFFSET + CHECK_LEN;
+const STARTUP_OFFSET: usize = HAPTIC_OFFSET + HAPTIC_LEN;
+const SPOOL_OFFSET: usize = STARTUP_OFFSET + STARTUP_LEN;
+const STUCK_KEY_OFFSET: usize = SPOOL_OFFSET + SPOOL_LEN;
+const LARGE_JOB_OFFSET: usize = STUCK_KEY_OFFSET + STUCK_KEY_LEN;
+const BODY_LEN: usize = LARGE_JOB_OFFSET + LARGE_JOB_LEN;
+pub const SERIALIZED_LEN: usize = HEADER_LEN + BODY_LEN + CHECKSUM_LEN;
+
+const INPUT_FLAG_ACTIVE_LOW: u8 = 0x01;
+const KEYPAD_FLAG_ACTIVE_LOW: u8 = 0x01;
+const KEYPAD_FLAG_ROW_PULLUPS: u8 = 0x02;
+const SOUND_FLAG_SILENT: u8 = 0x01;
+const JAMS_FLAG_REPLACE: u8 = 0x01;
+const LABELS_FLAG_PRINT: u8 = 0x01;
+const CHECK_FLAG_FIRST_PIECE: u8 = 0x01;
+const HAPTIC_FLAG_KEYS: u8 = 0x01;
+const HAPTIC_FLAG_JOB_DONE: u8 = 0x02;
+// Stored in place of a tuning override to keep the build default
+const TUNING_DEFAULT: u32 = u32::MAX;
+
+// Cutter servo frame rates. Analog servos want the standard 50Hz; many digital ones take updates up to
+// 333Hz, and respond sooner for it.
+pub const MIN_SERVO_RATE_HZ: u16 = 50;
+pub const MAX_SERVO_RATE_HZ: u16 = 333;
+
+// How long a key has to stay down at power-on to be taken for stuck, and how often it's sampled
+// meanwhile and while waiting for keys to be let go. Panels with stiff or bouncy switches may want
+// a longer threshold; a poll much finer than a switch settles only costs bus traffic.
+pub const MIN_STUCK_KEY_MS: u16 = 200;
+pub const MAX_STUCK_KEY_MS: u16 = 10_000;
+pub const MIN_STUCK_KEY_POLL_MS: u16 = 10;
+pub const MAX_STUCK_KEY_POLL_MS: u16 = 200;
+
+// Jobs with more pieces or more wire than these need a second confirmation, against fat-fingered
+// counts. Past the most cuts a job can have, only the wire sets it off.
+pub const MIN_LARGE_JOB_CUTS: u32 = 10;
+pub const MAX_LARGE_JOB_CUTS: u32 = 99_999;
+pub const MIN_LARGE_JOB_WIRE_MM: u32 = 1_000;
+pub const MAX_LARGE_JOB_WIRE_MM: u32 = 10_000_000;
+
+///////////////////////////////////////////////////////////////////////////////
+//  Data Structures
+///////////////////////////////////////////////////////////////////////////////
+
+// What the machine does at power-on
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Startup {
+    // The splash, then the choice of operator, then a new job
+    Greeting = 0,
+    // Straight to entering a new job, prefilled with the active operator's last one to confirm again.
+    // Nothing is kept of a job cut short, so this isn't a resume.
+    RepeatJob = 1,
+    // Straight to the job queue screen, with nothing queued
+    Queue = 2,
+}
+
+// Machine configuration, common to all operators
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Settings {
+    pub inputs: [InputConfig; MAX_INPUTS],
+    // Extra feed steps to take up slack when the feed reverses or the wire is released
+    pub feed_backlash_steps: u16,
+    // Character ROM of the fitted LCD
+    pub lcd_rom: Rom,
+    // Keypad rows read low while pressed, with the scanned column driven low
+    pub keypad_active_low: bool,
+    // Keypad rows need the expander's pull-ups, having none of their own
+    pub keypad_row_pullups: bool,
+    // Tunable parameters changed from their build defaults
+    pub tuning: Overrides,
+    // What the foot switch inputs do
+    pub pedal_action: PedalAction,
+    // Wire discarded at the start and end of each job
+    pub scrap: Scrap,
+    // Pieces between stops for the operator to mark them, or 0 to never stop
+    pub mark_every: u32,
+    // How often the cutter servo is sent its position
+    pub servo_rate_hz: u16,
+    // Silent mode and quiet hours
+    pub sound: Sound,
+    // Make up pieces scrapped by a jam or abort at the end of the job, so it still delivers its count
+    pub replace_scrapped: bool,
+    // Send a label for each finished job's bundle over serial, for a label printer
+    pub print_labels: bool,
+    // Stop after each job's first piece is fed for the operator to approve its length as measured
+    pub check_first_piece: bool,
+    // What the vibration motor runs for, on builds with one fitted
+    pub haptic: Haptic,
+    pub startup: Startup,
+    // Wire on the spool, counted down as jobs use it up
+    pub spool: Spool,
+    // Time a key has to be held at power-on to halt start-up as stuck
+    pub stuck_key_ms: u16,
+    // Time between samples of the keypad while checking for stuck keys or waiting for a release
+    pub stuck_key_poll_ms: u16,
+    // Jobs past which a second confirmation is asked for
+    pub large_job: LargeJob,
+}
+
+// A job with more pieces than this, or more wire, asks to be confirmed a second time
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct LargeJob {
+    pub cuts: u32,
+    pub wire_mm: u32,
+}
+
+///////////////////////////////////////////////////////////////////////////////
+//  Object Implementations
+///////////////////////////////////////////////////////////////////////////////
+
+impl Settings {
+    // Decode settings, or None if the bytes don't hold saved settings (e.g. erased flash) or they've
+    // been corrupted since
+    pub fn from_bytes(bytes: &[u8; SERIALIZED_LEN]) -> Option<Self> {
+        let magic = u32::from_le_bytes(bytes[0..4].try_into().unwrap());
+        if magic != SETTINGS_MAGIC {
+            return None;
+        }
+
+        // There's no knowing what a later layout means
+        let version = u16::from_le_bytes(bytes[6..8].try_into().unwrap());
+        if version > SETTINGS_VERSION {
+            return None;
+        }
+        if !has_valid_checksum(bytes) {
+            return None;
+        }
+
+        let mut settings = Self::default();
+        let body_len = u16::from_le_bytes(bytes[4..6].try_into().unwrap()) as usize;
+        let mut migrated = [0; BODY_LEN];
+        let body = &mut migrated[..body_len.min(BODY_LEN)];
+        body.copy_from_slice(&bytes[HEADER_LEN..HEADER_LEN + body.len()]);
+        migrate(version, body);
+        let body = &*body;
+
+        for (input, bytes) in settings.inputs.iter_mut().zip(body.chunks_exact(INPUT_LEN)) {
+            *input = InputConfig {
+                function: InputFunction::from(bytes[0]),
+                psel: bytes[1],
+                active_low: bytes[2] & INPUT_FLAG_ACTIVE_LOW != 0,
+            };
+        }
+
+        if let Some(feed) = body.get(FEED_OFFSET..FEED_OFFSET + FEED_LEN) {
+            settings.feed_backlash_steps = u16::from_le_bytes(feed[0..2].try_into().unwrap());
+        }
+        if let Some(display) = body.get(DISPLAY_OFFSET..DISPLAY_OFFSET + DISPLAY_LEN) {
+            settings.lcd_rom = Rom::from(display[0]);
+        }
+        if let Some(keypad) = body.get(KEYPAD_OFFSET..KEYPAD_OFFSET + KEYPAD_LEN) {
+            settings.keypad_active_low = keypad[0] & KEYPAD_FLAG_ACTIVE_LOW != 0;
+            settings.keypad_row_pullups = keypad[0] & KEYPAD_FLAG_ROW_PULLUPS != 0;
+        }
+        if let Some(tuning) = body.get(TUNING_OFFSET..TUNING_OFFSET + TUNING_LEN) {
+            for (value, bytes) in settings.tuning.iter_mut().zip(tuning.chunks_exact(4)) {
+                let raw = u32::from_le_bytes(bytes.try_into().unwrap());
+                *value = (raw != TUNING_DEFAULT).then_some(raw);
+            }
+        }
+        if let Some(pedal) = body.get(PEDAL_OFFSET..PEDAL_OFFSET + PEDAL_LEN) {
+            settings.pedal_action = PedalAction::from(pedal[0]);
+        }
+        if let Some(scrap) = body.get(SCRAP_OFFSET..SCRAP_OFFSET + SCRAP_LEN) {
+            settings.scrap = Scrap {
+                leader_mils: u32::from_le_bytes(scrap[0..4].try_into().unwrap()),
+                trailer_mils: u32::from_le_bytes(scrap[4..8].try_into().unwrap()),
+            };
+        }
+        if let Some(marking) = body.get(MARKING_OFFSET..MARKING_OFFSET + MARKING_LEN) {
+            settings.mark_every = u32::from_le_bytes(marking.try_into().unwrap());
+        }
+        if let Some(servo) = body.get(SERVO_OFFSET..SERVO_OFFSET + SERVO_LEN) {
+            let rate_hz = u16::from_le_bytes(servo[0..2].try_into().unwrap());
+            if (MIN_SERVO_RATE_HZ..=MAX_SERVO_RATE_HZ).contains(&rate_hz) {
+                settings.servo_rate_hz = rate_hz;
+            }
+        }
+        if let Some(sound) = body.get(SOUND_OFFSET..SOUND_OFFSET + SOUND_LEN) {
+            settings.sound.enabled = sound[0] & SOUND_FLAG_SILENT == 0;
+            let start = u16::from_le_bytes(sound[2..4].try_into().unwrap());
+            let end = u16::from_le_bytes(sound[4..6].try_into().unwrap());
+            if start < MINUTES_PER_DAY && end < MINUTES_PER_DAY {
+                settings.sound.quiet_hours = QuietHours { start, end };
+            }
+        }
+        if let Some(jams) = body.get(JAMS_OFFSET..JAMS_OFFSET + JAMS_LEN) {
+            settings.replace_scrapped = jams[0] & JAMS_FLAG_REPLACE != 0;
+        }
+        if let Some(labels) = body.get(LABELS_OFFSET..LABELS_OFFSET + LABELS_LEN) {
+            settings.print_labels = labels[0] & LABELS_FLAG_PRINT != 0;
+        }
+        if let Some(check) = body.get(CHECK_OFFSET..CHECK_OFFSET + CHECK_LEN) {
+            settings.check_first_piece = check[0] & CHECK_FLAG_FIRST_PIECE != 0;
+        }
+        if let Some(haptic) = body.get(HAPTIC_OFFSET..HAPTIC_OFFSET + HAPTIC_LEN) {
+            settings.haptic = Haptic {
+                keys: haptic[0] & HAPTIC_FLAG_KEYS != 0,
+                job_done: haptic[0] & HAPTIC_FLAG_JOB_DONE != 0,
+            };
+        }
+        if let Some(startup) = body.get(STARTUP_OFFSET..STARTUP_OFFSET + STARTUP_LEN) {
+            settings.startup = Startup::from(startup[0]);
+        }
+        if let Some(spool) = body.get(SPOOL_OFFSET..SPOOL_OFFSET + SPOOL_LEN) {
+            settings.spool = Spool {
+                loaded_mm: u32::from_le_bytes(spool[0..4].try_into().unwrap()),
+                used_before_mm: u32::from_le_bytes(spool[4..8].try_into().unwrap()),
+            };
+        }
+        if let Some(stuck_key) = body.get(STUCK_KEY_OFFSET..STUCK_KEY_OFFSET + STUCK_KEY_LEN) {
+            let threshold_ms = u16::from_le_bytes(stuck_key[0..2].try_into().unwrap());
+            if (MIN_STUCK_KEY_MS..=MAX_STUCK_KEY_MS).contains(&threshold_ms) {
+                settings.stuck_key_ms = threshold_ms;
+            }
+            let poll_ms = u16::from_le_bytes(stuck_key[2..4].try_into().unwrap());
+            if (MIN_STUCK_KEY_POLL_MS..=MAX_STUCK_KEY_POLL_MS).contains(&poll_ms) {
+                settings.stuck_key_poll_ms = poll_ms;
+            }
+        }
+        if let Some(large_job) = body.get(LARGE_JOB_OFFSET..LARGE_JOB_OFFSET + LARGE_JOB_LEN) {
+            let cuts = u32::from_le_bytes(large_job[0..4].try_into().unwrap());
+            if (MIN_LARGE_JOB_CUTS..=MAX_LARGE_JOB_CUTS).contains(&cuts) {
+                settings.large_job.cuts = cuts;
+            }
+            let wire_mm = u32::from_le_bytes(large_job[4..8].try_into().unwrap());
+            if (MIN_LARGE_JOB_WIRE_MM..=MAX_LARGE_JOB_WIRE_MM).contains(&wire_mm) {
+                settings.large_job.wire_mm = wire_mm;
+            }
+        }
+
+        Some(settings)
+    }
+
+    // Saved settings which decode, but not as this build would save them: an older version, or missing
+    // sections. Worth saving again so the flash holds the current layout.
+    pub fn is_outdated(bytes: &[u8; SERIALIZED_LEN]) -> bool {
+        let body_len = u16::from_le_bytes(bytes[4..6].try_into().unwrap()) as usize;
+        let version = u16::from_le_bytes(bytes[6..8].try_into().unwrap());
+        Self::from_bytes(bytes).is_some() && (version < SETTINGS_VERSION || body_len < BODY_LEN)
+    }
+
+    // Saved settings this build should understand, but whose checksum doesn't match, e.g. after a save
+    // was cut short. Unlike erased flash, these mustn't quietly give way to defaults.
+    pub fn is_corrupt(bytes: &[u8; SERIALIZED_LEN]) -> bool {
+        let magic = u32::from_le_bytes(bytes[0..4].try_into().unwrap());
+        let version = u16::from_le_bytes(bytes[6..8].try_into().unwrap());
+        magic == SETTINGS_MAGIC && version <= SETTINGS_VERSION && !has_valid_checksum(bytes)
+    }
+
+    pub fn to_bytes(&self) -> [u8; SERIALIZED_LEN] {
+        let mut bytes = [0; SERIALIZED_LEN];
+        bytes[0..4].copy_from_slice(&SETTINGS_MAGIC.to_le_bytes());
+        bytes[4..6].copy_from_slice(&(BODY_LEN as u16).to_le_bytes());
+        bytes[6..8].copy_from_slice(&SETTINGS_VERSION.to_le_bytes());
+
+        let inputs = &mut bytes[HEADER_LEN..HEADER_LEN + INPUTS_LEN];
+        for (input, bytes) in self.inputs.iter().zip(inputs.chunks_exact_mut(INPUT_LEN)) {
+            bytes[0] = input.function as u8;
+            bytes[1] = input.psel;
+            bytes[2] = if input.active_low {
+                INPUT_FLAG_ACTIVE_LOW
+            } else {
+                0
+            };
+        }
+
+        let body = &mut bytes[HEADER_LEN..];
+        body[FEED_OFFSET..FEED_OFFSET + 2].copy_from_slice(&self.feed_backlash_steps.to_le_bytes());
+        body[DISPLAY_OFFSET] = self.lcd_rom as u8;
+        if self.keypad_active_low {
+            body[KEYPAD_OFFSET] |= KEYPAD_FLAG_ACTIVE_LOW;
+        }
+        if self.keypad_row_pullups {
+            body[KEYPAD_OFFSET] |= KEYPAD_FLAG_ROW_PULLUPS;
+        }
+        let tuning = &mut body[TUNING_OFFSET..TUNING_OFFSET + TUNING_LEN];
+        for (value, bytes) in self.tuning.iter().zip(tuning.chunks_exact_mut(4)) {
+            bytes.copy_from_slice(&value.unwrap_or(TUNING_DEFAULT).to_le_bytes());
+        }
+        body[PEDAL_OFFSET] = self.pedal_action as u8;
+        body[SCRAP_OFFSET..SCRAP_OFFSET + 4].copy_from_slice(&self.scrap.leader_mils.to_le_bytes());
+        body[SCRAP_OFFSET + 4..SCRAP_OFFSET + 8]
+            .copy_from_slice(&self.scrap.trailer_mils.to_le_bytes());
+        body[MARKING_OFFSET..MARKING_OFFSET + MARKING_LEN]
+            .copy_from_slice(&self.mark_every.to_le_bytes());
+        body[SERVO_OFFSET..SERVO_OFFSET + 2].copy_from_slice(&self.servo_rate_hz.to_le_bytes());
+        if !self.sound.enabled {
+            body[SOUND_OFFSET] |= SOUND_FLAG_SILENT;
+        }
+        body[SOUND_OFFSET + 2..SOUND_OFFSET + 4]
+            .copy_from_slice(&self.sound.quiet_hours.start.to_le_bytes());
+        body[SOUND_OFFSET + 4..SOUND_OFFSET + 6]
+            .copy_from_slice(&self.sound.quiet_hours.end.to_le_bytes());
+        if self.replace_scrapped {
+            body[JAMS_OFFSET] |= JAMS_FLAG_REPLACE;
+        }
+        if self.print_labels {
+            body[LABELS_OFFSET] |= LABELS_FLAG_PRINT;
+        }
+        if self.check_first_piece {
+            body[CHECK_OFFSET] |= CHECK_FLAG_FIRST_PIECE;
+        }
+        if self.haptic.keys {
+            body[HAPTIC_OFFSET] |= HAPTIC_FLAG_KEYS;
+        }
+        if self.haptic.job_done {
+            body[HAPTIC_OFFSET] |= HAPTIC_FLAG_JOB_DONE;
+        }
+        body[STARTUP_OFFSET] = self.startup as u8;
+        body[SPOOL_OFFSET..SPOOL_OFFSET + 4].copy_from_slice(&self.spool.loaded_mm.to_le_bytes());
+        body[SPOOL_OFFSET + 4..SPOOL_OFFSET + 8]
+            .copy_from_slice(&self.spool.used_before_mm.to_le_bytes());
+        body[STUCK_KEY_OFFSET..STUCK_KEY_OFFSET + 2]
+            .copy_from_slice(&self.stuck_key_ms.to_le_bytes());
+        body[STUCK_KEY_OFFSET + 2..STUCK_KEY_OFFSET + 4]
+            .copy_from_slice(&self.stuck_key_poll_ms.to_le_bytes());
+        body[LARGE_JOB_OFFSET..LARGE_JOB_OFFSET + 4]
+            .copy_from_slice(&self.large_job.cuts.to_le_bytes());
+        body[LARGE_JOB_OFFSET + 4..LARGE_JOB_OFFSET + 8]
+            .copy_from_slice(&self.large_job.wire_mm.to_le_bytes());
+
+        let checksum = transfer::crc32(&bytes[..HEADER_LEN + BODY_LEN]);
+        bytes[HEADER_LEN + BODY_LEN..].copy_from_slice(&checksum.to_le_bytes());
+
+        bytes
+    }
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            inputs: [InputConfig::UNUSED; MAX_INPUTS],
+            feed_backlash_steps: 0,
+            lcd_rom: Rom::A00,
+            keypad_active_low: false,
+            keypad_row_pullups: false,
+            tuning: [None; NUM_PARAMS],
+            pedal_action: PedalAction::StartJob,
+            scrap: Scrap::default(),
+            mark_every: 0,
+            servo_rate_hz: MIN_SERVO_RATE_HZ,
+            sound: Sound::DEFAULT,
+            replace_scrapped: true,
+            print_labels: false,
+            check_first_piece: false,
+            haptic: Haptic::DEFAULT,
+            startup: Startup::Greeting,
+            spool: Spool::NONE,
+            stuck_key_ms: 1000,
+            stuck_key_poll_ms: 50,
+            large_job: LargeJob::DEFAULT,
+        }
+    }
+}
+
+impl LargeJob {
+    pub const DEFAULT: Self = Self {
+        cuts: 500,
+        wire_mm: 50_000,
+    };
+
+    // Whether a job of this many pieces, taking this much wire, needs its second confirmation
+    pub fn exceeded_by(&self, num_cuts: u32, wire: Length) -> bool {
+        num_cuts > self.cuts || wire.to(Unit::Millimeter, Rounding::Up) > self.wire_mm
+    }
+}
+
+impl From<u8> for Startup {
+    fn from(value: u8) -> Self {
+        match value {
+            1 => Self::RepeatJob,
+            2 => Self::Queue,
+            _ => Self::Greeting,
+        }
+    }
+}
+
+///////////////////////////////////////////////////////////////////////////////
+//  Helper Functions
+///////////////////////////////////////////////////////////////////////////////
+
+// Bring a body saved at the given version up to the current layout, one version at a time
+fn migrate(version: u16, _body: &mut [u8]) {
+    for from in version..SETTINGS_VERSION {
+        match from {
+            // Only the header changed, gaining the version
+            0 => {}
+            // Only the checksum was added, which is checked before migrating
+            1 => {}
+            _ => unreachable!("no migration from settings version {}", from),
+        }
+    }
+}
+
+// Blobs from before the checksum have nothing to check. A later build's longer body carries its
+// checksum beyond what this build reads, so it's taken on trust as before.
+fn has_valid_checksum(bytes: &[u8; SERIALIZED_LEN]) -> bool {
+    let version = u16::from_le_bytes(bytes[6..8].try_into().unwrap());
+    let body_len = u16::from_le_bytes(bytes[4..6].try_into().unwrap()) as usize;
+    if version < CHECKSUM_VERSION || body_len > BODY_LEN {
+        return true;
+    }
+
+    let (covered, rest) = bytes.split_at(HEADER_LEN + body_len);
+    let saved = u32::from_le_bytes(rest[..CHECKSUM_LEN].try_into().unwrap());
+    transfer::crc32(covered) == saved
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn codec_round_trips() {
+        let mut settings = Settings::default();
+        settings.inputs[3] = InputConfig {
+            function: InputFunction::WireRunout,
+            psel: 0x23,
+            active_low: false,
+        };
+        settings.feed_backlash_steps = 7;
+        settings.lcd_rom = Rom::A02;
+        settings.keypad_active_low = true;
+        settings.tuning[1] = Some(45);
+        settings.pedal_action = PedalAction::Pause;
+        settings.scrap.trailer_mils = 2500;
+        settings.mark_every = 10;
+        settings.servo_rate_hz = 200;
+        settings.sound = Sound {
+            enabled: false,
+            quiet_hours: QuietHours {
+                start: 1320,
+                end: 420,
+            },
+        };
+        settings.replace_scrapped = false;
+        settings.print_labels = true;
+        settings.check_first_piece = true;
+        settings.haptic.keys = false;
+        settings.startup = Startup::Queue;
+        settings.spool = Spool {
+            loaded_mm: 305_000,
+            used_before_mm: 1_234_567,
+        };
+        settings.stuck_key_ms = 2500;
+        settings.stuck_key_poll_ms = 20;
+        settings.large_job = LargeJob {
+            cuts: 2000,
+            wire_mm: 120_000,
+        };
+
+        assert_eq!(Settings::from_bytes(&settings.to_bytes()), Some(settings));
+    }
+
+    #[test]
+    fn erased_flash_has_no_settings() {
+        assert!(Settings::from_bytes(&[0xFF; SERIALIZED_LEN]).is_none());
+        assert!(!Settings::is_corrupt(&[0xFF; SERIALIZED_LEN]));
+    }
+
+    #[test]
+    fn corruption_is_caught_by_the_checksum() {
+        let settings = Settings {
+            feed_backlash_steps: 7,
+            ..Settings::default()
+        };
+        let mut bytes = settings.to_bytes();
+        assert!(!Settings::is_corrupt(&bytes));
+
+        bytes[HEADER_LEN + FEED_OFFSET] ^= 0x04;
+        assert!(Settings::from_bytes(&bytes).is_none());
+        assert!(Settings::is_corrupt(&bytes));
+
+        // Including in the header, as a length cut short moves where the checksum is looked for
+        let mut bytes = settings.to_bytes();
+        bytes[4..6].copy_from_slice(&(BODY_LEN as u16 - 4).to_le_bytes());
+        assert!(Settings::is_corrupt(&bytes));
+    }
+
+    #[test]
+    fn older_versions_are_upgraded() {
+        let settings = Settings {
+            feed_backlash_steps: 7,
+            mark_every: 5,
+            ..Settings::default()
+        };
+        let current = settings.to_bytes();
+        assert!(!Settings::is_outdated(&current));
+
+        // As saved before the header had a version, with a 32-bit length
+        let mut bytes = current;
+        bytes[4..8].copy_from_slice(&(BODY_LEN as u32).to_le_bytes());
+        assert_eq!(Settings::from_bytes(&bytes), Some(settings));
+        assert!(Settings::is_outdated(&bytes));
+
+        // As saved before the checksum, with whatever happened to follow the body
+        bytes[6..8].copy_from_slice(&1u16.to_le_bytes());
+        bytes[HEADER_LEN + BODY_LEN..].fill(0xFF);
+        assert_eq!(Settings::from_bytes(&bytes), Some(settings));
+        assert!(Settings::is_outdated(&bytes) && !Settings::is_corrupt(&bytes));
+
+        // A later build's layout is left alone
+        bytes[6..8].copy_from_slice(&(SETTINGS_VERSION + 1).to_le_bytes());
+        assert!(Settings::from_bytes(&bytes).is_none());
+        assert!(!Settings::is_outdated(&bytes));
+        assert!(!Settings::is_outdated(&[0xFF; SERIALIZED_LEN]));
+    }
+
+    #[test]
+    fn short_blobs_take_defaults() {
+        let mut settings = Settings::default();
+        settings.inputs[0].function = InputFunction::Door;
+        settings.inputs[7].function = InputFunction::Door;
+        settings.feed_backlash_steps = 7;
+        settings.lcd_rom = Rom::A02;
+        settings.keypad_active_low = true;
+        settings.keypad_row_pullups = true;
+        settings.tuning[2] = Some(1200);
+        settings.pedal_action = PedalAction::SingleCut;
+        settings.scrap.leader_mils = 4000;
+        settings.mark_every = 5;
+        settings.servo_rate_hz = 333;
+        settings.sound.enabled = false;
+        settings.replace_scrapped = false;
+        settings.print_labels = true;
+        settings.check_first_piece = true;
+        settings.haptic.job_done = false;
+        settings.startup = Startup::RepeatJob;
+        settings.spool.loaded_mm = 150_000;
+        settings.stuck_key_ms = 3000;
+        settings.large_job.cuts = 50;
+
+        // As saved by a build that only knew of the first input
+        let mut bytes = settings.to_bytes();
+        bytes[4..6].copy_from_slice(&(INPUT_LEN as u16).to_le_bytes());
+        let checksum = transfer::crc32(&bytes[..HEADER_LEN + INPUT_LEN]);
+        bytes[HEADER_LEN + INPUT_LEN..][..CHECKSUM_LEN].copy_from_slice(&checksum.to_le_bytes());
+
+        let decoded = Settings::from_bytes(&bytes).unwrap();
+        assert_eq!(decoded.inputs[0].function, InputFunction::Door);
+        assert_eq!(decoded.inputs[7].function, InputFunction::Unused);
+        assert_eq!(decoded.feed_backlash_steps, 0);
+        assert_eq!(decoded.lcd_rom, Rom::A00);
+        assert!(!decoded.keypad_active_low && !decoded.keypad_row_pullups);
+        assert_eq!(decoded.tuning, [None; NUM_PARAMS]);
+        assert_eq!(decoded.pedal_action, PedalAction::StartJob);
+        assert_eq!(decoded.scrap, Scrap::default());
+        assert_eq!(decoded.mark_every, 0);
+        assert_eq!(decoded.servo_rate_hz, MIN_SERVO_RATE_HZ);
+        assert_eq!(decoded.sound, Sound::DEFAULT);
+        assert!(decoded.replace_scrapped);
+        assert!(!decoded.print_labels);
+        assert!(!decoded.check_first_piece);
+        assert_eq!(decoded.haptic, Haptic::DEFAULT);
+        assert_eq!(decoded.startup, Startup::Greeting);
+        assert_eq!(decoded.spool, Spool::NONE);
+        assert_eq!(decoded.stuck_key_ms, Settings::default().stuck_key_ms);
+        assert_eq!(decoded.large_job, LargeJob::DEFAULT);
+    }
+
+    #[test]
+    fn unusable_servo_rates_take_default() {
+        let settings = Settings {
+            servo_rate_hz: MAX_SERVO_RATE_HZ + 1,
+            ..Settings::default()
+        };
+        let decoded = Settings::from_bytes(&settings.to_bytes()).unwrap();
+        assert_eq!(decoded.servo_rate_hz, MIN_SERVO_RATE_HZ);
+    }
+
+    #[test]
+    fn unusable_stuck_key_timing_takes_default() {
+        let settings = Settings {
+            stuck_key_ms: MIN_STUCK_KEY_MS - 1,
+            stuck_key_poll_ms: 0,
+            ..Settings::default()
+        };
+        let decoded = Settings::from_bytes(&settings.to_bytes()).unwrap();
+        assert_eq!(decoded.stuck_key_ms, Settings::default().stuck_key_ms);
+        assert_eq!(
+            decoded.stuck_key_poll_ms,
+            Settings::default().stuck_key_poll_ms
+        );
+    }
+
+    #[test]
+    fn large_jobs_exceed_either_threshold() {
+        let large_job = LargeJob::DEFAULT;
+        assert!(!large_job.exceeded_by(500, Length::new(50, Unit::Meter)));
+        assert!(large_job.exceeded_by(501, Length::new(1, Unit::Meter)));
+        assert!(large_job.exceeded_by(10, Length::new(50_001, Unit::Millimeter)));
+
+        // An unusable threshold left in flash falls back to the default
+        let settings = Settings {
+            large_job: LargeJob {
+                cuts: MIN_LARGE_JOB_CUTS - 1,
+                wire_mm: MAX_LARGE_JOB_WIRE_MM + 1,
+            },
+            ..Settings::default()
+        };
+        let decoded = Settings::from_bytes(&settings.to_bytes()).unwrap();
+        assert_eq!(decoded.large_job, LargeJob::DEFAULT);
+    }
+}
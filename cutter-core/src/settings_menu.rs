@@ -0,0 +1,671 @@
+/* * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * *\
+Copyright (C) 2023 CJ McAllister
+    This program is free software; you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation; either version 3 of the License, or
+    (at your option) any later version.
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+    You should have received a copy of the GNU General Public License
+    along with this program; if not, write to the Free Software Foundation,
+    Inc., 51 Franklin Street, Fifth Floor, Boston, MA 02110-1301  USA
+
+\* * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * */
+
+// What the settings menu offers, grouped into pages by category. Each item is a number or one of a few
+// choices, read from and written to wherever it's kept: the machine settings, the active operator's
+// profile, or the tuning.
+
+use core::fmt;
+use core::ops::RangeInclusive;
+
+use crate::charset::Rom;
+use crate::inputs::PedalAction;
+use crate::label::{Date, MAX_YEAR, MIN_YEAR};
+use crate::profiles::{Profile, Units, MAX_FEED_SPEED_PCT, MIN_FEED_SPEED_PCT};
+use crate::settings::{
+    Settings, Startup, MAX_LARGE_JOB_CUTS, MAX_SERVO_RATE_HZ, MAX_STUCK_KEY_MS,
+    MAX_STUCK_KEY_POLL_MS, MIN_LARGE_JOB_CUTS, MIN_SERVO_RATE_HZ, MIN_STUCK_KEY_MS,
+    MIN_STUCK_KEY_POLL_MS,
+};
+use crate::sound::{self, MAX_HHMM};
+use crate::spool::Spool;
+use crate::tuning::{Param, TuneError, Tuning};
+use crate::units::{Length, Rounding, Unit};
+
+///////////////////////////////////////////////////////////////////////////////
+//  Named Constants
+///////////////////////////////////////////////////////////////////////////////
+
+pub const NUM_CATEGORIES: usize = 6;
+
+// Choices, in the order of their stored values
+const UNITS_CHOICES: [&str; 2] = ["in", "mm"];
+const SWITCH_CHOICES: [&str; 2] = ["OFF", "ON"];
+const ROM_CHOICES: [&str; 2] = ["A00", "A02"];
+const MELODY_CHOICES: [&str; 3] = ["RISING", "FANFARE", "BEEPS"];
+const PEDAL_CHOICES: [&str; 3] = ["START JOB", "SINGLE CUT", "PAUSE"];
+const STARTUP_CHOICES: [&str; 3] = ["GREETING", "REPEAT JOB", "JOB QUEUE"];
+
+// Beyond these the feed backlash calibration gives up, so there's no sense entering more
+const MAX_BACKLASH_STEPS: u32 = 100;
+// Two feet of wire at either end of a job is already a lot to throw away
+const MAX_SCRAP_MILS: u32 = 24_000;
+const MAX_MARK_EVERY: u32 = 9999;
+// In feet or metres, as the operator's units go; more than any spool a bench machine takes
+const MAX_SPOOL: u32 = 99_999;
+// Also in feet or metres; both ends sit inside what the settings keep
+const MIN_LARGE_JOB_WIRE: u32 = 10;
+const MAX_LARGE_JOB_WIRE: u32 = 9_999;
+
+const UNITS_ITEMS: [Item; 1] = [Item::Units];
+const MOTION_ITEMS: [Item; 4] = [
+    Item::FeedSpeedPct,
+    Item::Tuned(Param::FeedSpeed),
+    Item::Tuned(Param::FeedAccel),
+    Item::FeedBacklash,
+];
+const CUTTER_ITEMS: [Item; 11] = [
+    Item::Tuned(Param::CutClosedDuty),
+    Item::Tuned(Param::CutOpenDuty),
+    Item::Tuned(Param::CutDwellMs),
+    Item::Tuned(Param::BladeClearanceMs),
+    Item::ServoRateHz,
+    Item::LeaderMils,
+    Item::TrailerMils,
+    Item::MarkEvery,
+    Item::ReplaceScrapped,
+    Item::PrintLabels,
+    Item::FirstPieceCheck,
+];
+const DISPLAY_ITEMS: [Item; 2] = [Item::BigDigits, Item::LcdRom];
+const SOUNDS_ITEMS: [Item; 9] = [
+    Item::SoundOn,
+    Item::CompletionMelody,
+    Item::BuzzKeys,
+    Item::BuzzJobDone,
+    Item::QuietFrom,
+    Item::QuietTo,
+    Item::TimeNow,
+    Item::YearNow,
+    Item::DateNow,
+];
+const MAINTENANCE_ITEMS: [Item; 8] = [
+    Item::PedalAction,
+    Item::Startup,
+    Item::SpoolLoaded,
+    Item::LargeJobCuts,
+    Item::LargeJobWire,
+    Item::Tuned(Param::KeyDebounceUs),
+    Item::StuckKeyMs,
+    Item::StuckKeyPollMs,
+];
+
+///////////////////////////////////////////////////////////////////////////////
+//  Data Structures
+///////////////////////////////////////////////////////////////////////////////
+
+// A page of the settings menu
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Category {
+    Units,
+    Motion,
+    Cutter,
+    Display,
+    Sounds,
+    Maintenance,
+}
+
+// One value the operator can change
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Item {
+    // Operator profile
+    Units,
+    FeedSpeedPct,
+    BigDigits,
+    CompletionMelody,
+    // Machine settings
+    FeedBacklash,
+    LeaderMils,
+    TrailerMils,
+    MarkEvery,
+    ReplaceScrapped,
+    PrintLabels,
+    FirstPieceCheck,
+    ServoRateHz,
+    LcdRom,
+    PedalAction,
+    Startup,
+    // Wire on a spool just loaded, in the operator's feet or metres, or 0 to stop counting it down
+    SpoolLoaded,
+    // Past either, a job is confirmed twice; the wire is in the operator's feet or metres
+    LargeJobCuts,
+    LargeJobWire,
+    // The power-on check for stuck keys; the poll also paces waits for keys to be let go
+    StuckKeyMs,
+    StuckKeyPollMs,
+    SoundOn,
+    QuietFrom,
+    QuietTo,
+    BuzzKeys,
+    BuzzJobDone,
+    // The clock for quiet hours, and the calendar for labels, kept only until power-off
+    TimeNow,
+    YearNow,
+    DateNow,
+    Tuned(Param),
+}
+
+// How an item's value is entered
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Kind {
+    // Digits on the keypad, within the range
+    Number(RangeInclusive<u32>),
+    // Stepped through in turn, the value being the index of the choice
+    Choice(&'static [&'static str]),
+}
+
+// Where a changed item has to be saved
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Store {
+    Settings,
+    Profile,
+    // Not saved, only set on the running clock
+    Clock,
+}
+
+// Everything the menu edits
+pub struct Editable<'a> {
+    pub settings: &'a mut Settings,
+    pub profile: &'a mut Profile,
+    pub tuning: &'a mut Tuning,
+    // Minutes since midnight, or None if the clock hasn't been set
+    pub time_of_day: &'a mut Option<u16>,
+    // None if the calendar hasn't been set
+    pub date: &'a mut Option<Date>,
+    // The job history's lifetime wire total, which a spool's counted down from
+    pub wire_used_mm: u32,
+}
+
+///////////////////////////////////////////////////////////////////////////////
+//  Object Implementations
+///////////////////////////////////////////////////////////////////////////////
+
+impl Category {
+    pub const ALL: [Category; NUM_CATEGORIES] = [
+        Category::Units,
+        Category::Motion,
+        Category::Cutter,
+        Category::Display,
+        Category::Sounds,
+        Category::Maintenance,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Category::Units => "UNITS",
+            Category::Motion => "MOTION",
+            Category::Cutter => "CUTTER",
+            Category::Display => "DISPLAY",
+            Category::Sounds => "SOUNDS",
+            Category::Maintenance => "MAINTENANCE",
+        }
+    }
+
+    pub fn items(self) -> &'static [Item] {
+        match self {
+            Category::Units => &UNITS_ITEMS,
+            Category::Motion => &MOTION_ITEMS,
+            Category::Cutter => &CUTTER_ITEMS,
+            Category::Display => &DISPLAY_ITEMS,
+            Category::Sounds => &SOUNDS_ITEMS,
+            Category::Maintenance => &MAINTENANCE_ITEMS,
+        }
+    }
+}
+
+impl Item {
+    // As written on the serial console, where the menu's items are also set on machines without a
+    // keypad. Tuned items go by their parameter's name.
+    pub fn name(self) -> &'static str {
+        match self {
+            Item::Units => "units",
+            Item::FeedSpeedPct => "feed_speed_pct",
+            Item::BigDigits => "big_digits",
+            Item::CompletionMelody => "done_melody",
+            Item::FeedBacklash => "backlash_steps",
+            Item::LeaderMils => "leader_mils",
+            Item::TrailerMils => "trailer_mils",
+            Item::MarkEvery => "mark_every",
+            Item::ReplaceScrapped => "replace_scrap",
+            Item::PrintLabels => "print_labels",
+            Item::FirstPieceCheck => "check_first_piece",
+            Item::ServoRateHz => "cut_servo_hz",
+            Item::SoundOn => "sound",
+            Item::QuietFrom => "quiet_from_hhmm",
+            Item::QuietTo => "quiet_to_hhmm",
+            Item::BuzzKeys => "buzz_keys",
+            Item::BuzzJobDone => "buzz_done",
+            Item::TimeNow => "time_now_hhmm",
+            Item::YearNow => "year_now",
+            Item::DateNow => "date_now_mmdd",
+            Item::LcdRom => "lcd_rom",
+            Item::PedalAction => "foot_switch",
+            Item::Startup => "startup",
+            Item::SpoolLoaded => "spool_loaded",
+            Item::LargeJobCuts => "large_job_cuts",
+            Item::LargeJobWire => "large_job_wire",
+            Item::StuckKeyMs => "stuck_key_ms",
+            Item::StuckKeyPollMs => "stuck_key_poll_ms",
+            Item::Tuned(param) => param.name(),
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Category::ALL
+            .iter()
+            .flat_map(|category| category.items())
+            .copied()
+            .find(|item| item.name().eq_ignore_ascii_case(name))
+    }
+
+    // Fits the top row of the display
+    pub fn label(self) -> &'static str {
+        match self {
+            Item::Units => "LENGTH UNITS",
+            Item::FeedSpeedPct => "FEED SPEED %",
+            Item::BigDigits => "BIG DIGITS",
+            Item::CompletionMelody => "DONE MELODY",
+            Item::FeedBacklash => "BACKLASH STEPS",
+            Item::LeaderMils => "LEADER MILS",
+            Item::TrailerMils => "TRAILER MILS",
+            Item::MarkEvery => "MARK EVERY N",
+            Item::ReplaceScrapped => "REPLACE SCRAP",
+            Item::PrintLabels => "PRINT LABELS",
+            Item::FirstPieceCheck => "CHECK 1ST PIECE",
+            Item::ServoRateHz => "CUT SERVO HZ",
+            Item::SoundOn => "SOUND",
+            Item::QuietFrom => "QUIET FROM HHMM",
+            Item::QuietTo => "QUIET TO HHMM",
+            Item::BuzzKeys => "BUZZ ON KEYS",
+            Item::BuzzJobDone => "BUZZ WHEN DONE",
+            Item::TimeNow => "TIME NOW HHMM",
+            Item::YearNow => "YEAR NOW",
+            Item::DateNow => "DATE NOW MMDD",
+            Item::LcdRom => "LCD CHAR ROM",
+            Item::PedalAction => "FOOT SWITCH",
+            Item::Startup => "AT POWER ON",
+            Item::SpoolLoaded => "SPOOL LOADED",
+            Item::LargeJobCuts => "LARGE JOB CUTS",
+            Item::LargeJobWire => "LARGE JOB WIRE",
+            Item::StuckKeyMs => "STUCK KEY MS",
+            Item::StuckKeyPollMs => "STUCK POLL MS",
+            Item::Tuned(Param::CutClosedDuty) => "BLADE CLOSED",
+            Item::Tuned(Param::CutOpenDuty) => "BLADE OPEN",
+            Item::Tuned(Param::CutDwellMs) => "CUT DWELL MS",
+            Item::Tuned(Param::BladeClearanceMs) => "CLEARANCE MS",
+            Item::Tuned(Param::FeedSpeed) => "MAX FEED MIL/S",
+            Item::Tuned(Param::FeedAccel) => "FEED ACC MIL/S2",
+            Item::Tuned(Param::KeyDebounceUs) => "KEY DEBOUNCE US",
+        }
+    }
+
+    pub fn kind(self) -> Kind {
+        match self {
+            Item::Units => Kind::Choice(&UNITS_CHOICES),
+            Item::BigDigits
+            | Item::SoundOn
+            | Item::ReplaceScrapped
+            | Item::PrintLabels
+            | Item::FirstPieceCheck
+            | Item::BuzzKeys
+            | Item::BuzzJobDone => Kind::Choice(&SWITCH_CHOICES),
+            Item::CompletionMelody => Kind::Choice(&MELODY_CHOICES),
+            Item::LcdRom => Kind::Choice(&ROM_CHOICES),
+            Item::PedalAction => Kind::Choice(&PEDAL_CHOICES),
+            Item::Startup => Kind::Choice(&STARTUP_CHOICES),
+            Item::FeedSpeedPct => {
+                Kind::Number(MIN_FEED_SPEED_PCT as u32..=MAX_FEED_SPEED_PCT as u32)
+            }
+            Item::FeedBacklash => Kind::Number(0..=MAX_BACKLASH_STEPS),
+            Item::LeaderMils | Item::TrailerMils => Kind::Number(0..=MAX_SCRAP_MILS),
+            Item::MarkEvery => Kind::Number(0..=MAX_MARK_EVERY),
+            Item::SpoolLoaded => Kind::Number(0..=MAX_SPOOL),
+            Item::LargeJobCuts => Kind::Number(MIN_LARGE_JOB_CUTS..=MAX_LARGE_JOB_CUTS),
+            Item::LargeJobWire => Kind::Number(MIN_LARGE_JOB_WIRE..=MAX_LARGE_JOB_WIRE),
+            Item::QuietFrom | Item::QuietTo | Item::TimeNow => Kind::Number(0..=MAX_HHMM),
+            Item::YearNow => Kind::Number(MIN_YEAR as u32..=MAX_YEAR as u32),
+            Item::DateNow => Kind::Number(101..=1231),
+            Item::ServoRateHz => Kind::Number(MIN_SERVO_RATE_HZ as u32..=MAX_SERVO_RATE_HZ as u32),
+            Item::StuckKeyMs => Kind::Number(MIN_STUCK_KEY_MS as u32..=MAX_STUCK_KEY_MS as u32),
+            Item::StuckKeyPollMs => {
+                Kind::Number(MIN_STUCK_KEY_POLL_MS as u32..=MAX_STUCK_KEY_POLL_MS as u32)
+            }
+            Item::Tuned(param) => Kind::Number(param.range()),
+        }
+    }
+
+    pub fn store(self) -> Store {
+        match self {
+            Item::Units | Item::FeedSpeedPct | Item::BigDigits | Item::CompletionMelody => {
+                Store::Profile
+            }
+            Item::TimeNow | Item::YearNow | Item::DateNow => Store::Clock,
+            _ => Store::Settings,
+        }
+    }
+}
+
+impl Kind {
+    pub fn accepts(&self, value: u32) -> bool {
+        match self {
+            Kind::Number(range) => range.contains(&value),
+            Kind::Choice(choices) => (value as usize) < choices.len(),
+        }
+    }
+
+    // The choice after `value`, wrapping around to the first
+    pub fn next(&self, value: u32) -> u32 {
+        match self {
+            Kind::Number(_) => value,
+            Kind::Choice(choices) => (value + 1) % choices.len() as u32,
+        }
+    }
+
+    // A value as the console writes it: a number, or a choice with `_` for its spaces, in any case.
+    // Not checked against the range, which set() does.
+    pub fn parse(&self, word: &str) -> Option<u32> {
+        match self {
+            Kind::Number(_) => word.parse().ok(),
+            Kind::Choice(choices) => choices
+                .iter()
+                .position(|choice| {
+                    choice.len() == word.len()
+                        && choice
+                            .bytes()
+                            .zip(word.bytes())
+                            .all(|(c, w)| c.eq_ignore_ascii_case(&w) || (c == b' ' && w == b'_'))
+                })
+                .map(|index| index as u32),
+        }
+    }
+
+    // As parse() reads it back
+    pub fn write_value(&self, value: u32, out: &mut dyn fmt::Write) -> fmt::Result {
+        match self {
+            Kind::Number(_) => write!(out, "{}", value),
+            Kind::Choice(choices) => {
+                for c in choices[value as usize].chars() {
+                    out.write_char(if c == ' ' { '_' } else { c })?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl Editable<'_> {
+    pub fn get(&self, item: Item) -> u32 {
+        match item {
+            Item::Units => self.profile.units as u32,
+            Item::FeedSpeedPct => self.profile.feed_speed_pct as u32,
+            Item::BigDigits => self.profile.big_digits as u32,
+            // Out-of-range melodies are wrapped by the player, so show them the same way
+            Item::CompletionMelody => {
+                self.profile.completion_melody as u32 % MELODY_CHOICES.len() as u32
+            }
+            Item::FeedBacklash => self.settings.feed_backlash_steps as u32,
+            Item::LeaderMils => self.settings.scrap.leader_mils,
+            Item::TrailerMils => self.settings.scrap.trailer_mils,
+            Item::MarkEvery => self.settings.mark_every,
+            Item::ReplaceScrapped => self.settings.replace_scrapped as u32,
+            Item::PrintLabels => self.settings.print_labels as u32,
+            Item::FirstPieceCheck => self.settings.check_first_piece as u32,
+            Item::ServoRateHz => self.settings.servo_rate_hz as u32,
+            Item::StuckKeyMs => self.settings.stuck_key_ms as u32,
+            Item::StuckKeyPollMs => self.settings.stuck_key_poll_ms as u32,
+            Item::SoundOn => self.settings.sound.enabled as u32,
+            Item::QuietFrom => sound::to_hhmm(self.settings.sound.quiet_hours.start),
+            Item::QuietTo => sound::to_hhmm(self.settings.sound.quiet_hours.end),
+            Item::BuzzKeys => self.settings.haptic.keys as u32,
+            Item::BuzzJobDone => self.settings.haptic.job_done as u32,
+            Item::TimeNow => self.time_of_day.map_or(0, sound::to_hhmm),
+            Item::YearNow => self.date.map_or(0, |date| date.year as u32),
+            Item::DateNow => self.date.map_or(0, Date::mmdd),
+            Item::LcdRom => self.settings.lcd_rom as u32,
+            Item::PedalAction => self.settings.pedal_action as u32,
+            Item::Startup => self.settings.startup as u32,
+            Item::SpoolLoaded => Length::new(self.settings.spool.loaded_mm, Unit::Millimeter)
+                .to(self.profile.units.spool_unit(), Rounding::Nearest),
+            Item::LargeJobCuts => self.settings.large_job.cuts,
+            Item::LargeJobWire => Length::new(self.settings.large_job.wire_mm, Unit::Millimeter)
+                .to(self.profile.units.spool_unit(), Rounding::Nearest),
+            Item::Tuned(param) => self.tuning.get(param),
+        }
+    }
+
+    // Change an item, returning where it has to be saved
+    pub fn set(&mut self, item: Item, value: u32) -> Result<Store, TuneError> {
+        if !item.kind().accepts(value) {
+            return Err(TuneError::OutOfRange);
+        }
+
+        match item {
+            Item::Units => {
+                let units = Units::from(value as u8);
+                if units != self.profile.units {
+                    self.profile.units = units;
+
+                    // Lengths from the last job are meaningless in the other units
+                    self.profile.last_cut_length = 0;
+                }
+            }
+            Item::FeedSpeedPct => self.profile.feed_speed_pct = value as u8,
+            Item::BigDigits => self.profile.big_digits = value != 0,
+            Item::CompletionMelody => self.profile.completion_melody = value as u8,
+            Item::FeedBacklash => self.settings.feed_backlash_steps = value as u16,
+            Item::LeaderMils => self.settings.scrap.leader_mils = value,
+            Item::TrailerMils => self.settings.scrap.trailer_mils = value,
+            Item::MarkEvery => self.settings.mark_every = value,
+            Item::ReplaceScrapped => self.settings.replace_scrapped = value != 0,
+            Item::PrintLabels => self.settings.print_labels = value != 0,
+            Item::FirstPieceCheck => self.settings.check_first_piece = value != 0,
+            Item::ServoRateHz => self.settings.servo_rate_hz = value as u16,
+            Item::StuckKeyMs => self.settings.stuck_key_ms = value as u16,
+            Item::StuckKeyPollMs => self.settings.stuck_key_poll_ms = value as u16,
+            Item::SoundOn => self.settings.sound.enabled = value != 0,
+            Item::BuzzKeys => self.settings.haptic.keys = value != 0,
+            Item::BuzzJobDone => self.settings.haptic.job_done = value != 0,
+            Item::QuietFrom | Item::QuietTo | Item::TimeNow => {
+                let minute_of_day = sound::from_hhmm(value).ok_or(TuneError::OutOfRange)?;
+                match item {
+                    Item::QuietFrom => self.settings.sound.quiet_hours.start = minute_of_day,
+                    Item::QuietTo => self.settings.sound.quiet_hours.end = minute_of_day,
+                    _ => *self.time_of_day = Some(minute_of_day),
+                }
+            }
+            // Either half of the date can be set first, starting from New Year's Day
+            Item::YearNow => {
+                let date = self.date.unwrap_or(Date::FIRST);
+                *self.date = Some(date.with_year(value as u16).ok_or(TuneError::OutOfRange)?);
+            }
+            Item::DateNow => {
+                let date = self.date.unwrap_or(Date::FIRST);
+                *self.date = Some(date.with_mmdd(value).ok_or(TuneError::OutOfRange)?);
+            }
+            Item::LcdRom => self.settings.lcd_rom = Rom::from(value as u8),
+            Item::PedalAction => self.settings.pedal_action = PedalAction::from(value as u8),
+            Item::Startup => self.settings.startup = Startup::from(value as u8),
+            Item::SpoolLoaded => {
+                self.settings.spool = Spool {
+                    loaded_mm: Length::new(value, self.profile.units.spool_unit())
+                        .to(Unit::Millimeter, Rounding::Nearest),
+                    used_before_mm: self.wire_used_mm,
+                }
+            }
+            Item::LargeJobCuts => self.settings.large_job.cuts = value,
+            Item::LargeJobWire => {
+                self.settings.large_job.wire_mm =
+                    Length::new(value, self.profile.units.spool_unit())
+                        .to(Unit::Millimeter, Rounding::Nearest)
+            }
+            Item::Tuned(param) => {
+                self.tuning.set(param, value)?;
+                self.settings.tuning = self.tuning.overrides();
+            }
+        }
+
+        Ok(item.store())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::profiles::Profiles;
+    use crate::tuning::NUM_PARAMS;
+
+    const DEFAULTS: [u32; NUM_PARAMS] = [120, 30, 1500, 200, 4000, 8000, 250];
+
+    #[test]
+    fn every_item_fits_the_display() {
+        for category in Category::ALL {
+            assert!(!category.items().is_empty());
+            for item in category.items() {
+                assert!(item.label().len() <= 16, "{:?}", item);
+                if let Kind::Choice(choices) = item.kind() {
+                    assert!(choices.iter().all(|choice| choice.len() <= 16));
+                }
+            }
+        }
+
+        // Every tunable parameter has a home
+        for param in Param::ALL {
+            let item = Item::Tuned(param);
+            assert!(Category::ALL.iter().any(|c| c.items().contains(&item)));
+        }
+    }
+
+    #[test]
+    fn items_and_values_round_trip_as_text() {
+        for category in Category::ALL {
+            for &item in category.items() {
+                assert_eq!(Item::from_name(item.name()), Some(item));
+                if let Kind::Choice(choices) = item.kind() {
+                    for value in 0..choices.len() as u32 {
+                        let mut text = String::new();
+                        item.kind().write_value(value, &mut text).unwrap();
+                        assert_eq!(item.kind().parse(&text), Some(value));
+                    }
+                }
+            }
+        }
+        assert_eq!(
+            Item::from_name("CUT_DWELL_MS"),
+            Some(Item::Tuned(Param::CutDwellMs))
+        );
+        assert_eq!(Item::from_name("cut_dwell"), None);
+
+        let pedal = Item::PedalAction.kind();
+        assert_eq!(pedal.parse("single_cut"), Some(1));
+        assert_eq!(pedal.parse("SINGLE CUT"), Some(1));
+        assert_eq!(pedal.parse("single"), None);
+        assert_eq!(Item::MarkEvery.kind().parse("25"), Some(25));
+        assert_eq!(Item::MarkEvery.kind().parse("ON"), None);
+    }
+
+    #[test]
+    fn edits_reach_their_store() {
+        let mut settings = Settings::default();
+        let mut profiles = Profiles::default();
+        profiles.active_mut().last_cut_length = 12;
+        let mut tuning = Tuning::new(DEFAULTS);
+        let mut editable = Editable {
+            settings: &mut settings,
+            profile: profiles.active_mut(),
+            tuning: &mut tuning,
+            time_of_day: &mut None,
+            date: &mut None,
+            wire_used_mm: 0,
+        };
+
+        // Lengths from the last job don't carry over to the other units
+        assert_eq!(editable.set(Item::Units, 1), Ok(Store::Profile));
+        assert_eq!(editable.profile.units, Units::Millimeters);
+        assert_eq!(editable.profile.last_cut_length, 0);
+
+        assert_eq!(editable.set(Item::PedalAction, 2), Ok(Store::Settings));
+        assert_eq!(editable.settings.pedal_action, PedalAction::Pause);
+        assert_eq!(editable.set(Item::Startup, 2), Ok(Store::Settings));
+        assert_eq!(editable.settings.startup, Startup::Queue);
+
+        // Spools are loaded in metres here, and counted down from the wire used so far
+        editable.wire_used_mm = 40_000;
+        assert_eq!(editable.set(Item::SpoolLoaded, 300), Ok(Store::Settings));
+        assert_eq!(
+            editable.settings.spool,
+            Spool {
+                loaded_mm: 300_000,
+                used_before_mm: 40_000,
+            }
+        );
+        assert_eq!(editable.get(Item::SpoolLoaded), 300);
+        assert_eq!(editable.set(Item::LargeJobWire, 80), Ok(Store::Settings));
+        assert_eq!(editable.settings.large_job.wire_mm, 80_000);
+        assert_eq!(editable.get(Item::LargeJobWire), 80);
+
+        let dwell = Item::Tuned(Param::CutDwellMs);
+        assert_eq!(editable.set(dwell, 1200), Ok(Store::Settings));
+        assert_eq!(editable.get(dwell), 1200);
+        assert_eq!(
+            editable.settings.tuning[Param::CutDwellMs as usize],
+            Some(1200)
+        );
+
+        // Times are entered as HHMM, and the clock is never saved
+        assert_eq!(editable.set(Item::QuietFrom, 2230), Ok(Store::Settings));
+        assert_eq!(editable.settings.sound.quiet_hours.start, 22 * 60 + 30);
+        assert_eq!(editable.set(Item::TimeNow, 905), Ok(Store::Clock));
+        assert_eq!(*editable.time_of_day, Some(9 * 60 + 5));
+        assert_eq!(editable.get(Item::TimeNow), 905);
+
+        // The date can be set a half at a time, and only to real days
+        assert_eq!(editable.set(Item::DateNow, 229), Ok(Store::Clock));
+        assert_eq!(editable.set(Item::YearNow, 2025), Ok(Store::Clock));
+        assert_eq!(*editable.date, Date::new(2025, 2, 28));
+        assert_eq!(editable.set(Item::DateNow, 229), Err(TuneError::OutOfRange));
+        assert_eq!(editable.get(Item::DateNow), 228);
+    }
+
+    #[test]
+    fn rejects_values_out_of_range() {
+        let mut settings = Settings::default();
+        let mut profiles = Profiles::default();
+        let mut tuning = Tuning::new(DEFAULTS);
+        let mut editable = Editable {
+            settings: &mut settings,
+            profile: profiles.active_mut(),
+            tuning: &mut tuning,
+            time_of_day: &mut None,
+            date: &mut None,
+            wire_used_mm: 0,
+        };
+
+        assert_eq!(editable.set(Item::LcdRom, 2), Err(TuneError::OutOfRange));
+        assert_eq!(
+            editable.set(Item::QuietTo, 1260),
+            Err(TuneError::OutOfRange)
+        );
+        assert_eq!(
+            editable.set(Item::FeedSpeedPct, 5),
+            Err(TuneError::OutOfRange)
+        );
+        assert_eq!(editable.get(Item::FeedSpeedPct), 100);
+        assert_eq!(Item::CompletionMelody.kind().next(2), 0);
+    }
+}
@@ -0,0 +1,95 @@
+/* * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * *\
+Copyright (C) 2023 CJ McAllister
+    This program is free software; you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation; either version 3 of the License, or
+    (at your option) any later version.
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+    You should have received a copy of the GNU General Public License
+    along with this program; if not, write to the Free Software Foundation,
+    Inc., 51 Franklin Street, Fifth Floor, Boston, MA 02110-1301  USA
+
+\* * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * */
+
+///////////////////////////////////////////////////////////////////////////////
+//  Data Structures
+///////////////////////////////////////////////////////////////////////////////
+
+// One of the two bins the piece chute can divert into
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Bin {
+    A,
+    B,
+}
+
+// How a job's pieces are shared between the bins
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum SortRule {
+    // Every piece into the one bin
+    Single(Bin),
+    // Swap bins every `size` pieces, to bundle a long job
+    Batches { size: u32 },
+    // The first `count` pieces into bin A and the rest into bin B, e.g. the two rungs of a cut ladder
+    Split { count: u32 },
+}
+
+///////////////////////////////////////////////////////////////////////////////
+//  Object Implementations
+///////////////////////////////////////////////////////////////////////////////
+
+impl SortRule {
+    // Bin for the given piece of a job, counting from 1
+    pub fn bin_for(&self, piece: u32) -> Bin {
+        match *self {
+            Self::Single(bin) => bin,
+            Self::Batches { size } => {
+                if size == 0 || (piece.saturating_sub(1) / size).is_multiple_of(2) {
+                    Bin::A
+                } else {
+                    Bin::B
+                }
+            }
+            Self::Split { count } => {
+                if piece <= count {
+                    Bin::A
+                } else {
+                    Bin::B
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn batches_alternate() {
+        let rule = SortRule::Batches { size: 3 };
+        let bins: Vec<_> = (1..=7).map(|piece| rule.bin_for(piece)).collect();
+        assert_eq!(
+            bins,
+            [Bin::A, Bin::A, Bin::A, Bin::B, Bin::B, Bin::B, Bin::A]
+        );
+    }
+
+    #[test]
+    fn empty_batches_never_swap() {
+        let rule = SortRule::Batches { size: 0 };
+        assert!((1..=10).all(|piece| rule.bin_for(piece) == Bin::A));
+    }
+
+    #[test]
+    fn split_after_count() {
+        let rule = SortRule::Split { count: 2 };
+        assert_eq!(rule.bin_for(2), Bin::A);
+        assert_eq!(rule.bin_for(3), Bin::B);
+        assert_eq!(SortRule::Single(Bin::B).bin_for(1), Bin::B);
+    }
+}
@@ -0,0 +1,170 @@
+/* * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * *\
+Copyright (C) 2023 CJ McAllister
+    This program is free software; you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation; either version 3 of the License, or
+    (at your option) any later version.
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+    You should have received a copy of the GNU General Public License
+    along with this program; if not, write to the Free Software Foundation,
+    Inc., 51 Franklin Street, Fifth Floor, Boston, MA 02110-1301  USA
+
+\* * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * */
+///////////////////////////////////////////////////////////////////////////////
+//  Named Constants
+///////////////////////////////////////////////////////////////////////////////
+
+// Times of day are minutes since midnight, entered as HHMM
+pub const MINUTES_PER_DAY: u16 = 24 * MINUTES_PER_HOUR;
+pub const MAX_HHMM: u32 = 2359;
+const MINUTES_PER_HOUR: u16 = 60;
+const HHMM_HOURS: u32 = 100;
+
+///////////////////////////////////////////////////////////////////////////////
+//  Data Structures
+///////////////////////////////////////////////////////////////////////////////
+
+// What a sound is for, which decides whether quiet hours hold it back
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Cue {
+    // Something went wrong, and the operator has to know
+    Alert,
+    // Melodies, countdown ticks and other beeps the job doesn't need heard
+    Chime,
+}
+
+// Span of the day the machine keeps quiet, running past midnight if it ends before it starts
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct QuietHours {
+    pub start: u16,
+    pub end: u16,
+}
+
+// When the buzzer may sound
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Sound {
+    // Off silences the buzzer entirely, alerts included
+    pub enabled: bool,
+    pub quiet_hours: QuietHours,
+}
+
+///////////////////////////////////////////////////////////////////////////////
+//  Object Implementations
+///////////////////////////////////////////////////////////////////////////////
+
+impl QuietHours {
+    // Starting and ending at the same time, so never quiet
+    pub const NONE: Self = Self { start: 0, end: 0 };
+
+    pub fn contains(&self, minute_of_day: u16) -> bool {
+        if self.start <= self.end {
+            (self.start..self.end).contains(&minute_of_day)
+        } else {
+            minute_of_day >= self.start || minute_of_day < self.end
+        }
+    }
+}
+
+impl Sound {
+    pub const DEFAULT: Self = Self {
+        enabled: true,
+        quiet_hours: QuietHours::NONE,
+    };
+
+    // Whether a cue may sound at the given time of day. Quiet hours can't apply until the clock is set.
+    pub fn allows(&self, cue: Cue, minute_of_day: Option<u16>) -> bool {
+        match cue {
+            _ if !self.enabled => false,
+            Cue::Alert => true,
+            Cue::Chime => !minute_of_day.is_some_and(|m| self.quiet_hours.contains(m)),
+        }
+    }
+}
+
+impl Default for Sound {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+///////////////////////////////////////////////////////////////////////////////
+//  Static Functions
+///////////////////////////////////////////////////////////////////////////////
+
+// Minutes since midnight from an HHMM time, or None if it isn't one
+pub fn from_hhmm(hhmm: u32) -> Option<u16> {
+    let hours = hhmm / HHMM_HOURS;
+    let minutes = hhmm % HHMM_HOURS;
+    let minute_of_day = hours * MINUTES_PER_HOUR as u32 + minutes;
+    (minutes < MINUTES_PER_HOUR as u32 && minute_of_day < MINUTES_PER_DAY as u32)
+        .then_some(minute_of_day as u16)
+}
+
+pub fn to_hhmm(minute_of_day: u16) -> u32 {
+    let hours = (minute_of_day / MINUTES_PER_HOUR) as u32;
+    let minutes = (minute_of_day % MINUTES_PER_HOUR) as u32;
+    hours * HHMM_HOURS + minutes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const OVERNIGHT: Sound = Sound {
+        enabled: true,
+        quiet_hours: QuietHours {
+            start: 22 * 60,
+            end: 7 * 60,
+        },
+    };
+
+    #[test]
+    fn quiet_hours_run_past_midnight() {
+        let quiet = OVERNIGHT.quiet_hours;
+        assert!(quiet.contains(23 * 60));
+        assert!(quiet.contains(0));
+        assert!(quiet.contains(7 * 60 - 1));
+        assert!(!quiet.contains(7 * 60));
+        assert!(!quiet.contains(12 * 60));
+
+        let daytime = QuietHours {
+            start: 12 * 60,
+            end: 13 * 60,
+        };
+        assert!(daytime.contains(12 * 60 + 30));
+        assert!(!daytime.contains(23 * 60));
+        assert!(!QuietHours::NONE.contains(0));
+    }
+
+    #[test]
+    fn alerts_sound_through_quiet_hours_but_not_silence() {
+        let night = Some(23 * 60);
+        assert!(!OVERNIGHT.allows(Cue::Chime, night));
+        assert!(OVERNIGHT.allows(Cue::Alert, night));
+        assert!(OVERNIGHT.allows(Cue::Chime, Some(9 * 60)));
+
+        // Without the time of day, there's no telling it's night
+        assert!(OVERNIGHT.allows(Cue::Chime, None));
+
+        let silent = Sound {
+            enabled: false,
+            ..OVERNIGHT
+        };
+        assert!(!silent.allows(Cue::Alert, Some(9 * 60)));
+        assert!(!silent.allows(Cue::Chime, None));
+    }
+
+    #[test]
+    fn hhmm_round_trips() {
+        assert_eq!(from_hhmm(2230), Some(22 * 60 + 30));
+        assert_eq!(from_hhmm(0), Some(0));
+        assert_eq!(from_hhmm(1275), None);
+        assert_eq!(from_hhmm(2400), None);
+        assert_eq!(to_hhmm(7 * 60 + 5), 705);
+        assert_eq!(to_hhmm(from_hhmm(MAX_HHMM).unwrap()), MAX_HHMM);
+    }
+}
@@ -0,0 +1,70 @@
+/* * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * *\
+Copyright (C) 2023 CJ McAllister
+    This program is free software; you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation; either version 3 of the License, or
+    (at your option) any later version.
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+    You should have received a copy of the GNU General Public License
+    along with this program; if not, write to the Free Software Foundation,
+    Inc., 51 Franklin Street, Fifth Floor, Boston, MA 02110-1301  USA
+
+\* * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * */
+
+// The spool feeding the machine, counted down from what was loaded by the wire the job history has
+// totalled since. The history's totals survive power-off and its oldest pages being erased, so the
+// count does too without flash being written for every piece.
+
+use crate::units::{Length, Unit};
+
+///////////////////////////////////////////////////////////////////////////////
+//  Data Structures
+///////////////////////////////////////////////////////////////////////////////
+
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Spool {
+    // Wire on it when it was loaded, or 0 if it isn't being counted
+    pub loaded_mm: u32,
+    // The history's lifetime wire total at the time
+    pub used_before_mm: u32,
+}
+
+///////////////////////////////////////////////////////////////////////////////
+//  Object Implementations
+///////////////////////////////////////////////////////////////////////////////
+
+impl Spool {
+    pub const NONE: Self = Self {
+        loaded_mm: 0,
+        used_before_mm: 0,
+    };
+
+    // Wire still on it, given the history's lifetime total now, or None if it isn't being counted
+    pub fn left(&self, used_mm: u32) -> Option<Length> {
+        (self.loaded_mm > 0).then(|| {
+            let used_mm = used_mm.saturating_sub(self.used_before_mm);
+            Length::new(self.loaded_mm.saturating_sub(used_mm), Unit::Millimeter)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn spools_count_down_from_loading() {
+        let spool = Spool {
+            loaded_mm: 150_000,
+            used_before_mm: 1_000_000,
+        };
+        assert_eq!(spool.left(1_000_000), Some(Length::new(150, Unit::Meter)));
+        assert_eq!(spool.left(1_100_000), Some(Length::new(50, Unit::Meter)));
+        assert_eq!(spool.left(2_000_000), Some(Length::ZERO));
+        assert_eq!(Spool::NONE.left(1_000), None);
+    }
+}
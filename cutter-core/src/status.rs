@@ -0,0 +1,136 @@
+/* * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * *\
+Copyright (C) 2023 CJ McAllister
+    This program is free software; you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation; either version 3 of the License, or
+    (at your option) any later version.
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+    You should have received a copy of the GNU General Public License
+    along with this program; if not, write to the Free Software Foundation,
+    Inc., 51 Franklin Street, Fifth Floor, Boston, MA 02110-1301  USA
+
+\* * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * */
+
+use core::fmt::{self, Write};
+
+use crate::profiles::Units;
+
+///////////////////////////////////////////////////////////////////////////////
+//  Data Structures
+///////////////////////////////////////////////////////////////////////////////
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum MachineState {
+    // Operator is choosing a profile or entering a job
+    Setup,
+    Cutting,
+    Finished,
+    // Job stopped by a fault
+    Halted,
+}
+
+// Snapshot of the machine for status reporting
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Status {
+    pub state: MachineState,
+    pub cut_length: u32,
+    pub units: Units,
+    pub num_cuts: u32,
+    // Pieces completed so far
+    pub piece: u32,
+    pub error: Option<&'static str>,
+}
+
+///////////////////////////////////////////////////////////////////////////////
+//  Object Implementations
+///////////////////////////////////////////////////////////////////////////////
+
+impl MachineState {
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Setup => "setup",
+            Self::Cutting => "cutting",
+            Self::Finished => "finished",
+            Self::Halted => "halted",
+        }
+    }
+}
+
+impl Status {
+    pub fn new(units: Units) -> Self {
+        Self {
+            state: MachineState::Setup,
+            cut_length: 0,
+            units,
+            num_cuts: 0,
+            piece: 0,
+            error: None,
+        }
+    }
+
+    pub fn wire_used_mils(&self) -> u64 {
+        self.units.to_mils(self.cut_length) as u64 * self.piece as u64
+    }
+
+    // Write the status as a single line of JSON, including the trailing newline
+    pub fn write_json_line(&self, out: &mut impl Write) -> fmt::Result {
+        write!(
+            out,
+            "{{\"state\":\"{}\",\"job\":{{\"cut_length\":{},\"units\":\"{}\",\"num_cuts\":{}}},\"piece\":{},\"wire_used_mils\":{},\"error\":",
+            self.state.label(),
+            self.cut_length,
+            self.units.label(),
+            self.num_cuts,
+            self.piece,
+            self.wire_used_mils(),
+        )?;
+
+        // Error messages are fixed uppercase strings, so need no escaping
+        match self.error {
+            Some(message) => writeln!(out, "\"{}\"}}", message),
+            None => out.write_str("null}\n"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn json_line_format() {
+        let status = Status {
+            state: MachineState::Cutting,
+            cut_length: 12,
+            units: Units::Inches,
+            num_cuts: 100,
+            piece: 5,
+            error: None,
+        };
+
+        let mut line = String::new();
+        status.write_json_line(&mut line).unwrap();
+        assert_eq!(
+            line,
+            "{\"state\":\"cutting\",\"job\":{\"cut_length\":12,\"units\":\"in\",\"num_cuts\":100},\"piece\":5,\"wire_used_mils\":60000,\"error\":null}\n"
+        );
+    }
+
+    #[test]
+    fn json_line_includes_error() {
+        let status = Status {
+            state: MachineState::Halted,
+            error: Some("BLADE NOT CLOSED"),
+            ..Status::new(Units::Millimeters)
+        };
+
+        let mut line = String::new();
+        status.write_json_line(&mut line).unwrap();
+        assert!(line.ends_with(",\"error\":\"BLADE NOT CLOSED\"}\n"));
+        assert_eq!(line.matches('\n').count(), 1);
+    }
+}
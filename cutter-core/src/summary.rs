@@ -0,0 +1,140 @@
+/* * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * *\
+Copyright (C) 2023 CJ McAllister
+    This program is free software; you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation; either version 3 of the License, or
+    (at your option) any later version.
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+    You should have received a copy of the GNU General Public License
+    along with this program; if not, write to the Free Software Foundation,
+    Inc., 51 Franklin Street, Fifth Floor, Boston, MA 02110-1301  USA
+
+\* * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * */
+
+// What a job will take, shown to the operator before it's confirmed: its pieces and wire, how long it's
+// likely to run, and what it'll leave on the spool once the jobs queued ahead of it have run.
+
+use crate::profiles::Units;
+use crate::units::{Length, Rounding};
+
+///////////////////////////////////////////////////////////////////////////////
+//  Named Constants
+///////////////////////////////////////////////////////////////////////////////
+
+const MS_PER_MINUTE: u64 = 60_000;
+
+///////////////////////////////////////////////////////////////////////////////
+//  Data Structures
+///////////////////////////////////////////////////////////////////////////////
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum SpoolAfter {
+    Left(Length),
+    // The spool runs out this far before the job's done
+    Short(Length),
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Summary {
+    pub pieces: u32,
+    pub wire: Length,
+    pub duration_ms: u64,
+    // None if the spool isn't being counted
+    pub spool_after: Option<SpoolAfter>,
+}
+
+///////////////////////////////////////////////////////////////////////////////
+//  Object Implementations
+///////////////////////////////////////////////////////////////////////////////
+
+impl Summary {
+    // A job of `pieces` pieces this long, each likely to take `piece_ms`
+    pub fn new(pieces: u32, piece: Length, piece_ms: u32) -> Self {
+        Self {
+            pieces,
+            wire: piece.times(pieces),
+            duration_ms: pieces as u64 * piece_ms as u64,
+            spool_after: None,
+        }
+    }
+
+    // Count the spool down by the job, after the wire queued ahead of it
+    pub fn with_spool(self, left: Option<Length>, queued: Length) -> Self {
+        let spool_after = left.map(|left| {
+            let needed = queued.plus(self.wire);
+            if needed > left {
+                SpoolAfter::Short(needed.minus(left))
+            } else {
+                SpoolAfter::Left(left.minus(needed))
+            }
+        });
+        Self {
+            spool_after,
+            ..self
+        }
+    }
+
+    // The duration in whole minutes, rounded up so the estimate is never short
+    pub fn minutes(&self) -> u32 {
+        self.duration_ms
+            .div_ceil(MS_PER_MINUTE)
+            .min(u32::MAX as u64) as u32
+    }
+
+    // Wire in the coarser unit spools are counted in, rounded up so it's never short
+    pub fn wire_in(&self, units: Units) -> u32 {
+        self.wire.to(units.spool_unit(), Rounding::Up)
+    }
+}
+
+impl SpoolAfter {
+    // In the coarser unit spools are counted in, always erring towards less wire on the spool
+    pub fn in_units(&self, units: Units) -> u32 {
+        match self {
+            Self::Left(left) => left.to(units.spool_unit(), Rounding::Down),
+            Self::Short(short) => short.to(units.spool_unit(), Rounding::Up),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::units::Unit;
+
+    #[test]
+    fn jobs_are_summed_up() {
+        let summary = Summary::new(100, Length::new(18, Unit::Inch), 1500);
+        assert_eq!(summary.wire_in(Units::Inches), 150);
+        assert_eq!(summary.minutes(), 3);
+        assert_eq!(summary.spool_after, None);
+
+        // Part-minutes and part-feet round up
+        let summary = Summary::new(7, Length::new(13, Unit::Inch), 10_001);
+        assert_eq!(summary.wire_in(Units::Inches), 8);
+        assert_eq!(summary.minutes(), 2);
+    }
+
+    #[test]
+    fn spool_is_counted_down_after_the_queue() {
+        let summary = Summary::new(100, Length::new(500, Unit::Millimeter), 1000);
+        let left = Some(Length::new(120, Unit::Meter));
+
+        let after = summary.with_spool(left, Length::ZERO).spool_after.unwrap();
+        assert_eq!(after, SpoolAfter::Left(Length::new(70, Unit::Meter)));
+        assert_eq!(after.in_units(Units::Millimeters), 70);
+
+        let after = summary
+            .with_spool(left, Length::new(95_500, Unit::Millimeter))
+            .spool_after
+            .unwrap();
+        assert_eq!(after.in_units(Units::Millimeters), 26);
+        assert!(matches!(after, SpoolAfter::Short(_)));
+
+        assert_eq!(summary.with_spool(None, Length::ZERO).spool_after, None);
+    }
+}
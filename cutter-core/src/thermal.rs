@@ -0,0 +1,167 @@
+/* * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * *\
+Copyright (C) 2023 CJ McAllister
+    This program is free software; you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation; either version 3 of the License, or
+    (at your option) any later version.
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+    You should have received a copy of the GNU General Public License
+    along with this program; if not, write to the Free Software Foundation,
+    Inc., 51 Franklin Street, Fifth Floor, Boston, MA 02110-1301  USA
+
+\* * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * */
+
+///////////////////////////////////////////////////////////////////////////////
+//  Named Constants
+///////////////////////////////////////////////////////////////////////////////
+
+// Resolution of the sliding window
+const NUM_BUCKETS: usize = 60;
+
+///////////////////////////////////////////////////////////////////////////////
+//  Data Structures
+///////////////////////////////////////////////////////////////////////////////
+
+// Most an actuator may be under load within any window of the given length
+#[derive(Copy, Clone, Debug)]
+pub struct DutyLimit {
+    pub window_ms: u32,
+    pub max_duty_pct: u32,
+}
+
+// Time an actuator has spent under load over the last window, split into buckets which expire
+// one at a time as time moves on
+#[derive(Clone, Debug)]
+pub struct DutyTracker {
+    limit: DutyLimit,
+    loaded_ms: [u32; NUM_BUCKETS],
+    current: usize,
+    current_elapsed_ms: u32,
+}
+
+///////////////////////////////////////////////////////////////////////////////
+//  Object Implementations
+///////////////////////////////////////////////////////////////////////////////
+
+impl DutyLimit {
+    pub fn max_loaded_ms(&self) -> u32 {
+        (self.window_ms as u64 * self.max_duty_pct as u64 / 100) as u32
+    }
+
+    fn bucket_ms(&self) -> u32 {
+        (self.window_ms / NUM_BUCKETS as u32).max(1)
+    }
+}
+
+impl DutyTracker {
+    pub fn new(limit: DutyLimit) -> Self {
+        Self {
+            limit,
+            loaded_ms: [0; NUM_BUCKETS],
+            current: 0,
+            current_elapsed_ms: 0,
+        }
+    }
+
+    // Account for time passing, with the actuator under load throughout or not at all
+    pub fn advance(&mut self, duration_ms: u32, loaded: bool) {
+        let bucket_ms = self.limit.bucket_ms();
+        if duration_ms >= bucket_ms * NUM_BUCKETS as u32 && !loaded {
+            // Everything recorded so far has expired
+            *self = Self::new(self.limit);
+            return;
+        }
+
+        let mut remaining_ms = duration_ms;
+        while remaining_ms > 0 {
+            let step_ms = remaining_ms.min(bucket_ms - self.current_elapsed_ms);
+            if loaded {
+                self.loaded_ms[self.current] += step_ms;
+            }
+            self.current_elapsed_ms += step_ms;
+            remaining_ms -= step_ms;
+
+            if self.current_elapsed_ms == bucket_ms {
+                self.current = (self.current + 1) % NUM_BUCKETS;
+                self.loaded_ms[self.current] = 0;
+                self.current_elapsed_ms = 0;
+            }
+        }
+    }
+
+    // Load time within the current window
+    pub fn loaded_ms(&self) -> u32 {
+        self.loaded_ms.iter().sum()
+    }
+
+    // Rest needed before the actuator can take another `load_ms` of load without exceeding its limit
+    pub fn cooldown_ms(&self, load_ms: u32) -> u32 {
+        let bucket_ms = self.limit.bucket_ms();
+        let mut rested = self.clone();
+        let mut cooldown_ms = 0;
+
+        // Resting for a whole window always clears the history, so give up there even if the load
+        // alone would exceed the limit
+        while rested.loaded_ms() + load_ms > self.limit.max_loaded_ms()
+            && cooldown_ms < self.limit.window_ms
+        {
+            let step_ms = bucket_ms - rested.current_elapsed_ms;
+            rested.advance(step_ms, false);
+            cooldown_ms += step_ms;
+        }
+
+        cooldown_ms
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LIMIT: DutyLimit = DutyLimit {
+        window_ms: 60_000,
+        max_duty_pct: 70,
+    };
+
+    #[test]
+    fn light_duty_needs_no_rest() {
+        let mut tracker = DutyTracker::new(LIMIT);
+        for _ in 0..100 {
+            assert_eq!(tracker.cooldown_ms(1500), 0);
+            tracker.advance(1500, true);
+            tracker.advance(3000, false);
+        }
+    }
+
+    #[test]
+    fn heavy_duty_rests_until_old_load_expires() {
+        let mut tracker = DutyTracker::new(LIMIT);
+        tracker.advance(42_000, true);
+        assert_eq!(tracker.loaded_ms(), 42_000);
+
+        // The first 2s of load must age out of the window before another 2s will fit; a bucket only
+        // expires once the window has entirely moved past it, so this rounds up by one bucket
+        let cooldown_ms = tracker.cooldown_ms(2000);
+        assert_eq!(cooldown_ms, 19_000);
+
+        tracker.advance(cooldown_ms, false);
+        assert!(tracker.loaded_ms() + 2000 <= LIMIT.max_loaded_ms());
+    }
+
+    #[test]
+    fn never_rests_longer_than_a_window() {
+        let tracker = DutyTracker::new(LIMIT);
+        assert_eq!(tracker.cooldown_ms(LIMIT.window_ms), LIMIT.window_ms);
+    }
+
+    #[test]
+    fn long_rests_clear_the_history() {
+        let mut tracker = DutyTracker::new(LIMIT);
+        tracker.advance(30_000, true);
+        tracker.advance(60_000, false);
+        assert_eq!(tracker.loaded_ms(), 0);
+    }
+}
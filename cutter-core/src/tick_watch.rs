@@ -0,0 +1,125 @@
+/* * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * *\
+Copyright (C) 2023 CJ McAllister
+    This program is free software; you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation; either version 3 of the License, or
+    (at your option) any later version.
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+    You should have received a copy of the GNU General Public License
+    along with this program; if not, write to the Free Software Foundation,
+    Inc., 51 Franklin Street, Fifth Floor, Boston, MA 02110-1301  USA
+
+\* * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * */
+///////////////////////////////////////////////////////////////////////////////
+
+// Watches a periodic timer interrupt for being serviced late, e.g. held off by a long critical
+// section or a stalled I2C transfer, by timing each tick against an independent clock. The timer is
+// restarted from its handler, so a late tick pushes back every tick after it: each interval is one
+// period plus however late its own tick was, and a tick more than a whole period late stands in for
+// the ones that never fired in the meantime.
+
+use core::fmt;
+
+///////////////////////////////////////////////////////////////////////////////
+//  Data Structures
+///////////////////////////////////////////////////////////////////////////////
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct TickWatch {
+    period_ms: u32,
+    pub ticks: u32,
+    pub missed: u32,
+    pub last_late_ms: u32,
+    pub worst_late_ms: u32,
+}
+
+///////////////////////////////////////////////////////////////////////////////
+//  Object Implementations
+///////////////////////////////////////////////////////////////////////////////
+
+impl TickWatch {
+    pub const fn new(period_ms: u32) -> Self {
+        Self {
+            period_ms,
+            ticks: 0,
+            missed: 0,
+            last_late_ms: 0,
+            worst_late_ms: 0,
+        }
+    }
+
+    // A tick, `interval_ms` after the one before
+    pub fn record(&mut self, interval_ms: u32) {
+        let late_ms = interval_ms.saturating_sub(self.period_ms);
+        self.ticks = self.ticks.saturating_add(1);
+        self.missed = self.missed.saturating_add(late_ms / self.period_ms);
+        self.last_late_ms = late_ms;
+        self.worst_late_ms = self.worst_late_ms.max(late_ms);
+    }
+
+    // Ticks that should have fired by now, `since_last_ms` after the last one, but haven't been
+    // serviced yet. A handler held off for good never gets to count these itself.
+    pub fn overdue(&self, since_last_ms: u32) -> u32 {
+        since_last_ms.saturating_sub(self.period_ms) / self.period_ms
+    }
+
+    pub fn write_report(
+        &self,
+        name: &str,
+        since_last_ms: u32,
+        out: &mut dyn fmt::Write,
+    ) -> fmt::Result {
+        writeln!(
+            out,
+            "{}_late_ms {} last, {} worst over {} ticks",
+            name, self.last_late_ms, self.worst_late_ms, self.ticks
+        )?;
+        writeln!(
+            out,
+            "{}_missed_ticks {}",
+            name,
+            self.missed.saturating_add(self.overdue(since_last_ms))
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lateness_is_measured_past_the_period() {
+        let mut watch = TickWatch::new(1000);
+        watch.record(1000);
+        watch.record(1250);
+        watch.record(1010);
+        assert_eq!(watch.ticks, 3);
+        assert_eq!(watch.last_late_ms, 10);
+        assert_eq!(watch.worst_late_ms, 250);
+        assert_eq!(watch.missed, 0);
+
+        // Early ticks, e.g. the timer borrowed for something else, aren't late
+        watch.record(400);
+        assert_eq!(watch.last_late_ms, 0);
+    }
+
+    #[test]
+    fn whole_periods_late_are_missed_ticks() {
+        let mut watch = TickWatch::new(1000);
+        watch.record(3500);
+        assert_eq!(watch.missed, 2);
+
+        // Those still held off are counted too
+        assert_eq!(watch.overdue(999), 0);
+        assert_eq!(watch.overdue(4200), 3);
+        let mut report = String::new();
+        watch.write_report("timer1", 4200, &mut report).unwrap();
+        assert_eq!(
+            report,
+            "timer1_late_ms 2500 last, 2500 worst over 1 ticks\ntimer1_missed_ticks 5\n"
+        );
+    }
+}
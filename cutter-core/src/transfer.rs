@@ -0,0 +1,152 @@
+/* * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * *\
+Copyright (C) 2023 CJ McAllister
+    This program is free software; you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation; either version 3 of the License, or
+    (at your option) any later version.
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+    You should have received a copy of the GNU General Public License
+    along with this program; if not, write to the Free Software Foundation,
+    Inc., 51 Franklin Street, Fifth Floor, Boston, MA 02110-1301  USA
+
+\* * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * */
+
+// Checksummed hex encoding, for moving binary blobs through the serial console intact
+
+use core::convert::TryInto;
+use core::fmt::{self, Write};
+
+///////////////////////////////////////////////////////////////////////////////
+//  Named Constants
+///////////////////////////////////////////////////////////////////////////////
+
+// CRC-32 (IEEE 802.3), as used by zip and Ethernet, so it can be checked with standard tools
+const CRC32_POLY_REFLECTED: u32 = 0xEDB8_8320;
+const CRC_LEN: usize = 4;
+
+///////////////////////////////////////////////////////////////////////////////
+//  Data Structures
+///////////////////////////////////////////////////////////////////////////////
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum TransferError {
+    NotHex,
+    WrongLength,
+    BadChecksum,
+}
+
+///////////////////////////////////////////////////////////////////////////////
+//  Object Implementations
+///////////////////////////////////////////////////////////////////////////////
+
+impl TransferError {
+    pub fn message(&self) -> &'static str {
+        match self {
+            Self::NotHex => "not hex",
+            Self::WrongLength => "wrong length",
+            Self::BadChecksum => "checksum mismatch",
+        }
+    }
+}
+
+///////////////////////////////////////////////////////////////////////////////
+//  Static Functions
+///////////////////////////////////////////////////////////////////////////////
+
+pub fn crc32(bytes: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for &byte in bytes {
+        crc ^= byte as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 != 0 {
+                (crc >> 1) ^ CRC32_POLY_REFLECTED
+            } else {
+                crc >> 1
+            };
+        }
+    }
+
+    !crc
+}
+
+// Write the bytes as uppercase hex, followed by their CRC-32 (big-endian, as it's usually printed)
+pub fn write_hex(bytes: &[u8], out: &mut impl Write) -> fmt::Result {
+    for byte in bytes.iter().chain(&crc32(bytes).to_be_bytes()) {
+        write!(out, "{:02X}", byte)?;
+    }
+
+    Ok(())
+}
+
+// Decode text written by write_hex into the start of `bytes`, checking the CRC-32, and return how
+// many bytes it held. Blobs shorter than the buffer are accepted, as exports from older builds may be.
+pub fn read_hex(text: &str, bytes: &mut [u8]) -> Result<usize, TransferError> {
+    let text = text.as_bytes();
+    let max_len = (bytes.len() + CRC_LEN) * 2;
+    if !text.len().is_multiple_of(2) || !(CRC_LEN * 2..=max_len).contains(&text.len()) {
+        return Err(TransferError::WrongLength);
+    }
+    let len = text.len() / 2 - CRC_LEN;
+    let bytes = &mut bytes[..len];
+
+    let mut crc = [0; CRC_LEN];
+    let decoded = bytes.iter_mut().chain(crc.iter_mut());
+    for (byte, pair) in decoded.zip(text.chunks_exact(2)) {
+        let pair = core::str::from_utf8(pair).map_err(|_| TransferError::NotHex)?;
+        *byte = u8::from_str_radix(pair, 16).map_err(|_| TransferError::NotHex)?;
+    }
+
+    if u32::from_be_bytes(crc[..].try_into().unwrap()) != crc32(bytes) {
+        return Err(TransferError::BadChecksum);
+    }
+
+    Ok(len)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn crc_matches_reference() {
+        // The standard CRC-32 check value
+        assert_eq!(crc32(b"123456789"), 0xCBF4_3926);
+    }
+
+    #[test]
+    fn round_trips() {
+        let mut text = String::new();
+        write_hex(&[0x00, 0x5A, 0xFF], &mut text).unwrap();
+        assert!(text.starts_with("005AFF"));
+
+        let mut bytes = [0; 4];
+        assert_eq!(read_hex(&text, &mut bytes), Ok(3));
+        assert_eq!(bytes, [0x00, 0x5A, 0xFF, 0x00]);
+        assert_eq!(read_hex(&text.to_lowercase(), &mut bytes), Ok(3));
+    }
+
+    #[test]
+    fn rejects_damage() {
+        let mut text = String::new();
+        write_hex(&[1, 2, 3], &mut text).unwrap();
+        let mut bytes = [0; 2];
+        assert_eq!(read_hex(&text, &mut bytes), Err(TransferError::WrongLength));
+        let mut bytes = [0; 3];
+
+        let corrupted = text.replacen("01", "11", 1);
+        assert_eq!(
+            read_hex(&corrupted, &mut bytes),
+            Err(TransferError::BadChecksum)
+        );
+        assert_eq!(
+            read_hex(&text[1..], &mut bytes),
+            Err(TransferError::WrongLength)
+        );
+        let not_hex = text.replacen("01", "0G", 1);
+        assert_eq!(read_hex(&not_hex, &mut bytes), Err(TransferError::NotHex));
+    }
+}
@@ -0,0 +1,236 @@
+/* * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * *\
+Copyright (C) 2023 CJ McAllister
+    This program is free software; you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation; either version 3 of the License, or
+    (at your option) any later version.
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+    You should have received a copy of the GNU General Public License
+    along with this program; if not, write to the Free Software Foundation,
+    Inc., 51 Franklin Street, Fifth Floor, Boston, MA 02110-1301  USA
+
+\* * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * */
+
+// Parameters which can be adjusted while the machine runs, so a build can be dialled in over the serial
+// console without reflashing. The firmware supplies each one's build default; settings keep only the
+// values which were changed from it.
+
+use core::ops::RangeInclusive;
+
+///////////////////////////////////////////////////////////////////////////////
+//  Named Constants
+///////////////////////////////////////////////////////////////////////////////
+
+pub const NUM_PARAMS: usize = 7;
+
+///////////////////////////////////////////////////////////////////////////////
+//  Data Structures
+///////////////////////////////////////////////////////////////////////////////
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Param {
+    // Cutter servo positions, in tenths of a percent duty cycle
+    CutClosedDuty,
+    CutOpenDuty,
+    // Cycle timing, as in motion::CycleTiming
+    CutDwellMs,
+    BladeClearanceMs,
+    // Feed axis limits at 100% feed speed, as in motion::FeedProfile
+    FeedSpeed,
+    FeedAccel,
+    // Time between keypad samples while a press settles
+    KeyDebounceUs,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum TuneError {
+    OutOfRange,
+}
+
+// Values changed from the build defaults, by parameter; None keeps the default
+pub type Overrides = [Option<u32>; NUM_PARAMS];
+
+// The value in force for every parameter
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Tuning {
+    defaults: [u32; NUM_PARAMS],
+    values: [u32; NUM_PARAMS],
+}
+
+///////////////////////////////////////////////////////////////////////////////
+//  Object Implementations
+///////////////////////////////////////////////////////////////////////////////
+
+impl Param {
+    pub const ALL: [Param; NUM_PARAMS] = [
+        Param::CutClosedDuty,
+        Param::CutOpenDuty,
+        Param::CutDwellMs,
+        Param::BladeClearanceMs,
+        Param::FeedSpeed,
+        Param::FeedAccel,
+        Param::KeyDebounceUs,
+    ];
+
+    // Name used on the console
+    pub fn name(self) -> &'static str {
+        match self {
+            Param::CutClosedDuty => "cut_closed_duty",
+            Param::CutOpenDuty => "cut_open_duty",
+            Param::CutDwellMs => "cut_dwell_ms",
+            Param::BladeClearanceMs => "blade_clearance_ms",
+            Param::FeedSpeed => "feed_speed_mils_per_s",
+            Param::FeedAccel => "feed_accel_mils_per_s2",
+            Param::KeyDebounceUs => "key_debounce_us",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL
+            .iter()
+            .copied()
+            .find(|param| param.name().eq_ignore_ascii_case(name))
+    }
+
+    // Values the machine can safely be run with
+    pub fn range(self) -> RangeInclusive<u32> {
+        match self {
+            // 0.5ms to 2.5ms pulses, the widest hobby servos accept
+            Param::CutClosedDuty | Param::CutOpenDuty => 25..=125,
+            Param::CutDwellMs => 100..=10_000,
+            Param::BladeClearanceMs => 0..=2000,
+            // The feed axis can't be stepped any faster than 5 in/s
+            Param::FeedSpeed => 100..=5000,
+            Param::FeedAccel => 100..=50_000,
+            Param::KeyDebounceUs => 50..=5000,
+        }
+    }
+
+    fn index(self) -> usize {
+        self as usize
+    }
+}
+
+impl TuneError {
+    pub fn message(&self) -> &'static str {
+        match self {
+            Self::OutOfRange => "out of range",
+        }
+    }
+}
+
+impl Tuning {
+    pub const fn new(defaults: [u32; NUM_PARAMS]) -> Self {
+        Self {
+            defaults,
+            values: defaults,
+        }
+    }
+
+    // Defaults with saved overrides applied, skipping any no longer in range
+    pub fn with_overrides(defaults: [u32; NUM_PARAMS], overrides: &Overrides) -> Self {
+        let mut tuning = Self::new(defaults);
+        for (param, value) in Param::ALL.iter().copied().zip(overrides) {
+            if let Some(value) = value {
+                let _ = tuning.set(param, *value);
+            }
+        }
+
+        tuning
+    }
+
+    pub fn get(&self, param: Param) -> u32 {
+        self.values[param.index()]
+    }
+
+    pub fn default_of(&self, param: Param) -> u32 {
+        self.defaults[param.index()]
+    }
+
+    pub fn set(&mut self, param: Param, value: u32) -> Result<(), TuneError> {
+        if !param.range().contains(&value) {
+            return Err(TuneError::OutOfRange);
+        }
+
+        self.values[param.index()] = value;
+        Ok(())
+    }
+
+    // The values which differ from the defaults, for saving
+    pub fn overrides(&self) -> Overrides {
+        let mut overrides = [None; NUM_PARAMS];
+        for (i, override_value) in overrides.iter_mut().enumerate() {
+            if self.values[i] != self.defaults[i] {
+                *override_value = Some(self.values[i]);
+            }
+        }
+
+        overrides
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DEFAULTS: [u32; NUM_PARAMS] = [120, 30, 1500, 200, 4000, 8000, 250];
+
+    #[test]
+    fn names_round_trip() {
+        for param in Param::ALL {
+            assert_eq!(Param::from_name(param.name()), Some(param));
+            assert_eq!(
+                param.index(),
+                Param::ALL.iter().position(|p| *p == param).unwrap()
+            );
+        }
+        assert_eq!(Param::from_name("CUT_DWELL_MS"), Some(Param::CutDwellMs));
+        assert_eq!(Param::from_name("cut_dwell"), None);
+    }
+
+    #[test]
+    fn defaults_are_in_range() {
+        for param in Param::ALL {
+            assert!(param.range().contains(&DEFAULTS[param.index()]));
+        }
+    }
+
+    #[test]
+    fn rejects_values_out_of_range() {
+        let mut tuning = Tuning::new(DEFAULTS);
+        assert_eq!(
+            tuning.set(Param::FeedSpeed, 6000),
+            Err(TuneError::OutOfRange)
+        );
+        assert_eq!(tuning.get(Param::FeedSpeed), 4000);
+
+        tuning.set(Param::FeedSpeed, 3000).unwrap();
+        assert_eq!(tuning.get(Param::FeedSpeed), 3000);
+        assert_eq!(tuning.default_of(Param::FeedSpeed), 4000);
+    }
+
+    #[test]
+    fn only_changes_are_saved() {
+        let mut tuning = Tuning::new(DEFAULTS);
+        tuning.set(Param::CutDwellMs, 1200).unwrap();
+        tuning.set(Param::CutOpenDuty, 30).unwrap();
+
+        let overrides = tuning.overrides();
+        assert_eq!(overrides[Param::CutDwellMs.index()], Some(1200));
+        assert_eq!(overrides.iter().flatten().count(), 1);
+        assert_eq!(Tuning::with_overrides(DEFAULTS, &overrides), tuning);
+    }
+
+    #[test]
+    fn bad_overrides_keep_defaults() {
+        let mut overrides = [None; NUM_PARAMS];
+        overrides[Param::KeyDebounceUs.index()] = Some(0);
+        let tuning = Tuning::with_overrides(DEFAULTS, &overrides);
+        assert_eq!(tuning.get(Param::KeyDebounceUs), 250);
+    }
+}
@@ -0,0 +1,274 @@
+/* * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * *\
+Copyright (C) 2023 CJ McAllister
+    This program is free software; you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation; either version 3 of the License, or
+    (at your option) any later version.
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+    You should have received a copy of the GNU General Public License
+    along with this program; if not, write to the Free Software Foundation,
+    Inc., 51 Franklin Street, Fifth Floor, Boston, MA 02110-1301  USA
+
+\* * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * */
+///////////////////////////////////////////////////////////////////////////////
+
+// Lengths in fixed point, so converting between inches and metric is exact. A tenth of a micrometre
+// goes into an inch (254000 times), a millimetre (10000) and a mil (254) alike; only converting back
+// out to a coarser unit rounds, and then however the caller says.
+
+use crate::profiles::Units;
+
+///////////////////////////////////////////////////////////////////////////////
+//  Named Constants
+///////////////////////////////////////////////////////////////////////////////
+
+const TENTH_UM_PER_MIL: u64 = 254;
+const TENTH_UM_PER_INCH: u64 = 254_000;
+const TENTH_UM_PER_MM: u64 = 10_000;
+const TENTH_UM_PER_CM: u64 = 100_000;
+const TENTH_UM_PER_FOOT: u64 = 12 * TENTH_UM_PER_INCH;
+const TENTH_UM_PER_METER: u64 = 1000 * TENTH_UM_PER_MM;
+
+///////////////////////////////////////////////////////////////////////////////
+//  Data Structures
+///////////////////////////////////////////////////////////////////////////////
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Unit {
+    // Thousandths of an inch, which motion plans are laid out in
+    Mil,
+    Inch,
+    Millimeter,
+    Centimeter,
+    // For wire by the spool
+    Foot,
+    Meter,
+}
+
+// How a length is brought to a whole number of a coarser unit
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Rounding {
+    // Halves round up
+    Nearest,
+    Up,
+    Down,
+}
+
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Length {
+    // Tenths of a micrometre, wide enough for every piece of a large job laid end to end
+    tenth_um: u64,
+}
+
+///////////////////////////////////////////////////////////////////////////////
+//  Object Implementations
+///////////////////////////////////////////////////////////////////////////////
+
+impl Unit {
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Mil => "mil",
+            Self::Inch => "in",
+            Self::Millimeter => "mm",
+            Self::Centimeter => "cm",
+            Self::Foot => "ft",
+            Self::Meter => "m",
+        }
+    }
+
+    fn tenth_um(self) -> u64 {
+        match self {
+            Self::Mil => TENTH_UM_PER_MIL,
+            Self::Inch => TENTH_UM_PER_INCH,
+            Self::Millimeter => TENTH_UM_PER_MM,
+            Self::Centimeter => TENTH_UM_PER_CM,
+            Self::Foot => TENTH_UM_PER_FOOT,
+            Self::Meter => TENTH_UM_PER_METER,
+        }
+    }
+}
+
+impl From<Units> for Unit {
+    fn from(units: Units) -> Self {
+        match units {
+            Units::Inches => Self::Inch,
+            Units::Millimeters => Self::Millimeter,
+        }
+    }
+}
+
+impl Rounding {
+    fn divide(self, num: u64, den: u64) -> u64 {
+        match self {
+            // Halves round up. Found from the remainder, as adding half first can overflow a
+            // numerator that's already saturated.
+            Self::Nearest => {
+                let quotient = num / den;
+                if num % den >= den - den / 2 {
+                    quotient.saturating_add(1)
+                } else {
+                    quotient
+                }
+            }
+            Self::Up => num.div_ceil(den),
+            Self::Down => num / den,
+        }
+    }
+}
+
+impl Length {
+    pub const ZERO: Self = Self { tenth_um: 0 };
+
+    pub const fn new(value: u32, unit: Unit) -> Self {
+        let per_unit = match unit {
+            Unit::Mil => TENTH_UM_PER_MIL,
+            Unit::Inch => TENTH_UM_PER_INCH,
+            Unit::Millimeter => TENTH_UM_PER_MM,
+            Unit::Centimeter => TENTH_UM_PER_CM,
+            Unit::Foot => TENTH_UM_PER_FOOT,
+            Unit::Meter => TENTH_UM_PER_METER,
+        };
+        Self {
+            tenth_um: value as u64 * per_unit,
+        }
+    }
+
+    // As a whole number of the given unit, saturating if it won't fit
+    pub fn to(self, unit: Unit, rounding: Rounding) -> u32 {
+        self.to_scaled(unit, 0, rounding)
+    }
+
+    // As a number of the given unit's 10^-decimals parts, e.g. hundredths of an inch for 2, for showing
+    // with a decimal point. Saturates if it won't fit.
+    pub fn to_scaled(self, unit: Unit, decimals: u32, rounding: Rounding) -> u32 {
+        let scaled = rounding.divide(
+            self.tenth_um.saturating_mul(10u64.pow(decimals)),
+            unit.tenth_um(),
+        );
+        scaled.min(u32::MAX as u64) as u32
+    }
+
+    // How many of something with the given number to the inch fit the length, e.g. a feed's steps
+    pub fn per_inch(self, count_per_inch: u32, rounding: Rounding) -> u32 {
+        let count = rounding.divide(
+            self.tenth_um.saturating_mul(count_per_inch as u64),
+            TENTH_UM_PER_INCH,
+        );
+        count.min(u32::MAX as u64) as u32
+    }
+
+    // The length of `count` of these end to end
+    pub fn times(self, count: u32) -> Self {
+        Self {
+            tenth_um: self.tenth_um.saturating_mul(count as u64),
+        }
+    }
+
+    // This and another end to end
+    pub fn plus(self, other: Self) -> Self {
+        Self {
+            tenth_um: self.tenth_um.saturating_add(other.tenth_um),
+        }
+    }
+
+    // What's left of this once another's cut from it, or nothing if the other's longer
+    pub fn minus(self, other: Self) -> Self {
+        Self {
+            tenth_um: self.tenth_um.saturating_sub(other.tenth_um),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn conversions_are_exact() {
+        let inch = Length::new(1, Unit::Inch);
+        assert_eq!(inch, Length::new(1000, Unit::Mil));
+        assert_eq!(
+            Length::new(254, Unit::Millimeter),
+            Length::new(10, Unit::Inch)
+        );
+        assert_eq!(
+            Length::new(3, Unit::Centimeter),
+            Length::new(30, Unit::Millimeter)
+        );
+        assert_eq!(inch.to_scaled(Unit::Millimeter, 1, Rounding::Down), 254);
+        assert_eq!(inch.times(5).to(Unit::Centimeter, Rounding::Nearest), 13);
+        assert_eq!(
+            inch.plus(Length::new(1, Unit::Millimeter)).to_scaled(
+                Unit::Millimeter,
+                1,
+                Rounding::Down
+            ),
+            264
+        );
+        assert_eq!(Length::new(3, Unit::Foot), Length::new(36, Unit::Inch));
+        assert_eq!(
+            Length::new(2, Unit::Meter).to(Unit::Centimeter, Rounding::Down),
+            200
+        );
+        assert_eq!(
+            Length::new(1, Unit::Foot).minus(inch),
+            Length::new(11, Unit::Inch)
+        );
+        assert_eq!(inch.minus(Length::new(1, Unit::Foot)), Length::ZERO);
+    }
+
+    #[test]
+    fn rounding_modes() {
+        // 1mm is 39.37 mils
+        let mm = Length::new(1, Unit::Millimeter);
+        assert_eq!(mm.to(Unit::Mil, Rounding::Down), 39);
+        assert_eq!(mm.to(Unit::Mil, Rounding::Up), 40);
+        assert_eq!(mm.to(Unit::Mil, Rounding::Nearest), 39);
+
+        // 127mm is exactly 5in, which no rounding changes
+        let length = Length::new(127, Unit::Millimeter);
+        for rounding in [Rounding::Nearest, Rounding::Up, Rounding::Down] {
+            assert_eq!(length.to(Unit::Inch, rounding), 5);
+        }
+
+        // Halves round up
+        assert_eq!(
+            Length::new(5, Unit::Millimeter).to(Unit::Centimeter, Rounding::Nearest),
+            1
+        );
+        assert_eq!(
+            Length::new(10, Unit::Millimeter).to_scaled(Unit::Inch, 2, Rounding::Nearest),
+            39
+        );
+    }
+
+    #[test]
+    fn counts_per_inch() {
+        let length = Length::new(2500, Unit::Mil);
+        assert_eq!(length.per_inch(200, Rounding::Down), 500);
+        assert_eq!(
+            Length::new(1, Unit::Millimeter).per_inch(200, Rounding::Down),
+            7
+        );
+        assert_eq!(
+            Length::new(1, Unit::Millimeter).per_inch(200, Rounding::Nearest),
+            8
+        );
+    }
+
+    #[test]
+    fn oversized_results_saturate() {
+        let length = Length::new(u32::MAX, Unit::Inch).times(u32::MAX);
+        for rounding in [Rounding::Nearest, Rounding::Up, Rounding::Down] {
+            assert_eq!(length.to(Unit::Mil, rounding), u32::MAX);
+            assert_eq!(length.per_inch(1000, rounding), u32::MAX);
+        }
+        assert!(length > Length::ZERO);
+    }
+}
@@ -0,0 +1,349 @@
+/* * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * *\
+Copyright (C) 2023 CJ McAllister
+    This program is free software; you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation; either version 3 of the License, or
+    (at your option) any later version.
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+    You should have received a copy of the GNU General Public License
+    along with this program; if not, write to the Free Software Foundation,
+    Inc., 51 Franklin Street, Fifth Floor, Boston, MA 02110-1301  USA
+
+\* * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * */
+///////////////////////////////////////////////////////////////////////////////
+
+// Small building blocks for screens, each filling an area of a frame, so a screen is composed by saying
+// where its parts go instead of moving the cursor about and writing. Rendering into a frame keeps the
+// widgets independent of the display the frame is drawn to, and means only what changes goes out.
+//
+// Widgets always fill their whole area, blanking whatever was there before, so a screen can be
+// re-rendered as its figures change without clearing the display.
+
+use crate::frame::Frame;
+use crate::numeric;
+
+///////////////////////////////////////////////////////////////////////////////
+//  Named Constants
+///////////////////////////////////////////////////////////////////////////////
+
+// Wide enough for a row of any frame
+const MAX_WIDTH: usize = crate::frame::COLS;
+
+const SPINNER_FRAMES: &[u8; 4] = b"|/-\\";
+const BAR_FILLED: u8 = b'#';
+const BAR_EMPTY: u8 = b'-';
+const LIST_MARKER: &[u8; 2] = b"> ";
+
+///////////////////////////////////////////////////////////////////////////////
+//  Data Structures
+///////////////////////////////////////////////////////////////////////////////
+
+// Where on a frame a widget goes
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Area {
+    pub row: usize,
+    pub col: usize,
+    pub width: usize,
+    pub height: usize,
+}
+
+pub trait Widget {
+    fn render(&self, frame: &mut Frame, area: Area);
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Align {
+    Left,
+    Right,
+}
+
+// Text, a line per row of the area, cut off at its edge
+#[derive(Copy, Clone, Debug)]
+pub struct Label<'a> {
+    text: &'a str,
+    align: Align,
+}
+
+// A zero-padded count, out of a total if it has one, e.g. "00012 / 00040"
+#[derive(Copy, Clone, Debug)]
+pub struct Counter {
+    value: u32,
+    total: Option<u32>,
+}
+
+// How far through something is, as a bar filling the area's first row
+#[derive(Copy, Clone, Debug)]
+pub struct ProgressBar {
+    done: u32,
+    total: u32,
+}
+
+// A single character turning a step per tick, to show the machine is busy
+#[derive(Copy, Clone, Debug)]
+pub struct Spinner {
+    tick: u32,
+}
+
+// Items a row each, with the selected one marked, scrolled so it's always in view
+#[derive(Copy, Clone, Debug)]
+pub struct ListView<'a> {
+    items: &'a [&'a str],
+    selected: usize,
+}
+
+///////////////////////////////////////////////////////////////////////////////
+//  Object Implementations
+///////////////////////////////////////////////////////////////////////////////
+
+impl Area {
+    // Part of a single row
+    pub const fn line(row: usize, col: usize, width: usize) -> Self {
+        Self {
+            row,
+            col,
+            width,
+            height: 1,
+        }
+    }
+}
+
+impl<'a> Label<'a> {
+    pub fn new(text: &'a str) -> Self {
+        Self {
+            text,
+            align: Align::Left,
+        }
+    }
+
+    pub fn aligned(self, align: Align) -> Self {
+        Self { align, ..self }
+    }
+}
+
+impl Widget for Label<'_> {
+    fn render(&self, frame: &mut Frame, area: Area) {
+        let mut lines = self.text.split('\n');
+        for row in area.row..area.row + area.height {
+            let text = lines.next().unwrap_or("").as_bytes();
+            let width = area.width.min(MAX_WIDTH);
+            let len = text.len().min(width);
+            let start = match self.align {
+                Align::Left => 0,
+                Align::Right => width - len,
+            };
+            write_padded(frame, row, area, start, &text[..len]);
+        }
+    }
+}
+
+impl Counter {
+    pub fn new(value: u32) -> Self {
+        Self { value, total: None }
+    }
+
+    pub fn of(self, total: u32) -> Self {
+        Self {
+            total: Some(total),
+            ..self
+        }
+    }
+}
+
+impl Widget for Counter {
+    // The total goes closer in when the area is narrow, and is left off if it still doesn't fit
+    fn render(&self, frame: &mut Frame, area: Area) {
+        let mut text = [b' '; MAX_WIDTH];
+        text[..numeric::PADDED_WIDTH].copy_from_slice(&numeric::padded(self.value));
+        let mut len = numeric::PADDED_WIDTH;
+
+        let separator: &[u8] = match area.width {
+            width if width >= 2 * numeric::PADDED_WIDTH + 3 => b" / ",
+            width if width > 2 * numeric::PADDED_WIDTH => b"/",
+            _ => b"",
+        };
+        if let (Some(total), false) = (self.total, separator.is_empty()) {
+            for part in [separator, &numeric::padded(total)] {
+                text[len..len + part.len()].copy_from_slice(part);
+                len += part.len();
+            }
+        }
+
+        write_padded(frame, area.row, area, 0, &text[..len.min(area.width)]);
+    }
+}
+
+impl ProgressBar {
+    pub fn new(done: u32, total: u32) -> Self {
+        Self { done, total }
+    }
+}
+
+impl Widget for ProgressBar {
+    // Only full once everything's done; nothing at all is empty
+    fn render(&self, frame: &mut Frame, area: Area) {
+        let width = area.width.min(MAX_WIDTH);
+        let filled = match self.total {
+            0 => width,
+            total => (self.done.min(total) as u64 * width as u64 / total as u64) as usize,
+        };
+
+        let mut bar = [BAR_EMPTY; MAX_WIDTH];
+        bar[..filled].fill(BAR_FILLED);
+        frame.write(area.row, area.col, &bar[..width]);
+    }
+}
+
+impl Spinner {
+    pub fn new(tick: u32) -> Self {
+        Self { tick }
+    }
+}
+
+impl Widget for Spinner {
+    fn render(&self, frame: &mut Frame, area: Area) {
+        let index = self.tick as usize % SPINNER_FRAMES.len();
+        write_padded(frame, area.row, area, 0, &SPINNER_FRAMES[index..index + 1]);
+    }
+}
+
+impl<'a> ListView<'a> {
+    pub fn new(items: &'a [&'a str], selected: usize) -> Self {
+        Self { items, selected }
+    }
+}
+
+impl Widget for ListView<'_> {
+    fn render(&self, frame: &mut Frame, area: Area) {
+        // Scrolling down keeps the selection on the bottom row, as the list moves up under it
+        let first = self.selected.saturating_sub(area.height.saturating_sub(1));
+        for (i, row) in (area.row..area.row + area.height).enumerate() {
+            let index = first + i;
+            let Some(item) = self.items.get(index) else {
+                write_padded(frame, row, area, 0, b"");
+                continue;
+            };
+
+            let mut text = [b' '; MAX_WIDTH];
+            if index == self.selected {
+                text[..LIST_MARKER.len()].copy_from_slice(LIST_MARKER);
+            }
+            let item = &item.as_bytes()[..item.len().min(MAX_WIDTH - LIST_MARKER.len())];
+            let len = LIST_MARKER.len() + item.len();
+            text[LIST_MARKER.len()..len].copy_from_slice(item);
+            write_padded(frame, row, area, 0, &text[..len.min(area.width)]);
+        }
+    }
+}
+
+///////////////////////////////////////////////////////////////////////////////
+//  Helper Functions
+///////////////////////////////////////////////////////////////////////////////
+
+// Fill a row of the area with blanks, but for the text at `start`
+fn write_padded(frame: &mut Frame, row: usize, area: Area, start: usize, text: &[u8]) {
+    let width = area.width.min(MAX_WIDTH);
+    let mut line = [b' '; MAX_WIDTH];
+    line[start..start + text.len()].copy_from_slice(text);
+    frame.write(row, area.col, &line[..width]);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::frame::Run;
+
+    // The first page of a row
+    fn row(frame: &Frame, row: usize) -> String {
+        let text = frame.text(&Run {
+            row,
+            col: 0,
+            len: 16,
+        });
+        String::from_utf8(text.to_vec()).unwrap()
+    }
+
+    #[test]
+    fn labels_fill_their_area() {
+        let mut frame = Frame::new();
+        Label::new("TOO LONG FOR IT").render(&mut frame, Area::line(0, 2, 8));
+        assert_eq!(row(&frame, 0), "  TOO LONG      ");
+
+        // A shorter label blanks what a longer one left behind
+        Label::new("OK")
+            .aligned(Align::Right)
+            .render(&mut frame, Area::line(0, 2, 8));
+        assert_eq!(row(&frame, 0), "        OK      ");
+
+        let area = Area {
+            row: 0,
+            col: 0,
+            width: 16,
+            height: 2,
+        };
+        Label::new("DOOR OPEN\nCLOSE TO RESUME").render(&mut frame, area);
+        assert_eq!(row(&frame, 1), "CLOSE TO RESUME ");
+    }
+
+    #[test]
+    fn counters_fit_the_total_in_if_they_can() {
+        let mut frame = Frame::new();
+        Counter::new(12)
+            .of(40)
+            .render(&mut frame, Area::line(0, 0, 16));
+        Counter::new(13)
+            .of(40)
+            .render(&mut frame, Area::line(1, 0, 11));
+        assert_eq!(row(&frame, 0), "00012 / 00040   ");
+        assert_eq!(row(&frame, 1), "00013/00040     ");
+
+        // Only the area is blanked
+        Counter::new(14)
+            .of(40)
+            .render(&mut frame, Area::line(1, 0, 8));
+        assert_eq!(row(&frame, 1), "00014   040     ");
+    }
+
+    #[test]
+    fn progress_bars_fill_in_proportion() {
+        let mut frame = Frame::new();
+        ProgressBar::new(1, 3).render(&mut frame, Area::line(0, 0, 10));
+        assert_eq!(row(&frame, 0), "###-------      ");
+        ProgressBar::new(29, 30).render(&mut frame, Area::line(0, 0, 10));
+        assert_eq!(row(&frame, 0), "#########-      ");
+        ProgressBar::new(30, 30).render(&mut frame, Area::line(0, 0, 10));
+        assert_eq!(row(&frame, 0), "##########      ");
+    }
+
+    #[test]
+    fn spinners_turn() {
+        let mut frame = Frame::new();
+        Spinner::new(5).render(&mut frame, Area::line(0, 15, 1));
+        assert_eq!(row(&frame, 0), "               /");
+    }
+
+    #[test]
+    fn lists_scroll_to_the_selection() {
+        let items = ["CUTTER", "SOUNDS", "INPUTS"];
+        let area = Area {
+            row: 0,
+            col: 0,
+            width: 16,
+            height: 2,
+        };
+        let mut frame = Frame::new();
+        ListView::new(&items, 0).render(&mut frame, area);
+        assert_eq!(row(&frame, 0), "> CUTTER        ");
+        assert_eq!(row(&frame, 1), "  SOUNDS        ");
+
+        ListView::new(&items, 2).render(&mut frame, area);
+        assert_eq!(row(&frame, 0), "  SOUNDS        ");
+        assert_eq!(row(&frame, 1), "> INPUTS        ");
+
+        // Rows past the end are blanked
+        ListView::new(&items[..1], 0).render(&mut frame, area);
+        assert_eq!(row(&frame, 1), "                ");
+    }
+}
@@ -0,0 +1,39 @@
+/* * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * *\
+Copyright (C) 2023 CJ McAllister
+    This program is free software; you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation; either version 3 of the License, or
+    (at your option) any later version.
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+    You should have received a copy of the GNU General Public License
+    along with this program; if not, write to the Free Software Foundation,
+    Inc., 51 Franklin Street, Fifth Floor, Boston, MA 02110-1301  USA
+
+\* * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * */
+
+use crate::{axis::Axis, stepper::Stepper, straightener::Straightener, Clamp};
+
+///////////////////////////////////////////////////////////////////////////////
+//  Static Functions
+///////////////////////////////////////////////////////////////////////////////
+
+// Blade open, feed unpowered so it can't be stepped, clamp released and straightener stopped. Safe to
+// call whatever state the actuators were left in, e.g. by a reset mid-cut.
+pub fn safe_state<C: Axis>(
+    cutter: &mut C,
+    feed: &mut Stepper,
+    clamp: Option<&mut Clamp>,
+    straightener: Option<&mut Straightener>,
+) {
+    cutter.home();
+    feed.set_enabled(false);
+    if let Some(clamp) = clamp {
+        clamp.home();
+    }
+    if let Some(straightener) = straightener {
+        straightener.set_running(false);
+    }
+}
@@ -0,0 +1,177 @@
+/* * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * *\
+Copyright (C) 2023 CJ McAllister
+    This program is free software; you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation; either version 3 of the License, or
+    (at your option) any later version.
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+    You should have received a copy of the GNU General Public License
+    along with this program; if not, write to the Free Software Foundation,
+    Inc., 51 Franklin Street, Fifth Floor, Boston, MA 02110-1301  USA
+
+\* * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * */
+
+use core::sync::atomic::{compiler_fence, Ordering};
+
+use crate::platform::pac::{saadc::oversample::OVERSAMPLE_A, SAADC};
+
+///////////////////////////////////////////////////////////////////////////////
+//  Named Constants
+///////////////////////////////////////////////////////////////////////////////
+
+const TRIGGER_TASK: u32 = 1;
+
+// Internal 0.6V reference with 1/6 gain gives a 0-3.6V input range, independent of VDD
+const FULL_SCALE_IN_MV: i32 = 3600;
+const FULL_SCALE_COUNTS: i32 = 1 << 12;
+
+// PSELP value for AIN0; AIN1-AIN7 follow consecutively
+const PSELP_AIN0: u32 = 1;
+const PSELP_VDD: u32 = 9;
+const PSELP_VDDHDIV5: u32 = 0x0D;
+
+const VDDH_DIVISOR: u32 = 5;
+
+///////////////////////////////////////////////////////////////////////////////
+//  Data Structures
+///////////////////////////////////////////////////////////////////////////////
+
+#[allow(dead_code)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, defmt::Format)]
+pub enum AnalogInput {
+    // micro:bit edge connector ring pins P0-P2 (AIN0-AIN2). The other analog inputs are taken by
+    // the microphone and LED matrix.
+    Ring0,
+    Ring1,
+    Ring2,
+    Vdd,
+    VddHDiv5,
+}
+
+#[allow(dead_code)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, defmt::Format)]
+pub enum Oversample {
+    Bypass,
+    Over4x,
+    Over16x,
+    Over64x,
+}
+
+pub struct Analog {
+    saadc: SAADC,
+}
+
+///////////////////////////////////////////////////////////////////////////////
+//  Object Implementations
+///////////////////////////////////////////////////////////////////////////////
+
+impl AnalogInput {
+    fn pselp_bits(self) -> u32 {
+        match self {
+            Self::Ring0 => PSELP_AIN0,
+            Self::Ring1 => PSELP_AIN0 + 1,
+            Self::Ring2 => PSELP_AIN0 + 2,
+            Self::Vdd => PSELP_VDD,
+            Self::VddHDiv5 => PSELP_VDDHDIV5,
+        }
+    }
+}
+
+impl From<Oversample> for OVERSAMPLE_A {
+    fn from(oversample: Oversample) -> Self {
+        match oversample {
+            Oversample::Bypass => OVERSAMPLE_A::BYPASS,
+            Oversample::Over4x => OVERSAMPLE_A::OVER4X,
+            Oversample::Over16x => OVERSAMPLE_A::OVER16X,
+            Oversample::Over64x => OVERSAMPLE_A::OVER64X,
+        }
+    }
+}
+
+impl Analog {
+    pub fn new(saadc: SAADC, oversample: Oversample) -> Self {
+        saadc.enable.write(|w| w.enable().enabled());
+        saadc.resolution.write(|w| w.val()._12bit());
+        saadc
+            .oversample
+            .write(|w| w.oversample().variant(oversample.into()));
+        saadc.samplerate.write(|w| w.mode().task());
+
+        // Single-ended, calibrated internal reference. Burst mode takes all oversamples for a single
+        // SAMPLE task, which oversampling requires when only one channel is enabled.
+        saadc.ch[0].config.write(|w| {
+            w.refsel().internal();
+            w.gain().gain1_6();
+            w.tacq()._10us();
+            w.mode().se();
+            w.resp().bypass();
+            w.resn().bypass();
+            w.burst().enabled();
+            w
+        });
+        saadc.ch[0].pseln.write(|w| w.pseln().nc());
+
+        let mut analog = Self { saadc };
+        analog.calibrate();
+
+        analog
+    }
+
+    // Calibrate the ADC offset. Should be re-run if the die temperature changes significantly.
+    pub fn calibrate(&mut self) {
+        self.saadc.events_calibratedone.reset();
+        self.saadc
+            .tasks_calibrateoffset
+            .write(|w| unsafe { w.bits(TRIGGER_TASK) });
+        while self.saadc.events_calibratedone.read().bits() == 0 {}
+        self.saadc.events_calibratedone.reset();
+    }
+
+    pub fn read_raw(&mut self, input: AnalogInput) -> i16 {
+        self.saadc.ch[0]
+            .pselp
+            .write(|w| unsafe { w.bits(input.pselp_bits()) });
+
+        // EasyDMA writes the result directly into this local
+        let mut result: i16 = 0;
+        self.saadc
+            .result
+            .ptr
+            .write(|w| unsafe { w.ptr().bits(&mut result as *mut i16 as u32) });
+        self.saadc
+            .result
+            .maxcnt
+            .write(|w| unsafe { w.maxcnt().bits(1) });
+
+        // Don't let the compiler reorder the DMA setup past the start of sampling
+        compiler_fence(Ordering::SeqCst);
+
+        self.saadc
+            .tasks_start
+            .write(|w| unsafe { w.bits(TRIGGER_TASK) });
+        self.saadc
+            .tasks_sample
+            .write(|w| unsafe { w.bits(TRIGGER_TASK) });
+        while self.saadc.events_end.read().bits() == 0 {}
+        self.saadc.events_end.reset();
+
+        // Nor let it read the result before DMA has written it
+        compiler_fence(Ordering::SeqCst);
+
+        result
+    }
+
+    pub fn read_mv(&mut self, input: AnalogInput) -> u32 {
+        // Single-ended readings can dip slightly negative from noise around 0V
+        let raw = self.read_raw(input).max(0) as i32;
+        let mv = (raw * FULL_SCALE_IN_MV / FULL_SCALE_COUNTS) as u32;
+
+        match input {
+            AnalogInput::VddHDiv5 => mv * VDDH_DIVISOR,
+            _ => mv,
+        }
+    }
+}
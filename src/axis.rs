@@ -0,0 +1,40 @@
+/* * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * *\
+Copyright (C) 2023 CJ McAllister
+    This program is free software; you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation; either version 3 of the License, or
+    (at your option) any later version.
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+    You should have received a copy of the GNU General Public License
+    along with this program; if not, write to the Free Software Foundation,
+    Inc., 51 Franklin Street, Fifth Floor, Boston, MA 02110-1301  USA
+
+\* * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * */
+
+///////////////////////////////////////////////////////////////////////////////
+//  Data Structures
+///////////////////////////////////////////////////////////////////////////////
+
+// A single actuated degree of freedom (feed, cut, straightener, bender, ...).
+// Positions are in the axis' own units, e.g. steps for a stepper or tenths of a percent duty for a servo.
+pub trait Axis {
+    // Begin moving to the given absolute position
+    fn move_to(&mut self, position: i32);
+
+    // Begin returning to the axis' reference position
+    fn home(&mut self);
+
+    // Last commanded (or reached, for axes which track it) position
+    fn position(&self) -> i32;
+
+    fn is_moving(&self) -> bool;
+
+    // Advance any move in progress. Must be called regularly by axes which aren't driven in hardware.
+    fn poll(&mut self) {}
+
+    // Take up any configured slack at the start of the next move, e.g. after the wire is released
+    fn take_up_slack(&mut self) {}
+}
@@ -0,0 +1,119 @@
+/* * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * *\
+Copyright (C) 2023 CJ McAllister
+    This program is free software; you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation; either version 3 of the License, or
+    (at your option) any later version.
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+    You should have received a copy of the GNU General Public License
+    along with this program; if not, write to the Free Software Foundation,
+    Inc., 51 Franklin Street, Fifth Floor, Boston, MA 02110-1301  USA
+
+\* * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * */
+
+use crate::platform::{
+    hal::{nvmc::Nvmc, prelude::*, timer, twim, Timer, Twim},
+    pac::NVMC,
+};
+
+use crate::{
+    axis::Axis,
+    i2c::lcd1602,
+    qdec::Qdec,
+    settings::{self, Settings},
+    stepper::Stepper,
+    FEED_POLL_INTERVAL_IN_MS, FEED_STEPS_PER_INCH,
+};
+
+///////////////////////////////////////////////////////////////////////////////
+//  Named Constants
+///////////////////////////////////////////////////////////////////////////////
+
+// Wound forward first, so all the play is on the far side when the feed reverses
+const PRELOAD_STEPS: i32 = FEED_STEPS_PER_INCH as i32 / 2;
+
+// Give up if the wheel hasn't turned after this much reversing; something is slipping or unplugged
+const MAX_BACKLASH_STEPS: u32 = 100;
+
+///////////////////////////////////////////////////////////////////////////////
+//  Static Functions
+///////////////////////////////////////////////////////////////////////////////
+
+// Measure the feed axis' backlash, then offer to save it as the compensation in the settings
+pub fn calibrate<T: timer::Instance, U: twim::Instance>(
+    timer: &mut Timer<T>,
+    i2c: &mut Twim<U>,
+    feed: &mut Stepper,
+    encoder: &mut Qdec,
+    settings: &mut Settings,
+    nvmc: &mut Nvmc<NVMC>,
+) {
+    lcd1602::clear_display(timer, i2c);
+    lcd1602::write_string("MEASURING\nBACKLASH...", timer, i2c);
+
+    let measured = measure(timer, feed, encoder);
+    feed.set_backlash(settings.feed_backlash_steps as u32);
+
+    lcd1602::clear_display(timer, i2c);
+    let Some(steps) = measured else {
+        defmt::println!(
+            "Measuring wheel didn't turn within {} steps of reversing",
+            MAX_BACKLASH_STEPS
+        );
+        lcd1602::write_string("NO WHEEL MOTION\nCHECK ENCODER", timer, i2c);
+        crate::await_confirmation(timer, i2c);
+        return;
+    };
+
+    defmt::println!(
+        "Measured feed backlash of {} steps (currently {})",
+        steps,
+        settings.feed_backlash_steps
+    );
+    lcd1602::write_string("BACKLASH ", timer, i2c);
+    lcd1602::write_u32_trimmed(steps, timer, i2c);
+    lcd1602::write_string(" STEP\n#=SAVE *=DISCARD", timer, i2c);
+    if crate::await_confirmation(timer, i2c) {
+        settings.feed_backlash_steps = steps as u16;
+        settings::save(settings, nvmc);
+        feed.set_backlash(steps);
+    }
+}
+
+// Reverse the feed a step at a time after winding it forward, counting the steps taken before the
+// measuring wheel turns. Returns None if it never does.
+fn measure<T: timer::Instance>(
+    timer: &mut Timer<T>,
+    feed: &mut Stepper,
+    encoder: &mut Qdec,
+) -> Option<u32> {
+    // Compensation would hide the very play being measured
+    feed.set_backlash(0);
+    let start = feed.position();
+    move_to(start + PRELOAD_STEPS, timer, feed);
+
+    encoder.reset();
+    let mut reversed_steps = 0;
+    while encoder.position() == 0 && reversed_steps <= MAX_BACKLASH_STEPS {
+        move_to(feed.position() - 1, timer, feed);
+        reversed_steps += 1;
+    }
+    let turned = encoder.position() != 0;
+
+    // Put the wire back where it started
+    move_to(start, timer, feed);
+
+    // The step which finally turned the wheel was real travel, not play
+    turned.then(|| reversed_steps - 1)
+}
+
+fn move_to<T: timer::Instance>(position: i32, timer: &mut Timer<T>, feed: &mut Stepper) {
+    feed.move_to(position);
+    while feed.is_moving() {
+        feed.poll();
+        timer.delay_ms(FEED_POLL_INTERVAL_IN_MS);
+    }
+}
@@ -0,0 +1,249 @@
+/* * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * *\
+Copyright (C) 2023 CJ McAllister
+    This program is free software; you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation; either version 3 of the License, or
+    (at your option) any later version.
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+    You should have received a copy of the GNU General Public License
+    along with this program; if not, write to the Free Software Foundation,
+    Inc., 51 Franklin Street, Fifth Floor, Boston, MA 02110-1301  USA
+
+\* * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * */
+
+use crate::settings::{InputFunction, Settings, MAX_INPUTS};
+
+///////////////////////////////////////////////////////////////////////////////
+//  Named Constants
+///////////////////////////////////////////////////////////////////////////////
+
+// Every GPIO claimed by the firmware. init() takes pins by name from the HAL, so keep this in sync
+// with it; subsystem names double as the LCD error text and must fit on one line.
+pub const ASSIGNMENTS: &[Assignment] = &[
+    Assignment::new("I2C SCL", PinId::p0(26)),         // P19
+    Assignment::new("I2C SDA", PinId::p1(0)),          // P20
+    Assignment::new("I2C RESET", PinId::p1(2)),        // P16
+    Assignment::new("LCD LEVEL SHIFT", PinId::p0(12)), // P12
+    Assignment::new("SPEAKER", PinId::p0(0)),
+    Assignment::new("CUTTER SERVO", PinId::p0(9)), // P9
+    Assignment::new("FEED STEP", PinId::p0(10)),   // P8
+    Assignment::new("FEED DIR", PinId::p0(13)),    // P15
+    Assignment::new("DOOR INTERLOCK", PinId::p0(2)), // P0
+    Assignment::new("FEED ENABLE", PinId::p0(4)),  // P2
+    Assignment::new("ENCODER A", PinId::p0(17)),   // P13
+    Assignment::new("ENCODER B", PinId::p0(1)),    // P14
+    #[cfg(feature = "servo_feedback")]
+    Assignment::new("SERVO FEEDBACK", PinId::p0(3)), // P1
+    #[cfg(feature = "piece_sorter")]
+    Assignment::new("PIECE CHUTE", PinId::p0(3)), // P1
+    #[cfg(feature = "vibration")]
+    Assignment::new("VIBRATION MOTOR", PinId::p0(3)), // P1
+    #[cfg(feature = "clamp")]
+    Assignment::new("CLAMP", PinId::p0(14)), // P5
+    #[cfg(feature = "straightener")]
+    Assignment::new("STRAIGHTENER", PinId::p0(23)), // P11
+    #[cfg(any(feature = "status_stream", feature = "log_mirror"))]
+    Assignment::new("UART TX", PinId::p0(6)),
+    #[cfg(any(feature = "status_stream", feature = "log_mirror"))]
+    Assignment::new("UART RX", PinId::p1(8)),
+    Assignment::new("INTERNAL SCL", PinId::p0(8)),
+    Assignment::new("INTERNAL SDA", PinId::p0(16)),
+    Assignment::new("MATRIX ROW 1", PinId::p0(21)),
+    Assignment::new("MATRIX ROW 2", PinId::p0(22)),
+    Assignment::new("MATRIX ROW 3", PinId::p0(15)),
+    Assignment::new("MATRIX ROW 4", PinId::p0(24)),
+    Assignment::new("MATRIX ROW 5", PinId::p0(19)),
+    Assignment::new("MATRIX COL 1", PinId::p0(28)),
+    Assignment::new("MATRIX COL 2", PinId::p0(11)),
+    Assignment::new("MATRIX COL 3", PinId::p0(31)),
+    Assignment::new("MATRIX COL 4", PinId::p1(5)),
+    Assignment::new("MATRIX COL 5", PinId::p0(30)),
+];
+
+// Names of the configurable digital inputs, in the same register as the fixed assignments
+const INPUT_NAMES: [&str; MAX_INPUTS] = [
+    "INPUT 1", "INPUT 2", "INPUT 3", "INPUT 4", "INPUT 5", "INPUT 6", "INPUT 7", "INPUT 8",
+];
+
+// Subsystems which must have a pin assigned for the enabled features to work
+const REQUIRED: &[&str] = &[
+    "I2C SCL",
+    "I2C SDA",
+    "CUTTER SERVO",
+    #[cfg(not(feature = "counter_only"))]
+    "FEED STEP",
+    #[cfg(not(feature = "counter_only"))]
+    "FEED DIR",
+    #[cfg(feature = "servo_feedback")]
+    "SERVO FEEDBACK",
+    #[cfg(feature = "piece_sorter")]
+    "PIECE CHUTE",
+    #[cfg(feature = "vibration")]
+    "VIBRATION MOTOR",
+    #[cfg(feature = "clamp")]
+    "CLAMP",
+    #[cfg(feature = "straightener")]
+    "STRAIGHTENER",
+    #[cfg(any(feature = "status_stream", feature = "log_mirror"))]
+    "UART TX",
+];
+
+///////////////////////////////////////////////////////////////////////////////
+//  Data Structures
+///////////////////////////////////////////////////////////////////////////////
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, defmt::Format)]
+pub struct PinId {
+    pub port: u8,
+    pub pin: u8,
+}
+
+#[derive(Copy, Clone, Debug, defmt::Format)]
+pub struct Assignment {
+    pub subsystem: &'static str,
+    pub pin: PinId,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, defmt::Format)]
+pub enum ConfigError {
+    // Two subsystems claim the same pin
+    PinConflict {
+        pin: PinId,
+        first: &'static str,
+        second: &'static str,
+    },
+    // An enabled feature's subsystem has no pin
+    MissingPin {
+        subsystem: &'static str,
+    },
+    // A configurable input names a pin the chip doesn't have
+    InvalidPin {
+        subsystem: &'static str,
+    },
+    // Two subsystems claim the same PWM channel, or one needs a whole instance another is using
+    PwmConflict {
+        first: &'static str,
+        second: &'static str,
+    },
+}
+
+///////////////////////////////////////////////////////////////////////////////
+//  Object Implementations
+///////////////////////////////////////////////////////////////////////////////
+
+impl PinId {
+    pub const fn p0(pin: u8) -> Self {
+        Self { port: 0, pin }
+    }
+
+    pub const fn p1(pin: u8) -> Self {
+        Self { port: 1, pin }
+    }
+}
+
+impl Assignment {
+    pub const fn new(subsystem: &'static str, pin: PinId) -> Self {
+        Self { subsystem, pin }
+    }
+}
+
+impl ConfigError {
+    // Short, LCD-line-sized description of the error
+    pub fn message(&self) -> &'static str {
+        match self {
+            Self::PinConflict { .. } => "PIN CONFLICT",
+            Self::MissingPin { .. } => "PIN NOT ASSIGNED",
+            Self::InvalidPin { .. } => "INVALID PIN",
+            Self::PwmConflict { .. } => "PWM CONFLICT",
+        }
+    }
+
+    // The subsystem at fault, for the LCD's second line
+    pub fn subsystem(&self) -> &'static str {
+        match self {
+            Self::PinConflict { second, .. } | Self::PwmConflict { second, .. } => second,
+            Self::MissingPin { subsystem } | Self::InvalidPin { subsystem } => subsystem,
+        }
+    }
+}
+
+///////////////////////////////////////////////////////////////////////////////
+//  Static Functions
+///////////////////////////////////////////////////////////////////////////////
+
+// Check the pin assignments for conflicts and omissions
+pub fn validate() -> Result<(), ConfigError> {
+    for (i, first) in ASSIGNMENTS.iter().enumerate() {
+        if let Some(second) = ASSIGNMENTS[i + 1..].iter().find(|a| a.pin == first.pin) {
+            return Err(ConfigError::PinConflict {
+                pin: first.pin,
+                first: first.subsystem,
+                second: second.subsystem,
+            });
+        }
+    }
+
+    for &subsystem in REQUIRED {
+        if !ASSIGNMENTS.iter().any(|a| a.subsystem == subsystem) {
+            return Err(ConfigError::MissingPin { subsystem });
+        }
+    }
+
+    Ok(())
+}
+
+// Check the configurable inputs' pins exist and are free of each other and the fixed assignments
+pub fn validate_inputs(settings: &Settings) -> Result<(), ConfigError> {
+    // Counter-only builds have nothing else to start a cut
+    #[cfg(feature = "counter_only")]
+    if !settings
+        .inputs
+        .iter()
+        .any(|input| input.function == InputFunction::FootSwitch)
+    {
+        return Err(ConfigError::MissingPin {
+            subsystem: "FOOT SWITCH",
+        });
+    }
+
+    let assigned = || {
+        settings
+            .inputs
+            .iter()
+            .zip(INPUT_NAMES)
+            .filter(|(input, _)| input.function != InputFunction::Unused)
+    };
+
+    for (i, (input, subsystem)) in assigned().enumerate() {
+        if !input.is_valid_pin() {
+            return Err(ConfigError::InvalidPin { subsystem });
+        }
+
+        let pin = PinId {
+            port: input.port(),
+            pin: input.pin(),
+        };
+        if let Some(first) = ASSIGNMENTS.iter().find(|a| a.pin == pin) {
+            return Err(ConfigError::PinConflict {
+                pin,
+                first: first.subsystem,
+                second: subsystem,
+            });
+        }
+        if let Some((_, first)) = assigned()
+            .take(i)
+            .find(|(other, _)| other.psel == input.psel)
+        {
+            return Err(ConfigError::PinConflict {
+                pin,
+                first,
+                second: subsystem,
+            });
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,149 @@
+/* * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * *\
+Copyright (C) 2023 CJ McAllister
+    This program is free software; you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation; either version 3 of the License, or
+    (at your option) any later version.
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+    You should have received a copy of the GNU General Public License
+    along with this program; if not, write to the Free Software Foundation,
+    Inc., 51 Franklin Street, Fifth Floor, Boston, MA 02110-1301  USA
+
+\* * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * */
+
+use crate::platform::hal::{
+    gpio::{Output, Pin, PushPull},
+    prelude::*,
+    pwm::{self, Prescaler, Pwm},
+    time::Hertz,
+    timer, Timer,
+};
+
+use crate::pwm_manager::Allocation;
+use crate::sound::{self, Cue};
+
+///////////////////////////////////////////////////////////////////////////////
+//  Named Constants
+///////////////////////////////////////////////////////////////////////////////
+
+// A frequency of 0 denotes a rest
+const REST: u32 = 0;
+
+const TICK: [Note; 1] = [Note::new(2000, 30)];
+
+const ERROR_TONE: [Note; 3] = [
+    Note::new(880, 150),
+    Note::new(660, 150),
+    Note::new(440, 300),
+];
+
+const MELODY_RISING: [Note; 4] = [
+    Note::new(523, 120),
+    Note::new(659, 120),
+    Note::new(784, 120),
+    Note::new(1047, 300),
+];
+const MELODY_FANFARE: [Note; 6] = [
+    Note::new(784, 100),
+    Note::new(REST, 50),
+    Note::new(784, 100),
+    Note::new(REST, 50),
+    Note::new(784, 100),
+    Note::new(1047, 400),
+];
+const MELODY_BEEPS: [Note; 5] = [
+    Note::new(1000, 200),
+    Note::new(REST, 100),
+    Note::new(1000, 200),
+    Note::new(REST, 100),
+    Note::new(1000, 200),
+];
+
+pub const NUM_MELODIES: u8 = 3;
+
+///////////////////////////////////////////////////////////////////////////////
+//  Data Structures
+///////////////////////////////////////////////////////////////////////////////
+
+#[derive(Copy, Clone, Debug)]
+pub struct Note {
+    freq_hz: u32,
+    duration_ms: u32,
+}
+
+pub struct Buzzer<T: pwm::Instance> {
+    pwm: Pwm<T>,
+}
+
+///////////////////////////////////////////////////////////////////////////////
+//  Object Implementations
+///////////////////////////////////////////////////////////////////////////////
+
+impl Note {
+    pub const fn new(freq_hz: u32, duration_ms: u32) -> Self {
+        Self {
+            freq_hz,
+            duration_ms,
+        }
+    }
+}
+
+impl<T: pwm::Instance> Buzzer<T> {
+    pub fn new(pwm_inst: T, allocation: Allocation, output_pin: Pin<Output<PushPull>>) -> Self {
+        debug_assert!(allocation.is_for::<T>());
+        let pwm = Pwm::new(pwm_inst);
+
+        // 4MHz PWM clock allows tones from ~122Hz up through the audible range
+        pwm.set_prescaler(Prescaler::Div4);
+        pwm.set_output_pin(allocation.channel(), output_pin);
+        pwm.disable();
+
+        Self { pwm }
+    }
+
+    pub fn play<U: timer::Instance>(&mut self, notes: &[Note], timer: &mut Timer<U>) {
+        for note in notes {
+            if note.freq_hz == REST {
+                self.pwm.disable();
+            } else {
+                // 50% duty gives the loudest square wave
+                self.pwm.set_period(Hertz(note.freq_hz));
+                self.pwm.enable();
+                self.pwm.set_duty_on_common(self.pwm.max_duty() / 2);
+            }
+
+            timer.delay_ms(note.duration_ms);
+        }
+
+        self.pwm.disable();
+    }
+
+    // Short click used to count down the final pieces of a job
+    pub fn tick<U: timer::Instance>(&mut self, timer: &mut Timer<U>) {
+        if sound::allows(Cue::Chime) {
+            self.play(&TICK, timer);
+        }
+    }
+
+    // Descending tone signalling that something went wrong
+    pub fn error<U: timer::Instance>(&mut self, timer: &mut Timer<U>) {
+        if sound::allows(Cue::Alert) {
+            self.play(&ERROR_TONE, timer);
+        }
+    }
+
+    pub fn completion_melody<U: timer::Instance>(&mut self, melody: u8, timer: &mut Timer<U>) {
+        if !sound::allows(Cue::Chime) {
+            return;
+        }
+
+        match melody % NUM_MELODIES {
+            0 => self.play(&MELODY_RISING, timer),
+            1 => self.play(&MELODY_FANFARE, timer),
+            _ => self.play(&MELODY_BEEPS, timer),
+        }
+    }
+}
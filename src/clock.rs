@@ -0,0 +1,248 @@
+/* * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * *\
+Copyright (C) 2023 CJ McAllister
+    This program is free software; you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation; either version 3 of the License, or
+    (at your option) any later version.
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+    You should have received a copy of the GNU General Public License
+    along with this program; if not, write to the Free Software Foundation,
+    Inc., 51 Franklin Street, Fifth Floor, Boston, MA 02110-1301  USA
+
+\* * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * */
+
+// Fine-grained time, and alarms for timeouts too long to tie up one of the 1MHz TIMERs, which are
+// kept for motion. Both run off the RTC, so they keep going while the CPU sleeps on a screen waiting
+// for a key.
+
+use core::cell::{Cell, RefCell};
+
+use cortex_m::interrupt::{self as cortex_interrupt, Mutex};
+
+use crate::isr_events::{self, IsrEvent};
+use crate::platform::{
+    hal::rtc::{Rtc, RtcCompareReg, RtcInterrupt},
+    pac::{Interrupt, NVIC, RTC1},
+};
+use crate::tick_watch;
+
+///////////////////////////////////////////////////////////////////////////////
+//  Named Constants
+///////////////////////////////////////////////////////////////////////////////
+
+// Fine-grained ticks are kept by an RTC alongside the uptime counter, so they count even while a
+// critical section holds interrupts off. 32.768kHz / 32 gives 1024 ticks a second, so the 24-bit
+// counter wraps every ~4.5 hours and only intervals shorter than that can be measured.
+const TICK_RTC_PRESCALER: u32 = 31;
+const TICKS_PER_SECOND: u64 = 1024;
+const COUNTER_MASK: u32 = 0x00FF_FFFF;
+
+const MS_PER_SECOND: u64 = 1000;
+
+// A compare value must lead the counter by at least 2 ticks, or the RTC may never see it match
+const MIN_ALARM_TICKS: u32 = 2;
+
+const NUM_ALARMS: usize = 3;
+
+///////////////////////////////////////////////////////////////////////////////
+//  Data Structures
+///////////////////////////////////////////////////////////////////////////////
+
+// A point in time, for measuring how long ago something happened
+#[derive(Copy, Clone, Debug, PartialEq, Eq, defmt::Format)]
+pub struct Instant {
+    ticks: u32,
+}
+
+// One per compare channel of the RTC
+#[derive(Copy, Clone, Debug, PartialEq, Eq, defmt::Format)]
+pub enum Alarm {
+    // Every second, for tick_watch, set again each time it fires
+    Second = 0,
+    Screensaver = 1,
+    // The end of a sleep_ms()
+    Wake = 2,
+}
+
+#[derive(Copy, Clone, PartialEq, Eq)]
+enum AlarmState {
+    Off,
+    Set,
+    Fired,
+}
+
+static TICK_HANDLE: Mutex<RefCell<Option<Rtc<RTC1>>>> = Mutex::new(RefCell::new(None));
+static ALARMS: Mutex<Cell<[AlarmState; NUM_ALARMS]>> =
+    Mutex::new(Cell::new([AlarmState::Off; NUM_ALARMS]));
+
+///////////////////////////////////////////////////////////////////////////////
+//  Object Implementations
+///////////////////////////////////////////////////////////////////////////////
+
+impl Instant {
+    // Time from an earlier instant to this one
+    pub fn ms_since(self, earlier: Instant) -> u32 {
+        let ticks = self.ticks.wrapping_sub(earlier.ticks) & COUNTER_MASK;
+        (ticks as u64 * MS_PER_SECOND / TICKS_PER_SECOND) as u32
+    }
+}
+
+impl Alarm {
+    const ALL: [Alarm; NUM_ALARMS] = [Alarm::Second, Alarm::Screensaver, Alarm::Wake];
+
+    fn register(self) -> RtcCompareReg {
+        match self {
+            Self::Second => RtcCompareReg::Compare0,
+            Self::Screensaver => RtcCompareReg::Compare1,
+            Self::Wake => RtcCompareReg::Compare2,
+        }
+    }
+
+    fn event(self) -> RtcInterrupt {
+        match self {
+            Self::Second => RtcInterrupt::Compare0,
+            Self::Screensaver => RtcInterrupt::Compare1,
+            Self::Wake => RtcInterrupt::Compare2,
+        }
+    }
+}
+
+///////////////////////////////////////////////////////////////////////////////
+//  Static Functions
+///////////////////////////////////////////////////////////////////////////////
+
+// Start counting, and the second tick. LFCLK must already be running.
+pub fn init(instance: RTC1) {
+    let mut rtc = Rtc::new(instance, TICK_RTC_PRESCALER).unwrap();
+    for alarm in Alarm::ALL {
+        rtc.enable_event(alarm.event());
+    }
+    rtc.enable_counter();
+    cortex_interrupt::free(|cs| TICK_HANDLE.borrow(cs).replace(Some(rtc)));
+    set(Alarm::Second, MS_PER_SECOND as u32);
+}
+
+// Before init() it's always the same instant
+pub fn now() -> Instant {
+    let ticks = cortex_interrupt::free(|cs| {
+        TICK_HANDLE
+            .borrow(cs)
+            .borrow()
+            .as_ref()
+            .map_or(0, |rtc| rtc.get_counter())
+    });
+
+    Instant { ticks }
+}
+
+// Have an alarm fire once `after_ms` have passed, replacing any time it was already set for. Only
+// timeouts shorter than the counter's wrap can be set.
+pub fn set(alarm: Alarm, after_ms: u32) {
+    let ticks = (after_ms as u64 * TICKS_PER_SECOND / MS_PER_SECOND) as u32;
+    let ticks = ticks.clamp(MIN_ALARM_TICKS, COUNTER_MASK);
+    cortex_interrupt::free(|cs| {
+        if let Some(rtc) = TICK_HANDLE.borrow(cs).borrow_mut().as_mut() {
+            let at = rtc.get_counter().wrapping_add(ticks) & COUNTER_MASK;
+            rtc.reset_event(alarm.event());
+            rtc.set_compare(alarm.register(), at).unwrap();
+            rtc.enable_interrupt(alarm.event(), None);
+            set_state(alarm, AlarmState::Set, cs);
+        }
+    });
+}
+
+pub fn cancel(alarm: Alarm) {
+    cortex_interrupt::free(|cs| {
+        if let Some(rtc) = TICK_HANDLE.borrow(cs).borrow_mut().as_mut() {
+            rtc.disable_interrupt(alarm.event(), None);
+            rtc.reset_event(alarm.event());
+        }
+        set_state(alarm, AlarmState::Off, cs);
+    });
+}
+
+// Whether an alarm has fired since it was set, which it's then no longer counted as
+pub fn take(alarm: Alarm) -> bool {
+    cortex_interrupt::free(|cs| {
+        let fired = ALARMS.borrow(cs).get()[alarm as usize] == AlarmState::Fired;
+        if fired {
+            set_state(alarm, AlarmState::Off, cs);
+        }
+        fired
+    })
+}
+
+// Sleep until `ms` have passed. The alarm is checked and the CPU put to sleep inside one critical
+// section, so the clock's handler can't mark it fired in between and leave wfi waiting on the next
+// compare, which may be the second tick's. A pending interrupt still wakes the CPU with its handler
+// held off, so the alarms are seen to here before the section ends.
+pub fn sleep_ms(ms: u32) {
+    if cortex_interrupt::free(|cs| TICK_HANDLE.borrow(cs).borrow().is_none()) {
+        return;
+    }
+
+    set(Alarm::Wake, ms);
+    loop {
+        let woke = cortex_interrupt::free(|_| {
+            if take(Alarm::Wake) {
+                return true;
+            }
+            cortex_m::asm::wfi();
+            on_interrupt();
+            NVIC::unpend(Interrupt::RTC1);
+            take(Alarm::Wake)
+        });
+        if woke {
+            break;
+        }
+    }
+}
+
+// Mark the alarms which have fired, and tick the second. Safe from the clock's handler, as nothing
+// here logs.
+pub fn on_interrupt() {
+    let second = cortex_interrupt::free(|cs| {
+        let mut fired = [false; NUM_ALARMS];
+        if let Some(rtc) = TICK_HANDLE.borrow(cs).borrow_mut().as_mut() {
+            for alarm in Alarm::ALL {
+                if !rtc.is_event_triggered(alarm.event()) {
+                    continue;
+                }
+                rtc.reset_event(alarm.event());
+
+                // The counter wrapped round to a compare value that had already fired
+                if ALARMS.borrow(cs).get()[alarm as usize] != AlarmState::Set {
+                    continue;
+                }
+                rtc.disable_interrupt(alarm.event(), None);
+                set_state(alarm, AlarmState::Fired, cs);
+                fired[alarm as usize] = true;
+            }
+        }
+        let second = fired[Alarm::Second as usize];
+        if second {
+            set_state(Alarm::Second, AlarmState::Off, cs);
+        }
+        second
+    });
+
+    if second {
+        set(Alarm::Second, MS_PER_SECOND as u32);
+        isr_events::record(IsrEvent::SecondTick);
+        tick_watch::tick();
+    }
+}
+
+///////////////////////////////////////////////////////////////////////////////
+//  Helper Functions
+///////////////////////////////////////////////////////////////////////////////
+
+fn set_state(alarm: Alarm, state: AlarmState, cs: &cortex_interrupt::CriticalSection) {
+    let alarms = ALARMS.borrow(cs);
+    let mut updated = alarms.get();
+    updated[alarm as usize] = state;
+    alarms.set(updated);
+}
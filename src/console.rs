@@ -0,0 +1,696 @@
+/* * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * *\
+Copyright (C) 2023 CJ McAllister
+    This program is free software; you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation; either version 3 of the License, or
+    (at your option) any later version.
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+    You should have received a copy of the GNU General Public License
+    along with this program; if not, write to the Free Software Foundation,
+    Inc., 51 Franklin Street, Fifth Floor, Boston, MA 02110-1301  USA
+
+\* * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * */
+
+// Serial console on the USB serial port, listened to while the queue screen is up. Hosts send one
+// line at a time and wait for the reply, since nothing is received while the firmware is replying.
+//
+//   import                -> READY, then one `length,count,label` line at a time, each answered with
+//                            OK or ERR, and `end` to finish. Jobs are only queued if every line was good.
+//   settings export       -> SETTINGS <hex>, the saved settings with a trailing CRC-32
+//   settings import <hex> -> OK once the exported settings are checked and saved; applied on restart
+//   dump                  -> the uptime, queue, machine state and recent errors as `key value` lines,
+//                            between DUMP BEGIN and DUMP END
+//   journal               -> the flash event journal, oldest first, as `uptime_s kind arg text` lines
+//                            between JOURNAL BEGIN and JOURNAL END; uptimes restart at each reset entry
+//   history               -> the finished jobs kept in flash, oldest first, as CSV under a header line,
+//                            between HISTORY BEGIN and HISTORY END; each row ends in the machine's
+//                            lifetime totals as of that job (not on minimal builds)
+//   run                   -> OK, then the queue is cut as if '#' was pressed; for machines with no keypad
+//   record export         -> the key presses since power-on as `at_ms,key` lines, between RECORDING BEGIN
+//                            and RECORDING END (input_replay builds only)
+//   tune                  -> every tunable parameter as `name value` lines, between TUNE BEGIN and TUNE END
+//   tune <name>           -> TUNE <name> <value>
+//   tune <name> <value>   -> OK once the parameter is changed, taking effect from the next cycle or key
+//                            press; lost on restart unless saved
+//   tune save             -> OK once the tuned values are saved to settings
+//   stroke <n>            -> STROKE <n> <points>, operator profile n's cut stroke as comma-separated
+//                            `position:hold_ms` points, or `plain` for the tuned closed position and dwell
+//   stroke <n> <points>   -> OK once the stroke is checked and saved; used from the next restart
+//   record import         -> READY, then `at_ms,key` lines as exported, each answered with OK or ERR, and
+//                            `end` to finish. Saved to replay from the next power-on if every line was good.
+//   config                -> every item of the settings menu as `name value` lines, between CONFIG BEGIN
+//                            and CONFIG END; choices are written with `_` for their spaces
+//   config <name>         -> CONFIG <name> <value>
+//   config <name> <value> -> OK once the item is changed and saved, or OK restart to apply for the feed
+//                            backlash and cutter servo rate
+//   input                 -> every configurable input as `n assignment` lines, between INPUT BEGIN and
+//                            INPUT END; an assignment is `unused` or `function:Pport.pin:low|high`
+//   input <n> <assignment> -> OK once the assignment is checked against the board, saved and in use
+//   settings save         -> OK once the running settings are saved as they are, e.g. to accept the
+//                            defaults after the saved settings were found corrupt
+//
+// Jobs can't be imported or run while the saved settings are corrupt.
+
+use core::fmt::{self, Write};
+
+#[cfg(feature = "input_replay")]
+use cutter_core::replay::{Event, Recording};
+use cutter_core::{
+    console::{self, Command, MAX_LINE_LEN},
+    import::{self, JobLimits},
+    motion::StrokeProfile,
+    queue::JobQueue,
+    settings_menu::{Category, Item, Kind},
+    transfer,
+};
+use microbit::hal::{nvmc::Nvmc, timer, twim, Timer, Twim};
+
+use crate::{
+    board_config,
+    i2c::lcd1602,
+    inputs::Inputs,
+    job_history, journal,
+    platform::pac::{NVMC, UARTE0},
+    profiles::{self, Profiles},
+    serial::SerialPort,
+    settings::{self, InputConfig, Settings, MAX_INPUTS, SERIALIZED_LEN},
+    settings_menu,
+    tuning::{self, Param},
+    ERROR_LOG, SERIAL_HANDLE,
+};
+
+///////////////////////////////////////////////////////////////////////////////
+//  Named Constants
+///////////////////////////////////////////////////////////////////////////////
+
+// How long each poll listens for a command; short enough not to slow the keypad
+pub const POLL_WINDOW_IN_US: u32 = 2_000;
+const PAYLOAD_WINDOW_IN_US: u32 = 10_000;
+// Give up on an import if the host goes quiet for this long
+const PAYLOAD_TIMEOUT_IN_MS: u32 = 10_000;
+
+///////////////////////////////////////////////////////////////////////////////
+//  Data Structures
+///////////////////////////////////////////////////////////////////////////////
+
+// What a poll did that the listener needs to act on
+#[derive(Copy, Clone, PartialEq, Eq)]
+pub enum Outcome {
+    Nothing,
+    QueueChanged,
+    Run,
+}
+
+// What commands need besides the queue, lent by whoever is listening
+pub struct Context<'a> {
+    pub nvmc: &'a mut Nvmc<NVMC>,
+    // The running settings, changed and saved by `config` and `input`
+    pub settings: &'a mut Settings,
+    // Polled by screens listening to the console, for the foot switch
+    pub inputs: &'a mut Inputs,
+    // Kept up to date with a changed stroke, so saving them later doesn't undo it
+    pub profiles: &'a mut Profiles,
+    // Writes the state of the machine itself, for `dump`
+    pub dump: &'a mut dyn FnMut(&mut dyn Write, &mut Inputs) -> fmt::Result,
+}
+
+///////////////////////////////////////////////////////////////////////////////
+//  Static Functions
+///////////////////////////////////////////////////////////////////////////////
+
+// Handle a command if one arrives.
+// Nothing can be changed or started while `locked`, e.g. while cutting, only read.
+pub fn poll<T: timer::Instance, U: twim::Instance>(
+    timer: &mut Timer<T>,
+    i2c: &mut Twim<U>,
+    queue: &mut JobQueue,
+    limits: &JobLimits,
+    locked: bool,
+    context: &mut Context,
+) -> Outcome {
+    // Listening means the NVMC is to hand, so it's as good a time as any to write out the journal
+    journal::flush(context.nvmc);
+
+    cortex_m::interrupt::free(|cs| {
+        let mut local_serial_handle_ref = SERIAL_HANDLE.borrow(cs).borrow_mut();
+        let port = local_serial_handle_ref.as_mut().unwrap();
+
+        // Copied out of the port, so it's free to reply while the command is handled
+        let mut line = [0; MAX_LINE_LEN];
+        let len = match port.read_line(timer, POLL_WINDOW_IN_US) {
+            None => return Outcome::Nothing,
+            Some(Ok("")) => return Outcome::Nothing,
+            Some(Ok(received)) => {
+                line[..received.len()].copy_from_slice(received.as_bytes());
+                received.len()
+            }
+            Some(Err(e)) => {
+                let _ = writeln!(port, "ERR {}", e.message());
+                return Outcome::Nothing;
+            }
+        };
+        let command = core::str::from_utf8(&line[..len])
+            .ok()
+            .and_then(Command::parse);
+
+        match command {
+            Some(
+                Command::Import
+                | Command::SettingsImport(_)
+                | Command::Run
+                | Command::RecordImport
+                | Command::TuneSave
+                | Command::StrokeSet(..)
+                | Command::ConfigSet(..)
+                | Command::InputSet(..)
+                | Command::SettingsSave,
+            ) if locked => {
+                let _ = writeln!(port, "ERR locked while cutting");
+                Outcome::Nothing
+            }
+            Some(Command::Import | Command::Run) if settings::is_corrupt() => {
+                let _ = writeln!(port, "ERR settings corrupt");
+                Outcome::Nothing
+            }
+            Some(Command::Import) => {
+                if import_jobs(port, timer, i2c, queue, limits) {
+                    Outcome::QueueChanged
+                } else {
+                    Outcome::Nothing
+                }
+            }
+            Some(Command::SettingsExport) => {
+                export_settings(port);
+                Outcome::Nothing
+            }
+            Some(Command::SettingsImport(hex)) => {
+                import_settings(port, hex, context.nvmc);
+                Outcome::Nothing
+            }
+            Some(Command::Dump) => {
+                let _ = dump(port, queue, context);
+                Outcome::Nothing
+            }
+            Some(Command::Journal) => {
+                let _ = export_journal(port);
+                Outcome::Nothing
+            }
+            Some(Command::History) if !job_history::ENABLED => {
+                let _ = writeln!(port, "ERR left out of minimal builds");
+                Outcome::Nothing
+            }
+            Some(Command::History) => {
+                let _ = export_history(port);
+                Outcome::Nothing
+            }
+            Some(Command::Run) if queue.is_empty() => {
+                let _ = writeln!(port, "ERR queue empty");
+                Outcome::Nothing
+            }
+            Some(Command::Run) => {
+                let _ = writeln!(port, "OK");
+                Outcome::Run
+            }
+            Some(Command::TuneList) => {
+                let _ = list_tuning(port);
+                Outcome::Nothing
+            }
+            Some(Command::TuneGet(name)) => {
+                match Param::from_name(name) {
+                    Some(param) => {
+                        let _ = writeln!(port, "TUNE {} {}", param.name(), tuning::get(param));
+                    }
+                    None => {
+                        let _ = writeln!(port, "ERR no such parameter");
+                    }
+                }
+                Outcome::Nothing
+            }
+            Some(Command::TuneSet(name, value)) => {
+                set_tuning(port, name, value);
+                Outcome::Nothing
+            }
+            Some(Command::TuneSave) => {
+                save_tuning(port, context.nvmc);
+                Outcome::Nothing
+            }
+            Some(Command::StrokeGet(number)) => {
+                match number
+                    .checked_sub(1)
+                    .and_then(|i| context.profiles.get_mut(i))
+                {
+                    Some(profile) => {
+                        let _ = writeln!(port, "STROKE {} {}", number, profile.stroke);
+                    }
+                    None => {
+                        let _ = writeln!(port, "ERR no such profile");
+                    }
+                }
+                Outcome::Nothing
+            }
+            Some(Command::StrokeSet(number, points)) => {
+                set_stroke(port, number, points, context);
+                Outcome::Nothing
+            }
+            Some(Command::ConfigList) => {
+                let _ = list_config(port, context);
+                Outcome::Nothing
+            }
+            Some(Command::ConfigGet(name)) => {
+                match Item::from_name(name) {
+                    Some(item) => {
+                        let value = settings_menu::value(item, context.settings, context.profiles);
+                        let _ = write!(port, "CONFIG {} ", item.name());
+                        let _ = item.kind().write_value(value, port);
+                        let _ = writeln!(port);
+                    }
+                    None => {
+                        let _ = writeln!(port, "ERR no such item");
+                    }
+                }
+                Outcome::Nothing
+            }
+            Some(Command::ConfigSet(name, value)) => {
+                set_config(port, name, value, context);
+                Outcome::Nothing
+            }
+            Some(Command::InputList) => {
+                let _ = list_inputs(port, context.settings);
+                Outcome::Nothing
+            }
+            Some(Command::InputSet(number, spec)) => {
+                set_input(port, number, spec, context);
+                Outcome::Nothing
+            }
+            Some(Command::SettingsSave) => {
+                settings::save(context.settings, context.nvmc);
+                defmt::println!("Saved running settings over serial");
+                let _ = writeln!(port, "OK");
+                Outcome::Nothing
+            }
+            #[cfg(feature = "input_replay")]
+            Some(Command::RecordExport) => {
+                let _ = export_recording(port);
+                Outcome::Nothing
+            }
+            #[cfg(feature = "input_replay")]
+            Some(Command::RecordImport) => {
+                import_recording(port, timer, context.nvmc);
+                Outcome::Nothing
+            }
+            #[cfg(not(feature = "input_replay"))]
+            Some(Command::RecordExport | Command::RecordImport) => {
+                let _ = writeln!(port, "ERR built without input_replay");
+                Outcome::Nothing
+            }
+            None => {
+                let _ = writeln!(port, "ERR unknown command");
+                Outcome::Nothing
+            }
+        }
+    })
+}
+
+// Receive a cut list, queueing it only if every line is good and it all fits
+fn import_jobs<T: timer::Instance, U: twim::Instance>(
+    port: &mut SerialPort<UARTE0>,
+    timer: &mut Timer<T>,
+    i2c: &mut Twim<U>,
+    queue: &mut JobQueue,
+    limits: &JobLimits,
+) -> bool {
+    defmt::println!("Importing jobs over serial");
+    lcd1602::clear_display(timer, i2c);
+    lcd1602::write_string("IMPORTING JOBS\nOVER SERIAL...", timer, i2c);
+    let _ = writeln!(port, "READY");
+
+    let mut staged = queue.clone();
+    let mut line_number = 0;
+    let mut errors = 0;
+    let mut quiet_ms = 0;
+    loop {
+        let result = match port.read_line(timer, PAYLOAD_WINDOW_IN_US) {
+            None => {
+                quiet_ms += PAYLOAD_WINDOW_IN_US / 1000;
+                if quiet_ms >= PAYLOAD_TIMEOUT_IN_MS {
+                    defmt::println!("Serial import timed out");
+                    let _ = writeln!(port, "ERR timed out, queue unchanged");
+                    return false;
+                }
+                continue;
+            }
+            Some(Ok(line)) if console::is_end_of_payload(line) => break,
+            Some(Ok(line)) => import::parse_job_line(line, limits)
+                .and_then(|job| job.map_or(Ok(()), |job| Ok(staged.push(job)?))),
+            Some(Err(e)) => {
+                line_number += 1;
+                errors += 1;
+                let _ = writeln!(port, "ERR line {}: {}", line_number, e.message());
+                quiet_ms = 0;
+                continue;
+            }
+        };
+
+        line_number += 1;
+        quiet_ms = 0;
+        match result {
+            Ok(()) => {
+                let _ = writeln!(port, "OK");
+            }
+            Err(e) => {
+                errors += 1;
+                let _ = writeln!(port, "ERR line {}: {}", line_number, e.message());
+            }
+        }
+    }
+
+    if errors > 0 {
+        defmt::println!("Serial import rejected with {} bad lines", errors);
+        let _ = writeln!(port, "ERR {} bad lines, queue unchanged", errors);
+        return false;
+    }
+
+    let imported = staged.len() - queue.len();
+    *queue = staged;
+    defmt::println!("Imported {} jobs over serial", imported);
+    let _ = writeln!(port, "DONE {} jobs queued", imported);
+
+    true
+}
+
+// Print the settings as saved, rather than as running, so the export is what a restart would load
+fn export_settings(port: &mut SerialPort<UARTE0>) {
+    let _ = write!(port, "SETTINGS ");
+    let _ = transfer::write_hex(&settings::load().to_bytes(), port);
+    let _ = writeln!(port);
+}
+
+// Save settings from another machine's export, provided they suit this build's pin assignments
+fn import_settings(port: &mut SerialPort<UARTE0>, hex: &str, nvmc: &mut Nvmc<NVMC>) {
+    let mut bytes = [0; SERIALIZED_LEN];
+    if let Err(e) = transfer::read_hex(hex, &mut bytes) {
+        let _ = writeln!(port, "ERR {}", e.message());
+        return;
+    }
+    let Some(imported) = Settings::from_bytes(&bytes) else {
+        let _ = writeln!(port, "ERR not a settings export");
+        return;
+    };
+    if let Err(e) = board_config::validate_inputs(&imported) {
+        defmt::println!("Rejected imported settings: {}", e);
+        let _ = writeln!(port, "ERR {}: {}", e.message(), e.subsystem());
+        return;
+    }
+
+    settings::save(&imported, nvmc);
+    defmt::println!("Saved settings imported over serial");
+    let _ = writeln!(port, "OK restart to apply");
+}
+
+fn list_tuning(port: &mut SerialPort<UARTE0>) -> fmt::Result {
+    let tuning = tuning::current();
+    writeln!(port, "TUNE BEGIN")?;
+    for &param in Param::ALL.iter() {
+        writeln!(port, "{} {}", param.name(), tuning.get(param))?;
+    }
+    writeln!(port, "TUNE END")
+}
+
+fn set_tuning(port: &mut SerialPort<UARTE0>, name: &str, value: u32) {
+    let Some(param) = Param::from_name(name) else {
+        let _ = writeln!(port, "ERR no such parameter");
+        return;
+    };
+    if let Err(e) = tuning::set(param, value) {
+        let range = param.range();
+        let _ = writeln!(
+            port,
+            "ERR {}, {}..={}",
+            e.message(),
+            range.start(),
+            range.end()
+        );
+        return;
+    }
+
+    defmt::println!("Tuned {} to {}", param, value);
+    let _ = writeln!(port, "OK");
+}
+
+// Save into the settings as saved, rather than as running, in case an import is waiting for a restart
+fn save_tuning(port: &mut SerialPort<UARTE0>, nvmc: &mut Nvmc<NVMC>) {
+    let mut saved = settings::load();
+    saved.tuning = tuning::current().overrides();
+    settings::save(&saved, nvmc);
+    defmt::println!("Saved tuning: {}", saved.tuning);
+    let _ = writeln!(port, "OK");
+}
+
+fn set_stroke(port: &mut SerialPort<UARTE0>, number: usize, points: &str, context: &mut Context) {
+    let Some(profile) = number
+        .checked_sub(1)
+        .and_then(|i| context.profiles.get_mut(i))
+    else {
+        let _ = writeln!(port, "ERR no such profile");
+        return;
+    };
+    let Some(stroke) = StrokeProfile::parse(points) else {
+        let _ = writeln!(port, "ERR bad stroke");
+        return;
+    };
+    // Shaped strokes are held to the same travel as the tuned blade positions
+    let range = Param::CutClosedDuty.range();
+    if let Some(point) = stroke
+        .points()
+        .iter()
+        .find(|point| !range.contains(&(point.position as u32)))
+    {
+        let _ = writeln!(
+            port,
+            "ERR position {} outside {}..={}",
+            point.position,
+            range.start(),
+            range.end()
+        );
+        return;
+    }
+
+    profile.stroke = stroke;
+    profiles::save(context.profiles, context.nvmc);
+    defmt::println!("Saved cut stroke of profile {}", number);
+    let _ = writeln!(port, "OK");
+}
+
+fn list_config(port: &mut SerialPort<UARTE0>, context: &mut Context) -> fmt::Result {
+    writeln!(port, "CONFIG BEGIN")?;
+    for &item in Category::ALL.iter().flat_map(|category| category.items()) {
+        let value = settings_menu::value(item, context.settings, context.profiles);
+        write!(port, "{} ", item.name())?;
+        item.kind().write_value(value, port)?;
+        writeln!(port)?;
+    }
+    writeln!(port, "CONFIG END")
+}
+
+fn set_config(port: &mut SerialPort<UARTE0>, name: &str, value: &str, context: &mut Context) {
+    let Some(item) = Item::from_name(name) else {
+        let _ = writeln!(port, "ERR no such item");
+        return;
+    };
+    let kind = item.kind();
+    let Some(value) = kind.parse(value) else {
+        let _ = writeln!(port, "ERR bad value");
+        return;
+    };
+    if let Err(e) = settings_menu::change(
+        item,
+        value,
+        context.settings,
+        context.profiles,
+        context.inputs,
+        context.nvmc,
+    ) {
+        match kind {
+            Kind::Number(range) => {
+                let _ = writeln!(
+                    port,
+                    "ERR {}, {}..={}",
+                    e.message(),
+                    range.start(),
+                    range.end()
+                );
+            }
+            Kind::Choice(_) => {
+                let _ = writeln!(port, "ERR {}", e.message());
+            }
+        }
+        return;
+    }
+
+    // The feed and cutter, which keep copies of their own, aren't to hand
+    match item {
+        Item::FeedBacklash | Item::ServoRateHz => {
+            let _ = writeln!(port, "OK restart to apply");
+        }
+        _ => {
+            let _ = writeln!(port, "OK");
+        }
+    }
+}
+
+fn list_inputs(port: &mut SerialPort<UARTE0>, settings: &Settings) -> fmt::Result {
+    writeln!(port, "INPUT BEGIN")?;
+    for (i, input) in settings.inputs.iter().enumerate() {
+        writeln!(port, "{} {}", i + 1, input)?;
+    }
+    writeln!(port, "INPUT END")
+}
+
+// Reassign an input, provided the board leaves its pin free, and put it to use straight away
+fn set_input(port: &mut SerialPort<UARTE0>, number: usize, spec: &str, context: &mut Context) {
+    let Some(index) = number.checked_sub(1).filter(|&i| i < MAX_INPUTS) else {
+        let _ = writeln!(port, "ERR no such input");
+        return;
+    };
+    let Some(input) = InputConfig::parse(spec) else {
+        let _ = writeln!(port, "ERR bad assignment");
+        return;
+    };
+    let mut changed = *context.settings;
+    changed.inputs[index] = input;
+    if let Err(e) = board_config::validate_inputs(&changed) {
+        defmt::println!("Rejected input {} assignment: {}", number, e);
+        let _ = writeln!(port, "ERR {}: {}", e.message(), e.subsystem());
+        return;
+    }
+
+    *context.settings = changed;
+    settings::save(context.settings, context.nvmc);
+    *context.inputs = Inputs::new(context.settings);
+    defmt::println!("Assigned input {}: {}", number, input);
+    let _ = writeln!(port, "OK");
+}
+
+#[cfg(feature = "input_replay")]
+fn export_recording(port: &mut SerialPort<UARTE0>) -> fmt::Result {
+    crate::replay::with_recording(|recording| {
+        writeln!(port, "RECORDING BEGIN")?;
+        for event in recording.events() {
+            writeln!(port, "{}", event)?;
+        }
+        if recording.dropped() > 0 {
+            writeln!(port, "dropped {}", recording.dropped())?;
+        }
+        writeln!(port, "RECORDING END")
+    })
+}
+
+// Receive a recorded session, saving it for replay only if every press is good
+#[cfg(feature = "input_replay")]
+fn import_recording<T: timer::Instance>(
+    port: &mut SerialPort<UARTE0>,
+    timer: &mut Timer<T>,
+    nvmc: &mut Nvmc<NVMC>,
+) {
+    let _ = writeln!(port, "READY");
+
+    let mut recording = Recording::new();
+    let mut line_number = 0;
+    let mut errors = 0;
+    let mut quiet_ms = 0;
+    loop {
+        let result = match port.read_line(timer, PAYLOAD_WINDOW_IN_US) {
+            None => {
+                quiet_ms += PAYLOAD_WINDOW_IN_US / 1000;
+                if quiet_ms >= PAYLOAD_TIMEOUT_IN_MS {
+                    let _ = writeln!(port, "ERR timed out, nothing saved");
+                    return;
+                }
+                continue;
+            }
+            Some(Ok(line)) if console::is_end_of_payload(line) => break,
+            Some(Ok(line)) => Event::parse(line).and_then(|event| recording.push(event)),
+            Some(Err(e)) => {
+                line_number += 1;
+                errors += 1;
+                let _ = writeln!(port, "ERR line {}: {}", line_number, e.message());
+                quiet_ms = 0;
+                continue;
+            }
+        };
+
+        line_number += 1;
+        quiet_ms = 0;
+        match result {
+            Ok(()) => {
+                let _ = writeln!(port, "OK");
+            }
+            Err(e) => {
+                errors += 1;
+                let _ = writeln!(port, "ERR line {}: {}", line_number, e.message());
+            }
+        }
+    }
+
+    if errors > 0 {
+        let _ = writeln!(port, "ERR {} bad lines, nothing saved", errors);
+        return;
+    }
+
+    crate::replay::save(&recording, nvmc);
+    defmt::println!("Saved {} key presses to replay", recording.len());
+    let _ = writeln!(port, "DONE {} presses, restart to replay", recording.len());
+}
+
+fn export_journal(port: &mut SerialPort<UARTE0>) -> fmt::Result {
+    writeln!(port, "JOURNAL BEGIN")?;
+    journal::for_each(|entry| writeln!(port, "{}", entry))?;
+    writeln!(port, "JOURNAL END")
+}
+
+fn export_history(port: &mut SerialPort<UARTE0>) -> fmt::Result {
+    writeln!(port, "HISTORY BEGIN")?;
+    writeln!(port, "{}", job_history::CSV_HEADER)?;
+    job_history::for_each(|record| record.write_csv(port))?;
+    writeln!(port, "HISTORY END")
+}
+
+fn dump(port: &mut SerialPort<UARTE0>, queue: &JobQueue, context: &mut Context) -> fmt::Result {
+    writeln!(port, "DUMP BEGIN")?;
+    writeln!(port, "uptime_s {}", crate::uptime_s())?;
+
+    writeln!(port, "queue_len {}", queue.len())?;
+    for i in 0..queue.len() {
+        let job = queue.get(i).unwrap();
+        let state = if queue.is_active(i) {
+            "active"
+        } else {
+            "pending"
+        };
+        writeln!(
+            port,
+            "job {} {} {}x{} \"{}\"",
+            i + 1,
+            state,
+            job.cut_length,
+            job.num_cuts,
+            job.label()
+        )?;
+    }
+
+    (context.dump)(port, context.inputs)?;
+
+    cortex_m::interrupt::free(|cs| {
+        let log = ERROR_LOG.borrow(cs).borrow();
+        writeln!(port, "errors_total {}", log.total())?;
+        for error in log.iter() {
+            writeln!(port, "error {} {}", error.uptime_s, error.message)?;
+        }
+        Ok(())
+    })?;
+
+    writeln!(port, "DUMP END")
+}
@@ -0,0 +1,108 @@
+/* * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * *\
+Copyright (C) 2023 CJ McAllister
+    This program is free software; you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation; either version 3 of the License, or
+    (at your option) any later version.
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+    You should have received a copy of the GNU General Public License
+    along with this program; if not, write to the Free Software Foundation,
+    Inc., 51 Franklin Street, Fifth Floor, Boston, MA 02110-1301  USA
+
+\* * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * */
+
+use crate::platform::hal::{prelude::*, pwm, timer, twim, Timer, Twim};
+
+use crate::{
+    axis::Axis,
+    buzzer::Buzzer,
+    cycle_timing,
+    events::{self, Event},
+    feed_profile,
+    i2c::{
+        keypad::{self, Key},
+        lcd1602,
+    },
+    log_error, run_cycle, show_door, CycleHardware, KEY_POLL_INTERVAL_IN_MS,
+};
+use cutter_core::motion::Plan;
+
+///////////////////////////////////////////////////////////////////////////////
+//  Static Functions
+///////////////////////////////////////////////////////////////////////////////
+
+// Counter-only mode, for wire fed by hand: each press of the foot switch runs one cut, whatever the
+// pedal is set to do, and the LCD counts the pieces. '#' zeroes the count. Never returns; the machine
+// is powered off when done.
+pub fn run<T: timer::Instance, U: twim::Instance, P: pwm::Instance, C: Axis, F: Axis, D: Axis>(
+    timer: &mut Timer<T>,
+    i2c: &mut Twim<U>,
+    buzzer: &mut Buzzer<P>,
+    hardware: &mut CycleHardware<C, F, D>,
+) -> ! {
+    defmt::println!("Entering counter-only mode");
+
+    // Only the cut half of a cycle; there's nothing to feed
+    let (plan, _) = Plan::cut_cycle(&cycle_timing(), &feed_profile(), 0).split_at_feed();
+
+    let mut count = 0;
+    show_count(count, timer, i2c);
+    events::clear();
+    loop {
+        // Wait for the pedal, keeping the count clearable meanwhile
+        loop {
+            hardware.inputs.poll();
+            if let Some(Event::Pedal(_)) = events::next() {
+                break;
+            }
+            if keypad::scan(timer, i2c) == Some(Key::Pound) {
+                defmt::println!("Piece count cleared at {}", count);
+                count = 0;
+                show_count(count, timer, i2c);
+            }
+            timer.delay_ms(KEY_POLL_INTERVAL_IN_MS);
+        }
+
+        let result = run_cycle(
+            &plan,
+            hardware,
+            timer,
+            None::<&mut Twim<U>>,
+            &mut |timer, open| show_door(open, timer, i2c),
+            None,
+        );
+        match result {
+            Ok(_) => {
+                count += 1;
+                defmt::println!("Cut piece {}", count);
+                show_count(count, timer, i2c);
+            }
+            Err(e) => {
+                // Shown until the next press, which tries the cut again
+                defmt::println!("Cut failed: {}", e);
+                log_error(e.message());
+                buzzer.error(timer);
+                lcd1602::clear_display(timer, i2c);
+                lcd1602::write_string("ERROR: NOT CUT\n", timer, i2c);
+                lcd1602::write_string(e.message(), timer, i2c);
+            }
+        }
+
+        // Presses while cutting don't queue up more cuts
+        events::clear();
+    }
+}
+
+fn show_count<T: timer::Instance, U: twim::Instance>(
+    count: u32,
+    timer: &mut Timer<T>,
+    i2c: &mut Twim<U>,
+) {
+    lcd1602::clear_display(timer, i2c);
+    lcd1602::write_string("PIECES ", timer, i2c);
+    lcd1602::write_u32_trimmed(count, timer, i2c);
+    lcd1602::write_string("\n#=CLEAR", timer, i2c);
+}
@@ -0,0 +1,85 @@
+/* * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * *\
+Copyright (C) 2023 CJ McAllister
+    This program is free software; you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation; either version 3 of the License, or
+    (at your option) any later version.
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+    You should have received a copy of the GNU General Public License
+    along with this program; if not, write to the Free Software Foundation,
+    Inc., 51 Franklin Street, Fifth Floor, Boston, MA 02110-1301  USA
+
+\* * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * */
+
+// Waits for the drivers. Those too short to be worth a hardware timer, e.g. the LCD's bus timings, which
+// are tens of nanoseconds, spin the CPU for a count calibrated against a timer at boot. Longer ones are
+// left to the timer, which then isn't restarted for every LCD strobe.
+
+use core::cell::Cell;
+
+use cortex_m::{
+    asm,
+    interrupt::{self as cortex_interrupt, Mutex},
+};
+
+use crate::platform::hal::{prelude::*, timer, Timer};
+
+///////////////////////////////////////////////////////////////////////////////
+//  Named Constants
+///////////////////////////////////////////////////////////////////////////////
+
+// Anything shorter is busy-waited
+pub const MAX_BUSY_WAIT_IN_NS: u32 = 10_000;
+
+const NS_PER_US: u32 = 1000;
+
+// asm::delay() cycles per microsecond at the 64MHz core clock. Each takes at least one clock, so until
+// calibrated, busy-waits can only run long.
+const NOMINAL_CYCLES_PER_US: u32 = 64;
+// Nominally 1ms, long enough that the timer's 1us resolution is good to 0.1%
+const CALIBRATION_CYCLES: u32 = 1000 * NOMINAL_CYCLES_PER_US;
+const CALIBRATION_TIMEOUT_IN_US: u32 = 1_000_000;
+
+///////////////////////////////////////////////////////////////////////////////
+//  Data Structures
+///////////////////////////////////////////////////////////////////////////////
+
+static CYCLES_PER_US: Mutex<Cell<u32>> = Mutex::new(Cell::new(NOMINAL_CYCLES_PER_US));
+
+///////////////////////////////////////////////////////////////////////////////
+//  Static Functions
+///////////////////////////////////////////////////////////////////////////////
+
+// Time asm::delay() against the timer, which takes flash wait states and the like into account
+pub fn calibrate<T: timer::Instance>(timer: &mut Timer<T>) {
+    timer.start(CALIBRATION_TIMEOUT_IN_US);
+    let start = timer.read();
+    asm::delay(CALIBRATION_CYCLES);
+    let elapsed_us = timer.read().wrapping_sub(start);
+
+    // Rounded down, so busy-waits err on the long side
+    let cycles_per_us = (CALIBRATION_CYCLES / elapsed_us.max(1)).clamp(1, NOMINAL_CYCLES_PER_US);
+    defmt::println!(
+        "Busy-wait calibrated to {} cycles/us over {}us",
+        cycles_per_us,
+        elapsed_us
+    );
+    cortex_interrupt::free(|cs| CYCLES_PER_US.borrow(cs).set(cycles_per_us));
+}
+
+// Wait at least the given time, on the CPU if it's short enough or on the timer otherwise
+pub fn delay_ns<T: timer::Instance>(ns: u32, timer: &mut Timer<T>) {
+    if ns < MAX_BUSY_WAIT_IN_NS {
+        let cycles_per_us = cortex_interrupt::free(|cs| CYCLES_PER_US.borrow(cs).get());
+        asm::delay((ns * cycles_per_us).div_ceil(NS_PER_US));
+    } else {
+        timer.delay_us(ns.div_ceil(NS_PER_US));
+    }
+}
+
+pub fn delay_us<T: timer::Instance>(us: u32, timer: &mut Timer<T>) {
+    delay_ns(us.saturating_mul(NS_PER_US), timer);
+}
@@ -0,0 +1,81 @@
+/* * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * *\
+Copyright (C) 2023 CJ McAllister
+    This program is free software; you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation; either version 3 of the License, or
+    (at your option) any later version.
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+    You should have received a copy of the GNU General Public License
+    along with this program; if not, write to the Free Software Foundation,
+    Inc., 51 Franklin Street, Fifth Floor, Boston, MA 02110-1301  USA
+
+\* * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * */
+
+use microbit::{
+    display::blocking::Display,
+    hal::{timer, twim, Timer, Twim},
+};
+
+use crate::{
+    i2c::{
+        keypad::{self, Key},
+        lcd1602,
+    },
+    led_matrix, splash,
+};
+
+///////////////////////////////////////////////////////////////////////////////
+//  Named Constants
+///////////////////////////////////////////////////////////////////////////////
+
+const DEMO_NUM_CUTS: u32 = 10;
+const DEMO_CUT_DUR_IN_MS: u32 = 600;
+
+///////////////////////////////////////////////////////////////////////////////
+//  Static Functions
+///////////////////////////////////////////////////////////////////////////////
+
+// Loop the splash, a fake cutting job, and the LED matrix show until '*' is pressed.
+// The cutter is never touched, so this is safe to run without any mechanics attached.
+pub fn run<T: timer::Instance, U: twim::Instance>(
+    timer: &mut Timer<T>,
+    i2c: &mut Twim<U>,
+    display: &mut Display,
+) {
+    defmt::println!("Entering demo mode");
+
+    'demo: loop {
+        // The same splash as at power-up
+        if splash::run(&crate::BootSplash::default(), timer, i2c, display) {
+            break 'demo;
+        }
+
+        // Fake progress animation, sweeping the matrix in place of a real cut
+        lcd1602::clear_display(timer, i2c);
+        lcd1602::write_string("DEMO: Cutting\n00000 / ", timer, i2c);
+        lcd1602::write_u32(DEMO_NUM_CUTS, timer, i2c);
+        lcd1602::shift_cursor(lcd1602::Direction::Left, 8, timer, i2c);
+        for i in 1..=DEMO_NUM_CUTS {
+            lcd1602::backspace(5, timer, i2c);
+            lcd1602::write_u32(i, timer, i2c);
+            led_matrix::show_sweep_frame(i as usize, DEMO_CUT_DUR_IN_MS, timer, display);
+
+            if exit_requested(timer, i2c) {
+                break 'demo;
+            }
+        }
+    }
+
+    display.clear();
+    defmt::println!("Exiting demo mode");
+}
+
+fn exit_requested<T: timer::Instance, U: twim::Instance>(
+    timer: &mut Timer<T>,
+    i2c: &mut Twim<U>,
+) -> bool {
+    keypad::scan(timer, i2c) == Some(Key::Star)
+}
@@ -0,0 +1,90 @@
+/* * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * *\
+Copyright (C) 2023 CJ McAllister
+    This program is free software; you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation; either version 3 of the License, or
+    (at your option) any later version.
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+    You should have received a copy of the GNU General Public License
+    along with this program; if not, write to the Free Software Foundation,
+    Inc., 51 Franklin Street, Fifth Floor, Boston, MA 02110-1301  USA
+
+\* * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * */
+
+use cutter_core::job::Fault;
+
+use crate::settings::InputFunction;
+
+///////////////////////////////////////////////////////////////////////////////
+//  Data Structures
+///////////////////////////////////////////////////////////////////////////////
+
+// Faults which stop the machine and put it in its safe "fail state"
+#[allow(dead_code)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, defmt::Format)]
+pub enum CutterError {
+    // Servo feedback showed the blade short of (or past) its closed position
+    BladeNotClosed { measured_mv: u32 },
+    // A configurable input called for the machine to stop
+    InputTripped { function: InputFunction },
+    // A servo was asked for a duty cycle no servo could be driven at
+    ServoDutyInvalid,
+    // A device on the bus held a transaction up past its timeout
+    I2cTimeout { addr: u8 },
+    // A device on the bus didn't acknowledge, or the transfer came up short
+    I2cFailed { addr: u8 },
+}
+
+///////////////////////////////////////////////////////////////////////////////
+//  Object Implementations
+///////////////////////////////////////////////////////////////////////////////
+
+impl CutterError {
+    // Short, LCD-line-sized description of the error
+    pub fn message(&self) -> &'static str {
+        match self {
+            Self::BladeNotClosed { .. } => "BLADE NOT CLOSED",
+            Self::ServoDutyInvalid => "BAD SERVO DUTY",
+            Self::I2cTimeout { .. } => "I2C TIMEOUT",
+            Self::I2cFailed { .. } => "I2C BUS ERROR",
+            Self::InputTripped { function } => match function {
+                InputFunction::LimitSwitch => "LIMIT SWITCH HIT",
+                InputFunction::WireRunout => "WIRE RAN OUT",
+                InputFunction::EstopOk => "E-STOP TRIPPED",
+                InputFunction::Unused | InputFunction::Door | InputFunction::FootSwitch => {
+                    "INPUT TRIPPED"
+                }
+            },
+        }
+    }
+
+    // A blade that stopped short may get through on another stroke; a tripped input needs the operator
+    pub fn is_retryable(&self) -> bool {
+        matches!(self, Self::BladeNotClosed { .. })
+    }
+
+    // Once released, an e-stop leaves nothing wrong with the machine, so the job can be re-zeroed and
+    // carry on
+    pub fn is_clearable(&self) -> bool {
+        self.is_retryable()
+            || matches!(
+                self,
+                Self::InputTripped {
+                    function: InputFunction::EstopOk
+                }
+            )
+    }
+}
+
+impl From<CutterError> for Fault {
+    fn from(e: CutterError) -> Self {
+        Fault {
+            message: e.message(),
+            retryable: e.is_retryable(),
+            clearable: e.is_clearable(),
+        }
+    }
+}
@@ -0,0 +1,83 @@
+/* * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * *\
+Copyright (C) 2023 CJ McAllister
+    This program is free software; you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation; either version 3 of the License, or
+    (at your option) any later version.
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+    You should have received a copy of the GNU General Public License
+    along with this program; if not, write to the Free Software Foundation,
+    Inc., 51 Franklin Street, Fifth Floor, Boston, MA 02110-1301  USA
+
+\* * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * */
+
+use crate::clock::{self, Instant};
+use crate::platform::{hal::nvmc::Nvmc, pac::NVMC};
+use crate::storage::{self, Region};
+
+pub use cutter_core::eta::*;
+
+///////////////////////////////////////////////////////////////////////////////
+//  Data Structures
+///////////////////////////////////////////////////////////////////////////////
+
+// Times the pieces of a job as they finish
+pub struct PieceTimer {
+    planned_ms: u32,
+    last: Option<Instant>,
+    sample: CycleSample,
+}
+
+///////////////////////////////////////////////////////////////////////////////
+//  Object Implementations
+///////////////////////////////////////////////////////////////////////////////
+
+impl PieceTimer {
+    pub fn new(planned_ms: u32) -> Self {
+        Self {
+            planned_ms,
+            last: None,
+            sample: CycleSample::default(),
+        }
+    }
+
+    // A piece has finished. The first only starts the clock, having carried the job's setup and leader.
+    pub fn piece_done(&mut self) {
+        let now = clock::now();
+        if let Some(last) = self.last {
+            self.sample.add_piece(self.planned_ms, now.ms_since(last));
+        }
+        self.last = Some(now);
+    }
+
+    // Don't count the time the job stood still, e.g. paused or waiting to be marked
+    pub fn restart(&mut self) {
+        self.last = Some(clock::now());
+    }
+
+    pub fn sample(&self) -> &CycleSample {
+        &self.sample
+    }
+}
+
+///////////////////////////////////////////////////////////////////////////////
+//  Static Functions
+///////////////////////////////////////////////////////////////////////////////
+
+// Load learned cycle times from flash, starting from the plans if none have been saved yet
+pub fn load() -> CycleTimes {
+    let mut bytes = [0; SERIALIZED_LEN];
+    storage::read(Region::CycleTimes, &mut bytes);
+
+    CycleTimes::from_bytes(&bytes).unwrap_or_else(|| {
+        defmt::println!("No learned cycle times found, estimating from plans");
+        CycleTimes::default()
+    })
+}
+
+pub fn save(times: &CycleTimes, nvmc: &mut Nvmc<NVMC>) {
+    storage::write(Region::CycleTimes, &times.to_bytes(), nvmc);
+}
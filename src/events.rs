@@ -0,0 +1,54 @@
+/* * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * *\
+Copyright (C) 2023 CJ McAllister
+    This program is free software; you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation; either version 3 of the License, or
+    (at your option) any later version.
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+    You should have received a copy of the GNU General Public License
+    along with this program; if not, write to the Free Software Foundation,
+    Inc., 51 Franklin Street, Fifth Floor, Boston, MA 02110-1301  USA
+
+\* * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * */
+
+// Events raised outside the UI loop, e.g. by the inputs as they're polled during a cycle, queued until
+// the screen that handles them next looks.
+
+use core::cell::RefCell;
+
+use cortex_m::interrupt::{self as cortex_interrupt, Mutex};
+pub use cutter_core::events::{Event, EventQueue, MidJob};
+
+///////////////////////////////////////////////////////////////////////////////
+//  Data Structures
+///////////////////////////////////////////////////////////////////////////////
+
+static EVENTS: Mutex<RefCell<EventQueue>> = Mutex::new(RefCell::new(EventQueue::new()));
+
+///////////////////////////////////////////////////////////////////////////////
+//  Static Functions
+///////////////////////////////////////////////////////////////////////////////
+
+pub fn push(event: Event) {
+    defmt::println!("Event: {}", event);
+    cortex_interrupt::free(|cs| EVENTS.borrow(cs).borrow_mut().push(event));
+}
+
+pub fn next() -> Option<Event> {
+    cortex_interrupt::free(|cs| EVENTS.borrow(cs).borrow_mut().pop())
+}
+
+pub fn take_mid_job() -> MidJob {
+    cortex_interrupt::free(|cs| EVENTS.borrow(cs).borrow_mut().take_mid_job())
+}
+
+pub fn clear() {
+    cortex_interrupt::free(|cs| EVENTS.borrow(cs).borrow_mut().clear());
+}
+
+pub fn dropped() -> u32 {
+    cortex_interrupt::free(|cs| EVENTS.borrow(cs).borrow().dropped())
+}
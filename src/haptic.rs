@@ -0,0 +1,74 @@
+/* * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * *\
+Copyright (C) 2023 CJ McAllister
+    This program is free software; you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation; either version 3 of the License, or
+    (at your option) any later version.
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+    You should have received a copy of the GNU General Public License
+    along with this program; if not, write to the Free Software Foundation,
+    Inc., 51 Franklin Street, Fifth Floor, Boston, MA 02110-1301  USA
+
+\* * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * */
+
+// Vibration motor on ring pin 1, switched through a transistor or driver, on builds with one fitted.
+// Runs for the cues the settings allow; everything here does nothing otherwise.
+
+use core::cell::{Cell, RefCell};
+
+use cortex_m::interrupt::{self as cortex_interrupt, Mutex};
+
+pub use cutter_core::haptic::Cue;
+use cutter_core::haptic::Haptic;
+
+use crate::platform::hal::{
+    gpio::{Output, Pin, PushPull},
+    prelude::*,
+    timer, Timer,
+};
+use crate::settings::Settings;
+
+///////////////////////////////////////////////////////////////////////////////
+//  Data Structures
+///////////////////////////////////////////////////////////////////////////////
+
+static MOTOR: Mutex<RefCell<Option<Pin<Output<PushPull>>>>> = Mutex::new(RefCell::new(None));
+static HAPTIC: Mutex<Cell<Haptic>> = Mutex::new(Cell::new(Haptic::DEFAULT));
+
+///////////////////////////////////////////////////////////////////////////////
+//  Static Functions
+///////////////////////////////////////////////////////////////////////////////
+
+// The pin must start low, leaving the motor off
+#[cfg_attr(not(feature = "vibration"), allow(dead_code))]
+pub fn init(pin: Pin<Output<PushPull>>) {
+    cortex_interrupt::free(|cs| MOTOR.borrow(cs).replace(Some(pin)));
+}
+
+// Put changed haptic settings into force
+pub fn set(settings: &Settings) {
+    cortex_interrupt::free(|cs| HAPTIC.borrow(cs).set(settings.haptic));
+}
+
+// Run the motor through a cue's pattern, if one's fitted and the settings allow it
+pub fn play<T: timer::Instance>(cue: Cue, timer: &mut Timer<T>) {
+    let haptic = cortex_interrupt::free(|cs| HAPTIC.borrow(cs).get());
+    if !haptic.allows(cue) {
+        return;
+    }
+
+    // Taken out for the pattern, which is too long to hold interrupts off for
+    let Some(mut motor) = cortex_interrupt::free(|cs| MOTOR.borrow(cs).take()) else {
+        return;
+    };
+    for pulse in cue.pattern() {
+        motor.set_high().unwrap();
+        timer.delay_ms(pulse.on_ms);
+        motor.set_low().unwrap();
+        timer.delay_ms(pulse.off_ms);
+    }
+    cortex_interrupt::free(|cs| MOTOR.borrow(cs).replace(Some(motor)));
+}
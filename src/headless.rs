@@ -0,0 +1,95 @@
+/* * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * *\
+Copyright (C) 2023 CJ McAllister
+    This program is free software; you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation; either version 3 of the License, or
+    (at your option) any later version.
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+    You should have received a copy of the GNU General Public License
+    along with this program; if not, write to the Free Software Foundation,
+    Inc., 51 Franklin Street, Fifth Floor, Boston, MA 02110-1301  USA
+
+\* * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * */
+
+// Headless mode, for cutters built with neither keypad nor LCD. Finding both missing at boot, the
+// machine says so on the serial console, which then does all the keypad would: `config` for the
+// settings menu's items, `input` for the input pin map, `tune` and `stroke` for calibration, and
+// `import` and `run` for jobs.
+
+use core::cell::Cell;
+use core::fmt::Write;
+
+use cortex_m::interrupt::{self as cortex_interrupt, Mutex};
+use microbit::hal::{timer, twim, Timer, Twim};
+
+use cutter_core::{import::JobLimits, queue::JobQueue};
+
+use crate::{
+    console,
+    i2c::{keypad, lcd1602},
+    settings, SERIAL_HANDLE,
+};
+
+///////////////////////////////////////////////////////////////////////////////
+//  Data Structures
+///////////////////////////////////////////////////////////////////////////////
+
+static HEADLESS: Mutex<Cell<bool>> = Mutex::new(Cell::new(false));
+
+///////////////////////////////////////////////////////////////////////////////
+//  Static Functions
+///////////////////////////////////////////////////////////////////////////////
+
+// Check for the keypad and LCD, once the keypad has been initialized
+pub fn detect<U: twim::Instance>(i2c: &mut Twim<U>) -> bool {
+    let headless = !keypad::is_present() && !lcd1602::Lcd::OPERATOR.is_present(i2c);
+    cortex_interrupt::free(|cs| HEADLESS.borrow(cs).set(headless));
+    headless
+}
+
+pub fn is_active() -> bool {
+    cortex_interrupt::free(|cs| HEADLESS.borrow(cs).get())
+}
+
+// Tell whoever is listening on the serial port how this machine is set up and run
+pub fn announce() {
+    send("HEADLESS no keypad or LCD, set up and run over serial");
+    send("HEADLESS config, input, tune and stroke to set up; import and run to cut");
+}
+
+// Stand in for safe mode, whose warning nobody can see or answer: hold the machine, motors off, while
+// the console is used to put the settings right. Saving them as they are or changing any of them ends
+// it, as either leaves saved settings which check out.
+pub fn hold_in_safe_mode<T: timer::Instance, U: twim::Instance>(
+    timer: &mut Timer<T>,
+    i2c: &mut Twim<U>,
+    limits: &JobLimits,
+    console: &mut console::Context,
+) {
+    defmt::println!("Holding in safe mode until settings are fixed over serial");
+    send("HEADLESS settings corrupt, motors off until settings save, config or input");
+
+    // Jobs aren't taken while the settings are corrupt, so nothing is ever queued here
+    let mut queue = JobQueue::new();
+    while settings::is_corrupt() {
+        console::poll(timer, i2c, &mut queue, limits, false, console);
+    }
+
+    defmt::println!("Leaving safe mode");
+    send("HEADLESS settings saved, motors on");
+}
+
+///////////////////////////////////////////////////////////////////////////////
+//  Helper Functions
+///////////////////////////////////////////////////////////////////////////////
+
+fn send(line: &str) {
+    cortex_interrupt::free(|cs| {
+        if let Some(port) = SERIAL_HANDLE.borrow(cs).borrow_mut().as_mut() {
+            let _ = writeln!(port, "{}", line);
+        }
+    });
+}
@@ -0,0 +1,49 @@
+/* * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * *\
+Copyright (C) 2023 CJ McAllister
+    This program is free software; you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation; either version 3 of the License, or
+    (at your option) any later version.
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+    You should have received a copy of the GNU General Public License
+    along with this program; if not, write to the Free Software Foundation,
+    Inc., 51 Franklin Street, Fifth Floor, Boston, MA 02110-1301  USA
+
+\* * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * */
+
+// Which prompt hints the operator has yet to use this session, kept across the menus that show them.
+
+use core::cell::Cell;
+
+use cortex_m::interrupt::{self as cortex_interrupt, Mutex};
+
+use cutter_core::help::Help;
+pub use cutter_core::help::Hint;
+
+///////////////////////////////////////////////////////////////////////////////
+//  Data Structures
+///////////////////////////////////////////////////////////////////////////////
+
+static HELP: Mutex<Cell<Help>> = Mutex::new(Cell::new(Help::NEW));
+
+///////////////////////////////////////////////////////////////////////////////
+//  Static Functions
+///////////////////////////////////////////////////////////////////////////////
+
+// The hint's text, unless it's been used or there aren't `width` columns for it
+pub fn hint(hint: Hint, width: usize) -> Option<&'static str> {
+    cortex_interrupt::free(|cs| HELP.borrow(cs).get().hint(hint, width))
+}
+
+// The operator's used what the hint describes, so it needn't be shown again
+pub fn learn(hint: Hint) {
+    cortex_interrupt::free(|cs| {
+        let help = HELP.borrow(cs);
+        let mut learned = help.get();
+        learned.learn(hint);
+        help.set(learned);
+    });
+}
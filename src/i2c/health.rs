@@ -0,0 +1,119 @@
+/* * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * *\
+Copyright (C) 2023 CJ McAllister
+    This program is free software; you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation; either version 3 of the License, or
+    (at your option) any later version.
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+    You should have received a copy of the GNU General Public License
+    along with this program; if not, write to the Free Software Foundation,
+    Inc., 51 Franklin Street, Fifth Floor, Boston, MA 02110-1301  USA
+
+\* * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * */
+
+// Running tally of how transfers to each device on the buses have gone, so wiring or a level shifter on
+// its way out shows up before it stops a job
+
+use core::cell::RefCell;
+
+use cortex_m::interrupt::{self as cortex_interrupt, Mutex};
+
+use crate::platform::hal::{timer, twim, Timer, Twim};
+
+use super::{
+    internal::{I2C_ADDR_ACCEL, I2C_ADDR_MAG},
+    keypad::{self, Key},
+    lcd1602, I2C_ADDR_KEYPAD, I2C_ADDR_LCD, I2C_ADDR_STATUS_LCD,
+};
+
+pub use cutter_core::bus_health::*;
+
+///////////////////////////////////////////////////////////////////////////////
+//  Data Structures
+///////////////////////////////////////////////////////////////////////////////
+
+static BUS_HEALTH: Mutex<RefCell<BusHealth>> = Mutex::new(RefCell::new(BusHealth::new()));
+
+///////////////////////////////////////////////////////////////////////////////
+//  Static Functions
+///////////////////////////////////////////////////////////////////////////////
+
+// Count a transfer, saying so when it changes how the device is doing
+pub fn record(addr: u8, outcome: Outcome, retried: bool) {
+    let (before, after) = cortex_interrupt::free(|cs| {
+        let mut bus = BUS_HEALTH.borrow(cs).borrow_mut();
+        let before = bus
+            .devices()
+            .iter()
+            .find(|d| d.addr == addr)
+            .map_or(Health::Good, DeviceHealth::health);
+        (before, bus.record(addr, outcome, retried))
+    });
+
+    if after != before {
+        defmt::println!("I2C device {} health now {}", device_name(addr), after);
+    }
+}
+
+// The worst of every device's health
+pub fn overall() -> Health {
+    cortex_interrupt::free(|cs| BUS_HEALTH.borrow(cs).borrow().overall())
+}
+
+// Page through each device's transfer counts, '#' for the next and '*' to exit
+pub fn show_details<T: timer::Instance, U: twim::Instance>(
+    timer: &mut Timer<T>,
+    i2c: &mut Twim<U>,
+) {
+    let mut index = 0;
+    loop {
+        // Copied out, as drawing the page is itself I2C traffic to be counted
+        let bus = cortex_interrupt::free(|cs| *BUS_HEALTH.borrow(cs).borrow());
+        let devices = bus.devices();
+
+        lcd1602::clear_display(timer, i2c);
+        match devices.get(index) {
+            Some(device) => {
+                lcd1602::write_string(device_name(device.addr), timer, i2c);
+                lcd1602::write_string(" ", timer, i2c);
+                lcd1602::write_string(device.health().label(), timer, i2c);
+                lcd1602::write_string("\nN", timer, i2c);
+                lcd1602::write_u32_trimmed(device.failures, timer, i2c);
+                lcd1602::write_string(" R", timer, i2c);
+                lcd1602::write_u32_trimmed(device.retries, timer, i2c);
+                lcd1602::write_string(" T", timer, i2c);
+                lcd1602::write_u32_trimmed(device.timeouts, timer, i2c);
+            }
+            None => lcd1602::write_string("NO I2C TRAFFIC\n*=EXIT", timer, i2c),
+        }
+
+        loop {
+            match keypad::scan(timer, i2c) {
+                Some(Key::Pound) if !devices.is_empty() => {
+                    index = (index + 1) % devices.len();
+                    break;
+                }
+                Some(Key::Star) => return,
+                _ => continue,
+            }
+        }
+    }
+}
+
+///////////////////////////////////////////////////////////////////////////////
+//  Helper Functions
+///////////////////////////////////////////////////////////////////////////////
+
+fn device_name(addr: u8) -> &'static str {
+    match addr {
+        I2C_ADDR_LCD => "LCD",
+        I2C_ADDR_KEYPAD => "KEYPAD",
+        I2C_ADDR_STATUS_LCD => "STATUS LCD",
+        I2C_ADDR_ACCEL => "ACCEL",
+        I2C_ADDR_MAG => "MAG",
+        _ => "UNKNOWN",
+    }
+}
@@ -0,0 +1,123 @@
+/* * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * *\
+Copyright (C) 2023 CJ McAllister
+    This program is free software; you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation; either version 3 of the License, or
+    (at your option) any later version.
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+    You should have received a copy of the GNU General Public License
+    along with this program; if not, write to the Free Software Foundation,
+    Inc., 51 Franklin Street, Fifth Floor, Boston, MA 02110-1301  USA
+\* * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * */
+
+use crate::platform::hal::{prelude::*, timer, twim, Timer, Twim};
+
+use super::*;
+
+///////////////////////////////////////////////////////////////////////////////
+//  Named Constants
+///////////////////////////////////////////////////////////////////////////////
+
+const WHO_AM_I_VALUE: u8 = 0x33;
+
+// 100Hz output data rate, X/Y/Z axes enabled
+const CTRL_REG1_100HZ_XYZ: u8 = 0b0101_0111;
+// Block data update, +/-4g full scale, high-resolution (12-bit) mode
+const CTRL_REG4_BDU_4G_HR: u8 = 0b1001_1000;
+
+// Setting the MSB of the sub-address auto-increments through consecutive registers
+const AUTO_INCREMENT: u8 = 0b1000_0000;
+
+// 12-bit samples are left-justified in 16 bits; each LSB is 2mg at +/-4g
+const HR_SAMPLE_SHIFT: u32 = 4;
+const HR_4G_MG_PER_LSB: i32 = 2;
+
+const SAMPLE_INTERVAL_IN_MS: u32 = 10;
+
+///////////////////////////////////////////////////////////////////////////////
+//  Data Structures
+///////////////////////////////////////////////////////////////////////////////
+
+#[allow(dead_code)]
+#[derive(Copy, Clone, Debug)]
+pub enum AccelRegister {
+    WhoAmI = 0x0F,
+    CtrlReg1 = 0x20,
+    CtrlReg4 = 0x23,
+    Status = 0x27,
+    OutXL = 0x28,
+}
+
+// Acceleration on each axis, in milli-g
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct Sample {
+    pub x: i32,
+    pub y: i32,
+    pub z: i32,
+}
+
+///////////////////////////////////////////////////////////////////////////////
+//  Static Functions
+///////////////////////////////////////////////////////////////////////////////
+
+// Configure the accelerometer for continuous sampling, returning false if it doesn't respond
+pub fn init<U: twim::Instance>(i2c: &mut Twim<U>) -> bool {
+    let who_am_i_reg = AccelRegister::WhoAmI as u8;
+    if !probe(I2C_ADDR_ACCEL, who_am_i_reg, WHO_AM_I_VALUE, i2c) {
+        return false;
+    }
+
+    register_value_set(AccelRegister::CtrlReg1, CTRL_REG1_100HZ_XYZ, i2c);
+    register_value_set(AccelRegister::CtrlReg4, CTRL_REG4_BDU_4G_HR, i2c);
+
+    true
+}
+
+pub fn read<U: twim::Instance>(i2c: &mut Twim<U>) -> Result<Sample, CutterError> {
+    let out_reg_addr = AccelRegister::OutXL as u8 | AUTO_INCREMENT;
+
+    let mut rd_buffer = [0x00; 6];
+    registers_read(I2C_ADDR_ACCEL, out_reg_addr, &mut rd_buffer, i2c)?;
+
+    let to_mg = |lo: u8, hi: u8| {
+        (i16::from_le_bytes([lo, hi]) >> HR_SAMPLE_SHIFT) as i32 * HR_4G_MG_PER_LSB
+    };
+
+    Ok(Sample {
+        x: to_mg(rd_buffer[0], rd_buffer[1]),
+        y: to_mg(rd_buffer[2], rd_buffer[3]),
+        z: to_mg(rd_buffer[4], rd_buffer[5]),
+    })
+}
+
+// Sample for the given duration and return the largest sample-to-sample change, in milli-g.
+// Steady gravity cancels out, so this tracks shaking rather than orientation.
+pub fn measure_vibration<T: timer::Instance, U: twim::Instance>(
+    duration_ms: u32,
+    timer: &mut Timer<T>,
+    i2c: &mut Twim<U>,
+) -> Result<u32, CutterError> {
+    let mut peak_delta = 0;
+    let mut prev_sample = read(i2c)?;
+    let mut elapsed_ms = 0;
+    while elapsed_ms < duration_ms {
+        timer.delay_ms(SAMPLE_INTERVAL_IN_MS);
+        elapsed_ms += SAMPLE_INTERVAL_IN_MS;
+
+        let sample = read(i2c)?;
+        let delta = (sample.x - prev_sample.x).unsigned_abs()
+            + (sample.y - prev_sample.y).unsigned_abs()
+            + (sample.z - prev_sample.z).unsigned_abs();
+        peak_delta = peak_delta.max(delta);
+        prev_sample = sample;
+    }
+
+    Ok(peak_delta)
+}
+
+fn register_value_set<U: twim::Instance>(reg_addr: AccelRegister, value: u8, i2c: &mut Twim<U>) {
+    super::register_value_set(I2C_ADDR_ACCEL, reg_addr as u8, value, i2c);
+}
@@ -0,0 +1,93 @@
+/* * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * *\
+Copyright (C) 2023 CJ McAllister
+    This program is free software; you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation; either version 3 of the License, or
+    (at your option) any later version.
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+    You should have received a copy of the GNU General Public License
+    along with this program; if not, write to the Free Software Foundation,
+    Inc., 51 Franklin Street, Fifth Floor, Boston, MA 02110-1301  USA
+\* * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * */
+
+use crate::platform::hal::{twim, Twim};
+
+use super::*;
+
+///////////////////////////////////////////////////////////////////////////////
+//  Named Constants
+///////////////////////////////////////////////////////////////////////////////
+
+const WHO_AM_I_VALUE: u8 = 0x40;
+
+// Temperature compensation, 10Hz output data rate, continuous mode
+const CFG_REG_A_TEMP_COMP_10HZ_CONT: u8 = 0b1000_0000;
+// Block data update
+const CFG_REG_C_BDU: u8 = 0b0001_0000;
+
+// Each LSB is 1.5 milli-gauss
+const MILLI_GAUSS_PER_LSB_X10: i32 = 15;
+
+///////////////////////////////////////////////////////////////////////////////
+//  Data Structures
+///////////////////////////////////////////////////////////////////////////////
+
+#[allow(dead_code)]
+#[derive(Copy, Clone, Debug)]
+pub enum MagRegister {
+    WhoAmI = 0x4F,
+    CfgRegA = 0x60,
+    CfgRegB = 0x61,
+    CfgRegC = 0x62,
+    Status = 0x67,
+    OutXL = 0x68,
+}
+
+// Magnetic field on each axis, in milli-gauss
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, defmt::Format)]
+pub struct Sample {
+    pub x: i32,
+    pub y: i32,
+    pub z: i32,
+}
+
+///////////////////////////////////////////////////////////////////////////////
+//  Static Functions
+///////////////////////////////////////////////////////////////////////////////
+
+// Configure the magnetometer for continuous sampling, returning false if it doesn't respond
+pub fn init<U: twim::Instance>(i2c: &mut Twim<U>) -> bool {
+    if !probe(I2C_ADDR_MAG, MagRegister::WhoAmI as u8, WHO_AM_I_VALUE, i2c) {
+        return false;
+    }
+
+    register_value_set(
+        I2C_ADDR_MAG,
+        MagRegister::CfgRegA as u8,
+        CFG_REG_A_TEMP_COMP_10HZ_CONT,
+        i2c,
+    );
+    register_value_set(I2C_ADDR_MAG, MagRegister::CfgRegC as u8, CFG_REG_C_BDU, i2c);
+
+    true
+}
+
+pub fn read<U: twim::Instance>(i2c: &mut Twim<U>) -> Result<Sample, CutterError> {
+    // The magnetometer auto-increments without needing the sub-address MSB set
+    let out_reg_addr = MagRegister::OutXL as u8;
+
+    let mut rd_buffer = [0x00; 6];
+    registers_read(I2C_ADDR_MAG, out_reg_addr, &mut rd_buffer, i2c)?;
+
+    let to_milli_gauss =
+        |lo: u8, hi: u8| i16::from_le_bytes([lo, hi]) as i32 * MILLI_GAUSS_PER_LSB_X10 / 10;
+
+    Ok(Sample {
+        x: to_milli_gauss(rd_buffer[0], rd_buffer[1]),
+        y: to_milli_gauss(rd_buffer[2], rd_buffer[3]),
+        z: to_milli_gauss(rd_buffer[4], rd_buffer[5]),
+    })
+}
@@ -0,0 +1,110 @@
+/* * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * *\
+Copyright (C) 2023 CJ McAllister
+    This program is free software; you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation; either version 3 of the License, or
+    (at your option) any later version.
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+    You should have received a copy of the GNU General Public License
+    along with this program; if not, write to the Free Software Foundation,
+    Inc., 51 Franklin Street, Fifth Floor, Boston, MA 02110-1301  USA
+
+\* * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * */
+
+///////////////////////////////////////////////////////////////////////////////
+//  Module Declarations
+///////////////////////////////////////////////////////////////////////////////
+
+// The internal bus only connects the micro:bit's onboard devices, so it runs independently of the
+// external bus shared by the LCD and keypad.
+
+use crate::{
+    error::CutterError,
+    platform::{
+        hal::{twim, Twim},
+        pac::twim0::frequency::FREQUENCY_A,
+    },
+};
+
+use super::timeout;
+
+pub mod accelerometer;
+pub mod magnetometer;
+
+///////////////////////////////////////////////////////////////////////////////
+//  Named Constants
+///////////////////////////////////////////////////////////////////////////////
+
+// Accelerometer and magnetometer halves of the onboard LSM303AGR
+pub const I2C_ADDR_ACCEL: u8 = 0b0011001;
+pub const I2C_ADDR_MAG: u8 = 0b0011110;
+
+///////////////////////////////////////////////////////////////////////////////
+//  Data Structures
+///////////////////////////////////////////////////////////////////////////////
+
+// Which onboard sensors responded during init
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, defmt::Format)]
+pub struct OnboardSensors {
+    pub accelerometer: bool,
+    pub magnetometer: bool,
+}
+
+///////////////////////////////////////////////////////////////////////////////
+//  Helper Functions
+///////////////////////////////////////////////////////////////////////////////
+
+pub fn init<T: twim::Instance>(instance: T, i2c_pins: twim::Pins) -> Twim<T> {
+    // Onboard devices are all 400kHz-capable and the traces are short
+    Twim::new(instance, i2c_pins, FREQUENCY_A::K400)
+}
+
+// Bring up every onboard sensor, recording which are present
+pub fn init_sensors<U: twim::Instance>(i2c: &mut Twim<U>) -> OnboardSensors {
+    OnboardSensors {
+        accelerometer: accelerometer::init(i2c),
+        magnetometer: magnetometer::init(i2c),
+    }
+}
+
+// Check that a device ACKs and reports the expected identity
+pub fn probe<U: twim::Instance>(
+    i2c_addr: u8,
+    who_am_i_reg: u8,
+    expected: u8,
+    i2c: &mut Twim<U>,
+) -> bool {
+    let mut rd_buffer: [u8; 1] = [0x00];
+    timeout::probe_read(i2c_addr, &[who_am_i_reg], &mut rd_buffer, i2c).is_ok()
+        && rd_buffer[0] == expected
+}
+
+pub fn register_value_set<U: twim::Instance>(
+    i2c_addr: u8,
+    reg_addr: u8,
+    value: u8,
+    i2c: &mut Twim<U>,
+) {
+    let reg_addr_and_data: [u8; 2] = [reg_addr, value];
+    if let Err(e) = timeout::write(i2c_addr, &reg_addr_and_data, i2c) {
+        // Only done while bringing the sensors up, when there's nothing to stop
+        defmt::println!("Onboard sensor setup lost: {}", e);
+        crate::log_error(e.message());
+    }
+}
+
+// Read consecutive registers from the first, which the caller has marked for auto-increment if needed
+pub fn registers_read<U: twim::Instance>(
+    i2c_addr: u8,
+    first_reg: u8,
+    rd_buffer: &mut [u8],
+    i2c: &mut Twim<U>,
+) -> Result<(), CutterError> {
+    // Must declare this locally or the I2C driver will panic
+    let reg_addr = first_reg;
+
+    timeout::write_then_read(i2c_addr, &[reg_addr], rd_buffer, i2c)
+}
@@ -13,18 +13,38 @@ Copyright (C) 2022 CJ McAllister
     Inc., 51 Franklin Street, Fifth Floor, Boston, MA 02110-1301  USA
 \* * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * */
 
-use microbit::hal::{timer, twim, Timer, Twim};
+use crate::platform::hal::{timer, twim, Timer, Twim};
 
 #[cfg(feature = "debug_keypad")]
 use rtt_target::rprintln;
 
+use core::cell::Cell;
+
+use cortex_m::interrupt::{self as cortex_interrupt, Mutex};
+
 use super::*;
 
+use crate::clock::{self, Instant};
+use crate::delay;
+use crate::settings::Settings;
+use crate::tuning::{self, Param};
+use cutter_core::input::Debouncer;
+pub use cutter_core::input::Key;
+
 ///////////////////////////////////////////////////////////////////////////////
 //  Named Constants
 ///////////////////////////////////////////////////////////////////////////////
 
-const DEBOUNCE_DELAY_IN_US: u32 = 250;
+// Default for the time between samples while a press settles; it can be tuned
+pub const DEBOUNCE_DELAY_IN_US: u32 = 250;
+// Prompts poll the keypad in a tight loop, so scans are spaced out to leave the bus and CPU for
+// whatever else the loop does. Still far quicker than anyone can press a key.
+const SCAN_INTERVAL_IN_MS: u32 = 20;
+// Stuck key timing until the settings are put into force
+const DEFAULT_STUCK_KEY_TIMING: StuckKeyTiming = StuckKeyTiming {
+    threshold_ms: 1000,
+    poll_interval_ms: 50,
+};
 
 const MASK_C2: u8 = 0b00000001;
 const MASK_R1: u8 = 0b00000010;
@@ -34,183 +54,358 @@ const MASK_C3: u8 = 0b00010000;
 const MASK_R3: u8 = 0b00100000;
 const MASK_R2: u8 = 0b01000000;
 
-#[allow(dead_code)]
 const MASK_ALL_COLS: u8 = MASK_C1 | MASK_C2 | MASK_C3;
 const MASK_ALL_ROWS: u8 = MASK_R1 | MASK_R2 | MASK_R3 | MASK_R4;
 
+// Every pin an input, so no column is driven between scans
+const IODIR_IDLE: u8 = MASK_ALL_ROWS | MASK_ALL_COLS;
+
 ///////////////////////////////////////////////////////////////////////////////
 //  Data Structures
 ///////////////////////////////////////////////////////////////////////////////
 
+// How the keypad's switches are wired to the expander
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
-pub enum Key {
-    One,
-    Two,
-    Three,
-    Four,
-    Five,
-    Six,
-    Seven,
-    Eight,
-    Nine,
-    Star,
-    Zero,
-    Pound,
+pub struct Wiring {
+    // The column being scanned is driven low, and rows read low while their key is pressed
+    pub active_low: bool,
+    // Use the expander's own pull-ups on the rows, for keypads that have none fitted
+    pub row_pullups: bool,
 }
 
+// Columns driven high and rows pulled down on the board, as on the original panel
+pub const DEFAULT_WIRING: Wiring = Wiring {
+    active_low: false,
+    row_pullups: false,
+};
+
+// How long a key has to stay down to be taken for stuck, and how often it's sampled meanwhile
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+struct StuckKeyTiming {
+    threshold_ms: u32,
+    poll_interval_ms: u32,
+}
+
+// A complete key press, and when the key went down. Times are comparable across events, e.g. to spot
+// a double press.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, defmt::Format)]
+pub struct KeyEvent {
+    pub key: Key,
+    pub at: Instant,
+}
+
+static WIRING: Mutex<Cell<Wiring>> = Mutex::new(Cell::new(DEFAULT_WIRING));
+
+static STUCK_KEY_TIMING: Mutex<Cell<StuckKeyTiming>> =
+    Mutex::new(Cell::new(DEFAULT_STUCK_KEY_TIMING));
+
+// Whether the keypad answered at init; without it nothing is ever pressed
+static PRESENT: Mutex<Cell<bool>> = Mutex::new(Cell::new(false));
+
+// When the keypad was last sampled by scan()
+static LAST_SCAN: Mutex<Cell<Option<Instant>>> = Mutex::new(Cell::new(None));
+
 ///////////////////////////////////////////////////////////////////////////////
-//  Object Implementations
+//  Static Functions
 ///////////////////////////////////////////////////////////////////////////////
 
-impl From<Key> for &str {
-    fn from(key: Key) -> Self {
-        match key {
-            Key::One => "1",
-            Key::Two => "2",
-            Key::Three => "3",
-            Key::Four => "4",
-            Key::Five => "5",
-            Key::Six => "6",
-            Key::Seven => "7",
-            Key::Eight => "8",
-            Key::Nine => "9",
-            Key::Star => "*",
-            Key::Zero => "0",
-            Key::Pound => "#",
+// Set up the keypad, returning whether it's there at all, e.g. on a bench setup with no panel wired
+pub fn init<T: twim::Instance>(wiring: Wiring, i2c: &mut Twim<T>) -> bool {
+    if !probe(I2C_ADDR_KEYPAD, i2c) {
+        return false;
+    }
+    cortex_interrupt::free(|cs| {
+        PRESENT.borrow(cs).set(true);
+        WIRING.borrow(cs).set(wiring);
+    });
+
+    // Set every pin on keypad's MCP23008 to Input mode (1); columns are only made outputs while scanned
+    release_columns(i2c);
+
+    let pullups = if wiring.row_pullups { MASK_ALL_ROWS } else { 0 };
+    set_pullups(I2C_ADDR_KEYPAD, pullups, i2c);
+
+    // Let the expander invert active-low rows, so a pressed key always reads as set
+    let inverted = if wiring.active_low { MASK_ALL_ROWS } else { 0 };
+    set_input_polarity(I2C_ADDR_KEYPAD, inverted, i2c);
+
+    true
+}
+
+// Put changed stuck key timing into force
+pub fn set_stuck_key_timing(settings: &Settings) {
+    let timing = StuckKeyTiming {
+        threshold_ms: settings.stuck_key_ms as u32,
+        poll_interval_ms: settings.stuck_key_poll_ms as u32,
+    };
+    cortex_interrupt::free(|cs| STUCK_KEY_TIMING.borrow(cs).set(timing));
+}
+
+pub fn is_present() -> bool {
+    cortex_interrupt::free(|cs| PRESENT.borrow(cs).get())
+}
+
+// Drive all columns and check if any row reads back as active
+pub fn any_row_active<U: twim::Instance>(i2c: &mut Twim<U>) -> bool {
+    if !is_present() {
+        return false;
+    }
+
+    let active = read_columns(MASK_ALL_COLS, i2c) > 0;
+    release_columns(i2c);
+
+    active
+}
+
+// Check for a key that stays active longer than any reasonable press, e.g. a shorted switch
+pub fn is_key_stuck<T: timer::Instance, U: twim::Instance>(
+    timer: &mut Timer<T>,
+    i2c: &mut Twim<U>,
+) -> bool {
+    let timing = cortex_interrupt::free(|cs| STUCK_KEY_TIMING.borrow(cs).get());
+    let mut active_time_ms = 0;
+    while any_row_active(i2c) {
+        if active_time_ms >= timing.threshold_ms {
+            return true;
         }
+
+        timer.delay_ms(timing.poll_interval_ms);
+        active_time_ms += timing.poll_interval_ms;
     }
+
+    false
 }
 
-///////////////////////////////////////////////////////////////////////////////
-//  Static Functions
-///////////////////////////////////////////////////////////////////////////////
+// Check whether '*' is held right now, without waiting for it to be let go as scan() does
+pub fn is_star_held<U: twim::Instance>(i2c: &mut Twim<U>) -> bool {
+    if !is_present() {
+        return false;
+    }
+
+    let held = read_columns(MASK_C1, i2c) & MASK_R4 > 0;
+    release_columns(i2c);
 
-pub fn init<T: twim::Instance>(i2c: &mut Twim<T>) {
-    // Set row pins on keypad's MCP23008 to Input mode (1), leave columns in Output mode (0)
-    register_value_set(I2C_ADDR_KEYPAD, MCP23008Register::IODIR, MASK_ALL_ROWS, i2c);
+    held
 }
 
-//OPT: Probably a more clever way to do this...
-// Sweep across keypad columns and read each row to get button presses
+// Check whether '*' and '#' are held together, which no single press can look like. They share a row,
+// so each column has to be driven on its own.
+pub fn is_chord_held<U: twim::Instance>(i2c: &mut Twim<U>) -> bool {
+    if !is_present() {
+        return false;
+    }
+
+    let star_held = read_columns(MASK_C1, i2c) & MASK_R4 > 0;
+    let pound_held = read_columns(MASK_C3, i2c) & MASK_R4 > 0;
+    release_columns(i2c);
+
+    star_held && pound_held
+}
+
+// Block until all keys have been released
+pub fn wait_for_release<T: timer::Instance, U: twim::Instance>(
+    timer: &mut Timer<T>,
+    i2c: &mut Twim<U>,
+) {
+    let timing = cortex_interrupt::free(|cs| STUCK_KEY_TIMING.borrow(cs).get());
+    while any_row_active(i2c) {
+        timer.delay_ms(timing.poll_interval_ms);
+    }
+}
+
+// Check for a key press, returning it once the key has been released and has stopped bouncing
 pub fn scan<T: timer::Instance, U: twim::Instance>(
     timer: &mut Timer<T>,
     i2c: &mut Twim<U>,
 ) -> Option<Key> {
+    scan_event(timer, i2c).map(|event| event.key)
+}
+
+// As scan(), for screens waiting on the keypad alone: the CPU sleeps until the next scan is due rather
+// than spinning, and the display blanks if the operator's left it long enough
+pub fn scan_idle<T: timer::Instance, U: twim::Instance>(
+    timer: &mut Timer<T>,
+    i2c: &mut Twim<U>,
+) -> Option<Key> {
+    crate::screensaver::poll(timer, i2c);
+
+    let last_scan = cortex_interrupt::free(|cs| LAST_SCAN.borrow(cs).get());
+    if let Some(last) = last_scan {
+        let since_ms = clock::now().ms_since(last);
+        if since_ms < SCAN_INTERVAL_IN_MS {
+            clock::sleep_ms(SCAN_INTERVAL_IN_MS - since_ms);
+        }
+    }
+
+    scan(timer, i2c)
+}
+
+// As scan(), keeping the time of the press
+pub fn scan_event<T: timer::Instance, U: twim::Instance>(
+    timer: &mut Timer<T>,
+    i2c: &mut Twim<U>,
+) -> Option<KeyEvent> {
+    // A replayed session stands in for the keypad, which may not even be fitted on the bench
+    #[cfg(feature = "input_replay")]
+    if crate::replay::is_replaying() {
+        return crate::replay::next_event();
+    }
+    if !is_present() {
+        return None;
+    }
+
+    // Too soon since the last scan, so leave the bus alone
+    let now = clock::now();
+    let last_scan = cortex_interrupt::free(|cs| LAST_SCAN.borrow(cs).get());
+    if last_scan.is_some_and(|last| now.ms_since(last) < SCAN_INTERVAL_IN_MS) {
+        return None;
+    }
+
+    // Nothing pressed, so nothing to debounce
+    let raw = sample(i2c);
+    cortex_interrupt::free(|cs| LAST_SCAN.borrow(cs).set(Some(now)));
+    let mut raw = Some(raw?);
+
+    // Keep sampling until the debouncer settles on either a complete press or a spurious one
+    let mut debouncer = Debouncer::new();
+    loop {
+        if let Some(key) = debouncer.update(raw) {
+            if crate::screensaver::wake(timer, i2c) {
+                return None;
+            }
+            crate::haptic::play(crate::haptic::Cue::KeyPress, timer);
+            let event = KeyEvent { key, at: now };
+            #[cfg(feature = "input_replay")]
+            crate::replay::record(&event);
+            return Some(event);
+        }
+        if debouncer.is_idle() {
+            return None;
+        }
+
+        #[cfg(feature = "debug_keypad")]
+        rprintln!("DEBUG_KEYPAD: Debouncing '{:?}'...", raw);
+        delay::delay_us(tuning::get(Param::KeyDebounceUs), timer);
+        raw = sample(i2c);
+    }
+}
+
+//OPT: Probably a more clever way to do this...
+// Sweep across keypad columns and read each row to get the raw, undebounced key state
+fn sample<U: twim::Instance>(i2c: &mut Twim<U>) -> Option<Key> {
     let mut pressed_key = None;
-    let mut key_pressed = false;
 
-    // Set C1 High and read Row values for presses
-    gpio_write(I2C_ADDR_KEYPAD, MASK_C1, i2c);
-    let c1_presses = gpio_read(I2C_ADDR_KEYPAD, i2c);
+    // Drive C1 and read Row values for presses
+    let c1_presses = read_columns(MASK_C1, i2c);
 
     // Check for "1" press
     if c1_presses & MASK_R1 > 0 {
         #[cfg(feature = "debug_keypad")]
         rprintln!("DEBUG_KEYPAD: '1' Pressed");
         pressed_key = Some(Key::One);
-        key_pressed = true;
     }
     // Check for "4" press
     if c1_presses & MASK_R2 > 0 {
         #[cfg(feature = "debug_keypad")]
         rprintln!("DEBUG_KEYPAD: '4' Pressed");
         pressed_key = Some(Key::Four);
-        key_pressed = true;
     }
     // Check for "7" press
     if c1_presses & MASK_R3 > 0 {
         #[cfg(feature = "debug_keypad")]
         rprintln!("DEBUG_KEYPAD: '7' Pressed");
         pressed_key = Some(Key::Seven);
-        key_pressed = true;
     }
     // Check for "*" press
     if c1_presses & MASK_R4 > 0 {
         #[cfg(feature = "debug_keypad")]
         rprintln!("DEBUG_KEYPAD: '*' Pressed");
         pressed_key = Some(Key::Star);
-        key_pressed = true;
     }
 
-    // Set C2 High and read Row values for presses
-    gpio_write(I2C_ADDR_KEYPAD, MASK_C2, i2c);
-    let c2_presses = gpio_read(I2C_ADDR_KEYPAD, i2c);
+    // Drive C2 and read Row values for presses
+    let c2_presses = read_columns(MASK_C2, i2c);
 
     // Check for "2" press
     if c2_presses & MASK_R1 > 0 {
         #[cfg(feature = "debug_keypad")]
         rprintln!("DEBUG_KEYPAD: '2' Pressed");
         pressed_key = Some(Key::Two);
-        key_pressed = true;
     }
     // Check for "5" press
     if c2_presses & MASK_R2 > 0 {
         #[cfg(feature = "debug_keypad")]
         rprintln!("DEBUG_KEYPAD: '5' Pressed");
         pressed_key = Some(Key::Five);
-        key_pressed = true;
     }
     // Check for "8" press
     if c2_presses & MASK_R3 > 0 {
         #[cfg(feature = "debug_keypad")]
         rprintln!("DEBUG_KEYPAD: '8' Pressed");
         pressed_key = Some(Key::Eight);
-        key_pressed = true;
     }
     // Check for "0" press
     if c2_presses & MASK_R4 > 0 {
         #[cfg(feature = "debug_keypad")]
         rprintln!("DEBUG_KEYPAD: '0' Pressed");
         pressed_key = Some(Key::Zero);
-        key_pressed = true;
     }
 
-    // Set C3 High and read Row values for presses
-    gpio_write(I2C_ADDR_KEYPAD, MASK_C3, i2c);
-    let c3_presses = gpio_read(I2C_ADDR_KEYPAD, i2c);
+    // Drive C3 and read Row values for presses
+    let c3_presses = read_columns(MASK_C3, i2c);
 
     // Check for "3" press
     if c3_presses & MASK_R1 > 0 {
         #[cfg(feature = "debug_keypad")]
         rprintln!("DEBUG_KEYPAD: '3' Pressed");
         pressed_key = Some(Key::Three);
-        key_pressed = true;
     }
     // Check for "6" press
     if c3_presses & MASK_R2 > 0 {
         #[cfg(feature = "debug_keypad")]
         rprintln!("DEBUG_KEYPAD: '6' Pressed");
         pressed_key = Some(Key::Six);
-        key_pressed = true;
     }
     // Check for "9" press
     if c3_presses & MASK_R3 > 0 {
         #[cfg(feature = "debug_keypad")]
         rprintln!("DEBUG_KEYPAD: '9' Pressed");
         pressed_key = Some(Key::Nine);
-        key_pressed = true;
     }
     // Check for "#" press
     if c3_presses & MASK_R4 > 0 {
         #[cfg(feature = "debug_keypad")]
         rprintln!("DEBUG_KEYPAD: '#' Pressed");
         pressed_key = Some(Key::Pound);
-        key_pressed = true;
     }
 
-    if key_pressed {
-        //OPT: This debouncing implementation will blow up if a key is held for too long...
-        // Key was pressed, to "debounce" scan until it's no longer pressed
-        while let Some(_still_pressed_key) = scan(timer, i2c) {
-            #[cfg(feature = "debug_keypad")]
-            rprintln!("DEBUG_KEYPAD: Debouncing '{:?}'...", _still_pressed_key);
-            timer.delay_us(DEBOUNCE_DELAY_IN_US);
-        }
+    release_columns(i2c);
 
-        // Return the pressed key
-        pressed_key
-    } else {
-        // No key was pressed, return None
-        None
-    }
+    pressed_key
+}
+
+// Drive the given columns at the active level and read which rows are active. Rows are inverted by
+// the expander if need be, so this is the same whatever the wiring.
+fn read_columns<U: twim::Instance>(col_mask: u8, i2c: &mut Twim<U>) -> u8 {
+    let wiring = cortex_interrupt::free(|cs| WIRING.borrow(cs).get());
+
+    // Set the level before making the columns outputs, so they never glitch to the active one
+    gpio_write(
+        I2C_ADDR_KEYPAD,
+        if wiring.active_low { 0 } else { col_mask },
+        i2c,
+    );
+    register_value_set(
+        I2C_ADDR_KEYPAD,
+        MCP23008Register::IODIR,
+        IODIR_IDLE & !col_mask,
+        i2c,
+    );
+
+    gpio_read(I2C_ADDR_KEYPAD, i2c) & MASK_ALL_ROWS
+}
+
+// Leave every column undriven, so nothing is left powered between scans
+fn release_columns<U: twim::Instance>(i2c: &mut Twim<U>) {
+    register_value_set(I2C_ADDR_KEYPAD, MCP23008Register::IODIR, IODIR_IDLE, i2c);
 }
@@ -13,48 +13,121 @@ Copyright (C) 2022 CJ McAllister
     Inc., 51 Franklin Street, Fifth Floor, Boston, MA 02110-1301  USA
 \* * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * */
 
-use microbit::hal::{prelude::*, timer, twim, Timer, Twim};
+// HD44780 character LCDs behind MCP23008 expanders on the I2C bus. Every LCD the machine drives goes
+// through here, the operator's through the free functions and any other through CharacterDisplay, so
+// there's one driver for fixes to land in.
+
+use crate::delay;
+use crate::platform::hal::{timer, twim, Timer, Twim};
+use core::cell::Cell;
+
+use cortex_m::interrupt::{self as cortex_interrupt, Mutex};
+use cutter_core::{
+    big_digits,
+    charset::{Glyph, Rom},
+    frame::Frame,
+    numeric,
+    widgets::{Area, Widget},
+};
 
 use super::*;
 
+use crate::clock::{self, Instant};
+
 ///////////////////////////////////////////////////////////////////////////////
 //  Named Constants
 ///////////////////////////////////////////////////////////////////////////////
 
 const LCD_MAX_LINE_LENGTH: usize = 16;
+
+// Each line holds 40 characters, of which 16 are visible, so a screen can be laid out as pages side by
+// side and the display panned between them
+pub const PAGE_WIDTH: usize = LCD_MAX_LINE_LENGTH;
+pub const NUM_PAGES: usize = 2;
 const LCD_MAX_NEWLINES: usize = 1;
 #[allow(dead_code)]
 const ASCII_INT_OFFSET: usize = 48;
 
 const MASK_RS: u8 = 0b00000001;
-#[allow(dead_code)]
 const MASK_RW: u8 = 0b00000010;
 const MASK_EN: u8 = 0b00000100;
 const MASK_D4: u8 = 0b00001000;
 const MASK_D5: u8 = 0b00010000;
 const MASK_D6: u8 = 0b00100000;
 const MASK_D7: u8 = 0b01000000;
+const MASK_DATA: u8 = MASK_D4 | MASK_D5 | MASK_D6 | MASK_D7;
 const MASK_ALL: u8 = 0b01111111;
 const MASK_NONE: u8 = 0b00000000;
 const MASK_PWR: u8 = 0b10000000;
 
+// Instructions, with their argument bits clear
+const CMD_CLEAR_DISPLAY: u8 = 0b0000_0001;
+const CMD_DISPLAY_CONTROL: u8 = 0b0000_1000;
+const CMD_SHIFT: u8 = 0b0001_0000;
+const CMD_SET_CGRAM_ADDR: u8 = 0b0100_0000;
+const CMD_SET_DDRAM_ADDR: u8 = 0b1000_0000;
+
+// Display control argument bits
+const DISPLAY_ON: u8 = 0b100;
+const CURSOR_ON: u8 = 0b010;
+const CURSOR_BLINK: u8 = 0b001;
+
+// Shift argument bits
+const SHIFT_DISPLAY: u8 = 0b1000;
+const SHIFT_RIGHT: u8 = 0b0100;
+
+// DDRAM address of the start of the second line
+const LINE_2_ADDR: u8 = 0x40;
+
+// Set alongside the address counter while an instruction is still executing
+const BUSY_FLAG: u8 = 0b1000_0000;
+
+// Written past the last page, where it's never seen, and read back to check the controller
+const SELF_TEST_PATTERN: [u8; 4] = [0x55, 0xAA, 0x00, 0xFF];
+
 /*  *  *  *  *  *  *  *  *  *  *  *  *  *  *  *  *  *  *  *  *\
  *   5V Bus Timing Characteristics, per HD44789U datasheet   *
 \*  *  *  *  *  *  *  *  *  *  *  *  *  *  *  *  *  *  *  *  */
 
 // Power supply rise time
 const T_RCC_IN_MS: u32 = 10;
+// Wait after VCC reaches 4.5V before the controller accepts instructions
+const T_POWER_ON_WAIT_IN_MS: u32 = 15;
+// Execution time of the first reset Function Set, while the busy flag can't yet be trusted
+const T_RESET_1_IN_US: u32 = 4100;
+// Execution time of the second reset Function Set
+const T_RESET_2_IN_US: u32 = 100;
+// Execution time of Clear Display
+const T_CLEAR_IN_US: u32 = 1520;
 // Address set-up time (RS, R/W to E)
-const T_AS_IN_US: u32 = 40;
+const T_AS_IN_NS: u32 = 40;
 // Enable pulse width (high level)
-const PW_EH_IN_US: u32 = 230;
+const PW_EH_IN_NS: u32 = 230;
 // Enable cycle time
-const T_CYCE_IN_US: u32 = 500;
+const T_CYCE_IN_NS: u32 = 500;
+
+// Clones take anything from well under to twice the datasheet's Clear Display time, so the operator's
+// display is timed at init. The busy flag is sampled ever later after a clear, in steps, until it reads
+// clear; a wait of what that took plus a margin replaces the datasheet's.
+const CLEAR_CALIBRATION_STEP_IN_US: u32 = 100;
+const MAX_CLEAR_IN_US: u32 = 2 * T_CLEAR_IN_US;
+const CLEAR_MARGIN_PCT: u32 = 20;
+const CALIBRATION_TIMEOUT_IN_US: u32 = 1_000_000;
+
+// Buffered screens are drawn at most ~4 times a second; any quicker is unreadable anyway, and each
+// character written costs ~2ms of bus time
+const FRAME_INTERVAL_IN_MS: u32 = 250;
 
 ///////////////////////////////////////////////////////////////////////////////
 //  Data Structures
 ///////////////////////////////////////////////////////////////////////////////
 
+// An HD44780 display behind an MCP23008 expander at the given address
+#[derive(Copy, Clone)]
+pub struct Lcd {
+    addr: u8,
+}
+
 #[allow(dead_code)]
 #[derive(PartialEq, Eq)]
 pub enum Direction {
@@ -62,6 +135,105 @@ pub enum Direction {
     Right,
 }
 
+#[allow(dead_code)]
+#[derive(Copy, Clone, PartialEq, Eq)]
+pub enum CursorStyle {
+    Off,
+    Underline,
+    Blinking,
+}
+
+// The fitted module's character ROM, which decides the codes characters are written as
+static ROM: Mutex<Cell<Rom>> = Mutex::new(Cell::new(Rom::A00));
+
+// Display control bits last written, since the display and cursor are switched with one instruction
+static DISPLAY_CONTROL: Mutex<Cell<u8>> = Mutex::new(Cell::new(0));
+
+// How long the operator's display is given to clear, once measured
+static CLEAR_TIME_IN_US: Mutex<Cell<u32>> = Mutex::new(Cell::new(T_CLEAR_IN_US));
+
+// Columns the display is panned left by, which clearing it resets
+static DISPLAY_SHIFT: Mutex<Cell<usize>> = Mutex::new(Cell::new(0));
+
+// Screen built up by buffer_*() and drawn by draw_frame(); clearing the display clears it too. A screen
+// drawn this way must not be written to directly as well, or the frame won't match what's showing.
+static FRAME: Mutex<Cell<Frame>> = Mutex::new(Cell::new(Frame::new()));
+static LAST_FRAME_DRAWN: Mutex<Cell<Option<Instant>>> = Mutex::new(Cell::new(None));
+
+///////////////////////////////////////////////////////////////////////////////
+//  Object Implementations
+///////////////////////////////////////////////////////////////////////////////
+
+impl Lcd {
+    // The interactive display, which the free functions below also drive
+    pub const OPERATOR: Self = Self { addr: I2C_ADDR_LCD };
+    // Optional second display, for showing the state of the machine to the room
+    pub const STATUS: Self = Self {
+        addr: I2C_ADDR_STATUS_LCD,
+    };
+
+    pub fn is_present<U: twim::Instance>(&self, i2c: &mut Twim<U>) -> bool {
+        probe(self.addr, i2c)
+    }
+}
+
+impl CharacterDisplay for Lcd {
+    // As init(), but with none of the operator display's cursor or panning state
+    fn init<T: timer::Instance, U: twim::Instance>(
+        &mut self,
+        timer: &mut Timer<T>,
+        i2c: &mut Twim<U>,
+    ) {
+        register_value_set(self.addr, MCP23008Register::IODIR, 0b00000000, i2c);
+        gpio_set_rmw(self.addr, MASK_PWR, i2c);
+        timer.delay_ms(T_RCC_IN_MS + T_POWER_ON_WAIT_IN_MS);
+
+        set_4bit_2line_mode(self.addr, timer, i2c);
+        write_instruction(self.addr, CMD_DISPLAY_CONTROL, timer, i2c);
+        self.clear(timer, i2c);
+        set_autoincrement(self.addr, timer, i2c);
+        load_glyphs(self.addr, timer, i2c);
+        write_instruction(self.addr, CMD_DISPLAY_CONTROL | DISPLAY_ON, timer, i2c);
+    }
+
+    fn clear<T: timer::Instance, U: twim::Instance>(
+        &mut self,
+        timer: &mut Timer<T>,
+        i2c: &mut Twim<U>,
+    ) {
+        // Higher-order data bits write
+        reset_pins(self.addr, i2c);
+        gpio_set_rmw(self.addr, MASK_NONE, i2c);
+        pulse_enable(self.addr, timer, i2c);
+
+        // Lower-order data bits write
+        reset_pins(self.addr, i2c);
+        gpio_set_rmw(self.addr, MASK_D4, i2c);
+        pulse_enable(self.addr, timer, i2c);
+
+        // Other displays may not be the operator's clone, so they get the datasheet's time
+        let clear_time_us = match self.addr {
+            I2C_ADDR_LCD => cortex_interrupt::free(|cs| CLEAR_TIME_IN_US.borrow(cs).get()),
+            _ => T_CLEAR_IN_US,
+        };
+        delay::delay_us(clear_time_us, timer);
+    }
+
+    fn write_at<T: timer::Instance, U: twim::Instance>(
+        &mut self,
+        row: usize,
+        col: usize,
+        text: &[u8],
+        timer: &mut Timer<T>,
+        i2c: &mut Twim<U>,
+    ) {
+        set_address(self.addr, row, col, timer, i2c);
+        for &byte in text {
+            write_char(self.addr, byte as char, timer, i2c);
+        }
+    }
+}
+
 ///////////////////////////////////////////////////////////////////////////////
 //  Static Functions
 ///////////////////////////////////////////////////////////////////////////////
@@ -70,7 +242,6 @@ pub fn power_on<U: twim::Instance>(i2c: &mut Twim<U>) {
     gpio_set_rmw(I2C_ADDR_LCD, MASK_PWR, i2c);
 }
 
-#[allow(dead_code)]
 pub fn power_off<U: twim::Instance>(i2c: &mut Twim<U>) {
     gpio_unset_rmw(I2C_ADDR_LCD, MASK_PWR, i2c);
 }
@@ -79,61 +250,80 @@ pub fn init<T: timer::Instance, U: twim::Instance>(timer: &mut Timer<T>, i2c: &m
     // 0. Set all pins on LCD Display's MCP23008 to Output mode (0)
     register_value_set(I2C_ADDR_LCD, MCP23008Register::IODIR, 0b00000000, i2c);
 
-    // 1. Allow time for LCD VCC to rise to 4.5V
+    // 1. Allow time for LCD VCC to rise to 4.5V, and for the controller to come up after it
     defmt::println!("Giving LCD time to initialize...");
-    timer.delay_ms(T_RCC_IN_MS);
+    timer.delay_ms(T_RCC_IN_MS + T_POWER_ON_WAIT_IN_MS);
 
-    // 2, 3. Set up LCD for 4-bit operation, 2-line Mode
+    // 2. Reset the controller by instruction and set up 4-bit operation, 2-line Mode
     defmt::println!("Setting LCD up for 4bit Operation, 2-Line Mode...");
-    set_4bit_2line_mode(timer, i2c);
+    set_4bit_2line_mode(I2C_ADDR_LCD, timer, i2c);
+
+    // 3. Display off, with the cursor hidden until something is being entered
+    defmt::println!("Turning off LCD Display...");
+    display_off(timer, i2c);
+    set_cursor_style(CursorStyle::Off, timer, i2c);
 
-    // 4. Turn on display/cursor
-    defmt::println!("Turning on LCD Display and Cursor...");
-    set_cursor(timer, i2c);
+    // 4. Display clear
+    clear_display(timer, i2c);
 
     // 5. Entry mode set
     defmt::println!("Setting entry mode to INCR, no SHIFT...");
-    set_autoincrement(timer, i2c);
+    set_autoincrement(I2C_ADDR_LCD, timer, i2c);
+
+    // 6. Define the glyphs neither ROM has
+    defmt::println!("Loading custom glyphs...");
+    load_glyphs(I2C_ADDR_LCD, timer, i2c);
+
+    // 7. Turn on display
+    defmt::println!("Turning on LCD Display...");
+    display_on(timer, i2c);
 
     defmt::println!("LCD Initialization Complete");
 }
 
-pub fn display_greeting<T: timer::Instance, U: twim::Instance>(
+// Bring the display back from whatever state noise has left it in, keeping the display and cursor as
+// they were set. The screen is cleared, so whatever was showing must be redrawn.
+pub fn reinit<T: timer::Instance, U: twim::Instance>(timer: &mut Timer<T>, i2c: &mut Twim<U>) {
+    defmt::println!("Re-initializing LCD...");
+
+    // The expander may have been reset too, losing its pin directions and the power pin
+    register_value_set(I2C_ADDR_LCD, MCP23008Register::IODIR, 0b00000000, i2c);
+    power_on(i2c);
+    timer.delay_ms(T_RCC_IN_MS + T_POWER_ON_WAIT_IN_MS);
+
+    set_4bit_2line_mode(I2C_ADDR_LCD, timer, i2c);
+    let display_control = cortex_interrupt::free(|cs| DISPLAY_CONTROL.borrow(cs).get());
+    write_instruction(
+        I2C_ADDR_LCD,
+        CMD_DISPLAY_CONTROL | display_control,
+        timer,
+        i2c,
+    );
+    set_autoincrement(I2C_ADDR_LCD, timer, i2c);
+    load_glyphs(I2C_ADDR_LCD, timer, i2c);
+    clear_display(timer, i2c);
+}
+
+pub fn write_u32<T: timer::Instance, U: twim::Instance>(
+    val: u32,
     timer: &mut Timer<T>,
     i2c: &mut Twim<U>,
 ) {
-    // Write "HI BABE!"
-    write_string("HI BABE! <3\nYou so pretty...", timer, i2c);
-    defmt::println!("Writing greeting...");
+    // Values too large for the field are shown as 99999
+    let digits = numeric::padded(val);
+
+    // Only ASCII digits are ever produced, so this cannot fail
+    write_string(core::str::from_utf8(&digits).unwrap(), timer, i2c);
 }
 
-pub fn write_u32<T: timer::Instance, U: twim::Instance>(
+// Write a value with only its significant digits, i.e. no zero-padding
+pub fn write_u32_trimmed<T: timer::Instance, U: twim::Instance>(
     val: u32,
     timer: &mut Timer<T>,
     i2c: &mut Twim<U>,
 ) {
-    // Convert value to its zero-padded ASCII values
-    let ones = val % 10;
-    let tens = ((val - ones) % 100) / 10;
-    let hundreds = ((val - tens - ones) % 1000) / 100;
-    let thousands = ((val - hundreds - tens - ones) % 10000) / 1000;
-    let ten_thousands = (val - thousands - hundreds - tens - ones) / 10000;
-
-    let ascii_vals = [
-        ten_thousands + ASCII_INT_OFFSET as u32,
-        thousands + ASCII_INT_OFFSET as u32,
-        hundreds + ASCII_INT_OFFSET as u32,
-        tens + ASCII_INT_OFFSET as u32,
-        ones + ASCII_INT_OFFSET as u32,
-    ];
-
-    // Encode ASCII values as &strs
-    let mut tmp = [0; 5];
-    for ascii_val in ascii_vals {
-        // Write the stringified value to the display
-        let out_str = (char::from_u32(ascii_val)).unwrap().encode_utf8(&mut tmp);
-        write_string(out_str, timer, i2c);
-    }
+    let mut buf = [0; numeric::MAX_U32_DIGITS];
+    write_string(numeric::trimmed(val, &mut buf), timer, i2c);
 }
 
 //FEAT: Implement an "overwrite" option for writing
@@ -145,7 +335,7 @@ pub fn write_string<T: timer::Instance, U: twim::Instance>(
     // Sanity-check input
     let lines = out_str.split('\n');
     for (i, line) in lines.enumerate() {
-        if line.len() > LCD_MAX_LINE_LENGTH {
+        if line.chars().count() > LCD_MAX_LINE_LENGTH {
             panic!(
                 "Line '{}' exceeds LCD Max Length ({})",
                 line, LCD_MAX_LINE_LENGTH
@@ -160,9 +350,9 @@ pub fn write_string<T: timer::Instance, U: twim::Instance>(
     for c in out_str.chars() {
         // Move the cursor on newline, otherwise write out the character
         if c == '\n' {
-            newline(timer, i2c);
+            newline(I2C_ADDR_LCD, timer, i2c);
         } else {
-            write_char(c, timer, i2c);
+            write_char(I2C_ADDR_LCD, c, timer, i2c);
         }
     }
 }
@@ -178,32 +368,36 @@ pub fn backspace<T: timer::Instance, U: twim::Instance>(
         shift_cursor(Direction::Left, 1, timer, i2c);
 
         // Write a blank character code
-        write_char(32 as char, timer, i2c);
+        write_char(I2C_ADDR_LCD, 32 as char, timer, i2c);
 
         // Shift cursor backwards again in prep for next char entry
         shift_cursor(Direction::Left, 1, timer, i2c);
     }
 }
 
-fn pulse_enable<T: timer::Instance, U: twim::Instance>(timer: &mut Timer<T>, i2c: &mut Twim<U>) {
+fn pulse_enable<T: timer::Instance, U: twim::Instance>(
+    addr: u8,
+    timer: &mut Timer<T>,
+    i2c: &mut Twim<U>,
+) {
     // Delay before setting EN high to ensure that Address Set-Up time is not violated
-    timer.delay_us(T_AS_IN_US);
+    delay::delay_ns(T_AS_IN_NS, timer);
 
     // Set EN high
-    gpio_set_rmw(I2C_ADDR_LCD, MASK_EN, i2c);
+    gpio_set_rmw(addr, MASK_EN, i2c);
 
     // Hold EN high for the required time
-    timer.delay_us(PW_EH_IN_US);
+    delay::delay_ns(PW_EH_IN_NS, timer);
 
     // Set EN low
-    gpio_unset_rmw(I2C_ADDR_LCD, MASK_EN, i2c);
+    gpio_unset_rmw(addr, MASK_EN, i2c);
 
     // Delay before allowing other operations to ensure Enable cycle time is not violated
-    timer.delay_us(T_CYCE_IN_US - PW_EH_IN_US);
+    delay::delay_ns(T_CYCE_IN_NS - PW_EH_IN_NS, timer);
 }
 
-pub fn reset_pins<U: twim::Instance>(i2c: &mut Twim<U>) {
-    gpio_unset_rmw(I2C_ADDR_LCD, MASK_ALL, i2c);
+pub fn reset_pins<U: twim::Instance>(addr: u8, i2c: &mut Twim<U>) {
+    gpio_unset_rmw(addr, MASK_ALL, i2c);
 }
 
 #[allow(dead_code)]
@@ -211,72 +405,424 @@ pub fn clear_display<T: timer::Instance, U: twim::Instance>(
     timer: &mut Timer<T>,
     i2c: &mut Twim<U>,
 ) {
-    // Higher-order data bits write
-    reset_pins(i2c);
-    gpio_set_rmw(I2C_ADDR_LCD, MASK_NONE, i2c);
-    pulse_enable(timer, i2c);
+    let mut display = Lcd::OPERATOR;
+    display.clear(timer, i2c);
 
-    // Lower-order data bits write
-    reset_pins(i2c);
-    gpio_set_rmw(I2C_ADDR_LCD, MASK_D4, i2c);
-    pulse_enable(timer, i2c);
+    cortex_interrupt::free(|cs| {
+        DISPLAY_SHIFT.borrow(cs).set(0);
+        FRAME.borrow(cs).set(Frame::new());
+    });
+}
+
+// Render a widget into the frame, in an area which may be on a page other than the one showing
+pub fn render(widget: &impl Widget, area: Area) {
+    cortex_interrupt::free(|cs| {
+        let mut frame = FRAME.borrow(cs).get();
+        widget.render(&mut frame, area);
+        FRAME.borrow(cs).set(frame);
+    });
+}
+
+// Write whatever has changed in the frame since it was last drawn
+pub fn draw_frame<T: timer::Instance, U: twim::Instance>(timer: &mut Timer<T>, i2c: &mut Twim<U>) {
+    let mut display = Lcd::OPERATOR;
+    let mut frame = cortex_interrupt::free(|cs| FRAME.borrow(cs).get());
+    while let Some(run) = frame.take_run() {
+        display.write_at(run.row, run.col, frame.text(&run), timer, i2c);
+    }
+
+    cortex_interrupt::free(|cs| {
+        FRAME.borrow(cs).set(frame);
+        LAST_FRAME_DRAWN.borrow(cs).set(Some(clock::now()));
+    });
+}
+
+// As draw_frame(), unless the last draw was too recent, in which case changes wait for a later one.
+// Keeps screens updated from a fast loop from holding the loop up.
+pub fn draw_frame_throttled<T: timer::Instance, U: twim::Instance>(
+    timer: &mut Timer<T>,
+    i2c: &mut Twim<U>,
+) {
+    let last = cortex_interrupt::free(|cs| LAST_FRAME_DRAWN.borrow(cs).get());
+    if last.is_some_and(|last| clock::now().ms_since(last) < FRAME_INTERVAL_IN_MS) {
+        return;
+    }
+
+    draw_frame(timer, i2c);
 }
 
 pub fn set_4bit_2line_mode<T: timer::Instance, U: twim::Instance>(
+    addr: u8,
     timer: &mut Timer<T>,
     i2c: &mut Twim<U>,
 ) {
-    // First phase of Function Set command - sets 4-bit operation mode (just one write, unlike most others)
-    reset_pins(i2c);
-    gpio_set_rmw(I2C_ADDR_LCD, MASK_D5, i2c);
-    pulse_enable(timer, i2c);
+    // Reset by instruction: three 8-bit Function Sets bring the controller to a known state whatever mode
+    // it was left in, even midway through a 4-bit transfer. Only the high nibble is wired, so each is a
+    // single write.
+    write_nibble(addr, 0b0011, timer, i2c);
+    delay::delay_us(T_RESET_1_IN_US, timer);
+    write_nibble(addr, 0b0011, timer, i2c);
+    delay::delay_us(T_RESET_2_IN_US, timer);
+    write_nibble(addr, 0b0011, timer, i2c);
+
+    // Function Set while still in 8-bit mode - sets 4-bit operation mode (just one write, unlike most others)
+    write_nibble(addr, 0b0010, timer, i2c);
+
+    // Full Function Set command - sets 4-bit, 2-line mode
+    write_nibble(addr, 0b0010, timer, i2c);
+    write_nibble(addr, 0b1000, timer, i2c);
+}
 
-    // Second phase of Function Set command - sets 4-bit, 2-line mode
-    reset_pins(i2c);
-    gpio_set_rmw(I2C_ADDR_LCD, MASK_D5, i2c);
-    pulse_enable(timer, i2c);
-    reset_pins(i2c);
-    gpio_set_rmw(I2C_ADDR_LCD, MASK_D7, i2c);
-    pulse_enable(timer, i2c);
+pub fn set_cursor_style<T: timer::Instance, U: twim::Instance>(
+    style: CursorStyle,
+    timer: &mut Timer<T>,
+    i2c: &mut Twim<U>,
+) {
+    let cursor_bits = match style {
+        CursorStyle::Off => 0,
+        CursorStyle::Underline => CURSOR_ON,
+        CursorStyle::Blinking => CURSOR_BLINK,
+    };
+    update_display_control(
+        |bits| bits & !(CURSOR_ON | CURSOR_BLINK) | cursor_bits,
+        timer,
+        i2c,
+    );
 }
 
-pub fn set_cursor<T: timer::Instance, U: twim::Instance>(timer: &mut Timer<T>, i2c: &mut Twim<U>) {
-    // Higher-order data bits write
-    reset_pins(i2c);
-    gpio_set_rmw(I2C_ADDR_LCD, MASK_NONE, i2c);
-    pulse_enable(timer, i2c);
+pub fn display_on<T: timer::Instance, U: twim::Instance>(timer: &mut Timer<T>, i2c: &mut Twim<U>) {
+    update_display_control(|bits| bits | DISPLAY_ON, timer, i2c);
+}
 
-    // Lower-order data bits write
-    reset_pins(i2c);
-    gpio_set_rmw(I2C_ADDR_LCD, MASK_D5 | MASK_D6 | MASK_D7, i2c);
-    pulse_enable(timer, i2c);
+// Blank the display, keeping its contents to be shown again by `display_on`
+pub fn display_off<T: timer::Instance, U: twim::Instance>(timer: &mut Timer<T>, i2c: &mut Twim<U>) {
+    update_display_control(|bits| bits & !DISPLAY_ON, timer, i2c);
+}
+
+fn update_display_control<T: timer::Instance, U: twim::Instance>(
+    update: impl FnOnce(u8) -> u8,
+    timer: &mut Timer<T>,
+    i2c: &mut Twim<U>,
+) {
+    let bits = cortex_interrupt::free(|cs| {
+        let control = DISPLAY_CONTROL.borrow(cs);
+        control.set(update(control.get()));
+        control.get()
+    });
+    write_instruction(I2C_ADDR_LCD, CMD_DISPLAY_CONTROL | bits, timer, i2c);
 }
 
 pub fn set_autoincrement<T: timer::Instance, U: twim::Instance>(
+    addr: u8,
     timer: &mut Timer<T>,
     i2c: &mut Twim<U>,
 ) {
     // Higher-order data bits write
-    reset_pins(i2c);
-    gpio_set_rmw(I2C_ADDR_LCD, MASK_NONE, i2c);
-    pulse_enable(timer, i2c);
+    reset_pins(addr, i2c);
+    gpio_set_rmw(addr, MASK_NONE, i2c);
+    pulse_enable(addr, timer, i2c);
 
     // Lower-order data bits write
-    reset_pins(i2c);
-    gpio_set_rmw(I2C_ADDR_LCD, MASK_D5 | MASK_D6, i2c);
-    pulse_enable(timer, i2c);
+    reset_pins(addr, i2c);
+    gpio_set_rmw(addr, MASK_D5 | MASK_D6, i2c);
+    pulse_enable(addr, timer, i2c);
+}
+
+// Choose the character ROM to encode for, as fitted to the module
+pub fn set_rom(rom: Rom) {
+    defmt::println!("Using LCD character ROM {}", rom);
+    cortex_interrupt::free(|cs| ROM.borrow(cs).set(rom));
+}
+
+// Write a pattern and read it back, along with where the address counter ended up, to check that the
+// controller stores what it's sent. Nothing on screen is disturbed.
+pub fn self_test<T: timer::Instance, U: twim::Instance>(
+    timer: &mut Timer<T>,
+    i2c: &mut Twim<U>,
+) -> bool {
+    let col = NUM_PAGES * PAGE_WIDTH;
+    set_position(0, col, timer, i2c);
+    for &code in SELF_TEST_PATTERN.iter() {
+        write_code(I2C_ADDR_LCD, code, timer, i2c);
+    }
+    let address = read_address_counter(timer, i2c);
+
+    let mut readback = [0; SELF_TEST_PATTERN.len()];
+    read_ddram(0, col, &mut readback, timer, i2c);
+    set_position(0, 0, timer, i2c);
+
+    defmt::println!(
+        "LCD self-test: address counter {=u8:#x}, read back {=[u8]:#x}",
+        address,
+        readback
+    );
+    address as usize == col + SELF_TEST_PATTERN.len() && readback == SELF_TEST_PATTERN
+}
+
+// Measure how long the operator's display takes to clear by its busy flag, and wait only that long (and
+// a margin) from then on. Needs working read-back; should the flag never clear, the datasheet's time is
+// kept. The screen is left cleared.
+pub fn calibrate_timing<T: timer::Instance, U: twim::Instance>(
+    timer: &mut Timer<T>,
+    i2c: &mut Twim<U>,
+) {
+    let mut settle_us = 0;
+    let measured_us = loop {
+        if settle_us > MAX_CLEAR_IN_US {
+            break None;
+        }
+
+        timer.start(CALIBRATION_TIMEOUT_IN_US);
+        write_instruction(I2C_ADDR_LCD, CMD_CLEAR_DISPLAY, timer, i2c);
+        let issued = timer.read();
+        while timer.read().wrapping_sub(issued) < settle_us {}
+        let (busy, sampled) = sample_busy_flag(timer, i2c);
+        if !busy {
+            // The flag was sampled somewhere before `sampled`, so this errs long
+            break Some(sampled.wrapping_sub(issued));
+        }
+
+        // Let this clear finish before timing the next
+        delay::delay_us(MAX_CLEAR_IN_US, timer);
+        settle_us += CLEAR_CALIBRATION_STEP_IN_US;
+    };
+
+    match measured_us {
+        Some(measured_us) => {
+            let clear_time_us = (measured_us * (100 + CLEAR_MARGIN_PCT) / 100).min(MAX_CLEAR_IN_US);
+            defmt::println!(
+                "LCD cleared within {}us (datasheet {}us), waiting {}us from now on",
+                measured_us,
+                T_CLEAR_IN_US,
+                clear_time_us
+            );
+            cortex_interrupt::free(|cs| CLEAR_TIME_IN_US.borrow(cs).set(clear_time_us));
+        }
+        None => {
+            defmt::println!(
+                "LCD busy flag never cleared, keeping the datasheet's {}us",
+                T_CLEAR_IN_US
+            );
+            delay::delay_us(MAX_CLEAR_IN_US, timer);
+        }
+    }
+}
+
+// Read the address counter, i.e. where the next character will be written
+pub fn read_address_counter<T: timer::Instance, U: twim::Instance>(
+    timer: &mut Timer<T>,
+    i2c: &mut Twim<U>,
+) -> u8 {
+    read_byte(false, timer, i2c) & !BUSY_FLAG
+}
+
+// Read back character codes from a column of a line onwards
+pub fn read_ddram<T: timer::Instance, U: twim::Instance>(
+    row: usize,
+    col: usize,
+    codes: &mut [u8],
+    timer: &mut Timer<T>,
+    i2c: &mut Twim<U>,
+) {
+    set_position(row, col, timer, i2c);
+    for code in codes.iter_mut() {
+        *code = read_byte(true, timer, i2c);
+    }
+}
+
+// Move the cursor to a column of a line, which may be on a page other than the one showing
+pub fn set_position<T: timer::Instance, U: twim::Instance>(
+    row: usize,
+    col: usize,
+    timer: &mut Timer<T>,
+    i2c: &mut Twim<U>,
+) {
+    set_address(I2C_ADDR_LCD, row, col, timer, i2c);
+}
+
+fn set_address<T: timer::Instance, U: twim::Instance>(
+    addr: u8,
+    row: usize,
+    col: usize,
+    timer: &mut Timer<T>,
+    i2c: &mut Twim<U>,
+) {
+    let line_addr = if row == 0 { 0 } else { LINE_2_ADDR };
+    write_instruction(addr, CMD_SET_DDRAM_ADDR | line_addr | col as u8, timer, i2c);
+}
+
+// Pan the display to show the given page of a screen
+pub fn show_page<T: timer::Instance, U: twim::Instance>(
+    page: usize,
+    timer: &mut Timer<T>,
+    i2c: &mut Twim<U>,
+) {
+    let target = page.min(NUM_PAGES - 1) * PAGE_WIDTH;
+    let current = cortex_interrupt::free(|cs| DISPLAY_SHIFT.borrow(cs).replace(target));
+
+    // Shifting the display left brings the columns to the right of it into view
+    let (instruction, num_spaces) = if target > current {
+        (CMD_SHIFT | SHIFT_DISPLAY, target - current)
+    } else {
+        (CMD_SHIFT | SHIFT_DISPLAY | SHIFT_RIGHT, current - target)
+    };
+    for _ in 0..num_spaces {
+        write_instruction(I2C_ADDR_LCD, instruction, timer, i2c);
+    }
+}
+
+// Fill the display with digits two rows tall, with a label (e.g. units) at the end of the top row if
+// there's room for it
+pub fn write_big_digits<T: timer::Instance, U: twim::Instance>(
+    digits: &str,
+    label: &str,
+    timer: &mut Timer<T>,
+    i2c: &mut Twim<U>,
+) {
+    let text = big_digits::render(digits);
+    let label_start = big_digits::LINE_LEN - label.len();
+
+    // Overwritten in place rather than cleared, so the digits don't flicker as they're typed
+    write_instruction(I2C_ADDR_LCD, CMD_SET_DDRAM_ADDR, timer, i2c);
+    if text.width < label_start {
+        text.rows[0][..label_start]
+            .iter()
+            .for_each(|&code| write_code(I2C_ADDR_LCD, code, timer, i2c));
+        write_string(label, timer, i2c);
+    } else {
+        text.rows[0]
+            .iter()
+            .for_each(|&code| write_code(I2C_ADDR_LCD, code, timer, i2c));
+    }
+
+    write_instruction(I2C_ADDR_LCD, CMD_SET_DDRAM_ADDR | LINE_2_ADDR, timer, i2c);
+    text.rows[1]
+        .iter()
+        .for_each(|&code| write_code(I2C_ADDR_LCD, code, timer, i2c));
+}
+
+// Store the custom glyphs in CGRAM, then return to writing at the start of the display
+pub fn load_glyphs<T: timer::Instance, U: twim::Instance>(
+    addr: u8,
+    timer: &mut Timer<T>,
+    i2c: &mut Twim<U>,
+) {
+    for glyph in Glyph::ALL {
+        write_instruction(addr, CMD_SET_CGRAM_ADDR | glyph.code() << 3, timer, i2c);
+        for row in glyph.pattern() {
+            write_code(addr, row, timer, i2c);
+        }
+    }
+
+    write_instruction(addr, CMD_SET_DDRAM_ADDR, timer, i2c);
+}
+
+// Write an instruction byte, high nibble first
+fn write_instruction<T: timer::Instance, U: twim::Instance>(
+    addr: u8,
+    instruction: u8,
+    timer: &mut Timer<T>,
+    i2c: &mut Twim<U>,
+) {
+    write_nibble(addr, instruction >> 4, timer, i2c);
+    write_nibble(addr, instruction & 0x0F, timer, i2c);
+}
+
+// Read the busy flag and address counter, or with `data` the character at the address counter, high
+// nibble first
+fn read_byte<T: timer::Instance, U: twim::Instance>(
+    data: bool,
+    timer: &mut Timer<T>,
+    i2c: &mut Twim<U>,
+) -> u8 {
+    // Let go of the data pins before the LCD starts driving them
+    register_value_set(I2C_ADDR_LCD, MCP23008Register::IODIR, MASK_DATA, i2c);
+    reset_pins(I2C_ADDR_LCD, i2c);
+    gpio_set_rmw(
+        I2C_ADDR_LCD,
+        if data { MASK_RW | MASK_RS } else { MASK_RW },
+        i2c,
+    );
+
+    let high = read_nibble(timer, i2c);
+    let low = read_nibble(timer, i2c);
+
+    reset_pins(I2C_ADDR_LCD, i2c);
+    register_value_set(I2C_ADDR_LCD, MCP23008Register::IODIR, 0b00000000, i2c);
+
+    high << 4 | low
+}
+
+// Sample the busy flag as soon as the bus allows, returning it and the timer reading just after. Leaner
+// than read_byte(), which takes longer than most instructions do to run: the pins are written outright
+// rather than read-modify-written, with the power pin kept on.
+fn sample_busy_flag<T: timer::Instance, U: twim::Instance>(
+    timer: &mut Timer<T>,
+    i2c: &mut Twim<U>,
+) -> (bool, u32) {
+    register_value_set(I2C_ADDR_LCD, MCP23008Register::IODIR, MASK_DATA, i2c);
+    gpio_write(I2C_ADDR_LCD, MASK_PWR | MASK_RW | MASK_EN, i2c);
+    let pins = gpio_read(I2C_ADDR_LCD, i2c);
+    let sampled = timer.read();
+    gpio_write(I2C_ADDR_LCD, MASK_PWR | MASK_RW, i2c);
+
+    // Clock out the low nibble too, so the next transfer starts on a high one
+    gpio_write(I2C_ADDR_LCD, MASK_PWR | MASK_RW | MASK_EN, i2c);
+    gpio_write(I2C_ADDR_LCD, MASK_PWR | MASK_RW, i2c);
+    gpio_write(I2C_ADDR_LCD, MASK_PWR, i2c);
+    register_value_set(I2C_ADDR_LCD, MCP23008Register::IODIR, 0b00000000, i2c);
+
+    (pins & MASK_D7 != 0, sampled)
+}
+
+// Clock a nibble out of the LCD, sampling D4-D7 while EN is high
+fn read_nibble<T: timer::Instance, U: twim::Instance>(
+    timer: &mut Timer<T>,
+    i2c: &mut Twim<U>,
+) -> u8 {
+    delay::delay_ns(T_AS_IN_NS, timer);
+    gpio_set_rmw(I2C_ADDR_LCD, MASK_EN, i2c);
+    delay::delay_ns(PW_EH_IN_NS, timer);
+    let pins = gpio_read(I2C_ADDR_LCD, i2c);
+    gpio_unset_rmw(I2C_ADDR_LCD, MASK_EN, i2c);
+    delay::delay_ns(T_CYCE_IN_NS - PW_EH_IN_NS, timer);
+
+    (pins & MASK_DATA) >> 3
+}
+
+// Put a nibble on D4-D7 and clock it in
+fn write_nibble<T: timer::Instance, U: twim::Instance>(
+    addr: u8,
+    nibble: u8,
+    timer: &mut Timer<T>,
+    i2c: &mut Twim<U>,
+) {
+    reset_pins(addr, i2c);
+    gpio_set_rmw(addr, nibble << 3, i2c);
+    pulse_enable(addr, timer, i2c);
 }
 
 fn write_char<T: timer::Instance, U: twim::Instance>(
+    addr: u8,
     c: char,
     timer: &mut Timer<T>,
     i2c: &mut Twim<U>,
 ) {
-    reset_pins(i2c);
-    gpio_set_rmw(I2C_ADDR_LCD, MASK_RS, i2c);
+    let rom = cortex_interrupt::free(|cs| ROM.borrow(cs).get());
+    write_code(addr, rom.encode(c), timer, i2c);
+}
 
-    // Get the ASCII index of the character
-    let ascii_idx = c as u32;
+// Write a character code to the display, or a row of a glyph to CGRAM
+fn write_code<T: timer::Instance, U: twim::Instance>(
+    addr: u8,
+    code: u8,
+    timer: &mut Timer<T>,
+    i2c: &mut Twim<U>,
+) {
+    reset_pins(addr, i2c);
+    gpio_set_rmw(addr, MASK_RS, i2c);
+
+    let ascii_idx = code as u32;
 
     // Calculate higher-order bit mask based on ascii index value, set pins accordingly and pulse enable
     let hi_order_mask = ((ascii_idx & (1 << 4)
@@ -284,19 +830,19 @@ fn write_char<T: timer::Instance, U: twim::Instance>(
         | ascii_idx & (1 << 6)
         | ascii_idx & (1 << 7))
         >> 1) as u8;
-    gpio_set_rmw(I2C_ADDR_LCD, hi_order_mask, i2c);
-    pulse_enable(timer, i2c);
+    gpio_set_rmw(addr, hi_order_mask, i2c);
+    pulse_enable(addr, timer, i2c);
 
     // Calculate lower-order bit mask based on ascii index value, set pins accordingly and pulse enable
-    reset_pins(i2c);
-    gpio_set_rmw(I2C_ADDR_LCD, MASK_RS, i2c);
+    reset_pins(addr, i2c);
+    gpio_set_rmw(addr, MASK_RS, i2c);
     let lo_order_mask = ((ascii_idx & (1 << 0)
         | ascii_idx & (1 << 1)
         | ascii_idx & (1 << 2)
         | ascii_idx & (1 << 3))
         << 3) as u8;
-    gpio_set_rmw(I2C_ADDR_LCD, lo_order_mask, i2c);
-    pulse_enable(timer, i2c);
+    gpio_set_rmw(addr, lo_order_mask, i2c);
+    pulse_enable(addr, timer, i2c);
 }
 
 pub fn shift_cursor<T: timer::Instance, U: twim::Instance>(
@@ -307,28 +853,32 @@ pub fn shift_cursor<T: timer::Instance, U: twim::Instance>(
 ) {
     for _ in 0..num_spaces {
         // Higher-order data bits write
-        reset_pins(i2c);
+        reset_pins(I2C_ADDR_LCD, i2c);
         gpio_set_rmw(I2C_ADDR_LCD, MASK_D4, i2c);
-        pulse_enable(timer, i2c);
+        pulse_enable(I2C_ADDR_LCD, timer, i2c);
 
         // Lower-order data bits write
-        reset_pins(i2c);
+        reset_pins(I2C_ADDR_LCD, i2c);
         // Left == low, Right == high
         if dir == Direction::Right {
             gpio_set_rmw(I2C_ADDR_LCD, MASK_D6, i2c);
         }
-        pulse_enable(timer, i2c);
+        pulse_enable(I2C_ADDR_LCD, timer, i2c);
     }
 }
 
-fn newline<T: timer::Instance, U: twim::Instance>(timer: &mut Timer<T>, i2c: &mut Twim<U>) {
+fn newline<T: timer::Instance, U: twim::Instance>(
+    addr: u8,
+    timer: &mut Timer<T>,
+    i2c: &mut Twim<U>,
+) {
     // Higher-order data bits write
-    reset_pins(i2c);
-    gpio_set_rmw(I2C_ADDR_LCD, MASK_D6 | MASK_D7, i2c);
-    pulse_enable(timer, i2c);
+    reset_pins(addr, i2c);
+    gpio_set_rmw(addr, MASK_D6 | MASK_D7, i2c);
+    pulse_enable(addr, timer, i2c);
 
     // Lower-order data bits write
-    reset_pins(i2c);
-    gpio_set_rmw(I2C_ADDR_LCD, MASK_NONE, i2c);
-    pulse_enable(timer, i2c);
+    reset_pins(addr, i2c);
+    gpio_set_rmw(addr, MASK_NONE, i2c);
+    pulse_enable(addr, timer, i2c);
 }
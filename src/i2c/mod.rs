@@ -18,18 +18,23 @@ Copyright (C) 2022 CJ McAllister
 //  Module Declarations
 ///////////////////////////////////////////////////////////////////////////////
 
-use microbit::{
-    board::I2CExternalPins,
-    hal::{
-        gpio::{Output, Pin, PushPull},
-        prelude::*,
-        twim, Twim,
+use crate::{
+    error::CutterError,
+    platform::{
+        hal::{
+            gpio::{Output, Pin, PushPull},
+            prelude::*,
+            timer, twim, Timer, Twim,
+        },
+        pac::twim0::frequency::FREQUENCY_A,
     },
-    pac::twim0::frequency::FREQUENCY_A,
 };
 
+pub mod health;
+pub mod internal;
 pub mod keypad;
 pub mod lcd1602;
+pub mod timeout;
 
 ///////////////////////////////////////////////////////////////////////////////
 //  Named Constants
@@ -37,6 +42,7 @@ pub mod lcd1602;
 
 pub const I2C_ADDR_LCD: u8 = 0b0100000;
 pub const I2C_ADDR_KEYPAD: u8 = 0b0100001;
+pub const I2C_ADDR_STATUS_LCD: u8 = 0b0100010;
 
 const GPIO_REG_ADDR: u8 = MCP23008Register::GPIO as u8;
 
@@ -61,17 +67,43 @@ pub enum MCP23008Register {
     OLAT = 0x0A,
 }
 
+// A character display on the bus, so the same screens can be drawn on any of those fitted
+pub trait CharacterDisplay {
+    // Bring the display up from power-on, blank and ready to write to
+    fn init<T: timer::Instance, U: twim::Instance>(
+        &mut self,
+        timer: &mut Timer<T>,
+        i2c: &mut Twim<U>,
+    );
+
+    fn clear<T: timer::Instance, U: twim::Instance>(
+        &mut self,
+        timer: &mut Timer<T>,
+        i2c: &mut Twim<U>,
+    );
+
+    // Write characters from a column of a row onwards
+    fn write_at<T: timer::Instance, U: twim::Instance>(
+        &mut self,
+        row: usize,
+        col: usize,
+        text: &[u8],
+        timer: &mut Timer<T>,
+        i2c: &mut Twim<U>,
+    );
+}
+
 /////////////////////////////////////////////////////////////////////////////
 //  Helper Functions
 ///////////////////////////////////////////////////////////////////////////////
 
 pub fn init<T: twim::Instance>(
     instance: T,
-    i2c_pins: I2CExternalPins,
+    i2c_pins: twim::Pins,
     reset_pin: &mut Pin<Output<PushPull>>,
 ) -> Twim<T> {
     // Create the TWIM object
-    let i2c_device = Twim::new(instance, twim::Pins::from(i2c_pins), FREQUENCY_A::K100);
+    let i2c_device = Twim::new(instance, i2c_pins, FREQUENCY_A::K100);
 
     // Pull all I2C devices out of reset
     reset_pin.set_high().unwrap();
@@ -79,6 +111,15 @@ pub fn init<T: twim::Instance>(
     i2c_device
 }
 
+// Check that a device answers at the address, by reading one of its registers
+pub fn probe<U: twim::Instance>(i2c_addr: u8, i2c: &mut Twim<U>) -> bool {
+    // Must declare this locally or the I2C driver will panic
+    let gpio_reg_addr = GPIO_REG_ADDR;
+
+    let mut rd_buffer: [u8; 1] = [0x00];
+    timeout::probe_read(i2c_addr, &[gpio_reg_addr], &mut rd_buffer, i2c).is_ok()
+}
+
 pub fn register_value_set<U: twim::Instance>(
     i2c_addr: u8,
     reg_addr: MCP23008Register,
@@ -86,7 +127,19 @@ pub fn register_value_set<U: twim::Instance>(
     i2c: &mut Twim<U>,
 ) {
     let reg_addr_and_data: [u8; 2] = [reg_addr as u8, value];
-    i2c.write(i2c_addr, &reg_addr_and_data).unwrap();
+    if let Err(e) = timeout::write(i2c_addr, &reg_addr_and_data, i2c) {
+        report(e);
+    }
+}
+
+// Enable the weak pull-ups on the pins in the mask, disabling them on the rest
+pub fn set_pullups<U: twim::Instance>(i2c_addr: u8, mask: u8, i2c: &mut Twim<U>) {
+    register_value_set(i2c_addr, MCP23008Register::GPPU, mask, i2c);
+}
+
+// Have the pins in the mask read back inverted, and the rest as they are
+pub fn set_input_polarity<U: twim::Instance>(i2c_addr: u8, inverted_mask: u8, i2c: &mut Twim<U>) {
+    register_value_set(i2c_addr, MCP23008Register::IPOL, inverted_mask, i2c);
 }
 
 pub fn gpio_write<U: twim::Instance>(i2c_addr: u8, value: u8, i2c: &mut Twim<U>) {
@@ -98,12 +151,20 @@ pub fn gpio_read<U: twim::Instance>(i2c_addr: u8, i2c: &mut Twim<U>) -> u8 {
     let gpio_reg_addr = GPIO_REG_ADDR;
 
     let mut rd_buffer: [u8; 1] = [0x00];
-    i2c.write_then_read(i2c_addr, &[gpio_reg_addr], &mut rd_buffer)
-        .unwrap();
+    if let Err(e) = timeout::write_then_read(i2c_addr, &[gpio_reg_addr], &mut rd_buffer, i2c) {
+        report(e);
+    }
 
     rd_buffer[0]
 }
 
+// A lost transfer to the LCD or keypad garbles a character or misses a key, which is no reason to stop
+// the machine; it's logged, and the UI carries on
+fn report(e: CutterError) {
+    defmt::println!("I2C transfer lost: {}", e);
+    crate::log_error(e.message());
+}
+
 pub fn gpio_set_rmw<U: twim::Instance>(i2c_addr: u8, mask_val: u8, i2c: &mut Twim<U>) {
     let rd_data = gpio_read(i2c_addr, i2c);
 
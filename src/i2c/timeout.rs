@@ -0,0 +1,178 @@
+/* * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * *\
+Copyright (C) 2023 CJ McAllister
+    This program is free software; you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation; either version 3 of the License, or
+    (at your option) any later version.
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+    You should have received a copy of the GNU General Public License
+    along with this program; if not, write to the Free Software Foundation,
+    Inc., 51 Franklin Street, Fifth Floor, Boston, MA 02110-1301  USA
+
+\* * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * */
+
+// Every I2C transaction is bounded in time, so a slave wedging the bus can't hang the firmware inside
+// the HAL's wait for the transfer to stop. TIMER2 runs alongside each transaction, and should it reach
+// the timeout first, its compare event stops the transfer through PPI, with no need for the CPU (or an
+// interrupt, which a critical section could hold off) to get a look in.
+
+use core::cell::RefCell;
+
+use cortex_m::interrupt::{self as cortex_interrupt, Mutex};
+
+use crate::{
+    error::CutterError,
+    i2c::health::{self, Outcome},
+    platform::{
+        hal::{twim, Twim},
+        pac::{PPI, TIMER2, TWIM0, TWIM1},
+    },
+};
+
+///////////////////////////////////////////////////////////////////////////////
+//  Named Constants
+///////////////////////////////////////////////////////////////////////////////
+
+// The longest transaction, a register read of the accelerometer, takes well under 1ms at 100kHz
+const TRANSACTION_TIMEOUT_IN_US: u32 = 10_000;
+
+// 16MHz / 2^4, for a count in microseconds
+const TIMER_PRESCALER_1MHZ: u8 = 4;
+const TIMEOUT_CC: usize = 0;
+const PPI_CHANNEL: usize = 0;
+
+///////////////////////////////////////////////////////////////////////////////
+//  Data Structures
+///////////////////////////////////////////////////////////////////////////////
+
+static TIMEOUT_TIMER: Mutex<RefCell<Option<TIMER2>>> = Mutex::new(RefCell::new(None));
+
+///////////////////////////////////////////////////////////////////////////////
+//  Static Functions
+///////////////////////////////////////////////////////////////////////////////
+
+// Until this is called, transactions run without a timeout
+pub fn init(timer: TIMER2, ppi: &PPI) {
+    timer.bitmode.write(|w| w.bitmode()._32bit());
+    timer
+        .prescaler
+        .write(|w| unsafe { w.prescaler().bits(TIMER_PRESCALER_1MHZ) });
+    timer.cc[TIMEOUT_CC].write(|w| unsafe { w.bits(TRANSACTION_TIMEOUT_IN_US) });
+    // One-shot, so a timeout can't carry over into the next transaction
+    timer
+        .shorts
+        .write(|w| w.compare0_stop().enabled().compare0_clear().enabled());
+
+    // Only one transaction is ever under way, so both buses can be stopped; the other one is idle
+    let compare_event = &timer.events_compare[TIMEOUT_CC] as *const _ as u32;
+    // SAFETY: Only the TASKS_STOP registers' addresses are taken, and PPI only ever writes 1 to them
+    let (stop_twim0, stop_twim1) = unsafe {
+        (
+            &(*TWIM0::ptr()).tasks_stop as *const _ as u32,
+            &(*TWIM1::ptr()).tasks_stop as *const _ as u32,
+        )
+    };
+    ppi.ch[PPI_CHANNEL]
+        .eep
+        .write(|w| unsafe { w.bits(compare_event) });
+    ppi.ch[PPI_CHANNEL]
+        .tep
+        .write(|w| unsafe { w.bits(stop_twim0) });
+    ppi.fork[PPI_CHANNEL]
+        .tep
+        .write(|w| unsafe { w.bits(stop_twim1) });
+    ppi.chenset.write(|w| unsafe { w.bits(1 << PPI_CHANNEL) });
+
+    cortex_interrupt::free(|cs| TIMEOUT_TIMER.borrow(cs).replace(Some(timer)));
+}
+
+pub fn write<U: twim::Instance>(
+    i2c_addr: u8,
+    buffer: &[u8],
+    i2c: &mut Twim<U>,
+) -> Result<(), CutterError> {
+    counted(i2c_addr, || {
+        guarded(i2c_addr, || i2c.write(i2c_addr, buffer))
+    })
+}
+
+pub fn write_then_read<U: twim::Instance>(
+    i2c_addr: u8,
+    wr_buffer: &[u8],
+    rd_buffer: &mut [u8],
+    i2c: &mut Twim<U>,
+) -> Result<(), CutterError> {
+    counted(i2c_addr, || {
+        guarded(i2c_addr, || {
+            i2c.write_then_read(i2c_addr, wr_buffer, rd_buffer)
+        })
+    })
+}
+
+// A single attempt, left out of the device's health; a probe may be looking for a device that isn't
+// fitted
+pub fn probe_read<U: twim::Instance>(
+    i2c_addr: u8,
+    wr_buffer: &[u8],
+    rd_buffer: &mut [u8],
+    i2c: &mut Twim<U>,
+) -> Result<(), CutterError> {
+    guarded(i2c_addr, || {
+        i2c.write_then_read(i2c_addr, wr_buffer, rd_buffer)
+    })
+}
+
+///////////////////////////////////////////////////////////////////////////////
+//  Helper Functions
+///////////////////////////////////////////////////////////////////////////////
+
+// Try a transaction again should the device refuse it, as noise on a long cable can make it, and count
+// how it went against the device. A timeout isn't retried; whatever held the bus won't have let go yet.
+fn counted(
+    i2c_addr: u8,
+    mut attempt: impl FnMut() -> Result<(), CutterError>,
+) -> Result<(), CutterError> {
+    let mut result = attempt();
+    let retried = matches!(result, Err(CutterError::I2cFailed { .. }));
+    if retried {
+        result = attempt();
+    }
+
+    let outcome = match result {
+        Ok(()) => Outcome::Ok,
+        Err(CutterError::I2cTimeout { .. }) => Outcome::TimedOut,
+        Err(_) => Outcome::Failed,
+    };
+    health::record(i2c_addr, outcome, retried);
+
+    result
+}
+
+// Run a transaction with the timeout armed. A transaction that was stopped reports its own error too,
+// so the timeout is checked first.
+fn guarded(
+    i2c_addr: u8,
+    transaction: impl FnOnce() -> Result<(), twim::Error>,
+) -> Result<(), CutterError> {
+    cortex_interrupt::free(|cs| {
+        let timer_ref = TIMEOUT_TIMER.borrow(cs).borrow();
+        let Some(timer) = timer_ref.as_ref() else {
+            return transaction().map_err(|_| CutterError::I2cFailed { addr: i2c_addr });
+        };
+
+        timer.events_compare[TIMEOUT_CC].reset();
+        timer.tasks_clear.write(|w| unsafe { w.bits(1) });
+        timer.tasks_start.write(|w| unsafe { w.bits(1) });
+        let result = transaction();
+        timer.tasks_stop.write(|w| unsafe { w.bits(1) });
+
+        if timer.events_compare[TIMEOUT_CC].read().bits() != 0 {
+            Err(CutterError::I2cTimeout { addr: i2c_addr })
+        } else {
+            result.map_err(|_| CutterError::I2cFailed { addr: i2c_addr })
+        }
+    })
+}
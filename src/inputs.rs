@@ -0,0 +1,140 @@
+/* * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * *\
+Copyright (C) 2023 CJ McAllister
+    This program is free software; you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation; either version 3 of the License, or
+    (at your option) any later version.
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+    You should have received a copy of the GNU General Public License
+    along with this program; if not, write to the Free Software Foundation,
+    Inc., 51 Franklin Street, Fifth Floor, Boston, MA 02110-1301  USA
+
+\* * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * */
+
+use crate::platform::hal::{
+    gpio::{Disconnected, Input, Pin, PullDown, PullUp},
+    prelude::*,
+};
+
+use crate::{
+    events::{self, Event},
+    settings::{InputConfig, InputFunction, Settings, MAX_INPUTS},
+};
+use cutter_core::inputs::{InputBank, PedalAction};
+
+///////////////////////////////////////////////////////////////////////////////
+//  Data Structures
+///////////////////////////////////////////////////////////////////////////////
+
+// Inputs are pulled to their inactive level, so a broken wire reads as inactive
+enum ConfiguredPin {
+    PullUp(Pin<Input<PullUp>>),
+    PullDown(Pin<Input<PullDown>>),
+}
+
+// Debounced digital inputs, assigned to pins and functions by the machine settings rather than in code
+pub struct Inputs {
+    configs: [InputConfig; MAX_INPUTS],
+    pins: [Option<ConfiguredPin>; MAX_INPUTS],
+    bank: InputBank,
+    pedal_action: PedalAction,
+}
+
+///////////////////////////////////////////////////////////////////////////////
+//  Object Implementations
+///////////////////////////////////////////////////////////////////////////////
+
+impl ConfiguredPin {
+    fn is_high(&self) -> bool {
+        match self {
+            Self::PullUp(pin) => pin.is_high().unwrap(),
+            Self::PullDown(pin) => pin.is_high().unwrap(),
+        }
+    }
+}
+
+impl Inputs {
+    // Settings must have passed board_config::validate_inputs(), so no pin is claimed twice
+    pub fn new(settings: &Settings) -> Self {
+        let pins = core::array::from_fn(|i| {
+            let config = &settings.inputs[i];
+            if config.function == InputFunction::Unused {
+                return None;
+            }
+
+            // SAFETY: validation guarantees the pin exists and nothing else in the firmware uses it
+            let pin = unsafe { Pin::<Disconnected>::from_psel_bits(config.psel as u32) };
+            Some(if config.active_low {
+                ConfiguredPin::PullUp(pin.into_pullup_input())
+            } else {
+                ConfiguredPin::PullDown(pin.into_pulldown_input())
+            })
+        });
+
+        let mut inputs = Self {
+            configs: settings.inputs,
+            pins,
+            bank: InputBank::default(),
+            pedal_action: settings.pedal_action,
+        };
+
+        // Start from the inputs' current states, rather than debouncing them in from inactive
+        inputs.bank = InputBank::new(inputs.sample());
+        inputs
+    }
+
+    // Take one sample of every input; call regularly for changes to get through the debounce.
+    // A foot switch going active raises its event.
+    pub fn poll(&mut self) {
+        let was_active = self.bank.state();
+        let active = self.sample();
+        let pressed = self.bank.update(active) & !was_active;
+
+        for (i, config) in self.configs.iter().enumerate() {
+            if config.function == InputFunction::FootSwitch && pressed & (1 << i) != 0 {
+                events::push(Event::Pedal(self.pedal_action));
+            }
+        }
+    }
+
+    // What a foot switch press raises from now on, e.g. once changed in the settings menu
+    pub fn set_pedal_action(&mut self, action: PedalAction) {
+        self.pedal_action = action;
+    }
+
+    // Debounced state of the given input, or None if it's unused
+    pub fn state(&self, index: usize) -> Option<bool> {
+        self.pins[index]
+            .as_ref()
+            .map(|_| self.bank.state() & (1 << index) != 0)
+    }
+
+    pub fn any_active(&self, function: InputFunction) -> bool {
+        (0..MAX_INPUTS).any(|i| self.configs[i].function == function && self.state(i) == Some(true))
+    }
+
+    fn sample(&self) -> u8 {
+        let mut active = 0;
+        for (i, (config, pin)) in self.configs.iter().zip(&self.pins).enumerate() {
+            if let Some(pin) = pin {
+                active |= (config.is_active(pin.is_high()) as u8) << i;
+            }
+        }
+
+        active
+    }
+
+    // Function of the first input calling for the machine to stop, if any
+    pub fn fault(&self) -> Option<InputFunction> {
+        (0..MAX_INPUTS).find_map(|i| {
+            let function = self.configs[i].function;
+            match self.state(i) {
+                Some(active) if function.is_fault(active) => Some(function),
+                _ => None,
+            }
+        })
+    }
+}
@@ -0,0 +1,46 @@
+/* * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * *\
+Copyright (C) 2023 CJ McAllister
+    This program is free software; you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation; either version 3 of the License, or
+    (at your option) any later version.
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+    You should have received a copy of the GNU General Public License
+    along with this program; if not, write to the Free Software Foundation,
+    Inc., 51 Franklin Street, Fifth Floor, Boston, MA 02110-1301  USA
+
+\* * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * */
+
+use crate::platform::hal::{
+    gpio::{Input, Pin, PullUp},
+    prelude::*,
+};
+
+///////////////////////////////////////////////////////////////////////////////
+//  Data Structures
+///////////////////////////////////////////////////////////////////////////////
+
+// Safety interlock, e.g. an enclosure door switch, which must be closed for the machine to move.
+// The switch connects the pin to ground when closed, so a broken wire reads as open.
+pub struct Interlock {
+    pin: Pin<Input<PullUp>>,
+    fitted: bool,
+}
+
+///////////////////////////////////////////////////////////////////////////////
+//  Object Implementations
+///////////////////////////////////////////////////////////////////////////////
+
+impl Interlock {
+    // Builds without a switch fitted treat the interlock as permanently closed
+    pub fn new(pin: Pin<Input<PullUp>>, fitted: bool) -> Self {
+        Self { pin, fitted }
+    }
+
+    pub fn is_closed(&self) -> bool {
+        !self.fitted || self.pin.is_low().unwrap()
+    }
+}
@@ -0,0 +1,106 @@
+/* * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * *\
+Copyright (C) 2023 CJ McAllister
+    This program is free software; you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation; either version 3 of the License, or
+    (at your option) any later version.
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+    You should have received a copy of the GNU General Public License
+    along with this program; if not, write to the Free Software Foundation,
+    Inc., 51 Franklin Street, Fifth Floor, Boston, MA 02110-1301  USA
+
+\* * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * */
+
+// Interrupt priorities, assigned in one place so the preemption design can be read off one table.
+//
+// The nRF52 implements 8 priority levels, 0 being the highest. A pending interrupt preempts any
+// handler of a lower priority, while handlers of the same priority run to completion one after another.
+// From highest to lowest:
+//   e-stop  Nothing may delay stopping the machine. The e-stop input is still polled between cycle
+//           steps, so no interrupt has this level yet; it's kept free for when one does.
+//   timers  Timekeeping, which everything else is measured against
+//   GPIOTE  Pin edges, e.g. inputs that trip the machine; quick to service
+//   TWIM    I2C transfers to the LCD, keypad and accelerometer, which tolerate latency
+//   radio   Never started by this firmware; last, so it can't disturb the machine if it ever is
+//
+// The main thread runs with interrupts enabled, taking critical sections only for shared state.
+// Those mask every level alike; priorities only decide which handler runs first when several are
+// pending, and which may interrupt another.
+//
+// Sharing model: state shared with a handler lives in a `Mutex` and is only touched inside a critical
+// section, on either side, so a handler can never find it half-updated. Critical sections just mask
+// interrupts on this single core, so they nest and can't deadlock; the cost is latency, which is why
+// handlers keep theirs short and never log (see isr_events).
+
+use crate::platform::pac::{Interrupt, NVIC};
+
+///////////////////////////////////////////////////////////////////////////////
+//  Named Constants
+///////////////////////////////////////////////////////////////////////////////
+
+// The nRF52's NVIC implements the top 3 bits of each priority byte
+const NVIC_PRIO_SHIFT: u8 = 5;
+
+const PRIORITIES: [(Interrupt, Level); 9] = [
+    (Interrupt::TIMER0, Level::Timer),
+    (Interrupt::TIMER1, Level::Timer),
+    (Interrupt::TIMER2, Level::Timer),
+    (Interrupt::RTC0, Level::Timer),
+    (Interrupt::RTC1, Level::Timer),
+    (Interrupt::GPIOTE, Level::Gpiote),
+    (Interrupt::SPIM0_SPIS0_TWIM0_TWIS0_SPI0_TWI0, Level::Twim),
+    (Interrupt::SPIM1_SPIS1_TWIM1_TWIS1_SPI1_TWI1, Level::Twim),
+    (Interrupt::RADIO, Level::Radio),
+];
+
+///////////////////////////////////////////////////////////////////////////////
+//  Data Structures
+///////////////////////////////////////////////////////////////////////////////
+
+#[allow(dead_code)]
+#[derive(Copy, Clone, PartialEq, Eq)]
+enum Level {
+    Estop = 0,
+    Timer = 1,
+    Gpiote = 2,
+    Twim = 3,
+    Radio = 4,
+}
+
+///////////////////////////////////////////////////////////////////////////////
+//  Static Functions
+///////////////////////////////////////////////////////////////////////////////
+
+// Assign every priority before any interrupt is unmasked
+pub fn init(nvic: &mut NVIC) {
+    for (interrupt, level) in PRIORITIES {
+        // SAFETY: Nothing is unmasked yet, so no handler can be preempted part-way through a
+        // priority-based critical section
+        unsafe {
+            nvic.set_priority(interrupt, (level as u8) << NVIC_PRIO_SHIFT);
+        }
+    }
+}
+
+// Mask an interrupt by number, for when there's no Interrupt for it, e.g. in the default handler
+pub fn mask_raw(irqn: u16) {
+    // SAFETY: Atomic write to a write-one-to-clear register; masking can't break a critical section
+    unsafe {
+        (*NVIC::PTR).icer[usize::from(irqn / 32)].write(1 << (irqn % 32));
+    }
+}
+
+// Let an interrupt through to its handler, which must only touch shared state in critical sections
+pub fn unmask(interrupt: Interrupt) {
+    debug_assert!(PRIORITIES
+        .iter()
+        .any(|(assigned, _)| *assigned == interrupt));
+
+    // SAFETY: Handlers share state only through Mutexes, so can't break a critical section
+    unsafe {
+        NVIC::unmask(interrupt);
+    }
+}
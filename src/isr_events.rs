@@ -0,0 +1,74 @@
+/* * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * *\
+Copyright (C) 2023 CJ McAllister
+    This program is free software; you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation; either version 3 of the License, or
+    (at your option) any later version.
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+    You should have received a copy of the GNU General Public License
+    along with this program; if not, write to the Free Software Foundation,
+    Inc., 51 Franklin Street, Fifth Floor, Boston, MA 02110-1301  USA
+
+\* * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * */
+
+// Counts of things that happen in interrupt handlers, for where logging isn't possible. Handlers must
+// not log: defmt-rtt takes a critical section for each message, holding off every other interrupt for
+// as long as the message takes to encode, and a handler is no place to be that slow. Counters are
+// plain atomics, so recording one needs no critical section and can't be blocked by the main thread
+// holding one. The main thread reads them back out, e.g. for the console's `dump`.
+
+use core::sync::atomic::{AtomicU32, Ordering};
+
+///////////////////////////////////////////////////////////////////////////////
+//  Named Constants
+///////////////////////////////////////////////////////////////////////////////
+
+const NUM_EVENTS: usize = 2;
+
+///////////////////////////////////////////////////////////////////////////////
+//  Data Structures
+///////////////////////////////////////////////////////////////////////////////
+
+#[derive(Copy, Clone, PartialEq, Eq, defmt::Format)]
+pub enum IsrEvent {
+    // The clock's second alarm fired and was set again
+    SecondTick = 0,
+    // An interrupt was unmasked with no handler for it, and has been masked again
+    UnhandledInterrupt = 1,
+}
+
+#[allow(clippy::declare_interior_mutable_const)]
+const ZERO: AtomicU32 = AtomicU32::new(0);
+static COUNTS: [AtomicU32; NUM_EVENTS] = [ZERO; NUM_EVENTS];
+
+///////////////////////////////////////////////////////////////////////////////
+//  Object Implementations
+///////////////////////////////////////////////////////////////////////////////
+
+impl IsrEvent {
+    pub const ALL: [IsrEvent; NUM_EVENTS] = [IsrEvent::SecondTick, IsrEvent::UnhandledInterrupt];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::SecondTick => "second_ticks",
+            Self::UnhandledInterrupt => "unhandled_interrupts",
+        }
+    }
+}
+
+///////////////////////////////////////////////////////////////////////////////
+//  Static Functions
+///////////////////////////////////////////////////////////////////////////////
+
+// Safe from any context, including handlers preempting one another
+pub fn record(event: IsrEvent) {
+    COUNTS[event as usize].fetch_add(1, Ordering::Relaxed);
+}
+
+// Times the event has happened since boot
+pub fn count(event: IsrEvent) -> u32 {
+    COUNTS[event as usize].load(Ordering::Relaxed)
+}
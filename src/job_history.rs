@@ -0,0 +1,118 @@
+/* * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * *\
+Copyright (C) 2023 CJ McAllister
+    This program is free software; you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation; either version 3 of the License, or
+    (at your option) any later version.
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+    You should have received a copy of the GNU General Public License
+    along with this program; if not, write to the Free Software Foundation,
+    Inc., 51 Franklin Street, Fifth Floor, Boston, MA 02110-1301  USA
+
+\* * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * */
+
+// Finished jobs are written to flash as each ends, as the NVMC is to hand there, and read back oldest
+// first for the console's CSV export. Minimal builds leave the history out, and keep none.
+
+use cutter_core::journal::{header, next_page, page_order, sequence, HEADER_LEN, NUM_PAGES};
+
+use crate::platform::{hal::nvmc::Nvmc, pac::NVMC};
+use crate::sound;
+use crate::storage::{self, Region};
+
+pub use cutter_core::job_history::*;
+
+///////////////////////////////////////////////////////////////////////////////
+//  Named Constants
+///////////////////////////////////////////////////////////////////////////////
+
+pub const ENABLED: bool = !cfg!(feature = "minimal");
+
+const PAGES: [Region; NUM_PAGES] = [Region::HistoryA, Region::HistoryB];
+
+///////////////////////////////////////////////////////////////////////////////
+//  Static Functions
+///////////////////////////////////////////////////////////////////////////////
+
+// Keep a job that's just ended, dated now and counted into the lifetime totals
+pub fn record(record: Record, nvmc: &mut Nvmc<NVMC>) {
+    if !ENABLED {
+        return;
+    }
+
+    let record = record
+        .at(sound::date(), sound::time_of_day())
+        .after(totals());
+    defmt::println!(
+        "Job {} recorded, {} pieces and {}mm of wire in all",
+        record.totals.jobs,
+        record.totals.pieces,
+        record.totals.wire_mm
+    );
+    append(&record, nvmc);
+}
+
+// The lifetime totals, as of the newest record
+pub fn totals() -> Totals {
+    let mut totals = Totals::default();
+    let _ = for_each(|record| {
+        totals = record.totals;
+        Ok::<(), ()>(())
+    });
+    totals
+}
+
+// Every kept record, oldest first
+pub fn for_each<E>(mut f: impl FnMut(&Record) -> Result<(), E>) -> Result<(), E> {
+    let (order, len) = page_order(sequences());
+    for &page in &order[..len] {
+        for slot in 0..RECORDS_PER_PAGE {
+            let bytes = read_slot(page, slot);
+            if is_free(&bytes) {
+                break;
+            }
+            if let Some(record) = Record::from_bytes(&bytes) {
+                f(&record)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+///////////////////////////////////////////////////////////////////////////////
+//  Helper Functions
+///////////////////////////////////////////////////////////////////////////////
+
+// Write a record into the newest page's first free slot, starting the next page over if it's full
+fn append(record: &Record, nvmc: &mut Nvmc<NVMC>) {
+    let sequences = sequences();
+    let (order, len) = page_order(sequences);
+    if len > 0 {
+        let newest = order[len - 1];
+        if let Some(slot) = (0..RECORDS_PER_PAGE).find(|&slot| is_free(&read_slot(newest, slot))) {
+            storage::write_at(PAGES[newest], slot_offset(slot), &record.to_bytes(), nvmc);
+            return;
+        }
+    }
+
+    let (page, sequence) = next_page(sequences);
+    storage::write(PAGES[page], &header(sequence), nvmc);
+    storage::write_at(PAGES[page], slot_offset(0), &record.to_bytes(), nvmc);
+}
+
+fn sequences() -> [Option<u32>; NUM_PAGES] {
+    core::array::from_fn(|page| {
+        let mut bytes = [0; HEADER_LEN];
+        storage::read(PAGES[page], &mut bytes);
+        sequence(&bytes)
+    })
+}
+
+fn read_slot(page: usize, slot: usize) -> [u8; RECORD_LEN] {
+    let mut bytes = [0; RECORD_LEN];
+    storage::read_at(PAGES[page], slot_offset(slot), &mut bytes);
+    bytes
+}
@@ -0,0 +1,144 @@
+/* * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * *\
+Copyright (C) 2023 CJ McAllister
+    This program is free software; you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation; either version 3 of the License, or
+    (at your option) any later version.
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+    You should have received a copy of the GNU General Public License
+    along with this program; if not, write to the Free Software Foundation,
+    Inc., 51 Franklin Street, Fifth Floor, Boston, MA 02110-1301  USA
+
+\* * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * */
+
+// Events are queued in RAM as they happen, as that can be anywhere, and written to flash by flush() from
+// wherever the NVMC is to hand: at boot, around each job, and between console polls.
+
+use core::cell::RefCell;
+
+use cortex_m::interrupt::{self as cortex_interrupt, Mutex};
+
+use crate::log_mirror;
+use crate::platform::{hal::nvmc::Nvmc, pac::NVMC};
+use crate::storage::{self, Region};
+
+pub use cutter_core::journal::*;
+
+///////////////////////////////////////////////////////////////////////////////
+//  Named Constants
+///////////////////////////////////////////////////////////////////////////////
+
+// Events waiting for a flush; more than this between flushes are dropped
+const MAX_PENDING: usize = 8;
+
+const PAGES: [Region; NUM_PAGES] = [Region::JournalA, Region::JournalB];
+
+///////////////////////////////////////////////////////////////////////////////
+//  Data Structures
+///////////////////////////////////////////////////////////////////////////////
+
+struct Pending {
+    entries: [Option<Entry>; MAX_PENDING],
+    len: usize,
+    dropped: u32,
+}
+
+static PENDING: Mutex<RefCell<Pending>> = Mutex::new(RefCell::new(Pending {
+    entries: [None; MAX_PENDING],
+    len: 0,
+    dropped: 0,
+}));
+
+///////////////////////////////////////////////////////////////////////////////
+//  Static Functions
+///////////////////////////////////////////////////////////////////////////////
+
+// Queue an event for the journal, timed now
+pub fn record(kind: Kind, arg: u16, text: &str) {
+    let entry = Entry::new(crate::uptime_s(), kind, arg, text);
+    cortex_interrupt::free(|cs| {
+        let mut pending = PENDING.borrow(cs).borrow_mut();
+        if pending.len == MAX_PENDING {
+            pending.dropped += 1;
+        } else {
+            let len = pending.len;
+            pending.entries[len] = Some(entry);
+            pending.len += 1;
+        }
+    });
+}
+
+// Write queued events to flash, oldest first
+pub fn flush(nvmc: &mut Nvmc<NVMC>) {
+    let (entries, len, dropped) = cortex_interrupt::free(|cs| {
+        let mut pending = PENDING.borrow(cs).borrow_mut();
+        let taken = (pending.entries, pending.len, pending.dropped);
+        pending.len = 0;
+        pending.dropped = 0;
+        taken
+    });
+
+    if dropped > 0 {
+        defmt::println!("{} events dropped before reaching the journal", dropped);
+        log_mirror::dropped(dropped);
+    }
+    for entry in entries[..len].iter().flatten() {
+        append(entry, nvmc);
+        log_mirror::entry(entry);
+    }
+}
+
+// Every journalled event, oldest first
+pub fn for_each<E>(mut f: impl FnMut(&Entry) -> Result<(), E>) -> Result<(), E> {
+    let (order, len) = page_order(sequences());
+    for &page in &order[..len] {
+        for slot in 0..ENTRIES_PER_PAGE {
+            let bytes = read_slot(page, slot);
+            if is_free(&bytes) {
+                break;
+            }
+            if let Some(entry) = Entry::from_bytes(&bytes) {
+                f(&entry)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+///////////////////////////////////////////////////////////////////////////////
+//  Helper Functions
+///////////////////////////////////////////////////////////////////////////////
+
+// Write an entry into the newest page's first free slot, starting the next page over if it's full
+fn append(entry: &Entry, nvmc: &mut Nvmc<NVMC>) {
+    let sequences = sequences();
+    let (order, len) = page_order(sequences);
+    if len > 0 {
+        let newest = order[len - 1];
+        if let Some(slot) = (0..ENTRIES_PER_PAGE).find(|&slot| is_free(&read_slot(newest, slot))) {
+            storage::write_at(PAGES[newest], slot_offset(slot), &entry.to_bytes(), nvmc);
+            return;
+        }
+    }
+
+    let (page, sequence) = next_page(sequences);
+    storage::write(PAGES[page], &header(sequence), nvmc);
+    storage::write_at(PAGES[page], slot_offset(0), &entry.to_bytes(), nvmc);
+}
+
+fn sequences() -> [Option<u32>; NUM_PAGES] {
+    core::array::from_fn(|page| {
+        let mut bytes = [0; HEADER_LEN];
+        storage::read(PAGES[page], &mut bytes);
+        sequence(&bytes)
+    })
+}
+
+fn read_slot(page: usize, slot: usize) -> [u8; ENTRY_LEN] {
+    let mut bytes = [0; ENTRY_LEN];
+    storage::read_at(PAGES[page], slot_offset(slot), &mut bytes);
+    bytes
+}
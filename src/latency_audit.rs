@@ -0,0 +1,160 @@
+/* * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * *\
+Copyright (C) 2023 CJ McAllister
+    This program is free software; you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation; either version 3 of the License, or
+    (at your option) any later version.
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+    You should have received a copy of the GNU General Public License
+    along with this program; if not, write to the Free Software Foundation,
+    Inc., 51 Franklin Street, Fifth Floor, Boston, MA 02110-1301  USA
+
+\* * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * */
+
+// Latency audit, on builds with latency_audit: the cut stroke and e-stop paths are timed with the DWT
+// cycle counter as jobs run, and the worst cases reported against the bounds below after each job and
+// in the console's dump. Other builds compile the hooks away to nothing.
+
+use core::cell::{Cell, RefCell};
+use core::fmt;
+
+use cortex_m::{
+    interrupt::{self as cortex_interrupt, Mutex},
+    peripheral::{DCB, DWT},
+};
+
+pub use cutter_core::latency::*;
+
+///////////////////////////////////////////////////////////////////////////////
+//  Named Constants
+///////////////////////////////////////////////////////////////////////////////
+
+const ENABLED: bool = cfg!(feature = "latency_audit");
+
+// Within one frame at the cutter servo's fastest rate, so a stroke is never a frame late for software
+// reasons; and the e-stop within what a hand can travel towards the blade in a blink
+pub const LIMITS: Limits = Limits {
+    cut_to_pwm_us: 1_000,
+    estop_to_safe_us: 50_000,
+};
+
+const CYCLES_PER_US: u32 = 64;
+
+///////////////////////////////////////////////////////////////////////////////
+//  Data Structures
+///////////////////////////////////////////////////////////////////////////////
+
+static AUDIT: Mutex<RefCell<Audit>> = Mutex::new(RefCell::new(Audit::new()));
+
+// Cycle counts when the last step fell due, the inputs were last looked at while a cycle ran, and an
+// input was seen tripped
+static STEP_DUE: Mutex<Cell<u32>> = Mutex::new(Cell::new(0));
+static INPUTS_CHECKED: Mutex<Cell<Option<u32>>> = Mutex::new(Cell::new(None));
+static TRIPPED: Mutex<Cell<Option<u32>>> = Mutex::new(Cell::new(None));
+
+///////////////////////////////////////////////////////////////////////////////
+//  Static Functions
+///////////////////////////////////////////////////////////////////////////////
+
+// Start the cycle counter the audit times everything by
+pub fn init(dcb: &mut DCB, dwt: &mut DWT) {
+    if ENABLED {
+        dcb.enable_trace();
+        dwt.enable_cycle_counter();
+        defmt::println!("Latency audit running, bounds {}", LIMITS);
+    }
+}
+
+// A step of the cycle has come due
+pub fn step_due() {
+    if ENABLED {
+        cortex_interrupt::free(|cs| STEP_DUE.borrow(cs).set(DWT::cycle_count()));
+    }
+}
+
+// The cutter's PWM sequence has been restarted for a stroke, whose step came due last
+pub fn pwm_updated() {
+    if ENABLED {
+        cortex_interrupt::free(|cs| {
+            let latency_us = elapsed_us(STEP_DUE.borrow(cs).get());
+            AUDIT.borrow(cs).borrow_mut().cut_to_pwm.record(latency_us);
+        });
+    }
+}
+
+// Start timing the gaps between looks at the inputs afresh, e.g. for a new cycle, or once the door is
+// shut again after the machine stood still for it
+pub fn restart_input_gap() {
+    if ENABLED {
+        cortex_interrupt::free(|cs| INPUTS_CHECKED.borrow(cs).set(Some(DWT::cycle_count())));
+    }
+}
+
+// The inputs have been looked at, and were found tripped or not
+pub fn inputs_checked(tripped: bool) {
+    if ENABLED {
+        cortex_interrupt::free(|cs| {
+            let now = DWT::cycle_count();
+            if let Some(last) = INPUTS_CHECKED.borrow(cs).replace(Some(now)) {
+                let gap_us = now.wrapping_sub(last) / CYCLES_PER_US;
+                AUDIT.borrow(cs).borrow_mut().input_gap.record(gap_us);
+            }
+            if tripped {
+                TRIPPED.borrow(cs).set(Some(now));
+            }
+        });
+    }
+}
+
+// The machine has been left safe after a fault, outputs stopped
+pub fn safe_state_reached() {
+    if ENABLED {
+        cortex_interrupt::free(|cs| {
+            INPUTS_CHECKED.borrow(cs).set(None);
+            if let Some(tripped) = TRIPPED.borrow(cs).take() {
+                let latency_us = elapsed_us(tripped);
+                AUDIT
+                    .borrow(cs)
+                    .borrow_mut()
+                    .trip_to_safe
+                    .record(latency_us);
+            }
+        });
+    }
+}
+
+// Log the worst cases so far, and whether they're within bounds
+pub fn report() {
+    if ENABLED {
+        let audit = cortex_interrupt::free(|cs| *AUDIT.borrow(cs).borrow());
+        defmt::println!(
+            "Latency audit {}: {}",
+            if audit.meets(&LIMITS) {
+                "bounds met"
+            } else {
+                "BOUNDS EXCEEDED"
+            },
+            audit
+        );
+    }
+}
+
+// The worst cases as `key value` lines, for the console's dump
+pub fn write_report(out: &mut dyn fmt::Write) -> fmt::Result {
+    if !ENABLED {
+        return Ok(());
+    }
+    let audit = cortex_interrupt::free(|cs| *AUDIT.borrow(cs).borrow());
+    audit.write_report(&LIMITS, out)
+}
+
+///////////////////////////////////////////////////////////////////////////////
+//  Helper Functions
+///////////////////////////////////////////////////////////////////////////////
+
+fn elapsed_us(since: u32) -> u32 {
+    DWT::cycle_count().wrapping_sub(since) / CYCLES_PER_US
+}
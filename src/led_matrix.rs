@@ -0,0 +1,85 @@
+/* * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * *\
+Copyright (C) 2023 CJ McAllister
+    This program is free software; you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation; either version 3 of the License, or
+    (at your option) any later version.
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+    You should have received a copy of the GNU General Public License
+    along with this program; if not, write to the Free Software Foundation,
+    Inc., 51 Franklin Street, Fifth Floor, Boston, MA 02110-1301  USA
+
+\* * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * */
+
+use microbit::{
+    display::blocking::Display,
+    hal::{timer, Timer},
+};
+
+use cutter_core::matrix_text::{self, MATRIX_SIZE};
+
+///////////////////////////////////////////////////////////////////////////////
+//  Named Constants
+///////////////////////////////////////////////////////////////////////////////
+
+pub const HEART: Image = [
+    [0, 1, 0, 1, 0],
+    [1, 1, 1, 1, 1],
+    [1, 1, 1, 1, 1],
+    [0, 1, 1, 1, 0],
+    [0, 0, 1, 0, 0],
+];
+
+pub const BLANK: Image = [[0; MATRIX_SIZE]; MATRIX_SIZE];
+
+// Time each column of scrolling text stays put; slow enough to read across a workshop
+const SCROLL_STEP_IN_MS: u32 = 120;
+
+///////////////////////////////////////////////////////////////////////////////
+//  Data Structures
+///////////////////////////////////////////////////////////////////////////////
+
+// Brightness of each LED, row by row
+pub type Image = [[u8; MATRIX_SIZE]; MATRIX_SIZE];
+
+///////////////////////////////////////////////////////////////////////////////
+//  Static Functions
+///////////////////////////////////////////////////////////////////////////////
+
+pub fn show<T: timer::Instance>(
+    image: Image,
+    duration_ms: u32,
+    timer: &mut Timer<T>,
+    display: &mut Display,
+) {
+    display.show(timer, image, duration_ms);
+}
+
+// Light a single vertical bar at the given column, wrapping around the matrix
+pub fn show_sweep_frame<T: timer::Instance>(
+    frame: usize,
+    duration_ms: u32,
+    timer: &mut Timer<T>,
+    display: &mut Display,
+) {
+    display.show(timer, sweep_frame(frame), duration_ms);
+}
+
+pub fn sweep_frame(frame: usize) -> Image {
+    let mut image = BLANK;
+    for row in image.iter_mut() {
+        row[frame % MATRIX_SIZE] = 1;
+    }
+
+    image
+}
+
+// Scroll a short message across the matrix once, for when there's no LCD to show it on
+pub fn scroll_text<T: timer::Instance>(text: &str, timer: &mut Timer<T>, display: &mut Display) {
+    for offset in 0..=matrix_text::num_frames(text) {
+        display.show(timer, matrix_text::frame(text, offset), SCROLL_STEP_IN_MS);
+    }
+}
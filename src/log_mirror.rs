@@ -0,0 +1,58 @@
+/* * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * *\
+Copyright (C) 2023 CJ McAllister
+    This program is free software; you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation; either version 3 of the License, or
+    (at your option) any later version.
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+    You should have received a copy of the GNU General Public License
+    along with this program; if not, write to the Free Software Foundation,
+    Inc., 51 Franklin Street, Fifth Floor, Boston, MA 02110-1301  USA
+
+\* * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * */
+
+// Plaintext copy of the journal's events on the USB serial port, on builds with log_mirror, so a
+// machine deployed without a debug probe still leaves a readable log for whoever plugs a terminal
+// in. Each line is `LOG ` then the entry as the console's `journal` prints it, so hosts listening
+// for status lines or console replies can tell them apart. Other builds compile this away.
+
+use core::fmt::Write;
+
+use cortex_m::interrupt as cortex_interrupt;
+
+use crate::{journal::Entry, SERIAL_HANDLE};
+
+///////////////////////////////////////////////////////////////////////////////
+//  Named Constants
+///////////////////////////////////////////////////////////////////////////////
+
+const ENABLED: bool = cfg!(feature = "log_mirror");
+
+///////////////////////////////////////////////////////////////////////////////
+//  Static Functions
+///////////////////////////////////////////////////////////////////////////////
+
+// An event has been written to the journal
+pub fn entry(entry: &Entry) {
+    if ENABLED {
+        cortex_interrupt::free(|cs| {
+            if let Some(port) = SERIAL_HANDLE.borrow(cs).borrow_mut().as_mut() {
+                let _ = writeln!(port, "LOG {}", entry);
+            }
+        });
+    }
+}
+
+// Events were lost before reaching the journal, so the mirror is missing them too
+pub fn dropped(count: u32) {
+    if ENABLED {
+        cortex_interrupt::free(|cs| {
+            if let Some(port) = SERIAL_HANDLE.borrow(cs).borrow_mut().as_mut() {
+                let _ = writeln!(port, "LOG {} events dropped", count);
+            }
+        });
+    }
+}
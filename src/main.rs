@@ -17,40 +17,315 @@ Copyright (C) 2023 CJ McAllister
 #![no_main]
 #![no_std]
 
-use core::cell::RefCell;
+// Both take ring pin 1 by name in init(), which won't build, so say why rather than leave a moved
+// value error; board_config::validate() catches the conflicts that only share a pin number
+#[cfg(all(feature = "vibration", feature = "piece_sorter"))]
+compile_error!(
+    "features `vibration` and `piece_sorter` both use ring pin 1; enable only one of them"
+);
 
-use cortex_m::{
-    interrupt::{self as cortex_interrupt, Mutex},
-    peripheral::NVIC,
-};
+use core::cell::{Cell, RefCell};
+use core::fmt;
+
+use cortex_m::interrupt::{self as cortex_interrupt, Mutex};
 
 use microbit::{
-    hal::{gpio::Level, prelude::*, timer, twim, Timer, Twim},
-    pac::{interrupt, Interrupt, PWM0, TIMER0, TIMER1, TWIM0},
+    display::blocking::Display,
+    hal::nvmc::Nvmc,
+    hal::{
+        clocks::{Clocks, LfOscConfiguration},
+        gpio::Level,
+        prelude::*,
+        pwm,
+        rtc::Rtc,
+        timer, twim, Timer, Twim,
+    },
+    pac::{interrupt, Interrupt, NVMC, PWM0, PWM1, PWM2, RTC0, TIMER0, TIMER1, TWIM0, TWIM1},
     Board,
 };
 
 mod i2c;
 use crate::i2c::{
+    internal::{self, accelerometer, magnetometer, OnboardSensors},
     keypad::{self, Key},
-    lcd1602,
+    lcd1602::{self, CursorStyle},
+};
+
+mod actuators;
+
+mod axis;
+use axis::Axis;
+
+mod analog;
+use analog::{Analog, AnalogInput, Oversample};
+
+mod backlash;
+
+mod board_config;
+
+mod buzzer;
+use buzzer::Buzzer;
+
+mod clock;
+
+mod console;
+
+mod counter;
+
+mod delay;
+
+mod demo;
+
+mod error;
+use error::CutterError;
+
+mod eta;
+use eta::{CycleHistory, PieceTimer};
+
+mod events;
+use events::Event;
+
+use cutter_core::{
+    error_log::ErrorLog,
+    import::JobLimits,
+    input::NumberEntry,
+    inputs::PedalAction,
+    job::{self, Bundle, Engine, Fault, Fed, Progress, State, BUNDLE_LABEL_LEN},
+    label::Label,
+    motion::{
+        Action, ClampTiming, CycleTiming, FeedProfile, Plan, StraightenerTiming, StrokeProfile,
+    },
+    numeric,
+    qa::{first_piece_line, FIRST_PIECE_LEN},
+    queue::{Job, JobQueue},
+    sorter::{Bin, SortRule},
+    status::{MachineState, Status},
+    summary::{SpoolAfter, Summary},
+    thermal::{DutyLimit, DutyTracker},
+    units::{Length, Rounding, Unit},
+    widgets::{self, Area, Counter},
 };
 
+mod haptic;
+
+mod headless;
+
+mod help;
+use help::Hint;
+
+mod inputs;
+use inputs::Inputs;
+
+mod irq;
+
+mod job_history;
+
+mod journal;
+
+mod latency_audit;
+
+mod isr_events;
+use isr_events::IsrEvent;
+
+mod interlock;
+use interlock::Interlock;
+
+mod led_matrix;
+
+mod log_mirror;
+
+mod platform;
+
+mod power_audit;
+
+mod profiles;
+
+mod pwm_manager;
+use pwm_manager::{Consumer, Usage};
+
+mod qa;
+use profiles::{Units, PREVIEW_LEN};
+
+mod qdec;
+use qdec::{MeasuringWheel, Qdec};
+
+mod queue_menu;
+
+mod recovery;
+
+#[cfg(feature = "input_replay")]
+mod replay;
+
+mod safe_mode;
+
+mod screensaver;
+
+mod serial;
+use serial::SerialPort;
+
 mod servo;
-use servo::Servo;
+use servo::{Servo, CUT_POSITION_OPEN, DIVERT_POSITION_BIN_A, DIVERT_POSITION_BIN_B};
+
+mod service_menu;
+
+mod settings;
+use settings::{InputFunction, LargeJob, Settings, Startup};
+
+mod settings_menu;
+
+#[cfg(not(feature = "clamp_servo"))]
+mod solenoid;
+#[cfg(not(feature = "clamp_servo"))]
+use solenoid::Solenoid;
+
+mod sound;
+
+mod splash;
+
+mod stats;
+use stats::JobStats;
+
+mod status_panel;
+
+#[cfg(feature = "status_stream")]
+mod status_stream;
+
+mod stepper;
+use stepper::Stepper;
+
+mod storage;
+
+mod straightener;
+use straightener::Straightener;
+
+mod tick_watch;
+
+mod tuning;
+use tuning::Param;
 
 ///////////////////////////////////////////////////////////////////////////////
 //  Named Constants
 ///////////////////////////////////////////////////////////////////////////////
 
 const ONE_SECOND_IN_MHZ: u32 = 1000000;
-const GREETING_DUR_IN_MS: u32 = 2500;
+const NO_KEYPAD_DUR_IN_MS: u32 = 3000;
+const KEY_POLL_INTERVAL_IN_MS: u32 = 50;
+
+const ENTRY_ERROR_DUR_IN_MS: u32 = 1500;
+
+const MIN_CUT_LENGTH: u32 = 1;
+const MAX_CUT_LENGTH_IN: u32 = 1000;
+const MAX_CUT_LENGTH_MM: u32 = 25400;
+const MIN_NUM_CUTS: u32 = 1;
+const MAX_NUM_CUTS: u32 = 99999;
+
+const MS_PER_MINUTE: u32 = 60 * 1000;
+const MINUTES_PER_HOUR: u32 = 60;
 
-// Cap input to 5 digits for ease of implementation
-const MAX_INPUT_CHARS: usize = 5;
+// Number of final pieces in a job which get an audible tick
+const COUNTDOWN_TICKS: u32 = 5;
+
+// Further strokes at a cut the blade didn't get through, before the job is halted
+const CUT_RETRIES: u32 = 1;
+
+const FINISHED_DUR_IN_MS: u32 = 3000;
+const IDLE_REPORT_INTERVAL_IN_MS: u32 = 500;
+
+// Uptime is kept by an RTC, which counts even while a critical section holds interrupts off.
+// 32.768kHz / 4096 gives 8 ticks a second, so the 24-bit counter lasts ~24 days.
+const UPTIME_RTC_PRESCALER: u32 = 4095;
+const UPTIME_TICKS_PER_SECOND: u32 = 8;
+
+// 100PPR encoder on a wheel of 1" circumference
+const MEASURING_WHEEL: MeasuringWheel = MeasuringWheel {
+    counts_per_rev: 400,
+    circumference_mils: 1000,
+};
 
 const CUT_CYCLE_TIME_MS: u32 = 1500;
-const WIRE_FEED_TIME_MS: u32 = 3000;
+
+// Hobby servos overheat if held stalled against the wire for too long, so fast jobs are paced to this
+const CUTTER_DUTY_LIMIT: DutyLimit = DutyLimit {
+    window_ms: 60_000,
+    max_duty_pct: 70,
+};
+
+// Wire clamp on pin 5, gripping the wire either side of the cut on builds with one fitted
+const CLAMP_FITTED: bool = cfg!(feature = "clamp");
+
+// Servo-driven axes, and the clamp if fitted. The blade's timings are defaults, and can be tuned.
+const CYCLE_TIMING: CycleTiming = CycleTiming {
+    cut_dwell_ms: CUT_CYCLE_TIME_MS,
+    blade_clearance_ms: 200,
+    stroke: StrokeProfile::PLAIN,
+    clamp: if CLAMP_FITTED {
+        Some(ClampTiming {
+            settle_ms: 150,
+            release_ms: 100,
+        })
+    } else {
+        None
+    },
+};
+
+// Clamp actuator, and its positions in the actuator's own units
+#[cfg(not(feature = "clamp_servo"))]
+type Clamp = Solenoid;
+#[cfg(not(feature = "clamp_servo"))]
+const CLAMP_GRIP: i32 = solenoid::ENERGISED;
+#[cfg(not(feature = "clamp_servo"))]
+const CLAMP_RELEASE: i32 = solenoid::RELEASED;
+#[cfg(feature = "clamp_servo")]
+type Clamp = Servo<platform::pac::PWM3>;
+#[cfg(feature = "clamp_servo")]
+const CLAMP_GRIP: i32 = servo::CLAMP_POSITION_CLOSED;
+#[cfg(feature = "clamp_servo")]
+const CLAMP_RELEASE: i32 = servo::CLAMP_POSITION_OPEN;
+
+// 32.768kHz watch crystal across the LFXO pins, on boards with one fitted. The RTCs run off the
+// internal RC oscillator otherwise, which drifts more with temperature.
+const WATCH_CRYSTAL_FITTED: bool = cfg!(feature = "watch_crystal");
+
+// Banner and animation shown at power-up; point this at your own splash::Splash to personalize it
+type BootSplash = splash::Greeting;
+
+// Feed stepper on a 1" circumference drive roller, at 200 full steps per revolution
+const FEED_STEPS_PER_INCH: u32 = 200;
+
+// Feed axis is stepped at most once per poll, giving a top speed of 5 in/s
+const FEED_POLL_INTERVAL_IN_MS: u32 = 1;
+const FEED_STEPS_PER_SECOND: u32 = 1000 / FEED_POLL_INTERVAL_IN_MS;
+const MILS_PER_FEED_STEP: u32 = 1000 / FEED_STEPS_PER_INCH;
+
+// Feed axis limits at 100% feed speed, by default; both can be tuned
+const FEED_PROFILE: FeedProfile = FeedProfile {
+    max_speed_mils_per_s: 4000,
+    accel_mils_per_s2: 8000,
+};
+
+// Straightener/de-reeler motor run-on either side of each feed, on builds with one fitted
+const STRAIGHTENER_TIMING: StraightenerTiming = StraightenerTiming {
+    pre_roll_ms: 250,
+    post_roll_ms: 150,
+};
+
+// Door switch wired from ring pin 0 to GND, closed when the enclosure is shut
+const DOOR_INTERLOCK_FITTED: bool = cfg!(feature = "door_interlock");
+
+// No feed stepper; wire is fed by hand and cut on the foot switch
+const COUNTER_ONLY: bool = cfg!(feature = "counter_only");
+const CUT_STEP_POLL_INTERVAL_IN_MS: u32 = 10;
+
+// How a job's pieces are shared between the bins, on builds with the piece chute
+const SORT_RULE: SortRule = SortRule::Batches { size: 10 };
+
+// Blade position feedback, for servos with the potentiometer wiper broken out to ring pin 1
+#[cfg(feature = "servo_feedback")]
+const CUTTER_FEEDBACK: servo::PositionFeedback = servo::PositionFeedback {
+    input: AnalogInput::Ring1,
+    closed_mv: 2400,
+    tolerance_mv: 150,
+};
 
 ///////////////////////////////////////////////////////////////////////////////
 //  Shared Peripheral Handles
@@ -59,7 +334,29 @@ const WIRE_FEED_TIME_MS: u32 = 3000;
 static TIMER0_HANDLE: Mutex<RefCell<Option<Timer<TIMER0>>>> = Mutex::new(RefCell::new(None));
 static TIMER1_HANDLE: Mutex<RefCell<Option<Timer<TIMER1>>>> = Mutex::new(RefCell::new(None));
 static I2C0_HANDLE: Mutex<RefCell<Option<Twim<TWIM0>>>> = Mutex::new(RefCell::new(None));
+static I2C1_HANDLE: Mutex<RefCell<Option<Twim<TWIM1>>>> = Mutex::new(RefCell::new(None));
+static ONBOARD_SENSORS: Mutex<Cell<OnboardSensors>> = Mutex::new(Cell::new(OnboardSensors {
+    accelerometer: false,
+    magnetometer: false,
+}));
+static STAR_AT_POWER_ON: Mutex<Cell<bool>> = Mutex::new(Cell::new(false));
 static CUTTER_HANDLE: Mutex<RefCell<Option<Servo<PWM0>>>> = Mutex::new(RefCell::new(None));
+static DIVERTER_HANDLE: Mutex<RefCell<Option<Servo<PWM2>>>> = Mutex::new(RefCell::new(None));
+static CLAMP_HANDLE: Mutex<RefCell<Option<Clamp>>> = Mutex::new(RefCell::new(None));
+static FEED_HANDLE: Mutex<RefCell<Option<Stepper>>> = Mutex::new(RefCell::new(None));
+static BUZZER_HANDLE: Mutex<RefCell<Option<Buzzer<PWM1>>>> = Mutex::new(RefCell::new(None));
+static STRAIGHTENER_HANDLE: Mutex<RefCell<Option<Straightener>>> = Mutex::new(RefCell::new(None));
+static ANALOG_HANDLE: Mutex<RefCell<Option<Analog>>> = Mutex::new(RefCell::new(None));
+static INTERLOCK_HANDLE: Mutex<RefCell<Option<Interlock>>> = Mutex::new(RefCell::new(None));
+static INPUTS_HANDLE: Mutex<RefCell<Option<Inputs>>> = Mutex::new(RefCell::new(None));
+static ENCODER_HANDLE: Mutex<RefCell<Option<Qdec>>> = Mutex::new(RefCell::new(None));
+static SERIAL_HANDLE: Mutex<RefCell<Option<SerialPort<platform::pac::UARTE0>>>> =
+    Mutex::new(RefCell::new(None));
+static LED_MATRIX_HANDLE: Mutex<RefCell<Option<Display>>> = Mutex::new(RefCell::new(None));
+static SETTINGS_HANDLE: Mutex<RefCell<Option<Settings>>> = Mutex::new(RefCell::new(None));
+static NVMC_HANDLE: Mutex<RefCell<Option<Nvmc<NVMC>>>> = Mutex::new(RefCell::new(None));
+static UPTIME_HANDLE: Mutex<RefCell<Option<Rtc<RTC0>>>> = Mutex::new(RefCell::new(None));
+static ERROR_LOG: Mutex<RefCell<ErrorLog>> = Mutex::new(RefCell::new(ErrorLog::new()));
 
 ///////////////////////////////////////////////////////////////////////////////
 //  Tasks
@@ -76,29 +373,467 @@ fn main() -> ! {
     idle();
 }
 
+// Hardware a feed/cut cycle (or the service menu) drives and monitors
+struct CycleHardware<'a, C: Axis, F: Axis, D: Axis> {
+    cutter: &'a mut C,
+    feed: &'a mut F,
+    // Piece chute, if fitted
+    diverter: Option<&'a mut D>,
+    clamp: Option<&'a mut Clamp>,
+    straightener: Option<&'a mut Straightener>,
+    interlock: &'a Interlock,
+    inputs: &'a mut Inputs,
+    encoder: &'a mut Qdec,
+    analog: &'a mut Analog,
+}
+
+// What's needed to plan and confirm a job, fixed once the operator profile is chosen
+struct JobSetup {
+    units: Units,
+    feed_speed_pct: u32,
+    straightener_fitted: bool,
+    big_digits: bool,
+    // How the profile's pieces have really run against their plans
+    cycle_history: CycleHistory,
+    // How the blade closes for the profile's material
+    stroke: StrokeProfile,
+    // Wire on the spool before the queue runs, if it's being counted
+    spool_left: Option<Length>,
+    // Jobs past which a second confirmation is asked for
+    large_job: LargeJob,
+}
+
+impl JobSetup {
+    // Limits on length/quantity for the job's current units
+    fn limits(&self) -> JobLimits {
+        job_limits(self.units)
+    }
+
+    // Plan the feed/cut cycle for one piece of the given length
+    fn plan(&self, cut_length: u32) -> Plan {
+        self.plan_mils(self.units.to_mils(cut_length))
+    }
+
+    // How long a piece of the given length is likely to take, going by past jobs
+    fn estimate_ms(&self, cut_length: u32) -> u32 {
+        self.cycle_history
+            .estimate_ms(self.plan(cut_length).duration_ms())
+    }
+
+    // As plan(), for a length already in mils, e.g. scrap
+    fn plan_mils(&self, feed_mils: u32) -> Plan {
+        let plan = Plan::cut_cycle(&self.cycle_timing(), &self.feed_profile(), feed_mils);
+        if self.straightener_fitted {
+            plan.with_straightener(&STRAIGHTENER_TIMING)
+        } else {
+            plan
+        }
+    }
+
+    // Feed axis limits as tuned, at the operator's feed speed
+    fn feed_profile(&self) -> FeedProfile {
+        feed_profile().scaled(self.feed_speed_pct)
+    }
+
+    // Cycle timing as tuned, closing the blade the operator's way
+    fn cycle_timing(&self) -> CycleTiming {
+        CycleTiming {
+            stroke: self.stroke,
+            ..cycle_timing()
+        }
+    }
+}
+
+// Feed axis limits as tuned, at full speed
+fn feed_profile() -> FeedProfile {
+    FeedProfile {
+        max_speed_mils_per_s: tuning::get(Param::FeedSpeed),
+        accel_mils_per_s2: tuning::get(Param::FeedAccel),
+    }
+}
+
+// Cycle timing as tuned
+fn cycle_timing() -> CycleTiming {
+    CycleTiming {
+        cut_dwell_ms: tuning::get(Param::CutDwellMs),
+        blade_clearance_ms: tuning::get(Param::BladeClearanceMs),
+        ..CYCLE_TIMING
+    }
+}
+
+// The machine as seen by the job engine, while cutting the pieces of one job
+struct JobMachine<'a> {
+    hardware: CycleHardware<'a, Servo<PWM0>, Stepper, Servo<PWM2>>,
+    setup: &'a JobSetup,
+    timer: &'a mut Timer<TIMER0>,
+    i2c: &'a mut Twim<TWIM0>,
+    // Internal bus, if the accelerometer is available for vibration monitoring
+    vibration_i2c: Option<&'a mut Twim<TWIM1>>,
+    buzzer: &'a mut Buzzer<PWM1>,
+    stats: &'a mut JobStats,
+    // That the piece being cut goes into, if the job has bundles
+    bundle: Option<Bundle>,
+    status: &'a mut Status,
+    // Of the job being cut, for the status panel
+    label: &'a str,
+    cutter_duty: &'a mut DutyTracker,
+    piece_timer: &'a mut PieceTimer,
+}
+
+impl JobMachine<'_> {
+    // Run part of a piece's cycle, telling the operator the job is held for as long as the door is open
+    fn run(
+        &mut self,
+        plan: &Plan,
+        piece: u32,
+        stopped_short: Option<&mut Option<u32>>,
+    ) -> Result<Option<u32>, CutterError> {
+        let Self {
+            hardware,
+            timer,
+            i2c,
+            vibration_i2c,
+            stats,
+            status,
+            bundle,
+            ..
+        } = self;
+        let mut show_door = |timer: &mut Timer<TIMER0>, open: bool| {
+            if open {
+                defmt::println!("Door opened during piece {}, pausing job", piece);
+                lcd1602::clear_display(timer, i2c);
+                lcd1602::write_string("DOOR OPEN\nCLOSE TO RESUME", timer, i2c);
+            } else {
+                defmt::println!("Door closed, resuming job");
+                draw_cutting_screen(status.piece, status.num_cuts, *bundle, stats, timer, i2c);
+            }
+        };
+
+        run_cycle(
+            plan,
+            hardware,
+            timer,
+            vibration_i2c.as_deref_mut(),
+            &mut show_door,
+            stopped_short,
+        )
+    }
+
+    // Run the feed part of a piece's cycle, which a pedal pause can stop short
+    fn run_feed(&mut self, feed_plan: &Plan, piece: u32) -> Result<Fed, CutterError> {
+        let mut stopped_short = None;
+        self.run(feed_plan, piece, Some(&mut stopped_short))?;
+        self.cutter_duty.advance(feed_plan.duration_ms(), false);
+
+        Ok(match stopped_short {
+            Some(left_mils) => {
+                defmt::println!("Feed for piece {} paused, {} mils short", piece, left_mils);
+                Fed::Short { left_mils }
+            }
+            None => Fed::All,
+        })
+    }
+
+    // Let the cutter servo cool first if cutting now would work it too hard
+    fn cool_cutter(&mut self, piece: u32) {
+        // The blade is pressed against the wire for the whole stroke, which is when the servo works hardest
+        let stroke_ms = self.setup.cycle_timing().stroke_ms();
+        let cooldown_ms = self.cutter_duty.cooldown_ms(stroke_ms);
+        if cooldown_ms > 0 {
+            defmt::println!(
+                "Cooling cutter servo for {}ms before cut {}",
+                cooldown_ms,
+                piece
+            );
+            lcd1602::clear_display(self.timer, self.i2c);
+            lcd1602::write_string("COOLING SERVO...", self.timer, self.i2c);
+            self.timer.delay_ms(cooldown_ms);
+            self.cutter_duty.advance(cooldown_ms, false);
+            draw_cutting_screen(
+                self.status.piece,
+                self.status.num_cuts,
+                self.bundle,
+                self.stats,
+                self.timer,
+                self.i2c,
+            );
+        }
+    }
+
+    // Count a cut stroke against the cutter servo's duty limit
+    fn account_cut(&mut self, cut_plan: &Plan) {
+        let stroke_ms = self.setup.cycle_timing().stroke_ms();
+        self.cutter_duty.advance(stroke_ms, true);
+        self.cutter_duty
+            .advance(cut_plan.duration_ms() - stroke_ms, false);
+    }
+}
+
+impl job::Cutter for JobMachine<'_> {
+    fn cut(&mut self, piece: u32) -> Result<(), Fault> {
+        self.cool_cutter(piece);
+
+        // Send the piece to its bin, if there's a chute to do it
+        let plan = self.setup.plan(self.status.cut_length);
+        let plan = match self.hardware.diverter {
+            Some(_) => plan.diverted_to(SORT_RULE.bin_for(piece)),
+            None => plan,
+        };
+        let (cut_plan, _) = plan.split_at_feed();
+
+        // Don't count the piece unless the blade is known to have gone all the way through
+        let vibration_mg = self.run(&cut_plan, piece, None).inspect_err(|e| {
+            defmt::println!("Cut {} failed: {}", piece, e);
+        })?;
+        self.stats.record_cut(vibration_mg);
+        self.account_cut(&cut_plan);
+
+        Ok(())
+    }
+
+    fn cut_scrap(&mut self) -> Result<(), Fault> {
+        let piece = self.status.piece + 1;
+        self.cool_cutter(piece);
+
+        let (cut_plan, _) = self.setup.plan_mils(0).split_at_feed();
+        self.run(&cut_plan, piece, None).inspect_err(|e| {
+            defmt::println!("Scrap cut failed: {}", e);
+        })?;
+        self.account_cut(&cut_plan);
+
+        Ok(())
+    }
+}
+
+impl job::Feeder for JobMachine<'_> {
+    fn feed(&mut self, length: u32) -> Result<Fed, Fault> {
+        let (_, feed_plan) = self.setup.plan(length).split_at_feed();
+
+        let piece = self.status.piece + 1;
+        let fed = self.run_feed(&feed_plan, piece).inspect_err(|e| {
+            defmt::println!("Feed after cut {} failed: {}", piece, e);
+        })?;
+
+        Ok(fed)
+    }
+
+    fn feed_rest(&mut self, length_mils: u32) -> Result<Fed, Fault> {
+        let (_, feed_plan) = self.setup.plan_mils(length_mils).split_at_feed();
+
+        defmt::println!("Feeding the last {} mils of a paused feed", length_mils);
+        let piece = self.status.piece + 1;
+        let fed = self.run_feed(&feed_plan, piece).inspect_err(|e| {
+            defmt::println!("Rest of paused feed failed: {}", e);
+        })?;
+
+        Ok(fed)
+    }
+
+    fn feed_scrap(&mut self, length_mils: u32) -> Result<(), Fault> {
+        let (_, feed_plan) = self.setup.plan_mils(length_mils).split_at_feed();
+
+        defmt::println!("Feeding {} mils of scrap", length_mils);
+        let piece = self.status.piece + 1;
+        self.run(&feed_plan, piece, None).inspect_err(|e| {
+            defmt::println!("Scrap feed failed: {}", e);
+        })?;
+        self.cutter_duty.advance(feed_plan.duration_ms(), false);
+
+        Ok(())
+    }
+}
+
+impl job::Display for JobMachine<'_> {
+    fn show_progress(&mut self, progress: Progress) {
+        // However quickly pieces come, the display is only drawn a few times a second
+        update_cutting_screen(progress, self.bundle, self.stats);
+        lcd1602::draw_frame_throttled(self.timer, self.i2c);
+        status_panel::show_progress(self.label, progress, self.timer, self.i2c);
+        self.status.piece = progress.piece;
+        report_status(self.status);
+        self.piece_timer.piece_done();
+
+        // Count down the final few pieces audibly
+        if progress.remaining() < COUNTDOWN_TICKS {
+            self.buzzer.tick(self.timer);
+        }
+    }
+}
+
+// Pins are taken by name below; keep board_config::ASSIGNMENTS in sync with them
 fn init() {
     // Take ownership of the full board
-    let board = Board::take().unwrap();
+    let mut board = Board::take().unwrap();
+    irq::init(&mut board.NVIC);
+
+    // The microbit crate's Board doesn't expose every peripheral, so take the rest from the PAC directly
+    let extra_periphs = unsafe { microbit::pac::Peripherals::steal() };
+
+    // The feed driver is switched off before anything else is set up. Its enable line is active-low,
+    // so it starts high, leaving the driver off until a job powers it.
+    defmt::println!("Initializing Feed Stepper...");
+    let feed_step_pin = board.pins.p0_10.into_push_pull_output(Level::Low).degrade(); // P8
+    let feed_dir_pin = board.pins.p0_13.into_push_pull_output(Level::Low).degrade(); // P15
+    let feed_enable_pin = board
+        .pins
+        .p0_04
+        .into_push_pull_output(Level::High)
+        .degrade(); // P2
+    let mut feed = Stepper::new(feed_step_pin, feed_dir_pin, Some(feed_enable_pin), None);
+
+    // The cutter's is the first PWM claim, so it can't be refused
+    defmt::println!("Initializing Cutter Servo...");
+    let cutter_pwm =
+        pwm_manager::claim::<PWM0>(pwm::Channel::C0, Consumer::CutterServo, Usage::Whole).unwrap();
+    let pwm_output_pin = board.pins.p0_09.into_push_pull_output(Level::Low).degrade();
+    let mut cutter = Servo::new(
+        board.PWM0,
+        cutter_pwm,
+        pwm_output_pin,
+        CUT_POSITION_OPEN,
+        servo::CUT_TRAVEL,
+    );
+
+    // Refused PWM claims leave their consumer unbuilt, and halt start-up once the LCD is up
+    #[cfg(feature = "piece_sorter")]
+    if let Ok(diverter_pwm) =
+        pwm_manager::claim::<PWM2>(pwm::Channel::C0, Consumer::DiverterServo, Usage::Whole)
+    {
+        defmt::println!("Initializing Piece Chute Servo...");
+        let diverter_pin = board.pins.p0_03.into_push_pull_output(Level::Low).degrade(); // P1
+        let diverter = Servo::new(
+            board.PWM2,
+            diverter_pwm,
+            diverter_pin,
+            DIVERT_POSITION_BIN_A,
+            servo::DIVERT_TRAVEL,
+        );
+        cortex_interrupt::free(|cs| DIVERTER_HANDLE.borrow(cs).replace(Some(diverter)));
+    }
+
+    #[cfg(feature = "clamp")]
+    {
+        defmt::println!("Initializing Wire Clamp...");
+        // Button A is wired to the same pin, and mustn't be pressed while a servo is driving it
+        #[cfg(feature = "clamp_servo")]
+        let clamp = match pwm_manager::claim::<platform::pac::PWM3>(
+            pwm::Channel::C0,
+            Consumer::ClampServo,
+            Usage::Whole,
+        ) {
+            Ok(clamp_pwm) => {
+                let clamp_pin = board
+                    .buttons
+                    .button_a
+                    .into_push_pull_output(Level::Low)
+                    .degrade(); // P5
+                Some(Servo::new(
+                    board.PWM3,
+                    clamp_pwm,
+                    clamp_pin,
+                    CLAMP_RELEASE,
+                    servo::CLAMP_TRAVEL,
+                ))
+            }
+            Err(_) => None,
+        };
+        #[cfg(not(feature = "clamp_servo"))]
+        let clamp = {
+            let clamp_pin = board
+                .buttons
+                .button_a
+                .into_open_drain_output(
+                    microbit::hal::gpio::OpenDrainConfig::Standard0Disconnect1,
+                    Level::High,
+                )
+                .degrade(); // P5
+            Some(Solenoid::new(clamp_pin))
+        };
+        cortex_interrupt::free(|cs| CLAMP_HANDLE.borrow(cs).replace(clamp));
+    }
+
+    #[cfg(feature = "straightener")]
+    {
+        defmt::println!("Initializing Wire Straightener...");
+        // Button B is wired to the same pin; the open-drain output never fights it
+        let straightener_pin = board
+            .buttons
+            .button_b
+            .into_open_drain_output(
+                microbit::hal::gpio::OpenDrainConfig::Standard0Disconnect1,
+                Level::High,
+            )
+            .degrade(); // P11
+        let straightener = Straightener::new(straightener_pin);
+        cortex_interrupt::free(|cs| STRAIGHTENER_HANDLE.borrow(cs).replace(Some(straightener)));
+    }
+
+    // Nothing may move or be left powered while the rest of the machine comes up, however the last
+    // run ended. The actuators above are only built before this, not homed or polled.
+    defmt::println!("Putting Actuators in Safe State...");
+    cortex_interrupt::free(|cs| {
+        actuators::safe_state(
+            &mut cutter,
+            &mut feed,
+            CLAMP_HANDLE.borrow(cs).borrow_mut().as_mut(),
+            STRAIGHTENER_HANDLE.borrow(cs).borrow_mut().as_mut(),
+        );
+        #[cfg(feature = "piece_sorter")]
+        if let Some(diverter) = DIVERTER_HANDLE.borrow(cs).borrow_mut().as_mut() {
+            diverter.home();
+        }
+    });
+
+    #[cfg(feature = "vibration")]
+    {
+        defmt::println!("Initializing Vibration Motor...");
+        let motor_pin = board.pins.p0_03.into_push_pull_output(Level::Low).degrade(); // P1
+        haptic::init(motor_pin);
+    }
 
     // Hold various chips in reset/output-disabled
     let i2c_reset_pin = board.pins.p1_02.into_push_pull_output(Level::Low); // P16
     let mut lcd_lvshift_oe_pin = board.pins.p0_12.into_push_pull_output(Level::High); // P12
 
     // Instantiate a timer
-    let timer0 = init_1s_timer(board.TIMER0);
+    let mut timer0 = init_1s_timer(board.TIMER0);
+
+    // Short waits spin the CPU rather than tie up a timer, so they have to be timed against one
+    delay::calibrate(&mut timer0);
 
-    // Initialize a 1-second timer
-    let timer1 = init_1s_timer(board.TIMER1);
+    // Nothing is timed on this one, the clock's RTC keeping the second tick, so it's free for motion
+    let timer1 = Timer::new(board.TIMER1);
     cortex_interrupt::free(|cs| TIMER1_HANDLE.borrow(cs).replace(Some(timer1)));
 
+    // Start the clocks early, so the keypad can be paced and errors timestamped from the start
+    defmt::println!("Initializing Uptime Counter...");
+    let clocks = Clocks::new(board.CLOCK);
+    if WATCH_CRYSTAL_FITTED {
+        clocks
+            .set_lfclk_src_external(LfOscConfiguration::NoExternalNoBypass)
+            .start_lfclk();
+    } else {
+        clocks.start_lfclk();
+    }
+    let uptime = Rtc::new(board.RTC0, UPTIME_RTC_PRESCALER).unwrap();
+    uptime.enable_counter();
+    cortex_interrupt::free(|cs| UPTIME_HANDLE.borrow(cs).replace(Some(uptime)));
+    clock::init(extra_periphs.RTC1);
+    irq::unmask(Interrupt::RTC1);
+    tick_watch::start();
+    latency_audit::init(&mut board.DCB, &mut board.DWT);
+
+    // Bound every I2C transaction before the first one, so a wedged device can't hang start-up
+    i2c::timeout::init(board.TIMER2, &extra_periphs.PPI);
+
     // Initialize the TWIM0 (I2C) controller
     let mut i2c0 = i2c::init(
         board.TWIM0,
-        board.i2c_external,
+        twim::Pins::from(board.i2c_external),
         &mut i2c_reset_pin.degrade(),
     );
 
-    // Initialize LCD Display and display greeting
+    // Initialize LCD Display
     defmt::println!("Enabling power to LCD Display...");
     lcd1602::power_on(&mut i2c0);
 
@@ -110,93 +845,762 @@ fn init() {
         let mut local_timer1_handle_ref = TIMER1_HANDLE.borrow(cs).borrow_mut();
         let local_timer1_handle = local_timer1_handle_ref.as_mut().unwrap();
         lcd1602::init(local_timer1_handle, &mut i2c0);
+
+        // A display that's lost its data lines still looks fine until it garbles something
+        defmt::println!("Checking LCD read-back...");
+        if !lcd1602::self_test(local_timer1_handle, &mut i2c0) {
+            defmt::println!("LCD did not store what was written to it!");
+            log_error("LCD READBACK FAILED");
+        } else {
+            // Read-back works, so the busy flag can be trusted to time the display
+            lcd1602::calibrate_timing(local_timer1_handle, &mut i2c0);
+        }
     });
 
+    // Refuse to drive anything if the pin assignments don't add up
+    defmt::println!("Validating Board Configuration...");
+    if let Err(e) = board_config::validate().and_then(|_| pwm_manager::validate()) {
+        halt_on_config_error(e, &mut timer0, &mut i2c0);
+    }
+
+    // Configurable inputs can claim any pin the board leaves free
+    let settings = settings::load();
+    // Defaults stand in for corrupt settings, but aren't to be run on until the operator says so
+    let safe_mode = settings::is_corrupt();
+    lcd1602::set_rom(settings.lcd_rom);
+    feed.set_backlash(settings.feed_backlash_steps as u32);
+    cutter.set_frame_rate(settings.servo_rate_hz);
+    if safe_mode {
+        log_error("SETTINGS CORRUPT");
+        cutter.set_enabled(false);
+    }
+    sound::init(&settings);
+    haptic::set(&settings);
+    tuning::init(&settings);
+    if let Err(e) = board_config::validate_inputs(&settings) {
+        halt_on_config_error(e, &mut timer0, &mut i2c0);
+    }
+
+    defmt::println!("Initializing Buzzer...");
+    let speaker_pin = board
+        .speaker_pin
+        .into_push_pull_output(Level::Low)
+        .degrade();
+    let buzzer_pwm = pwm_manager::claim::<PWM1>(pwm::Channel::C0, Consumer::Buzzer, Usage::Whole)
+        .unwrap_or_else(|e| halt_on_config_error(e, &mut timer0, &mut i2c0));
+    let mut buzzer = Buzzer::new(board.PWM1, buzzer_pwm, speaker_pin);
+
+    // A second display is optional, and only ever mirrors progress
+    defmt::println!("Looking for Status Panel...");
+    status_panel::init(&mut timer0, &mut i2c0);
+
     defmt::println!("Initializing 3x4 Matrix Keypad...");
-    keypad::init(&mut i2c0);
+    let keypad_wiring = keypad::Wiring {
+        active_low: settings.keypad_active_low,
+        row_pullups: settings.keypad_row_pullups,
+    };
+    keypad::set_stuck_key_timing(&settings);
+    if !keypad::init(keypad_wiring, &mut i2c0) {
+        // Carry on without it, e.g. when bench-testing the mechanics, taking jobs over serial instead
+        defmt::println!("Keypad not responding, falling back to serial control");
+        log_error("KEYPAD MISSING");
+
+        // With no LCD either there's nobody to tell but the console, which is told once it's up
+        if headless::detect(&mut i2c0) {
+            defmt::println!("LCD not responding either, running headless");
+        } else {
+            lcd1602::clear_display(&mut timer0, &mut i2c0);
+            lcd1602::write_string("NO KEYPAD FOUND\nUSE SERIAL", &mut timer0, &mut i2c0);
+            timer0.delay_ms(NO_KEYPAD_DUR_IN_MS);
+        }
+    }
 
-    defmt::println!("Initializing Cutter Servo...");
-    let pwm_output_pin = board.pins.p0_09.into_push_pull_output(Level::Low).degrade();
-    let cutter = Servo::new(board.PWM0, microbit::hal::pwm::Channel::C0, pwm_output_pin);
+    // '*' held at power-on asks for the greeting whatever the startup setting, so it's let go of here
+    // rather than being taken for a stuck key
+    let star_at_power_on = keypad::is_star_held(&mut i2c0);
+    if star_at_power_on {
+        defmt::println!("'*' held at power-on, waiting for release...");
+        lcd1602::clear_display(&mut timer0, &mut i2c0);
+        lcd1602::write_string("FULL STARTUP\nRELEASE * KEY", &mut timer0, &mut i2c0);
+        keypad::wait_for_release(&mut timer0, &mut i2c0);
+        lcd1602::clear_display(&mut timer0, &mut i2c0);
+    }
 
-    // Store the peripheral handles in RefCells, so interrupts and main thread can use them
+    // Don't let a stuck key feed phantom presses into the input loop
+    defmt::println!("Checking for stuck keys...");
+    if keypad::is_key_stuck(&mut timer0, &mut i2c0) {
+        defmt::println!("Keypad key stuck! Waiting for release...");
+        buzzer.error(&mut timer0);
+        lcd1602::clear_display(&mut timer0, &mut i2c0);
+        lcd1602::write_string("CHECK KEYPAD\nKEY STUCK", &mut timer0, &mut i2c0);
+        keypad::wait_for_release(&mut timer0, &mut i2c0);
+        lcd1602::clear_display(&mut timer0, &mut i2c0);
+    }
+
+    // Initialize the TWIM1 (I2C) controller on the internal bus, and the onboard sensors on it
+    defmt::println!("Initializing Onboard Sensors...");
+    let mut i2c1 = internal::init(extra_periphs.TWIM1, twim::Pins::from(board.i2c_internal));
+    let onboard_sensors = internal::init_sensors(&mut i2c1);
+    defmt::println!("Onboard sensors present: {}", onboard_sensors);
+    if !onboard_sensors.accelerometer {
+        defmt::println!("Accelerometer not responding, vibration monitoring disabled");
+    }
+    if onboard_sensors.magnetometer {
+        match magnetometer::read(&mut i2c1) {
+            Ok(sample) => defmt::println!("Magnetometer reading: {}", sample),
+            Err(e) => defmt::println!("Magnetometer read failed: {}", e),
+        }
+    }
+
+    defmt::println!("Initializing Analog Inputs...");
+    let mut analog = Analog::new(board.SAADC, Oversample::Over16x);
+    defmt::println!("Supply voltage: {}mV", analog.read_mv(AnalogInput::Vdd));
+
+    defmt::println!("Initializing Door Interlock...");
+    let interlock_pin = board.pins.p0_02.into_pullup_input().degrade(); // P0
+    let interlock = Interlock::new(interlock_pin, DOOR_INTERLOCK_FITTED);
+
+    defmt::println!("Initializing Configurable Inputs...");
+    let inputs = Inputs::new(&settings);
+
+    defmt::println!("Initializing Measuring Encoder...");
+    let encoder_pin_a = board.pins.p0_17.into_pullup_input().degrade(); // P13
+    let encoder_pin_b = board.pins.p0_01.into_pullup_input().degrade(); // P14
+    let encoder = Qdec::new(extra_periphs.QDEC, encoder_pin_a, encoder_pin_b);
+
+    defmt::println!("Initializing LED Matrix...");
+    let led_matrix = Display::new(board.display_pins);
+
+    defmt::println!("Initializing Persistent Storage...");
+    let mut nvmc = storage::init(extra_periphs.NVMC);
+    settings::upgrade(&mut nvmc);
+
+    // Journal why the machine came up, clearing the reasons so they don't pile up across resets
+    let resetreas = extra_periphs.POWER.resetreas.read().bits();
+    extra_periphs
+        .POWER
+        .resetreas
+        .write(|w| unsafe { w.bits(resetreas) });
+    defmt::println!("Reset cause: {}", journal::reset_cause(resetreas));
+
+    // Ready before the reset is journalled, so builds mirroring the journal to it log that too
+    defmt::println!("Initializing Serial Port...");
+    let serial = SerialPort::new(board.UARTE0, board.uart.into());
+    cortex_interrupt::free(|cs| SERIAL_HANDLE.borrow(cs).replace(Some(serial)));
+
+    journal::record(
+        journal::Kind::Reset,
+        resetreas as u16,
+        journal::reset_cause(resetreas),
+    );
+    journal::flush(&mut nvmc);
+
+    // Hand the peripherals over to idle(), which takes them out again as it starts
     cortex_interrupt::free(|cs| TIMER0_HANDLE.borrow(cs).replace(Some(timer0)));
     cortex_interrupt::free(|cs| I2C0_HANDLE.borrow(cs).replace(Some(i2c0)));
+    cortex_interrupt::free(|cs| I2C1_HANDLE.borrow(cs).replace(Some(i2c1)));
+    cortex_interrupt::free(|cs| ONBOARD_SENSORS.borrow(cs).set(onboard_sensors));
+    cortex_interrupt::free(|cs| STAR_AT_POWER_ON.borrow(cs).set(star_at_power_on));
     cortex_interrupt::free(|cs| CUTTER_HANDLE.borrow(cs).replace(Some(cutter)));
+    cortex_interrupt::free(|cs| FEED_HANDLE.borrow(cs).replace(Some(feed)));
+    cortex_interrupt::free(|cs| ANALOG_HANDLE.borrow(cs).replace(Some(analog)));
+    cortex_interrupt::free(|cs| INTERLOCK_HANDLE.borrow(cs).replace(Some(interlock)));
+    cortex_interrupt::free(|cs| INPUTS_HANDLE.borrow(cs).replace(Some(inputs)));
+    cortex_interrupt::free(|cs| ENCODER_HANDLE.borrow(cs).replace(Some(encoder)));
+    cortex_interrupt::free(|cs| BUZZER_HANDLE.borrow(cs).replace(Some(buzzer)));
+    cortex_interrupt::free(|cs| LED_MATRIX_HANDLE.borrow(cs).replace(Some(led_matrix)));
+    cortex_interrupt::free(|cs| SETTINGS_HANDLE.borrow(cs).replace(Some(settings)));
+    cortex_interrupt::free(|cs| NVMC_HANDLE.borrow(cs).replace(Some(nvmc)));
 }
 
 fn idle() -> ! {
-    cortex_interrupt::free(|cs| {
-        // Capture shared peripheral handles locally
-        let mut local_timer0_handle_ref = TIMER0_HANDLE.borrow(cs).borrow_mut();
-        let timer0 = local_timer0_handle_ref.as_mut().unwrap();
-        let mut local_i2c0_handle_ref = I2C0_HANDLE.borrow(cs).borrow_mut();
-        let i2c0 = local_i2c0_handle_ref.as_mut().unwrap();
-        let mut local_cutter_handle_ref = CUTTER_HANDLE.borrow(cs).borrow_mut();
-        let cutter = local_cutter_handle_ref.as_mut().unwrap();
-
-        // Display greeting
-        lcd1602::display_greeting(timer0, i2c0);
-        timer0.delay_ms(GREETING_DUR_IN_MS);
-
-        // Input Loop
-        let mut cut_length;
-        let mut num_cuts;
+    // Take the handles init() left, each in a critical section of its own, so the program runs with
+    // interrupts enabled and the clock's handler keeps time as it happens
+    let mut timer0 = take_handle(&TIMER0_HANDLE).unwrap();
+    let timer0 = &mut timer0;
+    let mut i2c0 = take_handle(&I2C0_HANDLE).unwrap();
+    let i2c0 = &mut i2c0;
+    let mut i2c1 = take_handle(&I2C1_HANDLE).unwrap();
+    let i2c1 = &mut i2c1;
+    let onboard_sensors = cortex_interrupt::free(|cs| ONBOARD_SENSORS.borrow(cs).get());
+    let star_at_power_on = cortex_interrupt::free(|cs| STAR_AT_POWER_ON.borrow(cs).get());
+    let mut analog = take_handle(&ANALOG_HANDLE).unwrap();
+    let analog = &mut analog;
+    let mut cutter = take_handle(&CUTTER_HANDLE).unwrap();
+    let cutter = &mut cutter;
+    let mut diverter = take_handle(&DIVERTER_HANDLE);
+    let mut diverter = diverter.as_mut();
+    let mut clamp = take_handle(&CLAMP_HANDLE);
+    let mut clamp = clamp.as_mut();
+    let mut feed = take_handle(&FEED_HANDLE).unwrap();
+    let feed = &mut feed;
+    let mut straightener = take_handle(&STRAIGHTENER_HANDLE);
+    let mut straightener = straightener.as_mut();
+    let interlock = &take_handle(&INTERLOCK_HANDLE).unwrap();
+    let mut inputs = take_handle(&INPUTS_HANDLE).unwrap();
+    let inputs = &mut inputs;
+    let mut encoder = take_handle(&ENCODER_HANDLE).unwrap();
+    let encoder = &mut encoder;
+    let mut buzzer = take_handle(&BUZZER_HANDLE).unwrap();
+    let buzzer = &mut buzzer;
+    let mut led_matrix = take_handle(&LED_MATRIX_HANDLE).unwrap();
+    let led_matrix = &mut led_matrix;
+    let mut settings = take_handle(&SETTINGS_HANDLE).unwrap();
+    let settings = &mut settings;
+    let mut nvmc = take_handle(&NVMC_HANDLE).unwrap();
+    let nvmc = &mut nvmc;
+
+    // Presses are timed from here, as the UI starts
+    #[cfg(feature = "input_replay")]
+    replay::init(nvmc);
+    screensaver::arm();
+
+    if headless::is_active() {
+        headless::announce();
+    }
+
+    // Corrupt settings stop the machine here, motors off, until the operator has seen to them,
+    // over the console on headless machines
+    if settings::is_corrupt() {
+        if headless::is_active() {
+            let mut profiles = profiles::load();
+            let limits = job_limits(profiles.active().units);
+            let mut console = console::Context {
+                nvmc: &mut *nvmc,
+                settings: &mut *settings,
+                inputs: &mut *inputs,
+                profiles: &mut profiles,
+                dump: &mut |_, _| Ok(()),
+            };
+            headless::hold_in_safe_mode(timer0, i2c0, &limits, &mut console);
+        } else {
+            safe_mode::run(timer0, i2c0, buzzer, feed, encoder, settings, nvmc);
+        }
+        cutter.set_enabled(true);
+    }
+
+    // Production machines can go straight to work. Holding '*' at power-on comes up the long way
+    // regardless, as that's where the service and settings menus are.
+    let startup = if star_at_power_on {
+        Startup::Greeting
+    } else {
+        settings.startup
+    };
+    defmt::println!("Starting up to {}", startup);
+
+    // Show the splash, which any key cuts short and '*' leaves for the service menu
+    if startup == Startup::Greeting && splash::run(&BootSplash::default(), timer0, i2c0, led_matrix)
+    {
+        let mut hardware = CycleHardware {
+            cutter: &mut *cutter,
+            feed: &mut *feed,
+            diverter: diverter.as_deref_mut(),
+            clamp: clamp.as_deref_mut(),
+            straightener: straightener.as_deref_mut(),
+            interlock,
+            inputs: &mut *inputs,
+            encoder: &mut *encoder,
+            analog: &mut *analog,
+        };
+        service_menu::run(timer0, i2c0, led_matrix, &mut hardware, settings, nvmc);
+    }
+
+    // Wire is fed by hand, so there are no jobs to enter
+    if COUNTER_ONLY {
+        let mut hardware = CycleHardware {
+            cutter: &mut *cutter,
+            feed: &mut *feed,
+            diverter: diverter.as_deref_mut(),
+            clamp: clamp.as_deref_mut(),
+            straightener: straightener.as_deref_mut(),
+            interlock,
+            inputs: &mut *inputs,
+            encoder: &mut *encoder,
+            analog: &mut *analog,
+        };
+        counter::run(timer0, i2c0, buzzer, &mut hardware);
+    }
+
+    // Select the operator profile, which supplies units, feed speed, and the last job
+    let mut profiles = profiles::load();
+    let mut cycle_times = eta::load();
+    report_status(&Status::new(profiles.active().units));
+    if startup == Startup::Greeting && keypad::is_present() {
+        let mut targets = settings_menu::Targets {
+            profiles: &mut profiles,
+            settings: &mut *settings,
+            inputs: &mut *inputs,
+            feed: &mut *feed,
+            cutter: &mut *cutter,
+            nvmc: &mut *nvmc,
+        };
+        select_profile(&mut targets, timer0, i2c0, buzzer);
+    }
+    let units = profiles.active().units;
+    let mut status = Status::new(units);
+    let mut stats = JobStats::default();
+
+    let setup = JobSetup {
+        units,
+        feed_speed_pct: profiles.active().feed_speed_pct as u32,
+        straightener_fitted: straightener.is_some(),
+        big_digits: profiles.active().big_digits,
+        cycle_history: cycle_times.get(profiles.active_index()),
+        stroke: profiles.active().stroke,
+        // Without the history's totals, there's nothing to count the spool down by
+        spool_left: if job_history::ENABLED {
+            settings.spool.left(job_history::totals().wire_mm)
+        } else {
+            None
+        },
+        large_job: settings.large_job,
+    };
+
+    // Input Loop: gather jobs into the queue until the operator starts cutting. Rejecting an entry
+    // still leads to the queue screen, where a cut list can be imported over serial instead, as it
+    // must be with no keypad.
+    let mut queue = JobQueue::new();
+    let mut add_job = startup != Startup::Queue;
+    loop {
+        let last_job = Job::new(
+            profiles.active().last_cut_length,
+            profiles.active().last_num_cuts,
+        );
+        let entered = if add_job && keypad::is_present() {
+            enter_job(
+                &setup,
+                &last_job,
+                queue.wire(units, None),
+                timer0,
+                i2c0,
+                buzzer,
+            )
+        } else {
+            None
+        };
+        if let Some(job) = entered {
+            match queue.push(job) {
+                Ok(()) => {
+                    // Remember this job for the operator's next session
+                    profiles.active_mut().last_cut_length = job.cut_length;
+                    profiles.active_mut().last_num_cuts = job.num_cuts;
+                    profiles::save(&profiles, nvmc);
+                }
+                Err(e) => {
+                    buzzer.error(timer0);
+                    lcd1602::clear_display(timer0, i2c0);
+                    lcd1602::write_string(e.message(), timer0, i2c0);
+                    timer0.delay_ms(ENTRY_ERROR_DUR_IN_MS);
+                }
+            }
+        }
+
+        let mut console = console::Context {
+            nvmc: &mut *nvmc,
+            settings: &mut *settings,
+            inputs: &mut *inputs,
+            profiles: &mut profiles,
+            dump: &mut |out, inputs| {
+                write_machine_state(out, &status, &stats, interlock, inputs, encoder, analog)
+            },
+        };
+        let exit = queue_menu::run(
+            &mut queue,
+            &setup,
+            false,
+            timer0,
+            i2c0,
+            buzzer,
+            &mut console,
+        );
+        add_job = exit == queue_menu::Exit::AddJob;
+        match exit {
+            queue_menu::Exit::AddJob => continue,
+            queue_menu::Exit::SingleCut => {
+                feed.set_enabled(true);
+                let mut hardware = CycleHardware {
+                    cutter: &mut *cutter,
+                    feed: &mut *feed,
+                    diverter: diverter.as_deref_mut(),
+                    clamp: clamp.as_deref_mut(),
+                    straightener: straightener.as_deref_mut(),
+                    interlock,
+                    inputs: &mut *inputs,
+                    encoder: &mut *encoder,
+                    analog: &mut *analog,
+                };
+                single_cut(&setup, &mut hardware, timer0, i2c0, buzzer);
+                feed.set_enabled(false);
+            }
+            queue_menu::Exit::Run => break,
+        }
+    }
+
+    // Cutting Loop
+    screensaver::disarm();
+    let mut job_error: Option<Fault> = None;
+    let mut cutter_duty = DutyTracker::new(CUTTER_DUTY_LIMIT);
+    let mut learned_cycle_times = false;
+    // The feed is only powered while there's a job to hold the wire for
+    feed.set_enabled(true);
+    while let Some(job) = queue.start() {
+        let Job {
+            cut_length,
+            num_cuts,
+            ..
+        } = job;
+        let plan = setup.plan(cut_length);
+        defmt::println!(
+            "Starting job '{}' of {} cuts, planned cycle of {}ms (expecting {}ms): {}",
+            job.label(),
+            num_cuts,
+            plan.duration_ms(),
+            setup.estimate_ms(cut_length),
+            plan.steps()
+        );
+        let mut piece_timer = PieceTimer::new(plan.duration_ms());
+        journal::record(
+            journal::Kind::JobStart,
+            num_cuts.min(u16::MAX as u32) as u16,
+            job.label(),
+        );
+        journal::flush(nvmc);
+
+        status = Status {
+            state: MachineState::Cutting,
+            cut_length,
+            num_cuts,
+            ..Status::new(units)
+        };
+        report_status(&status);
+        let mut engine = Engine::new(job, CUT_RETRIES)
+            .with_scrap(settings.scrap)
+            .with_marking(settings.mark_every)
+            .with_replacements(settings.replace_scrapped)
+            .with_first_piece_check(settings.check_first_piece);
+        draw_cutting_screen(0, num_cuts, engine.bundle(), &stats, timer0, i2c0);
+        status_panel::show_progress(job.label(), engine.progress(), timer0, i2c0);
         loop {
-            // Prompt user for Cut Length
-            defmt::println!("Prompting user for Cut Length...");
-            cut_length = get_user_parameter("CUT LENGTH (in):\n-> ", timer0, i2c0);
-            defmt::println!("User accepted Cut Length of {}", cut_length);
-
-            // Prompt user for Number of Cuts
-            defmt::println!("Prompting user for Number of Cuts...");
-            num_cuts = get_user_parameter("NUMBER OF CUTS:\n-> ", timer0, i2c0);
-            defmt::println!("User accepted Number of Cuts of {}", num_cuts);
-
-            // Present final confirmation
-            defmt::println!("Presenting final confirmation to user...");
-            if !final_confirmation(cut_length, num_cuts, timer0, i2c0) {
-                // User rejected confirmation, return to top of input loop
-                defmt::println!("User rejected confirmation");
+            let mut machine = JobMachine {
+                hardware: CycleHardware {
+                    cutter: &mut *cutter,
+                    feed: &mut *feed,
+                    diverter: diverter.as_deref_mut(),
+                    clamp: clamp.as_deref_mut(),
+                    straightener: straightener.as_deref_mut(),
+                    interlock,
+                    inputs: &mut *inputs,
+                    encoder: &mut *encoder,
+                    analog: &mut *analog,
+                },
+                setup: &setup,
+                timer: &mut *timer0,
+                i2c: &mut *i2c0,
+                vibration_i2c: onboard_sensors.accelerometer.then_some(&mut *i2c1),
+                buzzer: &mut *buzzer,
+                stats: &mut stats,
+                bundle: engine.bundle(),
+                status: &mut status,
+                cutter_duty: &mut cutter_duty,
+                label: job.label(),
+                piece_timer: &mut piece_timer,
+            };
+            let scrapped = engine.scrapped();
+            let state = engine.step(&mut machine);
+            stats.scrapped += engine.scrapped() - scrapped;
+            match state {
+                State::Running
+                | State::Paused
+                | State::Marking
+                | State::Bundled
+                | State::Checking
+                | State::Jammed(_) => {}
+                State::Finished => break,
+                State::Aborted(fault) => {
+                    job_error = Some(fault);
+                    break;
+                }
+            }
+            let Progress { piece, num_cuts } = engine.progress();
+
+            // The encoder still holds the feed just made, which is the first at the job's length.
+            // Once approved, any stop the piece cut before it called for follows.
+            if engine.state() == State::Checking {
+                let measured_mils = MEASURING_WHEEL.counts_to_mils(encoder.position()).max(0);
+                defmt::println!(
+                    "Checking first piece: measured {} mils, commanded {}{}",
+                    measured_mils,
+                    cut_length,
+                    units.label()
+                );
+                status_panel::show_message("CHECKING", job.label(), timer0, i2c0);
+                buzzer.tick(timer0);
+                let mut line = [0; FIRST_PIECE_LEN];
+                lcd1602::clear_display(timer0, i2c0);
+                lcd1602::write_string(
+                    first_piece_line(
+                        measured_mils as u32,
+                        cut_length,
+                        units,
+                        lcd1602::PAGE_WIDTH,
+                        &mut line,
+                    ),
+                    timer0,
+                    i2c0,
+                );
+                lcd1602::write_string("\nLEN OK? #=Y *=N", timer0, i2c0);
+                if await_confirmation(timer0, i2c0) {
+                    defmt::println!("First piece approved, resuming job");
+                    engine.resume();
+                    draw_cutting_screen(piece, num_cuts, engine.bundle(), &stats, timer0, i2c0);
+                    piece_timer.restart();
+                } else {
+                    defmt::println!("First piece rejected");
+                    engine.abort(Fault {
+                        message: "LENGTH REJECTED",
+                        retryable: false,
+                        clearable: false,
+                    });
+                }
                 continue;
-            } else {
-                // User accepted confirmation, break out of input loop
-                defmt::println!("User accepted confirmation");
-                break;
             }
-        }
 
-        // Cutting Loop
-        lcd1602::clear_display(timer0, i2c0);
-        lcd1602::write_string("Cutting...\n00000 / ", timer0, i2c0);
-        lcd1602::write_u32(num_cuts, timer0, i2c0);
-        lcd1602::shift_cursor(lcd1602::Direction::Left, 8, timer0, i2c0);
-        for i in 1..=num_cuts {
-            // Update LCD
-            lcd1602::backspace(5, timer0, i2c0);
-            lcd1602::write_u32(i, timer0, i2c0);
+            if engine.state() == State::Marking {
+                defmt::println!("Waiting for piece {} to be marked", piece);
+                status_panel::show_message("MARKING", job.label(), timer0, i2c0);
+                buzzer.tick(timer0);
+                wait_for_operator("APPLY MARKER", "PRESS #", inputs, timer0, i2c0);
+                defmt::println!("Marking done, resuming job");
+                engine.resume();
+                draw_cutting_screen(piece, num_cuts, engine.bundle(), &stats, timer0, i2c0);
+                piece_timer.restart();
+                continue;
+            }
 
-            // Perform a single cut
-            cutter.set_duty(12.0);
-            timer0.delay_ms(CUT_CYCLE_TIME_MS);
-            cutter.set_duty(3.0);
+            // The screen keeps counting the whole job through the bundles, under the bundle count
+            if let (State::Bundled, Some(bundle)) = (engine.state(), engine.bundle()) {
+                defmt::println!(
+                    "Bundle {} of {} done after piece {}, waiting for it to be taken",
+                    bundle.number,
+                    bundle.count,
+                    piece
+                );
+                status_panel::show_message("BUNDLED", job.label(), timer0, i2c0);
+                buzzer.tick(timer0);
+                let mut label = [0; BUNDLE_LABEL_LEN];
+                wait_for_operator(
+                    bundle.label(&mut label),
+                    "REMOVE, PRESS #",
+                    inputs,
+                    timer0,
+                    i2c0,
+                );
+                defmt::println!("Bundle taken, resuming job");
+                engine.resume();
+                draw_cutting_screen(piece, num_cuts, engine.bundle(), &stats, timer0, i2c0);
+                piece_timer.restart();
+                continue;
+            }
+
+            // The piece being made when the jam or e-stop struck is scrap; once the operator has
+            // cleared it, the machine is re-zeroed and the wire fed afresh for the next one
+            if let State::Jammed(fault) = engine.state() {
+                defmt::println!(
+                    "Stopped after piece {} ({}), {} scrapped so far",
+                    piece,
+                    fault.message,
+                    engine.scrapped()
+                );
+                log_error(fault.message);
+                status.error = Some(fault.message);
+                report_status(&status);
+                let title = if fault.retryable { "JAMMED" } else { "STOPPED" };
+                status_panel::show_message(title, fault.message, timer0, i2c0);
+                buzzer.error(timer0);
+                wait_for_operator(fault.message, "CLEAR, PRESS #", inputs, timer0, i2c0);
+                recovery::run(
+                    &mut CycleHardware {
+                        cutter: &mut *cutter,
+                        feed: &mut *feed,
+                        diverter: diverter.as_deref_mut(),
+                        clamp: clamp.as_deref_mut(),
+                        straightener: straightener.as_deref_mut(),
+                        interlock,
+                        inputs: &mut *inputs,
+                        encoder: &mut *encoder,
+                        analog: &mut *analog,
+                    },
+                    timer0,
+                    i2c0,
+                );
+                defmt::println!("Fault cleared, resuming job");
+                status.error = None;
+                engine.resume();
+                draw_cutting_screen(piece, num_cuts, engine.bundle(), &stats, timer0, i2c0);
+                piece_timer.restart();
+                continue;
+            }
+
+            // '*' and '#' together redo the LCD setup, for when noise has scrambled it mid-job
+            if keypad::is_chord_held(i2c0) {
+                defmt::println!("LCD reset requested during job");
+                lcd1602::reinit(timer0, i2c0);
+                keypad::wait_for_release(timer0, i2c0);
+                draw_cutting_screen(piece, num_cuts, engine.bundle(), &stats, timer0, i2c0);
+                piece_timer.restart();
+                continue;
+            }
 
-            // Allow time for wire feed
-            timer0.delay_ms(WIRE_FEED_TIME_MS);
+            // '0' (or a pedal set to pause) between pieces pauses the job and opens the queue, so
+            // jobs waiting behind this one can be rearranged, and '4'/'6' pan between the progress
+            // and stats pages. A pedal pause mid-feed has already paused it, with the rest of the
+            // feed made once it's resumed.
+            let key = keypad::scan(timer0, i2c0);
+            let pedal_pause = pedal_pause_mid_job();
+            match key {
+                _ if pedal_pause || key == Some(Key::Zero) || engine.state() == State::Paused => {
+                    engine.pause();
+                    status_panel::show_message("PAUSED", job.label(), timer0, i2c0);
+                    let mut console = console::Context {
+                        nvmc: &mut *nvmc,
+                        settings: &mut *settings,
+                        inputs: &mut *inputs,
+                        profiles: &mut profiles,
+                        dump: &mut |out, inputs| {
+                            write_machine_state(
+                                out, &status, &stats, interlock, inputs, encoder, analog,
+                            )
+                        },
+                    };
+                    queue_menu::run(&mut queue, &setup, true, timer0, i2c0, buzzer, &mut console);
+                    engine.resume();
+                    draw_cutting_screen(piece, num_cuts, engine.bundle(), &stats, timer0, i2c0);
+                    piece_timer.restart();
+                }
+                Some(Key::Four) => lcd1602::show_page(0, timer0, i2c0),
+                Some(Key::Six) => lcd1602::show_page(1, timer0, i2c0),
+                _ => {}
+            }
+        }
+
+        let cut = engine.progress().piece;
+        journal::record(
+            journal::Kind::JobEnd,
+            cut.min(u16::MAX as u32) as u16,
+            job.label(),
+        );
+        journal::flush(nvmc);
+        job_history::record(
+            job_history::Record::new(
+                &job,
+                units,
+                cut,
+                engine.scrapped(),
+                wire_used(&engine, units, settings.scrap, job_error.is_none()),
+            ),
+            nvmc,
+        );
+        if job_error.is_some() {
+            break;
+        }
+        if settings.print_labels {
+            print_label(&Label {
+                cut_length,
+                units,
+                count: cut,
+                date: sound::date(),
+                preset: profiles.active().name(),
+                job: job.label(),
+            });
+        }
+        queue.finish();
+
+        // Learn from how the job really ran, for the next one's estimate
+        let history = cycle_times.get_mut(profiles.active_index());
+        if history.record(piece_timer.sample()) {
+            defmt::println!(
+                "Pieces now expected to take {}/1000 of plan",
+                history.scale_permille
+            );
+            learned_cycle_times = true;
         }
+    }
+    feed.set_enabled(false);
+    if learned_cycle_times {
+        eta::save(&cycle_times, nvmc);
+    }
 
+    if let Some(e) = job_error {
+        // Don't leave the straightener pushing wire into a halted machine
+        if let Some(straightener) = straightener {
+            straightener.set_running(false);
+        }
+        if let Some(clamp) = clamp {
+            clamp.move_to(CLAMP_RELEASE);
+        }
+        latency_audit::safe_state_reached();
+
+        // Fail state: blade is already open, so report the error and stop
+        log_error(e.message);
+        status.state = MachineState::Halted;
+        status.error = Some(e.message);
+        report_status(&status);
+        buzzer.error(timer0);
+        haptic::play(haptic::Cue::JobDone, timer0);
+        lcd1602::clear_display(timer0, i2c0);
+        lcd1602::write_string("ERROR: JOB HALTED\n", timer0, i2c0);
+        status_panel::show_message("HALTED", e.message, timer0, i2c0);
+        lcd1602::write_string(e.message, timer0, i2c0);
+    } else {
+        status.state = MachineState::Finished;
+        report_status(&status);
+        buzzer.completion_melody(profiles.active().completion_melody, timer0);
+        haptic::play(haptic::Cue::JobDone, timer0);
         lcd1602::clear_display(timer0, i2c0);
         lcd1602::write_string("Finished Cutting\nWoohoo! <3", timer0, i2c0);
-    });
+        status_panel::show_message("FINISHED", "", timer0, i2c0);
+    }
+    latency_audit::report();
+
+    // Without the operator's LCD, the matrix is the only place to say how the job ended
+    if !lcd1602::Lcd::OPERATOR.is_present(i2c0) {
+        let message = job_error.map_or("DONE", |e| e.message);
+        led_matrix::scroll_text(message, timer0, led_matrix);
+    }
+    timer0.delay_ms(FINISHED_DUR_IN_MS);
+    screensaver::arm();
+
+    stats.display(timer0, i2c0);
 
     defmt::println!("Entering Idle loop");
+    let mut console = console::Context {
+        nvmc,
+        settings,
+        inputs,
+        profiles: &mut profiles,
+        dump: &mut |out, inputs| {
+            write_machine_state(out, &status, &stats, interlock, inputs, encoder, analog)
+        },
+    };
     loop {
-        cortex_interrupt::free(|cs| {
-            // Capture shared peripheral handles locally
-            let mut local_timer0_handle_ref = TIMER0_HANDLE.borrow(cs).borrow_mut();
-            let timer0 = local_timer0_handle_ref.as_mut().unwrap();
-            timer0.delay_ms(500_u32);
-        });
+        // Keep dashboards up to date with how the last job ended
+        report_status(&status);
+
+        // Left long enough, the display blanks until a key brings it back
+        screensaver::poll(timer0, i2c0);
+        let _ = keypad::scan(timer0, i2c0);
+
+        // Listen in between, so a halted machine can still be inspected remotely
+        for _ in 0..IDLE_REPORT_INTERVAL_IN_MS * 1000 / console::POLL_WINDOW_IN_US {
+            console::poll(
+                timer0,
+                i2c0,
+                &mut queue,
+                &setup.limits(),
+                true,
+                &mut console,
+            );
+        }
     }
 }
 
@@ -204,6 +1608,11 @@ fn idle() -> ! {
 //  Helper Functions
 ///////////////////////////////////////////////////////////////////////////////
 
+// Move a handle out of its static, for the main thread to own from then on
+fn take_handle<T>(handle: &Mutex<RefCell<Option<T>>>) -> Option<T> {
+    cortex_interrupt::free(|cs| handle.borrow(cs).take())
+}
+
 fn init_1s_timer<T: timer::Instance>(instance: T) -> Timer<T> {
     // Create the Timer object
     let mut timer_device = Timer::new(instance);
@@ -217,11 +1626,8 @@ fn init_1s_timer<T: timer::Instance>(instance: T) -> Timer<T> {
     // [2022-05-15] Don't need to set prescaler or bit mode because microbit crate
     // currently hardcodes these to 1MHz and 32bit mode
 
-    // Enable and unmask the interrupt
+    // Enable the interrupt; it's unmasked in the NVIC by irq::unmask() once there's a handler ready
     timer_device.enable_interrupt();
-    unsafe {
-        NVIC::unmask(Interrupt::TIMER1);
-    }
 
     // [2022-05-15] microbit crate currently does not expose the SHORTS register space
     // Cannot configure timer to auto-reset on reaching the specified tick count
@@ -232,51 +1638,132 @@ fn init_1s_timer<T: timer::Instance>(instance: T) -> Timer<T> {
     timer_device
 }
 
-fn get_user_parameter<T: timer::Instance, U: twim::Instance>(
+// Choose the operator, or change the settings with '0', which include the chosen operator's profile
+fn select_profile<T: timer::Instance, U: twim::Instance, V: pwm::Instance>(
+    targets: &mut settings_menu::Targets,
+    timer: &mut Timer<T>,
+    i2c: &mut Twim<U>,
+    buzzer: &mut Buzzer<V>,
+) {
+    loop {
+        lcd1602::clear_display(timer, i2c);
+        lcd1602::write_string("> ", timer, i2c);
+        lcd1602::write_string(targets.profiles.active().name(), timer, i2c);
+        lcd1602::write_string("\n1-4 0=SET #=OK", timer, i2c);
+
+        // Wait for a key that changes what's displayed
+        loop {
+            match keypad::scan_idle(timer, i2c) {
+                Some(Key::Pound) => {
+                    defmt::println!(
+                        "Operator profile {} selected",
+                        targets.profiles.active_index() + 1
+                    );
+                    profiles::save(targets.profiles, targets.nvmc);
+                    return;
+                }
+                Some(Key::Zero) => {
+                    settings_menu::run(targets, timer, i2c, buzzer);
+                    break;
+                }
+                Some(key) => match key.digit() {
+                    Some(digit @ 1..=4) => {
+                        targets.profiles.select(digit as usize - 1);
+                        break;
+                    }
+                    _ => continue,
+                },
+                None => continue,
+            }
+        }
+    }
+}
+
+// Prompt for a number on the keypad. With a `big_label`, the entry fills the display in digits two
+// rows tall, followed by the label, in place of the prompt.
+fn get_user_parameter<T: timer::Instance, U: twim::Instance, V: pwm::Instance>(
     prompt: &str,
+    mut entry: NumberEntry,
+    big_label: Option<&str>,
+    preview_units: Option<Units>,
     timer: &mut Timer<T>,
     i2c: &mut Twim<U>,
+    buzzer: &mut Buzzer<V>,
 ) -> u32 {
-    lcd1602::clear_display(timer, i2c);
-    lcd1602::write_string(prompt, timer, i2c);
+    // The keys are hinted at until the first is pressed, for operators yet to accept an entry
+    let mut hinting = big_label.is_none();
+    draw_user_parameter(
+        prompt,
+        &entry,
+        big_label,
+        preview_units,
+        hinting,
+        timer,
+        i2c,
+    );
 
-    let mut user_input: [&str; 12] = [""; 12];
-    let mut user_input_idx = 0;
     loop {
-        if let Some(pressed_key) = keypad::scan(timer, i2c) {
+        if let Some(pressed_key) = keypad::scan_idle(timer, i2c) {
+            if hinting {
+                hinting = false;
+                draw_entry_hint(prompt, &entry, false, timer, i2c);
+            }
+
             // Check for '#', which will parse and accept the input
             if pressed_key == Key::Pound {
-                let mut parsed_input = 0;
-                for (order_of_magnitude, parsed_value) in user_input
-                    .iter()
-                    .rev()
-                    .flat_map(|v| v.parse::<u32>())
-                    .enumerate()
-                {
-                    parsed_input += parsed_value * u32::pow(10, order_of_magnitude as u32);
+                match entry.value() {
+                    Ok(value) => {
+                        lcd1602::set_cursor_style(CursorStyle::Off, timer, i2c);
+                        help::learn(Hint::Entry);
+                        return value;
+                    }
+                    Err(e) => {
+                        defmt::println!("Rejected user input: {}", e);
+                        buzzer.error(timer);
+
+                        // Show the error in place of the prompt, then restore the prompt and input
+                        lcd1602::clear_display(timer, i2c);
+                        lcd1602::write_string("INVALID INPUT:\n", timer, i2c);
+                        lcd1602::write_string(e.message(), timer, i2c);
+                        timer.delay_ms(ENTRY_ERROR_DUR_IN_MS);
+
+                        draw_user_parameter(
+                            prompt,
+                            &entry,
+                            big_label,
+                            preview_units,
+                            false,
+                            timer,
+                            i2c,
+                        );
+                    }
                 }
 
-                return parsed_input;
+                continue;
             }
             // Check for '*', which acts as a backspace key
             if pressed_key == Key::Star {
                 //OPT: Beep if input is empty?
                 // Don't allow backspace if input is empty
-                if user_input_idx > 0 {
-                    lcd1602::backspace(1, timer, i2c);
-                    user_input_idx -= 1;
-                    user_input[user_input_idx] = "";
+                if entry.pop() {
+                    match big_label {
+                        Some(label) => lcd1602::write_big_digits(entry.as_str(), label, timer, i2c),
+                        None => lcd1602::backspace(1, timer, i2c),
+                    }
+                    draw_unit_preview(prompt, &entry, big_label, preview_units, timer, i2c);
                 }
 
                 continue;
             }
 
             //OPT: Beep if input is full?
-            // If not at max length, write the key to the LCD and record it in the input array
-            if user_input_idx < MAX_INPUT_CHARS {
-                lcd1602::write_string(pressed_key.into(), timer, i2c);
-                user_input[user_input_idx] = pressed_key.into();
-                user_input_idx += 1;
+            // If not at max length, write the key to the LCD and record it in the entry
+            if entry.push(pressed_key.digit()).is_ok() {
+                match big_label {
+                    Some(label) => lcd1602::write_big_digits(entry.as_str(), label, timer, i2c),
+                    None => lcd1602::write_string(pressed_key.into(), timer, i2c),
+                }
+                draw_unit_preview(prompt, &entry, big_label, preview_units, timer, i2c);
             }
         } else {
             continue;
@@ -284,20 +1771,843 @@ fn get_user_parameter<T: timer::Instance, U: twim::Instance>(
     }
 }
 
+fn draw_user_parameter<T: timer::Instance, U: twim::Instance>(
+    prompt: &str,
+    entry: &NumberEntry,
+    big_label: Option<&str>,
+    preview_units: Option<Units>,
+    hinting: bool,
+    timer: &mut Timer<T>,
+    i2c: &mut Twim<U>,
+) {
+    // The big digits fill the display, so there's no sensible place for the cursor
+    match big_label {
+        Some(label) => {
+            lcd1602::set_cursor_style(CursorStyle::Off, timer, i2c);
+            lcd1602::write_big_digits(entry.as_str(), label, timer, i2c);
+        }
+        None => {
+            lcd1602::clear_display(timer, i2c);
+            lcd1602::write_string(prompt, timer, i2c);
+            lcd1602::write_string(entry.as_str(), timer, i2c);
+            lcd1602::set_cursor_style(CursorStyle::Blinking, timer, i2c);
+        }
+    }
+    if hinting {
+        draw_entry_hint(prompt, entry, true, timer, i2c);
+    } else {
+        draw_unit_preview(prompt, entry, big_label, preview_units, timer, i2c);
+    }
+}
+
+// The entry keys' hint, right-aligned on the second line where the unit preview goes, or blanked again
+// once a key's been pressed. Left out if the operator's used them, or they'd crowd the entry.
+fn draw_entry_hint<T: timer::Instance, U: twim::Instance>(
+    prompt: &str,
+    entry: &NumberEntry,
+    shown: bool,
+    timer: &mut Timer<T>,
+    i2c: &mut Twim<U>,
+) {
+    let entry_end = entry_end(prompt, entry);
+    let width = lcd1602::PAGE_WIDTH.saturating_sub(entry_end + 1);
+    let Some(hint) = help::hint(Hint::Entry, width) else {
+        return;
+    };
+
+    lcd1602::set_position(1, lcd1602::PAGE_WIDTH - hint.len(), timer, i2c);
+    if shown {
+        lcd1602::write_string(hint, timer, i2c);
+    } else {
+        for _ in 0..hint.len() {
+            lcd1602::write_string(" ", timer, i2c);
+        }
+    }
+    lcd1602::set_position(1, entry_end, timer, i2c);
+}
+
+// The length being typed as it reads in the other units, right-aligned after the entry on the second
+// line, to catch a length typed in the wrong ones. The big digits leave no room for it.
+fn draw_unit_preview<T: timer::Instance, U: twim::Instance>(
+    prompt: &str,
+    entry: &NumberEntry,
+    big_label: Option<&str>,
+    preview_units: Option<Units>,
+    timer: &mut Timer<T>,
+    i2c: &mut Twim<U>,
+) {
+    let Some(units) = preview_units.filter(|_| big_label.is_none()) else {
+        return;
+    };
+
+    // Keep a space between the entry and its preview
+    let entry_end = entry_end(prompt, entry);
+    let width = lcd1602::PAGE_WIDTH.saturating_sub(entry_end + 1);
+    let mut buf = [0; PREVIEW_LEN];
+    let preview = match entry.as_str().parse() {
+        Ok(length) => units.preview(length, width, &mut buf),
+        Err(_) => "",
+    };
+
+    lcd1602::set_position(1, entry_end, timer, i2c);
+    for _ in preview.len()..lcd1602::PAGE_WIDTH - entry_end {
+        lcd1602::write_string(" ", timer, i2c);
+    }
+    lcd1602::write_string(preview, timer, i2c);
+    lcd1602::set_position(1, entry_end, timer, i2c);
+}
+
+// The column just after the entry, on the prompt's last line
+fn entry_end(prompt: &str, entry: &NumberEntry) -> usize {
+    prompt.rsplit('\n').next().unwrap_or("").len() + entry.as_str().len()
+}
+
+// Prompt for a job's length and count, starting from `defaults`, and have the operator confirm it
+// knowing what it'll take, with `queued` wire to run ahead of it. None if the operator rejects the job.
+fn enter_job<T: timer::Instance, U: twim::Instance, V: pwm::Instance>(
+    setup: &JobSetup,
+    defaults: &Job,
+    queued: Length,
+    timer: &mut Timer<T>,
+    i2c: &mut Twim<U>,
+    buzzer: &mut Buzzer<V>,
+) -> Option<Job> {
+    let units = setup.units;
+    let limits = setup.limits();
+
+    // Prompt user for Cut Length
+    defmt::println!("Prompting user for Cut Length...");
+    let cut_length_prompt = match units {
+        Units::Inches => "CUT LENGTH (in):\n-> ",
+        Units::Millimeters => "CUT LENGTH (mm):\n-> ",
+    };
+    let cut_length = get_user_parameter(
+        cut_length_prompt,
+        NumberEntry::with_value(
+            limits.min_cut_length,
+            limits.max_cut_length,
+            defaults.cut_length,
+        ),
+        setup.big_digits.then(|| units.label()),
+        Some(units),
+        timer,
+        i2c,
+        buzzer,
+    );
+    defmt::println!("User accepted Cut Length of {}", cut_length);
+
+    // Prompt user for Number of Cuts
+    defmt::println!("Prompting user for Number of Cuts...");
+    let num_cuts = get_user_parameter(
+        "NUMBER OF CUTS:\n-> ",
+        NumberEntry::with_value(limits.min_num_cuts, limits.max_num_cuts, defaults.num_cuts),
+        None,
+        None,
+        timer,
+        i2c,
+        buzzer,
+    );
+    defmt::println!("User accepted Number of Cuts of {}", num_cuts);
+
+    // Prompt user for Pieces per Bundle
+    defmt::println!("Prompting user for Pieces per Bundle...");
+    let pieces_per_bundle = get_user_parameter(
+        "PCS/BUNDLE 0=OFF\n-> ",
+        NumberEntry::with_value(0, num_cuts, defaults.pieces_per_bundle.min(num_cuts)),
+        None,
+        None,
+        timer,
+        i2c,
+        buzzer,
+    );
+    defmt::println!("User accepted Pieces per Bundle of {}", pieces_per_bundle);
+
+    // Present what the job will take, then final confirmation
+    let summary = Summary::new(
+        num_cuts,
+        units.length(cut_length),
+        setup.estimate_ms(cut_length),
+    )
+    .with_spool(setup.spool_left, queued);
+    if !job_summary(&summary, units, timer, i2c) {
+        defmt::println!("User rejected job summary");
+        return None;
+    }
+    defmt::println!("Presenting final confirmation to user...");
+    if !final_confirmation(cut_length, num_cuts, units, timer, i2c) {
+        defmt::println!("User rejected confirmation");
+        return None;
+    }
+    // The wire that decides a job is large is the wire shown for it
+    let wire = units.length(cut_length).times(num_cuts);
+    if setup.large_job.exceeded_by(num_cuts, wire)
+        && !large_job_confirmation(wire, num_cuts, setup.estimate_ms(cut_length), timer, i2c)
+    {
+        defmt::println!("User rejected large job confirmation");
+        return None;
+    }
+
+    defmt::println!("User accepted confirmation");
+    Some(
+        Job::new(cut_length, num_cuts)
+            .with_bundles(pieces_per_bundle)
+            .with_label(defaults.label()),
+    )
+}
+
+// The job's pieces and wire on the top line, and below how long it'll take and what it leaves on the
+// spool. '#' goes on to the final confirmation, '*' rejects the job.
+fn job_summary<T: timer::Instance, U: twim::Instance>(
+    summary: &Summary,
+    units: Units,
+    timer: &mut Timer<T>,
+    i2c: &mut Twim<U>,
+) -> bool {
+    let spool_unit = units.spool_unit().label();
+    defmt::println!(
+        "Job summary: {} pieces, {}{} of wire, {}m estimated, spool after {}",
+        summary.pieces,
+        summary.wire_in(units),
+        spool_unit,
+        summary.minutes(),
+        summary.spool_after
+    );
+
+    lcd1602::clear_display(timer, i2c);
+    lcd1602::write_u32_trimmed(summary.pieces, timer, i2c);
+    lcd1602::write_string("PC ", timer, i2c);
+    lcd1602::write_u32_trimmed(summary.wire_in(units), timer, i2c);
+    lcd1602::write_string(spool_unit, timer, i2c);
+    lcd1602::write_string("\n", timer, i2c);
+    write_duration(summary.minutes(), timer, i2c);
+    if let Some(after) = summary.spool_after {
+        lcd1602::write_string(
+            match after {
+                SpoolAfter::Left(_) => " LEFT ",
+                SpoolAfter::Short(_) => " SHORT ",
+            },
+            timer,
+            i2c,
+        );
+        lcd1602::write_u32_trimmed(after.in_units(units), timer, i2c);
+        lcd1602::write_string(spool_unit, timer, i2c);
+    }
+
+    await_confirmation(timer, i2c)
+}
+
 fn final_confirmation<T: timer::Instance, U: twim::Instance>(
     cut_length: u32,
     num_cuts: u32,
+    units: Units,
     timer: &mut Timer<T>,
     i2c: &mut Twim<U>,
 ) -> bool {
     lcd1602::clear_display(timer, i2c);
     lcd1602::write_u32(cut_length, timer, i2c);
-    lcd1602::write_string("in x ", timer, i2c);
+    lcd1602::write_string(units.label(), timer, i2c);
+    lcd1602::write_string(" x ", timer, i2c);
     lcd1602::write_u32(num_cuts, timer, i2c);
     lcd1602::write_string("\nOK? (#=Y, *=N) ", timer, i2c);
 
+    await_confirmation(timer, i2c)
+}
+
+// Show a configuration error and stop; nothing is safe to drive until the build or settings are fixed
+fn halt_on_config_error<T: timer::Instance, U: twim::Instance>(
+    e: board_config::ConfigError,
+    timer: &mut Timer<T>,
+    i2c: &mut Twim<U>,
+) -> ! {
+    defmt::println!("Board configuration error: {}", e);
+    lcd1602::clear_display(timer, i2c);
+    lcd1602::write_string(e.message(), timer, i2c);
+    lcd1602::write_string("\n", timer, i2c);
+    lcd1602::write_string(e.subsystem(), timer, i2c);
+    loop {
+        cortex_m::asm::wfi();
+    }
+}
+
+// Progress on the first page, with stats on the second for the operator to pan to. The screen is
+// drawn through the LCD's frame, so updates only cost the characters which change.
+fn draw_cutting_screen<T: timer::Instance, U: twim::Instance>(
+    piece: u32,
+    num_cuts: u32,
+    bundle: Option<Bundle>,
+    stats: &JobStats,
+    timer: &mut Timer<T>,
+    i2c: &mut Twim<U>,
+) {
+    lcd1602::clear_display(timer, i2c);
+    let stats_page = |row| Area::line(row, lcd1602::PAGE_WIDTH, lcd1602::PAGE_WIDTH);
+    lcd1602::render(&widgets::Label::new("PEAK VIB      mg"), stats_page(0));
+    lcd1602::render(&widgets::Label::new("WARNINGS"), stats_page(1));
+    update_cutting_screen(Progress { piece, num_cuts }, bundle, stats);
+    lcd1602::draw_frame(timer, i2c);
+}
+
+// Update just the figures that change from piece to piece, ready for the next draw
+fn update_cutting_screen(progress: Progress, bundle: Option<Bundle>, stats: &JobStats) {
+    const TITLE: Area = Area::line(0, 0, lcd1602::PAGE_WIDTH - 1);
+    const FIGURE_WIDTH: usize = numeric::PADDED_WIDTH;
+
+    // A bundle count goes in place of the title, spelled out if it fits and as bare counts if not
+    let mut label = [0; BUNDLE_LABEL_LEN];
+    match bundle.map(|bundle| (bundle, bundle.label(&mut label))) {
+        Some((_, label)) if label.len() <= TITLE.width => {
+            lcd1602::render(&widgets::Label::new(label), TITLE)
+        }
+        Some((bundle, _)) => {
+            lcd1602::render(&widgets::Label::new("BDL"), Area::line(0, 0, 4));
+            lcd1602::render(
+                &Counter::new(bundle.number).of(bundle.count),
+                Area::line(0, 4, TITLE.width - 4),
+            );
+        }
+        None => lcd1602::render(&widgets::Label::new("Cutting..."), TITLE),
+    }
+    lcd1602::render(
+        &Counter::new(progress.piece).of(progress.num_cuts),
+        Area::line(1, 0, lcd1602::PAGE_WIDTH),
+    );
+
+    // Top corner warns of a bus starting to lose transfers, before it loses one that matters
+    lcd1602::render(
+        &widgets::Label::new(i2c::health::overall().glyph()),
+        Area::line(0, lcd1602::PAGE_WIDTH - 1, 1),
+    );
+    lcd1602::render(
+        &Counter::new(stats.peak_vibration_mg),
+        Area::line(0, lcd1602::PAGE_WIDTH + 9, FIGURE_WIDTH),
+    );
+    lcd1602::render(
+        &Counter::new(stats.vibration_warnings),
+        Area::line(1, lcd1602::PAGE_WIDTH + 9, FIGURE_WIDTH),
+    );
+}
+
+// Hold the job for the operator to see to the machine, e.g. to mark the piece just cut, take a
+// bundle away or clear a jam, until '#' or a foot switch set to start
+fn wait_for_operator<T: timer::Instance, U: twim::Instance>(
+    task: &str,
+    prompt: &str,
+    inputs: &mut Inputs,
+    timer: &mut Timer<T>,
+    i2c: &mut Twim<U>,
+) {
+    lcd1602::clear_display(timer, i2c);
+    lcd1602::write_string(task, timer, i2c);
+    lcd1602::write_string("\n", timer, i2c);
+    lcd1602::write_string(prompt, timer, i2c);
+
+    events::clear();
     loop {
-        if let Some(pressed_key) = keypad::scan(timer, i2c) {
+        if keypad::scan(timer, i2c) == Some(Key::Pound) {
+            break;
+        }
+        inputs.poll();
+        if events::next() == Some(Event::Pedal(PedalAction::StartJob)) {
+            break;
+        }
+    }
+}
+
+// One stroke of the blade outside a job, as a foot switch can ask for at the queue screen
+fn single_cut<
+    T: timer::Instance,
+    U: twim::Instance,
+    V: pwm::Instance,
+    C: Axis,
+    F: Axis,
+    D: Axis,
+>(
+    setup: &JobSetup,
+    hardware: &mut CycleHardware<C, F, D>,
+    timer: &mut Timer<T>,
+    i2c: &mut Twim<U>,
+    buzzer: &mut Buzzer<V>,
+) {
+    defmt::println!("Single cut requested");
+    lcd1602::clear_display(timer, i2c);
+    lcd1602::write_string("CUTTING...", timer, i2c);
+
+    let (plan, _) = setup.plan(0).split_at_feed();
+    let result = run_cycle(
+        &plan,
+        hardware,
+        timer,
+        None::<&mut Twim<U>>,
+        &mut |timer, open| show_door(open, timer, i2c),
+        None,
+    );
+    if let Err(e) = result {
+        log_error(e.message());
+        buzzer.error(timer);
+        lcd1602::clear_display(timer, i2c);
+        lcd1602::write_string("ERROR: NOT CUT\n", timer, i2c);
+        lcd1602::write_string(e.message(), timer, i2c);
+        timer.delay_ms(ENTRY_ERROR_DUR_IN_MS);
+    }
+}
+
+// Tell the operator a cycle outside a job is held for the door; cleared once it's shut again
+fn show_door<T: timer::Instance, U: twim::Instance>(
+    open: bool,
+    timer: &mut Timer<T>,
+    i2c: &mut Twim<U>,
+) {
+    lcd1602::clear_display(timer, i2c);
+    if open {
+        lcd1602::write_string("DOOR OPEN\nCLOSE TO RESUME", timer, i2c);
+    }
+}
+
+// Execute a planned cycle, returning the peak vibration while the blade was closed.
+// The cycle is held whenever the door is found open, with `show_door` told as it opens and closes.
+fn run_cycle<T: timer::Instance, U: twim::Instance, C: Axis, F: Axis, D: Axis>(
+    plan: &Plan,
+    hardware: &mut CycleHardware<C, F, D>,
+    timer: &mut Timer<T>,
+    // Internal bus, if the accelerometer is available for vibration monitoring
+    mut vibration_i2c: Option<&mut Twim<U>>,
+    show_door: &mut impl FnMut(&mut Timer<T>, bool),
+    // Where a pedal pause mid-feed records how much of the feed it left, for cycles that can be paused
+    mut stopped_short: Option<&mut Option<u32>>,
+) -> Result<Option<u32>, CutterError> {
+    let CycleHardware {
+        cutter,
+        feed,
+        diverter,
+        clamp,
+        straightener,
+        interlock,
+        inputs,
+        encoder,
+        analog,
+    } = hardware;
+
+    let mut elapsed_ms = 0;
+    let mut blade_closed = false;
+    let mut feeding = false;
+    let mut feed_target = 0;
+    let mut peak_vibration_mg = None;
+
+    latency_audit::restart_input_gap();
+    for step in plan.steps() {
+        // Wait out the time until this step, doing whatever monitoring the current state calls for. A
+        // feed stopped short has nothing left to wait for.
+        let paused = stopped_short.as_deref().is_some_and(Option::is_some);
+        let wait_ms = if feeding && paused {
+            0
+        } else {
+            step.at_ms - elapsed_ms
+        };
+        if wait_ms > 0 {
+            if let (true, Some(i2c)) = (blade_closed, vibration_i2c.as_deref_mut()) {
+                // The stroke is already under way, so it's allowed to finish before pausing
+                let vibration_mg = accelerometer::measure_vibration(wait_ms, timer, i2c)?;
+                peak_vibration_mg = peak_vibration_mg.max(Some(vibration_mg));
+            } else {
+                // Step the feed axis, and drain the encoder often enough that it can't overflow
+                let interval_ms = if feeding {
+                    FEED_POLL_INTERVAL_IN_MS
+                } else {
+                    CUT_STEP_POLL_INTERVAL_IN_MS
+                };
+                let mut waited_ms = 0;
+                while waited_ms < wait_ms {
+                    hold_for_door(interlock, inputs, straightener, timer, show_door);
+                    check_inputs(inputs)?;
+                    if feeding {
+                        // Take a pause mid-feed now rather than once the piece is cut, wherever it
+                        // sits among the presses queued meanwhile
+                        if let (Some(left_mils), true) =
+                            (stopped_short.as_deref_mut(), feed.is_moving())
+                        {
+                            if pedal_pause_mid_job() {
+                                let stop_at = ramp_down_feed(
+                                    feed_target,
+                                    &mut **feed,
+                                    encoder,
+                                    interlock,
+                                    inputs,
+                                    straightener,
+                                    timer,
+                                    show_door,
+                                )?;
+                                let left_steps = (feed_target - stop_at).max(0) as u32;
+                                if left_steps > 0 {
+                                    *left_mils = Some(left_steps * MILS_PER_FEED_STEP);
+                                    break;
+                                }
+                                // Too near the end to stop short, so it's taken once the piece is
+                                // cut, as usual
+                                events::push(Event::Pedal(PedalAction::Pause));
+                            }
+                        }
+                        feed.poll();
+                        encoder.update();
+                    }
+
+                    let delay_ms = interval_ms.min(wait_ms - waited_ms);
+                    timer.delay_ms(delay_ms);
+                    waited_ms += delay_ms;
+                }
+            }
+        }
+        elapsed_ms = step.at_ms;
+        latency_audit::step_due();
+        hold_for_door(interlock, inputs, straightener, timer, show_door);
+
+        match step.action {
+            Action::ClampClose => {
+                if let Some(clamp) = clamp {
+                    clamp.move_to(CLAMP_GRIP);
+                }
+            }
+            Action::ClampOpen => {
+                if let Some(clamp) = clamp {
+                    clamp.move_to(CLAMP_RELEASE);
+                }
+                // The wire relaxes when released, so the feed has play to take up before it moves it
+                feed.take_up_slack();
+            }
+            Action::CutClose => {
+                cutter.move_to(tuning::get(Param::CutClosedDuty) as i32);
+                latency_audit::pwm_updated();
+                blade_closed = true;
+            }
+            Action::CutStage { position } => {
+                cutter.move_to(position as i32);
+                latency_audit::pwm_updated();
+                blade_closed = true;
+            }
+            Action::CutOpen => {
+                // Check the blade made it all the way through before it leaves the closed position
+                let result = verify_cut(analog);
+                cutter.move_to(tuning::get(Param::CutOpenDuty) as i32);
+                blade_closed = false;
+                result?;
+            }
+            Action::FeedStart { distance_mils } => {
+                let feed_steps = Length::new(distance_mils, Unit::Mil)
+                    .per_inch(FEED_STEPS_PER_INCH, Rounding::Down);
+                encoder.reset();
+                feed_target = feed.position() + feed_steps as i32;
+                feed.move_to(feed_target);
+                feeding = true;
+            }
+            Action::FeedStop => {
+                // The plan allows for the feed profile; finish off any steps a slower axis still owes
+                while feed.is_moving() {
+                    hold_for_door(interlock, inputs, straightener, timer, show_door);
+                    check_inputs(inputs)?;
+                    feed.poll();
+                    encoder.update();
+                    timer.delay_ms(FEED_POLL_INTERVAL_IN_MS);
+                }
+                feeding = false;
+                defmt::println!(
+                    "Fed {} mils of wire ({} overflows, {} double transitions)",
+                    MEASURING_WHEEL.counts_to_mils(encoder.position()),
+                    encoder.overflows(),
+                    encoder.double_transitions()
+                );
+            }
+            Action::StraightenerOn | Action::StraightenerOff => {
+                if let Some(straightener) = straightener.as_deref_mut() {
+                    straightener.set_running(step.action == Action::StraightenerOn);
+                }
+            }
+            Action::Divert { bin } => {
+                if let Some(diverter) = diverter {
+                    diverter.move_to(match bin {
+                        Bin::A => DIVERT_POSITION_BIN_A,
+                        Bin::B => DIVERT_POSITION_BIN_B,
+                    });
+                }
+            }
+        }
+    }
+
+    Ok(peak_vibration_mg)
+}
+
+// Bring the feed to rest along its ramp rather than dead, short of `target` if it's far enough off,
+// returning where it stops
+#[allow(clippy::too_many_arguments)]
+fn ramp_down_feed<T: timer::Instance, F: Axis>(
+    target: i32,
+    feed: &mut F,
+    encoder: &mut Qdec,
+    interlock: &Interlock,
+    inputs: &mut Inputs,
+    straightener: &mut Option<&mut Straightener>,
+    timer: &mut Timer<T>,
+    show_door: &mut impl FnMut(&mut Timer<T>, bool),
+) -> Result<i32, CutterError> {
+    // The feed steps once a poll, so that's the speed it's slowing from
+    let profile = feed_profile();
+    let stopping_steps = profile.stopping_steps(FEED_STEPS_PER_SECOND, FEED_STEPS_PER_INCH) as i32;
+    let stop_at = target.min(feed.position() + stopping_steps);
+    feed.move_to(stop_at);
+
+    while feed.is_moving() {
+        hold_for_door(interlock, inputs, straightener, timer, show_door);
+        check_inputs(inputs)?;
+        feed.poll();
+        encoder.update();
+        let steps_left = (stop_at - feed.position()).max(0) as u32;
+        let delay_us = profile.stopping_step_us(steps_left, FEED_STEPS_PER_INCH);
+        timer.delay_us(delay_us.max(FEED_POLL_INTERVAL_IN_MS * 1000));
+    }
+
+    Ok(stop_at)
+}
+
+// Hold the cycle while the door is open, with the straightener stopped so it doesn't pile up wire.
+// The door may be the fixed interlock or any input assigned to it.
+fn hold_for_door<T: timer::Instance>(
+    interlock: &Interlock,
+    inputs: &mut Inputs,
+    straightener: &mut Option<&mut Straightener>,
+    timer: &mut Timer<T>,
+    show_door: &mut impl FnMut(&mut Timer<T>, bool),
+) {
+    let is_closed =
+        |inputs: &Inputs| interlock.is_closed() && !inputs.any_active(InputFunction::Door);
+    if is_closed(inputs) {
+        return;
+    }
+
+    let was_running = straightener.as_ref().is_some_and(|s| s.is_running());
+    if let Some(straightener) = straightener.as_deref_mut() {
+        straightener.set_running(false);
+    }
+
+    show_door(timer, true);
+    while !is_closed(inputs) {
+        timer.delay_ms(KEY_POLL_INTERVAL_IN_MS);
+        inputs.poll();
+    }
+    show_door(timer, false);
+    latency_audit::restart_input_gap();
+
+    if let (true, Some(straightener)) = (was_running, straightener.as_deref_mut()) {
+        straightener.set_running(true);
+    }
+}
+
+// Sample the configurable inputs, failing if any calls for the machine to stop
+fn check_inputs(inputs: &mut Inputs) -> Result<(), CutterError> {
+    inputs.poll();
+    let fault = inputs.fault();
+    latency_audit::inputs_checked(fault.is_some());
+    match fault {
+        Some(function) => Err(CutterError::InputTripped { function }),
+        None => Ok(()),
+    }
+}
+
+// Whether the pedal has asked the running job to pause, taking every press queued since it last looked
+fn pedal_pause_mid_job() -> bool {
+    let presses = events::take_mid_job();
+    if presses.refused_cuts > 0 {
+        // No stroke outside the job's own while it has the blade
+        defmt::println!("{} single cut(s) refused mid-job", presses.refused_cuts);
+    }
+
+    presses.pause
+}
+
+// Write the live state of the machine, a `key value` pair per line, for the console's dump
+fn write_machine_state(
+    out: &mut dyn fmt::Write,
+    status: &Status,
+    stats: &JobStats,
+    interlock: &Interlock,
+    inputs: &mut Inputs,
+    encoder: &mut Qdec,
+    analog: &mut Analog,
+) -> fmt::Result {
+    write!(out, "status ")?;
+    status.write_json_line(&mut &mut *out)?;
+
+    inputs.poll();
+    let door = if interlock.is_closed() && !inputs.any_active(InputFunction::Door) {
+        "closed"
+    } else {
+        "open"
+    };
+    writeln!(out, "door {}", door)?;
+    write!(out, "inputs ")?;
+    for i in 0..settings::MAX_INPUTS {
+        let state = match inputs.state(i) {
+            Some(true) => '1',
+            Some(false) => '0',
+            None => '-',
+        };
+        write!(out, "{}", state)?;
+    }
+    writeln!(out)?;
+    writeln!(out, "events_dropped {}", events::dropped())?;
+    writeln!(out, "encoder_counts {}", encoder.position())?;
+    writeln!(out, "supply_mv {}", analog.read_mv(AnalogInput::Vdd))?;
+
+    writeln!(out, "pieces_cut {}", stats.pieces_cut)?;
+    writeln!(out, "pieces_scrapped {}", stats.scrapped)?;
+    writeln!(out, "vibration_warnings {}", stats.vibration_warnings)?;
+    writeln!(out, "peak_vibration_mg {}", stats.peak_vibration_mg)?;
+    writeln!(out, "encoder_overflows {}", encoder.overflows())?;
+    writeln!(
+        out,
+        "encoder_double_transitions {}",
+        encoder.double_transitions()
+    )?;
+    for event in IsrEvent::ALL {
+        writeln!(out, "{} {}", event.name(), isr_events::count(event))?;
+    }
+    tick_watch::write_report(out)?;
+    latency_audit::write_report(out)?;
+
+    Ok(())
+}
+
+// What a job may ask for, in the given units
+fn job_limits(units: Units) -> JobLimits {
+    JobLimits {
+        min_cut_length: MIN_CUT_LENGTH,
+        max_cut_length: match units {
+            Units::Inches => MAX_CUT_LENGTH_IN,
+            Units::Millimeters => MAX_CUT_LENGTH_MM,
+        },
+        min_num_cuts: MIN_NUM_CUTS,
+        max_num_cuts: MAX_NUM_CUTS,
+    }
+}
+
+// Wire a job used up: its pieces, those scrapped, and the scrap trimmed off either side. The trailer
+// is only trimmed off a job that finished.
+fn wire_used(engine: &Engine, units: Units, scrap: job::Scrap, finished: bool) -> Length {
+    let pieces = engine.progress().piece + engine.scrapped();
+    if pieces == 0 {
+        return Length::ZERO;
+    }
+
+    let trailer_mils = if finished { scrap.trailer_mils } else { 0 };
+    let scrap_mils = scrap.leader_mils.saturating_add(trailer_mils);
+    units
+        .length(engine.job().cut_length)
+        .times(pieces)
+        .plus(Length::new(scrap_mils, Unit::Mil))
+}
+
+// Seconds since boot
+fn uptime_s() -> u32 {
+    cortex_interrupt::free(|cs| {
+        UPTIME_HANDLE
+            .borrow(cs)
+            .borrow()
+            .as_ref()
+            .map_or(0, |rtc| rtc.get_counter() / UPTIME_TICKS_PER_SECOND)
+    })
+}
+
+// Keep an error for the console's dump
+fn log_error(message: &'static str) {
+    cortex_interrupt::free(|cs| {
+        ERROR_LOG
+            .borrow(cs)
+            .borrow_mut()
+            .record(uptime_s(), message);
+    });
+    journal::record(journal::Kind::Error, 0, message);
+}
+
+// Send a status line to any listening dashboard, on builds with the status stream
+#[allow(unused_variables)]
+fn report_status(status: &Status) {
+    #[cfg(feature = "status_stream")]
+    cortex_interrupt::free(|cs| {
+        if let Some(port) = SERIAL_HANDLE.borrow(cs).borrow_mut().as_mut() {
+            status_stream::emit(port, status);
+        }
+    });
+}
+
+// Send a label for a finished job's bundle to the label printer on the serial port
+fn print_label(label: &Label) {
+    cortex_interrupt::free(|cs| {
+        if let Some(port) = SERIAL_HANDLE.borrow(cs).borrow_mut().as_mut() {
+            let _ = label.write(port);
+        }
+    });
+}
+
+// Check the blade reached its closed position, on builds with servo position feedback
+#[allow(unused_variables)]
+fn verify_cut(analog: &mut Analog) -> Result<(), CutterError> {
+    #[cfg(feature = "servo_feedback")]
+    CUTTER_FEEDBACK.check_closed(analog)?;
+
+    Ok(())
+}
+
+fn large_job_confirmation<T: timer::Instance, U: twim::Instance>(
+    wire: Length,
+    num_cuts: u32,
+    cycle_time_ms: u32,
+    timer: &mut Timer<T>,
+    i2c: &mut Twim<U>,
+) -> bool {
+    // Round wire up to the next foot so the estimate is never short
+    let total_wire_ft = wire.to(Unit::Foot, Rounding::Up);
+    let total_minutes = (num_cuts as u64 * cycle_time_ms as u64).div_ceil(MS_PER_MINUTE as u64);
+    let total_minutes = total_minutes.min(u32::MAX as u64) as u32;
+    let hours = total_minutes / MINUTES_PER_HOUR;
+    let minutes = total_minutes % MINUTES_PER_HOUR;
+
+    defmt::println!(
+        "Large job: {} ft of wire, {}h{}m estimated",
+        total_wire_ft,
+        hours,
+        minutes
+    );
+
+    lcd1602::clear_display(timer, i2c);
+    lcd1602::write_string("WIRE: ", timer, i2c);
+    lcd1602::write_u32_trimmed(total_wire_ft, timer, i2c);
+    lcd1602::write_string(" ft\n", timer, i2c);
+    write_duration(total_minutes, timer, i2c);
+    lcd1602::write_string(" #=Y *=N", timer, i2c);
+
+    await_confirmation(timer, i2c)
+}
+
+// A duration as hours and minutes, e.g. "1h05m"
+fn write_duration<T: timer::Instance, U: twim::Instance>(
+    total_minutes: u32,
+    timer: &mut Timer<T>,
+    i2c: &mut Twim<U>,
+) {
+    lcd1602::write_u32_trimmed(total_minutes / MINUTES_PER_HOUR, timer, i2c);
+    lcd1602::write_string("h", timer, i2c);
+    let minutes = total_minutes % MINUTES_PER_HOUR;
+    if minutes < 10 {
+        lcd1602::write_string("0", timer, i2c);
+    }
+    lcd1602::write_u32_trimmed(minutes, timer, i2c);
+    lcd1602::write_string("m", timer, i2c);
+}
+
+// Block until the user presses '#' (accept) or '*' (reject)
+fn await_confirmation<T: timer::Instance, U: twim::Instance>(
+    timer: &mut Timer<T>,
+    i2c: &mut Twim<U>,
+) -> bool {
+    loop {
+        if let Some(pressed_key) = keypad::scan_idle(timer, i2c) {
             if pressed_key == Key::Pound {
                 // User accepted confirmation
                 return true;
@@ -316,17 +2626,18 @@ fn final_confirmation<T: timer::Instance, U: twim::Instance>(
 ///////////////////////////////////////////////////////////////////////////////
 
 #[interrupt]
-fn TIMER1() {
-    // Clear the timer interrupt flag && restart 1s timer
-    cortex_interrupt::free(|cs| {
-        let mut local_timer1_handle_ref = TIMER1_HANDLE.borrow(cs).borrow_mut();
-        let local_timer1_handle = local_timer1_handle_ref.as_mut().unwrap();
-        local_timer1_handle
-            .event_compare_cc0()
-            .write(|w| w.events_compare().not_generated());
+fn RTC1() {
+    clock::on_interrupt();
+}
 
-        local_timer1_handle.start(ONE_SECOND_IN_MHZ);
-    });
+// Any interrupt without a handler of its own. Masked again rather than left to fire forever, and
+// counted, as there's no logging from here.
+#[cortex_m_rt::exception]
+unsafe fn DefaultHandler(irqn: i16) {
+    isr_events::record(IsrEvent::UnhandledInterrupt);
+    if irqn >= 0 {
+        irq::mask_raw(irqn as u16);
+    }
 }
 
 ///////////////////////////////////////////////////////////////////////////////
@@ -0,0 +1,133 @@
+/* * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * *\
+Copyright (C) 2023 CJ McAllister
+    This program is free software; you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation; either version 3 of the License, or
+    (at your option) any later version.
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+    You should have received a copy of the GNU General Public License
+    along with this program; if not, write to the Free Software Foundation,
+    Inc., 51 Franklin Street, Fifth Floor, Boston, MA 02110-1301  USA
+
+\* * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * */
+
+use crate::platform::{
+    hal::{prelude::*, pwm, timer, twim, Timer, Twim},
+    pac::RADIO,
+};
+
+use crate::{
+    analog::{Analog, AnalogInput},
+    axis::Axis,
+    i2c::lcd1602,
+    servo::Servo,
+    stepper::Stepper,
+};
+
+///////////////////////////////////////////////////////////////////////////////
+//  Named Constants
+///////////////////////////////////////////////////////////////////////////////
+
+// Time for supply rails to settle after each change, before measuring
+const SETTLE_TIME_IN_MS: u32 = 1000;
+
+// Rough datasheet/bench figures, for builds without a current meter
+const EST_LCD_MA: u32 = 25; // 1602 module, mostly backlight
+const EST_SERVO_IDLE_MA: u32 = 10; // Hobby servo holding position, unloaded
+const EST_STEPPER_HOLD_MA: u32 = 400; // Holding current, drawn from the motor supply
+const EST_RADIO_IDLE_MA: u32 = 1; // Powered but idle; never started by this firmware
+
+///////////////////////////////////////////////////////////////////////////////
+//  Data Structures
+///////////////////////////////////////////////////////////////////////////////
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, defmt::Format)]
+enum Stage {
+    Baseline,
+    LcdOff,
+    ServoPwmOff,
+    StepperDisabled,
+    RadioOff,
+}
+
+///////////////////////////////////////////////////////////////////////////////
+//  Object Implementations
+///////////////////////////////////////////////////////////////////////////////
+
+impl Stage {
+    // Estimated current saved by this stage, relative to the previous one
+    fn estimated_saving_ma(self) -> u32 {
+        match self {
+            Self::Baseline => 0,
+            Self::LcdOff => EST_LCD_MA,
+            Self::ServoPwmOff => EST_SERVO_IDLE_MA,
+            Self::StepperDisabled => EST_STEPPER_HOLD_MA,
+            Self::RadioOff => EST_RADIO_IDLE_MA,
+        }
+    }
+}
+
+///////////////////////////////////////////////////////////////////////////////
+//  Static Functions
+///////////////////////////////////////////////////////////////////////////////
+
+// Switch subsystems off one at a time, reporting the effect of each over defmt, then restore them.
+// There's no current sense on the board, so savings are estimates; supply voltages are measured,
+// which shows sag on battery builds.
+pub fn run<T: timer::Instance, U: twim::Instance, V: pwm::Instance>(
+    timer: &mut Timer<T>,
+    i2c: &mut Twim<U>,
+    cutter: &mut Servo<V>,
+    feed: &mut Stepper,
+    analog: &mut Analog,
+) {
+    defmt::println!("Starting power audit");
+    lcd1602::clear_display(timer, i2c);
+    lcd1602::write_string("POWER AUDIT\nSEE DEBUG LOG", timer, i2c);
+
+    let mut total_saving_ma = 0;
+    for stage in [
+        Stage::Baseline,
+        Stage::LcdOff,
+        Stage::ServoPwmOff,
+        Stage::StepperDisabled,
+        Stage::RadioOff,
+    ] {
+        match stage {
+            // Only a job powers the feed, so it's powered here for the stage that switches it off
+            Stage::Baseline => feed.set_enabled(true),
+            Stage::LcdOff => lcd1602::power_off(i2c),
+            Stage::ServoPwmOff => cutter.set_enabled(false),
+            Stage::StepperDisabled => feed.set_enabled(false),
+            Stage::RadioOff => radio_power_off(),
+        }
+
+        timer.delay_ms(SETTLE_TIME_IN_MS);
+        total_saving_ma += stage.estimated_saving_ma();
+        defmt::println!(
+            "{}: est. {}mA saved ({}mA total), VDD {}mV, VDDH {}mV",
+            stage,
+            stage.estimated_saving_ma(),
+            total_saving_ma,
+            analog.read_mv(AnalogInput::Vdd),
+            analog.read_mv(AnalogInput::VddHDiv5)
+        );
+    }
+
+    // Bring everything back. The radio stays off, since nothing uses it, and the feed until a job
+    // needs it.
+    defmt::println!("Power audit complete, restoring subsystems");
+    cutter.set_enabled(true);
+    cutter.home();
+    lcd1602::power_on(i2c);
+    lcd1602::init(timer, i2c);
+}
+
+fn radio_power_off() {
+    // SAFETY: The radio is otherwise unused, so nothing else holds its registers
+    let radio = unsafe { &*RADIO::ptr() };
+    radio.power.write(|w| w.power().disabled());
+}
@@ -0,0 +1,39 @@
+/* * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * *\
+Copyright (C) 2023 CJ McAllister
+    This program is free software; you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation; either version 3 of the License, or
+    (at your option) any later version.
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+    You should have received a copy of the GNU General Public License
+    along with this program; if not, write to the Free Software Foundation,
+    Inc., 51 Franklin Street, Fifth Floor, Boston, MA 02110-1301  USA
+
+\* * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * */
+
+use crate::platform::{hal::nvmc::Nvmc, pac::NVMC};
+use crate::storage::{self, Region};
+
+pub use cutter_core::profiles::*;
+
+///////////////////////////////////////////////////////////////////////////////
+//  Static Functions
+///////////////////////////////////////////////////////////////////////////////
+
+// Load profiles from flash, falling back to defaults if none have been saved yet
+pub fn load() -> Profiles {
+    let mut bytes = [0; SERIALIZED_LEN];
+    storage::read(Region::Profiles, &mut bytes);
+
+    Profiles::from_bytes(&bytes).unwrap_or_else(|| {
+        defmt::println!("No saved operator profiles found, using defaults");
+        Profiles::default()
+    })
+}
+
+pub fn save(profiles: &Profiles, nvmc: &mut Nvmc<NVMC>) {
+    storage::write(Region::Profiles, &profiles.to_bytes(), nvmc);
+}
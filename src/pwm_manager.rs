@@ -0,0 +1,211 @@
+/* * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * *\
+Copyright (C) 2023 CJ McAllister
+    This program is free software; you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation; either version 3 of the License, or
+    (at your option) any later version.
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+    You should have received a copy of the GNU General Public License
+    along with this program; if not, write to the Free Software Foundation,
+    Inc., 51 Franklin Street, Fifth Floor, Boston, MA 02110-1301  USA
+
+\* * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * */
+
+use core::cell::{Cell, RefCell};
+
+use cortex_m::interrupt::{self as cortex_interrupt, Mutex};
+
+use crate::board_config::ConfigError;
+use crate::platform::{hal::pwm, pac::Interrupt};
+
+///////////////////////////////////////////////////////////////////////////////
+//  Named Constants
+///////////////////////////////////////////////////////////////////////////////
+
+pub const NUM_INSTANCES: usize = 4;
+const NUM_CHANNELS: usize = 4;
+
+///////////////////////////////////////////////////////////////////////////////
+//  Data Structures
+///////////////////////////////////////////////////////////////////////////////
+
+// Everything in the firmware which generates pulses
+#[allow(dead_code)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, defmt::Format)]
+pub enum Consumer {
+    CutterServo,
+    BenderServo,
+    DiverterServo,
+    ClampServo,
+    Buzzer,
+    Ws2812,
+}
+
+// Counter setup of an instance, which every channel on it runs from
+#[derive(Copy, Clone, Debug, PartialEq, Eq, defmt::Format)]
+pub struct Clock {
+    pub prescaler_div: u16,
+    pub countertop: u16,
+}
+
+// How much of an instance a consumer needs. Anything that changes the period as it runs, or drives
+// the common decoder or its own sequence, can't share its instance with anyone.
+#[allow(dead_code)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, defmt::Format)]
+pub enum Usage {
+    Whole,
+    Channel(Clock),
+}
+
+// Proof of a claim, handed to the consumer's constructor
+#[derive(Copy, Clone, Debug)]
+pub struct Allocation {
+    instance: usize,
+    channel: pwm::Channel,
+}
+
+#[derive(Copy, Clone)]
+struct Slot {
+    owners: [Option<Consumer>; NUM_CHANNELS],
+    // None while the instance is free or held whole
+    clock: Option<Clock>,
+    whole: bool,
+}
+
+static SLOTS: Mutex<RefCell<[Slot; NUM_INSTANCES]>> =
+    Mutex::new(RefCell::new([Slot::FREE; NUM_INSTANCES]));
+
+// First claim refused, kept until the board configuration is checked with the LCD up
+static FIRST_CONFLICT: Mutex<Cell<Option<ConfigError>>> = Mutex::new(Cell::new(None));
+
+///////////////////////////////////////////////////////////////////////////////
+//  Object Implementations
+///////////////////////////////////////////////////////////////////////////////
+
+impl Consumer {
+    // Subsystem name, matching board_config's for the LCD error text
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::CutterServo => "CUTTER SERVO",
+            Self::BenderServo => "BENDER SERVO",
+            Self::DiverterServo => "PIECE CHUTE",
+            Self::ClampServo => "CLAMP",
+            Self::Buzzer => "SPEAKER",
+            Self::Ws2812 => "LED STRIP",
+        }
+    }
+}
+
+impl Allocation {
+    pub fn channel(&self) -> pwm::Channel {
+        self.channel
+    }
+
+    // Index of the claimed instance, 0 for PWM0 and so on
+    pub fn instance(&self) -> usize {
+        self.instance
+    }
+
+    // Whether this claim was made on the given instance
+    pub fn is_for<T: pwm::Instance>(&self) -> bool {
+        instance_index::<T>() == self.instance
+    }
+}
+
+impl Slot {
+    const FREE: Self = Self {
+        owners: [None; NUM_CHANNELS],
+        clock: None,
+        whole: false,
+    };
+
+    // Whoever already holds what the new claim needs, if anyone
+    fn holder(&self, channel: pwm::Channel, usage: Usage) -> Option<Consumer> {
+        let mut owners = self.owners.iter().flatten();
+        match usage {
+            Usage::Whole => owners.next().copied(),
+            Usage::Channel(clock) => {
+                if self.whole || self.clock.is_some_and(|c| c != clock) {
+                    owners.next().copied()
+                } else {
+                    self.owners[usize::from(channel)]
+                }
+            }
+        }
+    }
+}
+
+///////////////////////////////////////////////////////////////////////////////
+//  Static Functions
+///////////////////////////////////////////////////////////////////////////////
+
+// Claim a channel of an instance, or the whole instance, for a consumer. Refused claims are also
+// remembered for validate(), as most are made before there's a display to report them on.
+pub fn claim<T: pwm::Instance>(
+    channel: pwm::Channel,
+    consumer: Consumer,
+    usage: Usage,
+) -> Result<Allocation, ConfigError> {
+    let instance = instance_index::<T>();
+
+    cortex_interrupt::free(|cs| {
+        let mut slots = SLOTS.borrow(cs).borrow_mut();
+        let slot = &mut slots[instance];
+
+        if let Some(holder) = slot.holder(channel, usage) {
+            let e = ConfigError::PwmConflict {
+                first: holder.name(),
+                second: consumer.name(),
+            };
+            defmt::println!(
+                "PWM{} claimed by {} is already held by {}",
+                instance,
+                consumer,
+                holder
+            );
+            let first_conflict = FIRST_CONFLICT.borrow(cs);
+            if first_conflict.get().is_none() {
+                first_conflict.set(Some(e));
+            }
+            return Err(e);
+        }
+
+        match usage {
+            Usage::Whole => {
+                slot.owners = [Some(consumer); NUM_CHANNELS];
+                slot.whole = true;
+            }
+            Usage::Channel(clock) => {
+                slot.owners[usize::from(channel)] = Some(consumer);
+                slot.clock = Some(clock);
+            }
+        }
+
+        Ok(Allocation { instance, channel })
+    })
+}
+
+// Report the first claim refused so far
+pub fn validate() -> Result<(), ConfigError> {
+    match cortex_interrupt::free(|cs| FIRST_CONFLICT.borrow(cs).get()) {
+        Some(e) => Err(e),
+        None => Ok(()),
+    }
+}
+
+///////////////////////////////////////////////////////////////////////////////
+//  Helper Functions
+///////////////////////////////////////////////////////////////////////////////
+
+// Each instance has its own interrupt, which is the only way the HAL lets it be told apart
+fn instance_index<T: pwm::Instance>() -> usize {
+    match T::INTERRUPT {
+        Interrupt::PWM0 => 0,
+        Interrupt::PWM1 => 1,
+        Interrupt::PWM2 => 2,
+        _ => 3,
+    }
+}
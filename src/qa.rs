@@ -0,0 +1,268 @@
+/* * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * *\
+Copyright (C) 2023 CJ McAllister
+    This program is free software; you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation; either version 3 of the License, or
+    (at your option) any later version.
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+    You should have received a copy of the GNU General Public License
+    along with this program; if not, write to the Free Software Foundation,
+    Inc., 51 Franklin Street, Fifth Floor, Boston, MA 02110-1301  USA
+
+\* * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * */
+
+use crate::platform::hal::{prelude::*, timer, twim, Timer, Twim};
+
+use crate::{
+    axis::Axis,
+    cycle_timing, feed_profile,
+    i2c::{
+        keypad::{self, Key},
+        lcd1602,
+    },
+    qdec::Qdec,
+    run_cycle, show_door, CycleHardware, FEED_POLL_INTERVAL_IN_MS, FEED_STEPS_PER_INCH,
+    MEASURING_WHEEL, STRAIGHTENER_TIMING,
+};
+use cutter_core::{
+    motion::Plan,
+    qa::{ErrorHistogram, RandomLengths, NUM_BINS},
+    units::{Length, Rounding, Unit},
+};
+
+///////////////////////////////////////////////////////////////////////////////
+//  Named Constants
+///////////////////////////////////////////////////////////////////////////////
+
+// Errors beyond this are shown clamped, to fit the LCD line
+const MAX_SHOWN_ERROR_MILS: u32 = 9999;
+
+const SUMMARY_DUR_IN_MS: u32 = 3000;
+
+// Random test program: pieces cut, and the span of lengths they're drawn from
+const RANDOM_PIECES: u32 = 20;
+const RANDOM_MIN_MILS: u32 = 500;
+const RANDOM_MAX_MILS: u32 = 12_000;
+
+///////////////////////////////////////////////////////////////////////////////
+//  Static Functions
+///////////////////////////////////////////////////////////////////////////////
+
+// Feed test pieces of 1-9" (chosen by key) and compare the measuring wheel's length against the
+// commanded one, to catch calibration drift before a long run. '0' runs the random test program
+// instead. '*' ends the run with a summary.
+pub fn run<T: timer::Instance, U: twim::Instance, C: Axis, F: Axis, D: Axis>(
+    timer: &mut Timer<T>,
+    i2c: &mut Twim<U>,
+    hardware: &mut CycleHardware<C, F, D>,
+) {
+    defmt::println!("Starting cut-length verification");
+    lcd1602::clear_display(timer, i2c);
+    lcd1602::write_string("QA: 1-9=FEED IN\n0=RANDOM *=DONE", timer, i2c);
+
+    let mut histogram = ErrorHistogram::default();
+    loop {
+        let inches = match keypad::scan(timer, i2c) {
+            Some(Key::Star) => break,
+            Some(Key::Zero) => {
+                run_random(&mut histogram, timer, i2c, hardware);
+                break;
+            }
+            Some(key) => match key.digit() {
+                Some(inches) if inches > 0 => inches as u32,
+                _ => continue,
+            },
+            None => continue,
+        };
+
+        let commanded = Length::new(inches, Unit::Inch);
+        let commanded_mils = commanded.to(Unit::Mil, Rounding::Down);
+        let measured_mils = feed_piece(
+            commanded.per_inch(FEED_STEPS_PER_INCH, Rounding::Down),
+            timer,
+            hardware.feed,
+            hardware.encoder,
+        );
+        record_piece(&mut histogram, commanded_mils, measured_mils, timer, i2c);
+    }
+
+    show_summary(&histogram, timer, i2c);
+}
+
+// Cut a run of pseudo-random lengths spanning the feed envelope, measuring each feed against the
+// encoder. The seed is chosen by key and logged, so the same run can be repeated exactly.
+//
+// Each cycle cuts before it feeds: the first cut trims the stub already out of the machine, and a
+// final cut frees the last piece.
+fn run_random<T: timer::Instance, U: twim::Instance, C: Axis, F: Axis, D: Axis>(
+    histogram: &mut ErrorHistogram,
+    timer: &mut Timer<T>,
+    i2c: &mut Twim<U>,
+    hardware: &mut CycleHardware<C, F, D>,
+) {
+    lcd1602::clear_display(timer, i2c);
+    lcd1602::write_string("RANDOM: SEED 1-9\n*=CANCEL", timer, i2c);
+    let seed = loop {
+        match keypad::scan(timer, i2c) {
+            Some(Key::Star) => return,
+            Some(key) => match key.digit() {
+                Some(seed) if seed > 0 => break seed as u32,
+                _ => continue,
+            },
+            None => continue,
+        }
+    };
+    defmt::println!(
+        "Random QA program: seed {}, {} pieces of {}-{} mils",
+        seed,
+        RANDOM_PIECES,
+        RANDOM_MIN_MILS,
+        RANDOM_MAX_MILS
+    );
+
+    let lengths = RandomLengths::new(seed, RANDOM_MIN_MILS, RANDOM_MAX_MILS);
+    let mut final_cut = None;
+    for commanded_mils in lengths.take(RANDOM_PIECES as usize) {
+        let plan = Plan::cut_cycle(&cycle_timing(), &feed_profile(), commanded_mils);
+        let plan = if hardware.straightener.is_some() {
+            plan.with_straightener(&STRAIGHTENER_TIMING)
+        } else {
+            plan
+        };
+        final_cut = Some(plan.split_at_feed().0);
+
+        if let Err(e) = run_cycle(
+            &plan,
+            hardware,
+            timer,
+            None::<&mut Twim<U>>,
+            &mut |timer, open| show_door(open, timer, i2c),
+            None,
+        ) {
+            report_abort(e.message(), timer, i2c);
+            return;
+        }
+        let measured_mils = MEASURING_WHEEL.counts_to_mils(hardware.encoder.position());
+        record_piece(histogram, commanded_mils, measured_mils, timer, i2c);
+    }
+
+    if let Some(plan) = final_cut {
+        if let Err(e) = run_cycle(
+            &plan,
+            hardware,
+            timer,
+            None::<&mut Twim<U>>,
+            &mut |timer, open| show_door(open, timer, i2c),
+            None,
+        ) {
+            report_abort(e.message(), timer, i2c);
+        }
+    }
+}
+
+// Log and show a measured piece, adding its error to the histogram
+fn record_piece<T: timer::Instance, U: twim::Instance>(
+    histogram: &mut ErrorHistogram,
+    commanded_mils: u32,
+    measured_mils: i32,
+    timer: &mut Timer<T>,
+    i2c: &mut Twim<U>,
+) {
+    let error_mils = measured_mils - commanded_mils as i32;
+    histogram.record(error_mils);
+    defmt::println!(
+        "QA piece {}: commanded {} mils, measured {} mils ({} error)",
+        histogram.count(),
+        commanded_mils,
+        measured_mils,
+        error_mils
+    );
+
+    lcd1602::clear_display(timer, i2c);
+    lcd1602::write_string("CMD  ", timer, i2c);
+    lcd1602::write_u32(commanded_mils, timer, i2c);
+    lcd1602::write_string(" MIL\nMEAS ", timer, i2c);
+    lcd1602::write_u32(measured_mils.max(0) as u32, timer, i2c);
+    lcd1602::write_string(" ", timer, i2c);
+    write_signed(error_mils, timer, i2c);
+}
+
+fn report_abort<T: timer::Instance, U: twim::Instance>(
+    message: &str,
+    timer: &mut Timer<T>,
+    i2c: &mut Twim<U>,
+) {
+    defmt::println!("Random QA program aborted: {}", message);
+    lcd1602::clear_display(timer, i2c);
+    lcd1602::write_string("ABORTED:\n", timer, i2c);
+    lcd1602::write_string(message, timer, i2c);
+    timer.delay_ms(SUMMARY_DUR_IN_MS);
+}
+
+// Feed the given number of steps, returning the length the measuring wheel saw
+fn feed_piece<T: timer::Instance, F: Axis>(
+    steps: u32,
+    timer: &mut Timer<T>,
+    feed: &mut F,
+    encoder: &mut Qdec,
+) -> i32 {
+    encoder.reset();
+    feed.move_to(feed.position() + steps as i32);
+    while feed.is_moving() {
+        feed.poll();
+        encoder.update();
+        timer.delay_ms(FEED_POLL_INTERVAL_IN_MS);
+    }
+
+    MEASURING_WHEEL.counts_to_mils(encoder.position())
+}
+
+fn show_summary<T: timer::Instance, U: twim::Instance>(
+    histogram: &ErrorHistogram,
+    timer: &mut Timer<T>,
+    i2c: &mut Twim<U>,
+) {
+    let (Some(mean_mils), Some((min_mils, max_mils))) =
+        (histogram.mean_mils(), histogram.range_mils())
+    else {
+        return;
+    };
+
+    defmt::println!(
+        "QA summary: {} pieces, mean error {} mils, range {} to {} mils",
+        histogram.count(),
+        mean_mils,
+        min_mils,
+        max_mils
+    );
+    for bin in 0..NUM_BINS {
+        defmt::println!(
+            "  {} mils: {}",
+            ErrorHistogram::bin_centre_mils(bin),
+            histogram.bins()[bin]
+        );
+    }
+
+    lcd1602::clear_display(timer, i2c);
+    lcd1602::write_string("N=", timer, i2c);
+    lcd1602::write_u32_trimmed(histogram.count(), timer, i2c);
+    lcd1602::write_string(" AVG ", timer, i2c);
+    write_signed(mean_mils, timer, i2c);
+    lcd1602::write_string("\n", timer, i2c);
+    write_signed(min_mils, timer, i2c);
+    lcd1602::write_string(" TO ", timer, i2c);
+    write_signed(max_mils, timer, i2c);
+    timer.delay_ms(SUMMARY_DUR_IN_MS);
+}
+
+fn write_signed<T: timer::Instance, U: twim::Instance>(
+    value: i32,
+    timer: &mut Timer<T>,
+    i2c: &mut Twim<U>,
+) {
+    lcd1602::write_string(if value < 0 { "-" } else { "+" }, timer, i2c);
+    lcd1602::write_u32_trimmed(value.unsigned_abs().min(MAX_SHOWN_ERROR_MILS), timer, i2c);
+}
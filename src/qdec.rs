@@ -0,0 +1,131 @@
+/* * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * *\
+Copyright (C) 2023 CJ McAllister
+    This program is free software; you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation; either version 3 of the License, or
+    (at your option) any later version.
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+    You should have received a copy of the GNU General Public License
+    along with this program; if not, write to the Free Software Foundation,
+    Inc., 51 Franklin Street, Fifth Floor, Boston, MA 02110-1301  USA
+
+\* * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * */
+
+use crate::platform::{
+    hal::gpio::{Input, Pin, PullUp},
+    pac::QDEC,
+};
+
+///////////////////////////////////////////////////////////////////////////////
+//  Named Constants
+///////////////////////////////////////////////////////////////////////////////
+
+const TRIGGER_TASK: u32 = 1;
+const EVENT_CLEAR: u32 = 0;
+///////////////////////////////////////////////////////////////////////////////
+//  Data Structures
+///////////////////////////////////////////////////////////////////////////////
+
+// Quadrature decoder for the wire-measurement encoder (and any other feedback encoder).
+// Position is kept in a 32-bit software accumulator, fed from the hardware's 11-bit one.
+pub struct Qdec {
+    qdec: QDEC,
+    _pin_a: Pin<Input<PullUp>>,
+    _pin_b: Pin<Input<PullUp>>,
+    position: i32,
+    overflows: u32,
+    double_transitions: u32,
+}
+
+// Converts encoder counts into distance for a measuring wheel riding on the wire
+#[derive(Copy, Clone, Debug)]
+pub struct MeasuringWheel {
+    // Quadrature counts per wheel revolution, i.e. 4x the encoder's rated PPR
+    pub counts_per_rev: u32,
+    // Wheel circumference, in thousandths of an inch
+    pub circumference_mils: u32,
+}
+
+///////////////////////////////////////////////////////////////////////////////
+//  Object Implementations
+///////////////////////////////////////////////////////////////////////////////
+
+impl Qdec {
+    pub fn new(qdec: QDEC, pin_a: Pin<Input<PullUp>>, pin_b: Pin<Input<PullUp>>) -> Self {
+        qdec.tasks_stop.write(|w| unsafe { w.bits(TRIGGER_TASK) });
+
+        // Connect the phase inputs; no LED output is used
+        qdec.psel.a.write(|w| unsafe { w.bits(pin_a.psel_bits()) });
+        qdec.psel.b.write(|w| unsafe { w.bits(pin_b.psel_bits()) });
+
+        // Fastest sampling, with the debounce filter to reject contact bounce and wire vibration
+        qdec.sampleper.write(|w| w.sampleper()._128us());
+        qdec.dbfen.write(|w| w.dbfen().enabled());
+
+        qdec.events_accof.write(|w| unsafe { w.bits(EVENT_CLEAR) });
+        qdec.enable.write(|w| w.enable().enabled());
+        qdec.tasks_start.write(|w| unsafe { w.bits(TRIGGER_TASK) });
+
+        Self {
+            qdec,
+            _pin_a: pin_a,
+            _pin_b: pin_b,
+            position: 0,
+            overflows: 0,
+            double_transitions: 0,
+        }
+    }
+
+    // Drain the hardware accumulator into the running position. It saturates at -1024..1023 counts,
+    // so this must be called faster than the encoder can produce that many; at the 128us sample
+    // period that's at least every ~130ms.
+    pub fn update(&mut self) {
+        // Capture and clear ACC and ACCDBL atomically
+        self.qdec
+            .tasks_readclracc
+            .write(|w| unsafe { w.bits(TRIGGER_TASK) });
+        let delta = self.qdec.accread.read().bits() as i32;
+        let double_transitions = self.qdec.accdblread.read().bits();
+
+        self.position = self.position.wrapping_add(delta);
+        self.double_transitions += double_transitions;
+
+        // Counts were lost if the accumulator saturated since the last read
+        if self.qdec.events_accof.read().bits() != EVENT_CLEAR {
+            self.qdec
+                .events_accof
+                .write(|w| unsafe { w.bits(EVENT_CLEAR) });
+            self.overflows += 1;
+            defmt::println!("QDEC accumulator overflow, encoder counts were lost");
+        }
+    }
+
+    pub fn position(&mut self) -> i32 {
+        self.update();
+        self.position
+    }
+
+    pub fn reset(&mut self) {
+        self.update();
+        self.position = 0;
+    }
+
+    // Number of times counts were lost to accumulator overflow
+    pub fn overflows(&self) -> u32 {
+        self.overflows
+    }
+
+    // Transitions where both phases changed at once, indicating a too-fast or noisy encoder
+    pub fn double_transitions(&self) -> u32 {
+        self.double_transitions
+    }
+}
+
+impl MeasuringWheel {
+    pub fn counts_to_mils(&self, counts: i32) -> i32 {
+        (counts as i64 * self.circumference_mils as i64 / self.counts_per_rev as i64) as i32
+    }
+}
@@ -0,0 +1,205 @@
+/* * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * *\
+Copyright (C) 2023 CJ McAllister
+    This program is free software; you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation; either version 3 of the License, or
+    (at your option) any later version.
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+    You should have received a copy of the GNU General Public License
+    along with this program; if not, write to the Free Software Foundation,
+    Inc., 51 Franklin Street, Fifth Floor, Boston, MA 02110-1301  USA
+
+\* * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * */
+
+use microbit::hal::{prelude::*, pwm, timer, twim, Timer, Twim};
+
+use cutter_core::{
+    inputs::PedalAction,
+    queue::{JobQueue, QueueError},
+};
+
+use crate::{
+    buzzer::Buzzer,
+    console,
+    events::{self, Event},
+    i2c::{
+        keypad::{self, Key},
+        lcd1602,
+    },
+    screensaver, JobSetup,
+};
+
+///////////////////////////////////////////////////////////////////////////////
+//  Named Constants
+///////////////////////////////////////////////////////////////////////////////
+
+const HELP_DUR_IN_MS: u32 = 1500;
+const ERROR_DUR_IN_MS: u32 = 1500;
+
+///////////////////////////////////////////////////////////////////////////////
+//  Data Structures
+///////////////////////////////////////////////////////////////////////////////
+
+// How the operator left the queue screen
+#[derive(Copy, Clone, PartialEq, Eq)]
+pub enum Exit {
+    // Start cutting, or carry on with the active job
+    Run,
+    // Enter another job before starting
+    AddJob,
+    // Make one cut, then come back
+    SingleCut,
+}
+
+///////////////////////////////////////////////////////////////////////////////
+//  Static Functions
+///////////////////////////////////////////////////////////////////////////////
+
+// Browse and rearrange the queue until the operator leaves with '#', or '*' to add a job. A foot switch
+// does the same as '#', or makes a single cut, as set in the settings.
+// While `running`, the front job is being cut and stays put, and no jobs can be added or cuts made.
+pub fn run<T: timer::Instance, U: twim::Instance, V: pwm::Instance>(
+    queue: &mut JobQueue,
+    setup: &JobSetup,
+    running: bool,
+    timer: &mut Timer<T>,
+    i2c: &mut Twim<U>,
+    buzzer: &mut Buzzer<V>,
+    console: &mut console::Context,
+) -> Exit {
+    defmt::println!("Entering queue screen with {} jobs", queue.len());
+
+    // Show the keys once, they don't fit alongside the job
+    lcd1602::clear_display(timer, i2c);
+    if running {
+        lcd1602::write_string("2/8SEL 1/7MOVE\n5ED 0DL #RESUME", timer, i2c);
+    } else {
+        lcd1602::write_string("2/8SEL 1/7MOVE\n5ED 0DL *ADD #GO", timer, i2c);
+    }
+    timer.delay_ms(HELP_DUR_IN_MS);
+
+    // Only presses made with the queue up count, not any left over from the last job
+    events::clear();
+
+    let mut selected = 0;
+    loop {
+        selected = selected.min(queue.len().saturating_sub(1));
+        draw_job(queue, selected, setup, timer, i2c);
+
+        let result = loop {
+            // Cut lists and settings can be pushed from a computer while the queue is up, and the
+            // queue started from there if there's no keypad
+            match console::poll(timer, i2c, queue, &setup.limits(), running, console) {
+                console::Outcome::Nothing => {}
+                console::Outcome::QueueChanged => break Ok(()),
+                console::Outcome::Run => {
+                    defmt::println!("Leaving queue screen with {} jobs", queue.len());
+                    return Exit::Run;
+                }
+            }
+
+            console.inputs.poll();
+            match events::next() {
+                Some(Event::Pedal(PedalAction::StartJob)) if !queue.is_empty() => {
+                    defmt::println!("Leaving queue screen with {} jobs", queue.len());
+                    return Exit::Run;
+                }
+                Some(Event::Pedal(PedalAction::SingleCut)) if !running => return Exit::SingleCut,
+                _ => {}
+            }
+
+            screensaver::poll(timer, i2c);
+            match keypad::scan(timer, i2c) {
+                Some(Key::Two) => {
+                    selected = selected.saturating_sub(1);
+                    break Ok(());
+                }
+                Some(Key::Eight) => {
+                    selected += 1;
+                    break Ok(());
+                }
+                Some(Key::One) => {
+                    break queue.move_up(selected).map(|()| selected -= 1);
+                }
+                Some(Key::Seven) => {
+                    break queue.move_down(selected).map(|()| selected += 1);
+                }
+                Some(Key::Five) => break edit_job(queue, selected, setup, timer, i2c, buzzer),
+                Some(Key::Zero) => break queue.remove(selected).map(|_| ()),
+                Some(Key::Star) if !running => return Exit::AddJob,
+                Some(Key::Pound) if !queue.is_empty() => {
+                    defmt::println!("Leaving queue screen with {} jobs", queue.len());
+                    return Exit::Run;
+                }
+                _ => continue,
+            }
+        };
+
+        if let Err(e) = result {
+            defmt::println!("Queue change rejected: {}", e);
+            buzzer.error(timer);
+            lcd1602::clear_display(timer, i2c);
+            lcd1602::write_string(e.message(), timer, i2c);
+            timer.delay_ms(ERROR_DUR_IN_MS);
+        }
+    }
+}
+
+// e.g. "J2/3 PANEL-A" over "120in X 40", or "RUNNING" in place of the label for the active job
+fn draw_job<T: timer::Instance, U: twim::Instance>(
+    queue: &JobQueue,
+    index: usize,
+    setup: &JobSetup,
+    timer: &mut Timer<T>,
+    i2c: &mut Twim<U>,
+) {
+    lcd1602::clear_display(timer, i2c);
+
+    let Some(job) = queue.get(index) else {
+        lcd1602::write_string("QUEUE EMPTY\n*ADD OR IMPORT", timer, i2c);
+        return;
+    };
+
+    lcd1602::write_string("J", timer, i2c);
+    lcd1602::write_u32_trimmed(index as u32 + 1, timer, i2c);
+    lcd1602::write_string("/", timer, i2c);
+    lcd1602::write_u32_trimmed(queue.len() as u32, timer, i2c);
+    lcd1602::write_string(" ", timer, i2c);
+    if queue.is_active(index) {
+        lcd1602::write_string("RUNNING", timer, i2c);
+    } else {
+        lcd1602::write_string(job.label(), timer, i2c);
+    }
+
+    lcd1602::write_string("\n", timer, i2c);
+    lcd1602::write_u32_trimmed(job.cut_length, timer, i2c);
+    lcd1602::write_string(setup.units.label(), timer, i2c);
+    lcd1602::write_string(" X ", timer, i2c);
+    lcd1602::write_u32_trimmed(job.num_cuts, timer, i2c);
+}
+
+// Re-enter a job's length and count; it's left as it was if the operator rejects the new values
+fn edit_job<T: timer::Instance, U: twim::Instance, V: pwm::Instance>(
+    queue: &mut JobQueue,
+    index: usize,
+    setup: &JobSetup,
+    timer: &mut Timer<T>,
+    i2c: &mut Twim<U>,
+    buzzer: &mut Buzzer<V>,
+) -> Result<(), QueueError> {
+    let job = *queue.get(index).ok_or(QueueError::NoSuchJob)?;
+
+    // Refuse before prompting, rather than after the operator has typed it all in
+    if queue.is_active(index) {
+        return Err(QueueError::JobActive);
+    }
+
+    let queued = queue.wire(setup.units, Some(index));
+    match crate::enter_job(setup, &job, queued, timer, i2c, buzzer) {
+        Some(edited) => queue.replace(index, edited),
+        None => Ok(()),
+    }
+}
@@ -0,0 +1,109 @@
+/* * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * *\
+Copyright (C) 2023 CJ McAllister
+    This program is free software; you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation; either version 3 of the License, or
+    (at your option) any later version.
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+    You should have received a copy of the GNU General Public License
+    along with this program; if not, write to the Free Software Foundation,
+    Inc., 51 Franklin Street, Fifth Floor, Boston, MA 02110-1301  USA
+
+\* * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * */
+
+// Re-zeroes the machine once a jam or e-stop is cleared, before the job carries on, walking the
+// operator through each stage on the LCD. Nothing is left as the fault found it: the blade is opened,
+// the feed re-referenced where the wire now sits, and the job held until every sensor reads clear.
+
+use cutter_core::recovery::{self, Stage};
+
+use crate::{
+    axis::Axis,
+    error::CutterError,
+    i2c::lcd1602,
+    platform::hal::{prelude::*, timer, twim, Timer, Twim},
+    settings::InputFunction,
+    stepper::Stepper,
+    tuning::{self, Param},
+    CycleHardware, CLAMP_RELEASE, FEED_POLL_INTERVAL_IN_MS,
+};
+
+///////////////////////////////////////////////////////////////////////////////
+//  Static Functions
+///////////////////////////////////////////////////////////////////////////////
+
+pub fn run<T: timer::Instance, U: twim::Instance, C: Axis, D: Axis>(
+    hardware: &mut CycleHardware<C, Stepper, D>,
+    timer: &mut Timer<T>,
+    i2c: &mut Twim<U>,
+) {
+    for stage in Stage::ALL {
+        defmt::println!("Re-zero: {}", stage);
+        show(stage, timer, i2c);
+        match stage {
+            Stage::OpenBlade => {
+                // Nothing may push wire at the blade or hold it while it moves
+                if let Some(straightener) = hardware.straightener.as_deref_mut() {
+                    straightener.set_running(false);
+                }
+                if let Some(clamp) = hardware.clamp.as_deref_mut() {
+                    clamp.move_to(CLAMP_RELEASE);
+                }
+                hardware
+                    .cutter
+                    .move_to(tuning::get(Param::CutOpenDuty) as i32);
+                timer.delay_ms(tuning::get(Param::BladeClearanceMs));
+            }
+            Stage::ReferenceFeed => {
+                // The wire end was put back at the blade by hand, so that's where the feed counts from
+                let feed = &mut *hardware.feed;
+                feed.set_enabled(true);
+                feed.home();
+                while feed.is_moving() {
+                    feed.poll();
+                    timer.delay_ms(FEED_POLL_INTERVAL_IN_MS);
+                }
+                // Handling the wire can leave the drive anywhere in its play
+                feed.take_up_slack();
+                hardware.encoder.reset();
+            }
+            Stage::CheckSensors => loop {
+                let inputs = &mut *hardware.inputs;
+                inputs.poll();
+                let door_closed =
+                    hardware.interlock.is_closed() && !inputs.any_active(InputFunction::Door);
+                let tripped = inputs
+                    .fault()
+                    .map(|function| CutterError::InputTripped { function }.message());
+                match recovery::blocker(door_closed, tripped) {
+                    None => break,
+                    Some(message) => {
+                        defmt::println!("Re-zero held up: {}", message);
+                        crate::wait_for_operator(message, "FIX, PRESS #", inputs, timer, i2c);
+                        show(stage, timer, i2c);
+                    }
+                }
+            },
+        }
+    }
+
+    defmt::println!("Re-zero done");
+}
+
+///////////////////////////////////////////////////////////////////////////////
+//  Helper Functions
+///////////////////////////////////////////////////////////////////////////////
+
+fn show<T: timer::Instance, U: twim::Instance>(
+    stage: Stage,
+    timer: &mut Timer<T>,
+    i2c: &mut Twim<U>,
+) {
+    lcd1602::clear_display(timer, i2c);
+    lcd1602::write_string(stage.heading(), timer, i2c);
+    lcd1602::write_string("\n", timer, i2c);
+    lcd1602::write_string(stage.label(), timer, i2c);
+}
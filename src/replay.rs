@@ -0,0 +1,146 @@
+/* * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * *\
+Copyright (C) 2023 CJ McAllister
+    This program is free software; you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation; either version 3 of the License, or
+    (at your option) any later version.
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+    You should have received a copy of the GNU General Public License
+    along with this program; if not, write to the Free Software Foundation,
+    Inc., 51 Franklin Street, Fifth Floor, Boston, MA 02110-1301  USA
+
+\* * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * */
+
+// Keypad presses recorded since power-on, so an intermittent UI fault an operator runs into can be
+// exported and reproduced press for press on the bench. A session imported over the console is saved
+// to flash and played back in place of the keypad from the next power-on, once only; the keypad goes
+// live again when it runs out.
+
+use core::cell::RefCell;
+
+use cortex_m::interrupt::{self as cortex_interrupt, Mutex};
+use cutter_core::replay::{Event, Player, RecordError, Recording, SERIALIZED_LEN};
+
+use crate::{
+    clock::{self, Instant},
+    i2c::keypad::KeyEvent,
+    platform::{hal::nvmc::Nvmc, pac::NVMC},
+    storage::{self, Region},
+};
+
+///////////////////////////////////////////////////////////////////////////////
+//  Data Structures
+///////////////////////////////////////////////////////////////////////////////
+
+enum Mode {
+    Recording,
+    Replaying(Player),
+}
+
+struct Session {
+    // Presses are timed from here
+    start: Instant,
+    mode: Mode,
+    recording: Recording,
+}
+
+static SESSION: Mutex<RefCell<Option<Session>>> = Mutex::new(RefCell::new(None));
+
+///////////////////////////////////////////////////////////////////////////////
+//  Static Functions
+///////////////////////////////////////////////////////////////////////////////
+
+// Start the session, replaying a saved one if there is one. The clock must already be running.
+pub fn init(nvmc: &mut Nvmc<NVMC>) {
+    let mut bytes = [0; SERIALIZED_LEN];
+    storage::read(Region::Recording, &mut bytes);
+    let (mode, recording) = match Recording::from_bytes(&bytes) {
+        Some(saved) if !saved.is_empty() => {
+            defmt::println!("Replaying {} recorded key presses", saved.len());
+            save(&Recording::new(), nvmc);
+            (Mode::Replaying(Player::new()), saved)
+        }
+        _ => (Mode::Recording, Recording::new()),
+    };
+
+    let session = Session {
+        start: clock::now(),
+        mode,
+        recording,
+    };
+    cortex_interrupt::free(|cs| SESSION.borrow(cs).replace(Some(session)));
+}
+
+pub fn is_replaying() -> bool {
+    cortex_interrupt::free(|cs| {
+        let session_ref = SESSION.borrow(cs).borrow();
+        session_ref
+            .as_ref()
+            .is_some_and(|session| matches!(session.mode, Mode::Replaying(_)))
+    })
+}
+
+// The next replayed press, once it's due. Replayed presses stay in the recording, so live ones
+// following on are recorded after them.
+pub fn next_event() -> Option<KeyEvent> {
+    cortex_interrupt::free(|cs| {
+        let mut session_ref = SESSION.borrow(cs).borrow_mut();
+        let session = session_ref.as_mut()?;
+        let Mode::Replaying(player) = &mut session.mode else {
+            return None;
+        };
+
+        let now = clock::now();
+        let event = player.poll(&session.recording, now.ms_since(session.start));
+        if player.is_finished(&session.recording) {
+            defmt::println!("Replay finished, keypad live");
+            session.mode = Mode::Recording;
+        }
+
+        event.map(|event| KeyEvent {
+            key: event.key,
+            at: now,
+        })
+    })
+}
+
+pub fn record(event: &KeyEvent) {
+    cortex_interrupt::free(|cs| {
+        let mut session_ref = SESSION.borrow(cs).borrow_mut();
+        let Some(session) = session_ref.as_mut() else {
+            return;
+        };
+        if !matches!(session.mode, Mode::Recording) {
+            return;
+        }
+
+        let event = Event {
+            key: event.key,
+            at_ms: event.at.ms_since(session.start),
+        };
+        if session.recording.push(event) == Err(RecordError::Full)
+            && session.recording.dropped() == 1
+        {
+            defmt::println!("Key recording full, later presses won't be kept");
+        }
+    })
+}
+
+// Lend out the session recorded so far, e.g. to export it
+pub fn with_recording<R>(f: impl FnOnce(&Recording) -> R) -> R {
+    cortex_interrupt::free(|cs| {
+        let session_ref = SESSION.borrow(cs).borrow();
+        match session_ref.as_ref() {
+            Some(session) => f(&session.recording),
+            None => f(&Recording::new()),
+        }
+    })
+}
+
+// Keep a recording to replay from the next power-on; an empty one replays nothing
+pub fn save(recording: &Recording, nvmc: &mut Nvmc<NVMC>) {
+    storage::write(Region::Recording, &recording.to_bytes(), nvmc);
+}
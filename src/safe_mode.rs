@@ -0,0 +1,81 @@
+/* * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * *\
+Copyright (C) 2023 CJ McAllister
+    This program is free software; you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation; either version 3 of the License, or
+    (at your option) any later version.
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+    You should have received a copy of the GNU General Public License
+    along with this program; if not, write to the Free Software Foundation,
+    Inc., 51 Franklin Street, Fifth Floor, Boston, MA 02110-1301  USA
+
+\* * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * */
+
+// Safe mode, for when the saved settings fail their checksum at boot. The machine comes up on defaults,
+// which know nothing of how this one is built, so the motors stay off until the operator has either
+// accepted the defaults or recalibrated. Either saves settings which check out, ending safe mode.
+
+use microbit::{
+    hal::{nvmc::Nvmc, pwm, timer, twim, Timer, Twim},
+    pac::NVMC,
+};
+
+use crate::{
+    backlash,
+    buzzer::Buzzer,
+    i2c::{
+        keypad::{self, Key},
+        lcd1602,
+    },
+    qdec::Qdec,
+    settings::{self, Settings},
+    stepper::Stepper,
+};
+
+///////////////////////////////////////////////////////////////////////////////
+//  Static Functions
+///////////////////////////////////////////////////////////////////////////////
+
+// Hold the machine at the warning until the saved settings check out again. Only the feed is needed
+// to recalibrate, so it alone is powered, and only while calibrating. Without a keypad the machine
+// stays here, as it would for a bad board configuration.
+pub fn run<T: timer::Instance, U: twim::Instance, V: pwm::Instance>(
+    timer: &mut Timer<T>,
+    i2c: &mut Twim<U>,
+    buzzer: &mut Buzzer<V>,
+    feed: &mut Stepper,
+    encoder: &mut Qdec,
+    settings: &mut Settings,
+    nvmc: &mut Nvmc<NVMC>,
+) {
+    defmt::println!("Entering safe mode");
+    buzzer.error(timer);
+
+    while settings::is_corrupt() {
+        lcd1602::clear_display(timer, i2c);
+        lcd1602::write_string("SETTINGS CORRUPT\n#=DEFAULTS 5=CAL", timer, i2c);
+
+        loop {
+            match keypad::scan(timer, i2c) {
+                Some(Key::Pound) => {
+                    defmt::println!("Operator accepted default settings");
+                    settings::save(settings, nvmc);
+                    break;
+                }
+                Some(Key::Five) => {
+                    feed.set_enabled(true);
+                    backlash::calibrate(timer, i2c, feed, encoder, settings, nvmc);
+                    feed.set_enabled(false);
+                    break;
+                }
+                _ => continue,
+            }
+        }
+    }
+
+    lcd1602::clear_display(timer, i2c);
+    defmt::println!("Leaving safe mode");
+}
@@ -0,0 +1,76 @@
+/* * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * *\
+Copyright (C) 2023 CJ McAllister
+    This program is free software; you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation; either version 3 of the License, or
+    (at your option) any later version.
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+    You should have received a copy of the GNU General Public License
+    along with this program; if not, write to the Free Software Foundation,
+    Inc., 51 Franklin Street, Fifth Floor, Boston, MA 02110-1301  USA
+
+\* * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * */
+
+// Blanks the LCD once the operator's left the machine waiting on the keypad for a while, kept by an
+// alarm on the clock so nothing has to count down. The next key press brings the display back, and
+// is swallowed rather than answer a prompt the operator couldn't see.
+
+use core::cell::Cell;
+
+use cortex_m::interrupt::{self as cortex_interrupt, Mutex};
+
+use crate::clock::{self, Alarm};
+use crate::i2c::lcd1602;
+use crate::platform::hal::{timer, twim, Timer, Twim};
+
+///////////////////////////////////////////////////////////////////////////////
+//  Named Constants
+///////////////////////////////////////////////////////////////////////////////
+
+const BLANK_AFTER_MS: u32 = 5 * 60 * 1000;
+
+///////////////////////////////////////////////////////////////////////////////
+//  Data Structures
+///////////////////////////////////////////////////////////////////////////////
+
+static BLANKED: Mutex<Cell<bool>> = Mutex::new(Cell::new(false));
+
+///////////////////////////////////////////////////////////////////////////////
+//  Static Functions
+///////////////////////////////////////////////////////////////////////////////
+
+// Start counting down afresh, e.g. once a job's over
+pub fn arm() {
+    clock::set(Alarm::Screensaver, BLANK_AFTER_MS);
+}
+
+// Never blank, e.g. while a job's running and the operator has no reason to touch the keypad
+pub fn disarm() {
+    clock::cancel(Alarm::Screensaver);
+}
+
+// Blank the display if it's been left long enough. Only screens waiting on the operator call this.
+pub fn poll<T: timer::Instance, U: twim::Instance>(timer: &mut Timer<T>, i2c: &mut Twim<U>) {
+    if clock::take(Alarm::Screensaver) {
+        defmt::println!("Blanking the display");
+        lcd1602::display_off(timer, i2c);
+        cortex_interrupt::free(|cs| BLANKED.borrow(cs).set(true));
+    }
+}
+
+// A key's been pressed. Whether it woke the display, and so is spent.
+pub fn wake<T: timer::Instance, U: twim::Instance>(
+    timer: &mut Timer<T>,
+    i2c: &mut Twim<U>,
+) -> bool {
+    arm();
+    if !cortex_interrupt::free(|cs| BLANKED.borrow(cs).replace(false)) {
+        return false;
+    }
+
+    lcd1602::display_on(timer, i2c);
+    true
+}
@@ -0,0 +1,101 @@
+/* * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * *\
+Copyright (C) 2023 CJ McAllister
+    This program is free software; you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation; either version 3 of the License, or
+    (at your option) any later version.
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+    You should have received a copy of the GNU General Public License
+    along with this program; if not, write to the Free Software Foundation,
+    Inc., 51 Franklin Street, Fifth Floor, Boston, MA 02110-1301  USA
+
+\* * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * */
+
+use core::fmt;
+
+use cutter_core::console::{LineError, LineReader, MAX_LINE_LEN};
+use microbit::hal::{timer, Timer};
+
+use crate::platform::hal::uarte::{self, Baudrate, Parity, Uarte};
+
+///////////////////////////////////////////////////////////////////////////////
+//  Named Constants
+///////////////////////////////////////////////////////////////////////////////
+
+// EasyDMA can only transmit from RAM, so anything else is staged through a buffer this big
+const TX_CHUNK_LEN: usize = 32;
+
+///////////////////////////////////////////////////////////////////////////////
+//  Data Structures
+///////////////////////////////////////////////////////////////////////////////
+
+// The micro:bit's USB serial port, shared by the status stream and the serial console
+pub struct SerialPort<T: uarte::Instance> {
+    uarte: Uarte<T>,
+    reader: LineReader,
+}
+
+///////////////////////////////////////////////////////////////////////////////
+//  Object Implementations
+///////////////////////////////////////////////////////////////////////////////
+
+impl<T: uarte::Instance> SerialPort<T> {
+    pub fn new(instance: T, pins: uarte::Pins) -> Self {
+        Self {
+            uarte: Uarte::new(instance, pins, Parity::EXCLUDED, Baudrate::BAUD115200),
+            reader: LineReader::new(),
+        }
+    }
+
+    // Send bytes from anywhere, flash included
+    pub fn write_bytes(&mut self, bytes: &[u8]) {
+        let mut chunk = [0; TX_CHUNK_LEN];
+        for part in bytes.chunks(TX_CHUNK_LEN) {
+            chunk[..part.len()].copy_from_slice(part);
+
+            // Nobody may be listening, so a failed send isn't worth stopping for
+            if self.uarte.write(&chunk[..part.len()]).is_err() {
+                defmt::println!("Failed to send on serial port");
+                return;
+            }
+        }
+    }
+
+    // Listen for up to `window_us`, returning a line if one was completed.
+    // Anything received after the end of the line is dropped, so hosts must wait for a reply before
+    // sending more.
+    pub fn read_line<I: timer::Instance>(
+        &mut self,
+        timer: &mut Timer<I>,
+        window_us: u32,
+    ) -> Option<Result<&str, LineError>> {
+        let mut received = [0; MAX_LINE_LEN];
+        let len = match self.uarte.read_timeout(&mut received, timer, window_us) {
+            Ok(()) => received.len(),
+            Err(uarte::Error::Timeout(len)) => len,
+            Err(_) => return None,
+        };
+        let received = &received[..len];
+
+        let Some(end) = received.iter().position(|&b| b == b'\n') else {
+            for &byte in received {
+                self.reader.push(byte);
+            }
+            return None;
+        };
+        for &byte in &received[..end] {
+            self.reader.push(byte);
+        }
+        self.reader.push(b'\n')
+    }
+}
+
+impl<T: uarte::Instance> fmt::Write for SerialPort<T> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.write_bytes(s.as_bytes());
+        Ok(())
+    }
+}
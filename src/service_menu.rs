@@ -0,0 +1,233 @@
+/* * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * *\
+Copyright (C) 2023 CJ McAllister
+    This program is free software; you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation; either version 3 of the License, or
+    (at your option) any later version.
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+    You should have received a copy of the GNU General Public License
+    along with this program; if not, write to the Free Software Foundation,
+    Inc., 51 Franklin Street, Fifth Floor, Boston, MA 02110-1301  USA
+
+\* * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * */
+
+use cortex_m::peripheral::SCB;
+use microbit::{
+    display::blocking::Display,
+    hal::{nvmc::Nvmc, prelude::*, pwm, timer, twim, Timer, Twim},
+    pac::NVMC,
+};
+
+use crate::{
+    axis::Axis,
+    backlash, demo,
+    i2c::{
+        health,
+        keypad::{self, Key},
+        lcd1602,
+    },
+    inputs::Inputs,
+    power_audit, qa,
+    servo::Servo,
+    settings::{self, Settings, MAX_INPUTS},
+    stepper::Stepper,
+    storage::{self, Region},
+    CycleHardware,
+};
+
+///////////////////////////////////////////////////////////////////////////////
+//  Named Constants
+///////////////////////////////////////////////////////////////////////////////
+
+const INPUT_VIEW_POLL_INTERVAL_IN_MS: u32 = 20;
+const RESET_DONE_DUR_IN_MS: u32 = 2000;
+
+///////////////////////////////////////////////////////////////////////////////
+//  Static Functions
+///////////////////////////////////////////////////////////////////////////////
+
+// Present the service menu until the user exits with '*'
+pub fn run<T: timer::Instance, U: twim::Instance, V: pwm::Instance, D: Axis>(
+    timer: &mut Timer<T>,
+    i2c: &mut Twim<U>,
+    display: &mut Display,
+    hardware: &mut CycleHardware<Servo<V>, Stepper, D>,
+    settings: &mut Settings,
+    nvmc: &mut Nvmc<NVMC>,
+) {
+    defmt::println!("Entering service menu");
+
+    'menu: loop {
+        lcd1602::clear_display(timer, i2c);
+        lcd1602::write_string("1DM 2PW 3IN 8I2C\n4QA 5BL 6LCD 7RS", timer, i2c);
+
+        loop {
+            match keypad::scan(timer, i2c) {
+                Some(Key::One) => {
+                    demo::run(timer, i2c, display);
+                    continue 'menu;
+                }
+                Some(Key::Two) => {
+                    power_audit::run(timer, i2c, hardware.cutter, hardware.feed, hardware.analog);
+                    continue 'menu;
+                }
+                Some(Key::Three) => {
+                    show_inputs(timer, i2c, hardware.inputs);
+                    continue 'menu;
+                }
+                Some(Key::Four) => {
+                    hardware.feed.set_enabled(true);
+                    qa::run(timer, i2c, hardware);
+                    hardware.feed.set_enabled(false);
+                    continue 'menu;
+                }
+                Some(Key::Five) => {
+                    hardware.feed.set_enabled(true);
+                    backlash::calibrate(
+                        timer,
+                        i2c,
+                        hardware.feed,
+                        hardware.encoder,
+                        settings,
+                        nvmc,
+                    );
+                    hardware.feed.set_enabled(false);
+                    continue 'menu;
+                }
+                Some(Key::Six) => {
+                    select_lcd_rom(timer, i2c, settings, nvmc);
+                    continue 'menu;
+                }
+                Some(Key::Seven) => {
+                    factory_reset(timer, i2c, nvmc);
+                    continue 'menu;
+                }
+                Some(Key::Eight) => {
+                    health::show_details(timer, i2c);
+                    continue 'menu;
+                }
+                Some(Key::Star) => break 'menu,
+                _ => continue,
+            }
+        }
+    }
+
+    lcd1602::clear_display(timer, i2c);
+    defmt::println!("Exiting service menu");
+}
+
+// Live view of the configurable inputs for checking their wiring: '1' active, '0' inactive, '-' unused
+fn show_inputs<T: timer::Instance, U: twim::Instance>(
+    timer: &mut Timer<T>,
+    i2c: &mut Twim<U>,
+    inputs: &mut Inputs,
+) {
+    lcd1602::clear_display(timer, i2c);
+    lcd1602::write_string("INPUTS  12345678\n        ", timer, i2c);
+
+    let mut shown: Option<[u8; MAX_INPUTS]> = None;
+    while keypad::scan(timer, i2c) != Some(Key::Star) {
+        inputs.poll();
+        let states = core::array::from_fn(|i| match inputs.state(i) {
+            Some(true) => b'1',
+            Some(false) => b'0',
+            None => b'-',
+        });
+
+        if shown != Some(states) {
+            if shown.is_some() {
+                lcd1602::backspace(MAX_INPUTS, timer, i2c);
+            }
+            lcd1602::write_string(core::str::from_utf8(&states).unwrap(), timer, i2c);
+            shown = Some(states);
+        }
+
+        timer.delay_ms(INPUT_VIEW_POLL_INTERVAL_IN_MS);
+    }
+}
+
+// Toggle the character ROM with a sample of the characters that differ, so the right one can be picked
+// by eye, then save it or put the old one back
+fn select_lcd_rom<T: timer::Instance, U: twim::Instance>(
+    timer: &mut Timer<T>,
+    i2c: &mut Twim<U>,
+    settings: &mut Settings,
+    nvmc: &mut Nvmc<NVMC>,
+) {
+    let mut rom = settings.lcd_rom;
+    loop {
+        lcd1602::set_rom(rom);
+        lcd1602::clear_display(timer, i2c);
+        lcd1602::write_string("ROM ", timer, i2c);
+        lcd1602::write_string(rom.label(), timer, i2c);
+        lcd1602::write_string(" \\~\u{b0}\u{b5}\u{2665}\n1SWAP #SAVE *X", timer, i2c);
+
+        // Wait for a key that changes what's displayed
+        loop {
+            match keypad::scan(timer, i2c) {
+                Some(Key::One) => {
+                    rom = rom.toggled();
+                    break;
+                }
+                Some(Key::Pound) => {
+                    settings.lcd_rom = rom;
+                    settings::save(settings, nvmc);
+                    return;
+                }
+                Some(Key::Star) => {
+                    lcd1602::set_rom(settings.lcd_rom);
+                    return;
+                }
+                _ => continue,
+            }
+        }
+    }
+}
+
+// Erase the saved settings and operator profiles once the user has confirmed it twice, then restart
+// so everything comes back up on defaults
+fn factory_reset<T: timer::Instance, U: twim::Instance>(
+    timer: &mut Timer<T>,
+    i2c: &mut Twim<U>,
+    nvmc: &mut Nvmc<NVMC>,
+) {
+    lcd1602::clear_display(timer, i2c);
+    lcd1602::write_string("FACTORY RESET?\n#=YES *=NO", timer, i2c);
+    if !confirm(Key::Pound, timer, i2c) {
+        return;
+    }
+
+    // A different key the second time, so one press held too long can't confirm both
+    lcd1602::clear_display(timer, i2c);
+    lcd1602::write_string("ERASE ALL DATA?\n0=ERASE *=NO", timer, i2c);
+    if !confirm(Key::Zero, timer, i2c) {
+        return;
+    }
+
+    defmt::println!("Factory reset: erasing settings and profiles");
+    storage::erase(Region::Settings, nvmc);
+    storage::erase(Region::Profiles, nvmc);
+
+    lcd1602::clear_display(timer, i2c);
+    lcd1602::write_string("RESET DONE\nRESTARTING...", timer, i2c);
+    timer.delay_ms(RESET_DONE_DUR_IN_MS);
+    SCB::sys_reset();
+}
+
+// Wait for either the given key, to confirm, or '*' to back out
+fn confirm<T: timer::Instance, U: twim::Instance>(
+    key: Key,
+    timer: &mut Timer<T>,
+    i2c: &mut Twim<U>,
+) -> bool {
+    loop {
+        match keypad::scan(timer, i2c) {
+            Some(Key::Star) => return false,
+            Some(pressed_key) if pressed_key == key => return true,
+            _ => continue,
+        }
+    }
+}
@@ -14,32 +14,127 @@ Copyright (C) 2022 CJ McAllister
 
 \* * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * */
 
-use core::fmt::Debug;
-use microbit::hal::{
+use crate::platform::hal::{
     gpio::{Output, Pin, PushPull},
     pwm,
 };
+use core::cell::Cell;
+use core::fmt::Debug;
+use core::sync::atomic::{compiler_fence, Ordering};
+
+use cortex_m::interrupt::{self as cortex_interrupt, CriticalSection, Mutex};
+
+use crate::{
+    axis::Axis,
+    error::CutterError,
+    pwm_manager::{self, Allocation},
+    settings::{MAX_SERVO_RATE_HZ, MIN_SERVO_RATE_HZ},
+};
+
+#[cfg(feature = "servo_feedback")]
+use crate::analog::{Analog, AnalogInput};
 
 ///////////////////////////////////////////////////////////////////////////////
 //  Named Constants
 ///////////////////////////////////////////////////////////////////////////////
 
-const FIFTY_HZ_IN_500KHZ_TICKS: u16 = 10000;
+// Clock into the prescaler, and the largest period the counter can count to
+const PWM_BASE_CLOCK_HZ: u32 = 16_000_000;
+const MAX_COUNTERTOP: u32 = 0x7FFF;
+const MAX_PRESCALER_SHIFT: u8 = 7;
+const ONE_MHZ: u32 = 1_000_000;
+
+// Servos respond to pulse width, not duty, so duties are percentages of the standard 50Hz frame
+// whatever rate the servo is actually driven at
+const STANDARD_FRAME_IN_US: f32 = 20_000.0;
 const PWM_ENABLE: u32 = 1;
+const PWM_DISABLE: u32 = 0;
 const TRIGGER_TASK: u32 = 1;
 const INFINITY_SAFE_LOOP_CNT: u32 = 2;
 
 const DECODER_CMP_VALUE_MASK: u16 = 0x7FFF;
 
+const MAX_DUTY_PCT: f32 = 100.0;
+
+#[allow(clippy::declare_interior_mutable_const)]
+const EMPTY_DUTY_BUFFER: Mutex<Cell<u16>> = Mutex::new(Cell::new(0));
+
+// Axis positions, in tenths of a percent duty cycle
+pub const CUT_POSITION_CLOSED: i32 = 120;
+pub const CUT_POSITION_OPEN: i32 = 30;
+// Wire clamp, on builds where it's servo-driven
+#[cfg(feature = "clamp_servo")]
+pub const CLAMP_POSITION_CLOSED: i32 = 80;
+#[cfg(feature = "clamp_servo")]
+pub const CLAMP_POSITION_OPEN: i32 = 40;
+// Piece chute, over each bin
+pub const DIVERT_POSITION_BIN_A: i32 = 50;
+pub const DIVERT_POSITION_BIN_B: i32 = 100;
+
+// How far each servo can be driven before its horn fouls the mechanism, a little past either end of its
+// working positions. The blade's limits are also the widest pulses hobby servos accept, as its
+// positions can be tuned.
+pub const CUT_TRAVEL: Travel = Travel { min: 25, max: 125 };
+#[cfg(feature = "clamp_servo")]
+pub const CLAMP_TRAVEL: Travel = Travel { min: 35, max: 85 };
+#[cfg(feature = "piece_sorter")]
+pub const DIVERT_TRAVEL: Travel = Travel { min: 45, max: 105 };
+
 ///////////////////////////////////////////////////////////////////////////////
 //  Data Structures
 ///////////////////////////////////////////////////////////////////////////////
 
+// Mechanical limits of a servo, in tenths of a percent duty cycle like its positions
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Travel {
+    pub min: i32,
+    pub max: i32,
+}
+
 pub struct Servo<T: pwm::Instance> {
     pwm_inst: T,
-    _channel: pwm::Channel,
+    allocation: Allocation,
     _output_pin: Pin<Output<PushPull>>,
-    common_duty: [u16; 2],
+    ticks_per_us: u32,
+    position: i32,
+    home_position: i32,
+    travel: Travel,
+}
+
+// Sequence each instance plays, read by EasyDMA for as long as the instance runs. The buffers live
+// here rather than in the Servo so they can't move out from under the peripheral, and being statics
+// they're in RAM where EasyDMA can reach them, and halfword-aligned as it needs. One step per
+// sequence is enough, as the common decoder drives every channel from it.
+static DUTY_BUFFERS: [Mutex<Cell<u16>>; pwm_manager::NUM_INSTANCES] =
+    [EMPTY_DUTY_BUFFER; pwm_manager::NUM_INSTANCES];
+
+// Plain copy of a servo's state and registers, for logging and diagnostics. The duty is read from
+// the instance's buffer rather than by dereferencing whatever SEQ[0].PTR holds.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, defmt::Format)]
+pub struct Snapshot {
+    pub position: i32,
+    pub home_position: i32,
+    pub common_duty: u16,
+    pub duty_ticks: u16,
+    pub enabled: bool,
+    pub shorts: u32,
+    pub mode: u32,
+    pub countertop: u16,
+    pub prescaler: u8,
+    pub decoder: u32,
+    pub loop_count: u16,
+    pub seq0_ptr: u32,
+    pub seq0_cnt: u16,
+    pub psel: u32,
+}
+
+// Wiper of the servo's internal potentiometer, broken out to an analog input.
+// Expected voltages vary between servos, so measure them at both blade positions for your build.
+#[cfg(feature = "servo_feedback")]
+pub struct PositionFeedback {
+    pub input: AnalogInput,
+    pub closed_mv: u32,
+    pub tolerance_mv: u32,
 }
 
 ///////////////////////////////////////////////////////////////////////////////
@@ -47,30 +142,41 @@ pub struct Servo<T: pwm::Instance> {
 ///////////////////////////////////////////////////////////////////////////////
 
 impl<T: pwm::Instance> Servo<T> {
-    pub fn new(pwm_inst: T, channel: pwm::Channel, output_pin: Pin<Output<PushPull>>) -> Self {
+    pub fn new(
+        pwm_inst: T,
+        allocation: Allocation,
+        output_pin: Pin<Output<PushPull>>,
+        home_position: i32,
+        travel: Travel,
+    ) -> Self {
+        debug_assert!(allocation.is_for::<T>());
+        debug_assert!(travel.contains(home_position));
+
         // Before configuring, trigger TASKS_STOP to ensure a stable reset state
         pwm_inst
             .tasks_stop
             .write(|w| unsafe { w.bits(TRIGGER_TASK) });
 
         // Set the output pin
-        pwm_inst.psel.out[0].write(|w| unsafe { w.bits(output_pin.psel_bits()) });
+        pwm_inst.psel.out[usize::from(allocation.channel())]
+            .write(|w| unsafe { w.bits(output_pin.psel_bits()) });
 
         // Enable the PWM Generator
         pwm_inst.enable.write(|w| unsafe { w.bits(PWM_ENABLE) });
 
-        // Set the frequency to 50Hz via PRESCALER, COUNTERTOP register
-        pwm_inst.prescaler.write(|w| w.prescaler().div_32()); // Divide clock down to 500kHz
-        pwm_inst
-            .countertop
-            .write(|w| unsafe { w.countertop().bits(FIFTY_HZ_IN_500KHZ_TICKS) });
+        // Start at the standard 50Hz, which every servo accepts
+        let ticks_per_us = write_frame_clock(&pwm_inst, MIN_SERVO_RATE_HZ);
 
         // Set the duty cycle to 0 (common to all channels due to default DECODER config)
-        let common_duty = [0, 0];
+        let duty_buffer = cortex_interrupt::free(|cs| {
+            let buffer = DUTY_BUFFERS[allocation.instance()].borrow(cs);
+            buffer.set(0);
+            buffer.as_ptr()
+        });
         pwm_inst
             .seq0
             .ptr
-            .write(|w| unsafe { w.ptr().bits(common_duty.as_ptr() as u32) });
+            .write(|w| unsafe { w.ptr().bits(duty_buffer as u32) });
         pwm_inst.seq0.cnt.write(|w| unsafe { w.bits(1) });
         pwm_inst.seq0.refresh.write(|w| unsafe { w.bits(0) });
 
@@ -86,85 +192,203 @@ impl<T: pwm::Instance> Servo<T> {
 
         Self {
             pwm_inst,
-            _channel: channel,
+            allocation,
             _output_pin: output_pin,
-            common_duty,
+            ticks_per_us,
+            position: 0,
+            home_position,
+            travel,
         }
     }
 
-    pub fn set_duty(&mut self, duty: f32) {
-        // Set the new duty cycle
-        self.common_duty = [
-            ((FIFTY_HZ_IN_500KHZ_TICKS as f32 / 100.0) * duty) as u16 | 1 << 15,
-            0,
-        ];
-        self.pwm_inst
-            .seq0
-            .ptr
-            .write(|w| unsafe { w.ptr().bits(self.common_duty.as_ptr() as u32) });
+    // Stop generating pulses entirely, letting the servo go limp, or resume the last duty cycle
+    pub fn set_enabled(&mut self, enabled: bool) {
+        if enabled {
+            self.pwm_inst
+                .enable
+                .write(|w| unsafe { w.bits(PWM_ENABLE) });
+            self.pwm_inst.tasks_seqstart[0].write(|w| unsafe { w.bits(TRIGGER_TASK) });
+        } else {
+            self.pwm_inst
+                .tasks_stop
+                .write(|w| unsafe { w.bits(TRIGGER_TASK) });
+            self.pwm_inst
+                .enable
+                .write(|w| unsafe { w.bits(PWM_DISABLE) });
+        }
+    }
+
+    // Send positions at a different rate, within what servos accept. Only digital servos cope with
+    // more than 50Hz.
+    pub fn set_frame_rate(&mut self, rate_hz: u16) {
+        defmt::println!("Servo frame rate set to {}Hz", rate_hz);
+        self.ticks_per_us = write_frame_clock(&self.pwm_inst, rate_hz);
+
+        // The pulse being sent was timed for the old clock
+        if self.travel.contains(self.position) {
+            self.move_to(self.position);
+        }
+    }
+
+    // Drive to a duty cycle in percent, held within the servo's travel. Values no servo could be driven
+    // at are refused, leaving it where it was.
+    pub fn set_duty(&mut self, duty: f32) -> Result<(), CutterError> {
+        if !(0.0..=MAX_DUTY_PCT).contains(&duty) {
+            return Err(CutterError::ServoDutyInvalid);
+        }
+        let duty = duty.clamp(self.travel.min as f32 / 10.0, self.travel.max as f32 / 10.0);
+
+        // Set the new duty cycle, and make sure it's in the buffer before EasyDMA next reads it
+        let pulse_in_us = STANDARD_FRAME_IN_US / 100.0 * duty;
+        let compare = (pulse_in_us * self.ticks_per_us as f32) as u16 | 1 << 15;
+        cortex_interrupt::free(|cs| self.duty_buffer(cs).set(compare));
+        compiler_fence(Ordering::SeqCst);
 
         // Start the sequence again
         self.pwm_inst.tasks_seqstart[0].write(|w| unsafe { w.bits(TRIGGER_TASK) });
+
+        Ok(())
+    }
+
+    // Current state, safe to take at any time
+    pub fn snapshot(&self) -> Snapshot {
+        let regs = &self.pwm_inst;
+        let common_duty = cortex_interrupt::free(|cs| self.duty_buffer(cs).get());
+        Snapshot {
+            position: self.position,
+            home_position: self.home_position,
+            common_duty,
+            duty_ticks: common_duty & DECODER_CMP_VALUE_MASK,
+            enabled: regs.enable.read().bits() == PWM_ENABLE,
+            shorts: regs.shorts.read().bits(),
+            mode: regs.mode.read().bits(),
+            countertop: regs.countertop.read().countertop().bits(),
+            prescaler: regs.prescaler.read().bits() as u8,
+            decoder: regs.decoder.read().bits(),
+            loop_count: regs.loop_.read().cnt().bits(),
+            seq0_ptr: regs.seq0.ptr.read().bits(),
+            seq0_cnt: regs.seq0.cnt.read().cnt().bits(),
+            psel: regs.psel.out[usize::from(self.allocation.channel())]
+                .read()
+                .bits(),
+        }
+    }
+
+    fn duty_buffer<'cs>(&self, cs: &'cs CriticalSection) -> &'cs Cell<u16> {
+        DUTY_BUFFERS[self.allocation.instance()].borrow(cs)
+    }
+}
+
+impl Travel {
+    pub fn contains(&self, position: i32) -> bool {
+        (self.min..=self.max).contains(&position)
+    }
+}
+
+// Hobby servos give no completion signal, so moves are treated as instantaneous and timed by the caller
+impl<T: pwm::Instance> Axis for Servo<T> {
+    fn move_to(&mut self, position: i32) {
+        if !self.travel.contains(position) {
+            defmt::println!(
+                "Servo move to {} is beyond its travel of {}-{}, limiting it",
+                position,
+                self.travel.min,
+                self.travel.max
+            );
+        }
+
+        let position = position.clamp(self.travel.min, self.travel.max);
+        match self.set_duty(position as f32 / 10.0) {
+            Ok(()) => self.position = position,
+            Err(e) => defmt::println!("Servo move to {} refused: {}", position, e),
+        }
+    }
+
+    fn home(&mut self) {
+        self.move_to(self.home_position);
+    }
+
+    fn position(&self) -> i32 {
+        self.position
+    }
+
+    fn is_moving(&self) -> bool {
+        false
+    }
+}
+
+#[cfg(feature = "servo_feedback")]
+impl PositionFeedback {
+    // Confirm the blade actually reached its closed position
+    pub fn check_closed(&self, analog: &mut Analog) -> Result<(), CutterError> {
+        let measured_mv = analog.read_mv(self.input);
+        if measured_mv.abs_diff(self.closed_mv) > self.tolerance_mv {
+            return Err(CutterError::BladeNotClosed { measured_mv });
+        }
+
+        Ok(())
     }
 }
 
 impl<T: pwm::Instance> Debug for Servo<T> {
     fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
-        // Gather register values
-        let shorts = self.pwm_inst.shorts.read().bits();
-        let enable = self.pwm_inst.enable.read().bits();
-        let mode = self.pwm_inst.mode.read().bits();
-        let countertop = self.pwm_inst.countertop.read().bits();
-        let prescaler = self.pwm_inst.prescaler.read().bits();
-        let decoder = self.pwm_inst.decoder.read().bits();
-        let loop_ = self.pwm_inst.loop_.read().bits();
-        let seq0_ptr = self.pwm_inst.seq0.ptr.read().bits();
-        let seq0_cnt = self.pwm_inst.seq0.cnt.read().bits();
-        let seq1_ptr = self.pwm_inst.seq1.ptr.read().bits();
-        let seq1_cnt = self.pwm_inst.seq1.cnt.read().bits();
-        let psel = self.pwm_inst.psel.out[0].read().bits();
-
-        // UNSAFELY retrieve seq values
-        let seq0_val = unsafe { *(seq0_ptr as *const i32) };
-        let seq1_val = unsafe { *(seq1_ptr as *const i32) };
+        let s = self.snapshot();
 
         f.write_fmt(format_args!("Servo Register Map:\n"))?;
-        f.write_fmt(format_args!("  SHORTS:         {:0>8b}\n", shorts))?;
-        f.write_fmt(format_args!("  ENABLE:         {:0>8b}\n", enable))?;
-        f.write_fmt(format_args!("  MODE:           {:0>8b}\n", mode))?;
+        f.write_fmt(format_args!("  SHORTS:         {:0>8b}\n", s.shorts))?;
+        f.write_fmt(format_args!(
+            "  ENABLE:         {:0>8b}\n",
+            s.enabled as u32
+        ))?;
+        f.write_fmt(format_args!("  MODE:           {:0>8b}\n", s.mode))?;
         f.write_fmt(format_args!(
             "  COUNTERTOP:     {:0>8b} ({})\n",
-            countertop, countertop
+            s.countertop, s.countertop
         ))?;
         f.write_fmt(format_args!(
             "  PRESCALER:      {:0>8b} ({})\n",
-            prescaler, prescaler
+            s.prescaler, s.prescaler
         ))?;
-        f.write_fmt(format_args!("  DECODER:        {:0>8b}\n", decoder))?;
+        f.write_fmt(format_args!("  DECODER:        {:0>8b}\n", s.decoder))?;
         f.write_fmt(format_args!(
             "  LOOP:           {:0>8b} ({})\n",
-            loop_, loop_
+            s.loop_count, s.loop_count
         ))?;
         f.write_fmt(format_args!(
             "  SEQ[0]PTR, CNT: {:0>8x}, {:0>8b} ({})\n",
-            seq0_ptr, seq0_cnt, seq0_cnt
+            s.seq0_ptr, s.seq0_cnt, s.seq0_cnt
         ))?;
         f.write_fmt(format_args!(
-            "  SEQ[0] Value:   {:0>16b} ({})\n",
-            seq0_val,
-            seq0_val & DECODER_CMP_VALUE_MASK as i32
+            "  Common Duty:    {:0>16b} ({})\n",
+            s.common_duty, s.duty_ticks
         ))?;
+        f.write_fmt(format_args!("  PSEL.OUT:       {:0>8b}\n", s.psel))?;
         f.write_fmt(format_args!(
-            "  SEQ[1]PTR, CNT: {:0>8x}, {:0>8b} ({})\n",
-            seq1_ptr, seq1_cnt, seq1_cnt
+            "  Position:       {} (home {})\n",
+            s.position, s.home_position
         ))?;
-        f.write_fmt(format_args!(
-            "  SEQ[1] Value:   {:0>16b} ({})\n",
-            seq1_val,
-            seq1_val & DECODER_CMP_VALUE_MASK as i32
-        ))?;
-        f.write_fmt(format_args!("  PSEL.OUT[0]:    {:0>8b}\n", psel))?;
 
         Ok(())
     }
 }
+
+///////////////////////////////////////////////////////////////////////////////
+//  Helper Functions
+///////////////////////////////////////////////////////////////////////////////
+
+// Set PRESCALER and COUNTERTOP for a frame rate, dividing the clock no further than needed to fit the
+// counter so pulses are timed as finely as possible. Returns the counter ticks per microsecond.
+fn write_frame_clock<T: pwm::Instance>(pwm_inst: &T, rate_hz: u16) -> u32 {
+    let rate_hz = rate_hz.clamp(MIN_SERVO_RATE_HZ, MAX_SERVO_RATE_HZ) as u32;
+    let (shift, countertop) = (0..=MAX_PRESCALER_SHIFT)
+        .map(|shift| (shift, (PWM_BASE_CLOCK_HZ >> shift) / rate_hz))
+        .find(|&(_, countertop)| countertop <= MAX_COUNTERTOP)
+        .unwrap_or((MAX_PRESCALER_SHIFT, MAX_COUNTERTOP));
+
+    pwm_inst.prescaler.write(|w| w.prescaler().bits(shift));
+    pwm_inst
+        .countertop
+        .write(|w| unsafe { w.countertop().bits(countertop as u16) });
+
+    (PWM_BASE_CLOCK_HZ >> shift) / ONE_MHZ
+}
@@ -0,0 +1,65 @@
+/* * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * *\
+Copyright (C) 2023 CJ McAllister
+    This program is free software; you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation; either version 3 of the License, or
+    (at your option) any later version.
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+    You should have received a copy of the GNU General Public License
+    along with this program; if not, write to the Free Software Foundation,
+    Inc., 51 Franklin Street, Fifth Floor, Boston, MA 02110-1301  USA
+
+\* * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * */
+
+use crate::platform::{hal::nvmc::Nvmc, pac::NVMC};
+use crate::storage::{self, Region};
+
+pub use cutter_core::inputs::{InputConfig, InputFunction, MAX_INPUTS};
+pub use cutter_core::settings::*;
+
+///////////////////////////////////////////////////////////////////////////////
+//  Static Functions
+///////////////////////////////////////////////////////////////////////////////
+
+// Load settings from flash, falling back to defaults if none have been saved yet or they're corrupt
+pub fn load() -> Settings {
+    let mut bytes = [0; SERIALIZED_LEN];
+    storage::read(Region::Settings, &mut bytes);
+
+    Settings::from_bytes(&bytes).unwrap_or_else(|| {
+        let reason = match Settings::is_corrupt(&bytes) {
+            true => "are corrupt",
+            false => "not found",
+        };
+        defmt::println!("Saved settings {}, using defaults", reason);
+        Settings::default()
+    })
+}
+
+// Whether the saved settings fail their checksum, so load() had to fall back to defaults
+pub fn is_corrupt() -> bool {
+    let mut bytes = [0; SERIALIZED_LEN];
+    storage::read(Region::Settings, &mut bytes);
+
+    Settings::is_corrupt(&bytes)
+}
+
+pub fn save(settings: &Settings, nvmc: &mut Nvmc<NVMC>) {
+    storage::write(Region::Settings, &settings.to_bytes(), nvmc);
+}
+
+// Save settings left by an older build again in the current layout, so they survive the next update
+pub fn upgrade(nvmc: &mut Nvmc<NVMC>) {
+    let mut bytes = [0; SERIALIZED_LEN];
+    storage::read(Region::Settings, &mut bytes);
+
+    if Settings::is_outdated(&bytes) {
+        if let Some(settings) = Settings::from_bytes(&bytes) {
+            defmt::println!("Upgrading saved settings to version {}", SETTINGS_VERSION);
+            save(&settings, nvmc);
+        }
+    }
+}
@@ -0,0 +1,309 @@
+/* * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * *\
+Copyright (C) 2023 CJ McAllister
+    This program is free software; you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation; either version 3 of the License, or
+    (at your option) any later version.
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+    You should have received a copy of the GNU General Public License
+    along with this program; if not, write to the Free Software Foundation,
+    Inc., 51 Franklin Street, Fifth Floor, Boston, MA 02110-1301  USA
+
+\* * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * */
+
+// Settings pages for the operator, one per category. Every change takes effect and is saved straight
+// away, to the machine settings or the active operator's profile.
+
+use microbit::{
+    hal::{nvmc::Nvmc, prelude::*, pwm, timer, twim, Timer, Twim},
+    pac::{NVMC, PWM0},
+};
+
+use cutter_core::{
+    input::NumberEntry,
+    settings_menu::{Category, Editable, Item, Kind, Store, NUM_CATEGORIES},
+    widgets::{Area, Label, ListView},
+};
+
+use crate::{
+    buzzer::Buzzer,
+    haptic,
+    help::{self, Hint},
+    i2c::{
+        keypad::{self, Key},
+        lcd1602,
+    },
+    inputs::Inputs,
+    job_history,
+    profiles::{self, Profiles},
+    servo::Servo,
+    settings::{self, Settings},
+    sound,
+    stepper::Stepper,
+    tuning::{self, TuneError},
+};
+
+///////////////////////////////////////////////////////////////////////////////
+//  Named Constants
+///////////////////////////////////////////////////////////////////////////////
+
+const HELP_DUR_IN_MS: u32 = 1500;
+
+// The longest item label, and the entry arrow after it
+const PROMPT_LEN: usize = 16 + 4;
+
+///////////////////////////////////////////////////////////////////////////////
+//  Data Structures
+///////////////////////////////////////////////////////////////////////////////
+
+// What the settings can change besides themselves
+pub struct Targets<'a> {
+    pub profiles: &'a mut Profiles,
+    pub settings: &'a mut Settings,
+    pub inputs: &'a mut Inputs,
+    pub feed: &'a mut Stepper,
+    pub cutter: &'a mut Servo<PWM0>,
+    pub nvmc: &'a mut Nvmc<NVMC>,
+}
+
+///////////////////////////////////////////////////////////////////////////////
+//  Static Functions
+///////////////////////////////////////////////////////////////////////////////
+
+// Pick a category with '2'/'8' and open it with '#', until the operator leaves with '*'
+pub fn run<T: timer::Instance, U: twim::Instance, V: pwm::Instance>(
+    targets: &mut Targets,
+    timer: &mut Timer<T>,
+    i2c: &mut Twim<U>,
+    buzzer: &mut Buzzer<V>,
+) {
+    defmt::println!("Entering settings menu");
+
+    let labels = Category::ALL.map(Category::label);
+    let mut index = 0;
+    'menu: loop {
+        let category = Category::ALL[index];
+        lcd1602::clear_display(timer, i2c);
+        lcd1602::render(
+            &ListView::new(&labels, index),
+            Area::line(0, 0, lcd1602::PAGE_WIDTH),
+        );
+        lcd1602::render(
+            &Label::new("2/8SEL #OPEN *X"),
+            Area::line(1, 0, lcd1602::PAGE_WIDTH),
+        );
+        lcd1602::draw_frame(timer, i2c);
+
+        // Wait for a key that changes what's displayed
+        loop {
+            match keypad::scan_idle(timer, i2c) {
+                Some(Key::Two) => {
+                    index = (index + NUM_CATEGORIES - 1) % NUM_CATEGORIES;
+                    continue 'menu;
+                }
+                Some(Key::Eight) => {
+                    index = (index + 1) % NUM_CATEGORIES;
+                    continue 'menu;
+                }
+                Some(Key::Pound) => {
+                    show_page(category, targets, timer, i2c, buzzer);
+                    continue 'menu;
+                }
+                Some(Key::Star) => break 'menu,
+                _ => continue,
+            }
+        }
+    }
+
+    lcd1602::clear_display(timer, i2c);
+    defmt::println!("Exiting settings menu");
+}
+
+// Step through a category's items with '2'/'8', editing the one shown with '#', until '*'
+fn show_page<T: timer::Instance, U: twim::Instance, V: pwm::Instance>(
+    category: Category,
+    targets: &mut Targets,
+    timer: &mut Timer<T>,
+    i2c: &mut Twim<U>,
+    buzzer: &mut Buzzer<V>,
+) {
+    // Show the keys first, they don't fit alongside the value, until the operator's used them
+    if let Some(hint) = help::hint(Hint::Page, lcd1602::PAGE_WIDTH) {
+        lcd1602::clear_display(timer, i2c);
+        lcd1602::write_string(category.label(), timer, i2c);
+        lcd1602::write_string("\n", timer, i2c);
+        lcd1602::write_string(hint, timer, i2c);
+        timer.delay_ms(HELP_DUR_IN_MS);
+    }
+
+    let items = category.items();
+    let mut index = 0;
+    'page: loop {
+        let item = items[index];
+        lcd1602::clear_display(timer, i2c);
+        lcd1602::write_string(item.label(), timer, i2c);
+        lcd1602::write_string("\n", timer, i2c);
+        let value = value(item, targets.settings, targets.profiles);
+        match item.kind() {
+            Kind::Choice(choices) => lcd1602::write_string(choices[value as usize], timer, i2c),
+            Kind::Number(_) => lcd1602::write_u32_trimmed(value, timer, i2c),
+        }
+
+        // Wait for a key that changes what's displayed
+        loop {
+            let key = keypad::scan_idle(timer, i2c);
+            if matches!(key, Some(Key::Two | Key::Eight | Key::Pound | Key::Star)) {
+                help::learn(Hint::Page);
+            }
+            match key {
+                Some(Key::Two) => {
+                    index = (index + items.len() - 1) % items.len();
+                    continue 'page;
+                }
+                Some(Key::Eight) => {
+                    index = (index + 1) % items.len();
+                    continue 'page;
+                }
+                Some(Key::Pound) => {
+                    edit(item, targets, timer, i2c, buzzer);
+                    continue 'page;
+                }
+                Some(Key::Star) => return,
+                _ => continue,
+            }
+        }
+    }
+}
+
+// Step a choice on to the next one, or have a number entered, then apply and save it
+fn edit<T: timer::Instance, U: twim::Instance, V: pwm::Instance>(
+    item: Item,
+    targets: &mut Targets,
+    timer: &mut Timer<T>,
+    i2c: &mut Twim<U>,
+    buzzer: &mut Buzzer<V>,
+) {
+    let current = value(item, targets.settings, targets.profiles);
+    let value = match item.kind() {
+        kind @ Kind::Choice(_) => kind.next(current),
+        Kind::Number(range) => {
+            let mut prompt = [0; PROMPT_LEN];
+            crate::get_user_parameter(
+                prompt_for(item, &mut prompt),
+                NumberEntry::with_value(*range.start(), *range.end(), current),
+                None,
+                None,
+                timer,
+                i2c,
+                buzzer,
+            )
+        }
+    };
+
+    if let Err(e) = change(
+        item,
+        value,
+        targets.settings,
+        targets.profiles,
+        targets.inputs,
+        targets.nvmc,
+    ) {
+        defmt::println!("Rejected {} for {}: {}", value, item, e.message());
+        buzzer.error(timer);
+        return;
+    }
+
+    // The feed and cutter keep copies of their own, and a new melody is played to be heard
+    match item {
+        Item::FeedBacklash => targets.feed.set_backlash(value),
+        Item::ServoRateHz => targets.cutter.set_frame_rate(value as u16),
+        Item::CompletionMelody => buzzer.completion_melody(value as u8, timer),
+        _ => {}
+    }
+}
+
+// An item's current value, wherever it's kept
+pub fn value(item: Item, settings: &mut Settings, profiles: &mut Profiles) -> u32 {
+    Editable {
+        settings,
+        profile: profiles.active_mut(),
+        tuning: &mut tuning::current(),
+        time_of_day: &mut sound::time_of_day(),
+        date: &mut sound::date(),
+        wire_used_mm: job_history::totals().wire_mm,
+    }
+    .get(item)
+}
+
+// Change an item and save it, putting it into force wherever a copy is held other than the feed and
+// cutter, which are left to the caller
+pub fn change(
+    item: Item,
+    value: u32,
+    settings: &mut Settings,
+    profiles: &mut Profiles,
+    inputs: &mut Inputs,
+    nvmc: &mut Nvmc<NVMC>,
+) -> Result<(), TuneError> {
+    let mut tuning = tuning::current();
+    let mut time_of_day = sound::time_of_day();
+    let mut date = sound::date();
+    let store = Editable {
+        settings: &mut *settings,
+        profile: profiles.active_mut(),
+        tuning: &mut tuning,
+        time_of_day: &mut time_of_day,
+        date: &mut date,
+        wire_used_mm: job_history::totals().wire_mm,
+    }
+    .set(item, value)?;
+    defmt::println!("Changed {} to {}", item, value);
+
+    match item {
+        Item::Tuned(param) => {
+            let _ = tuning::set(param, value);
+        }
+        Item::LcdRom => lcd1602::set_rom(settings.lcd_rom),
+        Item::PedalAction => inputs.set_pedal_action(settings.pedal_action),
+        Item::SoundOn | Item::QuietFrom | Item::QuietTo => sound::set(settings.sound),
+        Item::BuzzKeys | Item::BuzzJobDone => haptic::set(settings),
+        Item::StuckKeyMs | Item::StuckKeyPollMs => keypad::set_stuck_key_timing(settings),
+        _ => {}
+    }
+
+    match store {
+        Store::Settings => settings::save(settings, nvmc),
+        Store::Profile => profiles::save(profiles, nvmc),
+        Store::Clock => match item {
+            Item::TimeNow => {
+                if let Some(minute_of_day) = time_of_day {
+                    sound::set_time_of_day(minute_of_day);
+                }
+            }
+            _ => {
+                if let Some(date) = date {
+                    sound::set_date(date);
+                }
+            }
+        },
+    }
+
+    Ok(())
+}
+
+///////////////////////////////////////////////////////////////////////////////
+//  Helper Functions
+///////////////////////////////////////////////////////////////////////////////
+
+// The item's label with the entry arrow on the row below, as for the job's length and count
+fn prompt_for(item: Item, buf: &mut [u8; PROMPT_LEN]) -> &str {
+    let label = item.label().as_bytes();
+    buf[..label.len()].copy_from_slice(label);
+    buf[label.len()..label.len() + 4].copy_from_slice(b"\n-> ");
+
+    // Labels are ASCII, so this cannot fail
+    core::str::from_utf8(&buf[..label.len() + 4]).unwrap()
+}
@@ -0,0 +1,80 @@
+/* * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * *\
+Copyright (C) 2023 CJ McAllister
+    This program is free software; you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation; either version 3 of the License, or
+    (at your option) any later version.
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+    You should have received a copy of the GNU General Public License
+    along with this program; if not, write to the Free Software Foundation,
+    Inc., 51 Franklin Street, Fifth Floor, Boston, MA 02110-1301  USA
+
+\* * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * */
+
+use crate::platform::hal::{
+    gpio::{OpenDrain, Output, Pin},
+    prelude::*,
+};
+
+use crate::axis::Axis;
+
+///////////////////////////////////////////////////////////////////////////////
+//  Named Constants
+///////////////////////////////////////////////////////////////////////////////
+
+// Axis positions
+pub const RELEASED: i32 = 0;
+pub const ENERGISED: i32 = 1;
+
+///////////////////////////////////////////////////////////////////////////////
+//  Data Structures
+///////////////////////////////////////////////////////////////////////////////
+
+// Two-position actuator (solenoid, pneumatic valve, ...) switched by an external driver with its
+// input pulled up, so it stays released while the pin is
+pub struct Solenoid {
+    pin: Pin<Output<OpenDrain>>,
+    position: i32,
+}
+
+///////////////////////////////////////////////////////////////////////////////
+//  Object Implementations
+///////////////////////////////////////////////////////////////////////////////
+
+impl Solenoid {
+    // The pin must start released
+    #[cfg_attr(any(not(feature = "clamp"), feature = "clamp_servo"), allow(dead_code))]
+    pub fn new(pin: Pin<Output<OpenDrain>>) -> Self {
+        Self {
+            pin,
+            position: RELEASED,
+        }
+    }
+}
+
+impl Axis for Solenoid {
+    fn move_to(&mut self, position: i32) {
+        if position == RELEASED {
+            self.pin.set_high().unwrap();
+        } else {
+            self.pin.set_low().unwrap();
+        }
+        self.position = position;
+    }
+
+    fn home(&mut self) {
+        self.move_to(RELEASED);
+    }
+
+    fn position(&self) -> i32 {
+        self.position
+    }
+
+    // Solenoids are assumed to have finished their travel by the time anything waits on them
+    fn is_moving(&self) -> bool {
+        false
+    }
+}
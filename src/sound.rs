@@ -0,0 +1,107 @@
+/* * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * *\
+Copyright (C) 2023 CJ McAllister
+    This program is free software; you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation; either version 3 of the License, or
+    (at your option) any later version.
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+    You should have received a copy of the GNU General Public License
+    along with this program; if not, write to the Free Software Foundation,
+    Inc., 51 Franklin Street, Fifth Floor, Boston, MA 02110-1301  USA
+
+\* * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * */
+
+// Silent mode and quiet hours. There's no battery to keep the time of day through a power cut, so the
+// operator sets the clock from the settings menu and it's kept against the uptime counter until then.
+// The date for bundle labels is kept the same way. Quiet hours are checked against the clock as each
+// cue sounds, so they need no alarm of their own.
+
+use core::cell::Cell;
+
+use cortex_m::interrupt::{self as cortex_interrupt, Mutex};
+
+use cutter_core::label::Date;
+pub use cutter_core::sound::Cue;
+use cutter_core::sound::{Sound, MINUTES_PER_DAY};
+
+use crate::settings::Settings;
+
+///////////////////////////////////////////////////////////////////////////////
+//  Named Constants
+///////////////////////////////////////////////////////////////////////////////
+
+const SECONDS_PER_MINUTE: u32 = 60;
+const SECONDS_PER_DAY: u32 = MINUTES_PER_DAY as u32 * SECONDS_PER_MINUTE;
+
+///////////////////////////////////////////////////////////////////////////////
+//  Data Structures
+///////////////////////////////////////////////////////////////////////////////
+
+static SOUND: Mutex<Cell<Sound>> = Mutex::new(Cell::new(Sound::DEFAULT));
+
+// Time of day when the uptime counter read zero, in seconds since midnight, once the clock is set
+static BOOT_TIME_OF_DAY: Mutex<Cell<Option<u32>>> = Mutex::new(Cell::new(None));
+
+// Date as last set, and the uptime in seconds when it was
+static DATE_SET: Mutex<Cell<Option<(Date, u32)>>> = Mutex::new(Cell::new(None));
+
+///////////////////////////////////////////////////////////////////////////////
+//  Static Functions
+///////////////////////////////////////////////////////////////////////////////
+
+pub fn init(settings: &Settings) {
+    set(settings.sound);
+}
+
+// Put changed sound settings into force
+pub fn set(sound: Sound) {
+    cortex_interrupt::free(|cs| SOUND.borrow(cs).set(sound));
+}
+
+// Set the clock, in minutes since midnight
+pub fn set_time_of_day(minute_of_day: u16) {
+    let now_s = minute_of_day as u32 * SECONDS_PER_MINUTE;
+    let boot_s = (now_s + SECONDS_PER_DAY - crate::uptime_s() % SECONDS_PER_DAY) % SECONDS_PER_DAY;
+    cortex_interrupt::free(|cs| BOOT_TIME_OF_DAY.borrow(cs).set(Some(boot_s)));
+}
+
+// Minutes since midnight, or None until the clock is set. The uptime counter wraps after about 24
+// days, and the clock wants setting again after that.
+pub fn time_of_day() -> Option<u16> {
+    let boot_s = cortex_interrupt::free(|cs| BOOT_TIME_OF_DAY.borrow(cs).get())?;
+    let now_s = (boot_s + crate::uptime_s() % SECONDS_PER_DAY) % SECONDS_PER_DAY;
+    Some((now_s / SECONDS_PER_MINUTE) as u16)
+}
+
+pub fn set_date(date: Date) {
+    let set = (date, crate::uptime_s());
+    cortex_interrupt::free(|cs| DATE_SET.borrow(cs).set(Some(set)));
+}
+
+// Today's date, or None until it's set. It turns over at midnight if the clock is set too, and every
+// 24 hours from when it was set if not.
+pub fn date() -> Option<Date> {
+    let (date, set_s) = cortex_interrupt::free(|cs| DATE_SET.borrow(cs).get())?;
+    let elapsed_s = crate::uptime_s().wrapping_sub(set_s);
+    let midnights = match time_of_day() {
+        Some(minute_of_day) => {
+            // Where in its day the date was set, going back from now
+            let now_s = minute_of_day as u32 * SECONDS_PER_MINUTE;
+            let set_time_s =
+                (now_s + SECONDS_PER_DAY - elapsed_s % SECONDS_PER_DAY) % SECONDS_PER_DAY;
+            (set_time_s + elapsed_s) / SECONDS_PER_DAY
+        }
+        None => elapsed_s / SECONDS_PER_DAY,
+    };
+
+    Some(date.plus_days(midnights))
+}
+
+// Whether the buzzer may sound a cue right now
+pub fn allows(cue: Cue) -> bool {
+    let sound = cortex_interrupt::free(|cs| SOUND.borrow(cs).get());
+    sound.allows(cue, time_of_day())
+}
@@ -0,0 +1,150 @@
+/* * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * *\
+Copyright (C) 2023 CJ McAllister
+    This program is free software; you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation; either version 3 of the License, or
+    (at your option) any later version.
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+    You should have received a copy of the GNU General Public License
+    along with this program; if not, write to the Free Software Foundation,
+    Inc., 51 Franklin Street, Fifth Floor, Boston, MA 02110-1301  USA
+
+\* * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * */
+
+// What the machine shows as it comes up: a banner on the LCD and an animation on the LED matrix. To
+// personalize a build, implement Splash and point main's BootSplash at it. Any key cuts it short, its
+// time kept by the clock so the keypad can be scanned all the way through.
+
+use microbit::{
+    display::blocking::Display,
+    hal::{timer, twim, Timer, Twim},
+};
+
+use crate::{
+    clock,
+    i2c::{
+        keypad::{self, Key},
+        lcd1602,
+    },
+    led_matrix::{self, Image},
+};
+
+///////////////////////////////////////////////////////////////////////////////
+//  Named Constants
+///////////////////////////////////////////////////////////////////////////////
+
+// Long enough to read the banner, and for the operator to get to '*' for the service menu
+const DEFAULT_DURATION_IN_MS: u32 = 2500;
+
+const SWEEP_FRAME_DUR_IN_MS: u32 = 100;
+const NUM_SWEEP_FRAMES: usize = 5;
+const HEART_DUR_IN_MS: u32 = 1500;
+
+// Frames are shown in slices this long, with the keypad scanned in between
+const FRAME_SLICE_IN_MS: u32 = 20;
+
+///////////////////////////////////////////////////////////////////////////////
+//  Data Structures
+///////////////////////////////////////////////////////////////////////////////
+
+pub struct Frame {
+    pub image: Image,
+    pub duration_ms: u32,
+}
+
+pub trait Splash {
+    // Both LCD lines, split by a newline
+    fn banner(&self) -> &str;
+
+    // The animation's frames in turn, until None
+    fn frame(&self, index: usize) -> Option<Frame>;
+
+    // How long the whole splash lasts, holding the banner once the animation is over
+    fn duration_ms(&self) -> u32 {
+        DEFAULT_DURATION_IN_MS
+    }
+}
+
+// The stock splash: a sweep across the matrix, then a heart
+#[derive(Default)]
+pub struct Greeting;
+
+///////////////////////////////////////////////////////////////////////////////
+//  Object Implementations
+///////////////////////////////////////////////////////////////////////////////
+
+impl Splash for Greeting {
+    fn banner(&self) -> &str {
+        "HI BABE! \u{2665}\nYou so pretty..."
+    }
+
+    fn frame(&self, index: usize) -> Option<Frame> {
+        match index {
+            i if i < NUM_SWEEP_FRAMES => Some(Frame {
+                image: led_matrix::sweep_frame(i),
+                duration_ms: SWEEP_FRAME_DUR_IN_MS,
+            }),
+            i if i == NUM_SWEEP_FRAMES => Some(Frame {
+                image: led_matrix::HEART,
+                duration_ms: HEART_DUR_IN_MS,
+            }),
+            _ => None,
+        }
+    }
+}
+
+///////////////////////////////////////////////////////////////////////////////
+//  Static Functions
+///////////////////////////////////////////////////////////////////////////////
+
+// Show the splash until it's over or a key is pressed, returning true if the key was '*'
+pub fn run<S: Splash, T: timer::Instance, U: twim::Instance>(
+    splash: &S,
+    timer: &mut Timer<T>,
+    i2c: &mut Twim<U>,
+    display: &mut Display,
+) -> bool {
+    defmt::println!("Showing splash...");
+    lcd1602::clear_display(timer, i2c);
+    lcd1602::write_string(splash.banner(), timer, i2c);
+
+    let start = clock::now();
+    let mut frame_end_ms = 0;
+    let mut index = 0;
+    let key = loop {
+        let elapsed_ms = clock::now().ms_since(start);
+        match splash.frame(index) {
+            Some(frame) => {
+                if elapsed_ms >= frame_end_ms + frame.duration_ms {
+                    frame_end_ms += frame.duration_ms;
+                    index += 1;
+                    continue;
+                }
+                let left_ms = frame_end_ms + frame.duration_ms - elapsed_ms;
+                led_matrix::show(frame.image, left_ms.min(FRAME_SLICE_IN_MS), timer, display);
+                if let Some(key) = keypad::scan(timer, i2c) {
+                    break Some(key);
+                }
+            }
+            // The animation's over, so the banner's held with the CPU asleep between scans
+            None => {
+                display.clear();
+                if elapsed_ms >= splash.duration_ms() {
+                    break None;
+                }
+                if let Some(key) = keypad::scan_idle(timer, i2c) {
+                    break Some(key);
+                }
+            }
+        }
+    };
+    display.clear();
+
+    if let Some(key) = key {
+        defmt::println!("Splash cut short by {}", key);
+    }
+    key == Some(Key::Star)
+}
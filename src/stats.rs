@@ -0,0 +1,87 @@
+/* * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * *\
+Copyright (C) 2023 CJ McAllister
+    This program is free software; you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation; either version 3 of the License, or
+    (at your option) any later version.
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+    You should have received a copy of the GNU General Public License
+    along with this program; if not, write to the Free Software Foundation,
+    Inc., 51 Franklin Street, Fifth Floor, Boston, MA 02110-1301  USA
+
+\* * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * */
+
+use crate::platform::hal::{timer, twim, Timer, Twim};
+
+use crate::i2c::lcd1602;
+
+///////////////////////////////////////////////////////////////////////////////
+//  Named Constants
+///////////////////////////////////////////////////////////////////////////////
+
+// Sample-to-sample change during a cut stroke above which the blade or mount is suspect
+const VIBRATION_WARNING_MG: u32 = 800;
+
+///////////////////////////////////////////////////////////////////////////////
+//  Data Structures
+///////////////////////////////////////////////////////////////////////////////
+
+#[derive(Copy, Clone, Debug, Default)]
+pub struct JobStats {
+    pub pieces_cut: u32,
+    // Lost to jams and aborts partway through, replaced or not
+    pub scrapped: u32,
+    pub vibration_warnings: u32,
+    pub peak_vibration_mg: u32,
+}
+
+///////////////////////////////////////////////////////////////////////////////
+//  Object Implementations
+///////////////////////////////////////////////////////////////////////////////
+
+impl JobStats {
+    pub fn record_cut(&mut self, vibration_mg: Option<u32>) {
+        self.pieces_cut += 1;
+
+        if let Some(vibration_mg) = vibration_mg {
+            self.peak_vibration_mg = self.peak_vibration_mg.max(vibration_mg);
+            if vibration_mg > VIBRATION_WARNING_MG {
+                defmt::println!(
+                    "Abnormal vibration on piece {}: {}mg",
+                    self.pieces_cut,
+                    vibration_mg
+                );
+                self.vibration_warnings += 1;
+            }
+        }
+    }
+
+    pub fn has_maintenance_warning(&self) -> bool {
+        self.vibration_warnings > 0
+    }
+
+    pub fn display<T: timer::Instance, U: twim::Instance>(
+        &self,
+        timer: &mut Timer<T>,
+        i2c: &mut Twim<U>,
+    ) {
+        lcd1602::clear_display(timer, i2c);
+        lcd1602::write_string("CUT ", timer, i2c);
+        lcd1602::write_u32(self.pieces_cut, timer, i2c);
+        if self.scrapped > 0 {
+            lcd1602::write_string(" SCRAP ", timer, i2c);
+            lcd1602::write_u32(self.scrapped, timer, i2c);
+            lcd1602::write_string("\n", timer, i2c);
+        } else {
+            lcd1602::write_string(" PIECES\n", timer, i2c);
+        }
+        if self.has_maintenance_warning() {
+            lcd1602::write_string("MAINT: VIBRATION", timer, i2c);
+        } else {
+            lcd1602::write_string("VIBRATION OK", timer, i2c);
+        }
+    }
+}
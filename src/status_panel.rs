@@ -0,0 +1,187 @@
+/* * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * *\
+Copyright (C) 2023 CJ McAllister
+    This program is free software; you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation; either version 3 of the License, or
+    (at your option) any later version.
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+    You should have received a copy of the GNU General Public License
+    along with this program; if not, write to the Free Software Foundation,
+    Inc., 51 Franklin Street, Fifth Floor, Boston, MA 02110-1301  USA
+
+\* * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * */
+
+// Optional second display on the bus, mirroring the running job's progress for reading from across
+// the shop while the operator display carries on with the interactive UI. Found at power-on if fitted;
+// everything here does nothing otherwise, as on minimal builds, which leave it out.
+
+use core::cell::RefCell;
+
+use cortex_m::interrupt::{self as cortex_interrupt, Mutex};
+use cutter_core::{
+    frame::Frame,
+    job::Progress,
+    numeric,
+    widgets::{Align, Area, Counter, Label, Widget},
+};
+
+use crate::{
+    clock::{self, Instant},
+    i2c::{lcd1602::Lcd, CharacterDisplay},
+    platform::hal::{timer, twim, Timer, Twim},
+};
+
+///////////////////////////////////////////////////////////////////////////////
+//  Named Constants
+///////////////////////////////////////////////////////////////////////////////
+
+const ENABLED: bool = !cfg!(feature = "minimal");
+
+const LINE_LEN: usize = 16;
+// "00012/00040", leaving the rest of the line for the percentage
+const COUNTER_WIDTH: usize = 2 * numeric::PADDED_WIDTH + 1;
+
+// Progress is redrawn no more often than this, as on the operator display
+const DRAW_INTERVAL_IN_MS: u32 = 250;
+
+///////////////////////////////////////////////////////////////////////////////
+//  Data Structures
+///////////////////////////////////////////////////////////////////////////////
+
+// A display showing only what the machine is doing, drawn through a frame so each update only writes
+// what's changed
+pub struct StatusPanel<D: CharacterDisplay> {
+    display: D,
+    frame: Frame,
+    last_drawn: Option<Instant>,
+}
+
+static PANEL: Mutex<RefCell<Option<StatusPanel<Lcd>>>> = Mutex::new(RefCell::new(None));
+
+///////////////////////////////////////////////////////////////////////////////
+//  Object Implementations
+///////////////////////////////////////////////////////////////////////////////
+
+impl<D: CharacterDisplay> StatusPanel<D> {
+    pub fn new(display: D) -> Self {
+        Self {
+            display,
+            frame: Frame::new(),
+            last_drawn: None,
+        }
+    }
+
+    // e.g. "PANEL-A" over "00012/00040  30%"
+    pub fn show_progress<T: timer::Instance, U: twim::Instance>(
+        &mut self,
+        label: &str,
+        progress: Progress,
+        timer: &mut Timer<T>,
+        i2c: &mut Twim<U>,
+    ) {
+        let title = if label.is_empty() { "CUTTING" } else { label };
+        Label::new(title).render(&mut self.frame, Area::line(0, 0, LINE_LEN));
+
+        Counter::new(progress.piece)
+            .of(progress.num_cuts)
+            .render(&mut self.frame, Area::line(1, 0, COUNTER_WIDTH));
+        let mut buf = [0; numeric::MAX_U32_DIGITS];
+        let mut percent = [0; numeric::MAX_U32_DIGITS + 1];
+        let digits = numeric::trimmed(progress.percent(), &mut buf);
+        percent[..digits.len()].copy_from_slice(digits.as_bytes());
+        percent[digits.len()] = b'%';
+        // Only ASCII is ever written, so this cannot fail
+        let percent = core::str::from_utf8(&percent[..digits.len() + 1]).unwrap();
+        Label::new(percent).aligned(Align::Right).render(
+            &mut self.frame,
+            Area::line(1, COUNTER_WIDTH, LINE_LEN - COUNTER_WIDTH),
+        );
+
+        // The last piece is always shown, however soon after the one before
+        let last = self.last_drawn;
+        let is_due = last.is_none_or(|last| clock::now().ms_since(last) >= DRAW_INTERVAL_IN_MS);
+        if is_due || progress.remaining() == 0 {
+            self.draw(timer, i2c);
+        }
+    }
+
+    // A message in place of progress, e.g. how the job ended
+    pub fn show_message<T: timer::Instance, U: twim::Instance>(
+        &mut self,
+        top: &str,
+        bottom: &str,
+        timer: &mut Timer<T>,
+        i2c: &mut Twim<U>,
+    ) {
+        Label::new(top).render(&mut self.frame, Area::line(0, 0, LINE_LEN));
+        Label::new(bottom).render(&mut self.frame, Area::line(1, 0, LINE_LEN));
+        self.draw(timer, i2c);
+    }
+
+    fn draw<T: timer::Instance, U: twim::Instance>(
+        &mut self,
+        timer: &mut Timer<T>,
+        i2c: &mut Twim<U>,
+    ) {
+        while let Some(run) = self.frame.take_run() {
+            self.display
+                .write_at(run.row, run.col, self.frame.text(&run), timer, i2c);
+        }
+        self.last_drawn = Some(clock::now());
+    }
+}
+
+///////////////////////////////////////////////////////////////////////////////
+//  Static Functions
+///////////////////////////////////////////////////////////////////////////////
+
+// Bring up the panel if one answers on the bus
+pub fn init<T: timer::Instance, U: twim::Instance>(timer: &mut Timer<T>, i2c: &mut Twim<U>) {
+    if !ENABLED {
+        return;
+    }
+
+    let mut display = Lcd::STATUS;
+    if !display.is_present(i2c) {
+        defmt::println!("No status panel fitted");
+        return;
+    }
+
+    display.init(timer, i2c);
+    let mut panel = StatusPanel::new(display);
+    panel.show_message("READY", "", timer, i2c);
+    cortex_interrupt::free(|cs| PANEL.borrow(cs).replace(Some(panel)));
+}
+
+pub fn show_progress<T: timer::Instance, U: twim::Instance>(
+    label: &str,
+    progress: Progress,
+    timer: &mut Timer<T>,
+    i2c: &mut Twim<U>,
+) {
+    with_panel(|panel| panel.show_progress(label, progress, timer, i2c));
+}
+
+pub fn show_message<T: timer::Instance, U: twim::Instance>(
+    top: &str,
+    bottom: &str,
+    timer: &mut Timer<T>,
+    i2c: &mut Twim<U>,
+) {
+    with_panel(|panel| panel.show_message(top, bottom, timer, i2c));
+}
+
+fn with_panel(f: impl FnOnce(&mut StatusPanel<Lcd>)) {
+    if !ENABLED {
+        return;
+    }
+
+    cortex_interrupt::free(|cs| {
+        if let Some(panel) = PANEL.borrow(cs).borrow_mut().as_mut() {
+            f(panel);
+        }
+    });
+}
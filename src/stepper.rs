@@ -0,0 +1,180 @@
+/* * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * *\
+Copyright (C) 2023 CJ McAllister
+    This program is free software; you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation; either version 3 of the License, or
+    (at your option) any later version.
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+    You should have received a copy of the GNU General Public License
+    along with this program; if not, write to the Free Software Foundation,
+    Inc., 51 Franklin Street, Fifth Floor, Boston, MA 02110-1301  USA
+
+\* * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * */
+
+use crate::platform::hal::{
+    gpio::{Input, Output, Pin, PullUp, PushPull},
+    prelude::*,
+};
+
+use crate::axis::Axis;
+
+///////////////////////////////////////////////////////////////////////////////
+//  Data Structures
+///////////////////////////////////////////////////////////////////////////////
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum State {
+    Idle,
+    Moving,
+    Homing,
+}
+
+// Step/direction stepper driver (A4988, DRV8825, etc.), advanced one step per poll
+pub struct Stepper {
+    step_pin: Pin<Output<PushPull>>,
+    dir_pin: Pin<Output<PushPull>>,
+    // Active-low driver enable, if wired; disabling drops holding current
+    enable_pin: Option<Pin<Output<PushPull>>>,
+    // Active-low limit switch at the reference position, if fitted
+    home_switch: Option<Pin<Input<PullUp>>>,
+    position: i32,
+    target: i32,
+    state: State,
+    // Steps of play in the drive, taken up without counting towards position whenever it reverses
+    backlash_steps: u32,
+    slack_steps: u32,
+    last_forward: Option<bool>,
+}
+
+///////////////////////////////////////////////////////////////////////////////
+//  Object Implementations
+///////////////////////////////////////////////////////////////////////////////
+
+impl Stepper {
+    // Comes up with the driver off; the caller enables it once there's wire to hold
+    pub fn new(
+        step_pin: Pin<Output<PushPull>>,
+        dir_pin: Pin<Output<PushPull>>,
+        enable_pin: Option<Pin<Output<PushPull>>>,
+        home_switch: Option<Pin<Input<PullUp>>>,
+    ) -> Self {
+        let mut stepper = Self {
+            step_pin,
+            dir_pin,
+            enable_pin,
+            home_switch,
+            position: 0,
+            target: 0,
+            state: State::Idle,
+            backlash_steps: 0,
+            slack_steps: 0,
+            last_forward: None,
+        };
+        stepper.set_enabled(false);
+
+        stepper
+    }
+
+    pub fn set_enabled(&mut self, enabled: bool) {
+        if let Some(enable_pin) = self.enable_pin.as_mut() {
+            if enabled {
+                enable_pin.set_low().unwrap();
+            } else {
+                enable_pin.set_high().unwrap();
+            }
+        }
+    }
+
+    pub fn set_backlash(&mut self, steps: u32) {
+        self.backlash_steps = steps;
+    }
+
+    fn at_home(&self) -> bool {
+        self.home_switch
+            .as_ref()
+            .is_none_or(|switch| switch.is_low().unwrap())
+    }
+
+    fn step(&mut self, forward: bool) {
+        self.pulse(forward);
+        if forward {
+            self.position += 1;
+        } else {
+            self.position -= 1;
+        }
+    }
+
+    // Issue a step without counting it, i.e. one which only takes up play in the drive
+    fn pulse(&mut self, forward: bool) {
+        if forward {
+            self.dir_pin.set_high().unwrap();
+        } else {
+            self.dir_pin.set_low().unwrap();
+        }
+        self.last_forward = Some(forward);
+
+        // Drivers step on the rising edge and only need a ~2us pulse, which the pin writes provide
+        self.step_pin.set_high().unwrap();
+        self.step_pin.set_low().unwrap();
+    }
+}
+
+impl Axis for Stepper {
+    fn move_to(&mut self, position: i32) {
+        self.target = position;
+        self.state = State::Moving;
+    }
+
+    fn home(&mut self) {
+        self.state = State::Homing;
+    }
+
+    fn position(&self) -> i32 {
+        self.position
+    }
+
+    fn is_moving(&self) -> bool {
+        self.state != State::Idle
+    }
+
+    fn poll(&mut self) {
+        match self.state {
+            State::Idle => {}
+            State::Moving => {
+                if self.position == self.target {
+                    self.state = State::Idle;
+                    return;
+                }
+
+                let forward = self.target > self.position;
+                if self.last_forward.is_some_and(|last| last != forward) {
+                    self.slack_steps = self.backlash_steps;
+                }
+
+                if self.slack_steps > 0 {
+                    self.slack_steps -= 1;
+                    self.pulse(forward);
+                } else {
+                    self.step(forward);
+                }
+            }
+            State::Homing => {
+                // Without a switch, wherever the axis is now becomes the reference
+                if self.at_home() {
+                    self.position = 0;
+                    self.target = 0;
+                    self.state = State::Idle;
+                } else {
+                    self.step(false);
+                }
+            }
+        }
+    }
+
+    fn take_up_slack(&mut self) {
+        self.slack_steps = self.backlash_steps;
+    }
+}
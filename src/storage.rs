@@ -0,0 +1,115 @@
+/* * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * *\
+Copyright (C) 2023 CJ McAllister
+    This program is free software; you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation; either version 3 of the License, or
+    (at your option) any later version.
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+    You should have received a copy of the GNU General Public License
+    along with this program; if not, write to the Free Software Foundation,
+    Inc., 51 Franklin Street, Fifth Floor, Boston, MA 02110-1301  USA
+
+\* * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * */
+
+use crate::platform::{hal::nvmc::Nvmc, pac::NVMC};
+use embedded_storage::nor_flash::NorFlash;
+
+///////////////////////////////////////////////////////////////////////////////
+//  Named Constants
+///////////////////////////////////////////////////////////////////////////////
+
+pub const PAGE_SIZE: usize = 4096;
+
+// Persistent data lives in the last pages of the nRF52833's 512KB flash, well above the firmware image.
+// probe-rs only erases the sectors it flashes, so these survive re-flashing.
+const STORAGE_BASE_ADDR: usize = 0x0007_8000;
+const STORAGE_NUM_PAGES: usize = 8;
+const STORAGE_SIZE_IN_WORDS: usize = STORAGE_NUM_PAGES * PAGE_SIZE / 4;
+
+///////////////////////////////////////////////////////////////////////////////
+//  Data Structures
+///////////////////////////////////////////////////////////////////////////////
+
+// Each region occupies exactly one flash page
+#[derive(Copy, Clone, Debug, PartialEq, Eq, defmt::Format)]
+pub enum Region {
+    // The finished jobs' ring of pages, added below the rest so they kept their addresses
+    HistoryA = 0,
+    HistoryB = 1,
+    // The event journal's ring of pages, likewise
+    JournalA = 2,
+    JournalB = 3,
+    Profiles = 4,
+    Settings = 5,
+    // Keypad session to replay; the page is left alone by builds without input_replay
+    #[cfg(feature = "input_replay")]
+    Recording = 6,
+    // Per-piece times learned from finished jobs, for estimating how long the next ones take
+    CycleTimes = 7,
+}
+
+///////////////////////////////////////////////////////////////////////////////
+//  Object Implementations
+///////////////////////////////////////////////////////////////////////////////
+
+impl Region {
+    fn offset(self) -> usize {
+        self as usize * PAGE_SIZE
+    }
+}
+
+///////////////////////////////////////////////////////////////////////////////
+//  Static Functions
+///////////////////////////////////////////////////////////////////////////////
+
+pub fn init(instance: NVMC) -> Nvmc<NVMC> {
+    // SAFETY: This region is reserved for persistent storage and is only ever accessed through the
+    // returned Nvmc, which is held in a single shared handle.
+    let storage = unsafe {
+        core::slice::from_raw_parts_mut(STORAGE_BASE_ADDR as *mut u32, STORAGE_SIZE_IN_WORDS)
+    };
+
+    Nvmc::new(instance, storage)
+}
+
+pub fn read(region: Region, buffer: &mut [u8]) {
+    read_at(region, 0, buffer);
+}
+
+// Read from partway into the region
+pub fn read_at(region: Region, offset: usize, buffer: &mut [u8]) {
+    assert!(offset + buffer.len() <= PAGE_SIZE);
+
+    // Flash is memory-mapped, so reads can bypass the NVMC entirely
+    let region_addr = (STORAGE_BASE_ADDR + region.offset() + offset) as *const u8;
+    for (i, byte) in buffer.iter_mut().enumerate() {
+        *byte = unsafe { core::ptr::read_volatile(region_addr.add(i)) };
+    }
+}
+
+// Erase the region's page, leaving it as if nothing had ever been saved there
+pub fn erase(region: Region, nvmc: &mut Nvmc<NVMC>) {
+    let offset = region.offset() as u32;
+    nvmc.erase(offset, offset + PAGE_SIZE as u32).unwrap();
+}
+
+// Erase the region's page and write the buffer to it; buffer length must be word-aligned
+pub fn write(region: Region, buffer: &[u8], nvmc: &mut Nvmc<NVMC>) {
+    assert!(buffer.len() <= PAGE_SIZE);
+
+    let offset = region.offset() as u32;
+    nvmc.erase(offset, offset + PAGE_SIZE as u32).unwrap();
+    nvmc.write(offset, buffer).unwrap();
+}
+
+// Write partway into the region without erasing it, so only into flash that's still erased; offset and
+// length must be word-aligned
+pub fn write_at(region: Region, offset: usize, buffer: &[u8], nvmc: &mut Nvmc<NVMC>) {
+    assert!(offset + buffer.len() <= PAGE_SIZE);
+
+    nvmc.write((region.offset() + offset) as u32, buffer)
+        .unwrap();
+}
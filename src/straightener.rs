@@ -0,0 +1,58 @@
+/* * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * *\
+Copyright (C) 2023 CJ McAllister
+    This program is free software; you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation; either version 3 of the License, or
+    (at your option) any later version.
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+    You should have received a copy of the GNU General Public License
+    along with this program; if not, write to the Free Software Foundation,
+    Inc., 51 Franklin Street, Fifth Floor, Boston, MA 02110-1301  USA
+
+\* * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * */
+
+use crate::platform::hal::{
+    gpio::{OpenDrain, Output, Pin},
+    prelude::*,
+};
+
+///////////////////////////////////////////////////////////////////////////////
+//  Data Structures
+///////////////////////////////////////////////////////////////////////////////
+
+// Wire straightener/de-reeler motor, switched by an external driver with its input pulled up.
+pub struct Straightener {
+    pin: Pin<Output<OpenDrain>>,
+    running: bool,
+}
+
+///////////////////////////////////////////////////////////////////////////////
+//  Object Implementations
+///////////////////////////////////////////////////////////////////////////////
+
+impl Straightener {
+    // The pin must start released, leaving the motor off
+    #[cfg_attr(not(feature = "straightener"), allow(dead_code))]
+    pub fn new(pin: Pin<Output<OpenDrain>>) -> Self {
+        Self {
+            pin,
+            running: false,
+        }
+    }
+
+    pub fn set_running(&mut self, running: bool) {
+        if running {
+            self.pin.set_low().unwrap();
+        } else {
+            self.pin.set_high().unwrap();
+        }
+        self.running = running;
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.running
+    }
+}
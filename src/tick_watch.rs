@@ -0,0 +1,73 @@
+/* * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * *\
+Copyright (C) 2023 CJ McAllister
+    This program is free software; you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation; either version 3 of the License, or
+    (at your option) any later version.
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+    You should have received a copy of the GNU General Public License
+    along with this program; if not, write to the Free Software Foundation,
+    Inc., 51 Franklin Street, Fifth Floor, Boston, MA 02110-1301  USA
+
+\* * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * */
+
+// Times the clock's second ticks as they're handled, against the RTC counting on regardless, to
+// catch the time base being held off by long critical sections or I2C stalls. Debounce, timeouts and
+// ETAs all lean on it, so a late or missing tick degrades them quietly otherwise.
+
+use core::{cell::Cell, fmt};
+
+use cortex_m::interrupt::{self as cortex_interrupt, Mutex};
+
+use cutter_core::tick_watch::TickWatch;
+
+use crate::clock::{self, Instant};
+
+///////////////////////////////////////////////////////////////////////////////
+//  Named Constants
+///////////////////////////////////////////////////////////////////////////////
+
+const PERIOD_MS: u32 = 1000;
+
+///////////////////////////////////////////////////////////////////////////////
+//  Data Structures
+///////////////////////////////////////////////////////////////////////////////
+
+static WATCH: Mutex<Cell<TickWatch>> = Mutex::new(Cell::new(TickWatch::new(PERIOD_MS)));
+static LAST_TICK: Mutex<Cell<Option<Instant>>> = Mutex::new(Cell::new(None));
+
+///////////////////////////////////////////////////////////////////////////////
+//  Static Functions
+///////////////////////////////////////////////////////////////////////////////
+
+// The second alarm has been set. The clock must already be running.
+pub fn start() {
+    let now = clock::now();
+    cortex_interrupt::free(|cs| LAST_TICK.borrow(cs).set(Some(now)));
+}
+
+// The second alarm's fired. Safe from the clock's handler, as nothing here logs.
+pub fn tick() {
+    let now = clock::now();
+    cortex_interrupt::free(|cs| {
+        if let Some(last) = LAST_TICK.borrow(cs).replace(Some(now)) {
+            let watch = WATCH.borrow(cs);
+            let mut updated = watch.get();
+            updated.record(now.ms_since(last));
+            watch.set(updated);
+        }
+    });
+}
+
+// Lateness and missed ticks as `key value` lines, for the console's dump. Ticks held off right now
+// are counted as missed too, as the handler can't count them until it runs.
+pub fn write_report(out: &mut dyn fmt::Write) -> fmt::Result {
+    let now = clock::now();
+    let (watch, last) =
+        cortex_interrupt::free(|cs| (WATCH.borrow(cs).get(), LAST_TICK.borrow(cs).get()));
+    let since_last_ms = last.map_or(0, |last| now.ms_since(last));
+    watch.write_report("second_tick", since_last_ms, out)
+}
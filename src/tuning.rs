@@ -0,0 +1,81 @@
+/* * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * *\
+Copyright (C) 2023 CJ McAllister
+    This program is free software; you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation; either version 3 of the License, or
+    (at your option) any later version.
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+    You should have received a copy of the GNU General Public License
+    along with this program; if not, write to the Free Software Foundation,
+    Inc., 51 Franklin Street, Fifth Floor, Boston, MA 02110-1301  USA
+
+\* * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * */
+
+// Tunable parameters in force, starting from the build defaults and any overrides saved in settings.
+// They're changed over the serial console and read wherever they're used, so a change takes effect
+// from the next cycle or key press.
+
+use core::cell::Cell;
+
+use cortex_m::interrupt::{self as cortex_interrupt, Mutex};
+pub use cutter_core::tuning::{Param, TuneError, Tuning, NUM_PARAMS};
+
+use crate::{
+    i2c::keypad,
+    servo::{CUT_POSITION_CLOSED, CUT_POSITION_OPEN},
+    settings::Settings,
+    CYCLE_TIMING, FEED_PROFILE,
+};
+
+///////////////////////////////////////////////////////////////////////////////
+//  Named Constants
+///////////////////////////////////////////////////////////////////////////////
+
+// Build defaults, in Param order
+const DEFAULTS: [u32; NUM_PARAMS] = [
+    CUT_POSITION_CLOSED as u32,
+    CUT_POSITION_OPEN as u32,
+    CYCLE_TIMING.cut_dwell_ms,
+    CYCLE_TIMING.blade_clearance_ms,
+    FEED_PROFILE.max_speed_mils_per_s,
+    FEED_PROFILE.accel_mils_per_s2,
+    keypad::DEBOUNCE_DELAY_IN_US,
+];
+
+///////////////////////////////////////////////////////////////////////////////
+//  Data Structures
+///////////////////////////////////////////////////////////////////////////////
+
+static TUNING: Mutex<Cell<Tuning>> = Mutex::new(Cell::new(Tuning::new(DEFAULTS)));
+
+///////////////////////////////////////////////////////////////////////////////
+//  Static Functions
+///////////////////////////////////////////////////////////////////////////////
+
+// Apply the overrides saved in settings
+pub fn init(settings: &Settings) {
+    let tuning = Tuning::with_overrides(DEFAULTS, &settings.tuning);
+    cortex_interrupt::free(|cs| TUNING.borrow(cs).set(tuning));
+}
+
+pub fn get(param: Param) -> u32 {
+    cortex_interrupt::free(|cs| TUNING.borrow(cs).get().get(param))
+}
+
+// Until restart, unless saved
+pub fn set(param: Param, value: u32) -> Result<(), TuneError> {
+    cortex_interrupt::free(|cs| {
+        let mut tuning = TUNING.borrow(cs).get();
+        tuning.set(param, value)?;
+        TUNING.borrow(cs).set(tuning);
+        Ok(())
+    })
+}
+
+// Everything in force, for listing
+pub fn current() -> Tuning {
+    cortex_interrupt::free(|cs| TUNING.borrow(cs).get())
+}
@@ -0,0 +1,354 @@
+/* * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * *\
+Copyright (C) 2023 CJ McAllister
+    This program is free software; you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation; either version 3 of the License, or
+    (at your option) any later version.
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU General Public License for more details.
+    You should have received a copy of the GNU General Public License
+    along with this program; if not, write to the Free Software Foundation,
+    Inc., 51 Franklin Street, Fifth Floor, Boston, MA 02110-1301  USA
+
+\* * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * */
+
+// Footprint report for the firmware: builds it for release, then adds up the flash and RAM taken by
+// each module's symbols, so it's plain which subsystems are worth leaving out as features accumulate.
+// Run from anywhere in the repo, passing any build arguments on, e.g. to compare a minimal build:
+//   cargo size-report --features minimal
+//
+// Sizes come from `nm`, arm-none-eabi-nm unless NM names another, e.g. llvm-nm. Code inlined into its
+// callers counts towards theirs, which is how most of main.rs ends up under the crate itself, and
+// anonymous constants are left to (other). Only symbols are counted, so padding and the stack aren't,
+// and the totals fall a little short of the sections'.
+
+use std::{
+    collections::{BTreeSet, HashMap},
+    env, fs,
+    path::{Path, PathBuf},
+    process::{self, Command},
+};
+
+///////////////////////////////////////////////////////////////////////////////
+//  Named Constants
+///////////////////////////////////////////////////////////////////////////////
+
+const TARGET: &str = "thumbv7em-none-eabihf";
+const BINARY: &str = "diyer-cutter";
+
+// The nRF52833's flash up to the persistent storage pages (see storage.rs), and all its RAM
+const FLASH_BUDGET: u64 = 0x0007_8000;
+const RAM_BUDGET: u64 = 128 * 1024;
+
+// Modules are reported for these crates; every other crate is reported as a whole
+const OWN_CRATES: [(&str, &str); 2] = [("diyer_cutter", "src"), ("cutter_core", "cutter-core/src")];
+
+// For symbols that don't belong to any crate, e.g. the vector table or compiler intrinsics
+const NO_CRATE: &str = "(other)";
+
+///////////////////////////////////////////////////////////////////////////////
+//  Data Structures
+///////////////////////////////////////////////////////////////////////////////
+
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+struct Footprint {
+    flash: u64,
+    ram: u64,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+struct Symbol<'a> {
+    name: &'a str,
+    footprint: Footprint,
+}
+
+///////////////////////////////////////////////////////////////////////////////
+//  Object Implementations
+///////////////////////////////////////////////////////////////////////////////
+
+impl Footprint {
+    // What a symbol of the given `nm` type takes up. Initialized data is copied out of flash into RAM,
+    // so takes both; anything not loaded onto the chip, e.g. defmt's strings, takes neither.
+    fn of(kind: char, size: u64) -> Self {
+        match kind {
+            't' | 'T' | 'r' | 'R' | 'W' => Self {
+                flash: size,
+                ram: 0,
+            },
+            'd' | 'D' => Self {
+                flash: size,
+                ram: size,
+            },
+            'b' | 'B' => Self {
+                flash: 0,
+                ram: size,
+            },
+            _ => Self::default(),
+        }
+    }
+
+    fn add(&mut self, other: Self) {
+        self.flash += other.flash;
+        self.ram += other.ram;
+    }
+}
+
+impl<'a> Symbol<'a> {
+    // A line of `nm --print-size` output, as `address size type name`. Symbols without a size, i.e.
+    // labels, are skipped.
+    fn parse(line: &'a str) -> Option<Self> {
+        let mut fields = line.splitn(4, ' ');
+        let _address = fields.next()?;
+        let size = u64::from_str_radix(fields.next()?, 16).ok()?;
+        let kind = fields.next()?.chars().next()?;
+        let name = fields.next()?.trim();
+        Some(Self {
+            name,
+            footprint: Footprint::of(kind, size),
+        })
+    }
+
+    // Where the symbol's code or data comes from: `crate::module` for the repo's own crates, the crate
+    // alone for the rest. Trait impls go to the implementing type's crate, and generics to where they
+    // were defined rather than used.
+    fn owner(&self, modules: &HashMap<&str, BTreeSet<String>>) -> String {
+        let path = self.name.trim_start_matches(['<', '&', '*']);
+        let path = path.trim_start_matches("mut ").trim_start_matches("const ");
+        let mut segments = path.split("::");
+        let krate = segments.next().unwrap_or_default();
+        let is_ident =
+            |s: &str| !s.is_empty() && s.chars().all(|c| c.is_alphanumeric() || c == '_');
+        if !path.contains("::") || !is_ident(krate) {
+            return NO_CRATE.to_string();
+        }
+
+        match (modules.get(krate), segments.next()) {
+            (Some(known), Some(module)) if known.contains(module) => format!("{krate}::{module}"),
+            _ => krate.to_string(),
+        }
+    }
+}
+
+///////////////////////////////////////////////////////////////////////////////
+//  Static Functions
+///////////////////////////////////////////////////////////////////////////////
+
+fn main() {
+    let root = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("../..")
+        .canonicalize()
+        .unwrap_or_else(|e| fail(&format!("couldn't find the repo: {e}")));
+    let build_args: Vec<String> = env::args().skip(1).collect();
+
+    // The repo's cargo config picks the target and linker
+    let cargo = env::var("CARGO").unwrap_or_else(|_| "cargo".to_string());
+    let built = Command::new(cargo)
+        .current_dir(&root)
+        .args(["build", "--release", "--bin", BINARY])
+        .args(&build_args)
+        .status()
+        .unwrap_or_else(|e| fail(&format!("couldn't run cargo: {e}")));
+    if !built.success() {
+        fail("the firmware didn't build");
+    }
+
+    let elf: PathBuf = [root.as_path(), Path::new("target"), Path::new(TARGET)]
+        .iter()
+        .collect::<PathBuf>()
+        .join("release")
+        .join(BINARY);
+    let nm = env::var("NM").unwrap_or_else(|_| "arm-none-eabi-nm".to_string());
+    let output = Command::new(&nm)
+        .args(["--print-size", "--demangle"])
+        .arg(&elf)
+        .output()
+        .unwrap_or_else(|e| fail(&format!("couldn't run {nm}: {e}")));
+    if !output.status.success() {
+        fail(&String::from_utf8_lossy(&output.stderr));
+    }
+
+    let modules = OWN_CRATES
+        .iter()
+        .map(|&(krate, dir)| (krate, module_names(&root.join(dir))))
+        .collect();
+    let symbols = String::from_utf8_lossy(&output.stdout);
+    let by_owner = tally(symbols.lines().filter_map(Symbol::parse), &modules);
+    print_report(&elf, &build_args, by_owner);
+}
+
+///////////////////////////////////////////////////////////////////////////////
+//  Helper Functions
+///////////////////////////////////////////////////////////////////////////////
+
+// A crate's modules, as named by its source files and directories
+fn module_names(dir: &Path) -> BTreeSet<String> {
+    let entries = fs::read_dir(dir)
+        .unwrap_or_else(|e| fail(&format!("couldn't list {}: {e}", dir.display())));
+    entries
+        .flatten()
+        .filter_map(|entry| {
+            let path = entry.path();
+            let is_module = path.is_dir() || path.extension().is_some_and(|ext| ext == "rs");
+            let stem = path.file_stem()?.to_str()?.to_string();
+            is_module.then_some(stem)
+        })
+        .filter(|stem| stem != "main" && stem != "lib")
+        .collect()
+}
+
+// Footprints added up by owner, largest flash first
+fn tally<'a>(
+    symbols: impl Iterator<Item = Symbol<'a>>,
+    modules: &HashMap<&str, BTreeSet<String>>,
+) -> Vec<(String, Footprint)> {
+    let mut by_owner: HashMap<String, Footprint> = HashMap::new();
+    for symbol in symbols {
+        if symbol.footprint != Footprint::default() {
+            by_owner
+                .entry(symbol.owner(modules))
+                .or_default()
+                .add(symbol.footprint);
+        }
+    }
+
+    let mut by_owner: Vec<_> = by_owner.into_iter().collect();
+    by_owner.sort_by(|(a_name, a), (b_name, b)| {
+        (b.flash, b.ram, a_name).cmp(&(a.flash, a.ram, b_name))
+    });
+    by_owner
+}
+
+fn print_report(elf: &Path, build_args: &[String], by_owner: Vec<(String, Footprint)>) {
+    println!("Footprint of {} {}", elf.display(), build_args.join(" "));
+    println!("{:>8} {:>8}  MODULE", "FLASH", "RAM");
+    let mut total = Footprint::default();
+    for (owner, footprint) in by_owner {
+        println!("{:>8} {:>8}  {}", footprint.flash, footprint.ram, owner);
+        total.add(footprint);
+    }
+    println!("{:>8} {:>8}  TOTAL", total.flash, total.ram);
+    println!(
+        "Flash {}% of {} KiB, RAM {}% of {} KiB before the stack",
+        total.flash * 100 / FLASH_BUDGET,
+        FLASH_BUDGET / 1024,
+        total.ram * 100 / RAM_BUDGET,
+        RAM_BUDGET / 1024
+    );
+}
+
+fn fail(message: &str) -> ! {
+    eprintln!("size-report: {}", message.trim_end());
+    process::exit(1);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn modules() -> HashMap<&'static str, BTreeSet<String>> {
+        let names = |names: &[&str]| names.iter().map(|name| name.to_string()).collect();
+        HashMap::from([
+            ("diyer_cutter", names(&["status_panel", "serial", "i2c"])),
+            ("cutter_core", names(&["widgets"])),
+        ])
+    }
+
+    fn owner(name: &str) -> String {
+        Symbol {
+            name,
+            footprint: Footprint::default(),
+        }
+        .owner(&modules())
+    }
+
+    #[test]
+    fn symbols_are_parsed_from_nm() {
+        let symbol =
+            Symbol::parse("0000a1c4 00000130 T diyer_cutter::status_panel::init::h01ab").unwrap();
+        assert_eq!(symbol.name, "diyer_cutter::status_panel::init::h01ab");
+        assert_eq!(
+            symbol.footprint,
+            Footprint {
+                flash: 0x130,
+                ram: 0
+            }
+        );
+
+        let data = Symbol::parse("20000010 00000008 D diyer_cutter::SERIAL_HANDLE").unwrap();
+        assert_eq!(data.footprint, Footprint { flash: 8, ram: 8 });
+        let bss = Symbol::parse("20000018 00000040 b cutter_core::widgets::BUF").unwrap();
+        assert_eq!(
+            bss.footprint,
+            Footprint {
+                flash: 0,
+                ram: 0x40
+            }
+        );
+
+        // Names with spaces in them are kept whole, and labels without a size are skipped
+        let name = Symbol::parse("00001000 00000010 t <u8 as core::fmt::Display>::fmt").unwrap();
+        assert_eq!(name.name, "<u8 as core::fmt::Display>::fmt");
+        assert_eq!(Symbol::parse("00000100 T Reset"), None);
+    }
+
+    #[test]
+    fn symbols_are_owned_by_module_or_crate() {
+        assert_eq!(
+            owner("diyer_cutter::status_panel::init::h01ab"),
+            "diyer_cutter::status_panel"
+        );
+        assert_eq!(
+            owner("diyer_cutter::i2c::lcd1602::write::h02"),
+            "diyer_cutter::i2c"
+        );
+        assert_eq!(
+            owner("<cutter_core::widgets::Label as cutter_core::widgets::Widget>::render"),
+            "cutter_core::widgets"
+        );
+        assert_eq!(
+            owner("<&mut diyer_cutter::serial::SerialPort<T> as core::fmt::Write>::write_str"),
+            "diyer_cutter::serial"
+        );
+
+        // Functions in the crate roots, others' crates, and what belongs to no crate
+        assert_eq!(owner("diyer_cutter::run_cycle::h03"), "diyer_cutter");
+        assert_eq!(owner("core::fmt::write::h04"), "core");
+        assert_eq!(owner("__cortex_m_rt_TIMER1"), NO_CRATE);
+    }
+
+    #[test]
+    fn footprints_add_up_by_owner() {
+        let lines = [
+            "0000a1c4 00000100 T diyer_cutter::status_panel::init",
+            "0000a2c4 00000080 t diyer_cutter::status_panel::draw",
+            "20000000 00000040 B diyer_cutter::status_panel::PANEL",
+            "0000b000 00000200 T core::fmt::write",
+            "00000000 00000010 N defmt_string",
+        ];
+        let by_owner = tally(
+            lines.iter().filter_map(|line| Symbol::parse(line)),
+            &modules(),
+        );
+        assert_eq!(
+            by_owner,
+            [
+                (
+                    "core".to_string(),
+                    Footprint {
+                        flash: 0x200,
+                        ram: 0
+                    }
+                ),
+                (
+                    "diyer_cutter::status_panel".to_string(),
+                    Footprint {
+                        flash: 0x180,
+                        ram: 0x40
+                    }
+                ),
+            ]
+        );
+    }
+}